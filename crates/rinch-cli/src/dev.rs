@@ -0,0 +1,409 @@
+//! `rinch dev`: rebuild and restart (or hot reload) the app on changes.
+//!
+//! The app is started with `RINCH_DEV_DIR` naming a directory the runner
+//! reports builds in, which rinch's hot reloader watches:
+//!
+//! - `errors` holds the compiler errors of the last build, empty if it
+//!   succeeded, and is shown over the app's windows.
+//! - `library` is written after the hot library was rebuilt, holding the
+//!   path of the built file, telling the app to reload it.
+//!
+//! Both are written to a temporary file first and renamed, so the app never
+//! reads half of one.
+
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Environment variable naming the directory builds are reported in; the
+/// same as `rinch::shell::hot_reload::DEV_DIR_ENV`.
+const DEV_DIR_ENV: &str = "RINCH_DEV_DIR";
+
+/// Extensions of the files whose changes trigger a build. Other files, such
+/// as stylesheets, are reloaded by the app itself.
+const BUILD_EXTENSIONS: &[&str] = &["rs", "toml"];
+
+/// How long to wait for more changes after one, so saving several files
+/// builds once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How often to check whether the app exited while waiting for changes.
+const EXIT_POLL: Duration = Duration::from_millis(250);
+
+/// Options of `rinch dev`.
+#[derive(Debug, Clone, Default)]
+pub struct DevOptions {
+    /// Package of the app to run.
+    pub package: Option<String>,
+    /// Binary to run.
+    pub bin: Option<String>,
+    /// Example to run.
+    pub example: Option<String>,
+    /// Library package reloaded into the running app instead of restarting it.
+    pub lib: Option<String>,
+    /// Features to build with.
+    pub features: Option<String>,
+    /// Whether to build in release mode.
+    pub release: bool,
+    /// Paths to watch; empty for the default ones.
+    pub watch: Vec<PathBuf>,
+    /// Arguments passed to the app.
+    pub app_args: Vec<String>,
+}
+
+/// Run the development loop until the app exits on its own.
+pub fn run(options: DevOptions) -> Result<(), String> {
+    let dev_dir = std::env::temp_dir().join(format!("rinch-dev-{}", std::process::id()));
+    std::fs::create_dir_all(&dev_dir).map_err(|e| format!("failed to create {}: {}", dev_dir.display(), e))?;
+
+    let (tx, rx) = channel();
+    let mut watcher = RecommendedWatcher::new(
+        move |res| {
+            let _ = tx.send(res);
+        },
+        Config::default(),
+    )
+    .map_err(|e| format!("failed to watch files: {}", e))?;
+    for path in watch_paths(&options) {
+        watcher
+            .watch(&path, RecursiveMode::Recursive)
+            .map_err(|e| format!("failed to watch {}: {}", path.display(), e))?;
+        eprintln!("rinch dev: watching {}", path.display());
+    }
+
+    let mut app: Option<Child> = None;
+    let result = dev_loop(&options, &dev_dir, &rx, &mut app);
+    if let Some(mut child) = app {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let _ = std::fs::remove_dir_all(&dev_dir);
+    result
+}
+
+fn dev_loop(
+    options: &DevOptions,
+    dev_dir: &Path,
+    changes: &Receiver<notify::Result<Event>>,
+    app: &mut Option<Child>,
+) -> Result<(), String> {
+    loop {
+        // With a hot library and the app running, only the library is
+        // rebuilt; changes to the binary need a restart of `rinch dev`
+        let hot_patch = options.lib.is_some() && app.is_some();
+        let build = if hot_patch { build_library(options) } else { build_app(options) }?;
+        report(dev_dir, "errors", &build.errors)?;
+
+        if build.success {
+            if hot_patch {
+                let library = build
+                    .library
+                    .ok_or("cargo didn't build a dynamic library; is the crate-type of --lib \"dylib\"?")?;
+                report(dev_dir, "library", &library.to_string_lossy())?;
+            } else if let Some(executable) = build.executable {
+                if let Some(mut child) = app.take() {
+                    eprintln!("rinch dev: restarting the app");
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                *app = Some(start_app(options, &executable, dev_dir)?);
+            } else {
+                return Err("cargo didn't build an executable; pick one with --bin or --example".to_string());
+            }
+        } else if app.is_none() {
+            eprintln!("rinch dev: the build failed, waiting for changes");
+        }
+
+        if !wait_for_change(changes, app)? {
+            return Ok(());
+        }
+    }
+}
+
+/// The paths to watch: the ones given, or the usual source directories.
+fn watch_paths(options: &DevOptions) -> Vec<PathBuf> {
+    if !options.watch.is_empty() {
+        return options.watch.clone();
+    }
+    let paths: Vec<PathBuf> = ["src", "crates", "examples", "assets"]
+        .iter()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .collect();
+    if paths.is_empty() { vec![PathBuf::from(".")] } else { paths }
+}
+
+/// The outcome of a cargo build.
+struct Build {
+    success: bool,
+    /// The compiler errors, without colors; empty if there were none.
+    errors: String,
+    /// The executable built, if any.
+    executable: Option<PathBuf>,
+    /// The dynamic library built last, if any.
+    library: Option<PathBuf>,
+}
+
+/// Build the app's executable.
+fn build_app(options: &DevOptions) -> Result<Build, String> {
+    let mut command = cargo_build(options);
+    if let Some(package) = &options.package {
+        command.args(["-p", package]);
+    }
+    if let Some(bin) = &options.bin {
+        command.args(["--bin", bin]);
+    }
+    if let Some(example) = &options.example {
+        command.args(["--example", example]);
+    }
+    run_build(command)
+}
+
+/// Build the hot library.
+fn build_library(options: &DevOptions) -> Result<Build, String> {
+    let mut command = cargo_build(options);
+    command.arg("--lib");
+    if let Some(lib) = &options.lib {
+        command.args(["-p", lib]);
+    }
+    run_build(command)
+}
+
+/// A `cargo build` command with the options shared by every build.
+fn cargo_build(options: &DevOptions) -> Command {
+    let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.args(["build", "--message-format=json-render-diagnostics"]);
+    if std::io::stderr().is_terminal() {
+        command.arg("--color=always");
+    }
+    if let Some(features) = &options.features {
+        command.args(["--features", features]);
+    }
+    if options.release {
+        command.arg("--release");
+    }
+    command
+}
+
+/// Run a build, passing cargo's output on to the terminal while collecting
+/// the errors and the executable built.
+fn run_build(mut command: Command) -> Result<Build, String> {
+    eprintln!("rinch dev: building");
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run cargo: {}", e))?;
+
+    // Cargo prints the rendered diagnostics to stderr and JSON messages,
+    // including the artifacts built, to stdout
+    let stdout = child.stdout.take().expect("stdout is piped");
+    // Dependencies are reported before the crates depending on them, so the
+    // last library is the one asked for
+    let artifacts = std::thread::spawn(move || {
+        let mut executable = None;
+        let mut library = None;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if !line.contains(r#""reason":"compiler-artifact""#) {
+                continue;
+            }
+            if let Some(path) = json_string_field(&line, "executable") {
+                executable = Some(PathBuf::from(path));
+            }
+            if let Some(path) = json_string_array_field(&line, "filenames")
+                .into_iter()
+                .find(|file| file.ends_with(std::env::consts::DLL_SUFFIX))
+            {
+                library = Some(PathBuf::from(path));
+            }
+        }
+        (executable, library)
+    });
+
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let mut errors = String::new();
+    let mut in_error = false;
+    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+        eprintln!("{}", line);
+        let plain = strip_ansi(&line);
+        let trimmed = plain.trim_start();
+        if plain.starts_with("error") {
+            in_error = true;
+        } else if plain.starts_with("warning")
+            || ["Compiling ", "Checking ", "Building ", "Finished "].iter().any(|s| trimmed.starts_with(s))
+        {
+            in_error = false;
+        }
+        if in_error {
+            errors.push_str(&plain);
+            errors.push('\n');
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("failed to wait for cargo: {}", e))?;
+    let (executable, library) = artifacts.join().unwrap_or((None, None));
+    Ok(Build {
+        success: status.success(),
+        errors: errors.trim_end().to_string(),
+        executable,
+        library,
+    })
+}
+
+/// Start the built app, telling it where builds are reported.
+fn start_app(options: &DevOptions, executable: &Path, dev_dir: &Path) -> Result<Child, String> {
+    eprintln!("rinch dev: running {}", executable.display());
+    Command::new(executable)
+        .args(&options.app_args)
+        .env(DEV_DIR_ENV, dev_dir)
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", executable.display(), e))
+}
+
+/// Write a report file for the app, replacing the old one in one step.
+fn report(dev_dir: &Path, name: &str, contents: &str) -> Result<(), String> {
+    let temp = dev_dir.join(format!(".{}.tmp", name));
+    let path = dev_dir.join(name);
+    std::fs::File::create(&temp)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .and_then(|()| std::fs::rename(&temp, &path))
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Wait until a watched file that needs a build changes, returning `false`
+/// if the app exited on its own first.
+///
+/// An app that exited with an error is restarted with the next change
+/// instead, so a panic doesn't end the session.
+fn wait_for_change(changes: &Receiver<notify::Result<Event>>, app: &mut Option<Child>) -> Result<bool, String> {
+    loop {
+        if let Some(child) = app
+            && let Ok(Some(status)) = child.try_wait()
+        {
+            if status.success() {
+                return Ok(false);
+            }
+            eprintln!("rinch dev: the app exited with {}, waiting for changes", status);
+            *app = None;
+        }
+
+        match changes.recv_timeout(EXIT_POLL) {
+            Ok(Ok(event)) if needs_build(&event) => {
+                // Let the other files being saved settle
+                while changes.recv_timeout(DEBOUNCE).is_ok() {}
+                return Ok(true);
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(e)) => eprintln!("rinch dev: watch error: {}", e),
+            Err(RecvTimeoutError::Disconnected) => return Err("the file watcher stopped".to_string()),
+        }
+    }
+}
+
+/// Whether an event changed a file that needs a build.
+fn needs_build(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+        && event.paths.iter().any(|path| {
+            !path.components().any(|c| c.as_os_str() == "target")
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| BUILD_EXTENSIONS.contains(&ext))
+        })
+}
+
+/// The value of a string field in a line of cargo's JSON output.
+fn json_string_field(line: &str, field: &str) -> Option<String> {
+    let start = line.find(&format!(r#""{}":""#, field))? + field.len() + 4;
+    read_json_string(&line[start..]).map(|(value, _)| value)
+}
+
+/// The strings in an array field in a line of cargo's JSON output.
+fn json_string_array_field(line: &str, field: &str) -> Vec<String> {
+    let Some(start) = line.find(&format!(r#""{}":["#, field)) else {
+        return Vec::new();
+    };
+    let mut rest = &line[start + field.len() + 4..];
+    let mut values = Vec::new();
+    while let Some(quoted) = rest.strip_prefix('"') {
+        let Some((value, len)) = read_json_string(quoted) else {
+            break;
+        };
+        values.push(value);
+        rest = quoted[len..].strip_prefix(',').unwrap_or(&quoted[len..]);
+    }
+    values
+}
+
+/// Read a JSON string from just after its opening quote, returning it and
+/// the length read, including the closing quote.
+fn read_json_string(s: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, chars.offset())),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Remove the color codes cargo adds for the terminal.
+fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the end of the escape sequence, a letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_json_string_fields() {
+        let line = r#"{"reason":"compiler-artifact","executable":"C:\\app\\target\\debug\\my \"app\".exe","fresh":true}"#;
+        assert_eq!(
+            json_string_field(line, "executable").as_deref(),
+            Some(r#"C:\app\target\debug\my "app".exe"#)
+        );
+        assert_eq!(json_string_field(r#"{"executable":null}"#, "executable"), None);
+    }
+
+    #[test]
+    fn reads_json_string_arrays() {
+        let line = r#"{"reason":"compiler-artifact","filenames":["C:\\t\\ui.dll","C:\\t\\ui.dll.lib"],"executable":null}"#;
+        assert_eq!(json_string_array_field(line, "filenames"), [r"C:\t\ui.dll", r"C:\t\ui.dll.lib"]);
+        assert!(json_string_array_field(r#"{"filenames":[]}"#, "filenames").is_empty());
+        assert!(json_string_array_field(line, "missing").is_empty());
+    }
+
+    #[test]
+    fn strips_colors() {
+        assert_eq!(
+            strip_ansi("\x1b[0m\x1b[1m\x1b[38;5;9merror[E0425]\x1b[0m: cannot find value"),
+            "error[E0425]: cannot find value"
+        );
+    }
+}
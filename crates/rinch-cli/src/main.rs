@@ -0,0 +1,84 @@
+//! The `rinch` command-line tool.
+//!
+//! `rinch dev` is the development loop around `run_with_hot_reload` and
+//! `run_with_hot_library`: it watches the project, rebuilds it with cargo
+//! when a Rust file or manifest changes, and restarts the app (or reloads
+//! its hot library) once the build succeeds. Compiler errors are shown in
+//! the terminal and over the app's windows, and the app keeps running the
+//! code it was last built from until they're fixed.
+
+mod dev;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: rinch dev [OPTIONS] [-- APP_ARGS...]
+
+Build and run the app, rebuilding it when its source changes.
+
+Options:
+  -p, --package <NAME>   Package of the app to run
+      --bin <NAME>       Binary to run
+      --example <NAME>   Example to run
+      --lib <NAME>       Library package reloaded into the running app
+                         (see run_with_hot_library), instead of restarting it
+      --features <LIST>  Features to build with
+      --release          Build in release mode
+  -w, --watch <PATH>     Path to watch, instead of src, crates, examples and
+                         assets (can be repeated)
+  -h, --help             Print this help
+
+The app must be started with rinch::run_with_hot_reload or
+rinch::run_with_hot_library to show compiler errors and keep its state
+across restarts.";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("dev") => parse_dev(args).and_then(|options| match options {
+            Some(options) => dev::run(options),
+            None => {
+                println!("{}", USAGE);
+                Ok(())
+            }
+        }),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse the arguments of `rinch dev`, or `None` if help was asked for.
+fn parse_dev(mut args: impl Iterator<Item = String>) -> Result<Option<dev::DevOptions>, String> {
+    let mut options = dev::DevOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("`{}` needs a value", name));
+        match arg.as_str() {
+            "-p" | "--package" => options.package = Some(value(&arg)?),
+            "--bin" => options.bin = Some(value(&arg)?),
+            "--example" => options.example = Some(value(&arg)?),
+            "--lib" => options.lib = Some(value(&arg)?),
+            "--features" => options.features = Some(value(&arg)?),
+            "--release" => options.release = true,
+            "-w" | "--watch" => options.watch.push(PathBuf::from(value(&arg)?)),
+            "-h" | "--help" => return Ok(None),
+            "--" => {
+                options.app_args = args.collect();
+                break;
+            }
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    Ok(Some(options))
+}
@@ -1,6 +1,9 @@
 //! Element types and component traits.
 
+use crate::event::Modifiers;
 use std::any::Any;
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 /// A node in the UI tree.
@@ -15,8 +18,14 @@ pub enum Element {
     MenuItem(MenuItemProps),
     /// A separator line in menus.
     MenuSeparator,
+    /// A submenu listing recently opened files.
+    RecentFilesMenu(RecentFilesMenuProps),
+    /// A system tray icon with an optional context menu.
+    Tray(TrayProps, Children),
     /// Raw HTML content to be rendered by blitz.
-    Html(String),
+    ///
+    /// `rsx!` borrows the HTML of static parts of a tree from the binary.
+    Html(Cow<'static, str>),
     /// A user-defined component.
     Component(Box<dyn AnyComponent>),
     /// A fragment containing multiple children.
@@ -25,6 +34,25 @@ pub enum Element {
 
 pub type Children = Vec<Element>;
 
+impl Element {
+    /// Replace every `Component` in the tree with the element it renders.
+    ///
+    /// Components may use hooks, so call this inside the render cycle
+    /// (between `begin_render` and `end_render`).
+    pub fn expand_components(self) -> Element {
+        let expand = |children: Children| children.into_iter().map(Element::expand_components).collect();
+        match self {
+            Element::Component(component) => component.render_any().expand_components(),
+            Element::Window(props, children) => Element::Window(props, expand(children)),
+            Element::AppMenu(props, children) => Element::AppMenu(props, expand(children)),
+            Element::Menu(props, children) => Element::Menu(props, expand(children)),
+            Element::Tray(props, children) => Element::Tray(props, expand(children)),
+            Element::Fragment(children) => Element::Fragment(expand(children)),
+            element => element,
+        }
+    }
+}
+
 /// Properties for the Window component.
 #[derive(Debug, Clone)]
 pub struct WindowProps {
@@ -36,8 +64,24 @@ pub struct WindowProps {
     pub borderless: bool,
     pub resizable: bool,
     pub transparent: bool,
+    /// Whether the window casts a drop shadow on macOS, and on Windows when
+    /// it is borderless.
+    pub shadow: bool,
     pub always_on_top: bool,
     pub visible: bool,
+    /// Light or dark appearance, overriding the OS theme unless `System`.
+    pub color_scheme: WindowColorScheme,
+    /// Direction of the window's text and layout.
+    pub dir: TextDirection,
+    /// Identifies the window across runs of the app, so session restore can
+    /// put it back where it was.
+    pub key: Option<String>,
+    /// An HTML file (relative to the assets directory) or a URL with a
+    /// registered scheme, shown instead of the window's children.
+    pub src: Option<String>,
+    /// The HiDPI scale to draw at, such as `1.25`, overriding the
+    /// monitor's.
+    pub scale: Option<f32>,
 }
 
 impl Default for WindowProps {
@@ -51,8 +95,83 @@ impl Default for WindowProps {
             borderless: false,
             resizable: true,
             transparent: false,
+            shadow: true,
             always_on_top: false,
             visible: true,
+            color_scheme: WindowColorScheme::System,
+            dir: TextDirection::Auto,
+            key: None,
+            src: None,
+            scale: None,
+        }
+    }
+}
+
+/// The color scheme of a window, used for `prefers-color-scheme` media
+/// queries and the native title bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowColorScheme {
+    /// Follow the OS light/dark setting.
+    #[default]
+    System,
+    /// Always light.
+    Light,
+    /// Always dark.
+    Dark,
+}
+
+impl From<&str> for WindowColorScheme {
+    /// Parse `"light"` or `"dark"` (case-insensitive); anything else is `System`.
+    fn from(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("light") {
+            WindowColorScheme::Light
+        } else if value.eq_ignore_ascii_case("dark") {
+            WindowColorScheme::Dark
+        } else {
+            WindowColorScheme::System
+        }
+    }
+}
+
+/// The direction text and layout flow in: left to right, or right to left
+/// for languages such as Arabic and Hebrew.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextDirection {
+    /// Follow the active locale, if the app set up translations
+    /// (`rinch::i18n`); left to right otherwise.
+    #[default]
+    Auto,
+    /// Left to right.
+    Ltr,
+    /// Right to left.
+    Rtl,
+}
+
+impl TextDirection {
+    /// Whether this is `Rtl`.
+    pub fn is_rtl(self) -> bool {
+        self == TextDirection::Rtl
+    }
+
+    /// The value of the HTML `dir` attribute: `"ltr"`, `"rtl"` or `"auto"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextDirection::Auto => "auto",
+            TextDirection::Ltr => "ltr",
+            TextDirection::Rtl => "rtl",
+        }
+    }
+}
+
+impl From<&str> for TextDirection {
+    /// Parse `"ltr"` or `"rtl"` (case-insensitive); anything else is `Auto`.
+    fn from(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("ltr") {
+            TextDirection::Ltr
+        } else if value.eq_ignore_ascii_case("rtl") {
+            TextDirection::Rtl
+        } else {
+            TextDirection::Auto
         }
     }
 }
@@ -76,21 +195,40 @@ pub struct MenuProps {
     pub label: String,
 }
 
+/// Details about how a menu item was activated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MenuActivation {
+    /// `true` if the window matched the item's keyboard shortcut, `false` if clicked.
+    ///
+    /// Accelerators handled by the native menu itself (the macOS menu bar) arrive
+    /// as menu events and are reported as clicks.
+    pub via_shortcut: bool,
+    /// Modifier keys held when the item was activated.
+    pub modifiers: Modifiers,
+    /// The `id` prop of the activated item, if it has one.
+    pub item_id: Option<String>,
+}
+
 /// Callback type for menu items.
 ///
 /// Uses `Rc` for `Clone` support, allowing callbacks to be stored and invoked.
 #[derive(Clone)]
-pub struct MenuItemCallback(pub Rc<dyn Fn()>);
+pub struct MenuItemCallback(pub Rc<dyn Fn(&MenuActivation)>);
 
 impl MenuItemCallback {
-    /// Create a new menu item callback from a function.
+    /// Create a new menu item callback from a function that ignores activation details.
     pub fn new<F: Fn() + 'static>(f: F) -> Self {
+        Self(Rc::new(move |_| f()))
+    }
+
+    /// Create a new menu item callback that receives activation details.
+    pub fn with_activation<F: Fn(&MenuActivation) + 'static>(f: F) -> Self {
         Self(Rc::new(f))
     }
 
     /// Invoke the callback.
-    pub fn invoke(&self) {
-        (self.0)()
+    pub fn invoke(&self, activation: &MenuActivation) {
+        (self.0)(activation)
     }
 }
 
@@ -103,26 +241,187 @@ impl std::fmt::Debug for MenuItemCallback {
 /// Properties for a MenuItem.
 #[derive(Debug, Clone)]
 pub struct MenuItemProps {
+    /// Identifier for looking up the native item at runtime.
+    pub id: Option<String>,
     pub label: String,
     pub shortcut: Option<String>,
     pub enabled: bool,
     pub checked: Option<bool>,
     /// Callback to invoke when the menu item is activated.
     pub onclick: Option<MenuItemCallback>,
+    /// Editing command the item performs on the focused text input.
+    pub role: Option<MenuRole>,
 }
 
 impl Default for MenuItemProps {
     fn default() -> Self {
         Self {
+            id: None,
             label: String::new(),
             shortcut: None,
             enabled: true,
             checked: None,
             onclick: None,
+            role: None,
+        }
+    }
+}
+
+/// A standard editing command a menu item performs on the focused text
+/// input of the focused window, such as Edit > Copy, or on the focused
+/// window's undo manager, such as Edit > Undo.
+///
+/// The item is enabled only while the command can be performed, and gets
+/// the command's usual shortcut unless it declares one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuRole {
+    /// Undo the window's newest change.
+    Undo,
+    /// Redo the window's most recently undone change.
+    Redo,
+    /// Move the selected text to the clipboard.
+    Cut,
+    /// Copy the selected text to the clipboard.
+    Copy,
+    /// Replace the selection with the text on the clipboard.
+    Paste,
+    /// Select all the text of the input.
+    SelectAll,
+}
+
+impl MenuRole {
+    /// Parse `"undo"`, `"redo"`, `"cut"`, `"copy"`, `"paste"` or
+    /// `"select_all"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "undo" => Some(MenuRole::Undo),
+            "redo" => Some(MenuRole::Redo),
+            "cut" => Some(MenuRole::Cut),
+            "copy" => Some(MenuRole::Copy),
+            "paste" => Some(MenuRole::Paste),
+            "select_all" => Some(MenuRole::SelectAll),
+            _ => None,
+        }
+    }
+
+    /// The usual shortcut of the command.
+    pub fn shortcut(self) -> &'static str {
+        match self {
+            MenuRole::Undo => "Cmd+Z",
+            MenuRole::Redo => "Shift+Cmd+Z",
+            MenuRole::Cut => "Cmd+X",
+            MenuRole::Copy => "Cmd+C",
+            MenuRole::Paste => "Cmd+V",
+            MenuRole::SelectAll => "Cmd+A",
+        }
+    }
+}
+
+/// Callback type for recent file selection.
+///
+/// Receives the path of the file the user picked from the recent files menu.
+#[derive(Clone)]
+pub struct RecentFileCallback(pub Rc<dyn Fn(PathBuf)>);
+
+impl RecentFileCallback {
+    /// Create a new recent file callback from a function.
+    pub fn new<F: Fn(PathBuf) + 'static>(f: F) -> Self {
+        Self(Rc::new(f))
+    }
+
+    /// Invoke the callback with the selected path.
+    pub fn invoke(&self, path: PathBuf) {
+        (self.0)(path)
+    }
+}
+
+impl std::fmt::Debug for RecentFileCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RecentFileCallback(...)")
+    }
+}
+
+/// Properties for a RecentFilesMenu.
+#[derive(Debug, Clone)]
+pub struct RecentFilesMenuProps {
+    /// The submenu label.
+    pub label: String,
+    /// Label of the item that clears the list.
+    pub clear_label: String,
+    /// Callback to invoke when a recent file is selected.
+    pub onselect: Option<RecentFileCallback>,
+}
+
+impl Default for RecentFilesMenuProps {
+    fn default() -> Self {
+        Self {
+            label: String::from("Open Recent"),
+            clear_label: String::from("Clear Menu"),
+            onselect: None,
         }
     }
 }
 
+/// Mouse button used to click a tray icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// Details about a click on a tray icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrayClick {
+    /// The mouse button that was clicked.
+    pub button: TrayButton,
+    /// `true` for a double-click.
+    pub double_click: bool,
+}
+
+/// Callback type for tray icon clicks.
+#[derive(Clone)]
+pub struct TrayClickCallback(pub Rc<dyn Fn(&TrayClick)>);
+
+impl TrayClickCallback {
+    /// Create a new tray click callback from a function that ignores click details.
+    pub fn new<F: Fn() + 'static>(f: F) -> Self {
+        Self(Rc::new(move |_| f()))
+    }
+
+    /// Create a new tray click callback that receives click details.
+    pub fn with_click<F: Fn(&TrayClick) + 'static>(f: F) -> Self {
+        Self(Rc::new(f))
+    }
+
+    /// Invoke the callback.
+    pub fn invoke(&self, click: &TrayClick) {
+        (self.0)(click)
+    }
+}
+
+impl std::fmt::Debug for TrayClickCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TrayClickCallback(...)")
+    }
+}
+
+/// Properties for the Tray component.
+///
+/// The tray's children (`MenuItem`, `MenuSeparator` and nested `Menu`
+/// elements) make up its context menu.
+#[derive(Debug, Clone, Default)]
+pub struct TrayProps {
+    /// Tooltip text shown on hover.
+    pub tooltip: Option<String>,
+    /// Path to the icon image (PNG, ICO, etc.).
+    pub icon: Option<PathBuf>,
+    /// Callback to invoke when the icon is clicked.
+    pub onclick: Option<TrayClickCallback>,
+    /// Callback to invoke when the icon is double-clicked.
+    pub ondoubleclick: Option<TrayClickCallback>,
+}
+
 /// Trait for user-defined components.
 pub trait Component: 'static {
     type Props;
@@ -1 +1,18 @@
 //! Event types and handling.
+
+/// Keyboard modifier state at the time of an event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// The Command key on macOS, the Windows key elsewhere.
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Whether Ctrl or Cmd is held (the platform's primary shortcut modifier).
+    pub fn ctrl_or_cmd(&self) -> bool {
+        self.ctrl || self.meta
+    }
+}
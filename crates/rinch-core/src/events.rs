@@ -5,8 +5,11 @@
 
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use crate::reactive::{Effect, Memo, Signal};
+
 /// Escape HTML special characters in a string.
 ///
 /// This is used at runtime for dynamic content in RSX.
@@ -17,6 +20,134 @@ pub fn html_escape_string(s: &str) -> String {
         .replace('"', "&quot;")
 }
 
+/// Give the element whose HTML starts at `start` the key of its keyed `rsx!`
+/// loop item, as a `data-rkey` attribute after its tag name.
+///
+/// Windows find the elements of an item again by key when their document is
+/// rebuilt, so focus and scrolling follow an item the loop moved.
+#[doc(hidden)]
+pub fn key_element(html: &mut String, start: usize, key: &str) {
+    let Some(tag) = html.get(start..).and_then(|html| html.strip_prefix('<')) else {
+        return;
+    };
+    let name_len = tag.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(tag.len());
+    if name_len == 0 {
+        return;
+    }
+    html.insert_str(start + 1 + name_len, &format!(" data-rkey=\"{}\"", html_escape_string(key)));
+}
+
+/// A value for the `checked` attribute of a checkbox or radio button.
+///
+/// A `bool` sets the initial state. A `Signal<bool>` also binds the input
+/// to the signal: toggling the input sets the signal, and setting the signal
+/// checks or unchecks the input.
+pub trait CheckedValue {
+    /// Whether the input should be rendered as checked.
+    fn is_checked(&self) -> bool;
+
+    /// The signal to update when the input is toggled, if any.
+    fn binding(&self) -> Option<Signal<bool>> {
+        None
+    }
+}
+
+impl CheckedValue for bool {
+    fn is_checked(&self) -> bool {
+        *self
+    }
+}
+
+impl CheckedValue for Signal<bool> {
+    fn is_checked(&self) -> bool {
+        self.get()
+    }
+
+    fn binding(&self) -> Option<Signal<bool>> {
+        Some(self.clone())
+    }
+}
+
+impl<T: CheckedValue + ?Sized> CheckedValue for &T {
+    fn is_checked(&self) -> bool {
+        (**self).is_checked()
+    }
+
+    fn binding(&self) -> Option<Signal<bool>> {
+        (**self).binding()
+    }
+}
+
+/// A signal or memo that `rsx!` binds to the attribute or text it's read
+/// in, when written as `{value.get()}`.
+///
+/// Bound attributes and text are updated in place when the value changes,
+/// instead of rendering the app again. Other values read with `.get()` are
+/// rendered as usual.
+pub trait Bindable {
+    /// A function reading the current value as a string, subscribing the
+    /// current observer to it.
+    fn value_fn(&self) -> Box<dyn Fn() -> String>;
+}
+
+impl<T: Clone + ToString + 'static> Bindable for Signal<T> {
+    fn value_fn(&self) -> Box<dyn Fn() -> String> {
+        let signal = self.clone();
+        Box::new(move || signal.get().to_string())
+    }
+}
+
+impl<T: Clone + ToString + 'static> Bindable for Memo<T> {
+    fn value_fn(&self) -> Box<dyn Fn() -> String> {
+        let memo = self.clone();
+        Box::new(move || memo.get().to_string())
+    }
+}
+
+impl<T: Bindable + ?Sized> Bindable for &T {
+    fn value_fn(&self) -> Box<dyn Fn() -> String> {
+        (**self).value_fn()
+    }
+}
+
+/// Lets `rsx!` bind [`Bindable`] values and render others as usual:
+/// `(&BindProbe(&value)).bind_text()` picks [`BindIfBindable`] if the value
+/// is bindable, and [`RenderIfNotBindable`] otherwise.
+#[doc(hidden)]
+pub struct BindProbe<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait BindIfBindable {
+    fn bind_text(&self) -> Option<(BindingId, String)>;
+    fn bind_attribute(&self, name: &'static str) -> Option<(BindingId, String)>;
+}
+
+impl<T: Bindable + ?Sized> BindIfBindable for BindProbe<'_, T> {
+    fn bind_text(&self) -> Option<(BindingId, String)> {
+        Some(bind_text(self.0.value_fn()))
+    }
+
+    fn bind_attribute(&self, name: &'static str) -> Option<(BindingId, String)> {
+        Some(bind_attribute(name, self.0.value_fn()))
+    }
+}
+
+#[doc(hidden)]
+pub trait RenderIfNotBindable {
+    fn bind_text(&self) -> Option<(BindingId, String)>;
+    fn bind_attribute(&self, name: &'static str) -> Option<(BindingId, String)>;
+}
+
+impl<T: ?Sized> RenderIfNotBindable for &BindProbe<'_, T> {
+    fn bind_text(&self) -> Option<(BindingId, String)> {
+        None
+    }
+
+    fn bind_attribute(&self, _name: &'static str) -> Option<(BindingId, String)> {
+        None
+    }
+}
+
 /// Unique identifier for an event handler.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct EventHandlerId(pub usize);
@@ -30,6 +161,21 @@ impl std::fmt::Display for EventHandlerId {
 /// Type alias for event handler callbacks.
 pub type EventCallback = Box<dyn Fn() + 'static>;
 
+/// Type alias for `oninput` callbacks, which receive the element's new value.
+pub type InputCallback = Box<dyn Fn(String) + 'static>;
+
+/// Type alias for `onchange` callbacks, which receive whether a checkbox or
+/// radio button is now checked.
+pub type ChangeCallback = Box<dyn Fn(bool) + 'static>;
+
+/// Type alias for `onselect` callbacks, which receive the start and end of a
+/// text input's selection as byte offsets into its value.
+pub type SelectCallback = Box<dyn Fn(usize, usize) + 'static>;
+
+/// Type alias for `onreorder` callbacks, which receive the index an item was
+/// dragged from and the index it should move to.
+pub type ReorderCallback = Box<dyn Fn(usize, usize) + 'static>;
+
 /// Global counter for generating unique event handler IDs.
 static NEXT_HANDLER_ID: AtomicUsize = AtomicUsize::new(0);
 
@@ -43,20 +189,75 @@ pub fn reset_handler_ids() {
     NEXT_HANDLER_ID.store(0, Ordering::SeqCst);
 }
 
+/// Unique identifier for an attribute or text bound to a signal.
+///
+/// Unlike handler IDs these aren't reused by later renders, so a change
+/// reported for an old binding can't reach an element of a newer render.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BindingId(pub usize);
+
+impl std::fmt::Display for BindingId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A new value for an attribute bound with [`bind_attribute`] or text bound
+/// with [`bind_text`].
+///
+/// A bound attribute's element has a `data-rbind-<name>` attribute holding
+/// the binding's ID; bound text is written between `<!--rbind:<id>-->` and
+/// `<!--/rbind-->`, and its parent lists the ID in `data-rbind-text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingPatch {
+    pub id: BindingId,
+    /// The attribute's name, or `None` for text.
+    pub attribute: Option<&'static str>,
+    /// The new value, not escaped.
+    pub value: String,
+}
+
+/// Global counter for generating binding IDs.
+static NEXT_BINDING_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The effect keeping an attribute or text up to date; disposed with the
+/// render that created it.
+struct Binding(Effect);
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        self.0.dispose();
+    }
+}
+
 // Thread-local event handler registry.
 thread_local! {
     static EVENT_REGISTRY: RefCell<EventRegistry> = RefCell::new(EventRegistry::new());
+    /// Binding changes the runtime hasn't applied yet.
+    static BINDING_PATCHES: RefCell<Vec<BindingPatch>> = const { RefCell::new(Vec::new()) };
+    /// Called when the first change is added to `BINDING_PATCHES`.
+    static PATCH_NOTIFIER: RefCell<Option<Rc<dyn Fn()>>> = const { RefCell::new(None) };
 }
 
 /// Registry that maps event handler IDs to callbacks.
 pub struct EventRegistry {
     handlers: HashMap<EventHandlerId, EventCallback>,
+    input_handlers: HashMap<EventHandlerId, InputCallback>,
+    change_handlers: HashMap<EventHandlerId, ChangeCallback>,
+    select_handlers: HashMap<EventHandlerId, SelectCallback>,
+    reorder_handlers: HashMap<EventHandlerId, ReorderCallback>,
+    bindings: Vec<Binding>,
 }
 
 impl EventRegistry {
     fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            input_handlers: HashMap::new(),
+            change_handlers: HashMap::new(),
+            select_handlers: HashMap::new(),
+            reorder_handlers: HashMap::new(),
+            bindings: Vec::new(),
         }
     }
 }
@@ -96,19 +297,291 @@ pub fn dispatch_event(id: EventHandlerId) -> bool {
     })
 }
 
+/// Register an `oninput` handler and return its ID.
+///
+/// The handler will be called with the new value when the text of an
+/// element with the corresponding `data-oninput` attribute is edited.
+///
+/// # Example
+///
+/// ```ignore
+/// let text = Signal::new(String::new());
+/// let id = register_input_handler(move |value| text.set(value));
+/// // The element should have: data-oninput="{id}"
+/// ```
+pub fn register_input_handler(callback: impl Fn(String) + 'static) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().input_handlers.insert(id, Box::new(callback));
+    });
+    id
+}
+
+/// Dispatch an input event with the element's new value.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_input(id: EventHandlerId, value: String) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().input_handlers.get(&id) {
+            handler(value);
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `onchange` handler and return its ID.
+///
+/// The handler will be called with the new state when a checkbox or radio
+/// button with the corresponding `data-onchange` attribute is toggled.
+///
+/// # Example
+///
+/// ```ignore
+/// let agreed = Signal::new(false);
+/// let id = register_change_handler(move |checked| agreed.set(checked));
+/// // The element should have: data-onchange="{id}"
+/// ```
+pub fn register_change_handler(callback: impl Fn(bool) + 'static) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().change_handlers.insert(id, Box::new(callback));
+    });
+    id
+}
+
+/// Dispatch a change event with the element's new checked state.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_change(id: EventHandlerId, checked: bool) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().change_handlers.get(&id) {
+            handler(checked);
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `onselect` handler and return its ID.
+///
+/// The handler will be called with the start and end of the selection
+/// (byte offsets into the value, equal when nothing is selected) when the
+/// caret or selection of a text input with the corresponding `data-onselect`
+/// attribute moves.
+///
+/// # Example
+///
+/// ```ignore
+/// let id = register_select_handler(|start, end| println!("{start}..{end}"));
+/// // The element should have: data-onselect="{id}"
+/// ```
+pub fn register_select_handler(callback: impl Fn(usize, usize) + 'static) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().select_handlers.insert(id, Box::new(callback));
+    });
+    id
+}
+
+/// Dispatch a select event with the input's new selection.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_select(id: EventHandlerId, start: usize, end: usize) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().select_handlers.get(&id) {
+            handler(start, end);
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `onreorder` handler and return its ID.
+///
+/// The handler will be called when a child of an element with the
+/// corresponding `data-onreorder` attribute is dragged to a new position,
+/// with the child's index and the index it should have after the move, as in
+/// `let item = items.remove(from); items.insert(to, item);`.
+///
+/// # Example
+///
+/// ```ignore
+/// let tasks = Signal::new(vec!["a", "b", "c"]);
+/// let id = register_reorder_handler(move |from, to| {
+///     tasks.update(|tasks| {
+///         let task = tasks.remove(from);
+///         tasks.insert(to, task);
+///     })
+/// });
+/// // The element should have: data-onreorder="{id}"
+/// ```
+pub fn register_reorder_handler(callback: impl Fn(usize, usize) + 'static) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().reorder_handlers.insert(id, Box::new(callback));
+    });
+    id
+}
+
+/// Dispatch a reorder event with the moved item's old and new index.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_reorder(id: EventHandlerId, from: usize, to: usize) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().reorder_handlers.get(&id) {
+            handler(from, to);
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Bind an attribute to a value computed from signals, returning the
+/// binding's ID and the attribute's current value.
+///
+/// `rsx!` uses this for attributes written as `name: {signal.get()}`, if
+/// `signal` is [`Bindable`]. When
+/// the signals `value` reads change, a [`BindingPatch`] is queued for the
+/// runtime, which sets the attribute of the element marked with
+/// `data-rbind-<name>="<id>"` without rendering the app again. The binding
+/// lasts until the handlers of its render are cleared.
+pub fn bind_attribute<T: ToString>(
+    name: &'static str,
+    value: impl Fn() -> T + 'static,
+) -> (BindingId, String) {
+    bind(Some(name), value)
+}
+
+/// Bind text to a value computed from signals, returning the binding's ID
+/// and the current text.
+///
+/// `rsx!` uses this for text written as `{signal.get()}`, if `signal` is
+/// [`Bindable`], writing it between `<!--rbind:<id>-->` and `<!--/rbind-->`
+/// comments. When the signals `value` reads change, the runtime updates the
+/// text node between them in place.
+pub fn bind_text<T: ToString>(value: impl Fn() -> T + 'static) -> (BindingId, String) {
+    bind(None, value)
+}
+
+/// Create a binding of an attribute, or of text if `attribute` is `None`.
+fn bind<T: ToString>(
+    attribute: Option<&'static str>,
+    value: impl Fn() -> T + 'static,
+) -> (BindingId, String) {
+    let id = BindingId(NEXT_BINDING_ID.fetch_add(1, Ordering::SeqCst));
+    let current = Rc::new(RefCell::new(None::<String>));
+
+    let last = Rc::clone(&current);
+    let effect = Effect::new(move || {
+        let value = value().to_string();
+        let previous = last.borrow_mut().replace(value.clone());
+        if previous.is_some_and(|previous| previous != value) {
+            queue_patch(BindingPatch { id, attribute, value });
+        }
+    });
+    EVENT_REGISTRY.with(|registry| registry.borrow_mut().bindings.push(Binding(effect)));
+
+    let value = current.borrow().clone().unwrap_or_default();
+    (id, value)
+}
+
+/// Add a change to the queue, telling the runtime if it was empty.
+fn queue_patch(patch: BindingPatch) {
+    let first = BINDING_PATCHES.with(|patches| {
+        let mut patches = patches.borrow_mut();
+        patches.push(patch);
+        patches.len() == 1
+    });
+    if first && let Some(notify) = PATCH_NOTIFIER.with(|n| n.borrow().clone()) {
+        notify();
+    }
+}
+
+/// Set the function called when bound attributes or text change, so the
+/// runtime can apply the changes with [`take_binding_patches`].
+pub fn set_patch_notifier(notify: impl Fn() + 'static) {
+    PATCH_NOTIFIER.with(|n| *n.borrow_mut() = Some(Rc::new(notify)));
+}
+
+/// Take the binding changes queued since the last call, oldest first.
+pub fn take_binding_patches() -> Vec<BindingPatch> {
+    BINDING_PATCHES.with(|patches| std::mem::take(&mut *patches.borrow_mut()))
+}
+
+/// Combine a `checked` binding with an `onchange` handler, for `rsx!`.
+#[doc(hidden)]
+pub fn bind_checked(
+    binding: Option<Signal<bool>>,
+    handler: impl Fn(bool) + 'static,
+) -> impl Fn(bool) + 'static {
+    move |checked| {
+        if let Some(binding) = &binding {
+            binding.set(checked);
+        }
+        handler(checked);
+    }
+}
+
 /// Clear all registered event handlers.
 ///
 /// This should be called before re-rendering to avoid stale handlers.
 pub fn clear_handlers() {
     EVENT_REGISTRY.with(|registry| {
-        registry.borrow_mut().handlers.clear();
+        let mut registry = registry.borrow_mut();
+        registry.handlers.clear();
+        registry.input_handlers.clear();
+        registry.change_handlers.clear();
+        registry.select_handlers.clear();
+        registry.reorder_handlers.clear();
+        registry.bindings.clear();
     });
+    BINDING_PATCHES.with(|patches| patches.borrow_mut().clear());
     reset_handler_ids();
 }
 
+/// Event handlers taken out of the registry by [`take_handlers`].
+pub struct SavedHandlers {
+    registry: EventRegistry,
+    next_id: usize,
+}
+
+/// Take all registered event handlers out of the registry, leaving it empty
+/// as [`clear_handlers`] does.
+///
+/// Put them back with [`restore_handlers`] if the render that should have
+/// replaced them fails, so the elements on screen keep working.
+pub fn take_handlers() -> SavedHandlers {
+    let registry = EVENT_REGISTRY.with(|registry| registry.replace(EventRegistry::new()));
+    let next_id = NEXT_HANDLER_ID.swap(0, Ordering::SeqCst);
+    // The next render shows the current values of bound attributes
+    BINDING_PATCHES.with(|patches| patches.borrow_mut().clear());
+    SavedHandlers { registry, next_id }
+}
+
+/// Put back the handlers taken by [`take_handlers`], replacing the ones
+/// registered since.
+pub fn restore_handlers(saved: SavedHandlers) {
+    let replaced = EVENT_REGISTRY.with(|registry| registry.replace(saved.registry));
+    NEXT_HANDLER_ID.store(saved.next_id, Ordering::SeqCst);
+    // Dropped outside the borrow, in case a handler's captures register others
+    drop(replaced);
+}
+
 /// Get the number of registered handlers (for debugging).
 pub fn handler_count() -> usize {
-    EVENT_REGISTRY.with(|registry| registry.borrow().handlers.len())
+    EVENT_REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        registry.handlers.len()
+            + registry.input_handlers.len()
+            + registry.change_handlers.len()
+            + registry.select_handlers.len()
+            + registry.reorder_handlers.len()
+    })
 }
 
 #[cfg(test)]
@@ -133,6 +606,20 @@ mod tests {
         assert!(called.get());
     }
 
+    #[test]
+    fn test_key_element() {
+        let mut html = String::from("<ul><li class=\"todo\">Milk</li><br/>");
+        key_element(&mut html, 4, "a\"b");
+        assert_eq!(html, "<ul><li data-rkey=\"a&quot;b\" class=\"todo\">Milk</li><br/>");
+        let start = html.find("<br").unwrap();
+        key_element(&mut html, start, "7");
+        assert!(html.ends_with("<br data-rkey=\"7\"/>"));
+        // Text isn't an element
+        let mut text = String::from("Milk");
+        key_element(&mut text, 0, "1");
+        assert_eq!(text, "Milk");
+    }
+
     #[test]
     fn test_dispatch_unknown_id() {
         clear_handlers();
@@ -152,4 +639,183 @@ mod tests {
         assert_eq!(handler_count(), 0);
         assert!(!dispatch_event(id));
     }
+
+    #[test]
+    fn test_restore_taken_handlers() {
+        clear_handlers();
+
+        let called = Rc::new(Cell::new(false));
+        let called_clone = called.clone();
+        let id = register_handler(Box::new(move || called_clone.set(true)));
+
+        // A render that fails after registering its own handlers
+        let saved = take_handlers();
+        assert_eq!(handler_count(), 0);
+        register_handler(Box::new(|| {}));
+        register_handler(Box::new(|| {}));
+        restore_handlers(saved);
+
+        assert_eq!(handler_count(), 1);
+        assert!(dispatch_event(id));
+        assert!(called.get());
+    }
+
+    #[test]
+    fn test_register_and_dispatch_input() {
+        clear_handlers();
+
+        let value = Rc::new(RefCell::new(String::new()));
+        let value_clone = value.clone();
+
+        let id = register_input_handler(move |new_value| {
+            *value_clone.borrow_mut() = new_value;
+        });
+
+        assert!(dispatch_input(id, "hello".into()));
+        assert_eq!(*value.borrow(), "hello");
+
+        // Input handlers aren't click handlers
+        assert!(!dispatch_event(id));
+
+        clear_handlers();
+        assert!(!dispatch_input(id, "again".into()));
+    }
+
+    #[test]
+    fn test_register_and_dispatch_change() {
+        clear_handlers();
+
+        let checked = Rc::new(Cell::new(false));
+        let checked_clone = checked.clone();
+
+        let id = register_change_handler(move |value| checked_clone.set(value));
+
+        assert!(dispatch_change(id, true));
+        assert!(checked.get());
+        assert!(dispatch_change(id, false));
+        assert!(!checked.get());
+        assert!(!dispatch_input(id, "on".into()));
+    }
+
+    #[test]
+    fn test_register_and_dispatch_select() {
+        clear_handlers();
+
+        let selection = Rc::new(Cell::new((0, 0)));
+        let selection_clone = selection.clone();
+
+        let id = register_select_handler(move |start, end| selection_clone.set((start, end)));
+
+        assert!(dispatch_select(id, 2, 5));
+        assert_eq!(selection.get(), (2, 5));
+        assert!(!dispatch_change(id, true));
+
+        clear_handlers();
+        assert!(!dispatch_select(id, 0, 0));
+    }
+
+    #[test]
+    fn test_register_and_dispatch_reorder() {
+        clear_handlers();
+
+        let items = Rc::new(RefCell::new(vec!["a", "b", "c"]));
+        let items_clone = items.clone();
+
+        let id = register_reorder_handler(move |from, to| {
+            let mut items = items_clone.borrow_mut();
+            let item = items.remove(from);
+            items.insert(to, item);
+        });
+
+        assert!(dispatch_reorder(id, 0, 2));
+        assert_eq!(*items.borrow(), vec!["b", "c", "a"]);
+        assert!(!dispatch_select(id, 0, 2));
+        assert_eq!(handler_count(), 1);
+    }
+
+    #[test]
+    fn test_checked_value() {
+        assert!(true.is_checked());
+        assert!(false.binding().is_none());
+
+        let signal = Signal::new(true);
+        assert!(signal.is_checked());
+        let binding = signal.binding().unwrap();
+        binding.set(false);
+        assert!(!signal.is_checked());
+    }
+
+    #[test]
+    fn test_bound_attribute_queues_changes() {
+        clear_handlers();
+
+        let class = Signal::new(String::from("off"));
+        let bound = class.clone();
+        let (id, value) = bind_attribute("class", move || bound.get());
+        assert_eq!(value, "off");
+
+        class.set(String::from("on"));
+        // Setting the same value again changes nothing
+        class.set(String::from("on"));
+        assert_eq!(
+            take_binding_patches(),
+            vec![BindingPatch { id, attribute: Some("class"), value: String::from("on") }]
+        );
+
+        // Bindings end with the render that created them
+        clear_handlers();
+        class.set(String::from("off"));
+        assert!(take_binding_patches().is_empty());
+    }
+
+    #[test]
+    fn test_bound_text_queues_changes() {
+        clear_handlers();
+
+        let count = Signal::new(1);
+        let bound = count.clone();
+        let (id, value) = bind_text(move || bound.get());
+        assert_eq!(value, "1");
+
+        count.update(|n| *n += 1);
+        assert_eq!(
+            take_binding_patches(),
+            vec![BindingPatch { id, attribute: None, value: String::from("2") }]
+        );
+    }
+    #[test]
+    fn test_only_signals_and_memos_are_bound() {
+        clear_handlers();
+
+        let count = Signal::new(1);
+        let doubled = {
+            let count = count.clone();
+            Memo::new(move || count.get() * 2)
+        };
+        let plain = Rc::new(Cell::new(5));
+
+        assert_eq!(BindProbe(&count).bind_text().map(|(_, value)| value), Some(String::from("1")));
+        assert_eq!(
+            BindProbe(&&doubled).bind_attribute("title").map(|(_, value)| value),
+            Some(String::from("2"))
+        );
+        assert!((&BindProbe(&plain)).bind_text().is_none());
+        assert!((&BindProbe(&plain)).bind_attribute("title").is_none());
+    }
+
+    #[test]
+    fn test_bindings_unsubscribe_with_their_render() {
+        let count = Signal::new(0);
+        for _ in 0..10 {
+            clear_handlers();
+            let bound = count.clone();
+            bind_text(move || bound.get());
+            let bound = count.clone();
+            bind_attribute("title", move || bound.get());
+        }
+        assert_eq!(count.subscriber_count(), 2);
+
+        clear_handlers();
+        assert_eq!(count.subscriber_count(), 0);
+    }
 }
@@ -0,0 +1,416 @@
+//! Form state and validation.
+//!
+//! [`use_form`] keeps the values of a set of named text fields, checks them
+//! with validators, and tracks which fields have been edited, so dialogs
+//! don't have to wire up a signal and an error message per field. The `Field`
+//! element in `rsx!` renders a labelled input bound to a form field, with its
+//! error message underneath.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//!
+//! fn settings() -> Element {
+//!     let form = use_form(|| {
+//!         FormFields::new()
+//!             .field("name", "")
+//!             .validate("name", validators::required("Enter a name"))
+//!             .field("port", "8080")
+//!             .validate("port", |value: &str| match value.parse::<u16>() {
+//!                 Ok(_) => Ok(()),
+//!                 Err(_) => Err("Enter a port number".into()),
+//!             })
+//!     });
+//!     let submit = form.clone();
+//!
+//!     rsx! {
+//!         Field { form: form.clone(), name: "name", label: "Name" }
+//!         Field { form: form.clone(), name: "port", label: "Port" }
+//!         button {
+//!             onclick: move || if submit.submit() { save(submit.values()) },
+//!             "Save"
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::hooks::use_signal;
+use crate::reactive::Signal;
+
+/// Checks a field's value, returning an error message if it's invalid.
+pub type Validator = Rc<dyn Fn(&str) -> Result<(), String>>;
+
+/// The fields of a form, with their initial values and validators.
+#[derive(Clone, Default)]
+pub struct FormFields {
+    fields: Vec<FieldState>,
+}
+
+impl FormFields {
+    /// Create a form with no fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field with an initial value.
+    ///
+    /// Adding a field that already exists replaces its initial value.
+    pub fn field(mut self, name: impl Into<String>, initial: impl Into<String>) -> Self {
+        let name = name.into();
+        let initial = initial.into();
+        match self.fields.iter_mut().find(|field| field.name == name) {
+            Some(field) => {
+                field.value = initial.clone();
+                field.initial = initial;
+            }
+            None => self.fields.push(FieldState {
+                name,
+                value: initial.clone(),
+                initial,
+                touched: false,
+                validators: Vec::new(),
+            }),
+        }
+        self
+    }
+
+    /// Add a validator to a field.
+    ///
+    /// Validators run in the order they were added, and the first error is
+    /// the one shown. Validating a field that hasn't been added has no effect.
+    pub fn validate(
+        mut self,
+        name: &str,
+        validator: impl Fn(&str) -> Result<(), String> + 'static,
+    ) -> Self {
+        if let Some(field) = self.fields.iter_mut().find(|field| field.name == name) {
+            field.validators.push(Rc::new(validator));
+        }
+        self
+    }
+}
+
+#[derive(Clone)]
+struct FieldState {
+    name: String,
+    initial: String,
+    value: String,
+    touched: bool,
+    validators: Vec<Validator>,
+}
+
+impl FieldState {
+    fn error(&self) -> Option<String> {
+        self.validators
+            .iter()
+            .find_map(|validator| validator(&self.value).err())
+    }
+}
+
+/// A handle to a form's state, created with [`use_form`].
+///
+/// Cloning the handle is cheap and every clone refers to the same form.
+/// Changing a value re-renders the window like setting a signal.
+#[derive(Clone)]
+pub struct Form {
+    state: Signal<FormFields>,
+}
+
+impl Form {
+    /// Create a form outside of a component, e.g. for a form shared by
+    /// several windows.
+    pub fn new(fields: FormFields) -> Self {
+        Self {
+            state: Signal::new(fields),
+        }
+    }
+
+    /// The current value of a field, or an empty string for unknown fields.
+    pub fn value(&self, name: &str) -> String {
+        self.with_field(name, |field| field.value.clone())
+            .unwrap_or_default()
+    }
+
+    /// The current values of all fields, in the order they were added.
+    pub fn values(&self) -> Vec<(String, String)> {
+        self.state.with(|form| {
+            form.fields
+                .iter()
+                .map(|field| (field.name.clone(), field.value.clone()))
+                .collect()
+        })
+    }
+
+    /// Set a field's value, marking it as touched. Unknown fields are ignored.
+    pub fn set_value(&self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        self.update_field(name, |field| {
+            field.value = value;
+            field.touched = true;
+        });
+    }
+
+    /// A callback that sets the field's value, for an `oninput` handler.
+    pub fn input_handler(&self, name: &str) -> impl Fn(String) + 'static {
+        let form = self.clone();
+        let name = name.to_string();
+        move |value| form.set_value(&name, value)
+    }
+
+    /// The field's validation error, whether or not it has been touched.
+    pub fn error(&self, name: &str) -> Option<String> {
+        self.with_field(name, FieldState::error).flatten()
+    }
+
+    /// The field's validation error if it should be shown, which is once the
+    /// field has been touched.
+    pub fn visible_error(&self, name: &str) -> Option<String> {
+        self.with_field(name, |field| field.touched.then(|| field.error()).flatten())
+            .flatten()
+    }
+
+    /// Whether the field's value differs from its initial value.
+    pub fn is_dirty(&self, name: &str) -> bool {
+        self.with_field(name, |field| field.value != field.initial)
+            .unwrap_or(false)
+    }
+
+    /// Whether any field's value differs from its initial value.
+    pub fn is_form_dirty(&self) -> bool {
+        self.state
+            .with(|form| form.fields.iter().any(|field| field.value != field.initial))
+    }
+
+    /// Whether the field has been edited, or the form submitted.
+    pub fn is_touched(&self, name: &str) -> bool {
+        self.with_field(name, |field| field.touched)
+            .unwrap_or(false)
+    }
+
+    /// Whether every field passes its validators.
+    pub fn is_valid(&self) -> bool {
+        self.state
+            .with(|form| form.fields.iter().all(|field| field.error().is_none()))
+    }
+
+    /// Mark every field as touched, so all errors are shown, and return
+    /// whether the form is valid.
+    ///
+    /// Call this when the user submits the form.
+    pub fn submit(&self) -> bool {
+        self.state.update(|form| {
+            for field in &mut form.fields {
+                field.touched = true;
+            }
+        });
+        self.is_valid()
+    }
+
+    /// Restore the initial values and clear the touched state.
+    pub fn reset(&self) {
+        self.state.update(|form| {
+            for field in &mut form.fields {
+                field.value = field.initial.clone();
+                field.touched = false;
+            }
+        });
+    }
+
+    /// Make the current values the initial ones, e.g. after saving them.
+    pub fn mark_clean(&self) {
+        self.state.update(|form| {
+            for field in &mut form.fields {
+                field.initial = field.value.clone();
+                field.touched = false;
+            }
+        });
+    }
+
+    fn with_field<R>(&self, name: &str, f: impl FnOnce(&FieldState) -> R) -> Option<R> {
+        self.state
+            .with(|form| form.fields.iter().find(|field| field.name == name).map(f))
+    }
+
+    fn update_field(&self, name: &str, f: impl FnOnce(&mut FieldState)) {
+        if self.with_field(name, |_| ()).is_none() {
+            return;
+        }
+        self.state.update(|form| {
+            if let Some(field) = form.fields.iter_mut().find(|field| field.name == name) {
+                f(field);
+            }
+        });
+    }
+}
+
+impl fmt::Debug for Form {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Form").field("values", &self.values()).finish()
+    }
+}
+
+/// Create or retrieve a form that persists across renders.
+///
+/// The initializer is only called on the first render. See the
+/// [module documentation](self) for an example.
+pub fn use_form(init: impl FnOnce() -> FormFields) -> Form {
+    Form {
+        state: use_signal(init),
+    }
+}
+
+/// Common validators.
+///
+/// Each takes the error message to show when the value is invalid.
+pub mod validators {
+    /// The value must not be empty or only whitespace.
+    pub fn required(message: impl Into<String>) -> impl Fn(&str) -> Result<(), String> {
+        let message = message.into();
+        move |value| check(!value.trim().is_empty(), &message)
+    }
+
+    /// The value must have at least `min` characters, unless it's empty.
+    ///
+    /// Combine with [`required`] for fields that can't be left empty.
+    pub fn min_length(min: usize, message: impl Into<String>) -> impl Fn(&str) -> Result<(), String> {
+        let message = message.into();
+        move |value| check(value.is_empty() || value.chars().count() >= min, &message)
+    }
+
+    /// The value must have at most `max` characters.
+    pub fn max_length(max: usize, message: impl Into<String>) -> impl Fn(&str) -> Result<(), String> {
+        let message = message.into();
+        move |value| check(value.chars().count() <= max, &message)
+    }
+
+    /// The value must look like an email address, unless it's empty.
+    pub fn email(message: impl Into<String>) -> impl Fn(&str) -> Result<(), String> {
+        let message = message.into();
+        move |value| {
+            let value = value.trim();
+            let valid = value.is_empty()
+                || value.split_once('@').is_some_and(|(user, domain)| {
+                    !user.is_empty()
+                        && !domain.starts_with('.')
+                        && !domain.ends_with('.')
+                        && domain.contains('.')
+                        && !value.contains(char::is_whitespace)
+                });
+            check(valid, &message)
+        }
+    }
+
+    /// The value must be a number between `min` and `max` (inclusive),
+    /// unless it's empty.
+    pub fn number_range(
+        min: f64,
+        max: f64,
+        message: impl Into<String>,
+    ) -> impl Fn(&str) -> Result<(), String> {
+        let message = message.into();
+        move |value| {
+            let value = value.trim();
+            let valid = value.is_empty()
+                || value
+                    .parse::<f64>()
+                    .is_ok_and(|number| (min..=max).contains(&number));
+            check(valid, &message)
+        }
+    }
+
+    fn check(valid: bool, message: &str) -> Result<(), String> {
+        if valid { Ok(()) } else { Err(message.to_string()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validators::*;
+    use super::*;
+
+    fn form() -> Form {
+        Form::new(
+            FormFields::new()
+                .field("name", "")
+                .validate("name", required("Name is required"))
+                .validate("name", min_length(3, "Too short"))
+                .field("email", "a@b.co")
+                .validate("email", email("Invalid email")),
+        )
+    }
+
+    #[test]
+    fn test_values_and_dirty_state() {
+        let form = form();
+        assert_eq!(form.value("email"), "a@b.co");
+        assert!(!form.is_form_dirty());
+
+        form.set_value("name", "Ada");
+        assert_eq!(form.value("name"), "Ada");
+        assert!(form.is_dirty("name"));
+        assert!(form.is_touched("name"));
+        assert!(!form.is_dirty("email"));
+
+        form.set_value("name", "");
+        assert!(!form.is_dirty("name"));
+        assert!(form.is_touched("name"));
+
+        form.reset();
+        assert!(!form.is_touched("name"));
+    }
+
+    #[test]
+    fn test_errors_shown_once_touched() {
+        let form = form();
+        assert_eq!(form.error("name").as_deref(), Some("Name is required"));
+        assert_eq!(form.visible_error("name"), None);
+        assert!(!form.is_valid());
+
+        form.set_value("name", "Al");
+        assert_eq!(form.visible_error("name").as_deref(), Some("Too short"));
+
+        form.set_value("name", "Alan");
+        assert_eq!(form.visible_error("name"), None);
+        assert!(form.is_valid());
+    }
+
+    #[test]
+    fn test_submit_touches_all_fields() {
+        let form = form();
+        form.set_value("email", "not an email");
+        assert!(!form.submit());
+        assert!(form.is_touched("name"));
+        assert_eq!(form.visible_error("email").as_deref(), Some("Invalid email"));
+    }
+
+    #[test]
+    fn test_input_handler() {
+        let form = form();
+        let handler = form.input_handler("name");
+        handler("Grace".into());
+        assert_eq!(form.value("name"), "Grace");
+
+        form.mark_clean();
+        assert!(!form.is_dirty("name"));
+        assert_eq!(
+            form.values(),
+            vec![("name".into(), "Grace".into()), ("email".into(), "a@b.co".into())]
+        );
+    }
+
+    #[test]
+    fn test_validators() {
+        assert!(email("x")("").is_ok());
+        assert!(email("x")("me@example.com").is_ok());
+        assert!(email("x")("me@example").is_err());
+        assert!(email("x")("@example.com").is_err());
+        assert!(max_length(2, "x")("abc").is_err());
+        assert!(number_range(1.0, 10.0, "x")("5").is_ok());
+        assert!(number_range(1.0, 10.0, "x")("11").is_err());
+        assert!(number_range(1.0, 10.0, "x")("ten").is_err());
+    }
+}
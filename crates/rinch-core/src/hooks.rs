@@ -282,7 +282,7 @@
 //! }
 //! ```
 
-use crate::reactive::{Memo, Signal};
+use crate::reactive::{untracked, Memo, Signal, SignalId};
 use std::any::{Any, TypeId};
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -304,6 +304,8 @@ pub struct HookMeta {
 struct HookEntry {
     value: Box<dyn Any>,
     meta: HookMeta,
+    /// The signal a `use_signal` hook holds.
+    signal: Option<SignalId>,
 }
 
 /// Registry that manages hook state across renders.
@@ -321,6 +323,9 @@ pub struct HookRegistry {
     expected_count: Option<usize>,
     /// Number of completed renders (for debugging)
     render_count: usize,
+    /// Whether the app's code was swapped since the last render, so its
+    /// hooks may differ (see [`begin_hot_swap`])
+    swapping: bool,
 }
 
 impl HookRegistry {
@@ -332,6 +337,7 @@ impl HookRegistry {
             is_rendering: false,
             expected_count: None,
             render_count: 0,
+            swapping: false,
         }
     }
 
@@ -343,6 +349,13 @@ impl HookRegistry {
 
     /// Validate hook count and end the render cycle.
     fn end_render(&mut self) {
+        // Hooks the swapped code no longer calls are dropped
+        if self.swapping {
+            self.hooks.truncate(self.current_index);
+            self.expected_count = None;
+            self.swapping = false;
+        }
+
         // Check for hook count mismatch
         if let Some(expected) = self.expected_count
             && self.current_index != expected
@@ -388,6 +401,16 @@ impl HookRegistry {
         let index = self.current_index;
         self.current_index += 1;
 
+        // After a code swap, a hook that changed kind or type starts over,
+        // and so do the hooks after it
+        if self.swapping
+            && self.hooks.get(index).is_some_and(|entry| {
+                entry.meta.hook_type != hook_type || !entry.value.is::<T>()
+            })
+        {
+            self.hooks.truncate(index);
+        }
+
         if index < self.hooks.len() {
             // Hook already exists - validate type and return
             let entry = &self.hooks[index];
@@ -420,12 +443,32 @@ impl HookRegistry {
             self.hooks.push(HookEntry {
                 value: Box::new(value.clone()),
                 meta,
+                signal: None,
             });
 
             value
         }
     }
 
+    /// Remember the signal held by the hook just used.
+    fn mark_signal(&mut self, signal: SignalId) {
+        if let Some(entry) = self.current_index.checked_sub(1).and_then(|index| self.hooks.get_mut(index)) {
+            entry.signal = Some(signal);
+        }
+    }
+
+    /// Give up on a render that panicked.
+    fn abort_render(&mut self) {
+        // Hooks created by the failed render are dropped; after a code swap
+        // the next render may still change the hooks
+        if !self.swapping
+            && let Some(expected) = self.expected_count
+        {
+            self.hooks.truncate(expected);
+        }
+        self.is_rendering = false;
+    }
+
     /// Clear all hooks (for app restart).
     fn clear(&mut self) {
         self.hooks.clear();
@@ -433,6 +476,7 @@ impl HookRegistry {
         self.is_rendering = false;
         self.expected_count = None;
         self.render_count = 0;
+        self.swapping = false;
     }
 }
 
@@ -568,6 +612,31 @@ pub fn end_render() {
     });
 }
 
+/// Prepare for the first render after the app's code was replaced, such as
+/// by hot reloading a library.
+///
+/// During that render, hooks keep their state as long as they're called in
+/// the same order with the same kind and type as before. From the first
+/// hook that differs on, hooks start over with their initial values, and
+/// hooks that are no longer called are dropped, instead of panicking like a
+/// hook order or count mismatch does.
+pub fn begin_hot_swap() {
+    HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().swapping = true;
+    });
+}
+
+/// End a render that panicked instead of finishing.
+///
+/// Hooks the render created are dropped, and the next render is checked
+/// against the last one that finished. Hooks that already existed keep the
+/// values the render may have set.
+pub fn abort_render() {
+    HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().abort_render();
+    });
+}
+
 /// Clear all hook state. Call this when restarting the app.
 ///
 /// This also clears all context values created with `create_context`.
@@ -593,6 +662,235 @@ pub fn get_hooks_debug_info() -> Vec<HookMeta> {
     })
 }
 
+/// The index of the `use_signal` hook holding a signal, if any.
+///
+/// Useful for devtools showing which hooks' signals changed.
+pub fn hook_of_signal(signal: SignalId) -> Option<usize> {
+    HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .hooks
+            .iter()
+            .position(|entry| entry.signal == Some(signal))
+    })
+}
+
+/// Why a hook's value couldn't be set with [`set_hook_value`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookValueError {
+    /// There's no hook at the index.
+    NoHook,
+    /// The hook isn't a `use_signal` of a number, string, `bool` or `char`.
+    NotEditable,
+    /// The text couldn't be parsed as the signal's type.
+    Invalid(String),
+}
+
+impl std::fmt::Display for HookValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookValueError::NoHook => write!(f, "no hook at this index"),
+            HookValueError::NotEditable => write!(f, "the hook's value can't be edited"),
+            HookValueError::Invalid(msg) => write!(f, "invalid value: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HookValueError {}
+
+/// Generates the functions that read and write signals of primitive types,
+/// which are the hook values DevTools can show and edit.
+macro_rules! primitive_signals {
+    ($($ty:ty),*) => {
+        /// The value of a hook, formatted, if it's a signal of a primitive type.
+        fn primitive_value(value: &dyn Any) -> Option<String> {
+            $(
+                if let Some(signal) = value.downcast_ref::<Signal<$ty>>() {
+                    return Some(untracked(|| signal.get()).to_string());
+                }
+            )*
+            None
+        }
+
+        /// Parse `text` as the value of a signal of a primitive type and
+        /// return a function setting it.
+        fn primitive_setter(value: &dyn Any, text: &str) -> Result<Box<dyn FnOnce()>, HookValueError> {
+            $(
+                if let Some(signal) = value.downcast_ref::<Signal<$ty>>() {
+                    let parsed = text
+                        .parse::<$ty>()
+                        .map_err(|e| HookValueError::Invalid(e.to_string()))?;
+                    let signal = signal.clone();
+                    return Ok(Box::new(move || signal.set(parsed)));
+                }
+            )*
+            Err(HookValueError::NotEditable)
+        }
+    };
+}
+
+primitive_signals!(
+    bool, char, String, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+/// Get the current value of each registered hook, in call order.
+///
+/// Values are shown for `use_signal` hooks holding a number, string, `bool`
+/// or `char`, and are `None` for the others. Reading them doesn't subscribe
+/// the current effect. Useful for devtools inspection.
+pub fn get_hook_values() -> Vec<Option<String>> {
+    HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .hooks
+            .iter()
+            .map(|entry| primitive_value(entry.value.as_ref()))
+            .collect()
+    })
+}
+
+/// Set the value of the hook at `index` from text, as typed in devtools.
+///
+/// Only `use_signal` hooks holding a number, string, `bool` or `char` can be
+/// set. The signal is set like any other, so effects run and the app
+/// re-renders.
+pub fn set_hook_value(index: usize, text: &str) -> Result<(), HookValueError> {
+    let set = HOOK_REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let entry = registry.hooks.get(index).ok_or(HookValueError::NoHook)?;
+        primitive_setter(entry.value.as_ref(), text)
+    })?;
+    // Set outside the borrow, since effects may read the registry
+    set();
+    Ok(())
+}
+
+/// The values of the hooks at one point, to put back after a code reload or
+/// a restart of the app.
+///
+/// Like [`get_hook_values`], only `use_signal` hooks holding a number,
+/// string, `bool` or `char` are saved, as text. [`restore`](Self::restore)
+/// matches hooks by index and kind, and parses the text as the current
+/// signal's type, so a signal whose type changed to a compatible one (say
+/// `i32` to `i64`) keeps its value. Hooks that no longer match keep the value
+/// they were created with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookSnapshot {
+    hooks: Vec<SavedHook>,
+}
+
+/// A hook saved in a [`HookSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SavedHook {
+    hook_type: String,
+    value: Option<String>,
+}
+
+impl HookSnapshot {
+    /// Save the current values of the registered hooks.
+    pub fn capture() -> Self {
+        let hooks = get_hooks_debug_info()
+            .into_iter()
+            .zip(get_hook_values())
+            .map(|(meta, value)| SavedHook {
+                hook_type: meta.hook_type.to_string(),
+                value,
+            })
+            .collect();
+        Self { hooks }
+    }
+
+    /// Set the saved values on the hooks registered now, returning how many
+    /// were changed.
+    ///
+    /// Call this after a render, outside of it. Signals are set like any
+    /// other, so effects run and the app re-renders.
+    pub fn restore(&self) -> usize {
+        let current = get_hooks_debug_info()
+            .into_iter()
+            .zip(get_hook_values())
+            .collect::<Vec<_>>();
+        let mut restored = 0;
+        for (index, saved) in self.hooks.iter().enumerate() {
+            let Some((meta, value)) = current.get(index) else {
+                break;
+            };
+            let Some(saved_value) = &saved.value else {
+                continue;
+            };
+            if meta.hook_type != saved.hook_type || value.as_ref() == Some(saved_value) {
+                continue;
+            }
+            if set_hook_value(index, saved_value).is_ok() {
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    /// Whether no hooks were saved.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Write the snapshot as text, one hook per line, to keep in a file.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for saved in &self.hooks {
+            text.push_str(&saved.hook_type);
+            if let Some(value) = &saved.value {
+                text.push('\t');
+                for c in value.chars() {
+                    match c {
+                        '\\' => text.push_str("\\\\"),
+                        '\n' => text.push_str("\\n"),
+                        '\r' => text.push_str("\\r"),
+                        '\t' => text.push_str("\\t"),
+                        c => text.push(c),
+                    }
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Read a snapshot written by [`to_text`](Self::to_text).
+    pub fn from_text(text: &str) -> Self {
+        let hooks = text
+            .lines()
+            .map(|line| match line.split_once('\t') {
+                Some((hook_type, escaped)) => {
+                    let mut value = String::new();
+                    let mut chars = escaped.chars();
+                    while let Some(c) = chars.next() {
+                        if c != '\\' {
+                            value.push(c);
+                            continue;
+                        }
+                        match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('r') => value.push('\r'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => {}
+                        }
+                    }
+                    SavedHook {
+                        hook_type: hook_type.to_string(),
+                        value: Some(value),
+                    }
+                }
+                None => SavedHook {
+                    hook_type: line.to_string(),
+                    value: None,
+                },
+            })
+            .collect();
+        Self { hooks }
+    }
+}
+
 // ============================================================================
 // Public API - Hook functions
 // ============================================================================
@@ -617,9 +915,10 @@ pub fn get_hooks_debug_info() -> Vec<HookMeta> {
 /// ```
 pub fn use_signal<T: Clone + 'static>(init: impl FnOnce() -> T) -> Signal<T> {
     HOOK_REGISTRY.with(|registry| {
-        registry
-            .borrow_mut()
-            .use_hook("use_signal", || Signal::new(init()))
+        let mut registry = registry.borrow_mut();
+        let signal = registry.use_hook("use_signal", || Signal::new(init()));
+        registry.mark_signal(signal.id());
+        signal
     })
 }
 
@@ -1077,6 +1376,80 @@ mod tests {
         end_render();
     }
 
+    #[test]
+    fn hot_swap_keeps_matching_hooks() {
+        reset_registry();
+
+        begin_render();
+        use_signal(|| 1).set(10);
+        use_signal(|| 2).set(20);
+        use_ref(|| 3);
+        end_render();
+
+        // The new code changed the second signal's type and dropped the ref
+        begin_hot_swap();
+        begin_render();
+        assert_eq!(use_signal(|| 0).get(), 10);
+        assert_eq!(use_signal(|| String::from("new")).get(), "new");
+        end_render();
+        assert_eq!(get_hooks_debug_info().len(), 2);
+
+        // Later renders check the hook count again
+        begin_render();
+        assert_eq!(use_signal(|| 0).get(), 10);
+        assert_eq!(use_signal(String::new).get(), "new");
+        end_render();
+    }
+
+    #[test]
+    fn aborted_render_drops_new_hooks() {
+        reset_registry();
+
+        begin_render();
+        use_signal(|| 1).set(10);
+        end_render();
+
+        // A render that called an extra hook, then panicked
+        begin_render();
+        use_signal(|| 0);
+        use_ref(|| 0);
+        abort_render();
+        assert_eq!(get_hooks_debug_info().len(), 1);
+
+        begin_render();
+        assert_eq!(use_signal(|| 0).get(), 10);
+        end_render();
+    }
+
+    #[test]
+    fn hook_snapshot_restores_matching_hooks() {
+        reset_registry();
+
+        begin_render();
+        use_signal(|| 1).set(10);
+        use_signal(String::new).set("two\tlines\n".to_string());
+        use_signal(|| 3).set(30);
+        use_ref(|| 4);
+        end_render();
+
+        let snapshot = HookSnapshot::from_text(&HookSnapshot::capture().to_text());
+        assert_eq!(snapshot, HookSnapshot::capture());
+
+        // After a restart, the first signal is now an i64 and the third
+        // became a ref
+        reset_registry();
+        begin_render();
+        let first = use_signal(|| 0i64);
+        let second = use_signal(String::new);
+        use_ref(|| 0);
+        use_ref(|| 0);
+        end_render();
+
+        assert_eq!(snapshot.restore(), 2);
+        assert_eq!(first.get(), 10);
+        assert_eq!(second.get(), "two\tlines\n");
+    }
+
     #[test]
     #[should_panic(expected = "Hook order mismatch")]
     fn hook_order_mismatch_panics() {
@@ -1237,4 +1610,61 @@ mod tests {
         assert_eq!(c2.get(), 3);
         end_render();
     }
+
+    #[test]
+    fn hook_values_are_shown_for_primitive_signals() {
+        reset_registry();
+
+        begin_render();
+        let _count = use_signal(|| 42);
+        let _name = use_signal(|| String::from("rinch"));
+        let _items = use_signal(Vec::<i32>::new);
+        let _cell = use_ref(|| 0);
+        end_render();
+
+        assert_eq!(
+            get_hook_values(),
+            vec![Some("42".to_string()), Some("rinch".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn set_hook_value_parses_the_signal_type() {
+        reset_registry();
+
+        begin_render();
+        let count = use_signal(|| 1);
+        let enabled = use_signal(|| false);
+        let items = use_signal(Vec::<i32>::new);
+        end_render();
+
+        assert_eq!(set_hook_value(0, "7"), Ok(()));
+        assert_eq!(count.get(), 7);
+        assert_eq!(set_hook_value(1, "true"), Ok(()));
+        assert!(enabled.get());
+
+        assert!(matches!(set_hook_value(0, "seven"), Err(HookValueError::Invalid(_))));
+        assert_eq!(count.get(), 7);
+        assert_eq!(set_hook_value(2, "[]"), Err(HookValueError::NotEditable));
+        assert_eq!(set_hook_value(3, "1"), Err(HookValueError::NoHook));
+        assert!(items.get().is_empty());
+    }
+
+    #[test]
+    fn hook_of_signal_finds_use_signal_hooks() {
+        reset_registry();
+
+        begin_render();
+        let _cell = use_ref(|| 0);
+        let count = use_signal(|| 0);
+        end_render();
+        begin_render();
+        let _cell = use_ref(|| 0);
+        let again = use_signal(|| 0);
+        end_render();
+
+        assert_eq!(hook_of_signal(count.id()), Some(1));
+        assert_eq!(hook_of_signal(again.id()), Some(1));
+        assert_eq!(hook_of_signal(Signal::new(0).id()), None);
+    }
 }
@@ -3,20 +3,39 @@
 pub mod element;
 pub mod event;
 pub mod events;
+pub mod form;
 pub mod hooks;
 pub mod reactive;
+pub mod rich_text;
 
 // Re-export reactive types for convenience
-pub use reactive::{batch, derived, untracked, Effect, Memo, Scope, Signal};
+pub use reactive::{
+    affects_render, batch, derived, take_render_triggers, track_render, untracked, Effect, Memo, Scope, Signal,
+    SignalId,
+};
 
 // Re-export hooks for ergonomic state management
 pub use hooks::{
-    begin_render, clear_hooks, create_context, end_render, get_hooks_debug_info, use_callback,
-    use_context, use_derived, use_effect, use_effect_cleanup, use_memo, use_mount, use_ref,
-    use_signal, use_state, HookMeta, RefHandle,
+    abort_render, begin_hot_swap, begin_render, clear_hooks, create_context, end_render, get_hook_values,
+    get_hooks_debug_info, hook_of_signal, set_hook_value, use_callback, use_context, use_derived, use_effect,
+    use_effect_cleanup, use_memo, use_mount, use_ref, use_signal, use_state, HookMeta,
+    HookSnapshot, HookValueError, RefHandle,
 };
 
+// Re-export form state
+pub use form::{use_form, Form, FormFields};
+
+// Re-export rich text editing
+pub use rich_text::{use_rich_text, RichText, RichTextCommand, RichTextState};
+
+// Re-export event types
+pub use event::Modifiers;
+
 // Re-export event handling types
 pub use events::{
-    clear_handlers, dispatch_event, register_handler, EventCallback, EventHandlerId,
+    bind_attribute, bind_text, clear_handlers, dispatch_change, dispatch_event, dispatch_input, dispatch_reorder,
+    dispatch_select, register_change_handler, register_handler, register_input_handler,
+    register_reorder_handler, register_select_handler, restore_handlers, take_handlers,
+    Bindable, BindingId, BindingPatch, ChangeCallback, CheckedValue, EventCallback, EventHandlerId,
+    InputCallback, ReorderCallback, SavedHandlers, SelectCallback,
 };
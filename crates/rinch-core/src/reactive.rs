@@ -23,9 +23,9 @@
 //! ```
 
 use std::cell::{Cell, RefCell};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 // ============================================================================
 // Runtime Context
@@ -53,6 +53,20 @@ struct Runtime {
 
     /// Counter for generating unique IDs
     next_id: usize,
+
+    /// Whether a signal the app's render read changed, see [`affects_render`]
+    render_dirty: bool,
+
+    /// Number of signal changes so far
+    writes: usize,
+
+    /// Signals read by the render that changed since the last
+    /// [`take_render_triggers`]
+    render_triggers: Vec<SignalId>,
+
+    /// The subscriber sets of the signals and memos each effect or memo
+    /// reads, so disposing an effect can unsubscribe it
+    sources: HashMap<ObserverId, Vec<Weak<Subscribers>>>,
 }
 
 impl Runtime {
@@ -62,6 +76,10 @@ impl Runtime {
             pending_effects: Vec::new(),
             batching: false,
             next_id: 0,
+            render_dirty: false,
+            writes: 0,
+            render_triggers: Vec::new(),
+            sources: HashMap::new(),
         }
     }
 
@@ -76,6 +94,25 @@ impl Runtime {
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct ObserverId(usize);
 
+/// The observer of signals read while the app renders, see [`track_render`].
+const RENDER_OBSERVER: ObserverId = ObserverId(usize::MAX);
+
+/// The observers of a signal or memo.
+type Subscribers = RefCell<HashSet<ObserverId>>;
+
+/// Subscribe the current observer (if any) to a signal or memo.
+fn subscribe(subscribers: &Rc<Subscribers>) {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        if let Some(&observer) = rt.observer_stack.last()
+            && subscribers.borrow_mut().insert(observer)
+            && observer != RENDER_OBSERVER
+        {
+            rt.sources.entry(observer).or_default().push(Rc::downgrade(subscribers));
+        }
+    });
+}
+
 // ============================================================================
 // Signal
 // ============================================================================
@@ -106,28 +143,40 @@ pub struct Signal<T> {
 
 struct SignalInner<T> {
     value: RefCell<T>,
-    subscribers: RefCell<HashSet<ObserverId>>,
+    subscribers: Rc<Subscribers>,
 }
 
+/// Identifies a signal and its clones, for debugging tools.
+///
+/// The ID of a dropped signal may be reused by a new one.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SignalId(usize);
+
 impl<T> Signal<T> {
     /// Create a new signal with the given initial value.
     pub fn new(value: T) -> Self {
         Self {
             inner: Rc::new(SignalInner {
                 value: RefCell::new(value),
-                subscribers: RefCell::new(HashSet::new()),
+                subscribers: Rc::default(),
             }),
         }
     }
 
+    /// The ID shared by this signal and its clones.
+    pub fn id(&self) -> SignalId {
+        SignalId(Rc::as_ptr(&self.inner) as *const () as usize)
+    }
+
     /// Subscribe the current observer (if any) to this signal.
     fn track(&self) {
-        RUNTIME.with(|rt| {
-            let rt = rt.borrow();
-            if let Some(&observer) = rt.observer_stack.last() {
-                self.inner.subscribers.borrow_mut().insert(observer);
-            }
-        });
+        subscribe(&self.inner.subscribers);
+    }
+
+    /// The number of effects, memos, and renders subscribed to this signal.
+    #[cfg(test)]
+    pub(crate) fn subscriber_count(&self) -> usize {
+        self.inner.subscribers.borrow().len()
     }
 
     /// Notify all subscribers that the value has changed.
@@ -136,6 +185,11 @@ impl<T> Signal<T> {
 
         RUNTIME.with(|rt| {
             let mut rt = rt.borrow_mut();
+            rt.writes += 1;
+            let id = self.id();
+            if subscribers.contains(&RENDER_OBSERVER) && !rt.render_triggers.contains(&id) {
+                rt.render_triggers.push(id);
+            }
             for observer in subscribers {
                 if !rt.pending_effects.contains(&observer) {
                     rt.pending_effects.push(observer);
@@ -215,9 +269,10 @@ impl<T: fmt::Display> fmt::Display for Signal<T> {
 // Effect
 // ============================================================================
 
-// Storage for all effects (needed because effects reference themselves)
+// Storage for all live effects (needed because effects reference themselves),
+// keyed by ID so disposed effects free their entry
 thread_local! {
-    static EFFECTS: RefCell<Vec<Option<Rc<EffectInner>>>> = RefCell::new(Vec::new());
+    static EFFECTS: RefCell<HashMap<ObserverId, Rc<EffectInner>>> = RefCell::new(HashMap::new());
 }
 
 /// A side-effect that re-runs when its dependencies change.
@@ -263,14 +318,7 @@ impl Effect {
         });
 
         // Store the effect
-        EFFECTS.with(|effects| {
-            let mut effects = effects.borrow_mut();
-            let idx = id.0;
-            if idx >= effects.len() {
-                effects.resize(idx + 1, None);
-            }
-            effects[idx] = Some(Rc::clone(&inner));
-        });
+        EFFECTS.with(|effects| effects.borrow_mut().insert(id, inner));
 
         // Run the effect immediately
         run_effect(id);
@@ -291,14 +339,7 @@ impl Effect {
             disposed: Cell::new(false),
         });
 
-        EFFECTS.with(|effects| {
-            let mut effects = effects.borrow_mut();
-            let idx = id.0;
-            if idx >= effects.len() {
-                effects.resize(idx + 1, None);
-            }
-            effects[idx] = Some(inner);
-        });
+        EFFECTS.with(|effects| effects.borrow_mut().insert(id, inner));
 
         Effect { id }
     }
@@ -309,12 +350,29 @@ impl Effect {
     }
 
     /// Dispose of this effect, preventing it from running again.
+    ///
+    /// The effect unsubscribes from the signals and memos it read, and its
+    /// closure, and whatever it captured, is dropped.
     pub fn dispose(&self) {
-        EFFECTS.with(|effects| {
-            if let Some(Some(inner)) = effects.borrow().get(self.id.0) {
-                inner.disposed.set(true);
+        let sources = RUNTIME
+            .try_with(|rt| rt.borrow_mut().sources.remove(&self.id))
+            .ok()
+            .flatten();
+        for subscribers in sources.into_iter().flatten() {
+            if let Some(subscribers) = subscribers.upgrade() {
+                subscribers.borrow_mut().remove(&self.id);
             }
-        });
+        }
+
+        // `try_with`, as handles can be dropped while the thread exits
+        let inner = EFFECTS
+            .try_with(|effects| effects.borrow_mut().remove(&self.id))
+            .ok()
+            .flatten();
+        // Dropped outside the borrow, in case the closure owns other effects
+        if let Some(inner) = inner {
+            inner.disposed.set(true);
+        }
     }
 }
 
@@ -327,8 +385,13 @@ impl Drop for Effect {
 
 /// Run a specific effect by ID
 fn run_effect(id: ObserverId) {
+    if id == RENDER_OBSERVER {
+        RUNTIME.with(|rt| rt.borrow_mut().render_dirty = true);
+        return;
+    }
+
     let effect = EFFECTS.with(|effects| {
-        effects.borrow().get(id.0).and_then(|e| e.clone())
+        effects.borrow().get(&id).cloned()
     });
 
     if let Some(inner) = effect {
@@ -394,7 +457,7 @@ struct MemoInner<T> {
     value: RefCell<Option<T>>,
     f: RefCell<Box<dyn Fn() -> T>>,
     dirty: Cell<bool>,
-    subscribers: RefCell<HashSet<ObserverId>>,
+    subscribers: Rc<Subscribers>,
 }
 
 impl<T: Clone + 'static> Memo<T> {
@@ -410,20 +473,15 @@ impl<T: Clone + 'static> Memo<T> {
             value: RefCell::new(None),
             f: RefCell::new(Box::new(f)),
             dirty: Cell::new(true),
-            subscribers: RefCell::new(HashSet::new()),
+            subscribers: Rc::default(),
         });
 
         // Store memo as an effect so it can be notified
         let inner_clone = Rc::clone(&inner);
         EFFECTS.with(|effects| {
-            let mut effects = effects.borrow_mut();
-            let idx = id.0;
-            if idx >= effects.len() {
-                effects.resize(idx + 1, None);
-            }
             // We store a "marker" effect that marks the memo as dirty
             let memo_inner = inner_clone;
-            effects[idx] = Some(Rc::new(EffectInner {
+            effects.borrow_mut().insert(id, Rc::new(EffectInner {
                 id,
                 f: RefCell::new(Box::new(move || {
                     memo_inner.dirty.set(true);
@@ -448,12 +506,7 @@ impl<T: Clone + 'static> Memo<T> {
     /// Get the current value, recomputing if necessary.
     pub fn get(&self) -> T {
         // Subscribe current observer to this memo
-        RUNTIME.with(|rt| {
-            let rt = rt.borrow();
-            if let Some(&observer) = rt.observer_stack.last() {
-                self.inner.subscribers.borrow_mut().insert(observer);
-            }
-        });
+        subscribe(&self.inner.subscribers);
 
         // Recompute if dirty
         if self.inner.dirty.get() {
@@ -529,6 +582,63 @@ pub fn batch<R>(f: impl FnOnce() -> R) -> R {
     result
 }
 
+// ============================================================================
+// Render tracking
+// ============================================================================
+
+/// Run the app's render function, remembering which signals it reads.
+///
+/// Signals read by effects and memos created during the render are
+/// tracked by those, not by the render.
+pub fn track_render<R>(f: impl FnOnce() -> R) -> R {
+    RUNTIME.with(|rt| rt.borrow_mut().observer_stack.push(RENDER_OBSERVER));
+    let result = f();
+    RUNTIME.with(|rt| {
+        rt.borrow_mut().observer_stack.pop();
+    });
+    result
+}
+
+/// Take the signals read by a render that changed since the last call, in
+/// the order they first changed.
+///
+/// Called before rendering, these are the signals that made the render
+/// necessary, for debugging tools showing why the app rendered.
+pub fn take_render_triggers() -> Vec<SignalId> {
+    RUNTIME.with(|rt| std::mem::take(&mut rt.borrow_mut().render_triggers))
+}
+
+/// Run `f`, typically an event handler, and report whether the app should
+/// render again afterwards.
+///
+/// That is the case unless `f` changed signals and none of them was read
+/// by a render run with [`track_render`]. Changes to signals that only
+/// effects read, such as attribute bindings, are then already applied.
+/// State outside signals isn't tracked, so `f` changing no signal at all
+/// also asks for a render.
+///
+/// # Example
+///
+/// ```ignore
+/// let (handled, render) = affects_render(|| dispatch_event(id));
+/// if handled && render {
+///     request_render();
+/// }
+/// ```
+pub fn affects_render<R>(f: impl FnOnce() -> R) -> (R, bool) {
+    let writes = RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.render_dirty = false;
+        rt.writes
+    });
+    let result = f();
+    let render = RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.writes == writes || std::mem::take(&mut rt.render_dirty)
+    });
+    (result, render)
+}
+
 // ============================================================================
 // Scope (for memory management)
 // ============================================================================
@@ -744,4 +854,102 @@ mod tests {
         count.set(1);
         assert_eq!(run_count.get(), 1);
     }
+
+    #[test]
+    fn affects_render_follows_signals_read_by_render() {
+        let rendered = Signal::new(0);
+        let bound = Signal::new(0);
+
+        let rendered_clone = rendered.clone();
+        track_render(move || rendered_clone.get());
+        let bound_clone = bound.clone();
+        Effect::new(move || {
+            let _ = bound_clone.get();
+        });
+
+        assert!(affects_render(|| rendered.set(1)).1);
+        assert!(!affects_render(|| bound.set(1)).1);
+        // Nothing tracked changed, so other state may have
+        assert!(affects_render(|| ()).1);
+    }
+
+    #[test]
+    fn render_triggers_are_the_changed_signals_read_by_render() {
+        let first = Signal::new(0);
+        let second = Signal::new(0);
+        let bound = Signal::new(0);
+
+        let (first_clone, second_clone) = (first.clone(), second.clone());
+        track_render(move || first_clone.get() + second_clone.get());
+        let bound_clone = bound.clone();
+        Effect::new(move || {
+            let _ = bound_clone.get();
+        });
+        take_render_triggers();
+
+        second.set(1);
+        bound.set(1);
+        first.set(1);
+        second.set(2);
+        assert_eq!(take_render_triggers(), vec![second.id(), first.id()]);
+        assert!(take_render_triggers().is_empty());
+        assert_eq!(first.clone().id(), first.id());
+        assert_ne!(first.id(), second.id());
+    }
+
+    #[test]
+    fn disposed_effect_stops_running() {
+        let count = Signal::new(0);
+        let run_count = Rc::new(Cell::new(0));
+
+        let run_count_clone = Rc::clone(&run_count);
+        let count_clone = count.clone();
+        let effect = Effect::new(move || {
+            let _ = count_clone.get();
+            run_count_clone.set(run_count_clone.get() + 1);
+        });
+        effect.dispose();
+
+        count.set(1);
+        assert_eq!(run_count.get(), 1);
+        // The closure was dropped with its captures
+        assert_eq!(Rc::strong_count(&run_count), 1);
+    }
+
+    #[test]
+    fn disposed_effect_unsubscribes() {
+        let count = Signal::new(0);
+        let doubled = {
+            let count = count.clone();
+            Memo::new(move || count.get() * 2)
+        };
+
+        let (count_clone, doubled_clone) = (count.clone(), doubled.clone());
+        let effect = Effect::new(move || {
+            let _ = count_clone.get() + doubled_clone.get();
+        });
+        // Re-running doesn't subscribe the effect twice
+        count.set(1);
+        assert_eq!(count.subscriber_count(), 2);
+        assert_eq!(doubled.inner.subscribers.borrow().len(), 1);
+
+        effect.dispose();
+        // Only the memo is left
+        assert_eq!(count.subscriber_count(), 1);
+        assert!(doubled.inner.subscribers.borrow().is_empty());
+    }
+
+    #[test]
+    fn disposed_effects_free_their_storage() {
+        let count = Signal::new(0);
+        let live = EFFECTS.with(|effects| effects.borrow().len());
+        for _ in 0..10 {
+            let count = count.clone();
+            Effect::new(move || {
+                count.get();
+            })
+            .dispose();
+        }
+        assert_eq!(EFFECTS.with(|effects| effects.borrow().len()), live);
+    }
 }
@@ -0,0 +1,608 @@
+//! Rich text documents and editing state.
+//!
+//! A [`RichText`] is a list of paragraphs and list items made of bold and/or
+//! italic spans. [`use_rich_text`] keeps a document together with the
+//! selection of the text input it's edited in, and applies formatting
+//! commands to that selection. The `RichTextEditor` element in `rsx!` renders
+//! a formatting toolbar, the text input and the formatted document.
+//!
+//! Blitz text inputs edit plain text, so the document's formatting is kept
+//! alongside its text: edits to the text are matched against the previous
+//! text, and inserted characters take the formatting of the text before them.
+//! Text positions are byte offsets into the document's plain text, in which
+//! blocks are separated by `\n`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//!
+//! fn notes() -> Element {
+//!     let editor = use_rich_text(|| RichText::from_plain("Shopping\nMilk\nEggs"));
+//!
+//!     rsx! {
+//!         RichTextEditor {
+//!             editor: editor.clone(),
+//!             rows: "8",
+//!             onchange: move |document: RichText| save(&document.to_html()),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::events::html_escape_string;
+use crate::hooks::{use_ref, use_signal};
+use crate::reactive::Signal;
+
+/// The kind of a block of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockKind {
+    /// A paragraph.
+    #[default]
+    Paragraph,
+    /// An item of a bulleted list. Consecutive items form one list.
+    ListItem,
+}
+
+/// A run of text with the same formatting.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// A paragraph or list item.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Block {
+    pub kind: BlockKind,
+    /// The block's text. Adjacent spans have different formatting and none
+    /// are empty.
+    pub spans: Vec<Span>,
+}
+
+impl Block {
+    /// The block's text without formatting.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// A formatting command, applied to the selection with
+/// [`RichTextState::apply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RichTextCommand {
+    /// Toggle bold text.
+    Bold,
+    /// Toggle italic text.
+    Italic,
+    /// Toggle between list items and paragraphs.
+    List,
+}
+
+/// A document of formatted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RichText {
+    /// Never empty; an empty document is one empty paragraph.
+    blocks: Vec<Block>,
+}
+
+impl Default for RichText {
+    fn default() -> Self {
+        Self {
+            blocks: vec![Block::default()],
+        }
+    }
+}
+
+impl RichText {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a document of unformatted paragraphs, one per line.
+    pub fn from_plain(text: &str) -> Self {
+        let mut document = Self::new();
+        document.replace(0..0, text);
+        document
+    }
+
+    /// The document's paragraphs and list items.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// The document's text without formatting, with blocks separated by `\n`.
+    pub fn plain_text(&self) -> String {
+        self.blocks
+            .iter()
+            .map(Block::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Replace a range of the text.
+    ///
+    /// Inserted text takes the formatting of the text before it, and a
+    /// line break inserted in a list item starts a new list item.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        let mut flat = self.flatten();
+        let (start, end) = flat.char_range(&self.plain_text(), range);
+
+        let block = flat.breaks_before(start);
+        let removed_breaks = flat.breaks_before(end) - block;
+        let style = match (start.checked_sub(1).map(|i| flat.chars[i]), flat.chars.get(end)) {
+            (Some((c, style)), _) if c != '\n' => style,
+            (_, Some(&(c, style))) if c != '\n' => style,
+            _ => Style::default(),
+        };
+        flat.chars
+            .splice(start..end, text.chars().map(|c| (c, style)));
+
+        let kind = flat.kinds[block];
+        flat.kinds.drain(block + 1..block + 1 + removed_breaks);
+        let added_breaks = text.matches('\n').count();
+        flat.kinds
+            .splice(block + 1..block + 1, std::iter::repeat_n(kind, added_breaks));
+
+        *self = flat.into_document();
+    }
+
+    /// Change the text to `text`, keeping the formatting of the parts that
+    /// didn't change. Returns whether the text changed.
+    pub fn set_text(&mut self, text: &str) -> bool {
+        let old = self.plain_text();
+        if old == text {
+            return false;
+        }
+
+        let prefix: usize = old
+            .chars()
+            .zip(text.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        let suffix: usize = old[prefix..]
+            .chars()
+            .rev()
+            .zip(text[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        self.replace(prefix..old.len() - suffix, &text[prefix..text.len() - suffix]);
+        true
+    }
+
+    /// Whether all of the text in `range` is bold. For an empty range, this
+    /// is the formatting of the text before it.
+    pub fn is_bold(&self, range: Range<usize>) -> bool {
+        self.all_styled(range, |style| style.bold)
+    }
+
+    /// Make the text in `range` bold or not bold.
+    pub fn set_bold(&mut self, range: Range<usize>, bold: bool) {
+        self.restyle(range, |style| style.bold = bold);
+    }
+
+    /// Whether all of the text in `range` is italic. For an empty range, this
+    /// is the formatting of the text before it.
+    pub fn is_italic(&self, range: Range<usize>) -> bool {
+        self.all_styled(range, |style| style.italic)
+    }
+
+    /// Make the text in `range` italic or not italic.
+    pub fn set_italic(&mut self, range: Range<usize>, italic: bool) {
+        self.restyle(range, |style| style.italic = italic);
+    }
+
+    /// Whether every block that `range` touches is a list item.
+    pub fn is_list(&self, range: Range<usize>) -> bool {
+        self.blocks[self.block_range(range)]
+            .iter()
+            .all(|block| block.kind == BlockKind::ListItem)
+    }
+
+    /// Make the blocks that `range` touches list items or paragraphs.
+    pub fn set_list(&mut self, range: Range<usize>, list: bool) {
+        let kind = if list { BlockKind::ListItem } else { BlockKind::Paragraph };
+        let blocks = self.block_range(range);
+        for block in &mut self.blocks[blocks] {
+            block.kind = kind;
+        }
+    }
+
+    /// Apply a formatting command to `range`, toggling the formatting off if
+    /// all of the range already has it.
+    pub fn apply(&mut self, command: RichTextCommand, range: Range<usize>) {
+        let active = self.is_active(command, range.clone());
+        match command {
+            RichTextCommand::Bold => self.set_bold(range, !active),
+            RichTextCommand::Italic => self.set_italic(range, !active),
+            RichTextCommand::List => self.set_list(range, !active),
+        }
+    }
+
+    /// Whether all of `range` already has the command's formatting.
+    pub fn is_active(&self, command: RichTextCommand, range: Range<usize>) -> bool {
+        match command {
+            RichTextCommand::Bold => self.is_bold(range),
+            RichTextCommand::Italic => self.is_italic(range),
+            RichTextCommand::List => self.is_list(range),
+        }
+    }
+
+    /// The document as HTML: `<p>` for paragraphs, `<ul>` and `<li>` for
+    /// lists, and `<strong>` and `<em>` for formatting.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let mut in_list = false;
+        for block in &self.blocks {
+            let list_item = block.kind == BlockKind::ListItem;
+            if list_item != in_list {
+                html.push_str(if list_item { "<ul>" } else { "</ul>" });
+                in_list = list_item;
+            }
+            html.push_str(if list_item { "<li>" } else { "<p>" });
+            if block.spans.is_empty() {
+                html.push_str("<br>");
+            }
+            for span in &block.spans {
+                let text = html_escape_string(&span.text);
+                match (span.bold, span.italic) {
+                    (true, true) => html.push_str(&format!("<strong><em>{}</em></strong>", text)),
+                    (true, false) => html.push_str(&format!("<strong>{}</strong>", text)),
+                    (false, true) => html.push_str(&format!("<em>{}</em>", text)),
+                    (false, false) => html.push_str(&text),
+                }
+            }
+            html.push_str(if list_item { "</li>" } else { "</p>" });
+        }
+        if in_list {
+            html.push_str("</ul>");
+        }
+        html
+    }
+
+    fn all_styled(&self, range: Range<usize>, test: impl Fn(Style) -> bool) -> bool {
+        let flat = self.flatten();
+        let (start, end) = flat.char_range(&self.plain_text(), range);
+        if start == end {
+            return start
+                .checked_sub(1)
+                .map(|i| flat.chars[i])
+                .is_some_and(|(c, style)| c != '\n' && test(style));
+        }
+        let mut text = flat.chars[start..end].iter().filter(|(c, _)| *c != '\n').peekable();
+        text.peek().is_some() && text.all(|&(_, style)| test(style))
+    }
+
+    fn restyle(&mut self, range: Range<usize>, change: impl Fn(&mut Style)) {
+        let mut flat = self.flatten();
+        let (start, end) = flat.char_range(&self.plain_text(), range);
+        for (_, style) in &mut flat.chars[start..end] {
+            change(style);
+        }
+        *self = flat.into_document();
+    }
+
+    /// The indices of the blocks that a range of the text touches.
+    fn block_range(&self, range: Range<usize>) -> Range<usize> {
+        let text = self.plain_text();
+        let block = |offset: usize| text.as_bytes()[..offset.min(text.len())]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        block(range.start)..block(range.end.max(range.start)) + 1
+    }
+
+    fn flatten(&self) -> Flat {
+        let mut chars = Vec::new();
+        for (i, block) in self.blocks.iter().enumerate() {
+            if i > 0 {
+                chars.push(('\n', Style::default()));
+            }
+            for span in &block.spans {
+                let style = Style {
+                    bold: span.bold,
+                    italic: span.italic,
+                };
+                chars.extend(span.text.chars().map(|c| (c, style)));
+            }
+        }
+        Flat {
+            chars,
+            kinds: self.blocks.iter().map(|block| block.kind).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Style {
+    bold: bool,
+    italic: bool,
+}
+
+/// A document as formatted characters, with `\n` between blocks.
+struct Flat {
+    chars: Vec<(char, Style)>,
+    /// One per block.
+    kinds: Vec<BlockKind>,
+}
+
+impl Flat {
+    /// Convert a byte range of `text` (the plain text) to character indices,
+    /// clamping it to the text.
+    fn char_range(&self, text: &str, range: Range<usize>) -> (usize, usize) {
+        let index = |offset: usize| {
+            let mut offset = offset.min(text.len());
+            while !text.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            text[..offset].chars().count()
+        };
+        let start = index(range.start);
+        (start, index(range.end).max(start))
+    }
+
+    /// The number of block breaks before a character index.
+    fn breaks_before(&self, index: usize) -> usize {
+        self.chars[..index].iter().filter(|(c, _)| *c == '\n').count()
+    }
+
+    fn into_document(self) -> RichText {
+        let mut blocks: Vec<Block> = self
+            .kinds
+            .into_iter()
+            .map(|kind| Block { kind, spans: Vec::new() })
+            .collect();
+        let mut block = 0;
+        for (c, style) in self.chars {
+            if c == '\n' {
+                block += 1;
+                continue;
+            }
+            let spans = &mut blocks[block].spans;
+            match spans.last_mut() {
+                Some(span) if span.bold == style.bold && span.italic == style.italic => {
+                    span.text.push(c);
+                }
+                _ => spans.push(Span {
+                    text: c.to_string(),
+                    bold: style.bold,
+                    italic: style.italic,
+                }),
+            }
+        }
+        RichText { blocks }
+    }
+}
+
+/// A handle to a rich text document and the selection of the input it's
+/// edited in, created with [`use_rich_text`].
+///
+/// Cloning the handle is cheap and every clone refers to the same document.
+/// Changing the document re-renders the window like setting a signal.
+#[derive(Clone)]
+pub struct RichTextState {
+    document: Signal<RichText>,
+    selection: Rc<Cell<(usize, usize)>>,
+}
+
+/// Called with the document after it's edited or formatted.
+#[doc(hidden)]
+pub type ChangeHandler = Rc<dyn Fn(RichText)>;
+
+impl RichTextState {
+    /// Create editing state outside of a component.
+    pub fn new(document: RichText) -> Self {
+        Self {
+            document: Signal::new(document),
+            selection: Rc::new(Cell::new((0, 0))),
+        }
+    }
+
+    /// The current document.
+    pub fn document(&self) -> RichText {
+        self.document.get()
+    }
+
+    /// Replace the document, moving the selection to the start.
+    pub fn set_document(&self, document: RichText) {
+        self.selection.set((0, 0));
+        self.document.set(document);
+    }
+
+    /// The document's text without formatting.
+    pub fn text(&self) -> String {
+        self.document.with(RichText::plain_text)
+    }
+
+    /// The selected range of the text, which is empty when nothing is
+    /// selected.
+    pub fn selection(&self) -> Range<usize> {
+        let (start, end) = self.selection.get();
+        start.min(end)..start.max(end)
+    }
+
+    /// Set the selected range, which the next command applies to.
+    pub fn set_selection(&self, start: usize, end: usize) {
+        self.selection.set((start, end));
+    }
+
+    /// Change the text, keeping the formatting of the parts that didn't change.
+    pub fn set_text(&self, text: &str) {
+        if self.text() != text {
+            self.document.update(|document| {
+                document.set_text(text);
+            });
+        }
+    }
+
+    /// Apply a formatting command to the selection.
+    pub fn apply(&self, command: RichTextCommand) {
+        let selection = self.selection();
+        self.document.update(|document| document.apply(command, selection));
+    }
+
+    /// Whether the selection already has the command's formatting.
+    pub fn is_active(&self, command: RichTextCommand) -> bool {
+        let selection = self.selection();
+        self.document.with(|document| document.is_active(command, selection))
+    }
+
+    /// The `oninput` handler of the editor's text input.
+    #[doc(hidden)]
+    pub fn input_handler(&self, on_change: ChangeHandler) -> impl Fn(String) + 'static {
+        let state = self.clone();
+        move |text| {
+            state.set_text(&text);
+            on_change(state.document());
+        }
+    }
+
+    /// The `onselect` handler of the editor's text input.
+    #[doc(hidden)]
+    pub fn select_handler(&self) -> impl Fn(usize, usize) + 'static {
+        let state = self.clone();
+        move |start, end| state.set_selection(start, end)
+    }
+
+    /// The `onclick` handler of a toolbar button.
+    #[doc(hidden)]
+    pub fn command_handler(
+        &self,
+        command: RichTextCommand,
+        on_change: ChangeHandler,
+    ) -> impl Fn() + 'static {
+        let state = self.clone();
+        move || {
+            state.apply(command);
+            on_change(state.document());
+        }
+    }
+}
+
+impl fmt::Debug for RichTextState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RichTextState")
+            .field("document", &self.document())
+            .field("selection", &self.selection())
+            .finish()
+    }
+}
+
+/// Wrap an `onchange` handler, for `rsx!`.
+#[doc(hidden)]
+pub fn change_handler(handler: impl Fn(RichText) + 'static) -> ChangeHandler {
+    Rc::new(handler)
+}
+
+/// Create or retrieve rich text editing state that persists across renders.
+///
+/// The initializer is only called on the first render. See the
+/// [module documentation](self) for an example.
+pub fn use_rich_text(init: impl FnOnce() -> RichText) -> RichTextState {
+    let selection = use_ref(|| Rc::new(Cell::new((0, 0))));
+    let selection = selection.borrow().clone();
+    RichTextState {
+        document: use_signal(init),
+        selection,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(document: &RichText, block: usize) -> Vec<(&str, bool, bool)> {
+        document.blocks()[block]
+            .spans
+            .iter()
+            .map(|span| (span.text.as_str(), span.bold, span.italic))
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_text_round_trip() {
+        let document = RichText::from_plain("one\ntwo\n");
+        assert_eq!(document.blocks().len(), 3);
+        assert_eq!(document.plain_text(), "one\ntwo\n");
+        assert_eq!(RichText::new().plain_text(), "");
+        assert_eq!(RichText::new().blocks().len(), 1);
+    }
+
+    #[test]
+    fn test_formatting_toggles() {
+        let mut document = RichText::from_plain("hello world");
+        document.apply(RichTextCommand::Bold, 0..5);
+        assert_eq!(spans(&document, 0), vec![("hello", true, false), (" world", false, false)]);
+        assert!(document.is_bold(0..5));
+        assert!(!document.is_bold(0..6));
+
+        document.apply(RichTextCommand::Italic, 3..8);
+        assert_eq!(
+            spans(&document, 0),
+            vec![
+                ("hel", true, false),
+                ("lo", true, true),
+                (" wo", false, true),
+                ("rld", false, false),
+            ]
+        );
+
+        document.apply(RichTextCommand::Bold, 0..5);
+        assert!(!document.is_bold(0..1));
+    }
+
+    #[test]
+    fn test_edits_keep_formatting() {
+        let mut document = RichText::from_plain("ab\ncd");
+        document.set_bold(0..2, true);
+
+        // Typing after bold text continues it
+        assert!(document.set_text("abX\ncd"));
+        assert_eq!(spans(&document, 0), vec![("abX", true, false)]);
+        assert_eq!(spans(&document, 1), vec![("cd", false, false)]);
+
+        // Joining blocks keeps both formats
+        document.set_text("abXcd");
+        assert_eq!(document.blocks().len(), 1);
+        assert_eq!(spans(&document, 0), vec![("abX", true, false), ("cd", false, false)]);
+        assert!(!document.set_text("abXcd"));
+    }
+
+    #[test]
+    fn test_lists() {
+        let mut document = RichText::from_plain("title\nfirst\nsecond");
+        document.apply(RichTextCommand::List, 6..13);
+        assert!(document.is_list(6..13));
+        assert!(!document.is_list(0..13));
+
+        // A line break in a list item starts another one
+        document.set_text("title\nfirst\nsecond\nthird");
+        assert_eq!(document.blocks()[3].kind, BlockKind::ListItem);
+        assert_eq!(
+            document.to_html(),
+            "<p>title</p><ul><li>first</li><li>second</li><li>third</li></ul>"
+        );
+
+        document.apply(RichTextCommand::List, 6..6);
+        assert_eq!(document.blocks()[1].kind, BlockKind::Paragraph);
+    }
+
+    #[test]
+    fn test_state_applies_commands_to_selection() {
+        let state = RichTextState::new(RichText::from_plain("a <b> c"));
+        state.set_selection(5, 2);
+        assert_eq!(state.selection(), 2..5);
+        state.apply(RichTextCommand::Italic);
+        assert!(state.is_active(RichTextCommand::Italic));
+        assert_eq!(state.document().to_html(), "<p>a <em>&lt;b&gt;</em> c</p>");
+    }
+}
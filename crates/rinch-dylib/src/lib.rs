@@ -0,0 +1,14 @@
+//! rinch built as a Rust `dylib`.
+//!
+//! An app whose code is hot reloaded from a library (see
+//! `rinch::shell::hot_library`) depends on this crate instead of `rinch`,
+//! in both the binary and the library, so they share one copy of rinch's
+//! hooks, signals and event handlers. Rename it to keep `rinch::` paths
+//! (which `rsx!` relies on) working:
+//!
+//! ```toml
+//! [dependencies]
+//! rinch = { package = "rinch-dylib", version = "0.1" }
+//! ```
+
+pub use rinch::*;
@@ -1,18 +1,28 @@
 //! Procedural macros for rinch - RSX syntax.
 //!
-//! Provides the `rsx!` macro for declarative UI definition.
+//! Provides the `rsx!` macro for declarative UI definition, and
+//! `#[derive(Settings)]` for `rinch::settings`.
 
 mod prop_schema;
+mod scoped_css;
+mod settings;
 mod suggestions;
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
-use syn::{braced, token, Expr, Ident, LitStr, Result, Token};
+use syn::{braced, parenthesized, token, Expr, Ident, LitStr, Result, Token};
 
-use prop_schema::{get_prop_names, get_required_props, is_valid_prop};
-use suggestions::{format_missing_prop_error, format_unknown_prop_error};
+use prop_schema::{
+    get_aria_prop_names, get_menu_role_names, get_prop_names, get_required_props, get_role_names,
+    is_aria_prop, is_valid_aria_prop, is_valid_menu_role, is_valid_prop, is_valid_role,
+};
+use suggestions::{
+    format_missing_prop_error, format_unknown_aria_error, format_unknown_menu_role_error,
+    format_unknown_prop_error, format_unknown_role_error,
+};
 
 /// The main RSX macro for building UI.
 ///
@@ -31,12 +41,77 @@ use suggestions::{format_missing_prop_error, format_unknown_prop_error};
 ///     }
 /// }
 /// ```
+///
+/// # Loops and conditions
+///
+/// `for` and `if` / `else` work like in Rust, around children:
+///
+/// ```ignore
+/// rsx! {
+///     ul {
+///         for item in items.get() {
+///             li { {item} }
+///         }
+///     }
+///     if items.get().is_empty() {
+///         p { "Nothing here yet" }
+///     }
+/// }
+/// ```
+///
+/// # Scoped styles
+///
+/// A `css!` block adds styles that only apply to the HTML generated by the
+/// same `rsx!` invocation. Every HTML element gets a scope class and every
+/// selector in the block is rewritten to require it:
+///
+/// ```ignore
+/// fn card(title: &str) -> Element {
+///     rsx! {
+///         div { class: "card",
+///             css! { ".card { padding: 16px; } h2 { margin: 0; }" }
+///             h2 { {title} }
+///         }
+///     }
+/// }
+/// ```
 #[proc_macro]
 pub fn rsx(input: TokenStream) -> TokenStream {
-    let node = syn::parse_macro_input!(input as RsxNode);
+    let mut node = syn::parse_macro_input!(input as RsxNode);
+    if let Err(error) = expand_sortables(&mut node) {
+        return error.to_compile_error().into();
+    }
+    apply_scoped_styles(&mut node);
     node.to_element().into()
 }
 
+/// Store a struct as app settings with `rinch::settings`.
+///
+/// Implements `rinch::settings::Settings` and generates a `<Name>Fields`
+/// struct with a `Signal` per field. The struct also needs serde's
+/// `Serialize` and `Deserialize`, and `Clone` and `Default`.
+///
+/// ```ignore
+/// #[derive(Settings, Serialize, Deserialize, Clone, Default)]
+/// #[settings(name = "preferences", format = "toml", version = 2, migrate = migrate_preferences)]
+/// struct Preferences {
+///     font_size: u32,
+///     theme: String,
+/// }
+/// ```
+///
+/// All options are optional: `name` is the file name (default: the struct's
+/// name in snake case), `format` is `"toml"` (default) or `"json"`, `version`
+/// is the schema version written to the file (default 0), and `migrate` is a
+/// `fn(u32, &mut rinch::settings::Value)` run on files of older versions.
+#[proc_macro_derive(Settings, attributes(settings))]
+pub fn derive_settings(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    settings::derive_settings(input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
 /// A node in the RSX tree.
 enum RsxNode {
     /// A component or HTML element with optional props and children.
@@ -45,6 +120,12 @@ enum RsxNode {
     Text(LitStr),
     /// A Rust expression in braces.
     Expr(Expr),
+    /// A `css!` block of scoped styles.
+    Css(String),
+    /// A `for` loop repeating its children.
+    For(Box<RsxFor>),
+    /// An `if` block choosing between its branches.
+    If(Box<RsxIf>),
 }
 
 impl Parse for RsxNode {
@@ -55,6 +136,27 @@ impl Parse for RsxNode {
             let content;
             braced!(content in input);
             Ok(RsxNode::Expr(content.parse()?))
+        } else if input.peek(Token![for]) {
+            Ok(RsxNode::For(Box::new(input.parse()?)))
+        } else if input.peek(Token![if]) {
+            Ok(RsxNode::If(Box::new(input.parse()?)))
+        } else if input.peek(Ident) && input.peek2(Token![!]) {
+            let name: Ident = input.parse()?;
+            if name != "css" {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    format!("unsupported macro `{}!` in rsx, expected `css!`", name),
+                ));
+            }
+            input.parse::<Token![!]>()?;
+            let content;
+            if input.peek(token::Paren) {
+                parenthesized!(content in input);
+            } else {
+                braced!(content in input);
+            }
+            let css: LitStr = content.parse()?;
+            Ok(RsxNode::Css(css.value()))
         } else {
             Ok(RsxNode::Element(input.parse()?))
         }
@@ -67,12 +169,18 @@ impl RsxNode {
             RsxNode::Element(el) => el.to_element(),
             RsxNode::Text(lit) => {
                 let text = lit.value();
-                quote! { Element::Html(#text.into()) }
+                static_html_element(&text)
             }
             RsxNode::Expr(expr) => {
                 // Wrap expressions in a ToString call for display
-                quote! { Element::Html(::std::string::ToString::to_string(&#expr).into()) }
+                quote! { Element::Html(::std::borrow::Cow::Owned(::std::string::ToString::to_string(&#expr))) }
+            }
+            RsxNode::Css(css) => {
+                let html = style_html(css);
+                static_html_element(&html)
             }
+            RsxNode::For(each) => each.to_element(),
+            RsxNode::If(branch) => branch.to_element(),
         }
     }
 
@@ -87,12 +195,112 @@ impl RsxNode {
                 // Dynamic expression - needs runtime string conversion
                 quote! { &::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#expr)) }
             }
+            RsxNode::Css(css) => {
+                let html = style_html(css);
+                quote! { #html }
+            }
+            RsxNode::For(_) | RsxNode::If(_) => {
+                let statements = self.gen_html_statements();
+                quote! {
+                    &{
+                        let mut __html = String::new();
+                        #statements
+                        __html
+                    }
+                }
+            }
+        }
+    }
+
+    /// Statements that append this node's HTML to `__html`.
+    fn gen_html_statements(&self) -> TokenStream2 {
+        match self {
+            RsxNode::Element(el) if el.has_dynamic_content() && !el.is_generated_html() => el.gen_html_statements(),
+            RsxNode::For(each) => each.gen_html_statements(false),
+            RsxNode::If(branch) => branch.gen_html_statements(false),
+            _ => {
+                let tokens = self.to_html_tokens();
+                quote! { __html.push_str(#tokens); }
+            }
+        }
+    }
+
+    /// Like [`gen_html_statements`](Self::gen_html_statements), but text
+    /// written as `{signal.get()}` is bound to the signal if it's
+    /// `Bindable`: it's written between `<!--rbind:<id>-->` and
+    /// `<!--/rbind-->` comments, and its ID added to `__text_bindings`, which
+    /// the parent element writes to its `data-rbind-text` attribute.
+    ///
+    /// `bind_text` is false outside of HTML elements and inside elements
+    /// whose content isn't HTML, such as `textarea` and `title`.
+    fn gen_child_html_statements(&self, bind_text: bool) -> TokenStream2 {
+        let signal = match self {
+            RsxNode::Expr(expr) if bind_text => signal_get(expr),
+            RsxNode::For(each) => return each.gen_html_statements(bind_text),
+            RsxNode::If(branch) => return branch.gen_html_statements(bind_text),
+            _ => None,
+        };
+        let plain = self.gen_html_statements();
+        let Some(signal) = signal else {
+            return plain;
+        };
+        quote! {
+            {
+                #[allow(unused_imports)]
+                use ::rinch::core::events::{BindIfBindable as _, RenderIfNotBindable as _};
+                match (&::rinch::core::events::BindProbe(&#signal)).bind_text() {
+                    ::std::option::Option::Some((__binding_id, __value)) => {
+                        let __binding_id = ::std::string::ToString::to_string(&__binding_id);
+                        if !__text_bindings.is_empty() {
+                            __text_bindings.push(' ');
+                        }
+                        __text_bindings.push_str(&__binding_id);
+                        __html.push_str("<!--rbind:");
+                        __html.push_str(&__binding_id);
+                        __html.push_str("-->");
+                        __html.push_str(&::rinch::core::events::html_escape_string(&__value));
+                        __html.push_str("<!--/rbind-->");
+                    }
+                    ::std::option::Option::None => { #plain }
+                }
+            }
+        }
+    }
+
+    /// The length of the HTML this node always generates.
+    fn static_html_len(&self) -> usize {
+        match self {
+            RsxNode::Element(el) => el.static_html_len(),
+            RsxNode::Expr(_) | RsxNode::For(_) | RsxNode::If(_) => 0,
+            node => node_to_static_html(node).len(),
         }
     }
 
     fn is_rinch_component(&self) -> bool {
         match self {
             RsxNode::Element(el) => el.is_rinch_component(),
+            RsxNode::For(each) => each.children.iter().any(RsxNode::is_rinch_component),
+            RsxNode::If(branch) => branch.nodes().any(RsxNode::is_rinch_component),
+            _ => false,
+        }
+    }
+
+    fn validate_aria_props(&self) -> Option<TokenStream2> {
+        match self {
+            RsxNode::Element(el) => el.validate_aria_props(),
+            RsxNode::For(each) => each.children.iter().find_map(RsxNode::validate_aria_props),
+            RsxNode::If(branch) => branch.nodes().find_map(RsxNode::validate_aria_props),
+            _ => None,
+        }
+    }
+
+    /// Whether this is text written as `{signal.get()}`, or a loop or `if`
+    /// containing some, which its parent element binds.
+    fn has_bound_text(&self) -> bool {
+        match self {
+            RsxNode::Expr(expr) => signal_get(expr).is_some(),
+            RsxNode::For(each) => each.children.iter().any(RsxNode::has_bound_text),
+            RsxNode::If(branch) => branch.nodes().any(RsxNode::has_bound_text),
             _ => false,
         }
     }
@@ -100,8 +308,8 @@ impl RsxNode {
     fn has_dynamic_content(&self) -> bool {
         match self {
             RsxNode::Element(el) => el.has_dynamic_content(),
-            RsxNode::Text(_) => false,
-            RsxNode::Expr(_) => true,
+            RsxNode::Text(_) | RsxNode::Css(_) => false,
+            RsxNode::Expr(_) | RsxNode::For(_) | RsxNode::If(_) => true,
         }
     }
 }
@@ -124,8 +332,8 @@ impl Parse for RsxElement {
         let mut children = Vec::new();
 
         while !content.is_empty() {
-            // Try to parse as a prop (name: value)
-            if content.peek(Ident) && content.peek2(Token![:]) && !content.peek2(Token![::]) {
+            // Try to parse as a prop (name: value), which may be a keyword like `type`
+            if content.peek(Ident::peek_any) && content.peek2(Token![:]) && !content.peek2(Token![::]) {
                 let prop: RsxProp = content.parse()?;
                 props.push(prop);
 
@@ -158,18 +366,47 @@ impl RsxElement {
         let name = self.name.to_string();
         matches!(
             name.as_str(),
-            "Window" | "AppMenu" | "Menu" | "MenuItem" | "MenuSeparator" | "Fragment"
+            "Window"
+                | "AppMenu"
+                | "Menu"
+                | "MenuItem"
+                | "MenuSeparator"
+                | "RecentFilesMenu"
+                | "Tray"
+                | "Fragment"
         )
     }
 
+    /// Add a class to the element, keeping any existing `class` value.
+    fn add_class(&mut self, class: &str) {
+        match self.props.iter_mut().find(|p| p.name == "class") {
+            Some(prop) => {
+                prop.value = match &prop.value {
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(existing),
+                        ..
+                    }) => {
+                        let joined = format!("{} {}", existing.value(), class);
+                        syn::parse_quote! { #joined }
+                    }
+                    value => syn::parse_quote! { ::std::format!("{} {}", #value, #class) },
+                };
+            }
+            None => self.props.push(RsxProp {
+                name: Ident::new("class", self.name.span()),
+                value: syn::parse_quote! { #class },
+            }),
+        }
+    }
+
     /// Validate props for a component and return a compile_error! if invalid.
     /// Returns None if validation passes, Some(error_tokens) otherwise.
     fn validate_props(&self) -> Option<TokenStream2> {
         let component_name = self.name.to_string();
 
-        // Skip validation for HTML elements (not rinch components)
+        // HTML elements (not rinch components) only have their ARIA attributes checked
         if !self.is_rinch_component() {
-            return None;
+            return self.validate_aria_props();
         }
 
         // MenuSeparator and Fragment don't have props
@@ -198,11 +435,49 @@ impl RsxElement {
             }
         }
 
-        None
+        // Menu item roles are checked like ARIA roles
+        if component_name == "MenuItem"
+            && let Some(prop) = self.props.iter().find(|p| p.name == "role")
+            && let Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(role), .. }) = &prop.value
+            && !is_valid_menu_role(&role.value())
+        {
+            let error_msg = format_unknown_menu_role_error(&role.value(), get_menu_role_names());
+            return Some(syn::Error::new_spanned(&prop.value, error_msg).to_compile_error());
+        }
+
+        self.children.iter().find_map(RsxNode::validate_aria_props)
+    }
+
+    /// Check the ARIA attributes and `role` of this element and the HTML
+    /// elements inside it.
+    fn validate_aria_props(&self) -> Option<TokenStream2> {
+        if !self.is_rinch_component() {
+            for prop in &self.props {
+                let prop_name = prop.name.to_string();
+                if is_aria_prop(&prop_name) && !is_valid_aria_prop(&prop_name) {
+                    let error_msg = format_unknown_aria_error(&prop_name, get_aria_prop_names());
+                    return Some(syn::Error::new_spanned(&prop.name, error_msg).to_compile_error());
+                }
+                // A role can list fallbacks, separated by spaces
+                if prop_name == "role"
+                    && let Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(roles), .. }) = &prop.value
+                    && let Some(role) = roles.value().split_whitespace().find(|role| !is_valid_role(role))
+                {
+                    let error_msg = format_unknown_role_error(role, get_role_names());
+                    return Some(syn::Error::new_spanned(&prop.value, error_msg).to_compile_error());
+                }
+            }
+        }
+        self.children.iter().find_map(RsxNode::validate_aria_props)
     }
 
     /// Check if this element or any children have event handlers or dynamic expressions.
     fn has_dynamic_content(&self) -> bool {
+        // Fields and editors read their state when rendered
+        if self.name == "Field" || self.name == "RichTextEditor" {
+            return true;
+        }
+
         // Check for event handlers
         if self.props.iter().any(|p| is_event_prop(&p.name.to_string())) {
             return true;
@@ -231,7 +506,17 @@ impl RsxElement {
             "Menu" => self.gen_menu(),
             "MenuItem" => self.gen_menu_item(),
             "MenuSeparator" => quote! { Element::MenuSeparator },
+            "RecentFilesMenu" => self.gen_recent_files_menu(),
+            "Tray" => self.gen_tray(),
             "Fragment" => self.gen_fragment(),
+            "Field" => {
+                let field = self.gen_field();
+                quote! { Element::Html(::std::borrow::Cow::Owned(#field)) }
+            }
+            "RichTextEditor" => {
+                let editor = self.gen_rich_text_editor();
+                quote! { Element::Html(::std::borrow::Cow::Owned(#editor)) }
+            }
             _ => self.gen_html_element(),
         }
     }
@@ -254,8 +539,14 @@ impl RsxElement {
         let mut borderless = quote! { false };
         let mut resizable = quote! { true };
         let mut transparent = quote! { false };
+        let mut shadow = quote! { true };
         let mut always_on_top = quote! { false };
         let mut visible = quote! { true };
+        let mut color_scheme = quote! { WindowColorScheme::System };
+        let mut dir = quote! { TextDirection::Auto };
+        let mut key = quote! { None };
+        let mut src = quote! { None };
+        let mut scale = quote! { None };
 
         for prop in &self.props {
             let name = prop.name.to_string();
@@ -270,8 +561,14 @@ impl RsxElement {
                 "borderless" => borderless = quote! { #value },
                 "resizable" => resizable = quote! { #value },
                 "transparent" => transparent = quote! { #value },
+                "shadow" => shadow = quote! { #value },
                 "always_on_top" => always_on_top = quote! { #value },
                 "visible" => visible = quote! { #value },
+                "color_scheme" => color_scheme = quote! { WindowColorScheme::from(#value) },
+                "dir" => dir = quote! { TextDirection::from(#value) },
+                "key" => key = quote! { Some(String::from(#value)) },
+                "src" => src = quote! { Some(String::from(#value)) },
+                "scale" => scale = quote! { Some(#value) },
                 _ => {}
             }
         }
@@ -286,8 +583,14 @@ impl RsxElement {
                 borderless: #borderless,
                 resizable: #resizable,
                 transparent: #transparent,
+                shadow: #shadow,
                 always_on_top: #always_on_top,
                 visible: #visible,
+                color_scheme: #color_scheme,
+                dir: #dir,
+                key: #key,
+                src: #src,
+                scale: #scale,
             }
         }
     }
@@ -333,103 +636,171 @@ impl RsxElement {
     }
 
     fn gen_menu_item(&self) -> TokenStream2 {
+        let mut id = quote! { None };
         let mut label = quote! { String::new() };
         let mut shortcut = quote! { None };
         let mut enabled = quote! { true };
         let mut checked = quote! { None };
         let mut onclick = quote! { None };
+        let mut role = quote! { None };
 
         for prop in &self.props {
             let name = prop.name.to_string();
             let value = &prop.value;
 
             match name.as_str() {
+                "id" => id = quote! { Some(String::from(#value)) },
                 "label" => label = quote! { String::from(#value) },
                 "shortcut" => shortcut = quote! { Some(String::from(#value)) },
                 "enabled" => enabled = quote! { #value },
                 "checked" => checked = quote! { Some(#value) },
-                "onclick" => onclick = quote! { Some(MenuItemCallback::new(#value)) },
+                "onclick" => onclick = gen_menu_callback(value),
+                "role" => role = quote! { MenuRole::from_name(#value) },
                 _ => {}
             }
         }
 
         quote! {
             Element::MenuItem(MenuItemProps {
+                id: #id,
                 label: #label,
                 shortcut: #shortcut,
                 enabled: #enabled,
                 checked: #checked,
                 onclick: #onclick,
+                role: #role,
             })
         }
     }
 
-    fn gen_fragment(&self) -> TokenStream2 {
-        let children = self.gen_children_as_elements();
-        quote! { Element::Fragment(#children) }
-    }
+    fn gen_recent_files_menu(&self) -> TokenStream2 {
+        let mut label = quote! { String::from("Open Recent") };
+        let mut clear_label = quote! { String::from("Clear Menu") };
+        let mut onselect = quote! { None };
 
-    fn gen_children_as_elements(&self) -> TokenStream2 {
-        if self.children.is_empty() {
-            return quote! { vec![] };
+        for prop in &self.props {
+            let name = prop.name.to_string();
+            let value = &prop.value;
+
+            match name.as_str() {
+                "label" => label = quote! { String::from(#value) },
+                "clear_label" => clear_label = quote! { String::from(#value) },
+                "onselect" => onselect = quote! { Some(RecentFileCallback::new(#value)) },
+                _ => {}
+            }
         }
 
-        // Check if all children are HTML elements (can be combined into one HTML string)
-        let all_html = self.children.iter().all(|c| !c.is_rinch_component());
+        quote! {
+            Element::RecentFilesMenu(RecentFilesMenuProps {
+                label: #label,
+                clear_label: #clear_label,
+                onselect: #onselect,
+            })
+        }
+    }
 
-        if all_html {
-            // Check if we need dynamic HTML generation
-            let has_dynamic = self.children.iter().any(|c| c.has_dynamic_content());
+    fn gen_tray(&self) -> TokenStream2 {
+        let mut tooltip = quote! { None };
+        let mut icon = quote! { None };
+        let mut onclick = quote! { None };
+        let mut ondoubleclick = quote! { None };
 
-            if has_dynamic {
-                // Generate runtime HTML building
-                let html_parts: Vec<TokenStream2> =
-                    self.children.iter().map(|c| c.to_html_tokens()).collect();
+        for prop in &self.props {
+            let name = prop.name.to_string();
+            let value = &prop.value;
 
-                quote! {
-                    vec![Element::Html({
-                        let mut __html = String::new();
-                        #( __html.push_str(#html_parts); )*
-                        __html
-                    })]
-                }
-            } else {
-                // Static HTML string
-                let html: String = self.children.iter().map(|c| node_to_static_html(c)).collect();
-                quote! { vec![Element::Html(#html.into())] }
+            match name.as_str() {
+                "tooltip" => tooltip = quote! { Some(String::from(#value)) },
+                "icon" => icon = quote! { Some(std::path::PathBuf::from(#value)) },
+                "onclick" => onclick = gen_tray_click_callback(value),
+                "ondoubleclick" => ondoubleclick = gen_tray_click_callback(value),
+                _ => {}
             }
-        } else {
-            // Mix of components and HTML - generate each separately
-            let children: Vec<TokenStream2> = self
-                .children
-                .iter()
-                .map(|c| {
-                    if c.is_rinch_component() {
-                        c.to_element()
-                    } else if c.has_dynamic_content() {
-                        let html_tokens = c.to_html_tokens();
-                        quote! { Element::Html(#html_tokens.into()) }
-                    } else {
-                        let html = node_to_static_html(c);
-                        quote! { Element::Html(#html.into()) }
-                    }
-                })
-                .collect();
+        }
 
-            quote! { vec![#(#children),*] }
+        let children = self.gen_children_as_elements();
+
+        quote! {
+            Element::Tray(
+                TrayProps {
+                    tooltip: #tooltip,
+                    icon: #icon,
+                    onclick: #onclick,
+                    ondoubleclick: #ondoubleclick,
+                },
+                #children
+            )
         }
     }
 
+    fn gen_fragment(&self) -> TokenStream2 {
+        let children = self.gen_children_as_elements();
+        quote! { Element::Fragment(#children) }
+    }
+
+    fn gen_children_as_elements(&self) -> TokenStream2 {
+        gen_elements(&self.children)
+    }
+
     fn gen_html_element(&self) -> TokenStream2 {
         if self.has_dynamic_content() {
             self.gen_dynamic_html_element()
         } else {
             let html = self.to_static_html();
-            quote! { Element::Html(#html.into()) }
+            static_html_element(&html)
         }
     }
 
     fn gen_dynamic_html_element(&self) -> TokenStream2 {
+        let statements = self.gen_html_statements();
+        let capacity = self.static_html_len();
+        quote! {
+            Element::Html(::std::borrow::Cow::Owned({
+                let mut __html = String::with_capacity(#capacity);
+                #statements
+                __html
+            }))
+        }
+    }
+
+    fn to_html_tokens(&self) -> TokenStream2 {
+        if self.name == "Field" {
+            let field = self.gen_field();
+            quote! { &#field }
+        } else if self.name == "RichTextEditor" {
+            let editor = self.gen_rich_text_editor();
+            quote! { &#editor }
+        } else if self.has_dynamic_content() {
+            self.gen_dynamic_html_tokens()
+        } else {
+            let html = self.to_static_html();
+            quote! { #html }
+        }
+    }
+
+    /// Whether this is a `Field` or `RichTextEditor`, whose HTML is built by
+    /// code of its own.
+    fn is_generated_html(&self) -> bool {
+        self.name == "Field" || self.name == "RichTextEditor"
+    }
+
+    fn gen_dynamic_html_tokens(&self) -> TokenStream2 {
+        let statements = self.gen_html_statements();
+        let capacity = self.static_html_len();
+        quote! {
+            &{
+                let mut __html = String::with_capacity(#capacity);
+                #statements
+                __html
+            }
+        }
+    }
+
+    /// Statements that append this element's HTML to `__html`.
+    ///
+    /// Dynamic children write into the same buffer rather than building
+    /// strings of their own to be copied in.
+    fn gen_html_statements(&self) -> TokenStream2 {
         let tag = self.name.to_string();
 
         // Separate event handlers from regular attributes
@@ -438,179 +809,326 @@ impl RsxElement {
             .iter()
             .partition(|p| is_event_prop(&p.name.to_string()));
 
-        // Build attribute string
+        // Build attribute parts
         let attr_parts: Vec<TokenStream2> = attr_props
             .iter()
             .map(|p| {
                 let name = p.name.to_string();
                 let value = &p.value;
-                if is_literal_expr(value) {
+                let attr_name = html_attr_name(&name);
+                if name == "checked" {
+                    let attr = gen_checked_attr(value);
+                    quote! { __html.push_str(#attr); }
+                } else if is_literal_expr(value) {
                     let val_str = expr_to_string(value);
                     let escaped = html_escape(&val_str);
-                    let attr = format!(" {}=\"{}\"", name, escaped);
-                    quote! { #attr }
+                    let attr = format!(" {}=\"{}\"", attr_name, escaped);
+                    quote! { __html.push_str(#attr); }
+                } else if let Some(signal) = bound_signal(value) {
+                    // Patched in place when the signal changes, if it's `Bindable`
+                    let start = format!(" {}=\"", attr_name);
+                    let marker = format!("\" data-rbind-{}=\"", attr_name);
+                    quote! {
+                        {
+                            #[allow(unused_imports)]
+                            use ::rinch::core::events::{BindIfBindable as _, RenderIfNotBindable as _};
+                            __html.push_str(#start);
+                            match (&::rinch::core::events::BindProbe(&#signal)).bind_attribute(#attr_name) {
+                                ::std::option::Option::Some((__binding_id, __value)) => {
+                                    __html.push_str(&::rinch::core::events::html_escape_string(&__value));
+                                    __html.push_str(#marker);
+                                    __html.push_str(&::std::string::ToString::to_string(&__binding_id));
+                                }
+                                ::std::option::Option::None => {
+                                    __html.push_str(&::rinch::core::events::html_escape_string(
+                                        &::std::string::ToString::to_string(&#value),
+                                    ));
+                                }
+                            }
+                            __html.push('"');
+                        }
+                    }
                 } else {
-                    // Dynamic attribute value
+                    let start = format!(" {}=\"", attr_name);
                     quote! {
-                        &format!(" {}=\"{}\"", #name, ::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#value)))
+                        __html.push_str(#start);
+                        __html.push_str(&::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#value)));
+                        __html.push('"');
                     }
                 }
             })
             .collect();
 
-        // Generate event handler registration
-        let event_registrations: Vec<TokenStream2> = event_props
-            .iter()
-            .map(|p| {
-                let handler = &p.value;
-                quote! {
-                    let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
-                }
-            })
-            .collect();
-
-        // Build the data-rid attribute if we have event handlers
-        let rid_attr = if !event_props.is_empty() {
-            quote! { &format!(" data-rid=\"{}\"", __handler_id) }
-        } else {
-            quote! { "" }
-        };
-
-        // Build children HTML
-        let children_tokens: Vec<TokenStream2> =
-            self.children.iter().map(|c| c.to_html_tokens()).collect();
+        // Event handler registrations and their attributes
+        let checked = attr_props.iter().copied().find(|p| p.name == "checked");
+        let (event_registrations, event_attrs) = gen_event_bindings(&event_props, checked);
 
+        let open = format!("<{}", tag);
         if is_void_element(&tag) {
             quote! {
                 {
                     #(#event_registrations)*
-                    Element::Html({
-                        let mut __html = String::new();
-                        __html.push_str("<");
-                        __html.push_str(#tag);
-                        #( __html.push_str(#attr_parts); )*
-                        __html.push_str(#rid_attr);
-                        __html.push_str(" />");
-                        __html
-                    })
+                    __html.push_str(#open);
+                    #( #attr_parts )*
+                    #( __html.push_str(#event_attrs); )*
+                    __html.push_str(" />");
                 }
             }
         } else {
+            let bind_text = !is_raw_text_element(&tag) && self.children.iter().any(RsxNode::has_bound_text);
+            let children: Vec<TokenStream2> =
+                self.children.iter().map(|c| c.gen_child_html_statements(bind_text)).collect();
+            let close = format!("</{}>", tag);
+            // The IDs of bound text are only known once the children are written
+            let children = if bind_text {
+                quote! {
+                    // Before the `>`
+                    let __open_end = __html.len() - 1;
+                    let mut __text_bindings = String::new();
+                    #( #children )*
+                    if !__text_bindings.is_empty() {
+                        __html.insert_str(__open_end, &format!(" data-rbind-text=\"{}\"", __text_bindings));
+                    }
+                }
+            } else {
+                quote! { #( #children )* }
+            };
             quote! {
                 {
                     #(#event_registrations)*
-                    Element::Html({
-                        let mut __html = String::new();
-                        __html.push_str("<");
-                        __html.push_str(#tag);
-                        #( __html.push_str(#attr_parts); )*
-                        __html.push_str(#rid_attr);
-                        __html.push_str(">");
-                        #( __html.push_str(#children_tokens); )*
-                        __html.push_str("</");
-                        __html.push_str(#tag);
-                        __html.push_str(">");
-                        __html
-                    })
+                    __html.push_str(#open);
+                    #( #attr_parts )*
+                    #( __html.push_str(#event_attrs); )*
+                    __html.push_str(">");
+                    #children
+                    __html.push_str(#close);
                 }
             }
         }
     }
 
-    fn to_html_tokens(&self) -> TokenStream2 {
-        if self.has_dynamic_content() {
-            self.gen_dynamic_html_tokens()
-        } else {
-            let html = self.to_static_html();
-            quote! { #html }
+    /// The length of the HTML this element always generates, to size the
+    /// buffer it is built in.
+    fn static_html_len(&self) -> usize {
+        if !self.has_dynamic_content() {
+            return self.to_static_html().len();
+        }
+        if self.is_generated_html() {
+            return 0;
         }
-    }
-
-    fn gen_dynamic_html_tokens(&self) -> TokenStream2 {
         let tag = self.name.to_string();
-
-        // Separate event handlers from regular attributes
-        let (event_props, attr_props): (Vec<_>, Vec<_>) = self
+        let attrs: usize = self
             .props
             .iter()
-            .partition(|p| is_event_prop(&p.name.to_string()));
-
-        // Build attribute parts
-        let attr_parts: Vec<TokenStream2> = attr_props
-            .iter()
-            .map(|p| {
-                let name = p.name.to_string();
-                let value = &p.value;
-                if is_literal_expr(value) {
-                    let val_str = expr_to_string(value);
-                    let escaped = html_escape(&val_str);
-                    let attr = format!(" {}=\"{}\"", name, escaped);
-                    quote! { __html.push_str(#attr); }
-                } else {
-                    quote! {
-                        __html.push_str(&format!(" {}=\"{}\"", #name, ::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#value))));
-                    }
+            .filter(|p| !is_event_prop(&p.name.to_string()))
+            .map(|p| match (p.name.to_string().as_str(), is_literal_expr(&p.value)) {
+                ("checked", _) => 0,
+                (name, true) => {
+                    let value = html_escape(&expr_to_string(&p.value));
+                    format!(" {}=\"{}\"", html_attr_name(name), value).len()
                 }
+                (name, false) => html_attr_name(name).len() + 4,
             })
-            .collect();
+            .sum();
+        if is_void_element(&tag) {
+            return tag.len() + attrs + 4;
+        }
+        let children: usize = self.children.iter().map(RsxNode::static_html_len).sum();
+        tag.len() * 2 + attrs + children + 5
+    }
 
-        // Event handler registrations
-        let event_registrations: Vec<TokenStream2> = event_props
-            .iter()
-            .map(|p| {
-                let handler = &p.value;
-                quote! {
-                    let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
-                }
-            })
-            .collect();
+    /// Generate a `Field`: a labelled input bound to a field of a `Form`,
+    /// followed by the field's error message once it has been touched.
+    ///
+    /// `form` and `name` select the field and `label` is optional; all other
+    /// props are passed on to the `<input>`.
+    fn gen_field(&self) -> TokenStream2 {
+        let prop = |name: &str| self.props.iter().find(|p| p.name == name).map(|p| &p.value);
+        let (Some(form), Some(name)) = (prop("form"), prop("name")) else {
+            let missing = if prop("form").is_none() { "form" } else { "name" };
+            return syn::Error::new_spanned(
+                &self.name,
+                format!("Field is missing required prop `{}`", missing),
+            )
+            .to_compile_error();
+        };
+        if let Some(p) = self.props.iter().find(|p| p.name == "oninput") {
+            return syn::Error::new_spanned(
+                &p.name,
+                "Field sets `oninput` itself to update the form; read the value with `form.value(name)`",
+            )
+            .to_compile_error();
+        }
 
-        // data-rid attribute
-        let rid_attr = if !event_props.is_empty() {
-            quote! { __html.push_str(&format!(" data-rid=\"{}\"", __handler_id)); }
-        } else {
-            quote! {}
+        let label = prop("label").map(|label| {
+            quote! {
+                __html.push_str("<label class=\"rinch-field-label\">");
+                __html.push_str(&::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#label)));
+                __html.push_str("</label>");
+            }
+        });
+
+        let input = RsxElement {
+            name: Ident::new("input", self.name.span()),
+            props: self
+                .props
+                .iter()
+                .filter(|p| !matches!(p.name.to_string().as_str(), "form" | "name" | "label"))
+                .map(|p| RsxProp {
+                    name: p.name.clone(),
+                    value: p.value.clone(),
+                })
+                .chain([
+                    RsxProp {
+                        name: Ident::new("name", self.name.span()),
+                        value: syn::parse_quote! { __name },
+                    },
+                    RsxProp {
+                        name: Ident::new("value", self.name.span()),
+                        value: syn::parse_quote! { __form.value(__name) },
+                    },
+                    RsxProp {
+                        name: Ident::new("oninput", self.name.span()),
+                        value: syn::parse_quote! { __form.input_handler(__name) },
+                    },
+                ])
+                .collect(),
+            children: Vec::new(),
         };
+        let input = input.gen_html_statements();
 
-        // Children
-        let children_tokens: Vec<TokenStream2> = self
-            .children
+        quote! {
+            {
+                let __form: &::rinch::core::form::Form = &(#form);
+                let __name: &str = ::std::convert::AsRef::<str>::as_ref(&(#name));
+                let __error = __form.visible_error(__name);
+                let mut __html = String::new();
+                __html.push_str(if __error.is_some() {
+                    "<div class=\"rinch-field rinch-field-invalid\">"
+                } else {
+                    "<div class=\"rinch-field\">"
+                });
+                #label
+                #input
+                if let Some(error) = __error {
+                    __html.push_str("<p class=\"rinch-field-error\">");
+                    __html.push_str(&::rinch::core::events::html_escape_string(&error));
+                    __html.push_str("</p>");
+                }
+                __html.push_str("</div>");
+                __html
+            }
+        }
+    }
+
+    /// Generate a `RichTextEditor`: a formatting toolbar, a textarea editing
+    /// the text of a `RichTextState`, and the formatted document.
+    ///
+    /// `editor` is required and `onchange` is called with the document after
+    /// each edit; all other props are passed on to the `<textarea>`.
+    fn gen_rich_text_editor(&self) -> TokenStream2 {
+        let span = self.name.span();
+        let Some(editor) = self.props.iter().find(|p| p.name == "editor").map(|p| &p.value) else {
+            return syn::Error::new_spanned(&self.name, "RichTextEditor is missing required prop `editor`")
+                .to_compile_error();
+        };
+        if let Some(p) = self
+            .props
             .iter()
-            .map(|c| {
-                let tokens = c.to_html_tokens();
-                quote! { __html.push_str(#tokens); }
-            })
-            .collect();
+            .find(|p| p.name == "oninput" || p.name == "onselect")
+        {
+            return syn::Error::new_spanned(
+                &p.name,
+                format!("RichTextEditor sets `{}` itself; use `onchange` to be told about edits", p.name),
+            )
+            .to_compile_error();
+        }
+        let on_change = match self.props.iter().find(|p| p.name == "onchange") {
+            Some(p) => p.value.to_token_stream(),
+            None => quote! { |_| {} },
+        };
 
-        if is_void_element(&tag) {
-            quote! {
-                &{
-                    #(#event_registrations)*
-                    let mut __html = String::new();
-                    __html.push_str("<");
-                    __html.push_str(#tag);
-                    #( #attr_parts )*
-                    #rid_attr
-                    __html.push_str(" />");
-                    __html
-                }
+        let buttons = [
+            ("Bold", "rinch-rich-text-bold", "B"),
+            ("Italic", "rinch-rich-text-italic", "I"),
+            ("List", "rinch-rich-text-list", "•"),
+        ]
+        .into_iter()
+        .map(|(command, class, label)| {
+            let command = Ident::new(command, span);
+            let tooltip = command.to_string();
+            let active_class = format!("{} rinch-rich-text-active", class);
+            RsxElement {
+                name: Ident::new("button", span),
+                props: vec![
+                    RsxProp {
+                        name: Ident::new("class", span),
+                        value: syn::parse_quote! {
+                            if __editor.is_active(::rinch::core::rich_text::RichTextCommand::#command) {
+                                #active_class
+                            } else {
+                                #class
+                            }
+                        },
+                    },
+                    RsxProp {
+                        name: Ident::new("tooltip", span),
+                        value: syn::parse_quote! { #tooltip },
+                    },
+                    RsxProp {
+                        name: Ident::new("onclick", span),
+                        value: syn::parse_quote! {
+                            __editor.command_handler(
+                                ::rinch::core::rich_text::RichTextCommand::#command,
+                                __on_change.clone(),
+                            )
+                        },
+                    },
+                ],
+                children: vec![RsxNode::Text(LitStr::new(label, span))],
             }
-        } else {
-            quote! {
-                &{
-                    #(#event_registrations)*
-                    let mut __html = String::new();
-                    __html.push_str("<");
-                    __html.push_str(#tag);
-                    #( #attr_parts )*
-                    #rid_attr
-                    __html.push_str(">");
-                    #( #children_tokens )*
-                    __html.push_str("</");
-                    __html.push_str(#tag);
-                    __html.push_str(">");
-                    __html
-                }
+            .gen_html_statements()
+        });
+
+        let mut textarea = RsxElement {
+            name: Ident::new("textarea", span),
+            props: self
+                .props
+                .iter()
+                .filter(|p| !matches!(p.name.to_string().as_str(), "editor" | "onchange"))
+                .map(|p| RsxProp {
+                    name: p.name.clone(),
+                    value: p.value.clone(),
+                })
+                .chain([
+                    RsxProp {
+                        name: Ident::new("oninput", span),
+                        value: syn::parse_quote! { __editor.input_handler(__on_change.clone()) },
+                    },
+                    RsxProp {
+                        name: Ident::new("onselect", span),
+                        value: syn::parse_quote! { __editor.select_handler() },
+                    },
+                ])
+                .collect(),
+            children: vec![RsxNode::Expr(syn::parse_quote! { __editor.text() })],
+        };
+        textarea.add_class("rinch-rich-text-input");
+        let textarea = textarea.gen_html_statements();
+
+        quote! {
+            {
+                let __editor: &::rinch::core::rich_text::RichTextState = &(#editor);
+                let __on_change = ::rinch::core::rich_text::change_handler(#on_change);
+                let mut __html = String::new();
+                __html.push_str("<div class=\"rinch-rich-text\"><div class=\"rinch-rich-text-toolbar\">");
+                #( #buttons )*
+                __html.push_str("</div>");
+                #textarea
+                __html.push_str("<div class=\"rinch-rich-text-view\">");
+                __html.push_str(&__editor.document().to_html());
+                __html.push_str("</div></div>");
+                __html
             }
         }
     }
@@ -626,7 +1144,11 @@ impl RsxElement {
             .map(|p| {
                 let name = p.name.to_string();
                 let value = expr_to_string(&p.value);
-                format!(" {}=\"{}\"", name, html_escape(&value))
+                match (name.as_str(), value.as_str()) {
+                    ("checked", "true") => " checked".to_string(),
+                    ("checked", "false") => String::new(),
+                    _ => format!(" {}=\"{}\"", html_attr_name(&name), html_escape(&value)),
+                }
             })
             .collect();
 
@@ -636,12 +1158,228 @@ impl RsxElement {
         }
 
         // Build children
-        let children: String = self.children.iter().map(|c| node_to_static_html(c)).collect();
+        let children: String = self.children.iter().map(node_to_static_html).collect();
 
         format!("<{}{}>{}</{}>", tag, attrs, children, tag)
     }
 }
 
+/// A `for` loop in RSX, repeating its children for every item:
+/// `for item in items { li { {item} } }`.
+struct RsxFor {
+    pat: syn::Pat,
+    expr: Expr,
+    /// The item's key, from `key: expr` before the children.
+    key: Option<Expr>,
+    children: Vec<RsxNode>,
+}
+
+impl Parse for RsxFor {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![for]>()?;
+        let pat = syn::Pat::parse_multi_with_leading_vert(input)?;
+        input.parse::<Token![in]>()?;
+        let expr = Expr::parse_without_eager_brace(input)?;
+
+        let content;
+        braced!(content in input);
+        let key = if content.peek2(Token![:])
+            && !content.peek2(Token![::])
+            && content.fork().call(Ident::parse_any).is_ok_and(|name| name == "key")
+        {
+            content.call(Ident::parse_any)?;
+            content.parse::<Token![:]>()?;
+            let key: Expr = content.parse()?;
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+            Some(key)
+        } else {
+            None
+        };
+        let children = parse_nodes(&content, "a `for` loop")?;
+        if let Some(key) = &key
+            && children.iter().any(RsxNode::is_rinch_component)
+        {
+            let message = "`key` is for loops of HTML elements; menu items are told apart by `id`";
+            return Err(syn::Error::new_spanned(key, message));
+        }
+        Ok(RsxFor { pat, expr, key, children })
+    }
+}
+
+impl RsxFor {
+    /// A loop appending the HTML of every iteration to `__html`.
+    ///
+    /// In a keyed loop, the top-level elements of every item get the item's
+    /// key as `data-rkey`.
+    fn gen_html_statements(&self, bind_text: bool) -> TokenStream2 {
+        let (pat, expr) = (&self.pat, &self.expr);
+        let children = self.children.iter().map(|c| {
+            let statements = c.gen_child_html_statements(bind_text);
+            match (&self.key, c) {
+                (Some(_), RsxNode::Element(_)) => quote! {
+                    let __item_start = __html.len();
+                    #statements
+                    ::rinch::core::events::key_element(&mut __html, __item_start, &__key);
+                },
+                _ => statements,
+            }
+        });
+        let key = self.key.as_ref().map(|key| quote! { let __key = ::std::string::ToString::to_string(&(#key)); });
+        quote! {
+            for #pat in #expr {
+                #key
+                #( #children )*
+            }
+        }
+    }
+
+    /// A loop adding the elements of every iteration to `__children`.
+    fn gen_element_statements(&self) -> TokenStream2 {
+        let (pat, expr) = (&self.pat, &self.expr);
+        let elements = gen_elements(&self.children);
+        quote! {
+            for #pat in #expr {
+                __children.extend(#elements);
+            }
+        }
+    }
+
+    fn to_element(&self) -> TokenStream2 {
+        if self.children.iter().any(RsxNode::is_rinch_component) {
+            let statements = self.gen_element_statements();
+            quote! {
+                Element::Fragment({
+                    let mut __children = ::std::vec::Vec::new();
+                    #statements
+                    __children
+                })
+            }
+        } else {
+            let statements = self.gen_html_statements(false);
+            quote! {
+                Element::Html(::std::borrow::Cow::Owned({
+                    let mut __html = String::new();
+                    #statements
+                    __html
+                }))
+            }
+        }
+    }
+}
+
+/// An `if` block in RSX, with an optional `else` or `else if`:
+/// `if logged_in { Profile {} } else { button { "Log in" } }`.
+struct RsxIf {
+    cond: Expr,
+    then: Vec<RsxNode>,
+    /// The `else` branch; an `else if` is a single `If` node.
+    otherwise: Vec<RsxNode>,
+}
+
+impl Parse for RsxIf {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![if]>()?;
+        let cond = Expr::parse_without_eager_brace(input)?;
+        let then = parse_children(input, "an `if` block")?;
+
+        let otherwise = if !input.peek(Token![else]) {
+            Vec::new()
+        } else {
+            input.parse::<Token![else]>()?;
+            if input.peek(Token![if]) {
+                vec![RsxNode::If(Box::new(input.parse()?))]
+            } else {
+                parse_children(input, "an `else` block")?
+            }
+        };
+
+        Ok(RsxIf { cond, then, otherwise })
+    }
+}
+
+impl RsxIf {
+    /// The nodes of both branches.
+    fn nodes(&self) -> impl Iterator<Item = &RsxNode> {
+        self.then.iter().chain(&self.otherwise)
+    }
+
+    /// An `if` appending the HTML of the chosen branch to `__html`.
+    fn gen_html_statements(&self, bind_text: bool) -> TokenStream2 {
+        let cond = &self.cond;
+        let then: Vec<TokenStream2> = self.then.iter().map(|c| c.gen_child_html_statements(bind_text)).collect();
+        if self.otherwise.is_empty() {
+            return quote! {
+                if #cond {
+                    #( #then )*
+                }
+            };
+        }
+        let otherwise: Vec<TokenStream2> =
+            self.otherwise.iter().map(|c| c.gen_child_html_statements(bind_text)).collect();
+        quote! {
+            if #cond {
+                #( #then )*
+            } else {
+                #( #otherwise )*
+            }
+        }
+    }
+
+    /// The elements of the chosen branch.
+    fn gen_elements(&self) -> TokenStream2 {
+        let cond = &self.cond;
+        let then = gen_elements(&self.then);
+        let otherwise = gen_elements(&self.otherwise);
+        quote! {
+            if #cond { #then } else { #otherwise }
+        }
+    }
+
+    fn to_element(&self) -> TokenStream2 {
+        if self.nodes().any(RsxNode::is_rinch_component) {
+            let elements = self.gen_elements();
+            quote! { Element::Fragment(#elements) }
+        } else {
+            let statements = self.gen_html_statements(false);
+            quote! {
+                Element::Html(::std::borrow::Cow::Owned({
+                    let mut __html = String::new();
+                    #statements
+                    __html
+                }))
+            }
+        }
+    }
+}
+
+/// Parse the braced children of a `for` loop or `if` block, which can't
+/// have props.
+fn parse_children(input: ParseStream, what: &str) -> Result<Vec<RsxNode>> {
+    let content;
+    braced!(content in input);
+    parse_nodes(&content, what)
+}
+
+/// Parse the rest of the children of a `for` loop or `if` block, inside its
+/// braces.
+fn parse_nodes(content: ParseStream, what: &str) -> Result<Vec<RsxNode>> {
+    let mut children = Vec::new();
+    while !content.is_empty() {
+        if content.peek(Ident::peek_any) && content.peek2(Token![:]) && !content.peek2(Token![::]) {
+            return Err(content.error(format!("{} has no props; put them on an element inside it", what)));
+        }
+        children.push(content.parse()?);
+
+        // Consume trailing comma if present
+        if content.peek(Token![,]) {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(children)
+}
+
 /// A property in RSX (name: value).
 struct RsxProp {
     name: Ident,
@@ -650,13 +1388,276 @@ struct RsxProp {
 
 impl Parse for RsxProp {
     fn parse(input: ParseStream) -> Result<Self> {
-        let name: Ident = input.parse()?;
+        let name = Ident::parse_any(input)?.unraw();
         input.parse::<Token![:]>()?;
         let value: Expr = input.parse()?;
         Ok(RsxProp { name, value })
     }
 }
 
+/// Generate the `Vec<Element>` of a list of child nodes.
+///
+/// Runs of HTML children are combined into one HTML string when possible.
+/// `for` loops and `if` blocks over components add their elements to the
+/// list itself, so a loop of `MenuItem`s inside a `Menu` becomes the menu's
+/// items.
+fn gen_elements(children: &[RsxNode]) -> TokenStream2 {
+    if children.is_empty() {
+        return quote! { vec![] };
+    }
+
+    // Check if all children are HTML elements (can be combined into one HTML string)
+    let all_html = children.iter().all(|c| !c.is_rinch_component());
+
+    if all_html {
+        // Check if we need dynamic HTML generation
+        let has_dynamic = children.iter().any(|c| c.has_dynamic_content());
+
+        if has_dynamic {
+            // Generate runtime HTML building
+            let html_parts: Vec<TokenStream2> = children.iter().map(RsxNode::gen_html_statements).collect();
+            let capacity: usize = children.iter().map(RsxNode::static_html_len).sum();
+
+            quote! {
+                vec![Element::Html(::std::borrow::Cow::Owned({
+                    let mut __html = String::with_capacity(#capacity);
+                    #( #html_parts )*
+                    __html
+                }))]
+            }
+        } else {
+            // Static HTML string
+            let html: String = children.iter().map(node_to_static_html).collect();
+            let element = static_html_element(&html);
+            quote! { vec![#element] }
+        }
+    } else {
+        // Mix of components and HTML - generate each separately
+        let element = |c: &RsxNode| {
+            if c.is_rinch_component() {
+                c.to_element()
+            } else if c.has_dynamic_content() {
+                let html_parts = c.gen_html_statements();
+                let capacity = c.static_html_len();
+                quote! {
+                    Element::Html(::std::borrow::Cow::Owned({
+                        let mut __html = String::with_capacity(#capacity);
+                        #html_parts
+                        __html
+                    }))
+                }
+            } else {
+                let html = node_to_static_html(c);
+                static_html_element(&html)
+            }
+        };
+
+        let spliced = children
+            .iter()
+            .any(|c| matches!(c, RsxNode::For(_) | RsxNode::If(_)) && c.is_rinch_component());
+        if !spliced {
+            let children: Vec<TokenStream2> = children.iter().map(element).collect();
+            return quote! { vec![#(#children),*] };
+        }
+
+        // Loops and `if` blocks add any number of elements
+        let statements = children.iter().map(|c| match c {
+            RsxNode::For(each) if c.is_rinch_component() => each.gen_element_statements(),
+            RsxNode::If(branch) if c.is_rinch_component() => {
+                let elements = branch.gen_elements();
+                quote! { __children.extend(#elements); }
+            }
+            _ => {
+                let element = element(c);
+                quote! { __children.push(#element); }
+            }
+        });
+        quote! {
+            {
+                let mut __children = ::std::vec::Vec::new();
+                #( #statements )*
+                __children
+            }
+        }
+    }
+}
+
+/// Generate a `MenuItemCallback` from an `onclick` value.
+///
+/// Closures taking one argument receive the `MenuActivation`; anything else
+/// is treated as a zero-argument callback.
+fn gen_menu_callback(value: &Expr) -> TokenStream2 {
+    match value {
+        Expr::Closure(closure) if closure.inputs.len() == 1 => {
+            quote! { Some(MenuItemCallback::with_activation(#value)) }
+        }
+        _ => quote! { Some(MenuItemCallback::new(#value)) },
+    }
+}
+
+/// Generate a `TrayClickCallback` from an `onclick` or `ondoubleclick` value.
+///
+/// Closures taking one argument receive the `TrayClick`; anything else
+/// is treated as a zero-argument callback.
+fn gen_tray_click_callback(value: &Expr) -> TokenStream2 {
+    match value {
+        Expr::Closure(closure) if closure.inputs.len() == 1 => {
+            quote! { Some(TrayClickCallback::with_click(#value)) }
+        }
+        _ => quote! { Some(TrayClickCallback::new(#value)) },
+    }
+}
+
+/// Generate the registration of an element's event handlers, and the
+/// attributes that connect the element to them.
+///
+/// `oninput` handlers receive the element's new value and are referenced by
+/// `data-oninput`. `onchange` handlers receive a checkbox or radio button's
+/// new state and are referenced by `data-onchange`; a dynamic `checked` value
+/// is passed to the same handler so a `Signal<bool>` stays in sync. All other
+/// handlers are click handlers referenced by `data-rid`.
+fn gen_event_bindings(
+    event_props: &[&RsxProp],
+    checked: Option<&RsxProp>,
+) -> (Vec<TokenStream2>, Vec<TokenStream2>) {
+    let mut registrations = Vec::new();
+    let mut attrs = Vec::new();
+
+    let (input_props, other_props): (Vec<&RsxProp>, Vec<&RsxProp>) =
+        event_props.iter().partition(|p| p.name == "oninput");
+    let (change_props, other_props): (Vec<&RsxProp>, Vec<&RsxProp>) =
+        other_props.into_iter().partition(|p| p.name == "onchange");
+    let (select_props, other_props): (Vec<&RsxProp>, Vec<&RsxProp>) =
+        other_props.into_iter().partition(|p| p.name == "onselect");
+    let (reorder_props, click_props): (Vec<&RsxProp>, Vec<&RsxProp>) =
+        other_props.into_iter().partition(|p| p.name == "onreorder");
+
+    for prop in &click_props {
+        let handler = &prop.value;
+        registrations.push(quote! {
+            let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
+        });
+    }
+    if !click_props.is_empty() {
+        attrs.push(quote! { &format!(" data-rid=\"{}\"", __handler_id) });
+    }
+
+    for prop in &input_props {
+        let handler = &prop.value;
+        registrations.push(quote! {
+            let __input_handler_id = ::rinch::core::register_input_handler(#handler);
+        });
+    }
+    if !input_props.is_empty() {
+        attrs.push(quote! { &format!(" data-oninput=\"{}\"", __input_handler_id) });
+    }
+
+    for prop in &select_props {
+        let handler = &prop.value;
+        registrations.push(quote! {
+            let __select_handler_id = ::rinch::core::register_select_handler(#handler);
+        });
+    }
+    if !select_props.is_empty() {
+        attrs.push(quote! { &format!(" data-onselect=\"{}\"", __select_handler_id) });
+    }
+
+    for prop in &reorder_props {
+        let handler = &prop.value;
+        registrations.push(quote! {
+            let __reorder_handler_id = ::rinch::core::register_reorder_handler(#handler);
+        });
+    }
+    if !reorder_props.is_empty() {
+        attrs.push(quote! { &format!(" data-onreorder=\"{}\"", __reorder_handler_id) });
+    }
+
+    let binding = checked
+        .filter(|p| !is_literal_expr(&p.value))
+        .map(|p| &p.value);
+    if binding.is_some() || !change_props.is_empty() {
+        let binding = match binding {
+            Some(value) => quote! { ::rinch::core::CheckedValue::binding(&(#value)) },
+            None => quote! { ::std::option::Option::<::rinch::core::Signal<bool>>::None },
+        };
+        let handler = match change_props.first() {
+            Some(p) => p.value.to_token_stream(),
+            None => quote! { |_| {} },
+        };
+        registrations.push(quote! {
+            let __change_handler_id = ::rinch::core::register_change_handler(
+                ::rinch::core::events::bind_checked(#binding, #handler),
+            );
+        });
+        attrs.push(quote! { &format!(" data-onchange=\"{}\"", __change_handler_id) });
+    }
+
+    (registrations, attrs)
+}
+
+/// Generate the `checked` attribute, which is present only when checked.
+fn gen_checked_attr(value: &Expr) -> TokenStream2 {
+    match value {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. }) => {
+            let attr = if b.value { " checked" } else { "" };
+            quote! { #attr }
+        }
+        Expr::Lit(_) => {
+            let attr = format!(" checked=\"{}\"", html_escape(&expr_to_string(value)));
+            quote! { #attr }
+        }
+        _ => quote! {
+            if ::rinch::core::CheckedValue::is_checked(&(#value)) { " checked" } else { "" }
+        },
+    }
+}
+
+/// The receiver of an attribute value written as `{signal.get()}`, which is
+/// bound to the attribute rather than read by the render if it's a
+/// `Bindable` signal or memo.
+fn bound_signal(value: &Expr) -> Option<&Ident> {
+    let Expr::Block(block) = value else {
+        return None;
+    };
+    let [syn::Stmt::Expr(expr, None)] = block.block.stmts.as_slice() else {
+        return None;
+    };
+    signal_get(expr)
+}
+
+/// The receiver of an expression written as `signal.get()`, which may or
+/// may not be a signal.
+fn signal_get(expr: &Expr) -> Option<&Ident> {
+    let Expr::MethodCall(call) = expr else {
+        return None;
+    };
+    if call.method != "get" || !call.args.is_empty() || call.turbofish.is_some() {
+        return None;
+    }
+    match &*call.receiver {
+        Expr::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// The HTML attribute a prop is rendered as.
+///
+/// Most props are attributes of the same name; `tooltip` is stored in a data
+/// attribute that the window reads when the element is hovered, `layer` and
+/// `layer_order` in data attributes that move the element into the window's
+/// overlay, `pointer_events` in one the window's hit testing reads, and ARIA
+/// attributes are written with underscores (`aria_label` is `aria-label`).
+fn html_attr_name(prop: &str) -> String {
+    match prop {
+        "tooltip" => "data-rinch-tooltip".to_string(),
+        "layer" => "data-rinch-layer".to_string(),
+        "layer_order" => "data-rinch-layer-order".to_string(),
+        "pointer_events" => "data-rinch-pointer-events".to_string(),
+        aria if is_aria_prop(aria) => aria.replace('_', "-"),
+        other => other.to_string(),
+    }
+}
+
 /// Check if a property name is an event handler.
 fn is_event_prop(name: &str) -> bool {
     name.starts_with("on")
@@ -689,6 +1690,12 @@ fn html_escape(s: &str) -> String {
         .replace('"', "&quot;")
 }
 
+/// Whether an element's content is text rather than HTML, so bound text
+/// can't be delimited with comments.
+fn is_raw_text_element(tag: &str) -> bool {
+    matches!(tag, "textarea" | "title" | "style" | "script" | "option")
+}
+
 /// Check if an HTML tag is a void element (self-closing).
 fn is_void_element(tag: &str) -> bool {
     matches!(
@@ -698,11 +1705,246 @@ fn is_void_element(tag: &str) -> bool {
     )
 }
 
+/// An element for HTML known at compile time, borrowed from the binary
+/// instead of allocated on every render.
+fn static_html_element(html: &str) -> TokenStream2 {
+    quote! {
+        Element::Html(::std::borrow::Cow::Borrowed(#html))
+    }
+}
+
 /// Convert an RSX node to static HTML (for compile-time generation).
 fn node_to_static_html(node: &RsxNode) -> String {
     match node {
         RsxNode::Element(el) => el.to_static_html(),
         RsxNode::Text(lit) => html_escape(&lit.value()),
-        RsxNode::Expr(_) => String::new(), // Expressions can't be static
+        RsxNode::Expr(_) | RsxNode::For(_) | RsxNode::If(_) => String::new(), // Expressions can't be static
+        RsxNode::Css(css) => style_html(css),
+    }
+}
+
+/// Wrap CSS in a `<style>` element.
+///
+/// Style contents are raw text, so the CSS is not escaped; only a closing
+/// tag inside it is broken up.
+fn style_html(css: &str) -> String {
+    format!("<style>{}</style>", css.replace("</", "<\\/"))
+}
+
+/// Scope the styles of `css!` blocks to the HTML generated by this `rsx!`.
+///
+/// Every HTML element in the tree gets a scope class derived from the CSS,
+/// and the selectors of every `css!` block are rewritten to require it.
+/// Turn `Sortable` elements into the `div.rinch-sortable` they render as.
+///
+/// The `onreorder` handler is required; the window handles dragging the
+/// element's children.
+fn expand_sortables(node: &mut RsxNode) -> Result<()> {
+    let children: Vec<&mut RsxNode> = match node {
+        RsxNode::Element(el) => {
+            if el.name == "Sortable" {
+                if !el.props.iter().any(|p| p.name == "onreorder") {
+                    return Err(syn::Error::new_spanned(
+                        &el.name,
+                        "Sortable is missing required prop `onreorder`",
+                    ));
+                }
+                el.name = Ident::new("div", el.name.span());
+                el.add_class("rinch-sortable");
+            }
+            el.children.iter_mut().collect()
+        }
+        RsxNode::For(each) => each.children.iter_mut().collect(),
+        RsxNode::If(branch) => branch.then.iter_mut().chain(&mut branch.otherwise).collect(),
+        _ => return Ok(()),
+    };
+    for child in children {
+        expand_sortables(child)?;
+    }
+    Ok(())
+}
+
+fn apply_scoped_styles(node: &mut RsxNode) {
+    let mut sources = Vec::new();
+    collect_css(node, &mut sources);
+    if sources.is_empty() {
+        return;
+    }
+    let scope = scoped_css::scope_class(&sources.concat());
+    scope_node(node, &scope);
+}
+
+fn collect_css(node: &RsxNode, sources: &mut Vec<String>) {
+    match node {
+        RsxNode::Css(css) => sources.push(css.clone()),
+        RsxNode::Element(el) => {
+            for child in &el.children {
+                collect_css(child, sources);
+            }
+        }
+        RsxNode::For(each) => {
+            for child in &each.children {
+                collect_css(child, sources);
+            }
+        }
+        RsxNode::If(branch) => {
+            for child in branch.nodes() {
+                collect_css(child, sources);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn scope_node(node: &mut RsxNode, scope: &str) {
+    match node {
+        RsxNode::Css(css) => *css = scoped_css::scope_css(css, scope),
+        RsxNode::Element(el) => {
+            if !el.is_rinch_component() && !is_unstyled_element(&el.name.to_string()) {
+                el.add_class(scope);
+            }
+            for child in &mut el.children {
+                scope_node(child, scope);
+            }
+        }
+        RsxNode::For(each) => {
+            for child in &mut each.children {
+                scope_node(child, scope);
+            }
+        }
+        RsxNode::If(branch) => {
+            for child in branch.then.iter_mut().chain(&mut branch.otherwise) {
+                scope_node(child, scope);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check if an HTML tag is never rendered, so it doesn't need a scope class.
+fn is_unstyled_element(tag: &str) -> bool {
+    matches!(
+        tag,
+        "head" | "title" | "style" | "script" | "meta" | "link" | "base"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html_statements(rsx: &str) -> String {
+        syn::parse_str::<RsxNode>(rsx).unwrap().gen_html_statements().to_string()
+    }
+
+    #[test]
+    fn text_read_with_get_is_bound_in_its_parent() {
+        let code = html_statements("p { \"Count: \" {count.get()} }");
+        assert!(code.contains("BindProbe"));
+        assert!(code.contains("data-rbind-text"));
+        assert!(code.contains("<!--rbind:"));
+        assert!(!code.contains("rinch-text"));
+
+        // Loops and `if`s write into the element around them
+        let code = html_statements("ul { for item in items { li { {item.get()} } } }");
+        assert_eq!(code.matches("__text_bindings = String :: new ()").count(), 1);
+        let code = html_statements("p { if on { {count.get()} } }");
+        assert!(code.contains("data-rbind-text"));
+    }
+
+    #[test]
+    fn raw_text_is_not_bound() {
+        let code = html_statements("textarea { {text.get()} }");
+        assert!(!code.contains("BindProbe"));
+        assert!(!code.contains("__text_bindings"));
+        // Nor is text without an element around it
+        let code = html_statements("for item in items { {item.get()} }");
+        assert!(!code.contains("BindProbe"));
+    }
+
+    #[test]
+    fn attribute_read_with_get_is_bound() {
+        let code = html_statements("div { class: {class.get()} }");
+        assert!(code.contains("bind_attribute"));
+        assert!(code.contains("data-rbind-class"));
+        let code = html_statements("div { class: class.get() }");
+        assert!(!code.contains("BindProbe"));
+    }
+
+    fn element(rsx: &str) -> String {
+        syn::parse_str::<RsxNode>(rsx).unwrap().to_element().to_string()
+    }
+
+    #[test]
+    fn for_loop_parses_pattern_and_children() {
+        let node = syn::parse_str("for (index, name) in names.iter().enumerate() { li { {name} } }").unwrap();
+        let RsxNode::For(each) = node else { panic!("not a loop") };
+        assert!(matches!(each.pat, syn::Pat::Tuple(_)));
+        assert_eq!(each.children.len(), 1);
+        assert!(syn::parse_str::<RsxNode>("for item in items li {}").is_err());
+    }
+
+    #[test]
+    fn for_loop_generates_html_or_elements() {
+        let code = element("for item in items { li { {item} } }");
+        assert!(code.starts_with("Element :: Html"));
+        assert!(code.contains("for item in items"));
+
+        let code = element("for title in titles { MenuItem { label: title } }");
+        assert!(code.starts_with("Element :: Fragment"));
+        assert!(code.contains("__children . extend"));
+    }
+
+    #[test]
+    fn if_parses_else_if_as_a_nested_if() {
+        let RsxNode::If(branch) = syn::parse_str("if a { p {} } else if b { span {} } else { div {} }").unwrap() else {
+            panic!("not an if")
+        };
+        assert_eq!(branch.then.len(), 1);
+        let [RsxNode::If(nested)] = branch.otherwise.as_slice() else { panic!("`else if` is not a single if") };
+        assert_eq!(nested.otherwise.len(), 1);
+
+        let RsxNode::If(branch) = syn::parse_str("if let Some(name) = user { p { {name} } }").unwrap() else {
+            panic!("not an if")
+        };
+        assert!(matches!(branch.cond, Expr::Let(_)));
+        assert!(branch.otherwise.is_empty());
+    }
+
+    #[test]
+    fn if_generates_html_or_elements() {
+        let code = element("if let Some(name) = user { p { {name} } } else if guest { p { \"Guest\" } }");
+        assert!(code.starts_with("Element :: Html"));
+        assert!(code.contains("if let Some (name) = user"));
+        assert!(code.contains("else { if guest"));
+
+        let code = element("if native { MenuItem { label: \"Quit\" } }");
+        assert!(code.starts_with("Element :: Fragment"));
+        assert!(code.contains("else { vec ! []"));
+    }
+
+    #[test]
+    fn keyed_loop_keys_its_elements() {
+        let RsxNode::For(each) = syn::parse_str("for item in items { key: item.id, li { {item.name} } }").unwrap() else {
+            panic!("not a loop")
+        };
+        assert!(each.key.is_some());
+        assert_eq!(each.children.len(), 1);
+
+        let code = html_statements("ul { for item in items { key: item.id, li { {item.name} } \"text\" } }");
+        assert!(code.contains("let __key = :: std :: string :: ToString :: to_string (& (item . id))"));
+        // Only the element, not the text
+        assert_eq!(code.matches("key_element").count(), 1);
+        assert!(!html_statements("for item in items { li {} }").contains("key_element"));
+    }
+
+    #[test]
+    fn loop_props_are_rejected() {
+        let error = syn::parse_str::<RsxNode>("for item in items { class: \"item\", li {} }").err().unwrap();
+        assert!(error.to_string().contains("has no props"));
+        let error = syn::parse_str::<RsxNode>("for item in items { li {}, key: item.id }").err().unwrap();
+        assert!(error.to_string().contains("has no props"));
+        let error = syn::parse_str::<RsxNode>("for title in titles { key: title, MenuItem { label: title } }").err().unwrap();
+        assert!(error.to_string().contains("menu items"));
     }
 }
@@ -2,6 +2,9 @@
 //!
 //! Defines which properties are valid for each component type,
 //! enabling compile-time validation and helpful error messages.
+//!
+//! HTML elements accept any property, except that ARIA attributes
+//! (`aria_*` props) and `role` values are checked against WAI-ARIA 1.2.
 
 /// A property schema describing a component's valid properties.
 #[derive(Debug, Clone, Copy)]
@@ -36,8 +39,14 @@ static WINDOW_PROPS: &[PropSchema] = &[
     PropSchema::optional("borderless"),
     PropSchema::optional("resizable"),
     PropSchema::optional("transparent"),
+    PropSchema::optional("shadow"),
     PropSchema::optional("always_on_top"),
     PropSchema::optional("visible"),
+    PropSchema::optional("color_scheme"),
+    PropSchema::optional("dir"),
+    PropSchema::optional("key"),
+    PropSchema::optional("src"),
+    PropSchema::optional("scale"),
 ];
 
 /// AppMenu component properties.
@@ -48,11 +57,167 @@ static MENU_PROPS: &[PropSchema] = &[PropSchema::required("label")];
 
 /// MenuItem component properties.
 static MENU_ITEM_PROPS: &[PropSchema] = &[
+    PropSchema::optional("id"),
     PropSchema::required("label"),
     PropSchema::optional("shortcut"),
     PropSchema::optional("enabled"),
     PropSchema::optional("checked"),
     PropSchema::optional("onclick"),
+    PropSchema::optional("role"),
+];
+
+/// Values of the MenuItem `role` property.
+static MENU_ROLES: &[&str] = &["undo", "redo", "cut", "copy", "paste", "select_all"];
+
+/// RecentFilesMenu component properties.
+static RECENT_FILES_MENU_PROPS: &[PropSchema] = &[
+    PropSchema::optional("label"),
+    PropSchema::optional("clear_label"),
+    PropSchema::optional("onselect"),
+];
+
+/// Tray component properties.
+static TRAY_PROPS: &[PropSchema] = &[
+    PropSchema::optional("tooltip"),
+    PropSchema::optional("icon"),
+    PropSchema::optional("onclick"),
+    PropSchema::optional("ondoubleclick"),
+];
+
+/// ARIA attributes, as RSX props (`aria_label` is rendered as `aria-label`).
+static ARIA_PROPS: &[&str] = &[
+    "aria_activedescendant",
+    "aria_atomic",
+    "aria_autocomplete",
+    "aria_busy",
+    "aria_checked",
+    "aria_colcount",
+    "aria_colindex",
+    "aria_colspan",
+    "aria_controls",
+    "aria_current",
+    "aria_describedby",
+    "aria_description",
+    "aria_details",
+    "aria_disabled",
+    "aria_errormessage",
+    "aria_expanded",
+    "aria_flowto",
+    "aria_haspopup",
+    "aria_hidden",
+    "aria_invalid",
+    "aria_keyshortcuts",
+    "aria_label",
+    "aria_labelledby",
+    "aria_level",
+    "aria_live",
+    "aria_modal",
+    "aria_multiline",
+    "aria_multiselectable",
+    "aria_orientation",
+    "aria_owns",
+    "aria_placeholder",
+    "aria_posinset",
+    "aria_pressed",
+    "aria_readonly",
+    "aria_relevant",
+    "aria_required",
+    "aria_roledescription",
+    "aria_rowcount",
+    "aria_rowindex",
+    "aria_rowspan",
+    "aria_selected",
+    "aria_setsize",
+    "aria_sort",
+    "aria_valuemax",
+    "aria_valuemin",
+    "aria_valuenow",
+    "aria_valuetext",
+];
+
+/// Values of the `role` attribute.
+static ARIA_ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "application",
+    "article",
+    "banner",
+    "blockquote",
+    "button",
+    "caption",
+    "cell",
+    "checkbox",
+    "code",
+    "columnheader",
+    "combobox",
+    "complementary",
+    "contentinfo",
+    "definition",
+    "deletion",
+    "dialog",
+    "document",
+    "emphasis",
+    "feed",
+    "figure",
+    "form",
+    "generic",
+    "grid",
+    "gridcell",
+    "group",
+    "heading",
+    "img",
+    "insertion",
+    "link",
+    "list",
+    "listbox",
+    "listitem",
+    "log",
+    "main",
+    "marquee",
+    "math",
+    "menu",
+    "menubar",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "meter",
+    "navigation",
+    "none",
+    "note",
+    "option",
+    "paragraph",
+    "presentation",
+    "progressbar",
+    "radio",
+    "radiogroup",
+    "region",
+    "row",
+    "rowgroup",
+    "rowheader",
+    "scrollbar",
+    "search",
+    "searchbox",
+    "separator",
+    "slider",
+    "spinbutton",
+    "status",
+    "strong",
+    "subscript",
+    "superscript",
+    "switch",
+    "tab",
+    "table",
+    "tablist",
+    "tabpanel",
+    "term",
+    "textbox",
+    "time",
+    "timer",
+    "toolbar",
+    "tooltip",
+    "tree",
+    "treegrid",
+    "treeitem",
 ];
 
 /// Get valid property names for a component.
@@ -62,6 +227,8 @@ pub fn get_valid_props(component: &str) -> Option<&'static [PropSchema]> {
         "AppMenu" => Some(APP_MENU_PROPS),
         "Menu" => Some(MENU_PROPS),
         "MenuItem" => Some(MENU_ITEM_PROPS),
+        "RecentFilesMenu" => Some(RECENT_FILES_MENU_PROPS),
+        "Tray" => Some(TRAY_PROPS),
         _ => None,
     }
 }
@@ -86,9 +253,76 @@ pub fn is_valid_prop(component: &str, prop_name: &str) -> bool {
         .unwrap_or(true) // Unknown components allow any props
 }
 
+/// Check if a property of an HTML element is an ARIA attribute.
+pub fn is_aria_prop(prop_name: &str) -> bool {
+    prop_name.starts_with("aria_")
+}
+
+/// Check if an `aria_*` property is a known ARIA attribute.
+pub fn is_valid_aria_prop(prop_name: &str) -> bool {
+    ARIA_PROPS.contains(&prop_name)
+}
+
+/// Get all ARIA attribute property names.
+pub fn get_aria_prop_names() -> &'static [&'static str] {
+    ARIA_PROPS
+}
+
+/// Check if a `role` value is a known ARIA role.
+pub fn is_valid_role(role: &str) -> bool {
+    ARIA_ROLES.contains(&role)
+}
+
+/// Get all ARIA role names.
+pub fn get_role_names() -> &'static [&'static str] {
+    ARIA_ROLES
+}
+
+/// Check if a MenuItem `role` value is a known editing command.
+pub fn is_valid_menu_role(role: &str) -> bool {
+    MENU_ROLES.contains(&role)
+}
+
+/// Get all MenuItem role names.
+pub fn get_menu_role_names() -> &'static [&'static str] {
+    MENU_ROLES
+}
+
 /// Get all valid property names for a component.
 pub fn get_prop_names(component: &str) -> Vec<&'static str> {
     get_valid_props(component)
         .map(|props| props.iter().map(|p| p.name).collect())
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aria_props() {
+        assert!(is_aria_prop("aria_label"));
+        assert!(!is_aria_prop("label"));
+        assert!(is_valid_aria_prop("aria_label"));
+        assert!(is_valid_aria_prop("aria_labelledby"));
+        assert!(!is_valid_aria_prop("aria_labeledby"));
+    }
+
+    #[test]
+    fn test_roles() {
+        assert!(is_valid_role("menuitem"));
+        assert!(is_valid_role("presentation"));
+        assert!(!is_valid_role("menu_item"));
+        assert!(!is_valid_role("Button"));
+    }
+
+    #[test]
+    fn test_menu_roles() {
+        assert!(is_valid_prop("MenuItem", "role"));
+        assert!(is_valid_menu_role("copy"));
+        assert!(is_valid_menu_role("redo"));
+        assert!(is_valid_menu_role("select_all"));
+        assert!(!is_valid_menu_role("selectAll"));
+        assert!(!is_valid_menu_role("menuitem"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Scoped styles for `css!` blocks.
+//!
+//! Selectors in a `css!` block are rewritten at compile time so they only
+//! match elements carrying a scope class, which `rsx!` adds to every HTML
+//! element generated by the same invocation. Styles written for one
+//! component therefore can't leak into the rest of the document.
+
+/// Prefix of generated scope class names.
+const SCOPE_PREFIX: &str = "rinch-s-";
+
+/// At-rules whose blocks contain style rules that need scoping.
+///
+/// Other at-rules (`@keyframes`, `@font-face`, ...) are copied unchanged.
+const NESTING_AT_RULES: &[&str] = &["@media", "@supports", "@container", "@layer"];
+
+/// Derive a scope class name from the CSS source.
+///
+/// Uses FNV-1a so the name is the same on every build.
+pub fn scope_class(css: &str) -> String {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in css.bytes() {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    format!("{}{:08x}", SCOPE_PREFIX, hash)
+}
+
+/// Rewrite every selector in `css` to require the `scope` class.
+///
+/// The class is added to the last compound selector, so `.card p` becomes
+/// `.card p.scope`. A selector wrapped in `:global(...)` is left unscoped.
+pub fn scope_css(css: &str, scope: &str) -> String {
+    let css = strip_comments(css);
+    let mut out = String::with_capacity(css.len() + 64);
+    scope_rules(&css, scope, &mut out);
+    out
+}
+
+/// Scope a sequence of rules, recursing into nesting at-rules.
+fn scope_rules(css: &str, scope: &str, out: &mut String) {
+    let mut rest = css;
+    while let Some(end) = scan(rest, |_, c, depth| depth == 0 && (c == '{' || c == ';')) {
+        // Statement at-rules such as `@import url(...);`
+        if rest[end..].starts_with(';') {
+            out.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        let prelude = &rest[..end];
+        let body_end = matching_brace(rest, end);
+        let body = &rest[end + 1..body_end];
+        let selector = prelude.trim();
+
+        out.push_str(&prelude[..prelude.len() - prelude.trim_start().len()]);
+        if selector.starts_with('@') {
+            out.push_str(selector);
+            out.push_str(" {");
+            let name = selector
+                .split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if NESTING_AT_RULES.contains(&name.as_str()) {
+                scope_rules(body, scope, out);
+            } else {
+                out.push_str(body);
+            }
+        } else {
+            let selectors: Vec<String> = split_selectors(selector)
+                .into_iter()
+                .map(|s| scope_selector(s, scope))
+                .collect();
+            out.push_str(&selectors.join(", "));
+            out.push_str(" {");
+            out.push_str(body);
+        }
+        out.push('}');
+
+        rest = rest.get(body_end + 1..).unwrap_or_default();
+    }
+    out.push_str(rest);
+}
+
+/// Add the scope class to a single selector.
+fn scope_selector(selector: &str, scope: &str) -> String {
+    let selector = selector.trim();
+    if let Some(inner) = selector
+        .strip_prefix(":global(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        return inner.trim().to_string();
+    }
+
+    // The last compound selector starts after the last top-level combinator
+    let mut compound_start = 0;
+    scan(selector, |i, c, depth| {
+        if depth == 0 && (c.is_whitespace() || matches!(c, '>' | '+' | '~')) {
+            compound_start = i + c.len_utf8();
+        }
+        false
+    });
+
+    // The class has to come before any pseudo-element
+    let compound = &selector[compound_start..];
+    let insert_at = scan(compound, |i, c, depth| {
+        depth == 0 && c == ':' && compound[i + 1..].starts_with(':')
+    })
+    .map_or(selector.len(), |i| compound_start + i);
+
+    format!(
+        "{}.{}{}",
+        &selector[..insert_at],
+        scope,
+        &selector[insert_at..]
+    )
+}
+
+/// Split a selector list on its top-level commas.
+fn split_selectors(list: &str) -> Vec<&str> {
+    let mut selectors = Vec::new();
+    let mut start = 0;
+    scan(list, |i, c, depth| {
+        if depth == 0 && c == ',' {
+            selectors.push(&list[start..i]);
+            start = i + 1;
+        }
+        false
+    });
+    selectors.push(&list[start..]);
+    selectors
+        .into_iter()
+        .filter(|s| !s.trim().is_empty())
+        .collect()
+}
+
+/// Find the `}` closing the block opened at `open`, or the end of the input.
+fn matching_brace(css: &str, open: usize) -> usize {
+    let mut braces = 0usize;
+    scan(&css[open..], |_, c, _| {
+        match c {
+            '{' => braces += 1,
+            '}' => braces -= 1,
+            _ => {}
+        }
+        braces == 0
+    })
+    .map_or(css.len(), |i| open + i)
+}
+
+/// Call `f` with the byte index, character and `()`/`[]` nesting depth of
+/// every character outside quoted strings, until it returns `true`.
+///
+/// Returns the index at which `f` returned `true`.
+fn scan(s: &str, mut f: impl FnMut(usize, char, usize) -> bool) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                continue;
+            }
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if f(i, c, depth) {
+            return Some(i);
+        }
+        if matches!(c, '(' | '[') {
+            depth += 1;
+        }
+    }
+    None
+}
+
+/// Remove `/* ... */` comments outside quoted strings.
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    loop {
+        let comment = scan(rest, |i, c, _| c == '/' && rest[i + 1..].starts_with('*'));
+        let Some(start) = comment else {
+            out.push_str(rest);
+            return out;
+        };
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_class_is_stable() {
+        assert_eq!(scope_class(".a {}"), scope_class(".a {}"));
+        assert_ne!(scope_class(".a {}"), scope_class(".b {}"));
+        assert!(scope_class("").starts_with(SCOPE_PREFIX));
+    }
+
+    #[test]
+    fn test_scope_simple_rules() {
+        assert_eq!(
+            scope_css(".card { padding: 8px; } p { margin: 0 }", "s"),
+            ".card.s { padding: 8px; } p.s { margin: 0 }"
+        );
+    }
+
+    #[test]
+    fn test_scope_last_compound() {
+        assert_eq!(scope_selector(".card p", "s"), ".card p.s");
+        assert_eq!(scope_selector("ul > li + li", "s"), "ul > li + li.s");
+        assert_eq!(scope_selector("a:hover", "s"), "a:hover.s");
+        assert_eq!(scope_selector(".tip::before", "s"), ".tip.s::before");
+        assert_eq!(scope_selector("li:not(.a, .b)", "s"), "li:not(.a, .b).s");
+        assert_eq!(scope_selector("input[type=\"a b\"]", "s"), "input[type=\"a b\"].s");
+    }
+
+    #[test]
+    fn test_scope_selector_list() {
+        assert_eq!(scope_css("h1, h2 {}", "s"), "h1.s, h2.s {}");
+    }
+
+    #[test]
+    fn test_global_selector() {
+        assert_eq!(scope_css(":global(body) { margin: 0 }", "s"), "body { margin: 0 }");
+    }
+
+    #[test]
+    fn test_at_rules() {
+        assert_eq!(
+            scope_css("@media (max-width: 600px) { .a { b: c } }", "s"),
+            "@media (max-width: 600px) { .a.s { b: c } }"
+        );
+        assert_eq!(
+            scope_css("@keyframes spin { from { a: b } to { a: c } }", "s"),
+            "@keyframes spin { from { a: b } to { a: c } }"
+        );
+        assert_eq!(scope_css("@import url(\"x.css\");", "s"), "@import url(\"x.css\");");
+    }
+
+    #[test]
+    fn test_strings_and_comments() {
+        assert_eq!(
+            scope_css("/* .x { } */ .a { content: \"}\"; }", "s"),
+            " .a.s { content: \"}\"; }"
+        );
+    }
+}
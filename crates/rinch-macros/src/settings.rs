@@ -0,0 +1,177 @@
+//! `#[derive(Settings)]`.
+//!
+//! Implements `rinch::settings::Settings` for a struct with named fields and
+//! generates a `<Name>Fields` struct holding a signal per field.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Expr, Fields, Lit, Path, Result};
+
+/// Options given with `#[settings(...)]`.
+#[derive(Default)]
+struct Options {
+    name: Option<String>,
+    format: Option<String>,
+    version: Option<u32>,
+    migrate: Option<Path>,
+}
+
+/// Expand `#[derive(Settings)]`.
+pub fn derive_settings(input: DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let vis = &input.vis;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "Settings can't be derived for generic structs"));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(ident, "Settings can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(ident, "Settings can only be derived for structs")),
+    };
+    let options = parse_options(&input)?;
+
+    let name = options.name.unwrap_or_else(|| snake_case(&ident.to_string()));
+    let format = match options.format.as_deref() {
+        None | Some("toml") => quote! { ::rinch::settings::Format::Toml },
+        Some("json") => quote! { ::rinch::settings::Format::Json },
+        Some(_) => unreachable!("checked by parse_options"),
+    };
+    let version = options.version.unwrap_or(0);
+    let migrate = options.migrate.map(|path| {
+        quote! {
+            fn migrate(from_version: u32, value: &mut ::rinch::settings::Value) {
+                #path(from_version, value)
+            }
+        }
+    });
+
+    let fields_ident = format_ident!("{}Fields", ident);
+    let names: Vec<_> = fields.iter().map(|field| field.ident.as_ref().expect("named field")).collect();
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let doc = format!("A signal per field of [`{}`], from `rinch::settings::use_settings`.", ident);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Clone)]
+        #vis struct #fields_ident {
+            #( #vis #names: ::rinch::core::Signal<#types>, )*
+        }
+
+        impl ::rinch::settings::Settings for #ident {
+            type Fields = #fields_ident;
+
+            const NAME: &'static str = #name;
+            const FORMAT: ::rinch::settings::Format = #format;
+            const VERSION: u32 = #version;
+
+            fn into_fields(self) -> Self::Fields {
+                #fields_ident {
+                    #( #names: ::rinch::core::Signal::new(self.#names), )*
+                }
+            }
+
+            fn from_fields(fields: &Self::Fields) -> Self {
+                Self {
+                    #( #names: fields.#names.get(), )*
+                }
+            }
+
+            fn set_fields(self, fields: &Self::Fields) {
+                ::rinch::core::batch(|| {
+                    #( fields.#names.set(self.#names); )*
+                });
+            }
+
+            #migrate
+        }
+    })
+}
+
+/// Read the `#[settings(...)]` attributes.
+fn parse_options(input: &DeriveInput) -> Result<Options> {
+    let mut options = Options::default();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("settings")) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(ToString::to_string).unwrap_or_default();
+            let value: Expr = meta.value()?.parse()?;
+            let lit = match &value {
+                Expr::Lit(lit) => Some(&lit.lit),
+                _ => None,
+            };
+            match (key.as_str(), lit) {
+                ("name", Some(Lit::Str(name))) => options.name = Some(name.value()),
+                ("format", Some(Lit::Str(format))) => {
+                    if !matches!(format.value().as_str(), "toml" | "json") {
+                        return Err(Error::new_spanned(format, "expected \"toml\" or \"json\""));
+                    }
+                    options.format = Some(format.value());
+                }
+                ("version", Some(Lit::Int(version))) => options.version = Some(version.base10_parse()?),
+                ("migrate", _) => match value {
+                    Expr::Path(path) => options.migrate = Some(path.path),
+                    _ => return Err(Error::new_spanned(value, "expected the path of a function")),
+                },
+                ("name" | "format" | "version", _) => {
+                    return Err(Error::new_spanned(value, format!("unexpected value for `{}`", key)));
+                }
+                _ => {
+                    return Err(meta.error("expected `name`, `format`, `version` or `migrate`"));
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// `AppSettings` → `app_settings`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if previous_lower {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+            previous_lower = false;
+        } else {
+            snake.push(c);
+            previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("AppSettings"), "app_settings");
+        assert_eq!(snake_case("Prefs"), "prefs");
+        assert_eq!(snake_case("UISettings2"), "uisettings2");
+    }
+
+    #[test]
+    fn test_options() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[settings(name = "prefs", format = "json", version = 2, migrate = migrate_prefs)]
+            struct Prefs { size: u32 }
+        };
+        let options = parse_options(&input).unwrap();
+        assert_eq!(options.name.as_deref(), Some("prefs"));
+        assert_eq!(options.format.as_deref(), Some("json"));
+        assert_eq!(options.version, Some(2));
+        assert!(options.migrate.is_some_and(|path| path.is_ident("migrate_prefs")));
+
+        let input: DeriveInput = syn::parse_quote! {
+            #[settings(format = "yaml")]
+            struct Prefs { size: u32 }
+        };
+        assert!(parse_options(&input).is_err());
+    }
+}
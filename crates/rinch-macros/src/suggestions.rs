@@ -30,8 +30,8 @@ pub fn levenshtein_distance(a: &str, b: &str) -> usize {
     }
 
     // Initialize first row
-    for j in 0..=b_len {
-        matrix[0][j] = j;
+    for (j, cell) in matrix[0].iter_mut().enumerate() {
+        *cell = j;
     }
 
     // Fill in the rest of the matrix
@@ -84,6 +84,35 @@ pub fn format_unknown_prop_error(component: &str, unknown_prop: &str, valid_prop
     msg
 }
 
+/// Format a helpful error message for an unknown ARIA attribute.
+pub fn format_unknown_aria_error(unknown_prop: &str, valid_props: &[&str]) -> String {
+    let mut msg = format!("unknown ARIA attribute `{}`", unknown_prop);
+    if let Some(suggestion) = find_closest_prop(unknown_prop, valid_props) {
+        msg.push_str(&format!("\n\nDid you mean `{}`?", suggestion));
+    }
+    msg
+}
+
+/// Format a helpful error message for an unknown `role` value.
+pub fn format_unknown_role_error(unknown_role: &str, valid_roles: &[&str]) -> String {
+    let mut msg = format!("unknown ARIA role `{}`", unknown_role);
+    if let Some(suggestion) = find_closest_prop(unknown_role, valid_roles) {
+        msg.push_str(&format!("\n\nDid you mean `{}`?", suggestion));
+    }
+    msg
+}
+
+/// Format a helpful error message for an unknown MenuItem role.
+pub fn format_unknown_menu_role_error(unknown_role: &str, valid_roles: &[&str]) -> String {
+    let mut msg = format!("unknown menu item role `{}`", unknown_role);
+    if let Some(suggestion) = find_closest_prop(unknown_role, valid_roles) {
+        msg.push_str(&format!("\n\nDid you mean `{}`?", suggestion));
+    }
+    msg.push_str("\n\nValid roles are: ");
+    msg.push_str(&valid_roles.join(", "));
+    msg
+}
+
 /// Format a helpful error message for a missing required property.
 pub fn format_missing_prop_error(component: &str, missing_prop: &str) -> String {
     format!(
@@ -122,4 +151,11 @@ mod tests {
         assert_eq!(find_closest_prop("widht", &valid), Some("width".into()));
         assert_eq!(find_closest_prop("xyz", &valid), None);
     }
+
+    #[test]
+    fn test_unknown_role_error() {
+        let msg = format_unknown_role_error("buton", &["button", "link"]);
+        assert!(msg.starts_with("unknown ARIA role `buton`"));
+        assert!(msg.contains("Did you mean `button`?"));
+    }
 }
@@ -0,0 +1,146 @@
+//! Putting rendered HTML in the page.
+
+use std::cell::RefCell;
+
+use rinch_core::element::{Element, TextDirection, WindowProps};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, HtmlInputElement, HtmlTextAreaElement, Node};
+
+/// The props and HTML of the first `Window` in the tree.
+pub(crate) fn first_window(element: Element) -> Option<(WindowProps, String)> {
+    match element {
+        Element::Window(props, children) => Some((props, children_to_html(&children))),
+        Element::Fragment(children) => children.into_iter().find_map(first_window),
+        _ => None,
+    }
+}
+
+/// Convert element children to an HTML string.
+fn children_to_html(children: &[Element]) -> String {
+    let mut html = String::new();
+    for child in children {
+        match child {
+            Element::Html(content) => html.push_str(content),
+            Element::Fragment(kids) => html.push_str(&children_to_html(kids)),
+            _ => {}
+        }
+    }
+    html
+}
+
+thread_local! {
+    /// The HTML the mount element was last filled with.
+    static CONTENT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Replace the content of the mount element with a window's HTML, keeping
+/// focus and the text selection where they were.
+pub(crate) fn update(mount: &web_sys::Element, props: &WindowProps, html: &str) {
+    if let Some(document) = mount.owner_document()
+        && document.title() != props.title
+    {
+        document.set_title(&props.title);
+    }
+    let _ = match props.dir {
+        TextDirection::Auto => mount.remove_attribute("dir"),
+        TextDirection::Ltr => mount.set_attribute("dir", "ltr"),
+        TextDirection::Rtl => mount.set_attribute("dir", "rtl"),
+    };
+
+    // Nothing changed, and replacing the content would reset scroll positions
+    if CONTENT.with(|content| *content.borrow() == html) {
+        return;
+    }
+    CONTENT.with(|content| *content.borrow_mut() = html.to_string());
+    let focus = Focus::save(mount);
+    mount.set_inner_html(html);
+    if let Some(focus) = focus {
+        focus.restore(mount);
+    }
+}
+
+/// The focused element, by its position under the mount element, and its
+/// text selection.
+struct Focus {
+    path: Vec<Step>,
+    selection: Option<(u32, u32)>,
+}
+
+/// A step of a [`Focus`] path.
+enum Step {
+    /// The child at this index.
+    Index(u32),
+    /// The `n`th child with this `data-rkey`: an element of a keyed `rsx!`
+    /// loop item, found again wherever the loop moved the item.
+    Key(String, u32),
+}
+
+impl Focus {
+    fn save(mount: &web_sys::Element) -> Option<Self> {
+        let active = mount.owner_document()?.active_element()?;
+        let mount: &Node = mount.as_ref();
+        let mut path = Vec::new();
+        let mut node: Node = active.clone().into();
+        while !node.is_same_node(Some(mount)) {
+            let parent = node.parent_node()?;
+            let siblings = parent.child_nodes();
+            let index = (0..siblings.length()).find(|&i| siblings.get(i).is_some_and(|n| n.is_same_node(Some(&node))))?;
+            path.push(match item_key(&node) {
+                Some(key) => {
+                    let same_key = |i: &u32| siblings.get(*i).and_then(|n| item_key(&n)).as_ref() == Some(&key);
+                    let n = (0..index).filter(same_key).count() as u32;
+                    Step::Key(key, n)
+                }
+                None => Step::Index(index),
+            });
+            node = parent;
+        }
+        path.reverse();
+        Some(Self { path, selection: selection(&active) })
+    }
+
+    fn restore(self, mount: &web_sys::Element) {
+        let mut node: Node = mount.clone().into();
+        for step in self.path {
+            let children = node.child_nodes();
+            let child = match step {
+                Step::Index(index) => children.get(index),
+                Step::Key(key, n) => (0..children.length())
+                    .filter_map(|i| children.get(i))
+                    .filter(|child| item_key(child).as_ref() == Some(&key))
+                    .nth(n as usize),
+            };
+            let Some(child) = child else {
+                return;
+            };
+            node = child;
+        }
+        let Some(element) = node.dyn_ref::<HtmlElement>() else {
+            return;
+        };
+        let _ = element.focus();
+        if let Some((start, end)) = self.selection {
+            if let Some(input) = element.dyn_ref::<HtmlInputElement>() {
+                let _ = input.set_selection_range(start, end);
+            } else if let Some(textarea) = element.dyn_ref::<HtmlTextAreaElement>() {
+                let _ = textarea.set_selection_range(start, end);
+            }
+        }
+    }
+}
+
+/// The key of the keyed `rsx!` loop item an element belongs to.
+fn item_key(node: &Node) -> Option<String> {
+    node.dyn_ref::<web_sys::Element>()?.get_attribute("data-rkey")
+}
+
+/// The selection in a text input or textarea, in UTF-16 code units.
+pub(crate) fn selection(element: &web_sys::Element) -> Option<(u32, u32)> {
+    if let Some(input) = element.dyn_ref::<HtmlInputElement>() {
+        Some((input.selection_start().ok()??, input.selection_end().ok()??))
+    } else if let Some(textarea) = element.dyn_ref::<HtmlTextAreaElement>() {
+        Some((textarea.selection_start().ok()??, textarea.selection_end().ok()??))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,131 @@
+//! Routing DOM events to the handlers registered while rendering.
+//!
+//! One listener per event type sits on the mount element and finds the
+//! handler from the `data-*` attribute `rsx!` gave the target, as the
+//! desktop runtime does for blitz documents.
+
+use std::cell::Cell;
+
+use rinch_core::events::{dispatch_change, dispatch_event, dispatch_input, dispatch_select, EventHandlerId};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+
+use crate::dom::selection;
+
+thread_local! {
+    /// The last selection passed to an `onselect` handler, so moving the
+    /// caret by clicking or typing only reports changes.
+    static LAST_SELECTION: Cell<Option<(EventHandlerId, usize, usize)>> = const { Cell::new(None) };
+}
+
+/// Listen for the events `rsx!` handlers respond to on `mount`.
+pub(crate) fn listen(mount: &web_sys::Element) {
+    add_listener(mount, "click", on_click);
+    add_listener(mount, "input", on_input);
+    add_listener(mount, "change", on_change);
+    for event in ["select", "keyup", "mouseup"] {
+        add_listener(mount, event, on_select);
+    }
+}
+
+/// Call `handler` for every `event` on `mount`, for as long as the page is
+/// open.
+fn add_listener(mount: &web_sys::Element, event: &str, handler: fn(&web_sys::Element)) {
+    let closure = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+        if let Some(target) = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()) {
+            handler(&target);
+        }
+    });
+    if mount.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).is_ok() {
+        closure.forget();
+    }
+}
+
+/// Call the click handler of the clicked element or its nearest ancestor
+/// with one.
+fn on_click(target: &web_sys::Element) {
+    let Some(element) = target.closest("[data-rid]").ok().flatten() else {
+        return;
+    };
+    if let Some(id) = handler_attr(&element, "data-rid")
+        && dispatch_event(id)
+    {
+        crate::request_render();
+    }
+}
+
+/// Pass the new value of an edited text field to its `oninput` handler.
+fn on_input(target: &web_sys::Element) {
+    let Some(id) = handler_attr(target, "data-oninput") else {
+        return;
+    };
+    let value = if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+        input.value()
+    } else if let Some(textarea) = target.dyn_ref::<HtmlTextAreaElement>() {
+        textarea.value()
+    } else if let Some(select) = target.dyn_ref::<HtmlSelectElement>() {
+        select.value()
+    } else {
+        return;
+    };
+    if dispatch_input(id, value) {
+        crate::request_render();
+    }
+}
+
+/// Pass the state of a toggled checkbox or radio button to its `onchange`
+/// handler.
+fn on_change(target: &web_sys::Element) {
+    let Some(id) = handler_attr(target, "data-onchange") else {
+        return;
+    };
+    let Some(input) = target.dyn_ref::<HtmlInputElement>() else {
+        return;
+    };
+    if dispatch_change(id, input.checked()) {
+        crate::request_render();
+    }
+}
+
+/// Pass a text field's selection to its `onselect` handler when it moves.
+fn on_select(target: &web_sys::Element) {
+    let Some(id) = handler_attr(target, "data-onselect") else {
+        return;
+    };
+    let Some((start, end)) = selection(target) else {
+        return;
+    };
+    let value = if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+        input.value()
+    } else if let Some(textarea) = target.dyn_ref::<HtmlTextAreaElement>() {
+        textarea.value()
+    } else {
+        return;
+    };
+    let (start, end) = (byte_offset(&value, start), byte_offset(&value, end));
+    if LAST_SELECTION.replace(Some((id, start, end))) == Some((id, start, end)) {
+        return;
+    }
+    if dispatch_select(id, start, end) {
+        crate::request_render();
+    }
+}
+
+/// The handler ID in an element's `name` attribute.
+fn handler_attr(element: &web_sys::Element, name: &str) -> Option<EventHandlerId> {
+    element.get_attribute(name)?.parse::<usize>().ok().map(EventHandlerId)
+}
+
+/// Convert an offset into `value` in UTF-16 code units, as the DOM counts,
+/// to a byte offset, as handlers expect.
+fn byte_offset(value: &str, utf16_offset: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in value.char_indices() {
+        if units >= utf16_offset as usize {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    value.len()
+}
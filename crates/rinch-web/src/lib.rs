@@ -0,0 +1,182 @@
+//! Rinch in the browser.
+//!
+//! `rinch-web` runs a rinch app function in a web page, built for
+//! `wasm32-unknown-unknown` with `wasm-bindgen`. The element tree renders
+//! into the page's DOM, clicks and edits reach the handlers set up in
+//! `rsx!`, and hooks and signals work as they do on the desktop.
+//!
+//! Depend on it under the name `rinch` when building for the web, so
+//! `use rinch::prelude::*` and the code `rsx!` generates work unchanged:
+//!
+//! ```toml
+//! [target.'cfg(not(target_arch = "wasm32"))'.dependencies]
+//! rinch = "0.1"
+//!
+//! [target.'cfg(target_arch = "wasm32")'.dependencies]
+//! rinch = { package = "rinch-web", version = "0.1" }
+//! wasm-bindgen = "0.2"
+//! ```
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//!
+//! fn app() -> Element {
+//!     let count = use_signal(|| 0);
+//!     let increment = count.clone();
+//!     rsx! {
+//!         Window { title: "Counter",
+//!             p { "Count: " {count.get()} }
+//!             button { onclick: move || increment.update(|n| *n += 1), "Increment" }
+//!         }
+//!     }
+//! }
+//!
+//! #[cfg(target_arch = "wasm32")]
+//! #[wasm_bindgen::prelude::wasm_bindgen(start)]
+//! fn start() {
+//!     rinch::run(app);
+//! }
+//!
+//! #[cfg(not(target_arch = "wasm32"))]
+//! fn main() {
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! The page has room for one window: the children of the first `Window`
+//! element render into the mount element (`<body>`, or the element given to
+//! [`run_in`]) and its title becomes the page's title. Other windows, menus
+//! and tray icons are left out, as are the desktop modules of `rinch`
+//! (`rinch::windows`, `rinch::settings` and so on) and its default
+//! stylesheet; keep code that uses them behind
+//! `#[cfg(not(target_arch = "wasm32"))]`.
+
+mod dom;
+mod events;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use rinch_core::events::{restore_handlers, take_handlers};
+use rinch_core::hooks::{begin_render, end_render};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+pub mod prelude {
+    //! Common imports for rinch applications.
+    pub use crate::run;
+    pub use rinch_core::element::*;
+    pub use rinch_core::{batch, derived, untracked, Effect, Memo, Scope, Signal};
+    // Hooks for ergonomic state management
+    pub use rinch_core::{
+        create_context, use_callback, use_context, use_derived, use_effect, use_effect_cleanup,
+        use_memo, use_mount, use_ref, use_signal, use_state, RefHandle,
+    };
+    pub use rinch_core::form::{use_form, validators, Form, FormFields};
+    pub use rinch_core::rich_text::{use_rich_text, RichText, RichTextCommand, RichTextState};
+    pub use rinch_macros::rsx;
+}
+
+// Re-export core types at crate root
+pub use rinch_core::element::{Children, Element, TextDirection, WindowProps};
+pub use rinch_core::{batch, derived, untracked, Effect, Memo, Scope, Signal};
+pub use rinch_macros::rsx;
+
+pub use rinch_core as core;
+
+thread_local! {
+    /// The app function, once [`run`] has been called.
+    static APP: RefCell<Option<Rc<dyn Fn() -> Element>>> = RefCell::new(None);
+    /// The element the app renders into.
+    static MOUNT: RefCell<Option<web_sys::Element>> = const { RefCell::new(None) };
+    /// Whether a render is waiting for the next animation frame.
+    static RENDER_SCHEDULED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run the app in the page, rendering into `<body>`.
+///
+/// Returns once the app has rendered; the browser keeps the app running
+/// after that.
+pub fn run<F>(app: F)
+where
+    F: Fn() -> Element + 'static,
+{
+    let Some(body) = web_sys::window().and_then(|window| window.document()).and_then(|document| document.body())
+    else {
+        web_sys::console::error_1(&"rinch: the page has no <body> to render into".into());
+        return;
+    };
+    mount(app, body.into());
+}
+
+/// Run the app in the page, rendering into the first element matching the
+/// CSS `selector` instead of `<body>`.
+pub fn run_in<F>(app: F, selector: &str)
+where
+    F: Fn() -> Element + 'static,
+{
+    let element = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.query_selector(selector).ok().flatten());
+    match element {
+        Some(element) => mount(app, element),
+        None => web_sys::console::error_1(&format!("rinch: no element matches `{}`", selector).into()),
+    }
+}
+
+/// Render the app again at the next animation frame.
+///
+/// Event handlers set up in `rsx!` re-render by themselves; call this after
+/// changing state elsewhere, such as in a timer or a `fetch` callback.
+pub fn request_render() {
+    if RENDER_SCHEDULED.replace(true) {
+        return;
+    }
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let callback = Closure::once_into_js(render);
+    if window.request_animation_frame(callback.unchecked_ref()).is_err() {
+        RENDER_SCHEDULED.set(false);
+    }
+}
+
+/// Store the app, render it into `element` and start listening for events.
+fn mount<F>(app: F, element: web_sys::Element)
+where
+    F: Fn() -> Element + 'static,
+{
+    events::listen(&element);
+    APP.with(|a| *a.borrow_mut() = Some(Rc::new(app)));
+    MOUNT.with(|m| *m.borrow_mut() = Some(element));
+    render();
+}
+
+/// Run the app function and put its first window's HTML in the page.
+fn render() {
+    RENDER_SCHEDULED.set(false);
+    let Some(app) = APP.with(|a| a.borrow().clone()) else {
+        return;
+    };
+    let Some(mount) = MOUNT.with(|m| m.borrow().clone()) else {
+        return;
+    };
+
+    // Set old event handlers aside; the page still uses them if there is no
+    // window to show
+    let previous_handlers = take_handlers();
+    begin_render();
+    let root = app().expand_components();
+    end_render();
+
+    match dom::first_window(root) {
+        Some((props, html)) => {
+            drop(previous_handlers);
+            dom::update(&mount, &props, &html);
+        }
+        None => {
+            restore_handlers(previous_handlers);
+            web_sys::console::warn_1(&"rinch: the app has no Window element to render".into());
+        }
+    }
+}
@@ -1,26 +1,82 @@
 //! Application lifecycle and entry point.
 
-/// Main application container.
+use rinch_core::element::Element;
+
+use crate::plugin::RinchPlugin;
+use crate::shell::RunConfig;
+use crate::tasks::AppHandle;
+
+/// Builds and runs an application.
+///
+/// [`run`](crate::run), [`run_with_config`](crate::run_with_config) and
+/// [`run_with_handle`](crate::run_with_handle) are shortcuts for the common
+/// cases; `App` combines their options.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::{App, RunConfig};
+///
+/// fn main() {
+///     App::new(app)
+///         .with_config(RunConfig::new().log_filter("warn").devtools(false))
+///         .setup(|handle| start_sync(handle))
+///         .run();
+/// }
+/// ```
 pub struct App {
-    // TODO: Application state
+    app: Box<dyn Fn() -> Element>,
+    config: RunConfig,
+    setup: Box<dyn FnOnce(AppHandle)>,
+    #[cfg(feature = "hot-reload")]
+    hot_reload: bool,
 }
 
 impl App {
-    /// Create a new application.
-    pub fn new() -> Self {
-        Self {}
+    /// Create an application rendered by `app`, with the default options.
+    pub fn new(app: impl Fn() -> Element + 'static) -> Self {
+        Self {
+            app: Box::new(app),
+            config: RunConfig::default(),
+            setup: Box::new(|_| {}),
+            #[cfg(feature = "hot-reload")]
+            hot_reload: false,
+        }
     }
 
-    /// Run the application event loop.
-    pub fn run(self) {
-        // TODO: Initialize winit event loop
-        // TODO: Create window with blitz renderer
-        // TODO: Run event loop
+    /// Set the options, replacing any set before.
+    pub fn with_config(mut self, config: RunConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Add a [plugin](crate::plugin) to the options.
+    pub fn with_plugin(mut self, plugin: impl RinchPlugin + 'static) -> Self {
+        self.config = self.config.with_plugin(plugin);
+        self
     }
-}
 
-impl Default for App {
-    fn default() -> Self {
-        Self::new()
+    /// Call `setup` with an [`AppHandle`] just before the event loop
+    /// starts, as [`run_with_handle`](crate::run_with_handle) does.
+    pub fn setup(mut self, setup: impl FnOnce(AppHandle) + 'static) -> Self {
+        self.setup = Box::new(setup);
+        self
+    }
+
+    /// Re-render when files in the project change, as
+    /// [`run_with_hot_reload`](crate::run_with_hot_reload) does.
+    #[cfg(feature = "hot-reload")]
+    pub fn hot_reload(mut self, enabled: bool) -> Self {
+        self.hot_reload = enabled;
+        self
+    }
+
+    /// Run the application event loop.
+    pub fn run(self) {
+        #[cfg(feature = "hot-reload")]
+        let hot_reload = self.hot_reload.then(crate::shell::HotReloadConfig::default);
+        #[cfg(not(feature = "hot-reload"))]
+        let hot_reload = None;
+        crate::shell::runtime::run_internal(self.app, self.config, hot_reload, self.setup);
     }
 }
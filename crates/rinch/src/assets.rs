@@ -0,0 +1,474 @@
+//! Images, stylesheets and other files referenced from documents.
+//!
+//! Relative URLs in the element tree, such as `img { src: "logo.png" }`, are
+//! resolved against the assets directory. By default this is an `assets`
+//! directory next to the executable, falling back to `assets` in the current
+//! working directory (the project root under `cargo run`).
+//!
+//! Assets can also be embedded in the binary with [`asset!`](crate::asset) or
+//! [`register_asset`], so an application can ship as a single executable.
+//! Embedded assets take priority over files on disk.
+//!
+//! Applications can serve generated content under their own URL scheme with
+//! [`register_protocol`].
+//!
+//! With the `net` feature, `http` and `https` URLs are downloaded in the
+//! background and kept in a size-limited in-memory cache.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::assets;
+//!
+//! fn main() {
+//!     assets::register_asset("logo.png", include_bytes!("../assets/logo.png"));
+//!     rinch::run(app);
+//! }
+//!
+//! fn app() -> Element {
+//!     rsx! {
+//!         Window { title: "Assets",
+//!             img { src: "logo.png" }
+//!             img { src: "icons/save.png" }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use blitz_traits::net::Url;
+
+/// Name of the default assets directory.
+const DEFAULT_ASSETS_DIR: &str = "assets";
+
+/// URL schemes that can't be taken over by [`register_protocol`].
+const RESERVED_SCHEMES: &[&str] = &["file", "http", "https", "data", "about"];
+
+/// Handler serving requests for a custom URL scheme.
+type ProtocolHandler = Rc<dyn Fn(&ProtocolRequest) -> ProtocolResponse>;
+
+/// Asset configuration and embedded assets.
+#[derive(Default)]
+struct Assets {
+    dir: Option<PathBuf>,
+    embedded: HashMap<String, Cow<'static, [u8]>>,
+    protocols: HashMap<String, ProtocolHandler>,
+}
+
+thread_local! {
+    static ASSETS: RefCell<Assets> = RefCell::new(Assets::default());
+    /// Files windows were loaded from with their `src` prop, for hot reload
+    /// to watch.
+    #[cfg(feature = "hot-reload")]
+    static PAGE_FILES: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Where the bytes for an asset come from.
+pub(crate) enum AssetSource {
+    /// Registered with [`register_asset`].
+    Embedded(Cow<'static, [u8]>),
+    /// A file on disk.
+    File(PathBuf),
+}
+
+/// Set the directory that relative asset URLs are resolved against.
+///
+/// Relative paths are resolved against the current working directory. Takes
+/// effect for documents created after the call, which includes the next
+/// re-render of every window.
+///
+/// # Example
+///
+/// ```ignore
+/// rinch::assets::set_assets_dir("resources");
+/// ```
+pub fn set_assets_dir(dir: impl Into<PathBuf>) {
+    let dir = absolute(dir.into());
+    ASSETS.with(|assets| assets.borrow_mut().dir = Some(dir));
+}
+
+/// Get the directory that relative asset URLs are resolved against.
+pub fn assets_dir() -> PathBuf {
+    ASSETS
+        .with(|assets| assets.borrow().dir.clone())
+        .unwrap_or_else(default_assets_dir)
+}
+
+/// Embed an asset in the binary under the given path.
+///
+/// The path is relative to the assets directory and uses `/` separators, so
+/// `register_asset("icons/save.png", ...)` serves `img { src: "icons/save.png" }`.
+/// Registering the same path again replaces the previous bytes.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::assets::register_asset;
+///
+/// register_asset("logo.png", include_bytes!("../assets/logo.png"));
+/// register_asset("theme.css", include_str!("../assets/theme.css").as_bytes());
+/// ```
+pub fn register_asset(path: impl AsRef<str>, bytes: impl Into<Cow<'static, [u8]>>) {
+    let key = normalize(path.as_ref());
+    ASSETS.with(|assets| {
+        assets.borrow_mut().embedded.insert(key, bytes.into());
+    });
+}
+
+/// Embed a file from the `assets` directory at compile time.
+///
+/// `asset!("icons/save.svg")` includes `assets/icons/save.svg` (relative to
+/// the crate's `Cargo.toml`) in the binary and returns a URL for it, for use
+/// in `src`, `href` and similar attributes. The app then doesn't need an
+/// `assets` directory at runtime. A missing file is a compile error.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+///
+/// fn app() -> Element {
+///     rsx! {
+///         Window { title: "Editor",
+///             img { src: asset!("icons/save.svg"), alt: "Save" }
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! asset {
+    ($path:literal) => {
+        $crate::assets::embedded_asset(
+            $path,
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $path)),
+        )
+    };
+}
+
+/// Register an asset embedded by [`asset!`](crate::asset) and return its URL.
+#[doc(hidden)]
+pub fn embedded_asset(path: &'static str, bytes: &'static [u8]) -> &'static str {
+    let key = normalize(path);
+    ASSETS.with(|assets| {
+        assets
+            .borrow_mut()
+            .embedded
+            .entry(key)
+            .or_insert(Cow::Borrowed(bytes));
+    });
+    path
+}
+
+/// A request for a URL with a custom scheme, passed to a protocol handler.
+#[derive(Debug, Clone)]
+pub struct ProtocolRequest {
+    url: Url,
+}
+
+impl ProtocolRequest {
+    /// The full URL, e.g. `app://thumbnails/42.png?size=64`.
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// The URL scheme, e.g. `app`.
+    pub fn scheme(&self) -> &str {
+        self.url.scheme()
+    }
+
+    /// The host, e.g. `thumbnails` in `app://thumbnails/42.png`.
+    pub fn host(&self) -> Option<&str> {
+        self.url.host_str()
+    }
+
+    /// The path, e.g. `/42.png` in `app://thumbnails/42.png`.
+    pub fn path(&self) -> &str {
+        self.url.path()
+    }
+
+    /// The query string without the leading `?`, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.url.query()
+    }
+}
+
+/// The response from a protocol handler.
+#[derive(Debug, Clone)]
+pub struct ProtocolResponse {
+    status: u16,
+    body: Cow<'static, [u8]>,
+}
+
+impl ProtocolResponse {
+    /// A successful response with the given body.
+    pub fn new(body: impl Into<Cow<'static, [u8]>>) -> Self {
+        Self {
+            status: 200,
+            body: body.into(),
+        }
+    }
+
+    /// A response for a resource that doesn't exist.
+    pub fn not_found() -> Self {
+        Self::error(404)
+    }
+
+    /// An error response with the given HTTP-style status code.
+    pub fn error(status: u16) -> Self {
+        Self {
+            status,
+            body: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// The status code of the response.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Whether the response was successful (a 2xx status).
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The body of the response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub(crate) fn into_body(self) -> Cow<'static, [u8]> {
+        self.body
+    }
+}
+
+/// Serve URLs with a custom scheme from the application.
+///
+/// Documents requesting `<scheme>://...` (in `img { src }`, stylesheets and
+/// so on) call the handler, which returns the bytes to use. This is the way
+/// to show generated content, such as thumbnails, charts or data from a
+/// database, without writing it to disk first. The handler runs on the UI
+/// thread while the document loads, so it should return quickly.
+///
+/// Registering a scheme again replaces its handler. The `file`, `http`,
+/// `https`, `data` and `about` schemes can't be registered.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::assets::{register_protocol, ProtocolResponse};
+///
+/// register_protocol("thumb", |request| {
+///     let id = request.path().trim_start_matches('/');
+///     match load_thumbnail(id) {
+///         Some(png) => ProtocolResponse::new(png),
+///         None => ProtocolResponse::not_found(),
+///     }
+/// });
+///
+/// // Later, in rsx!
+/// img { src: "thumb://photos/42" }
+/// ```
+pub fn register_protocol(
+    scheme: impl AsRef<str>,
+    handler: impl Fn(&ProtocolRequest) -> ProtocolResponse + 'static,
+) {
+    let scheme = scheme.as_ref().to_ascii_lowercase();
+    if RESERVED_SCHEMES.contains(&scheme.as_str()) {
+        tracing::warn!("Can't register a handler for the built-in '{}' scheme", scheme);
+        return;
+    }
+    ASSETS.with(|assets| {
+        assets
+            .borrow_mut()
+            .protocols
+            .insert(scheme, Rc::new(handler));
+    });
+}
+
+/// Remove the handler for a custom URL scheme.
+pub fn unregister_protocol(scheme: impl AsRef<str>) {
+    let scheme = scheme.as_ref().to_ascii_lowercase();
+    ASSETS.with(|assets| {
+        assets.borrow_mut().protocols.remove(&scheme);
+    });
+}
+
+/// Serve a URL with a registered custom scheme.
+///
+/// Returns `None` if no handler is registered for the URL's scheme.
+pub(crate) fn handle_protocol(url: &Url) -> Option<ProtocolResponse> {
+    let handler = ASSETS.with(|assets| assets.borrow().protocols.get(url.scheme()).cloned())?;
+    let request = ProtocolRequest { url: url.clone() };
+    Some(handler(&request))
+}
+
+/// Set the maximum total size of downloaded resources kept in memory.
+///
+/// Defaults to 64 MiB. When the cache is full, the least recently used
+/// responses are evicted; responses larger than the limit aren't cached.
+///
+/// # Example
+///
+/// ```ignore
+/// rinch::assets::set_net_cache_limit(16 * 1024 * 1024);
+/// ```
+#[cfg(feature = "net")]
+pub fn set_net_cache_limit(bytes: usize) {
+    crate::shell::remote::set_cache_limit(bytes);
+}
+
+/// Remove all downloaded resources from the cache.
+///
+/// Resources are downloaded again the next time a document requests them.
+#[cfg(feature = "net")]
+pub fn clear_net_cache() {
+    crate::shell::remote::clear_cache();
+}
+
+/// The base URL for documents, so relative URLs resolve into the assets directory.
+pub(crate) fn base_url() -> Option<String> {
+    match Url::from_directory_path(assets_dir()) {
+        Ok(url) => Some(url.to_string()),
+        Err(()) => {
+            tracing::warn!("Assets directory {:?} can't be used as a base URL", assets_dir());
+            None
+        }
+    }
+}
+
+/// Load the HTML page a window's `src` prop names: a path relative to the
+/// assets directory (or an embedded asset), a `file:` URL, or a URL with a
+/// registered scheme.
+///
+/// For a complete page, the contents of its `<head>` and `<body>` are
+/// returned, to go in the window's body; fragments are returned as they are.
+pub(crate) fn load_page(src: &str) -> Result<String, String> {
+    let base = base_url().and_then(|base| Url::parse(&base).ok());
+    let url = Url::options()
+        .base_url(base.as_ref())
+        .parse(src)
+        .map_err(|e| format!("invalid URL: {}", e))?;
+
+    let bytes = if url.scheme() == "file" {
+        match resolve_url(&url) {
+            Some(AssetSource::Embedded(bytes)) => bytes,
+            Some(AssetSource::File(path)) => {
+                let bytes = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                #[cfg(feature = "hot-reload")]
+                PAGE_FILES.with(|files| {
+                    let path = path.canonicalize().unwrap_or(path);
+                    let mut files = files.borrow_mut();
+                    if !files.contains(&path) {
+                        files.push(path);
+                    }
+                });
+                Cow::Owned(bytes)
+            }
+            None => return Err("not a file".into()),
+        }
+    } else {
+        let response = handle_protocol(&url).ok_or_else(|| format!("no handler is registered for '{}'", url.scheme()))?;
+        if !response.is_success() {
+            return Err(format!("the handler answered with status {}", response.status()));
+        }
+        response.into_body()
+    };
+    let html = String::from_utf8(bytes.into_owned()).map_err(|_| "the page isn't UTF-8".to_string())?;
+    Ok(page_content(&html))
+}
+
+/// The files windows were loaded from (called by the hot reloader).
+#[cfg(feature = "hot-reload")]
+pub(crate) fn page_files() -> Vec<PathBuf> {
+    PAGE_FILES.with(|files| files.borrow().clone())
+}
+
+/// The contents of a page's `<head>` and `<body>`, or the whole text if it
+/// has no `<body>`.
+fn page_content(html: &str) -> String {
+    // Lowercasing ASCII keeps byte offsets
+    let lower = html.to_ascii_lowercase();
+    let inner = |name: &str| {
+        let content_start = find_tag(&lower, &format!("<{}", name))?;
+        let content_end = lower[content_start..]
+            .find(&format!("</{}", name))
+            .map_or(html.len(), |end| content_start + end);
+        Some(&html[content_start..content_end])
+    };
+    match inner("body") {
+        Some(body) => format!("{}{}", inner("head").unwrap_or_default(), body),
+        None => html.to_string(),
+    }
+}
+
+/// Find the first start tag beginning with `open` (such as `<head`, but not
+/// `<header`), returning where its content starts.
+fn find_tag(lower: &str, open: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(open) {
+        let start = from + found;
+        let after = start + open.len();
+        if lower[after..].starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace()) {
+            return Some(after + lower[after..].find('>')? + 1);
+        }
+        from = after;
+    }
+    None
+}
+
+/// Find the source of a `file:` URL requested by a document.
+///
+/// Returns `None` for other schemes.
+pub(crate) fn resolve_url(url: &Url) -> Option<AssetSource> {
+    if url.scheme() != "file" {
+        return None;
+    }
+    let path = url.to_file_path().ok()?;
+
+    // Embedded assets are keyed by their path inside the assets directory
+    if let Ok(relative) = path.strip_prefix(assets_dir()) {
+        let key = normalize(&relative.to_string_lossy());
+        let embedded = ASSETS.with(|assets| assets.borrow().embedded.get(&key).cloned());
+        if let Some(bytes) = embedded {
+            return Some(AssetSource::Embedded(bytes));
+        }
+    }
+
+    Some(AssetSource::File(path))
+}
+
+/// Normalize an asset path into an embedded asset key.
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+/// Resolve a relative path against the current working directory.
+fn absolute(path: PathBuf) -> PathBuf {
+    if path.is_absolute() {
+        return path;
+    }
+    match std::env::current_dir() {
+        Ok(cwd) => cwd.join(path),
+        Err(_) => path,
+    }
+}
+
+/// `assets` next to the executable if it exists, else in the working directory.
+fn default_assets_dir() -> PathBuf {
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(DEFAULT_ASSETS_DIR)));
+    if let Some(dir) = beside_exe
+        && dir.is_dir()
+    {
+        return dir;
+    }
+    absolute(PathBuf::from(DEFAULT_ASSETS_DIR))
+}
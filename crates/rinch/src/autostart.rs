@@ -0,0 +1,265 @@
+//! Starting the app when the user logs in.
+//!
+//! For tray-resident utilities that should always be running. The entry
+//! points at the running executable and is named after its file name, so
+//! [`enable`] should be called from the installed copy of the app.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::autostart;
+//!
+//! let start_at_login = use_signal(|| autostart::is_enabled());
+//! let toggle = start_at_login.clone();
+//! let on_toggle = move || {
+//!     let result = if toggle.get() { autostart::disable() } else { autostart::enable() };
+//!     match result {
+//!         Ok(()) => toggle.set(autostart::is_enabled()),
+//!         Err(e) => eprintln!("Couldn't change autostart: {e}"),
+//!     }
+//! };
+//! ```
+//!
+//! # Platform support
+//!
+//! - **Windows**: a value under `HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Run`.
+//! - **macOS**: a launch agent in `~/Library/LaunchAgents`.
+//! - **Linux**: a `.desktop` file in `~/.config/autostart`.
+
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
+
+/// Autostart error type.
+#[derive(Debug)]
+pub enum AutostartError {
+    /// The path of the running executable couldn't be determined.
+    NoExecutable,
+    /// Writing or removing the entry failed.
+    Failed(String),
+}
+
+impl std::fmt::Display for AutostartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutostartError::NoExecutable => write!(f, "could not determine the executable path"),
+            AutostartError::Failed(msg) => write!(f, "failed to update autostart: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AutostartError {}
+
+/// Result type for autostart operations.
+pub type AutostartResult<T> = Result<T, AutostartError>;
+
+/// Start the app when the user logs in.
+pub fn enable() -> AutostartResult<()> {
+    enable_with_args(&[])
+}
+
+/// Start the app with command line arguments when the user logs in, e.g.
+/// `--minimized` to start in the tray.
+///
+/// Replaces the existing entry, if any.
+pub fn enable_with_args(args: &[&str]) -> AutostartResult<()> {
+    let (name, exe) = app()?;
+    write_entry(&name, &exe, args)
+}
+
+/// Stop starting the app at login. Does nothing if it isn't enabled.
+pub fn disable() -> AutostartResult<()> {
+    let (name, _) = app()?;
+    remove_entry(&name)
+}
+
+/// Whether the app starts when the user logs in.
+pub fn is_enabled() -> bool {
+    app().is_ok_and(|(name, _)| has_entry(&name))
+}
+
+/// The app's name (the executable's file name) and the executable's path.
+fn app() -> AutostartResult<(String, String)> {
+    let exe = std::env::current_exe().map_err(|_| AutostartError::NoExecutable)?;
+    let name = exe
+        .file_stem()
+        .ok_or(AutostartError::NoExecutable)?
+        .to_string_lossy()
+        .into_owned();
+    Ok((name, exe.to_string_lossy().into_owned()))
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run\0";
+
+#[cfg(target_os = "windows")]
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Set the app's value under the `Run` key to its command line.
+#[cfg(target_os = "windows")]
+fn write_entry(name: &str, exe: &str, args: &[&str]) -> AutostartResult<()> {
+    use windows_sys::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let mut command = format!("\"{}\"", exe);
+    for arg in args {
+        command.push_str(&format!(" \"{}\"", arg));
+    }
+    let key: Vec<u16> = RUN_KEY.encode_utf16().collect();
+    let name = wide(name);
+    let data = wide(&command);
+    // SAFETY: the strings are NUL-terminated and `data` is `data.len()` UTF-16 units.
+    let status = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            key.as_ptr(),
+            name.as_ptr(),
+            REG_SZ,
+            data.as_ptr().cast(),
+            (data.len() * std::mem::size_of::<u16>()) as u32,
+        )
+    };
+    if status != 0 {
+        return Err(AutostartError::Failed(format!("registry error {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn remove_entry(name: &str) -> AutostartResult<()> {
+    use windows_sys::Win32::System::Registry::{RegDeleteKeyValueW, HKEY_CURRENT_USER};
+
+    const ERROR_FILE_NOT_FOUND: u32 = 2;
+
+    let key: Vec<u16> = RUN_KEY.encode_utf16().collect();
+    let name = wide(name);
+    // SAFETY: the strings are NUL-terminated.
+    let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key.as_ptr(), name.as_ptr()) };
+    if status != 0 && status != ERROR_FILE_NOT_FOUND {
+        return Err(AutostartError::Failed(format!("registry error {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn has_entry(name: &str) -> bool {
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    let key: Vec<u16> = RUN_KEY.encode_utf16().collect();
+    let name = wide(name);
+    // SAFETY: the strings are NUL-terminated; with no data buffer only the
+    // existence of the value is checked.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    status == 0
+}
+
+/// The file holding the app's entry.
+#[cfg(target_os = "macos")]
+fn entry_path(name: &str) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join("Library/LaunchAgents").join(format!("{}.plist", label(name))))
+}
+
+/// The launch agent's label.
+#[cfg(target_os = "macos")]
+fn label(name: &str) -> String {
+    format!("rinch.autostart.{}", name)
+}
+
+/// Write a launch agent that runs the app at load (login).
+#[cfg(target_os = "macos")]
+fn write_entry(name: &str, exe: &str, args: &[&str]) -> AutostartResult<()> {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let arguments: String = std::iter::once(exe)
+        .chain(args.iter().copied())
+        .map(|arg| format!("        <string>{}</string>\n", escape(arg)))
+        .collect();
+    let plist = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {}\
+         \x20   </array>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         </dict>\n\
+         </plist>\n",
+        escape(&label(name)),
+        arguments
+    );
+    write_file(entry_path(name), plist)
+}
+
+/// The file holding the app's entry.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn entry_path(name: &str) -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("autostart").join(format!("{}.desktop", name)))
+}
+
+/// Write an XDG autostart entry.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn write_entry(name: &str, exe: &str, args: &[&str]) -> AutostartResult<()> {
+    // Quoted arguments need `"`, `` ` ``, `$` and `\` escaped
+    let quote = |arg: &str| {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    };
+    let command: Vec<String> = std::iter::once(exe).chain(args.iter().copied()).map(quote).collect();
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        name,
+        command.join(" ")
+    );
+    write_file(entry_path(name), entry)
+}
+
+#[cfg(unix)]
+fn write_file(path: Option<PathBuf>, contents: String) -> AutostartResult<()> {
+    let failed = |e: std::io::Error| AutostartError::Failed(e.to_string());
+    let path = path.ok_or_else(|| AutostartError::Failed("no home directory".into()))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(failed)?;
+    }
+    std::fs::write(&path, contents).map_err(failed)
+}
+
+#[cfg(unix)]
+fn remove_entry(name: &str) -> AutostartResult<()> {
+    let Some(path) = entry_path(name) else {
+        return Ok(());
+    };
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AutostartError::Failed(e.to_string())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn has_entry(name: &str) -> bool {
+    entry_path(name).is_some_and(|path| path.exists())
+}
@@ -1,6 +1,10 @@
-//! Cross-platform clipboard support for text and images.
+//! Cross-platform clipboard support for text, HTML, RTF, file lists and images.
 //!
 //! This module provides clipboard operations using the `arboard` crate.
+//! Formatted text is exchanged as HTML with [`copy_html`] and
+//! [`paste_html`], or as RTF with [`copy_rtf`] and [`paste_rtf`]. `arboard`
+//! has no RTF support, so RTF goes through the Windows and macOS clipboard
+//! APIs directly and isn't available on Linux.
 //!
 //! # Example
 //!
@@ -19,8 +23,15 @@
 //! }
 //! ```
 
+use crate::shell::runtime::RinchEvent;
 use arboard::Clipboard;
+use rinch_core::Signal;
+use std::cell::{Cell, RefCell};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
+use winit::event_loop::EventLoopProxy;
 
 /// Clipboard error type.
 #[derive(Debug)]
@@ -29,6 +40,8 @@ pub enum ClipboardError {
     AccessFailed(String),
     /// The clipboard doesn't contain the expected content type.
     ContentTypeMismatch,
+    /// The content type isn't supported on this platform.
+    Unsupported,
 }
 
 impl std::fmt::Display for ClipboardError {
@@ -36,6 +49,7 @@ impl std::fmt::Display for ClipboardError {
         match self {
             ClipboardError::AccessFailed(msg) => write!(f, "clipboard access failed: {}", msg),
             ClipboardError::ContentTypeMismatch => write!(f, "clipboard content type mismatch"),
+            ClipboardError::Unsupported => write!(f, "clipboard content type not supported on this platform"),
         }
     }
 }
@@ -130,6 +144,194 @@ pub fn clear() -> ClipboardResult<()> {
     })
 }
 
+/// Copy HTML to the clipboard, with an optional plain-text alternative.
+///
+/// Applications that understand rich text (word processors, browsers, mail
+/// clients) paste the HTML; others paste `alt_text`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::copy_html;
+///
+/// copy_html("<b>Bold</b> and <i>italic</i>", Some("Bold and italic")).unwrap();
+/// ```
+pub fn copy_html(html: impl AsRef<str>, alt_text: Option<&str>) -> ClipboardResult<()> {
+    with_clipboard(|clipboard| {
+        clipboard.set_html(html.as_ref(), alt_text)?;
+        Ok(())
+    })
+}
+
+/// Paste HTML from the clipboard.
+///
+/// Returns `Err` if the clipboard doesn't contain HTML. Content copied from
+/// other applications may be a fragment or a full document.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::{paste_html, paste_text};
+///
+/// let content = paste_html().or_else(|_| paste_text());
+/// ```
+pub fn paste_html() -> ClipboardResult<String> {
+    with_clipboard(|clipboard| {
+        let html = clipboard.get().html()?;
+        Ok(html)
+    })
+}
+
+/// Check if the clipboard contains HTML.
+pub fn has_html() -> bool {
+    paste_html().is_ok()
+}
+
+/// Copy RTF to the clipboard, with an optional plain-text alternative.
+///
+/// Use this for applications that read RTF but not HTML, such as WordPad
+/// and TextEdit. Returns [`ClipboardError::Unsupported`] on Linux, where
+/// [`copy_html`] is the way to copy formatted text.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::copy_rtf;
+///
+/// copy_rtf(r"{\rtf1\ansi {\b Bold} text}", Some("Bold text")).unwrap();
+/// ```
+pub fn copy_rtf(rtf: impl AsRef<str>, alt_text: Option<&str>) -> ClipboardResult<()> {
+    // Hold the lock so arboard doesn't write at the same time
+    let _guard = CLIPBOARD.lock().unwrap();
+    set_rtf(rtf.as_ref(), alt_text)
+}
+
+/// Paste RTF from the clipboard.
+///
+/// Returns `Err` if the clipboard doesn't contain RTF, or
+/// [`ClipboardError::Unsupported`] on Linux.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::{paste_html, paste_rtf};
+///
+/// if let Ok(rtf) = paste_rtf() {
+///     import_rtf(&rtf);
+/// } else if let Ok(html) = paste_html() {
+///     import_html(&html);
+/// }
+/// ```
+pub fn paste_rtf() -> ClipboardResult<String> {
+    let _guard = CLIPBOARD.lock().unwrap();
+    get_rtf()
+}
+
+/// Check if the clipboard contains RTF.
+pub fn has_rtf() -> bool {
+    paste_rtf().is_ok()
+}
+
+/// Copy a list of file paths to the clipboard.
+///
+/// The paths are placed in the platform's file-list format, so they can be
+/// pasted into Explorer, Finder or a Linux file manager as a file copy.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::copy_files;
+///
+/// copy_files(&["/home/user/report.pdf", "/home/user/notes.txt"]).unwrap();
+/// ```
+pub fn copy_files(paths: &[impl AsRef<Path>]) -> ClipboardResult<()> {
+    with_clipboard(|clipboard| {
+        clipboard.set().file_list(paths)?;
+        Ok(())
+    })
+}
+
+/// Paste a list of file paths from the clipboard.
+///
+/// Returns `Err` if the clipboard doesn't contain a file list, for example
+/// when the user copied text rather than files.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::paste_files;
+///
+/// if let Ok(paths) = paste_files() {
+///     for path in paths {
+///         println!("Pasted: {}", path.display());
+///     }
+/// }
+/// ```
+pub fn paste_files() -> ClipboardResult<Vec<PathBuf>> {
+    with_clipboard(|clipboard| {
+        let paths = clipboard.get().file_list()?;
+        Ok(paths)
+    })
+}
+
+/// Check if the clipboard contains a list of file paths.
+pub fn has_files() -> bool {
+    paste_files().is_ok_and(|paths| !paths.is_empty())
+}
+
+/// Copy text to the primary selection (Linux only).
+///
+/// On Linux, selected text is placed in the primary selection and pasted with
+/// a middle click, independently of the regular clipboard. Call this when the
+/// user selects text in your app so it can be middle-click pasted elsewhere.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::copy_primary;
+///
+/// copy_primary("selected text").unwrap();
+/// ```
+#[cfg(target_os = "linux")]
+pub fn copy_primary(text: impl AsRef<str>) -> ClipboardResult<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    with_clipboard(|clipboard| {
+        clipboard
+            .set()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text(text.as_ref())?;
+        Ok(())
+    })
+}
+
+/// Paste text from the primary selection (Linux only).
+///
+/// Returns the text most recently selected in any application, which is what
+/// a middle click pastes.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::clipboard::paste_primary;
+///
+/// if let Ok(text) = paste_primary() {
+///     println!("Selection: {}", text);
+/// }
+/// ```
+#[cfg(target_os = "linux")]
+pub fn paste_primary() -> ClipboardResult<String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    with_clipboard(|clipboard| {
+        let text = clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()?;
+        Ok(text)
+    })
+}
+
 /// Copy an image to the clipboard.
 ///
 /// The image data should be in RGBA format.
@@ -229,3 +431,505 @@ impl<'a> ImageData<'a> {
         }
     }
 }
+
+// ============================================================================
+// RTF
+// ============================================================================
+
+/// The plain-text clipboard format, UTF-16 with a trailing NUL.
+#[cfg(target_os = "windows")]
+const CF_UNICODETEXT: u32 = 13;
+
+/// Replace the clipboard content with `rtf` and, if given, `alt_text`.
+#[cfg(target_os = "windows")]
+fn set_rtf(rtf: &str, alt_text: Option<&str>) -> ClipboardResult<()> {
+    use windows_sys::Win32::System::DataExchange::EmptyClipboard;
+
+    let format = rtf_format()?;
+    let _open = OpenedClipboard::open()?;
+    // SAFETY: the clipboard is open on this thread.
+    if unsafe { EmptyClipboard() } == 0 {
+        return Err(last_os_error());
+    }
+    let mut bytes = rtf.as_bytes().to_vec();
+    bytes.push(0);
+    set_clipboard_data(format, &bytes)?;
+    if let Some(text) = alt_text {
+        let bytes: Vec<u8> = text.encode_utf16().chain([0]).flat_map(u16::to_ne_bytes).collect();
+        set_clipboard_data(CF_UNICODETEXT, &bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn get_rtf() -> ClipboardResult<String> {
+    use windows_sys::Win32::System::DataExchange::GetClipboardData;
+    use windows_sys::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+    let format = rtf_format()?;
+    let _open = OpenedClipboard::open()?;
+    // SAFETY: the clipboard is open on this thread, and the data is only read
+    // while locked, within the size of its memory block.
+    unsafe {
+        let memory = GetClipboardData(format);
+        if memory.is_null() {
+            return Err(ClipboardError::ContentTypeMismatch);
+        }
+        let data = GlobalLock(memory);
+        if data.is_null() {
+            return Err(last_os_error());
+        }
+        let bytes = std::slice::from_raw_parts(data.cast::<u8>(), GlobalSize(memory));
+        // The block may be larger than the NUL-terminated text
+        let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+        let rtf = String::from_utf8_lossy(&bytes[..end]).into_owned();
+        GlobalUnlock(memory);
+        Ok(rtf)
+    }
+}
+
+/// The "Rich Text Format" clipboard format, registered by name.
+#[cfg(target_os = "windows")]
+fn rtf_format() -> ClipboardResult<u32> {
+    use windows_sys::Win32::System::DataExchange::RegisterClipboardFormatW;
+
+    let name: Vec<u16> = "Rich Text Format\0".encode_utf16().collect();
+    // SAFETY: the name is NUL-terminated.
+    match unsafe { RegisterClipboardFormatW(name.as_ptr()) } {
+        0 => Err(last_os_error()),
+        format => Ok(format),
+    }
+}
+
+/// Put a copy of `bytes` on the open clipboard.
+#[cfg(target_os = "windows")]
+fn set_clipboard_data(format: u32, bytes: &[u8]) -> ClipboardResult<()> {
+    use windows_sys::Win32::Foundation::GlobalFree;
+    use windows_sys::Win32::System::DataExchange::SetClipboardData;
+    use windows_sys::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+    // SAFETY: the block is written while locked and within its size; once
+    // SetClipboardData succeeds the system owns it, otherwise it's freed.
+    unsafe {
+        let memory = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+        if memory.is_null() {
+            return Err(last_os_error());
+        }
+        let data = GlobalLock(memory);
+        if data.is_null() {
+            let error = last_os_error();
+            GlobalFree(memory);
+            return Err(error);
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.cast::<u8>(), bytes.len());
+        GlobalUnlock(memory);
+        if SetClipboardData(format, memory).is_null() {
+            let error = last_os_error();
+            GlobalFree(memory);
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn last_os_error() -> ClipboardError {
+    ClipboardError::AccessFailed(std::io::Error::last_os_error().to_string())
+}
+
+/// Keeps the clipboard open on this thread, closing it when dropped.
+#[cfg(target_os = "windows")]
+struct OpenedClipboard;
+
+#[cfg(target_os = "windows")]
+impl OpenedClipboard {
+    /// Open the clipboard, retrying briefly while another app has it open.
+    fn open() -> ClipboardResult<Self> {
+        use windows_sys::Win32::System::DataExchange::OpenClipboard;
+
+        for _ in 0..10 {
+            // SAFETY: a null owner associates the clipboard with this task.
+            if unsafe { OpenClipboard(std::ptr::null_mut()) } != 0 {
+                return Ok(Self);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Err(last_os_error())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        // SAFETY: the clipboard was opened by this thread.
+        unsafe { windows_sys::Win32::System::DataExchange::CloseClipboard() };
+    }
+}
+
+/// Replace the pasteboard content with `rtf` and, if given, `alt_text`.
+#[cfg(target_os = "macos")]
+fn set_rtf(rtf: &str, alt_text: Option<&str>) -> ClipboardResult<()> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeRTF, NSPasteboardTypeString};
+    use objc2_foundation::{NSData, NSString};
+
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.clearContents();
+    // SAFETY: the type names are immutable strings exported by AppKit.
+    let (rtf_type, string_type) = unsafe { (NSPasteboardTypeRTF, NSPasteboardTypeString) };
+    let mut written = pasteboard.setData_forType(Some(&NSData::with_bytes(rtf.as_bytes())), rtf_type);
+    if let Some(text) = alt_text {
+        written &= pasteboard.setString_forType(&NSString::from_str(text), string_type);
+    }
+    if !written {
+        return Err(ClipboardError::AccessFailed("the pasteboard didn't accept the content".into()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn get_rtf() -> ClipboardResult<String> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeRTF};
+
+    // SAFETY: the type name is an immutable string exported by AppKit.
+    let rtf_type = unsafe { NSPasteboardTypeRTF };
+    let data = NSPasteboard::generalPasteboard()
+        .dataForType(rtf_type)
+        .ok_or(ClipboardError::ContentTypeMismatch)?;
+    Ok(String::from_utf8_lossy(&data.to_vec()).into_owned())
+}
+
+/// Linux clipboards are served by `arboard`, which can't offer RTF.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn set_rtf(_rtf: &str, _alt_text: Option<&str>) -> ClipboardResult<()> {
+    Err(ClipboardError::Unsupported)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn get_rtf() -> ClipboardResult<String> {
+    Err(ClipboardError::Unsupported)
+}
+
+// ============================================================================
+// Change notifications
+// ============================================================================
+
+/// How often the watcher thread checks the clipboard for changes where the
+/// platform doesn't report them.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Identifies a clipboard state observed by [`watch`].
+///
+/// The stamp changes every time the system clipboard changes, whether the
+/// change was made by this application or another one. Compare stamps to
+/// find out whether the clipboard changed since you last looked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ClipboardStamp(u64);
+
+impl ClipboardStamp {
+    /// The number of clipboard changes observed since watching started.
+    pub fn sequence(self) -> u64 {
+        self.0
+    }
+}
+
+thread_local! {
+    /// Signal updated by the runtime when the watcher reports a change.
+    static WATCH_SIGNAL: RefCell<Option<Signal<ClipboardStamp>>> = const { RefCell::new(None) };
+    /// Whether the watcher thread has been started.
+    static WATCHER_STARTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Watch the system clipboard for changes.
+///
+/// Returns a signal whose [`ClipboardStamp`] changes whenever the clipboard
+/// content changes, and the UI re-renders when it does. Every call returns the
+/// same signal, so it's fine to call this from a component. This is useful
+/// for clipboard history panels or enabling a "Paste as..." menu item only
+/// when the clipboard holds a matching format.
+///
+/// Nothing watches the clipboard until this is first called. From then on
+/// a background thread waits for changes:
+///
+/// - On Windows and X11 the system reports changes, so the thread sleeps
+///   until one happens.
+/// - On macOS the thread reads the pasteboard's change count every 500 ms,
+///   which is cheap: no content is read.
+/// - On Wayland, or where the above can't be set up, the thread reads and
+///   hashes the clipboard's text, HTML and file list every 500 ms, and its
+///   image while it holds nothing else. Large clipboard content makes this
+///   costly, so only watch while something needs the stamp.
+///
+/// Where the clipboard is checked periodically, changes are reported up to
+/// 500 ms late.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+/// use rinch::clipboard::{has_html, watch};
+///
+/// fn app() -> Element {
+///     let clipboard = watch();
+///
+///     // Re-evaluated on every render, including after clipboard changes
+///     let status = if has_html() { "Formatted text" } else { "Plain content" };
+///
+///     rsx! {
+///         Window { title: "Clipboard Monitor",
+///             p { "Changes seen: " {clipboard.get().sequence()} }
+///             p { "Clipboard holds: " {status} }
+///         }
+///     }
+/// }
+/// ```
+pub fn watch() -> Signal<ClipboardStamp> {
+    let signal = WATCH_SIGNAL.with(|s| {
+        s.borrow_mut()
+            .get_or_insert_with(|| Signal::new(ClipboardStamp::default()))
+            .clone()
+    });
+    start_watcher();
+    signal
+}
+
+/// Start the watcher thread if [`watch`] has been called and the event loop
+/// is running (called by runtime once the event loop proxy is available).
+pub(crate) fn start_watcher() {
+    if WATCHER_STARTED.with(Cell::get) || WATCH_SIGNAL.with(|s| s.borrow().is_none()) {
+        return;
+    }
+    let Some(proxy) = crate::windows::event_proxy() else {
+        // watch() was called before the event loop started; the runtime
+        // calls start_watcher() again once it's running
+        return;
+    };
+    WATCHER_STARTED.with(|started| started.set(true));
+
+    let spawned = std::thread::Builder::new()
+        .name("rinch-clipboard-watcher".into())
+        .spawn(move || {
+            if let Err(e) = listen(&proxy) {
+                tracing::debug!("No clipboard change notifications ({}), polling the clipboard instead", e);
+                poll(&proxy);
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to start clipboard watcher: {}", e);
+    }
+}
+
+/// Send `ClipboardChanged` when Windows reports a clipboard change, until the
+/// event loop exits.
+#[cfg(target_os = "windows")]
+fn listen(proxy: &EventLoopProxy<RinchEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::DataExchange::AddClipboardFormatListener;
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, PostQuitMessage, RegisterClassW,
+        HWND_MESSAGE, MSG, WM_CLIPBOARDUPDATE, WNDCLASSW,
+    };
+
+    thread_local! {
+        /// The proxy for `window_proc`, which runs on this thread.
+        static PROXY: RefCell<Option<EventLoopProxy<RinchEvent>>> = const { RefCell::new(None) };
+    }
+
+    unsafe extern "system" fn window_proc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if message == WM_CLIPBOARDUPDATE {
+            let sent = PROXY.with(|proxy| {
+                proxy
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|proxy| proxy.send_event(RinchEvent::ClipboardChanged).is_ok())
+            });
+            if !sent {
+                // The event loop has exited
+                // SAFETY: ends the message loop of this thread.
+                unsafe { PostQuitMessage(0) };
+            }
+            return 0;
+        }
+        // SAFETY: passes the message on unchanged.
+        unsafe { DefWindowProcW(window, message, wparam, lparam) }
+    }
+
+    PROXY.with(|p| *p.borrow_mut() = Some(proxy.clone()));
+    let class_name: Vec<u16> = "RinchClipboardWatcher\0".encode_utf16().collect();
+
+    // SAFETY: the class name outlives the class and window, which only live
+    // as long as this thread; a message-only window is never shown.
+    let window = unsafe {
+        let instance = GetModuleHandleW(std::ptr::null());
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance,
+            lpszClassName: class_name.as_ptr(),
+            ..std::mem::zeroed()
+        };
+        if RegisterClassW(&class) == 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            std::ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            std::ptr::null_mut(),
+            instance,
+            std::ptr::null(),
+        )
+    };
+    if window.is_null() {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // SAFETY: `window` is a window of this thread.
+    if unsafe { AddClipboardFormatListener(window) } == 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    // SAFETY: MSG is plain data, filled in by GetMessageW.
+    let mut message: MSG = unsafe { std::mem::zeroed() };
+    // GetMessageW returns 0 once PostQuitMessage is called and -1 on errors
+    while unsafe { GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) } > 0 {
+        // SAFETY: dispatches the message GetMessageW just filled in.
+        unsafe { DispatchMessageW(&message) };
+    }
+    Ok(())
+}
+
+/// Send `ClipboardChanged` when the pasteboard's change count moves, until
+/// the event loop exits.
+///
+/// macOS doesn't notify apps of pasteboard changes. Reading the change
+/// count is how they find out, and is cheap: no content is read.
+#[cfg(target_os = "macos")]
+fn listen(proxy: &EventLoopProxy<RinchEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    let pasteboard = objc2_app_kit::NSPasteboard::generalPasteboard();
+    let mut last = pasteboard.changeCount();
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        let count = pasteboard.changeCount();
+        if count != last {
+            last = count;
+            if proxy.send_event(RinchEvent::ClipboardChanged).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Send `ClipboardChanged` when the X11 `CLIPBOARD` selection changes owner,
+/// until the event loop exits.
+///
+/// Wayland has no such notification for apps without keyboard focus, so
+/// Wayland sessions fall back to [`poll`].
+#[cfg(all(unix, not(target_os = "macos")))]
+fn listen(proxy: &EventLoopProxy<RinchEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, WindowClass};
+    use x11rb::protocol::Event;
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Err("Wayland sessions don't report clipboard changes".into());
+    }
+
+    let (conn, screen) = x11rb::connect(None)?;
+    conn.xfixes_query_version(5, 0)?.reply()?;
+    let root = conn.setup().roots[screen].root;
+    let window = conn.generate_id()?;
+    conn.create_window(
+        x11rb::COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new(),
+    )?;
+    let clipboard = conn.intern_atom(false, b"CLIPBOARD")?.reply()?.atom;
+    conn.xfixes_select_selection_input(
+        window,
+        clipboard,
+        SelectionEventMask::SET_SELECTION_OWNER
+            | SelectionEventMask::SELECTION_WINDOW_DESTROY
+            | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+    )?;
+    conn.flush()?;
+
+    loop {
+        if let Event::XfixesSelectionNotify(_) = conn.wait_for_event()?
+            && proxy.send_event(RinchEvent::ClipboardChanged).is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn listen(_proxy: &EventLoopProxy<RinchEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    Err("this platform doesn't report clipboard changes".into())
+}
+
+/// Send `ClipboardChanged` when the clipboard's content changes, reading it
+/// every [`WATCH_INTERVAL`], until the event loop exits.
+fn poll(proxy: &EventLoopProxy<RinchEvent>) {
+    let mut clipboard = match Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            tracing::warn!("Clipboard watcher could not access the clipboard: {}", e);
+            return;
+        }
+    };
+    let mut last = fingerprint(&mut clipboard);
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        let current = fingerprint(&mut clipboard);
+        if current != last {
+            last = current;
+            if proxy.send_event(RinchEvent::ClipboardChanged).is_err() {
+                // The event loop has exited
+                break;
+            }
+        }
+    }
+}
+
+/// Advance the watched stamp (called by runtime on `RinchEvent::ClipboardChanged`).
+pub(crate) fn notify_changed() {
+    WATCH_SIGNAL.with(|s| {
+        if let Some(signal) = s.borrow().as_ref() {
+            signal.update(|stamp| stamp.0 += 1);
+        }
+    });
+}
+
+/// Hash the current clipboard content so changes can be detected.
+///
+/// Images are only read when the clipboard has no text, HTML or file list,
+/// since decoding them is comparatively expensive.
+fn fingerprint(clipboard: &mut Clipboard) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let text = clipboard.get_text().ok();
+    let html = clipboard.get().html().ok();
+    let files = clipboard.get().file_list().ok();
+    if text.is_none()
+        && html.is_none()
+        && files.is_none()
+        && let Ok(image) = clipboard.get_image()
+    {
+        (image.width, image.height, &image.bytes[..]).hash(&mut hasher);
+    }
+    (text, html, files).hash(&mut hasher);
+    hasher.finish()
+}
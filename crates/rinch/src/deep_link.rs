@@ -0,0 +1,334 @@
+//! Opening the app from custom URL schemes (`myapp://...`).
+//!
+//! [`register`] associates a URL scheme with the running executable and
+//! makes the app single-instance for links: when the OS starts a second
+//! instance to open a link, that instance hands the URL to the one already
+//! running and exits. URLs reach the callbacks registered with
+//! [`on_open_url`] on the UI thread, whether the app was launched with the
+//! link or was already running.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::deep_link;
+//!
+//! fn main() {
+//!     // Forwards the link and exits if the app is already running
+//!     if let Err(e) = deep_link::register("myapp") {
+//!         eprintln!("Deep links unavailable: {e}");
+//!     }
+//!     deep_link::on_open_url(|url| open_document_from_link(url));
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! # Platform support
+//!
+//! - **Windows**: the scheme is registered under `HKEY_CURRENT_USER\Software\Classes`.
+//! - **Linux**: a `.desktop` file handling `x-scheme-handler/<scheme>` is
+//!   written to `~/.local/share/applications` and made the default with
+//!   `xdg-mime`.
+//! - **macOS**: the scheme must be declared under `CFBundleURLTypes` in the
+//!   app bundle's `Info.plist`, which Launch Services reads when the bundle
+//!   is installed. Links arrive as `GetURL` Apple Events, and Launch Services
+//!   sends them to the running instance itself, so nothing is forwarded.
+
+use std::cell::RefCell;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::path::PathBuf;
+use std::rc::Rc;
+
+type UrlCallback = Rc<dyn Fn(&str)>;
+
+thread_local! {
+    static CALLBACKS: RefCell<Vec<UrlCallback>> = const { RefCell::new(Vec::new()) };
+    /// URLs that arrived before any callback was registered.
+    static PENDING: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Deep link error type.
+#[derive(Debug)]
+pub enum DeepLinkError {
+    /// The scheme isn't a valid URL scheme.
+    InvalidScheme(String),
+    /// Registering the scheme with the OS failed.
+    RegistrationFailed(String),
+    /// Deep links aren't supported on this platform.
+    Unsupported,
+}
+
+impl std::fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeepLinkError::InvalidScheme(scheme) => write!(f, "invalid URL scheme: {:?}", scheme),
+            DeepLinkError::RegistrationFailed(msg) => write!(f, "failed to register URL scheme: {}", msg),
+            DeepLinkError::Unsupported => write!(f, "deep links are not supported on this platform"),
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+/// Result type for deep link operations.
+pub type DeepLinkResult<T> = Result<T, DeepLinkError>;
+
+/// Register the app as the handler of `scheme` URLs.
+///
+/// Call this at the start of `main`. If the app was launched with a link and
+/// another instance is already running, the link is handed to that instance
+/// and this process exits. Otherwise links from the command line are queued
+/// for [`on_open_url`], and this instance starts accepting links from later
+/// ones.
+pub fn register(scheme: &str) -> DeepLinkResult<()> {
+    let scheme = scheme.to_ascii_lowercase();
+    if !is_valid_scheme(&scheme) {
+        return Err(DeepLinkError::InvalidScheme(scheme));
+    }
+    if cfg!(target_os = "macos") {
+        return register_scheme(&scheme);
+    }
+
+    let urls: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| is_link(arg, &scheme))
+        .collect();
+    let channel = format!("deep-link-{}", scheme);
+    if !urls.is_empty() && crate::instance::forward(&channel, &urls) {
+        tracing::info!("Forwarded {} link(s) to the running instance", urls.len());
+        std::process::exit(0);
+    }
+
+    register_scheme(&scheme)?;
+    let listened_scheme = scheme.clone();
+    crate::instance::listen(&channel, move |messages| {
+        let urls: Vec<String> = messages
+            .into_iter()
+            .filter(|message| is_link(message, &listened_scheme))
+            .collect();
+        receive(urls);
+    });
+    for url in urls {
+        crate::tasks::app_handle().run_on_ui_thread(move || open_url(url));
+    }
+    Ok(())
+}
+
+/// Call `callback` with every link the app is opened with.
+///
+/// Links that arrived before the first callback was registered are
+/// delivered to it straight away.
+pub fn on_open_url(callback: impl Fn(&str) + 'static) {
+    let callback: UrlCallback = Rc::new(callback);
+    CALLBACKS.with(|callbacks| callbacks.borrow_mut().push(callback.clone()));
+    for url in PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut())) {
+        callback(&url);
+    }
+}
+
+/// Deliver links that arrived while the app was running, on the UI thread.
+fn receive(urls: Vec<String>) {
+    if urls.is_empty() {
+        return;
+    }
+    crate::tasks::app_handle().run_on_ui_thread(move || {
+        // The user expects the app to come to the front
+        crate::windows::show_windows();
+        for url in urls {
+            open_url(url);
+        }
+    });
+}
+
+/// Deliver a link to the callbacks, or keep it until one is registered.
+fn open_url(url: String) {
+    let callbacks = CALLBACKS.with(|callbacks| callbacks.borrow().clone());
+    if callbacks.is_empty() {
+        PENDING.with(|pending| pending.borrow_mut().push(url));
+        return;
+    }
+    for callback in callbacks {
+        callback(&url);
+    }
+}
+
+/// Whether `scheme` is a valid URL scheme (RFC 3986).
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Whether a command line argument is a link with the scheme.
+fn is_link(arg: &str, scheme: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(prefix, _)| prefix.eq_ignore_ascii_case(scheme))
+}
+
+/// Point `HKEY_CURRENT_USER\Software\Classes\<scheme>` at this executable.
+#[cfg(target_os = "windows")]
+fn register_scheme(scheme: &str) -> DeepLinkResult<()> {
+    use windows_sys::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let exe = std::env::current_exe().map_err(|e| DeepLinkError::RegistrationFailed(e.to_string()))?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let key = format!("Software\\Classes\\{}", scheme);
+    let values = [
+        (key.clone(), None, format!("URL:{} Protocol", scheme)),
+        (key.clone(), Some("URL Protocol"), String::new()),
+        (format!("{}\\shell\\open\\command", key), None, command),
+    ];
+
+    let wide = |s: &str| -> Vec<u16> { s.encode_utf16().chain(std::iter::once(0)).collect() };
+    for (key, name, data) in values {
+        let key = wide(&key);
+        let name = name.map(wide);
+        let data = wide(&data);
+        // SAFETY: the strings are NUL-terminated and `data` is `data.len()` UTF-16 units.
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()),
+                REG_SZ,
+                data.as_ptr().cast(),
+                (data.len() * std::mem::size_of::<u16>()) as u32,
+            )
+        };
+        if status != 0 {
+            return Err(DeepLinkError::RegistrationFailed(format!("registry error {}", status)));
+        }
+    }
+    Ok(())
+}
+
+/// Write a `.desktop` file handling the scheme and make it the default.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn register_scheme(scheme: &str) -> DeepLinkResult<()> {
+    let failed = |e: std::io::Error| DeepLinkError::RegistrationFailed(e.to_string());
+
+    let exe = std::env::current_exe().map_err(failed)?;
+    let name = exe
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| scheme.to_string());
+    let applications = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| DeepLinkError::RegistrationFailed("no home directory".into()))?
+        .join("applications");
+    let file_name = format!("rinch-{}-handler.desktop", scheme);
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\" %u\nTerminal=false\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        name,
+        exe.display(),
+        scheme
+    );
+    std::fs::create_dir_all(&applications).map_err(failed)?;
+    std::fs::write(applications.join(&file_name), entry).map_err(failed)?;
+
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", &file_name, &format!("x-scheme-handler/{}", scheme)])
+        .status()
+        .map_err(failed)?;
+    if !status.success() {
+        return Err(DeepLinkError::RegistrationFailed(format!("xdg-mime exited with {}", status)));
+    }
+    Ok(())
+}
+
+/// Check that the bundle declares the scheme and start handling `GetURL`
+/// Apple Events.
+#[cfg(target_os = "macos")]
+fn register_scheme(scheme: &str) -> DeepLinkResult<()> {
+    if !apple_events::bundle_declares(scheme) {
+        return Err(DeepLinkError::RegistrationFailed(format!(
+            "the app bundle's Info.plist doesn't declare the {:?} scheme under CFBundleURLTypes",
+            scheme
+        )));
+    }
+    apple_events::install_handler();
+    Ok(())
+}
+
+/// Receiving links as Apple Events.
+#[cfg(target_os = "macos")]
+mod apple_events {
+    use objc2::rc::Retained;
+    use objc2::runtime::NSObject;
+    use objc2::{define_class, msg_send, sel, AllocAnyThread};
+    use objc2_foundation::{
+        ns_string, NSAppleEventDescriptor, NSAppleEventManager, NSArray, NSBundle, NSDictionary, NSString,
+    };
+
+    /// `kInternetEventClass` and `kAEGetURL`, which are both 'GURL'.
+    const GET_URL: u32 = u32::from_be_bytes(*b"GURL");
+    /// `keyDirectObject`, the parameter holding the URL.
+    const DIRECT_OBJECT: u32 = u32::from_be_bytes(*b"----");
+
+    define_class!(
+        /// Target of the `GetURL` Apple Event handler.
+        #[unsafe(super(NSObject))]
+        struct UrlHandler;
+
+        impl UrlHandler {
+            #[unsafe(method(handleGetURLEvent:withReplyEvent:))]
+            fn handle_get_url(&self, event: &NSAppleEventDescriptor, _reply: &NSAppleEventDescriptor) {
+                // SAFETY: paramDescriptorForKeyword: takes an AEKeyword (a
+                // four-character code) and returns a descriptor or nil.
+                let url: Option<Retained<NSAppleEventDescriptor>> =
+                    unsafe { msg_send![event, paramDescriptorForKeyword: DIRECT_OBJECT] };
+                if let Some(url) = url.and_then(|url| url.stringValue()) {
+                    super::receive(vec![url.to_string()]);
+                }
+            }
+        }
+    );
+
+    /// Handle `GetURL` events, including the one the app was launched with,
+    /// which arrives once the event loop starts.
+    pub(super) fn install_handler() {
+        let handler = UrlHandler::alloc().set_ivars(());
+        // SAFETY: NSObject's init takes no arguments.
+        let handler: Retained<UrlHandler> = unsafe { msg_send![super(handler), init] };
+        let manager = NSAppleEventManager::sharedAppleEventManager();
+        // SAFETY: the handler implements the selector with the signature
+        // Apple Event handlers are called with.
+        let _: () = unsafe {
+            msg_send![
+                &*manager,
+                setEventHandler: &*handler,
+                andSelector: sel!(handleGetURLEvent:withReplyEvent:),
+                forEventClass: GET_URL,
+                andEventID: GET_URL
+            ]
+        };
+        // The event manager doesn't retain its handlers
+        std::mem::forget(handler);
+    }
+
+    /// Whether the app bundle's `Info.plist` lists the scheme under
+    /// `CFBundleURLTypes`.
+    pub(super) fn bundle_declares(scheme: &str) -> bool {
+        let Some(url_types) = NSBundle::mainBundle().objectForInfoDictionaryKey(ns_string!("CFBundleURLTypes"))
+        else {
+            return false;
+        };
+        let Some(url_types) = url_types.downcast_ref::<NSArray>() else {
+            return false;
+        };
+        url_types.iter().any(|url_type| {
+            url_type
+                .downcast_ref::<NSDictionary>()
+                .and_then(|url_type| url_type.objectForKey(ns_string!("CFBundleURLSchemes")))
+                .is_some_and(|schemes| {
+                    schemes.downcast_ref::<NSArray>().is_some_and(|schemes| {
+                        schemes.iter().any(|declared| {
+                            declared
+                                .downcast_ref::<NSString>()
+                                .is_some_and(|declared| declared.to_string().eq_ignore_ascii_case(scheme))
+                        })
+                    })
+                })
+        })
+    }
+}
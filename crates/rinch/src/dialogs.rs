@@ -25,18 +25,63 @@
 //!     println!("Save to: {}", path.display());
 //! }
 //!
-//! // Pick a folder
-//! if let Some(path) = pick_folder().pick() {
+//! // Pick a folder, modal to the window the user is working in
+//! let mut folder = pick_folder();
+//! if let Some(window) = rinch::windows::current_window() {
+//!     folder = folder.set_parent(&window);
+//! }
+//! if let Some(path) = folder.pick() {
 //!     println!("Folder: {}", path.display());
 //! }
+//!
+//! // Ask with custom buttons
+//! #[derive(Clone, PartialEq)]
+//! enum Choice { Save, DontSave, Cancel }
+//!
+//! let choice = message("Save changes before closing?")
+//!     .add_button("Save", Choice::Save)
+//!     .add_button("Don't Save", Choice::DontSave)
+//!     .add_button("Cancel", Choice::Cancel)
+//!     .cancel_button(Choice::Cancel)
+//!     .choose();
 //! ```
 
-use rfd::{FileDialog, MessageDialog, MessageButtons};
+use crate::windows::WindowHandle;
+use rfd::{FileDialog, MessageDialog, MessageButtons, MessageDialogResult};
 use std::path::{Path, PathBuf};
 
 // Re-export MessageLevel for convenience
 pub use rfd::MessageLevel;
 
+/// An rfd dialog that can be made modal to a window.
+trait ParentedDialog: Sized {
+    fn with_parent(self, parent: &winit::window::Window) -> Self;
+}
+
+impl ParentedDialog for FileDialog {
+    fn with_parent(self, parent: &winit::window::Window) -> Self {
+        self.set_parent(parent)
+    }
+}
+
+impl ParentedDialog for MessageDialog {
+    fn with_parent(self, parent: &winit::window::Window) -> Self {
+        self.set_parent(parent)
+    }
+}
+
+/// Make `dialog` modal to `window`, leaving it as it is if the window
+/// isn't open.
+fn set_parent<D: ParentedDialog>(dialog: D, window: &WindowHandle) -> D {
+    match crate::windows::native_window(*window) {
+        Some(parent) => dialog.with_parent(&parent),
+        None => {
+            tracing::warn!("Dialog parent window {:?} is not open", window);
+            dialog
+        }
+    }
+}
+
 /// Builder for opening files.
 ///
 /// # Example
@@ -68,6 +113,15 @@ impl OpenFileDialog {
         self
     }
 
+    /// Make the dialog modal to a window, keeping it in front of that window.
+    ///
+    /// Use [`current_window`](crate::windows::current_window) to get the
+    /// window the user is interacting with.
+    pub fn set_parent(mut self, window: &WindowHandle) -> Self {
+        self.dialog = set_parent(self.dialog, window);
+        self
+    }
+
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
         self.dialog = self.dialog.set_directory(path);
@@ -128,6 +182,15 @@ impl SaveFileDialog {
         self
     }
 
+    /// Make the dialog modal to a window, keeping it in front of that window.
+    ///
+    /// Use [`current_window`](crate::windows::current_window) to get the
+    /// window the user is interacting with.
+    pub fn set_parent(mut self, window: &WindowHandle) -> Self {
+        self.dialog = set_parent(self.dialog, window);
+        self
+    }
+
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
         self.dialog = self.dialog.set_directory(path);
@@ -188,6 +251,15 @@ impl FolderDialog {
         self
     }
 
+    /// Make the dialog modal to a window, keeping it in front of that window.
+    ///
+    /// Use [`current_window`](crate::windows::current_window) to get the
+    /// window the user is interacting with.
+    pub fn set_parent(mut self, window: &WindowHandle) -> Self {
+        self.dialog = set_parent(self.dialog, window);
+        self
+    }
+
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
         self.dialog = self.dialog.set_directory(path);
@@ -247,6 +319,15 @@ impl MessageDialogBuilder {
         self
     }
 
+    /// Make the dialog modal to a window, keeping it in front of that window.
+    ///
+    /// Use [`current_window`](crate::windows::current_window) to get the
+    /// window the user is interacting with.
+    pub fn set_parent(mut self, window: &WindowHandle) -> Self {
+        self.dialog = set_parent(self.dialog, window);
+        self
+    }
+
     /// Set the message level (Info, Warning, Error).
     pub fn set_level(mut self, level: MessageLevel) -> Self {
         self.dialog = self.dialog.set_level(level);
@@ -273,6 +354,173 @@ impl MessageDialogBuilder {
             .show()
             == rfd::MessageDialogResult::Yes
     }
+
+    /// Add a button with a custom label, returning a builder for a dialog
+    /// whose result is the value of the chosen button.
+    ///
+    /// See [`ChoiceDialogBuilder`] for details.
+    pub fn add_button<T: Clone + PartialEq>(
+        self,
+        label: impl Into<String>,
+        value: T,
+    ) -> ChoiceDialogBuilder<T> {
+        ChoiceDialogBuilder {
+            dialog: self.dialog,
+            buttons: Vec::new(),
+            default_button: None,
+            cancel_button: None,
+        }
+        .add_button(label, value)
+    }
+}
+
+/// Maximum number of buttons supported by native message dialogs.
+const MAX_DIALOG_BUTTONS: usize = 3;
+
+/// Builder for message dialogs with custom buttons and a typed result.
+///
+/// Created with [`MessageDialogBuilder::add_button`]. Up to three buttons
+/// are supported. The default button is the one activated by Enter, and the
+/// cancel button is activated by Escape or by closing the dialog. The
+/// platform decides where each button is placed.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::dialogs::{message, MessageLevel};
+///
+/// #[derive(Clone, PartialEq)]
+/// enum Choice { Save, DontSave, Cancel }
+///
+/// let choice = message("Do you want to save the changes made to \"notes.txt\"?")
+///     .set_level(MessageLevel::Warning)
+///     .add_button("Save", Choice::Save)
+///     .add_button("Don't Save", Choice::DontSave)
+///     .add_button("Cancel", Choice::Cancel)
+///     .default_button(Choice::Save)
+///     .cancel_button(Choice::Cancel)
+///     .choose();
+///
+/// match choice {
+///     Choice::Save => { /* save, then close */ }
+///     Choice::DontSave => { /* close */ }
+///     Choice::Cancel => { /* keep editing */ }
+/// }
+/// ```
+pub struct ChoiceDialogBuilder<T> {
+    dialog: MessageDialog,
+    buttons: Vec<(String, T)>,
+    default_button: Option<T>,
+    cancel_button: Option<T>,
+}
+
+impl<T: Clone + PartialEq> ChoiceDialogBuilder<T> {
+    /// Add another button. Buttons beyond the third are ignored.
+    pub fn add_button(mut self, label: impl Into<String>, value: T) -> Self {
+        let label = label.into();
+        if self.buttons.len() >= MAX_DIALOG_BUTTONS {
+            tracing::warn!(
+                "Message dialogs support at most {} buttons, ignoring \"{}\"",
+                MAX_DIALOG_BUTTONS,
+                label
+            );
+            return self;
+        }
+        self.buttons.push((label, value));
+        self
+    }
+
+    /// Set the button activated by Enter (default: the first non-cancel button).
+    pub fn default_button(mut self, value: T) -> Self {
+        self.default_button = Some(value);
+        self
+    }
+
+    /// Set the button activated by Escape or by closing the dialog
+    /// (default: the last button).
+    pub fn cancel_button(mut self, value: T) -> Self {
+        self.cancel_button = Some(value);
+        self
+    }
+
+    /// Set the dialog title.
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.dialog = self.dialog.set_title(title);
+        self
+    }
+
+    /// Make the dialog modal to a window, keeping it in front of that window.
+    ///
+    /// Use [`current_window`](crate::windows::current_window) to get the
+    /// window the user is interacting with.
+    pub fn set_parent(mut self, window: &WindowHandle) -> Self {
+        self.dialog = set_parent(self.dialog, window);
+        self
+    }
+
+    /// Set the message level (Info, Warning, Error).
+    pub fn set_level(mut self, level: MessageLevel) -> Self {
+        self.dialog = self.dialog.set_level(level);
+        self
+    }
+
+    /// Show the dialog and return the value of the chosen button.
+    pub fn choose(self) -> T {
+        let buttons = self.ordered_buttons();
+        let labels: Vec<String> = buttons.iter().map(|(label, _)| label.clone()).collect();
+
+        let native_buttons = match labels.as_slice() {
+            [ok] => MessageButtons::OkCustom(ok.clone()),
+            [ok, cancel] => MessageButtons::OkCancelCustom(ok.clone(), cancel.clone()),
+            [yes, no, cancel] => {
+                MessageButtons::YesNoCancelCustom(yes.clone(), no.clone(), cancel.clone())
+            }
+            _ => unreachable!("choice dialogs have between one and three buttons"),
+        };
+
+        let result = self.dialog.set_buttons(native_buttons).show();
+        let index = chosen_index(result, &labels);
+
+        buttons.into_iter().nth(index).map(|(_, value)| value).expect("button index in range")
+    }
+
+    /// Order buttons by role: default first, cancel last.
+    ///
+    /// Native dialogs treat the first button as the default and the last as
+    /// the cancel button.
+    fn ordered_buttons(&self) -> Vec<(String, T)> {
+        let mut buttons = self.buttons.clone();
+
+        if let Some(value) = &self.cancel_button
+            && let Some(idx) = buttons.iter().position(|(_, v)| v == value)
+        {
+            let button = buttons.remove(idx);
+            buttons.push(button);
+        }
+
+        if let Some(value) = &self.default_button
+            && let Some(idx) = buttons.iter().position(|(_, v)| v == value)
+        {
+            let button = buttons.remove(idx);
+            buttons.insert(0, button);
+        }
+
+        buttons
+    }
+}
+
+/// Find which of the ordered buttons with `labels` a dialog result stands for.
+///
+/// Backends report custom buttons by label, but some fall back to the
+/// standard result for the button's position.
+fn chosen_index(result: MessageDialogResult, labels: &[String]) -> usize {
+    let last = labels.len() - 1;
+    match result {
+        MessageDialogResult::Custom(label) => labels.iter().position(|l| *l == label).unwrap_or(last),
+        MessageDialogResult::Ok | MessageDialogResult::Yes => 0,
+        MessageDialogResult::No if labels.len() == MAX_DIALOG_BUTTONS => 1,
+        _ => last,
+    }
 }
 
 /// Create an open file dialog builder.
@@ -340,3 +588,69 @@ pub fn pick_folder() -> FolderDialog {
 pub fn message(text: impl Into<String>) -> MessageDialogBuilder {
     MessageDialogBuilder::new(text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Choice {
+        Save,
+        DontSave,
+        Cancel,
+    }
+
+    fn labels(builder: &ChoiceDialogBuilder<Choice>) -> Vec<String> {
+        builder.ordered_buttons().into_iter().map(|(label, _)| label).collect()
+    }
+
+    fn save_dialog() -> ChoiceDialogBuilder<Choice> {
+        message("Save changes?")
+            .add_button("Cancel", Choice::Cancel)
+            .add_button("Save", Choice::Save)
+            .add_button("Don't Save", Choice::DontSave)
+    }
+
+    #[test]
+    fn buttons_keep_their_order_without_roles() {
+        assert_eq!(labels(&save_dialog()), ["Cancel", "Save", "Don't Save"]);
+    }
+
+    #[test]
+    fn default_goes_first_and_cancel_last() {
+        let builder = save_dialog().default_button(Choice::Save).cancel_button(Choice::Cancel);
+        assert_eq!(labels(&builder), ["Save", "Don't Save", "Cancel"]);
+
+        let values: Vec<Choice> = builder.ordered_buttons().into_iter().map(|(_, value)| value).collect();
+        assert_eq!(values, [Choice::Save, Choice::DontSave, Choice::Cancel]);
+    }
+
+    #[test]
+    fn roles_of_missing_buttons_are_ignored() {
+        let builder = message("Save changes?")
+            .add_button("Save", Choice::Save)
+            .add_button("Don't Save", Choice::DontSave)
+            .cancel_button(Choice::Cancel);
+        assert_eq!(labels(&builder), ["Save", "Don't Save"]);
+    }
+
+    #[test]
+    fn buttons_beyond_the_third_are_dropped() {
+        let builder = save_dialog().add_button("Later", Choice::Cancel);
+        assert_eq!(labels(&builder), ["Cancel", "Save", "Don't Save"]);
+    }
+
+    #[test]
+    fn results_map_to_button_positions() {
+        let three: Vec<String> = ["Save", "Don't Save", "Cancel"].map(String::from).to_vec();
+        assert_eq!(chosen_index(MessageDialogResult::Custom("Don't Save".into()), &three), 1);
+        assert_eq!(chosen_index(MessageDialogResult::Custom("Unknown".into()), &three), 2);
+        assert_eq!(chosen_index(MessageDialogResult::Yes, &three), 0);
+        assert_eq!(chosen_index(MessageDialogResult::No, &three), 1);
+        assert_eq!(chosen_index(MessageDialogResult::Cancel, &three), 2);
+
+        let two: Vec<String> = ["Save", "Cancel"].map(String::from).to_vec();
+        assert_eq!(chosen_index(MessageDialogResult::Ok, &two), 0);
+        assert_eq!(chosen_index(MessageDialogResult::No, &two), 1);
+    }
+}
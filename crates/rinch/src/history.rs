@@ -0,0 +1,337 @@
+//! Browser-style back and forward navigation.
+//!
+//! Every window has a [`History`]: a stack of entries, such as the path of
+//! the page the window shows, with a position in it. Pushing an entry drops
+//! the ones ahead of the position, like following a link in a browser, and
+//! [`back`](History::back) and [`forward`](History::forward) move through
+//! the stack. The stack doesn't decide what the window shows; render from
+//! [`current`](History::current), which re-renders the app when it changes.
+//!
+//! Histories are independent of any router, and windows start with an empty
+//! one. [`bind_input`](History::bind_input) lets the mouse's back and forward
+//! buttons and Alt+Left / Alt+Right navigate too.
+//!
+//! ```ignore
+//! use rinch::history::use_history;
+//!
+//! fn app() -> Element {
+//!     let history = use_history();
+//!     history.bind_input(true);
+//!     let page = history.current().unwrap_or_else(|| "home".into());
+//!
+//!     let back = history.clone();
+//!     let open_settings = history.clone();
+//!     rsx! {
+//!         Window { title: "Browser",
+//!             button { disabled: !history.can_go_back().get(), onclick: move || { back.back(); }, "Back" }
+//!             button { onclick: move || open_settings.push("settings"), "Settings" }
+//!             p { {format!("Showing {}", page)} }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rinch_core::{untracked, Signal};
+
+use crate::windows::{PerWindow, WindowHandle};
+
+/// The entries of a history and the position in them.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Stack {
+    entries: Vec<String>,
+    /// The current entry; meaningless while `entries` is empty.
+    index: usize,
+}
+
+/// The back and forward history of a window.
+///
+/// Clones refer to the same history.
+#[derive(Clone)]
+pub struct History {
+    stack: Signal<Stack>,
+    can_go_back: Signal<bool>,
+    can_go_forward: Signal<bool>,
+    input_bound: Rc<Cell<bool>>,
+}
+
+impl History {
+    fn new() -> Self {
+        Self {
+            stack: Signal::new(Stack::default()),
+            can_go_back: Signal::new(false),
+            can_go_forward: Signal::new(false),
+            input_bound: Rc::new(Cell::new(false)),
+        }
+    }
+
+    /// Go to a new entry, dropping the entries ahead of the current one.
+    ///
+    /// Pushing the current entry again does nothing.
+    pub fn push(&self, entry: impl Into<String>) {
+        let entry = entry.into();
+        self.change(|stack| {
+            if stack.entries.get(stack.index) == Some(&entry) {
+                return false;
+            }
+            if !stack.entries.is_empty() {
+                stack.entries.truncate(stack.index + 1);
+            }
+            stack.entries.push(entry);
+            stack.index = stack.entries.len() - 1;
+            true
+        });
+    }
+
+    /// Swap the current entry for another, such as after a redirect, without
+    /// adding one to go back to.
+    pub fn replace(&self, entry: impl Into<String>) {
+        let entry = entry.into();
+        self.change(|stack| match stack.entries.get_mut(stack.index) {
+            Some(current) if *current == entry => false,
+            Some(current) => {
+                *current = entry;
+                true
+            }
+            None => {
+                stack.entries.push(entry);
+                true
+            }
+        });
+    }
+
+    /// Go to the previous entry. Returns `false` if there is none.
+    pub fn back(&self) -> bool {
+        self.go(-1)
+    }
+
+    /// Go to the next entry, after going back. Returns `false` if there is
+    /// none.
+    pub fn forward(&self) -> bool {
+        self.go(1)
+    }
+
+    /// Move `delta` entries back (negative) or forward (positive). Returns
+    /// `false`, staying put, if the stack doesn't reach that far.
+    pub fn go(&self, delta: isize) -> bool {
+        self.change(|stack| {
+            let Some(index) = stack.index.checked_add_signed(delta) else {
+                return false;
+            };
+            if delta == 0 || index >= stack.entries.len() {
+                return false;
+            }
+            stack.index = index;
+            true
+        })
+    }
+
+    /// Drop every entry.
+    pub fn clear(&self) {
+        self.change(|stack| {
+            let changed = !stack.entries.is_empty();
+            *stack = Stack::default();
+            changed
+        });
+    }
+
+    /// The current entry, or `None` before the first push.
+    ///
+    /// Reading it while rendering re-renders when the window navigates.
+    pub fn current(&self) -> Option<String> {
+        self.stack.with(|stack| stack.entries.get(stack.index).cloned())
+    }
+
+    /// All entries, oldest first, and the index of the current one.
+    pub fn entries(&self) -> (Vec<String>, usize) {
+        self.stack.with(|stack| (stack.entries.clone(), stack.index))
+    }
+
+    /// A signal that is `true` while there is an entry to go back to.
+    pub fn can_go_back(&self) -> Signal<bool> {
+        self.can_go_back.clone()
+    }
+
+    /// A signal that is `true` while there is an entry to go forward to.
+    pub fn can_go_forward(&self) -> Signal<bool> {
+        self.can_go_forward.clone()
+    }
+
+    /// Whether the mouse's back and forward buttons and Alt+Left /
+    /// Alt+Right navigate this history (off by default).
+    ///
+    /// The keys are left alone while a text field has focus.
+    pub fn bind_input(&self, bound: bool) {
+        self.input_bound.set(bound);
+    }
+
+    /// Whether [`bind_input`](Self::bind_input) is on.
+    pub fn is_input_bound(&self) -> bool {
+        self.input_bound.get()
+    }
+
+    /// Change the stack, updating the signals and re-rendering if `change`
+    /// returns `true`.
+    fn change(&self, change: impl FnOnce(&mut Stack) -> bool) -> bool {
+        let mut stack = untracked(|| self.stack.get());
+        if !change(&mut stack) {
+            return false;
+        }
+        let can_go_back = stack.index > 0;
+        let can_go_forward = stack.index + 1 < stack.entries.len();
+        self.stack.set(stack);
+        if untracked(|| self.can_go_back.get()) != can_go_back {
+            self.can_go_back.set(can_go_back);
+        }
+        if untracked(|| self.can_go_forward.get()) != can_go_forward {
+            self.can_go_forward.set(can_go_forward);
+        }
+        crate::shell::runtime::request_render();
+        true
+    }
+}
+
+impl std::fmt::Debug for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (entries, index) = untracked(|| self.entries());
+        f.debug_struct("History")
+            .field("entries", &entries)
+            .field("index", &index)
+            .field("input_bound", &self.input_bound.get())
+            .finish()
+    }
+}
+
+thread_local! {
+    /// The histories of open windows.
+    static HISTORIES: PerWindow<History> = PerWindow::new(History::new);
+}
+
+/// Get the history of a window.
+///
+/// Every call for the same window returns the same history.
+pub fn history(window: WindowHandle) -> History {
+    HISTORIES.with(|histories| histories.get(window))
+}
+
+/// Get the history of the window handling the current event, or of the
+/// app's main window (the oldest one open) while rendering.
+pub fn use_history() -> History {
+    HISTORIES.with(PerWindow::current)
+}
+
+/// Give a window that was just opened its history (called by the runtime).
+pub(crate) fn attach(window: WindowHandle) {
+    HISTORIES.with(|histories| histories.attach(window));
+}
+
+/// Forget the history of a closed window (called by the runtime).
+pub(crate) fn detach(window: WindowHandle) {
+    HISTORIES.with(|histories| histories.detach(window));
+}
+
+/// Go back or forward in a window's history for the mouse's back and
+/// forward buttons or Alt+Left / Alt+Right, if its history is bound to them.
+///
+/// Returns whether the input was used.
+pub(crate) fn navigate_from_input(window: WindowHandle, forward: bool) -> bool {
+    let Some(history) = HISTORIES.with(|histories| histories.existing(window)) else {
+        return false;
+    };
+    if !history.is_input_bound() {
+        return false;
+    }
+    if forward {
+        history.forward();
+    } else {
+        history.back();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(history: &History) -> (Vec<String>, usize) {
+        untracked(|| history.entries())
+    }
+
+    fn pushed(list: &[&str]) -> History {
+        let history = History::new();
+        for entry in list {
+            history.push(*entry);
+        }
+        history
+    }
+
+    #[test]
+    fn push_adds_entries_and_moves_to_them() {
+        let history = pushed(&["home", "settings"]);
+        assert_eq!(entries(&history), (vec!["home".into(), "settings".into()], 1));
+        assert_eq!(untracked(|| history.current()).as_deref(), Some("settings"));
+        assert!(untracked(|| history.can_go_back().get()));
+        assert!(!untracked(|| history.can_go_forward().get()));
+    }
+
+    #[test]
+    fn push_of_the_current_entry_does_nothing() {
+        let history = pushed(&["home", "home"]);
+        assert_eq!(entries(&history), (vec!["home".into()], 0));
+    }
+
+    #[test]
+    fn push_drops_the_entries_ahead() {
+        let history = pushed(&["a", "b", "c"]);
+        assert!(history.back());
+        assert!(history.back());
+        history.push("d");
+        assert_eq!(entries(&history), (vec!["a".into(), "d".into()], 1));
+        assert!(!untracked(|| history.can_go_forward().get()));
+    }
+
+    #[test]
+    fn replace_swaps_the_current_entry() {
+        let history = pushed(&["a", "b", "c"]);
+        history.back();
+        history.replace("x");
+        assert_eq!(entries(&history), (vec!["a".into(), "x".into(), "c".into()], 1));
+
+        // Replacing in an empty history adds the entry
+        let empty = History::new();
+        empty.replace("home");
+        assert_eq!(entries(&empty), (vec!["home".into()], 0));
+    }
+
+    #[test]
+    fn go_moves_within_the_stack_only() {
+        let history = pushed(&["a", "b", "c"]);
+        assert!(history.go(-2));
+        assert_eq!(entries(&history).1, 0);
+        assert!(!history.go(-1));
+        assert!(!history.go(3));
+        assert!(!history.go(0));
+        assert_eq!(entries(&history).1, 0);
+
+        assert!(history.forward());
+        assert!(history.go(1));
+        assert!(!history.forward());
+        assert_eq!(untracked(|| history.current()).as_deref(), Some("c"));
+        assert!(untracked(|| history.can_go_back().get()));
+    }
+
+    #[test]
+    fn empty_history_can_not_move() {
+        let history = History::new();
+        assert!(!history.back());
+        assert!(!history.forward());
+        assert_eq!(untracked(|| history.current()), None);
+
+        let history = pushed(&["a", "b"]);
+        history.clear();
+        assert_eq!(entries(&history), (Vec::new(), 0));
+        assert!(!untracked(|| history.can_go_back().get()));
+    }
+}
@@ -0,0 +1,907 @@
+//! Formatting numbers, amounts of money, dates and relative times for the
+//! active locale.
+//!
+//! Every function reads the [`locale`](super::locale) signal, so text
+//! formatted while rendering follows the locale when it changes.
+//!
+//! Separators and the order of dates are known for most European and East
+//! Asian locales. Month names and relative time words are built in for
+//! English, German, French, Spanish, Italian, Portuguese and Dutch; other
+//! languages use English words, and numeric dates in `YYYY-MM-DD` order
+//! unless their order is known.
+//!
+//! ```ignore
+//! use rinch::i18n::{format_currency, format_date, format_number, format_relative_time, DateStyle};
+//!
+//! format_number(1234567.891);             // "1,234,567.891" in en, "1.234.567,891" in de
+//! format_currency(-5.5, "EUR");           // "-€5.50" in en, "-5,50 €" in de
+//! format_date(modified, DateStyle::Long); // "January 2, 2024" in en, "2. Januar 2024" in de
+//! format_relative_time(modified);         // "3 hours ago" in en, "il y a 3 heures" in fr
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{locale, Locale};
+
+/// Seconds in a day.
+const DAY: i64 = 24 * 60 * 60;
+
+/// How a number is written: how many digits after the decimal separator,
+/// and whether thousands are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    min_fraction_digits: usize,
+    max_fraction_digits: usize,
+    grouping: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            min_fraction_digits: 0,
+            max_fraction_digits: 3,
+            grouping: true,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Up to three fraction digits, with grouped thousands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Round to at most `max` digits after the decimal separator, and pad
+    /// with zeros to at least `min`.
+    pub fn fraction_digits(mut self, min: usize, max: usize) -> Self {
+        self.min_fraction_digits = min.min(max);
+        self.max_fraction_digits = max;
+        self
+    }
+
+    /// Whether to group thousands (default `true`).
+    pub fn grouping(mut self, grouping: bool) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Format a number for the active locale.
+    pub fn format(&self, value: f64) -> String {
+        self.format_in(&locale().get(), value)
+    }
+
+    fn format_in(&self, locale: &Locale, value: f64) -> String {
+        if value.is_nan() {
+            return "NaN".to_string();
+        }
+        let sign = if value.is_sign_negative() && value != 0.0 { "-" } else { "" };
+        if value.is_infinite() {
+            return format!("{}∞", sign);
+        }
+
+        let fixed = format!("{:.*}", self.max_fraction_digits, value.abs());
+        let (integer, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+        let mut fraction = fraction.trim_end_matches('0').to_string();
+        while fraction.len() < self.min_fraction_digits {
+            fraction.push('0');
+        }
+        // Rounding can leave nothing but zeros
+        let sign = if integer.bytes().all(|b| b == b'0') && fraction.bytes().all(|b| b == b'0') { "" } else { sign };
+
+        let separators = Separators::of(locale);
+        let mut text = String::from(sign);
+        if self.grouping {
+            text.push_str(&group(integer, &separators));
+        } else {
+            text.push_str(integer);
+        }
+        if !fraction.is_empty() {
+            text.push(separators.decimal);
+            text.push_str(&fraction);
+        }
+        text
+    }
+}
+
+/// Format a number for the active locale, with up to three fraction digits
+/// and grouped thousands: `1,234.5` in English, `1.234,5` in German.
+pub fn format_number(value: f64) -> String {
+    NumberFormat::new().format(value)
+}
+
+/// Format a fraction as a percentage for the active locale: `0.25` is
+/// `25%` in English, `25 %` in German.
+pub fn format_percent(fraction: f64) -> String {
+    let locale = locale().get();
+    let number = NumberFormat::new().fraction_digits(0, 0).format_in(&locale, fraction * 100.0);
+    let space = match locale.language() {
+        "de" | "fr" | "es" | "sv" | "nb" | "no" | "fi" | "da" | "cs" | "sk" | "ru" | "uk" | "pl" => "\u{a0}",
+        _ => "",
+    };
+    format!("{}{}%", number, space)
+}
+
+/// Format an amount of money in a currency, given by its ISO 4217 code,
+/// for the active locale: `format_currency(1234.5, "USD")` is `$1,234.50`
+/// in English and `1.234,50 $` in German.
+///
+/// Known currencies are shown with their symbol and their usual number of
+/// fraction digits; others with their code and two digits.
+pub fn format_currency(amount: f64, currency: &str) -> String {
+    let locale = locale().get();
+    let (symbol, digits) = match currency.to_ascii_uppercase().as_str() {
+        "USD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "CNY" => ("CN¥", 2),
+        "KRW" => ("₩", 0),
+        "INR" => ("₹", 2),
+        "BRL" => ("R$", 2),
+        "RUB" => ("₽", 2),
+        "ILS" => ("₪", 2),
+        "TRY" => ("₺", 2),
+        _ => (currency, 2),
+    };
+    let number = NumberFormat::new()
+        .fraction_digits(digits, digits)
+        .format_in(&locale, amount.abs());
+    let sign = if number.bytes().any(|b| b.is_ascii_digit() && b != b'0') && amount < 0.0 { "-" } else { "" };
+
+    let separators = Separators::of(&locale);
+    let spaced_before = matches!(
+        (locale.language(), locale.region()),
+        ("nl", _) | ("pt", Some("BR") | None) | ("de", Some("CH" | "LI"))
+    );
+    let before = spaced_before
+        || matches!(locale.language(), "en" | "ja" | "zh" | "ko" | "hi" | "th")
+        || separators.decimal == '.';
+    if spaced_before {
+        format!("{}{}\u{a0}{}", sign, symbol, number)
+    } else if before {
+        format!("{}{}{}", sign, symbol, number)
+    } else {
+        format!("{}{}\u{a0}{}", sign, number, symbol)
+    }
+}
+
+/// The separators of numbers in a locale.
+struct Separators {
+    decimal: char,
+    group: &'static str,
+    /// Whether digits above the thousands are grouped in twos (`12,34,567`).
+    indian: bool,
+}
+
+impl Separators {
+    fn of(locale: &Locale) -> Self {
+        let (decimal, group) = match (locale.language(), locale.region()) {
+            ("de" | "it", Some("CH" | "LI")) => ('.', "’"),
+            ("pt", Some("PT")) => (',', "\u{a0}"),
+            ("fr", _) => (',', "\u{202f}"),
+            ("de" | "nl" | "it" | "es" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl" | "sr" | "vi", _) => {
+                (',', ".")
+            }
+            ("ru" | "uk" | "pl" | "cs" | "sk" | "hu" | "bg" | "sv" | "fi" | "nb" | "no", _) => (',', "\u{a0}"),
+            _ => ('.', ","),
+        };
+        Self {
+            decimal,
+            group,
+            indian: matches!(locale.language(), "hi" | "bn") || locale.region() == Some("IN"),
+        }
+    }
+}
+
+/// Insert group separators into a string of digits.
+fn group(digits: &str, separators: &Separators) -> String {
+    let mut groups = Vec::new();
+    let mut end = digits.len();
+    let mut size = 3;
+    while end > size {
+        groups.push(&digits[end - size..end]);
+        end -= size;
+        if separators.indian {
+            size = 2;
+        }
+    }
+    groups.push(&digits[..end]);
+    groups.reverse();
+    groups.join(separators.group)
+}
+
+/// A date and time of day in the Gregorian calendar, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub year: i32,
+    /// 1 to 12.
+    pub month: u8,
+    /// 1 to 31.
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Midnight at the start of a day.
+    pub fn new(year: i32, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }
+    }
+
+    /// The same day at another time.
+    pub fn with_time(mut self, hour: u8, minute: u8, second: u8) -> Self {
+        self.hour = hour;
+        self.minute = minute;
+        self.second = second;
+        self
+    }
+
+    /// The current date and time in the local time zone.
+    pub fn now() -> Self {
+        Self::local(SystemTime::now())
+    }
+
+    /// A point in time as a date and time in the local time zone.
+    pub fn local(time: SystemTime) -> Self {
+        let seconds = unix_seconds(time);
+        Self::from_unix_seconds(seconds + local_offset(seconds))
+    }
+
+    /// A point in time as a date and time in UTC.
+    pub fn utc(time: SystemTime) -> Self {
+        Self::from_unix_seconds(unix_seconds(time))
+    }
+
+    fn from_unix_seconds(seconds: i64) -> Self {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(DAY));
+        let time = seconds.rem_euclid(DAY);
+        Self::new(year, month, day).with_time((time / 3600) as u8, (time / 60 % 60) as u8, (time % 60) as u8)
+    }
+}
+
+impl From<SystemTime> for DateTime {
+    /// The date and time in the local time zone.
+    fn from(time: SystemTime) -> Self {
+        Self::local(time)
+    }
+}
+
+/// How much of a date to spell out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateStyle {
+    /// Digits only: `1/2/2024` in English, `02.01.2024` in German.
+    Short,
+    /// An abbreviated month: `Jan 2, 2024`, `2. Jan. 2024`.
+    #[default]
+    Medium,
+    /// The month's full name: `January 2, 2024`, `2. Januar 2024`.
+    Long,
+}
+
+/// Format a date for the active locale.
+///
+/// Takes a [`DateTime`] or a `SystemTime`, which is shown in the local time
+/// zone.
+pub fn format_date(date: impl Into<DateTime>, style: DateStyle) -> String {
+    date_in(&locale().get(), &date.into(), style)
+}
+
+/// Format a time of day for the active locale: `3:04 PM` in American
+/// English, `15:04` in most other locales.
+pub fn format_time(time: impl Into<DateTime>) -> String {
+    time_in(&locale().get(), &time.into())
+}
+
+/// Format a date and its time of day for the active locale:
+/// `Jan 2, 2024, 3:04 PM` in English.
+pub fn format_date_time(date: impl Into<DateTime>, style: DateStyle) -> String {
+    let locale = locale().get();
+    let date = date.into();
+    let separator = if matches!(locale.language(), "ja" | "zh" | "ko") { " " } else { ", " };
+    format!("{}{}{}", date_in(&locale, &date, style), separator, time_in(&locale, &date))
+}
+
+/// Format how long ago or how far ahead a point in time is, in the largest
+/// fitting unit: `3 hours ago`, `in 2 days`, `now`.
+///
+/// The text doesn't update as time passes; re-render on a timer (see
+/// [`schedule_repeating`](crate::shell::schedule_repeating)) to keep it
+/// current.
+pub fn format_relative_time(time: SystemTime) -> String {
+    let seconds = match time.duration_since(SystemTime::now()) {
+        Ok(ahead) => ahead.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    format_relative_seconds(seconds)
+}
+
+/// Format an offset from now in seconds, negative for the past, like
+/// [`format_relative_time`].
+pub fn format_relative_seconds(seconds: i64) -> String {
+    let locale = locale().get();
+    let words = Words::of(locale.language());
+    let distance = seconds.unsigned_abs();
+    // Each unit is used from a little before one of it
+    let (count, unit) = match distance {
+        0..45 => return words.now.to_string(),
+        45..2_700 => ((distance + 30) / 60, 1),
+        2_700..79_200 => ((distance + 1_800) / 3_600, 2),
+        79_200..561_600 => ((distance + 43_200) / 86_400, 3),
+        561_600..2_246_400 => ((distance + 302_400) / 604_800, 4),
+        2_246_400..28_512_000 => ((distance + 1_314_000) / 2_628_000, 5),
+        _ => ((distance + 15_768_000) / 31_536_000, 6),
+    };
+    let count = count.max(1);
+    let (singular, plural) = words.units[unit];
+    let amount = format!(
+        "{} {}",
+        NumberFormat::new().fraction_digits(0, 0).format_in(&locale, count as f64),
+        if count == 1 { singular } else { plural }
+    );
+    let pattern = if seconds < 0 { words.past } else { words.future };
+    pattern.replace("{}", &amount)
+}
+
+/// A date formatted in a locale.
+fn date_in(locale: &Locale, date: &DateTime, style: DateStyle) -> String {
+    let DateTime { year: y, month: m, day: d, .. } = *date;
+    let language = locale.language();
+    let american = language == "en" && matches!(locale.region(), None | Some("US" | "PH"));
+    if style == DateStyle::Short {
+        return match language {
+            "en" if american => format!("{m}/{d}/{y}"),
+            "en" | "fr" | "es" | "it" | "pt" | "el" | "vi" => format!("{d:02}/{m:02}/{y}"),
+            "de" | "ru" | "uk" | "pl" | "cs" | "sk" | "fi" | "nb" | "no" | "da" | "tr" | "ro" | "bg" => {
+                format!("{d:02}.{m:02}.{y}")
+            }
+            "nl" => format!("{d:02}-{m:02}-{y}"),
+            "ja" | "zh" => format!("{y}/{m:02}/{d:02}"),
+            "ko" => format!("{y}. {m}. {d}."),
+            "hu" => format!("{y}. {m:02}. {d:02}."),
+            _ => format!("{y}-{m:02}-{d:02}"),
+        };
+    }
+    match language {
+        "ja" | "zh" => return format!("{y}年{m}月{d}日"),
+        "ko" => return format!("{y}년 {m}월 {d}일"),
+        _ => {}
+    }
+    let words = Words::of(language);
+    let index = usize::from(m.clamp(1, 12)) - 1;
+    let month = if style == DateStyle::Long { words.months[index] } else { words.short_months[index] };
+    match language {
+        "en" if american => format!("{month} {d}, {y}"),
+        "de" => format!("{d}. {month} {y}"),
+        "es" | "pt" => format!("{d} de {month} de {y}"),
+        _ => format!("{d} {month} {y}"),
+    }
+}
+
+/// A time of day formatted in a locale.
+fn time_in(locale: &Locale, time: &DateTime) -> String {
+    let twelve_hour = locale.language() == "en" && matches!(locale.region(), None | Some("US" | "PH" | "CA" | "AU" | "IN"));
+    if twelve_hour {
+        let hour = match time.hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let period = if time.hour < 12 { "AM" } else { "PM" };
+        format!("{}:{:02}\u{202f}{}", hour, time.minute, period)
+    } else {
+        format!("{:02}:{:02}", time.hour, time.minute)
+    }
+}
+
+/// The words of a language used in dates and relative times.
+struct Words {
+    months: [&'static str; 12],
+    short_months: [&'static str; 12],
+    now: &'static str,
+    /// `{}` is replaced with the amount, such as `3 days`.
+    future: &'static str,
+    past: &'static str,
+    /// Singular and plural of seconds, minutes, hours, days, weeks, months
+    /// and years.
+    units: [(&'static str, &'static str); 7],
+}
+
+impl Words {
+    fn of(language: &str) -> &'static Words {
+        match language {
+            "de" => &GERMAN,
+            "fr" => &FRENCH,
+            "es" => &SPANISH,
+            "it" => &ITALIAN,
+            "pt" => &PORTUGUESE,
+            "nl" => &DUTCH,
+            _ => &ENGLISH,
+        }
+    }
+}
+
+static ENGLISH: Words = Words {
+    months: [
+        "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+        "December",
+    ],
+    short_months: ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"],
+    now: "now",
+    future: "in {}",
+    past: "{} ago",
+    units: [
+        ("second", "seconds"),
+        ("minute", "minutes"),
+        ("hour", "hours"),
+        ("day", "days"),
+        ("week", "weeks"),
+        ("month", "months"),
+        ("year", "years"),
+    ],
+};
+
+static GERMAN: Words = Words {
+    months: [
+        "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November",
+        "Dezember",
+    ],
+    short_months: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez.",
+    ],
+    now: "jetzt",
+    future: "in {}",
+    past: "vor {}",
+    // Dative, as both patterns need it
+    units: [
+        ("Sekunde", "Sekunden"),
+        ("Minute", "Minuten"),
+        ("Stunde", "Stunden"),
+        ("Tag", "Tagen"),
+        ("Woche", "Wochen"),
+        ("Monat", "Monaten"),
+        ("Jahr", "Jahren"),
+    ],
+};
+
+static FRENCH: Words = Words {
+    months: [
+        "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre",
+        "décembre",
+    ],
+    short_months: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc.",
+    ],
+    now: "maintenant",
+    future: "dans {}",
+    past: "il y a {}",
+    units: [
+        ("seconde", "secondes"),
+        ("minute", "minutes"),
+        ("heure", "heures"),
+        ("jour", "jours"),
+        ("semaine", "semaines"),
+        ("mois", "mois"),
+        ("an", "ans"),
+    ],
+};
+
+static SPANISH: Words = Words {
+    months: [
+        "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre",
+        "diciembre",
+    ],
+    short_months: ["ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic"],
+    now: "ahora",
+    future: "dentro de {}",
+    past: "hace {}",
+    units: [
+        ("segundo", "segundos"),
+        ("minuto", "minutos"),
+        ("hora", "horas"),
+        ("día", "días"),
+        ("semana", "semanas"),
+        ("mes", "meses"),
+        ("año", "años"),
+    ],
+};
+
+static ITALIAN: Words = Words {
+    months: [
+        "gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno", "luglio", "agosto", "settembre", "ottobre",
+        "novembre", "dicembre",
+    ],
+    short_months: ["gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic"],
+    now: "ora",
+    future: "tra {}",
+    past: "{} fa",
+    units: [
+        ("secondo", "secondi"),
+        ("minuto", "minuti"),
+        ("ora", "ore"),
+        ("giorno", "giorni"),
+        ("settimana", "settimane"),
+        ("mese", "mesi"),
+        ("anno", "anni"),
+    ],
+};
+
+static PORTUGUESE: Words = Words {
+    months: [
+        "janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho", "agosto", "setembro", "outubro", "novembro",
+        "dezembro",
+    ],
+    short_months: [
+        "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.", "dez.",
+    ],
+    now: "agora",
+    future: "em {}",
+    past: "há {}",
+    units: [
+        ("segundo", "segundos"),
+        ("minuto", "minutos"),
+        ("hora", "horas"),
+        ("dia", "dias"),
+        ("semana", "semanas"),
+        ("mês", "meses"),
+        ("ano", "anos"),
+    ],
+};
+
+static DUTCH: Words = Words {
+    months: [
+        "januari", "februari", "maart", "april", "mei", "juni", "juli", "augustus", "september", "oktober", "november",
+        "december",
+    ],
+    short_months: ["jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec"],
+    now: "nu",
+    future: "over {}",
+    past: "{} geleden",
+    units: [
+        ("seconde", "seconden"),
+        ("minuut", "minuten"),
+        ("uur", "uur"),
+        ("dag", "dagen"),
+        ("week", "weken"),
+        ("maand", "maanden"),
+        ("jaar", "jaar"),
+    ],
+};
+
+/// Seconds since the Unix epoch, negative before it.
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+    }
+}
+
+/// The year, month and day of a day counted from 1970-01-01.
+///
+/// Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
+
+/// The offset of the local time zone from UTC, in seconds, at a point in
+/// time given in seconds since the Unix epoch.
+#[cfg(unix)]
+fn local_offset(seconds: i64) -> i64 {
+    let time = seconds as libc::time_t;
+    // SAFETY: `tm` is plain data that `localtime_r` fills in; both pointers
+    // are valid for the call.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            0
+        } else {
+            tm.tm_gmtoff as i64
+        }
+    }
+}
+
+/// The offset of the local time zone from UTC, in seconds, at a point in
+/// time given in seconds since the Unix epoch.
+#[cfg(windows)]
+fn local_offset(seconds: i64) -> i64 {
+    use windows_sys::Win32::Foundation::SYSTEMTIME;
+    use windows_sys::Win32::System::Time::SystemTimeToTzSpecificLocalTime;
+
+    let utc = DateTime::from_unix_seconds(seconds);
+    let to_system_time = |time: &DateTime| SYSTEMTIME {
+        wYear: time.year as u16,
+        wMonth: u16::from(time.month),
+        wDayOfWeek: 0,
+        wDay: u16::from(time.day),
+        wHour: u16::from(time.hour),
+        wMinute: u16::from(time.minute),
+        wSecond: u16::from(time.second),
+        wMilliseconds: 0,
+    };
+    let utc_time = to_system_time(&utc);
+    // SAFETY: `local` is plain data the call fills in, and a null time zone
+    // means the current one.
+    let local = unsafe {
+        let mut local: SYSTEMTIME = std::mem::zeroed();
+        if SystemTimeToTzSpecificLocalTime(std::ptr::null(), &utc_time, &mut local) == 0 {
+            return 0;
+        }
+        local
+    };
+    let days = |year: i64, month: i64, day: i64| days_from_civil(year, month, day);
+    let local_seconds = days(i64::from(local.wYear), i64::from(local.wMonth), i64::from(local.wDay)) * DAY
+        + i64::from(local.wHour) * 3600
+        + i64::from(local.wMinute) * 60
+        + i64::from(local.wSecond);
+    local_seconds - seconds
+}
+
+#[cfg(not(any(unix, windows)))]
+fn local_offset(_seconds: i64) -> i64 {
+    0
+}
+
+/// The day counted from 1970-01-01 of a year, month and day; the inverse of
+/// [`civil_from_days`].
+#[cfg(windows)]
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::set_locale;
+
+    fn number(tag: &str, value: f64) -> String {
+        NumberFormat::new().format_in(&Locale::new(tag), value)
+    }
+
+    fn date(tag: &str, style: DateStyle) -> String {
+        date_in(&Locale::new(tag), &DateTime::new(2024, 1, 2), style)
+    }
+
+    fn time(tag: &str, hour: u8, minute: u8) -> String {
+        time_in(&Locale::new(tag), &DateTime::new(2024, 1, 2).with_time(hour, minute, 0))
+    }
+
+    /// Relative times in a locale.
+    fn relative(tag: &str, seconds: i64) -> String {
+        set_locale(tag);
+        format_relative_seconds(seconds)
+    }
+
+    #[test]
+    fn number_grouping() {
+        assert_eq!(number("en", 1234567.891), "1,234,567.891");
+        assert_eq!(number("en", 999.0), "999");
+        assert_eq!(number("en", 1000.0), "1,000");
+        assert_eq!(number("de", 1234567.891), "1.234.567,891");
+        assert_eq!(number("fr", 1234567.5), "1\u{202f}234\u{202f}567,5");
+        assert_eq!(number("de-CH", 1234567.5), "1’234’567.5");
+        assert_eq!(number("pt-PT", 1234.5), "1\u{a0}234,5");
+        assert_eq!(number("ru", 1234.5), "1\u{a0}234,5");
+        assert_eq!(number("ja", 1234.5), "1,234.5");
+    }
+
+    #[test]
+    fn indian_grouping() {
+        assert_eq!(number("en-IN", 1234567.891), "12,34,567.891");
+        assert_eq!(number("en-IN", 100000.0), "1,00,000");
+        assert_eq!(number("en-IN", 1000.0), "1,000");
+        assert_eq!(number("hi", 123456789.0), "12,34,56,789");
+    }
+
+    #[test]
+    fn number_rounding_and_fraction_digits() {
+        let en = Locale::new("en");
+        assert_eq!(number("en", 1.23456), "1.235");
+        assert_eq!(number("en", 0.9996), "1");
+        assert_eq!(number("en", 2.5), "2.5");
+        assert_eq!(NumberFormat::new().fraction_digits(0, 2).format_in(&en, 1999.9999), "2,000");
+        assert_eq!(NumberFormat::new().fraction_digits(2, 2).format_in(&en, 5.0), "5.00");
+        // The minimum is capped at the maximum
+        assert_eq!(NumberFormat::new().fraction_digits(3, 1).format_in(&en, 5.0), "5.0");
+        assert_eq!(NumberFormat::new().grouping(false).format_in(&en, 1234567.0), "1234567");
+    }
+
+    #[test]
+    fn negative_and_special_numbers() {
+        assert_eq!(number("en", -1234.5), "-1,234.5");
+        assert_eq!(number("de", -0.25), "-0,25");
+        // Nothing left after rounding, so no sign either
+        assert_eq!(number("en", -0.0001), "0");
+        assert_eq!(number("en", -0.0), "0");
+        assert_eq!(number("en", f64::NAN), "NaN");
+        assert_eq!(number("en", f64::INFINITY), "∞");
+        assert_eq!(number("en", f64::NEG_INFINITY), "-∞");
+    }
+
+    #[test]
+    fn percentages() {
+        set_locale("en");
+        assert_eq!(format_percent(0.25), "25%");
+        assert_eq!(format_percent(1.5), "150%");
+        assert_eq!(format_percent(-0.5), "-50%");
+        assert_eq!(format_percent(0.1234), "12%");
+        set_locale("de");
+        assert_eq!(format_percent(0.25), "25\u{a0}%");
+        set_locale("ja");
+        assert_eq!(format_percent(0.25), "25%");
+    }
+
+    #[test]
+    fn currencies() {
+        set_locale("en");
+        assert_eq!(format_currency(1234.5, "USD"), "$1,234.50");
+        assert_eq!(format_currency(-5.5, "EUR"), "-€5.50");
+        assert_eq!(format_currency(1234.4, "JPY"), "¥1,234");
+        assert_eq!(format_currency(12.0, "usd"), "$12.00");
+        assert_eq!(format_currency(12.0, "CHF"), "CHF12.00");
+        // Rounds to zero, so no sign
+        assert_eq!(format_currency(-0.001, "USD"), "$0.00");
+        set_locale("de");
+        assert_eq!(format_currency(1234.5, "EUR"), "1.234,50\u{a0}€");
+        assert_eq!(format_currency(-5.5, "EUR"), "-5,50\u{a0}€");
+        set_locale("de-CH");
+        assert_eq!(format_currency(10.0, "CHF"), "CHF\u{a0}10.00");
+        set_locale("fr");
+        assert_eq!(format_currency(1234.5, "EUR"), "1\u{202f}234,50\u{a0}€");
+        set_locale("nl");
+        assert_eq!(format_currency(12.5, "EUR"), "€\u{a0}12,50");
+        set_locale("pt-BR");
+        assert_eq!(format_currency(10.0, "BRL"), "R$\u{a0}10,00");
+    }
+
+    #[test]
+    fn days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+    }
+
+    #[test]
+    fn days_to_dates_in_leap_years() {
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        // 2000 is a leap year, as it divides by 400
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(11_322), (2000, 12, 31));
+    }
+
+    #[test]
+    fn days_before_1970() {
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(-365), (1969, 1, 1));
+        assert_eq!(civil_from_days(-25_567), (1900, 1, 1));
+        // 1900 isn't a leap year, as it divides by 100
+        assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+    }
+
+    #[test]
+    fn utc_date_times() {
+        use std::time::Duration;
+
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_164_645);
+        assert_eq!(DateTime::utc(time), DateTime::new(2024, 1, 2).with_time(3, 4, 5));
+        let before = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(DateTime::utc(before), DateTime::new(1969, 12, 31).with_time(23, 59, 59));
+    }
+
+    #[test]
+    fn local_offsets() {
+        let now = unix_seconds(SystemTime::now());
+        for seconds in [now, 1_719_835_200] {
+            let offset = local_offset(seconds);
+            assert!(offset.abs() <= 14 * 3600, "offset {} out of range", offset);
+            assert_eq!(offset % 900, 0, "offset {} isn't whole quarter hours", offset);
+        }
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(now as u64);
+        assert_eq!(DateTime::local(time), DateTime::from_unix_seconds(now + local_offset(now)));
+    }
+
+    #[test]
+    fn short_dates() {
+        assert_eq!(date("en", DateStyle::Short), "1/2/2024");
+        assert_eq!(date("en-GB", DateStyle::Short), "02/01/2024");
+        assert_eq!(date("de", DateStyle::Short), "02.01.2024");
+        assert_eq!(date("nl", DateStyle::Short), "02-01-2024");
+        assert_eq!(date("ja", DateStyle::Short), "2024/01/02");
+        assert_eq!(date("ko", DateStyle::Short), "2024. 1. 2.");
+        assert_eq!(date("hu", DateStyle::Short), "2024. 01. 02.");
+        assert_eq!(date("sv", DateStyle::Short), "2024-01-02");
+    }
+
+    #[test]
+    fn medium_and_long_dates() {
+        assert_eq!(date("en", DateStyle::Medium), "Jan 2, 2024");
+        assert_eq!(date("en", DateStyle::Long), "January 2, 2024");
+        assert_eq!(date("en-GB", DateStyle::Long), "2 January 2024");
+        assert_eq!(date("de", DateStyle::Medium), "2. Jan. 2024");
+        assert_eq!(date("de", DateStyle::Long), "2. Januar 2024");
+        assert_eq!(date("es", DateStyle::Long), "2 de enero de 2024");
+        assert_eq!(date("fr", DateStyle::Long), "2 janvier 2024");
+        assert_eq!(date("ja", DateStyle::Long), "2024年1月2日");
+        assert_eq!(date("ko", DateStyle::Medium), "2024년 1월 2일");
+        // Without built-in words, English month names
+        assert_eq!(date("ru", DateStyle::Long), "2 January 2024");
+    }
+
+    #[test]
+    fn times_of_day() {
+        assert_eq!(time("en", 15, 4), "3:04\u{202f}PM");
+        assert_eq!(time("en", 0, 5), "12:05\u{202f}AM");
+        assert_eq!(time("en", 12, 0), "12:00\u{202f}PM");
+        assert_eq!(time("en-IN", 9, 30), "9:30\u{202f}AM");
+        assert_eq!(time("en-GB", 15, 4), "15:04");
+        assert_eq!(time("de", 9, 5), "09:05");
+    }
+
+    #[test]
+    fn dates_with_times() {
+        let date = DateTime::new(2024, 1, 2).with_time(15, 4, 0);
+        set_locale("en");
+        assert_eq!(format_date_time(date, DateStyle::Medium), "Jan 2, 2024, 3:04\u{202f}PM");
+        set_locale("ja");
+        assert_eq!(format_date_time(date, DateStyle::Long), "2024年1月2日 15:04");
+    }
+
+    #[test]
+    fn relative_times() {
+        assert_eq!(relative("en", 0), "now");
+        assert_eq!(relative("en", 44), "now");
+        assert_eq!(relative("en", -44), "now");
+        assert_eq!(relative("en", 45), "in 1 minute");
+        assert_eq!(relative("en", -90), "2 minutes ago");
+        assert_eq!(relative("en", 3_600), "in 1 hour");
+        assert_eq!(relative("en", -86_400), "1 day ago");
+        assert_eq!(relative("en", 7 * 86_400), "in 1 week");
+        assert_eq!(relative("en", 30 * 86_400), "in 1 month");
+        assert_eq!(relative("en", -3 * 365 * 86_400), "3 years ago");
+        assert_eq!(relative("en", 2_000 * 31_536_000), "in 2,000 years");
+    }
+
+    #[test]
+    fn relative_times_in_other_languages() {
+        assert_eq!(relative("de", -10_800), "vor 3 Stunden");
+        assert_eq!(relative("fr", -10_800), "il y a 3 heures");
+        assert_eq!(relative("nl", 7_200), "over 2 uur");
+        assert_eq!(relative("it", -60), "1 minuto fa");
+        assert_eq!(relative("es", 2 * 86_400), "dentro de 2 días");
+        assert_eq!(relative("pt", 0), "agora");
+        // Without built-in words, English
+        assert_eq!(relative("sv", -60), "1 minute ago");
+    }
+
+    #[test]
+    fn relative_time_from_now() {
+        use std::time::Duration;
+
+        set_locale("en");
+        assert_eq!(format_relative_time(SystemTime::now() - Duration::from_secs(3 * 3_600)), "3 hours ago");
+        assert_eq!(format_relative_time(SystemTime::now() + Duration::from_secs(2 * 86_400)), "in 2 days");
+    }
+}
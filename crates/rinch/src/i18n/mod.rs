@@ -0,0 +1,1137 @@
+//! Translations and the app's locale.
+//!
+//! Translations are kept in a [`Bundle`] per locale, loaded from
+//! [Fluent](https://projectfluent.org) files or JSON objects, and looked up
+//! with [`t!`](crate::t) (or [`translate`]) while rendering. The active
+//! locale is a signal ([`locale`]); setting it re-renders every window with
+//! the new strings. It starts out as the OS locale ([`Locale::system`]).
+//!
+//! The locale also decides which way text flows: windows whose `dir` prop is
+//! left at `auto` lay out right to left while the locale is written right to
+//! left (see [`use_text_direction`]).
+//!
+//! Numbers, amounts of money, dates and relative times are formatted for the
+//! active locale by the functions in [`format`].
+//!
+//! A message is looked up in the bundle of the active locale, then of its
+//! parent locales (`de-CH`, then `de`), then of the fallback locale (see
+//! [`set_fallback_locale`]). A message found nowhere renders as its key.
+//!
+//! # Fluent files
+//!
+//! The commonly used part of the Fluent syntax is supported: messages and
+//! their `.attributes`, terms (`-brand`), multiline text, comments, variable
+//! and term references, and select expressions choosing a variant by a
+//! variable's value. A number selects the variant with that exact value, or
+//! else the variant of its plural category in the bundle's locale (`zero`,
+//! `one`, `two`, `few`, `many` or `other`, following the CLDR rules).
+//!
+//! ```ftl
+//! -brand = Rinch Notes
+//! hello = Hello, { $name }!
+//! about = About { -brand }
+//! notes = { $count ->
+//!     [0] No notes
+//!     [one] One note
+//!    *[other] { $count } notes
+//! }
+//! save = Save
+//!     .tooltip = Save the note (Ctrl+S)
+//! ```
+//!
+//! # JSON files
+//!
+//! A JSON object of strings; nested objects give keys joined with dots, so
+//! `{"menu": {"open": "Open"}}` holds `menu.open`. Strings use the same
+//! placeables as Fluent, and `{ name }` without the `$` works too.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::i18n::{self, Bundle};
+//!
+//! fn main() {
+//!     i18n::add_bundle("en", Bundle::from_ftl(include_str!("../i18n/en.ftl")).unwrap());
+//!     i18n::add_bundle("de", Bundle::from_ftl(include_str!("../i18n/de.ftl")).unwrap());
+//!     rinch::run(app);
+//! }
+//!
+//! fn app() -> Element {
+//!     let count = use_signal(|| 0);
+//!     rsx! {
+//!         Window { title: t!("about"),
+//!             p { {t!("notes", count = count.get())} }
+//!             button { onclick: || i18n::set_locale("de"), "Deutsch" }
+//!         }
+//!     }
+//! }
+//! ```
+
+pub mod format;
+mod plural;
+
+pub use format::{
+    format_currency, format_date, format_date_time, format_number, format_percent, format_relative_seconds,
+    format_relative_time, format_time, DateStyle, DateTime, NumberFormat,
+};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use rinch_core::element::TextDirection;
+use rinch_core::{untracked, Effect, Signal};
+
+/// How deeply terms may refer to other terms.
+const MAX_TERM_DEPTH: usize = 8;
+
+/// Languages written right to left, unless a script subtag says otherwise.
+const RTL_LANGUAGES: &[&str] = &["ar", "arc", "ckb", "dv", "fa", "he", "iw", "ks", "ps", "sd", "ug", "ur", "yi"];
+
+/// Scripts written right to left.
+const RTL_SCRIPTS: &[&str] = &["Adlm", "Arab", "Hebr", "Nkoo", "Rohg", "Syrc", "Thaa"];
+
+/// A language with an optional script and region, as a BCP 47 tag such as
+/// `en`, `de-CH` or `zh-Hant-TW`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tag: String,
+}
+
+impl Locale {
+    /// A locale from a tag such as `de-CH`.
+    ///
+    /// POSIX names such as `de_CH.UTF-8` are accepted too; the subtags are
+    /// brought into their usual case.
+    pub fn new(tag: &str) -> Self {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+        let tag = tag
+            .split('-')
+            .filter(|subtag| !subtag.is_empty())
+            .enumerate()
+            .map(|(index, subtag)| match (index, subtag.len()) {
+                (0, _) => subtag.to_ascii_lowercase(),
+                (_, 2) => subtag.to_ascii_uppercase(),
+                (_, 4) if subtag.bytes().all(|b| b.is_ascii_alphabetic()) => {
+                    let mut script = subtag.to_ascii_lowercase();
+                    script[..1].make_ascii_uppercase();
+                    script
+                }
+                _ => subtag.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("-");
+        Self { tag }
+    }
+
+    /// The locale's tag, e.g. `de-CH`.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The language subtag, e.g. `de` for `de-CH`.
+    pub fn language(&self) -> &str {
+        self.tag.split('-').next().unwrap_or_default()
+    }
+
+    /// The region subtag, e.g. `CH` for `de-CH`, if the tag has one.
+    pub fn region(&self) -> Option<&str> {
+        self.tag.split('-').skip(1).find(|subtag| {
+            subtag.len() == 2 || (subtag.len() == 3 && subtag.bytes().all(|b| b.is_ascii_digit()))
+        })
+    }
+
+    /// The direction the locale's language is written in, from its script
+    /// subtag if it has one (`pa-Arab` is right to left) or else its language.
+    pub fn direction(&self) -> TextDirection {
+        let script = self.tag.split('-').skip(1).find(|subtag| subtag.len() == 4);
+        let rtl = match script {
+            Some(script) => RTL_SCRIPTS.contains(&script),
+            None => RTL_LANGUAGES.contains(&self.language()),
+        };
+        if rtl { TextDirection::Rtl } else { TextDirection::Ltr }
+    }
+
+    /// The locale the OS is set to, or `en` if it can't be found.
+    pub fn system() -> Self {
+        system_locale()
+            .map(|tag| Locale::new(&tag))
+            .filter(|locale| !locale.tag.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// This tag and the tags of its parents, most specific first:
+    /// `zh-Hant-TW`, `zh-Hant`, `zh`.
+    fn chain(&self) -> impl Iterator<Item = &str> {
+        let tag = self.tag.as_str();
+        let mut end = Some(tag.len());
+        std::iter::from_fn(move || {
+            let current = &tag[..end?];
+            end = current.rfind('-');
+            Some(current)
+        })
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new("en")
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+impl From<&str> for Locale {
+    fn from(tag: &str) -> Self {
+        Self::new(tag)
+    }
+}
+
+impl From<String> for Locale {
+    fn from(tag: String) -> Self {
+        Self::new(&tag)
+    }
+}
+
+/// Why a translation file couldn't be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct I18nError {
+    /// The line the problem is on, starting at 1.
+    pub line: usize,
+    /// What's wrong.
+    pub message: String,
+}
+
+impl fmt::Display for I18nError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for I18nError {}
+
+/// Result type for loading translations.
+pub type I18nResult<T> = Result<T, I18nError>;
+
+/// A piece of a message.
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    /// A variable passed to [`translate`].
+    Var(String),
+    /// A reference to a term of the same bundle.
+    Term(String),
+    /// Variants chosen by a variable's value.
+    Select {
+        var: String,
+        variants: Vec<(String, Vec<Part>)>,
+        default: usize,
+    },
+}
+
+/// The translated messages of one locale.
+#[derive(Debug, Clone, Default)]
+pub struct Bundle {
+    messages: HashMap<String, Vec<Part>>,
+    terms: HashMap<String, Vec<Part>>,
+    /// The locale whose plural rules select variants, set by [`add_bundle`].
+    locale: Locale,
+}
+
+impl Bundle {
+    /// An empty bundle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a message, with placeables as in Fluent (`{ $name }`).
+    ///
+    /// A message that doesn't parse is kept as plain text.
+    pub fn message(mut self, key: impl Into<String>, value: &str) -> Self {
+        let parts = PatternParser::parse(value).unwrap_or_else(|_| vec![Part::Text(value.to_string())]);
+        self.messages.insert(key.into(), parts);
+        self
+    }
+
+    /// Whether the bundle has a message.
+    pub fn contains(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    /// Format a message with the given variables, if the bundle has it.
+    ///
+    /// Variables missing from `args` show as `{$name}`. Numbers select
+    /// variants by the plural rules of the locale the bundle was added for
+    /// with [`add_bundle`], or of English before then.
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> Option<String> {
+        let parts = self.messages.get(key)?;
+        let mut text = String::new();
+        self.format_parts(parts, args, 0, &mut text);
+        Some(text)
+    }
+
+    fn format_parts(&self, parts: &[Part], args: &[(&str, String)], depth: usize, out: &mut String) {
+        for part in parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Var(name) => match arg(args, name) {
+                    Some(value) => out.push_str(value),
+                    None => {
+                        out.push_str("{$");
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+                Part::Term(name) => match self.terms.get(name) {
+                    Some(term) if depth < MAX_TERM_DEPTH => self.format_parts(term, args, depth + 1, out),
+                    _ => {
+                        out.push_str("{-");
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+                Part::Select { var, variants, default } => {
+                    let index = arg(args, var)
+                        .and_then(|value| select_variant(&self.locale, variants, value))
+                        .unwrap_or(*default);
+                    self.format_parts(&variants[index].1, args, depth, out);
+                }
+            }
+        }
+    }
+
+    /// Load a bundle from the text of a Fluent (`.ftl`) file.
+    pub fn from_ftl(source: &str) -> I18nResult<Self> {
+        let mut bundle = Self::new();
+        let lines: Vec<&str> = source.lines().collect();
+        let mut index = 0;
+        while index < lines.len() {
+            let line = lines[index];
+            index += 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| I18nError {
+                line: index,
+                message: message.to_string(),
+            };
+            if line.starts_with(char::is_whitespace) {
+                return Err(error("indented line outside a message"));
+            }
+            let Some((id, value)) = line.split_once('=') else {
+                return Err(error("expected `key = value`"));
+            };
+            let id = id.trim();
+            if !is_identifier(id.strip_prefix('-').unwrap_or(id)) {
+                return Err(error(&format!("invalid message name `{}`", id)));
+            }
+
+            // The value, its continuation lines and attributes
+            let mut entries = vec![(id.to_string(), value.trim().to_string(), index)];
+            while index < lines.len() {
+                let next = lines[index];
+                let next_trimmed = next.trim();
+                if next_trimmed.is_empty() {
+                    // Blank lines belong to the message if it continues after them
+                    let continues = lines[index..]
+                        .iter()
+                        .find(|line| !line.trim().is_empty())
+                        .is_some_and(|line| line.starts_with(char::is_whitespace));
+                    if !continues {
+                        break;
+                    }
+                    index += 1;
+                    continue;
+                }
+                if !next.starts_with(char::is_whitespace) && !next_trimmed.starts_with('}') {
+                    break;
+                }
+                index += 1;
+                if let Some(attribute) = next_trimmed.strip_prefix('.')
+                    && let Some((name, value)) = attribute.split_once('=')
+                    && is_identifier(name.trim())
+                {
+                    entries.push((format!("{}.{}", id, name.trim()), value.trim().to_string(), index));
+                    continue;
+                }
+                let value = &mut entries.last_mut().expect("the message is the first entry").1;
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(next_trimmed);
+            }
+
+            let has_attributes = entries.len() > 1;
+            for (key, value, line) in entries {
+                // A message may have only attributes
+                if key == id && value.is_empty() && has_attributes {
+                    continue;
+                }
+                let parts = PatternParser::parse(&value).map_err(|message| I18nError { line, message })?;
+                match key.strip_prefix('-') {
+                    Some(term) => bundle.terms.insert(term.to_string(), parts),
+                    None => bundle.messages.insert(key, parts),
+                };
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Load a bundle from a JSON object of strings (and nested objects).
+    pub fn from_json(source: &str) -> I18nResult<Self> {
+        let mut bundle = Self::new();
+        let mut parser = JsonParser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        parser.skip_whitespace();
+        parser.object("", &mut bundle)?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unexpected text after the object"));
+        }
+        Ok(bundle)
+    }
+
+    /// Add the messages and terms of another bundle, replacing those with
+    /// the same names.
+    pub fn extend(&mut self, other: Bundle) {
+        self.messages.extend(other.messages);
+        self.terms.extend(other.terms);
+    }
+}
+
+/// The value of a variable passed to [`translate`].
+fn arg<'a>(args: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+    args.iter().find(|(arg, _)| *arg == name).map(|(_, value)| value.as_str())
+}
+
+/// The variant of a select expression matching a value: the one with the
+/// same key, or for numbers the one of its plural category in `locale`.
+fn select_variant(locale: &Locale, variants: &[(String, Vec<Part>)], value: &str) -> Option<usize> {
+    if let Some(index) = variants.iter().position(|(key, _)| key == value) {
+        return Some(index);
+    }
+    let number: f64 = value.trim().parse().ok()?;
+    if let Some(index) = variants
+        .iter()
+        .position(|(key, _)| key.parse::<f64>().is_ok_and(|key| key == number))
+    {
+        return Some(index);
+    }
+    let category = plural::category(locale, value)?;
+    variants.iter().position(|(key, _)| key == category)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Parses the text of a message into [`Part`]s.
+struct PatternParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl PatternParser {
+    fn parse(text: &str) -> Result<Vec<Part>, String> {
+        let mut parser = Self {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        parser.parts(false)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let matches = token
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| self.chars.get(self.pos + offset) == Some(&c));
+        if matches {
+            self.pos += token.chars().count();
+        }
+        matches
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Text and placeables, up to the end; in a variant, up to the end of
+    /// its line or of the select expression.
+    fn parts(&mut self, in_variant: bool) -> Result<Vec<Part>, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if in_variant && (c == '\n' || c == '}') {
+                break;
+            }
+            self.pos += 1;
+            match c {
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.placeable()?);
+                }
+                '}' => return Err("`}` without a matching `{`".to_string()),
+                c => text.push(c),
+            }
+        }
+        if in_variant {
+            text.truncate(text.trim_end().len());
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// What's inside `{ }`, after the `{`.
+    fn placeable(&mut self) -> Result<Part, String> {
+        self.skip_whitespace();
+        let part = match self.peek() {
+            Some('$') => {
+                self.pos += 1;
+                Part::Var(self.identifier()?)
+            }
+            Some('-') => {
+                self.pos += 1;
+                Part::Term(self.identifier()?)
+            }
+            Some('"') => {
+                self.pos += 1;
+                Part::Text(self.string_literal()?)
+            }
+            Some(c) if c.is_ascii_alphabetic() => Part::Var(self.identifier()?),
+            _ => return Err("expected a `$variable`, `-term` or string in `{ }`".to_string()),
+        };
+        self.skip_whitespace();
+        if self.eat("->") {
+            let Part::Var(var) = part else {
+                return Err("only variables can select a variant".to_string());
+            };
+            return self.select(var);
+        }
+        if !self.eat("}") {
+            return Err("expected `}`".to_string());
+        }
+        Ok(part)
+    }
+
+    /// The variants of a select expression, after the `->`.
+    fn select(&mut self, var: String) -> Result<Part, String> {
+        let mut variants = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_whitespace();
+            if self.eat("}") {
+                break;
+            }
+            let is_default = self.eat("*");
+            if !self.eat("[") {
+                return Err("expected a `[variant]` or `}`".to_string());
+            }
+            let mut key = String::new();
+            loop {
+                match self.peek() {
+                    Some(']') => break,
+                    Some('\n') | None => return Err("expected `]`".to_string()),
+                    Some(c) => key.push(c),
+                }
+                self.pos += 1;
+            }
+            self.pos += 1;
+            while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+                self.pos += 1;
+            }
+            if is_default {
+                if default.is_some() {
+                    return Err("more than one `*[default]` variant".to_string());
+                }
+                default = Some(variants.len());
+            }
+            let value = self.parts(true)?;
+            variants.push((key.trim().to_string(), value));
+        }
+        let default = default.ok_or("missing a `*[default]` variant")?;
+        Ok(Part::Select { var, variants, default })
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        if is_identifier(&name) {
+            Ok(name)
+        } else {
+            Err("expected a name".to_string())
+        }
+    }
+
+    /// A `"quoted"` string, after the opening quote.
+    fn string_literal(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let c = self.peek().ok_or("unterminated string")?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = self.peek().ok_or("unterminated string")?;
+                    self.pos += 1;
+                    text.push(escaped);
+                }
+                '\n' => return Err("unterminated string".to_string()),
+                c => text.push(c),
+            }
+        }
+    }
+}
+
+/// Parses a JSON object of strings into a [`Bundle`].
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn error(&self, message: &str) -> I18nError {
+        let end = self.pos.min(self.chars.len());
+        I18nError {
+            line: self.chars[..end].iter().filter(|c| **c == '\n').count() + 1,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> I18nResult<()> {
+        if self.chars.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    /// An object, adding its strings to `bundle` with `prefix` before their keys.
+    fn object(&mut self, prefix: &str, bundle: &mut Bundle) -> I18nResult<()> {
+        self.expect('{')?;
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            self.skip_whitespace();
+            let key = format!("{}{}", prefix, self.string()?);
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            match self.chars.get(self.pos) {
+                Some('"') => {
+                    let line = self.error("").line;
+                    let value = self.string()?;
+                    let parts = PatternParser::parse(&value).map_err(|message| I18nError { line, message })?;
+                    bundle.messages.insert(key, parts);
+                }
+                Some('{') => self.object(&format!("{}.", key), bundle)?,
+                _ => return Err(self.error("expected a string or an object")),
+            }
+            self.skip_whitespace();
+            match self.chars.get(self.pos) {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    /// A string, with its escapes decoded.
+    fn string(&mut self) -> I18nResult<String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            let c = *self.chars.get(self.pos).ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escape = *self.chars.get(self.pos).ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escape {
+                        '"' | '\\' | '/' => text.push(escape),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'u' => {
+                            let mut code = self.hex4()?;
+                            // A surrogate pair encodes one character
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect('\\')?;
+                                self.expect('u')?;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("invalid `\\u` escape"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            text.push(char::from_u32(code).ok_or_else(|| self.error("invalid `\\u` escape"))?);
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("unescaped control character in a string")),
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> I18nResult<u32> {
+        let digits: String = self.chars.iter().skip(self.pos).take(4).collect();
+        let code = u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| digits.len() == 4)
+            .ok_or_else(|| self.error("invalid `\\u` escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+thread_local! {
+    /// The loaded bundles, by locale tag.
+    static BUNDLES: RefCell<HashMap<String, Bundle>> = RefCell::new(HashMap::new());
+    /// The active locale, created on first access.
+    static LOCALE: RefCell<Option<Signal<Locale>>> = const { RefCell::new(None) };
+    /// The direction of the active locale, created with it.
+    static DIRECTION: RefCell<Option<Signal<TextDirection>>> = const { RefCell::new(None) };
+    /// The locale messages are looked up in when the active one lacks them.
+    static FALLBACK: RefCell<Locale> = RefCell::new(Locale::default());
+}
+
+/// Add translations for a locale, merged with the ones added before.
+pub fn add_bundle(locale: impl Into<Locale>, bundle: Bundle) {
+    let locale = locale.into();
+    BUNDLES.with(|bundles| {
+        let mut bundles = bundles.borrow_mut();
+        let entry = bundles.entry(locale.tag.clone()).or_default();
+        entry.extend(bundle);
+        entry.locale = locale;
+    });
+    crate::shell::runtime::request_render();
+}
+
+/// Get the signal holding the active locale.
+///
+/// Setting the signal re-renders every window. The locale starts out as
+/// [`Locale::system`]. Every call returns the same signal.
+pub fn locale() -> Signal<Locale> {
+    if let Some(signal) = LOCALE.with(|l| l.borrow().clone()) {
+        return signal;
+    }
+
+    let signal = Signal::new(Locale::system());
+    let direction = Signal::new(signal.with(Locale::direction));
+    LOCALE.with(|l| *l.borrow_mut() = Some(signal.clone()));
+    DIRECTION.with(|d| *d.borrow_mut() = Some(direction.clone()));
+
+    // Re-render with the new strings and direction whenever the locale changes
+    let watched = signal.clone();
+    let mut initial = true;
+    Effect::new(move || {
+        let new_direction = watched.with(Locale::direction);
+        if initial {
+            initial = false;
+            return;
+        }
+        if untracked(|| direction.get()) != new_direction {
+            direction.set(new_direction);
+        }
+        crate::shell::runtime::request_render();
+    });
+
+    signal
+}
+
+/// Get the signal holding the direction of the active locale: `Rtl` for
+/// languages written right to left, `Ltr` for the others.
+///
+/// Windows whose `dir` prop is `auto` follow it. Use it for what CSS doesn't
+/// mirror by itself, such as which way an arrow icon points.
+pub fn use_text_direction() -> Signal<TextDirection> {
+    if let Some(signal) = DIRECTION.with(|d| d.borrow().clone()) {
+        return signal;
+    }
+    locale();
+    DIRECTION.with(|d| d.borrow().clone()).expect("created with the locale")
+}
+
+/// The direction a window with the given `dir` prop lays out in.
+///
+/// `Auto` follows the locale once the app has used translations, and is
+/// left to right before then, so apps without them don't pay for finding
+/// the OS locale.
+pub(crate) fn resolve_direction(dir: TextDirection) -> TextDirection {
+    match dir {
+        TextDirection::Auto => DIRECTION
+            .with(|d| d.borrow().clone())
+            .map(|direction| untracked(|| direction.get()))
+            .unwrap_or(TextDirection::Ltr),
+        dir => dir,
+    }
+}
+
+/// Switch the app to a locale.
+///
+/// Shorthand for `locale().set(locale.into())`.
+pub fn set_locale(locale: impl Into<Locale>) {
+    self::locale().set(locale.into());
+}
+
+/// Set the locale whose messages are used when the active locale lacks
+/// them (default `en`).
+pub fn set_fallback_locale(locale: impl Into<Locale>) {
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = locale.into());
+    crate::shell::runtime::request_render();
+}
+
+/// Translate a message into the active locale, with the given variables.
+///
+/// Returns the key itself if no bundle has the message. Usually called
+/// through [`t!`](crate::t).
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let locale = locale().get();
+    let fallback = FALLBACK.with(|fallback| fallback.borrow().clone());
+    let text = BUNDLES.with(|bundles| {
+        let bundles = bundles.borrow();
+        locale
+            .chain()
+            .chain(fallback.chain())
+            .find_map(|tag| bundles.get(tag)?.format(key, args))
+    });
+    text.unwrap_or_else(|| {
+        tracing::debug!("No translation for `{}` in {}", key, locale);
+        key.to_string()
+    })
+}
+
+/// Translate a message into the active locale.
+///
+/// Variables are given as `name = value`, where the value is anything that
+/// implements `Display`.
+///
+/// ```ignore
+/// rsx! {
+///     h1 { {t!("hello", name = user.get())} }
+///     p { {t!("notes", count = notes.len())} }
+///     button { tooltip: t!("save.tooltip"), {t!("save")} }
+/// }
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:expr $(,)?) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $key,
+            &[$((stringify!($name), ::std::string::ToString::to_string(&$value))),+],
+        )
+    };
+}
+
+/// The OS locale from the environment (`LC_ALL`, `LC_MESSAGES`, `LANG`).
+#[cfg(target_os = "linux")]
+fn system_locale() -> Option<String> {
+    env_locale()
+}
+
+/// The OS locale from the environment, or else the `AppleLocale` default.
+#[cfg(target_os = "macos")]
+fn system_locale() -> Option<String> {
+    env_locale().or_else(|| {
+        let output = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+            .ok()?;
+        let locale = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !locale.is_empty()).then_some(locale)
+    })
+}
+
+/// The user's locale with `GetUserDefaultLocaleName`.
+#[cfg(target_os = "windows")]
+fn system_locale() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+
+    const LOCALE_NAME_MAX_LENGTH: usize = 85;
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+    // SAFETY: `name` is a writable buffer of the length passed.
+    let len = unsafe { GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32) };
+    // The length includes the terminating null
+    (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn system_locale() -> Option<String> {
+    None
+}
+
+/// The locale set in the environment, skipping the `C` and `POSIX` locales.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn env_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .filter(|value| value != "C" && value != "POSIX" && !value.starts_with("C."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(bundle: &Bundle, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        let args: Vec<(&str, String)> = args.iter().map(|(name, value)| (*name, value.to_string())).collect();
+        bundle.format(key, &args)
+    }
+
+    fn parse_error(source: &str) -> I18nError {
+        Bundle::from_ftl(source).expect_err("the source should fail to parse")
+    }
+
+    #[test]
+    fn locale_tags_are_normalized() {
+        assert_eq!(Locale::new("de_CH.UTF-8").tag(), "de-CH");
+        assert_eq!(Locale::new("ZH-hant-tw").tag(), "zh-Hant-TW");
+        assert_eq!(Locale::new("sr@latin").tag(), "sr");
+        assert_eq!(Locale::new("es-419").region(), Some("419"));
+        assert_eq!(Locale::new("en").region(), None);
+        // Four bytes but not a script
+        assert_eq!(Locale::new("en-éab").tag(), "en-éab");
+        assert_eq!(Locale::new("en-1996").tag(), "en-1996");
+    }
+
+    #[test]
+    fn locale_direction_and_parents() {
+        assert_eq!(Locale::new("ar-EG").direction(), TextDirection::Rtl);
+        assert_eq!(Locale::new("pa-Arab").direction(), TextDirection::Rtl);
+        assert_eq!(Locale::new("az-Latn").direction(), TextDirection::Ltr);
+        assert_eq!(Locale::new("de").direction(), TextDirection::Ltr);
+        let locale = Locale::new("zh-Hant-TW");
+        assert_eq!(locale.chain().collect::<Vec<_>>(), ["zh-Hant-TW", "zh-Hant", "zh"]);
+    }
+
+    #[test]
+    fn messages_and_variables() {
+        let bundle = Bundle::from_ftl("# Greetings\nhello = Hello, { $name }!\n\nbye = Bye, {name}.\n").unwrap();
+        assert_eq!(format(&bundle, "hello", &[("name", "Ada")]).as_deref(), Some("Hello, Ada!"));
+        assert_eq!(format(&bundle, "bye", &[("name", "Ada")]).as_deref(), Some("Bye, Ada."));
+        assert_eq!(format(&bundle, "hello", &[]).as_deref(), Some("Hello, {$name}!"));
+        assert_eq!(format(&bundle, "missing", &[]), None);
+        assert!(bundle.contains("hello") && !bundle.contains("Greetings"));
+    }
+
+    #[test]
+    fn multiline_messages() {
+        let bundle = Bundle::from_ftl("long =\n    First line\n\n    second line\nnext = Next\n").unwrap();
+        assert_eq!(format(&bundle, "long", &[]).as_deref(), Some("First line\nsecond line"));
+        assert_eq!(format(&bundle, "next", &[]).as_deref(), Some("Next"));
+    }
+
+    #[test]
+    fn terms() {
+        let bundle = Bundle::from_ftl("-brand = Rinch\n-full = { -brand } Notes\nabout = About { -full }\nlost = { -missing }\n")
+            .unwrap();
+        assert_eq!(format(&bundle, "about", &[]).as_deref(), Some("About Rinch Notes"));
+        assert_eq!(format(&bundle, "lost", &[]).as_deref(), Some("{-missing}"));
+        // Terms aren't messages
+        assert!(!bundle.contains("brand") && !bundle.contains("-brand"));
+    }
+
+    #[test]
+    fn terms_referring_to_themselves_stop() {
+        let bundle = Bundle::from_ftl("-loop = x{ -loop }\nmessage = { -loop }\n").unwrap();
+        let text = format(&bundle, "message", &[]).unwrap();
+        assert_eq!(text, format!("{}{{-loop}}", "x".repeat(MAX_TERM_DEPTH)));
+    }
+
+    #[test]
+    fn attributes() {
+        let bundle = Bundle::from_ftl("save = Save\n    .tooltip = Save the note\nicon =\n    .alt = An icon\n").unwrap();
+        assert_eq!(format(&bundle, "save", &[]).as_deref(), Some("Save"));
+        assert_eq!(format(&bundle, "save.tooltip", &[]).as_deref(), Some("Save the note"));
+        assert_eq!(format(&bundle, "icon.alt", &[]).as_deref(), Some("An icon"));
+        // A message with only attributes has no value
+        assert!(!bundle.contains("icon"));
+    }
+
+    #[test]
+    fn selectors() {
+        let bundle = Bundle::from_ftl(
+            "notes = { $count ->\n    [0] No notes\n    [one] One note\n   *[other] { $count } notes\n}\n\
+             greeting = { $kind ->\n    [formal] Good day\n   *[casual] Hi\n} there\n",
+        )
+        .unwrap();
+        assert_eq!(format(&bundle, "notes", &[("count", "0")]).as_deref(), Some("No notes"));
+        assert_eq!(format(&bundle, "notes", &[("count", "1")]).as_deref(), Some("One note"));
+        assert_eq!(format(&bundle, "notes", &[("count", "2")]).as_deref(), Some("2 notes"));
+        assert_eq!(format(&bundle, "notes", &[("count", "0.0")]).as_deref(), Some("No notes"));
+        assert_eq!(format(&bundle, "greeting", &[("kind", "formal")]).as_deref(), Some("Good day there"));
+    }
+
+    #[test]
+    fn selectors_fall_back_to_the_default_variant() {
+        let bundle = Bundle::from_ftl("status = { $state ->\n   *[idle] Idle\n    [busy] Busy\n}\n").unwrap();
+        assert_eq!(format(&bundle, "status", &[("state", "busy")]).as_deref(), Some("Busy"));
+        assert_eq!(format(&bundle, "status", &[("state", "unknown")]).as_deref(), Some("Idle"));
+        assert_eq!(format(&bundle, "status", &[("state", "3")]).as_deref(), Some("Idle"));
+        assert_eq!(format(&bundle, "status", &[]).as_deref(), Some("Idle"));
+    }
+
+    #[test]
+    fn selectors_use_the_plural_rules_of_the_locale() {
+        let source = "files = { $count ->\n    [one] { $count } файл\n    [few] { $count } файла\n   *[many] { $count } файлов\n}\n";
+        let mut bundle = Bundle::from_ftl(source).unwrap();
+        bundle.locale = Locale::new("ru");
+        assert_eq!(format(&bundle, "files", &[("count", "21")]).as_deref(), Some("21 файл"));
+        assert_eq!(format(&bundle, "files", &[("count", "3")]).as_deref(), Some("3 файла"));
+        assert_eq!(format(&bundle, "files", &[("count", "11")]).as_deref(), Some("11 файлов"));
+
+        // French counts 0 as one; English doesn't
+        let source = "items = { $count ->\n    [one] one\n   *[other] other\n}\n";
+        let mut bundle = Bundle::from_ftl(source).unwrap();
+        assert_eq!(format(&bundle, "items", &[("count", "0")]).as_deref(), Some("other"));
+        bundle.locale = Locale::new("fr");
+        assert_eq!(format(&bundle, "items", &[("count", "0")]).as_deref(), Some("one"));
+    }
+
+    #[test]
+    fn string_literals() {
+        let bundle = Bundle::from_ftl(r#"braces = { "{" }x{ "}" } and { "\"q\"" }"#).unwrap();
+        assert_eq!(format(&bundle, "braces", &[]).as_deref(), Some(r#"{x} and "q""#));
+    }
+
+    #[test]
+    fn ftl_parse_errors() {
+        let error = parse_error("# Comment\n\n  indented = x\n");
+        assert_eq!((error.line, error.message.as_str()), (3, "indented line outside a message"));
+        assert_eq!(parse_error("just text").message, "expected `key = value`");
+        assert_eq!(parse_error("1abc = x").message, "invalid message name `1abc`");
+        assert_eq!(parse_error("a = b }").message, "`}` without a matching `{`");
+        assert_eq!(parse_error("a = { $x").message, "expected `}`");
+        assert_eq!(parse_error("a = { 1 }").message, "expected a `$variable`, `-term` or string in `{ }`");
+        assert_eq!(parse_error("a = { \"open }").message, "unterminated string");
+        assert_eq!(parse_error("a = { -t -> *[x] y }").message, "only variables can select a variant");
+        assert_eq!(parse_error("a = { $x ->\n  [one] y\n}").message, "missing a `*[default]` variant");
+        assert_eq!(parse_error("a = { $x ->\n *[one] y\n *[other] z\n}").message, "more than one `*[default]` variant");
+        assert_eq!(parse_error("a = { $x ->\n *[one y\n}").message, "expected `]`");
+        let error = parse_error("a = A\nb = B\nc = { $\n");
+        assert_eq!((error.line, error.message.as_str()), (3, "expected a name"));
+        assert_eq!(error.to_string(), "line 3: expected a name");
+    }
+
+    #[test]
+    fn invalid_messages_are_kept_as_text() {
+        let bundle = Bundle::new().message("odd", "100% { sure").message("ok", "Hi { $name }");
+        assert_eq!(format(&bundle, "odd", &[]).as_deref(), Some("100% { sure"));
+        assert_eq!(format(&bundle, "ok", &[("name", "Ada")]).as_deref(), Some("Hi Ada"));
+    }
+
+    #[test]
+    fn json_bundles() {
+        let bundle = Bundle::from_json(
+            r#"{
+                "hello": "Hello, { name }!",
+                "menu": { "open": "Open", "recent": { "clear": "Clear" } },
+                "empty": {}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(format(&bundle, "hello", &[("name", "Ada")]).as_deref(), Some("Hello, Ada!"));
+        assert_eq!(format(&bundle, "menu.open", &[]).as_deref(), Some("Open"));
+        assert_eq!(format(&bundle, "menu.recent.clear", &[]).as_deref(), Some("Clear"));
+        assert!(Bundle::from_json("{}").unwrap().messages.is_empty());
+    }
+
+    #[test]
+    fn json_escapes() {
+        let bundle = Bundle::from_json(r#"{"a": "q\"b\\s\/n\nt\tué€", "emoji": "😀!"}"#).unwrap();
+        assert_eq!(format(&bundle, "a", &[]).as_deref(), Some("q\"b\\s/n\nt\tué€"));
+        assert_eq!(format(&bundle, "emoji", &[]).as_deref(), Some("😀!"));
+    }
+
+    #[test]
+    fn json_invalid_surrogates() {
+        for source in [
+            r#"{"a": "\ud83d"}"#,
+            r#"{"a": "\ud83dx"}"#,
+            r#"{"a": "\ud83dA"}"#,
+            r#"{"a": "\udbff\u0000"}"#,
+            r#"{"a": "\ude00"}"#,
+        ] {
+            assert!(Bundle::from_json(source).is_err(), "{} should fail", source);
+        }
+        let error = Bundle::from_json(r#"{"a": "\u12"}"#).unwrap_err();
+        assert_eq!(error.message, "invalid `\\u` escape");
+    }
+
+    #[test]
+    fn json_parse_errors() {
+        let error = Bundle::from_json("{\n  \"a\": \"x\",\n  \"b\": 1\n}").unwrap_err();
+        assert_eq!((error.line, error.message.as_str()), (3, "expected a string or an object"));
+        assert_eq!(Bundle::from_json(r#"{"a": "x"} extra"#).unwrap_err().message, "unexpected text after the object");
+        assert_eq!(Bundle::from_json(r#"{"a": "x" "b": "y"}"#).unwrap_err().message, "expected `,` or `}`");
+        assert_eq!(Bundle::from_json(r#"{"a" "x"}"#).unwrap_err().message, "expected `:`");
+        assert_eq!(Bundle::from_json(r#"{"a": "x"#).unwrap_err().message, "unterminated string");
+        assert_eq!(Bundle::from_json(r#"{"a": "\q"}"#).unwrap_err().message, "invalid escape");
+        assert_eq!(Bundle::from_json("{\"a\": \"\u{1}\"}").unwrap_err().message, "unescaped control character in a string");
+        let error = Bundle::from_json("{\n\"a\": \"{ $x\"}").unwrap_err();
+        assert_eq!((error.line, error.message.as_str()), (2, "expected `}`"));
+    }
+
+    #[test]
+    fn translate_falls_back_to_parent_and_fallback_locales() {
+        add_bundle("en", Bundle::new().message("hello", "Hello").message("bye", "Bye"));
+        add_bundle("de", Bundle::new().message("hello", "Hallo"));
+        add_bundle("de-CH", Bundle::new().message("hello", "Grüezi"));
+        set_locale("de-CH");
+        assert_eq!(translate("hello", &[]), "Grüezi");
+        set_locale("de-AT");
+        assert_eq!(translate("hello", &[]), "Hallo");
+        assert_eq!(translate("bye", &[]), "Bye");
+        assert_eq!(translate("unknown.key", &[]), "unknown.key");
+        assert_eq!(use_text_direction().get(), TextDirection::Ltr);
+        set_locale("he");
+        assert_eq!(use_text_direction().get(), TextDirection::Rtl);
+    }
+}
@@ -0,0 +1,366 @@
+//! Plural categories of numbers, from the CLDR cardinal plural rules.
+//!
+//! A select expression given a number picks the variant named after the
+//! number's category in the bundle's locale: `zero`, `one`, `two`, `few`,
+//! `many` or `other`. The rules are built in for the languages below;
+//! others follow English.
+
+use super::Locale;
+
+/// The operands CLDR plural rules are written in, for a number as written:
+/// `1.50` has `n` 1.5, `i` 1, `v` 2, `f` 50 and `t` 5.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Operands {
+    /// The absolute value.
+    n: f64,
+    /// The integer digits.
+    i: u64,
+    /// How many fraction digits are written.
+    v: usize,
+    /// The fraction digits, with trailing zeros.
+    f: u64,
+    /// The fraction digits, without trailing zeros.
+    t: u64,
+}
+
+impl Operands {
+    /// The operands of a number written as `value`, such as `3` or `-1.50`.
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let n = value.parse::<f64>().ok()?.abs();
+        if !n.is_finite() {
+            return None;
+        }
+        let digits = value.trim_start_matches(['-', '+']);
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        // Exponents and the like are taken as whole numbers
+        let written = !integer.is_empty() && integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit());
+        if !written {
+            return Some(Self {
+                n,
+                i: n.trunc() as u64,
+                v: 0,
+                f: 0,
+                t: 0,
+            });
+        }
+        let trimmed = fraction.trim_end_matches('0');
+        Some(Self {
+            n,
+            i: integer.parse().unwrap_or(n.trunc() as u64),
+            v: fraction.len(),
+            f: fraction.parse().unwrap_or(0),
+            t: trimmed.parse().unwrap_or(0),
+        })
+    }
+
+    /// Whether `n` is a whole number from `low` to `high`.
+    fn n_in(&self, low: u64, high: u64) -> bool {
+        self.n.fract() == 0.0 && (low as f64..=high as f64).contains(&self.n)
+    }
+
+    /// Whether `n` modulo `modulus` is a whole number from `low` to `high`.
+    fn n_mod_in(&self, modulus: u64, low: u64, high: u64) -> bool {
+        let rest = self.n % modulus as f64;
+        rest.fract() == 0.0 && (low as f64..=high as f64).contains(&rest)
+    }
+}
+
+/// The plural category of a number written as `value` in a locale, or
+/// `None` if `value` isn't a number.
+pub(super) fn category(locale: &Locale, value: &str) -> Option<&'static str> {
+    let o = Operands::parse(value)?;
+    let (i, v, f, t) = (o.i, o.v, o.f, o.t);
+    let category = match locale.language() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "lo" | "my" | "km" | "yue" | "bo" | "jv" | "yo" | "ig"
+        | "wo" | "to" | "sg" => "other",
+        // `n = 1`
+        "af" | "az" | "bg" | "el" | "eu" | "hu" | "ka" | "kk" | "ky" | "mn" | "mr" | "ne" | "ps" | "sq" | "ta"
+        | "te" | "tr" | "uz" | "ml" | "or" | "tk" => {
+            if o.n == 1.0 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        // `i = 0,1`, and millions for French and Brazilian Portuguese
+        language @ ("fr" | "hy" | "kab" | "ff" | "pt") if language != "pt" || locale.region() != Some("PT") => {
+            if i <= 1 {
+                "one"
+            } else if matches!(language, "fr" | "pt") && i % 1_000_000 == 0 && v == 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "es" | "it" | "ca" | "pt" => {
+            let one = if locale.language() == "es" { o.n == 1.0 } else { i == 1 && v == 0 };
+            if one {
+                "one"
+            } else if i != 0 && i % 1_000_000 == 0 && v == 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        // `i = 0 or n = 1`
+        "hi" | "bn" | "fa" | "gu" | "kn" | "zu" | "am" | "as" => {
+            if i == 0 || o.n == 1.0 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "da" => {
+            if o.n == 1.0 || (t != 0 && i <= 1) {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "is" => {
+            if (t == 0 && i % 10 == 1 && i % 100 != 11) || (t % 10 == 1 && t % 100 != 11) {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "mk" => {
+            if (v == 0 && i % 10 == 1 && i % 100 != 11) || (f % 10 == 1 && f % 100 != 11) {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "fil" | "tl" => {
+            let one = if v == 0 {
+                (1..=3).contains(&i) || ![4, 6, 9].contains(&(i % 10))
+            } else {
+                ![4, 6, 9].contains(&(f % 10))
+            };
+            if one { "one" } else { "other" }
+        }
+        "cs" | "sk" => {
+            if i == 1 && v == 0 {
+                "one"
+            } else if (2..=4).contains(&i) && v == 0 {
+                "few"
+            } else if v != 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "pl" => {
+            if i == 1 && v == 0 {
+                "one"
+            } else if v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                "few"
+            } else if v == 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" => {
+            if v == 0 && i % 10 == 1 && i % 100 != 11 {
+                "one"
+            } else if v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                "few"
+            } else if v == 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "hr" | "sr" | "bs" | "sh" => {
+            if (v == 0 && i % 10 == 1 && i % 100 != 11) || (f % 10 == 1 && f % 100 != 11) {
+                "one"
+            } else if (v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)))
+                || ((2..=4).contains(&(f % 10)) && !(12..=14).contains(&(f % 100)))
+            {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        "lt" => {
+            if o.n_mod_in(10, 1, 1) && !o.n_mod_in(100, 11, 19) {
+                "one"
+            } else if o.n_mod_in(10, 2, 9) && !o.n_mod_in(100, 11, 19) {
+                "few"
+            } else if f != 0 {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "lv" => {
+            if o.n_mod_in(10, 0, 0) || o.n_mod_in(100, 11, 19) || (v == 2 && (11..=19).contains(&(f % 100))) {
+                "zero"
+            } else if (o.n_mod_in(10, 1, 1) && !o.n_mod_in(100, 11, 11)) || (f % 10 == 1 && (v != 2 || f % 100 != 11)) {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "sl" => {
+            if v == 0 && i % 100 == 1 {
+                "one"
+            } else if v == 0 && i % 100 == 2 {
+                "two"
+            } else if v != 0 || (3..=4).contains(&(i % 100)) {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        "ro" | "mo" => {
+            if i == 1 && v == 0 {
+                "one"
+            } else if v != 0 || o.n == 0.0 || (o.n != 1.0 && o.n_mod_in(100, 1, 19)) {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        "he" | "iw" => {
+            if (i == 1 && v == 0) || (i == 0 && v != 0) {
+                "one"
+            } else if i == 2 && v == 0 {
+                "two"
+            } else {
+                "other"
+            }
+        }
+        "ar" => {
+            if o.n == 0.0 {
+                "zero"
+            } else if o.n == 1.0 {
+                "one"
+            } else if o.n == 2.0 {
+                "two"
+            } else if o.n_mod_in(100, 3, 10) {
+                "few"
+            } else if o.n_mod_in(100, 11, 99) {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "ga" => {
+            if o.n == 1.0 {
+                "one"
+            } else if o.n == 2.0 {
+                "two"
+            } else if o.n_in(3, 6) {
+                "few"
+            } else if o.n_in(7, 10) {
+                "many"
+            } else {
+                "other"
+            }
+        }
+        "cy" => match (o.n.fract() == 0.0).then_some(i) {
+            Some(0) => "zero",
+            Some(1) => "one",
+            Some(2) => "two",
+            Some(3) => "few",
+            Some(6) => "many",
+            _ => "other",
+        },
+        // `i = 1 and v = 0`, as in English
+        _ => {
+            if i == 1 && v == 0 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    };
+    Some(category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(tag: &str, values: &[&str]) -> Vec<&'static str> {
+        let locale = Locale::new(tag);
+        values.iter().map(|value| category(&locale, value).unwrap()).collect()
+    }
+
+    #[test]
+    fn operands() {
+        let o = Operands::parse("-1.50").unwrap();
+        assert_eq!((o.n, o.i, o.v, o.f, o.t), (1.5, 1, 2, 50, 5));
+        let o = Operands::parse("1e3").unwrap();
+        assert_eq!((o.n, o.i, o.v), (1000.0, 1000, 0));
+        assert_eq!(Operands::parse("many"), None);
+        assert_eq!(Operands::parse("inf"), None);
+    }
+
+    #[test]
+    fn english() {
+        assert_eq!(categories("en", &["0", "1", "2", "1.0", "1.5"]), ["other", "one", "other", "other", "other"]);
+    }
+
+    #[test]
+    fn no_plurals() {
+        assert_eq!(categories("ja", &["0", "1", "2"]), ["other", "other", "other"]);
+    }
+
+    #[test]
+    fn french_counts_zero_as_one() {
+        assert_eq!(categories("fr", &["0", "1", "1.5", "2", "1000000"]), ["one", "one", "one", "other", "many"]);
+        assert_eq!(categories("pt-BR", &["0", "1"]), ["one", "one"]);
+        assert_eq!(categories("pt-PT", &["0", "1"]), ["other", "one"]);
+    }
+
+    #[test]
+    fn slavic() {
+        assert_eq!(
+            categories("ru", &["1", "2", "5", "11", "21", "22", "25", "1.5"]),
+            ["one", "few", "many", "many", "one", "few", "many", "other"]
+        );
+        assert_eq!(
+            categories("pl", &["1", "2", "5", "12", "22", "1.5"]),
+            ["one", "few", "many", "many", "few", "other"]
+        );
+        assert_eq!(categories("cs", &["1", "3", "5", "1.5"]), ["one", "few", "other", "many"]);
+        assert_eq!(categories("hr", &["1", "2", "5", "21", "0.1"]), ["one", "few", "other", "one", "one"]);
+        assert_eq!(categories("sl", &["1", "2", "3", "5", "101"]), ["one", "two", "few", "other", "one"]);
+    }
+
+    #[test]
+    fn baltic() {
+        assert_eq!(categories("lt", &["1", "2", "11", "10", "0.5"]), ["one", "few", "other", "other", "many"]);
+        assert_eq!(categories("lv", &["0", "1", "2", "11", "21"]), ["zero", "one", "other", "zero", "one"]);
+    }
+
+    #[test]
+    fn arabic() {
+        assert_eq!(
+            categories("ar", &["0", "1", "2", "3", "11", "100", "102"]),
+            ["zero", "one", "two", "few", "many", "other", "other"]
+        );
+    }
+
+    #[test]
+    fn celtic() {
+        assert_eq!(categories("ga", &["1", "2", "3", "7", "11"]), ["one", "two", "few", "many", "other"]);
+        assert_eq!(categories("cy", &["0", "1", "2", "3", "6", "4"]), ["zero", "one", "two", "few", "many", "other"]);
+    }
+
+    #[test]
+    fn others() {
+        assert_eq!(categories("hi", &["0", "1", "2"]), ["one", "one", "other"]);
+        assert_eq!(categories("he", &["1", "2", "3"]), ["one", "two", "other"]);
+        assert_eq!(categories("ro", &["1", "2", "19", "20", "101"]), ["one", "few", "few", "other", "few"]);
+        assert_eq!(categories("is", &["1", "11", "21", "0.1"]), ["one", "other", "one", "one"]);
+        assert_eq!(categories("da", &["0", "1", "0.5", "2"]), ["other", "one", "one", "other"]);
+        assert_eq!(categories("tr", &["1", "2"]), ["one", "other"]);
+        // Unknown languages follow English
+        assert_eq!(categories("xx", &["1", "2"]), ["one", "other"]);
+    }
+}
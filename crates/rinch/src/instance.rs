@@ -0,0 +1,505 @@
+//! Handing messages from a second instance of the app to the running one.
+//!
+//! When the OS starts the app again to open a link or run a jump list task,
+//! the new instance sends its request to the one already running and exits.
+//! The running instance listens on an endpoint only the same user can reach:
+//! a Unix socket in `$XDG_RUNTIME_DIR`, or in a private per-user directory in
+//! the temp directory, and on Windows a named pipe whose ACL only admits the
+//! user. It acknowledges what it received, so an endpoint left behind by an
+//! instance that crashed isn't mistaken for a running one.
+//!
+//! Messages are sent one per line, ending with an empty line. Each connection
+//! is handled on its own thread with a read timeout, so a client that
+//! connects and stalls can't hold up the others.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Reply of the running instance once it has received forwarded messages.
+const ACK: &[u8] = b"ok";
+
+/// How long either side waits for the other while exchanging messages.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Send messages (one per line) to the running instance. Returns `false` if
+/// there isn't one.
+pub(crate) fn forward(channel: &str, messages: &[String]) -> bool {
+    match forward_to(channel, messages) {
+        Ok(acknowledged) => acknowledged,
+        Err(e) => {
+            tracing::debug!("No running instance on {}: {}", channel, e);
+            false
+        }
+    }
+}
+
+/// Accept messages from later instances on a background thread, passing the
+/// messages of each instance to `on_messages`.
+pub(crate) fn listen(channel: &str, on_messages: impl Fn(Vec<String>) + Send + Sync + 'static) {
+    if let Err(e) = listen_on(channel, Arc::new(on_messages)) {
+        tracing::warn!("Failed to listen for other instances: {}", e);
+    }
+}
+
+/// Callback for the messages of one instance, shared by connection threads.
+type OnMessages = Arc<dyn Fn(Vec<String>) + Send + Sync>;
+
+/// Write the messages and an empty line, then wait for the acknowledgement.
+fn send_messages(mut stream: impl Read + Write, messages: &[String]) -> io::Result<bool> {
+    for message in messages {
+        writeln!(stream, "{}", message)?;
+    }
+    writeln!(stream)?;
+    stream.flush()?;
+    let mut reply = [0; ACK.len()];
+    stream.read_exact(&mut reply)?;
+    Ok(reply == ACK)
+}
+
+/// Read messages up to an empty line, or the end of the stream, and
+/// acknowledge them.
+fn receive_messages(mut stream: impl Read + Write) -> io::Result<Vec<String>> {
+    let mut messages = Vec::new();
+    for line in BufReader::new(&mut stream).lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        messages.push(line);
+    }
+    stream.write_all(ACK)?;
+    Ok(messages)
+}
+
+/// Handle one connection on its own thread.
+fn spawn_connection<S: Send + 'static>(
+    channel: &str,
+    stream: S,
+    receive: impl FnOnce(S) -> io::Result<Vec<String>> + Send + 'static,
+    on_messages: &OnMessages,
+) {
+    let on_messages = Arc::clone(on_messages);
+    let spawned = std::thread::Builder::new()
+        .name(format!("rinch-{}-connection", channel))
+        .spawn(move || match receive(stream) {
+            Ok(messages) if !messages.is_empty() => on_messages(messages),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Failed to read messages from another instance: {}", e),
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to handle a message from another instance: {}", e);
+    }
+}
+
+#[cfg(unix)]
+fn forward_to(channel: &str, messages: &[String]) -> io::Result<bool> {
+    let stream = std::os::unix::net::UnixStream::connect(socket_path(channel)?)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.set_write_timeout(Some(FORWARD_TIMEOUT))?;
+    send_messages(&stream, messages)
+}
+
+#[cfg(unix)]
+fn listen_on(channel: &str, on_messages: OnMessages) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    let path = socket_path(channel)?;
+    // Left behind by an instance that exited without cleaning up, since
+    // forwarding to it just failed
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    let name = channel.to_string();
+    std::thread::Builder::new()
+        .name(format!("rinch-{}", channel))
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = stream.set_read_timeout(Some(FORWARD_TIMEOUT)) {
+                    tracing::debug!("Failed to set a read timeout: {}", e);
+                    continue;
+                }
+                spawn_connection(&name, stream, |stream| receive_messages(&stream), &on_messages);
+            }
+        })?;
+    Ok(())
+}
+
+/// Socket the running instance accepts messages on.
+#[cfg(unix)]
+fn socket_path(channel: &str) -> io::Result<std::path::PathBuf> {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        // Already private to the user, by the spec
+        Some(dir) if std::path::Path::new(&dir).is_dir() => dir.into(),
+        _ => private_dir()?,
+    };
+    Ok(dir.join(format!("rinch-{}.sock", channel)))
+}
+
+/// A directory in the temp directory that only the current user can use.
+#[cfg(unix)]
+fn private_dir() -> io::Result<std::path::PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    // SAFETY: getuid has no preconditions and can't fail.
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("rinch-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    // Another user may have created it first
+    let metadata = std::fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} isn't private to this user", dir.display()),
+        ));
+    }
+    Ok(dir)
+}
+
+#[cfg(windows)]
+fn forward_to(channel: &str, messages: &[String]) -> io::Result<bool> {
+    use std::fs::OpenOptions;
+    use windows_sys::Win32::Foundation::ERROR_PIPE_BUSY;
+    use windows_sys::Win32::System::Pipes::WaitNamedPipeW;
+
+    let sid = pipe_security::user_sid()?;
+    let name = pipe_name(channel, &sid);
+    let open = || OpenOptions::new().read(true).write(true).open(&name);
+    let pipe = match open() {
+        Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+            let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+            // SAFETY: `wide` is a null-terminated UTF-16 string.
+            unsafe { WaitNamedPipeW(wide.as_ptr(), FORWARD_TIMEOUT.as_millis() as u32) };
+            open()?
+        }
+        result => result?,
+    };
+    // Another user could have created a pipe with this name first
+    if pipe_security::owner_sid(&pipe)? != sid {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "pipe is owned by another user"));
+    }
+    let messages = messages.to_vec();
+    with_timeout(pipe, move |pipe| send_messages(pipe, &messages))
+}
+
+#[cfg(windows)]
+fn listen_on(channel: &str, on_messages: OnMessages) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_PIPE_CONNECTED};
+    use windows_sys::Win32::System::Pipes::ConnectNamedPipe;
+
+    let sid = pipe_security::user_sid()?;
+    let security = pipe_security::SecurityDescriptor::user_only(&sid)?;
+    let name = pipe_name(channel, &sid);
+    // Fails if the pipe already exists, so it can't be taken over
+    let mut pipe = security.create_pipe(&name, true)?;
+
+    let channel = channel.to_string();
+    std::thread::Builder::new()
+        .name(format!("rinch-{}", channel))
+        .spawn(move || loop {
+            // SAFETY: the handle is a pipe instance that's open until `pipe`
+            // is dropped.
+            let connected = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) } != 0
+                || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+            let next = match security.create_pipe(&name, false) {
+                Ok(next) => next,
+                Err(e) => {
+                    tracing::warn!("Stopped listening for other instances: {}", e);
+                    return;
+                }
+            };
+            let current = std::mem::replace(&mut pipe, next);
+            if connected {
+                spawn_connection(
+                    &channel,
+                    current,
+                    |pipe| with_timeout(pipe, |pipe| receive_messages(pipe)),
+                    &on_messages,
+                );
+            }
+        })?;
+    Ok(())
+}
+
+/// Name of the pipe, which includes the user's SID since pipe names are
+/// shared by all users.
+#[cfg(windows)]
+fn pipe_name(channel: &str, sid: &str) -> String {
+    format!(r"\\.\pipe\rinch-{}-{}", channel, sid)
+}
+
+/// Exchange messages over a pipe, giving up after [`FORWARD_TIMEOUT`].
+///
+/// Reads on a synchronous pipe can't time out, so the exchange runs on its
+/// own thread and its pending I/O is cancelled when time runs out.
+#[cfg(windows)]
+fn with_timeout<T: Send + 'static>(
+    pipe: std::fs::File,
+    exchange: impl FnOnce(&std::fs::File) -> io::Result<T> + Send + 'static,
+) -> io::Result<T> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::System::IO::CancelSynchronousIo;
+
+    let (done, finished) = std::sync::mpsc::channel();
+    let worker = std::thread::Builder::new()
+        .name("rinch-pipe".into())
+        .spawn(move || {
+            let result = exchange(&pipe);
+            let _ = done.send(());
+            result
+        })?;
+    if finished.recv_timeout(FORWARD_TIMEOUT).is_err() {
+        // SAFETY: the thread handle stays valid until the thread is joined.
+        // If the exchange just finished, there's nothing to cancel.
+        unsafe { CancelSynchronousIo(worker.as_raw_handle()) };
+    }
+    worker
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("pipe thread panicked")))
+}
+
+/// Restricting named pipes to the current user.
+#[cfg(windows)]
+mod pipe_security {
+    use std::io;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+    use std::ptr::null_mut;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Security::Authorization::{
+        ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW,
+        GetSecurityInfo, SDDL_REVISION_1, SE_KERNEL_OBJECT,
+    };
+    use windows_sys::Win32::Security::{
+        GetTokenInformation, TokenUser, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID,
+        SECURITY_ATTRIBUTES, TOKEN_QUERY, TOKEN_USER,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+    use windows_sys::Win32::System::Pipes::{
+        CreateNamedPipeW, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    /// The current user's SID, like `S-1-5-21-...`.
+    pub(super) fn user_sid() -> io::Result<String> {
+        // SAFETY: the token is closed after use, and the buffer is sized by
+        // the first call and aligned for TOKEN_USER.
+        unsafe {
+            let mut token: HANDLE = null_mut();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut len = 0;
+            GetTokenInformation(token, TokenUser, null_mut(), 0, &mut len);
+            let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+            let ok = GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), len, &mut len);
+            let error = io::Error::last_os_error();
+            CloseHandle(token);
+            if ok == 0 {
+                return Err(error);
+            }
+            let user = &*buffer.as_ptr().cast::<TOKEN_USER>();
+            sid_string(user.User.Sid)
+        }
+    }
+
+    /// The SID of the owner of an open pipe.
+    pub(super) fn owner_sid(pipe: &std::fs::File) -> io::Result<String> {
+        let mut owner: PSID = null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+        // SAFETY: `owner` points into `descriptor`, which is freed after the
+        // SID is converted.
+        unsafe {
+            let error = GetSecurityInfo(
+                pipe.as_raw_handle(),
+                SE_KERNEL_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                &mut owner,
+                null_mut(),
+                null_mut(),
+                null_mut(),
+                &mut descriptor,
+            );
+            if error != 0 {
+                return Err(io::Error::from_raw_os_error(error as i32));
+            }
+            let sid = sid_string(owner);
+            LocalFree(descriptor);
+            sid
+        }
+    }
+
+    /// Convert a SID to its string form.
+    ///
+    /// # Safety
+    ///
+    /// `sid` must point to a valid SID.
+    unsafe fn sid_string(sid: PSID) -> io::Result<String> {
+        let mut string = null_mut();
+        // SAFETY: the string is allocated by the call, read up to its null
+        // terminator and then freed.
+        unsafe {
+            if ConvertSidToStringSidW(sid, &mut string) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let len = (0..).take_while(|&i| *string.add(i) != 0).count();
+            let sid = String::from_utf16_lossy(std::slice::from_raw_parts(string, len));
+            LocalFree(string.cast());
+            Ok(sid)
+        }
+    }
+
+    /// A security descriptor, freed when dropped.
+    pub(super) struct SecurityDescriptor(PSECURITY_DESCRIPTOR);
+
+    // SAFETY: the descriptor is only read after it's created.
+    unsafe impl Send for SecurityDescriptor {}
+
+    impl SecurityDescriptor {
+        /// Owned by the user with this SID, and only accessible to them.
+        pub(super) fn user_only(sid: &str) -> io::Result<Self> {
+            let sddl: Vec<u16> = format!("O:{sid}D:P(A;;GA;;;{sid})")
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            let mut descriptor = null_mut();
+            // SAFETY: `sddl` is a null-terminated UTF-16 string.
+            let ok = unsafe {
+                ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                    sddl.as_ptr(),
+                    SDDL_REVISION_1,
+                    &mut descriptor,
+                    null_mut(),
+                )
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self(descriptor))
+        }
+
+        /// Create an instance of the pipe `name` with this descriptor.
+        /// `first` fails if the pipe already exists.
+        pub(super) fn create_pipe(&self, name: &str, first: bool) -> io::Result<std::fs::File> {
+            let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+            let attributes = SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: self.0,
+                bInheritHandle: 0,
+            };
+            let mut open_mode = PIPE_ACCESS_DUPLEX;
+            if first {
+                open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+            }
+            // SAFETY: `wide` is null-terminated and `attributes` points to a
+            // live descriptor.
+            let handle = unsafe {
+                CreateNamedPipeW(
+                    wide.as_ptr(),
+                    open_mode,
+                    PIPE_TYPE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                    PIPE_UNLIMITED_INSTANCES,
+                    4096,
+                    4096,
+                    0,
+                    &attributes,
+                )
+            };
+            if handle == INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: the handle was just created and is owned by the file.
+            Ok(unsafe { OwnedHandle::from_raw_handle(handle) }.into())
+        }
+    }
+
+    impl Drop for SecurityDescriptor {
+        fn drop(&mut self) {
+            // SAFETY: the descriptor was allocated with LocalAlloc.
+            unsafe { LocalFree(self.0) };
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn forward_to(_channel: &str, _messages: &[String]) -> io::Result<bool> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn listen_on(_channel: &str, _on_messages: OnMessages) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn messages_are_forwarded_and_acknowledged() {
+        let channel = format!("test-{}", std::process::id());
+        let (sender, received) = mpsc::channel();
+        listen(&channel, move |messages| sender.send(messages).unwrap());
+
+        let messages = vec!["myapp://open/a".to_string(), "second".to_string()];
+        assert!(forward(&channel, &messages));
+        assert_eq!(received.recv_timeout(FORWARD_TIMEOUT).unwrap(), messages);
+
+        // A client that never finishes doesn't hold up the next one
+        let stalled = std::os::unix::net::UnixStream::connect(socket_path(&channel).unwrap()).unwrap();
+        assert!(forward(&channel, &["third".to_string()]));
+        assert_eq!(received.recv_timeout(FORWARD_TIMEOUT).unwrap(), ["third"]);
+        drop(stalled);
+
+        let _ = std::fs::remove_file(socket_path(&channel).unwrap());
+    }
+
+    #[test]
+    fn socket_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let channel = format!("private-{}", std::process::id());
+        listen(&channel, |_| {});
+        let path = socket_path(&channel).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn nothing_to_forward_to() {
+        assert!(!forward(&format!("missing-{}", std::process::id()), &["link".to_string()]));
+    }
+
+    #[test]
+    fn stale_socket_is_replaced() {
+        let channel = format!("stale-{}", std::process::id());
+        let path = socket_path(&channel).unwrap();
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(!forward(&channel, &["link".to_string()]));
+
+        let (sender, received) = mpsc::channel();
+        listen(&channel, move |messages| sender.send(messages).unwrap());
+        assert!(forward(&channel, &["link".to_string()]));
+        assert_eq!(received.recv_timeout(FORWARD_TIMEOUT).unwrap(), ["link"]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn receive_stops_at_empty_line() {
+        let mut stream = io::Cursor::new(b"one\ntwo\n\nignored\n".to_vec());
+        let messages = receive_messages(&mut stream).unwrap();
+        assert_eq!(messages, ["one", "two"]);
+        assert!(stream.into_inner().ends_with(ACK));
+    }
+}
@@ -0,0 +1,480 @@
+//! Talking to another process over newline-delimited JSON.
+//!
+//! For apps that are the front end of a separate backend process, such as
+//! a daemon or a companion CLI. Every message is one line holding a JSON
+//! object with a `name` and a `value`:
+//!
+//! ```text
+//! {"name": "progress", "value": 0.42}
+//! {"name": "status", "value": {"state": "syncing", "files": 12}}
+//! ```
+//!
+//! [`serve_stdio`] exchanges messages over the app's stdin and stdout, for a
+//! backend that starts the app as a child process. [`serve_socket`] (Unix)
+//! and [`serve_tcp`] accept any number of processes connecting to a socket.
+//! Each returns an [`IpcChannel`], which sets signals to the values of
+//! incoming messages with [`bind`](IpcChannel::bind), calls
+//! [`on_message`](IpcChannel::on_message) callbacks, and sends messages to
+//! every connected process with [`send`](IpcChannel::send). Incoming
+//! messages are read on background threads and handled on the UI thread,
+//! which re-renders after each one. Outgoing messages are queued and written
+//! on a background thread per process, so a process that stops reading
+//! doesn't block the UI.
+//!
+//! While stdio is in use, rinch's terminal log goes to stderr so it doesn't
+//! mix with the messages on stdout.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::ipc;
+//!
+//! fn app() -> Element {
+//!     let progress = use_signal(|| 0.0_f64);
+//!     let channel = use_ref(|| {
+//!         let channel = ipc::serve_stdio().expect("stdio channel");
+//!         channel.bind("progress", progress.clone());
+//!         channel
+//!     })
+//!     .get();
+//!
+//!     rsx! {
+//!         Window { title: "Sync",
+//!             progress { value: progress.get().to_string() }
+//!             button { onclick: move || { let _ = channel.send("cancel", ()); }, "Cancel" }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Requires the `ipc` feature.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+#[cfg(unix)]
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+use rinch_core::Signal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// IPC error type.
+#[derive(Debug)]
+pub enum IpcError {
+    /// Reading, writing or listening failed.
+    Io(std::io::Error),
+    /// A value couldn't be turned into JSON.
+    Encode(serde_json::Error),
+}
+
+impl std::fmt::Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpcError::Io(e) => write!(f, "IPC I/O error: {}", e),
+            IpcError::Encode(e) => write!(f, "failed to encode IPC message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IpcError::Io(e) => Some(e),
+            IpcError::Encode(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for IpcError {
+    fn from(e: std::io::Error) -> Self {
+        IpcError::Io(e)
+    }
+}
+
+/// Result type for IPC operations.
+pub type IpcResult<T> = Result<T, IpcError>;
+
+/// A message exchanged with another process: one line of JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpcMessage {
+    /// What the message is about, such as `"progress"`.
+    pub name: String,
+    /// The message's data.
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// The connected processes of a channel, with an ID each so a process can
+/// be removed when it disconnects, and the queue of lines its writer thread
+/// writes.
+type Peers = Arc<Mutex<Vec<(u64, Sender<Arc<str>>)>>>;
+
+/// A channel to other processes, returned by [`serve_stdio`],
+/// [`serve_socket`] and [`serve_tcp`].
+///
+/// Clones refer to the same channel. It keeps serving for as long as the
+/// app runs.
+#[derive(Clone)]
+pub struct IpcChannel {
+    id: u64,
+    peers: Peers,
+}
+
+impl IpcChannel {
+    fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(1);
+        Self {
+            id: COUNTER.fetch_add(1, Ordering::Relaxed),
+            peers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Set `signal` to the value of every message named `name`.
+    ///
+    /// If such a message already arrived, the signal is set to the latest
+    /// one's value straight away. Values that don't deserialize to `T` are
+    /// logged and skipped. Must be called on the UI thread.
+    pub fn bind<T: DeserializeOwned + 'static>(&self, name: impl Into<String>, signal: Signal<T>) {
+        let name = name.into();
+        let binding: Handler = Rc::new(move |message| match serde_json::from_value::<T>(message.value.clone()) {
+            Ok(value) => signal.set(value),
+            Err(e) => tracing::warn!("Ignoring IPC message `{}`: {}", message.name, e),
+        });
+        let latest = with_handlers(self.id, |handlers| {
+            handlers.bindings.entry(name.clone()).or_default().push(binding.clone());
+            handlers.latest.get(&name).cloned()
+        });
+        if let Some(message) = latest {
+            binding(&message);
+        }
+    }
+
+    /// Call `callback` with every message that arrives from now on.
+    ///
+    /// Must be called on the UI thread.
+    pub fn on_message(&self, callback: impl Fn(&IpcMessage) + 'static) {
+        with_handlers(self.id, |handlers| handlers.callbacks.push(Rc::new(callback)));
+    }
+
+    /// Send a message to every connected process.
+    ///
+    /// The message is queued for each process and written on a background
+    /// thread, so this doesn't wait for processes that are slow to read.
+    /// Processes that can't be written to any more are disconnected. Sending
+    /// with no process connected does nothing.
+    pub fn send(&self, name: impl Into<String>, value: impl Serialize) -> IpcResult<()> {
+        let message = IpcMessage {
+            name: name.into(),
+            value: serde_json::to_value(value).map_err(IpcError::Encode)?,
+        };
+        let mut line = serde_json::to_string(&message).map_err(IpcError::Encode)?;
+        line.push('\n');
+        let line: Arc<str> = line.into();
+        // A peer whose writer thread stopped can't receive any more
+        self.peers.lock().unwrap().retain(|(_, queue)| queue.send(line.clone()).is_ok());
+        Ok(())
+    }
+
+    /// The number of processes connected to the channel.
+    pub fn peer_count(&self) -> usize {
+        self.peers.lock().unwrap().len()
+    }
+
+    /// Start exchanging messages with a process: `reader` is read on a
+    /// background thread, and `writer` written what is sent on another.
+    ///
+    /// The process is disconnected when either ends: removing it drops its
+    /// queue, which stops the writer thread.
+    fn connect(&self, reader: impl Read + Send + 'static, writer: impl Write + Send + 'static) -> IpcResult<()> {
+        static PEER_COUNTER: AtomicU64 = AtomicU64::new(1);
+        let peer = PEER_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let (queue, lines) = mpsc::channel();
+        self.peers.lock().unwrap().push((peer, queue));
+        let disconnect = move |peers: &Peers| peers.lock().unwrap().retain(|(other, _)| *other != peer);
+
+        let peers = self.peers.clone();
+        let spawned = std::thread::Builder::new()
+            .name("rinch-ipc-writer".into())
+            .spawn(move || {
+                write_messages(writer, lines);
+                disconnect(&peers);
+            });
+        if let Err(e) = spawned {
+            disconnect(&self.peers);
+            return Err(e.into());
+        }
+
+        let (id, peers) = (self.id, self.peers.clone());
+        let spawned = std::thread::Builder::new()
+            .name("rinch-ipc".into())
+            .spawn(move || {
+                read_messages(id, BufReader::new(reader));
+                disconnect(&peers);
+            });
+        if let Err(e) = spawned {
+            disconnect(&self.peers);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for IpcChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpcChannel")
+            .field("id", &self.id)
+            .field("peers", &self.peer_count())
+            .finish()
+    }
+}
+
+/// Exchange messages over the app's stdin and stdout.
+///
+/// Use this when the backend starts the app as a child process with piped
+/// stdio. rinch's terminal log moves to stderr.
+pub fn serve_stdio() -> IpcResult<IpcChannel> {
+    crate::logging::move_terminal_to_stderr();
+    let channel = IpcChannel::new();
+    channel.connect(std::io::stdin(), std::io::stdout())?;
+    Ok(channel)
+}
+
+/// Accept processes connecting to a Unix domain socket at `path`.
+///
+/// Only processes of the same user can connect. A socket left at `path` by
+/// an earlier run is replaced.
+#[cfg(unix)]
+pub fn serve_socket(path: impl AsRef<Path>) -> IpcResult<IpcChannel> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    let path = path.as_ref();
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    serve(move || {
+        let (stream, _) = listener.accept()?;
+        Ok((stream.try_clone()?, stream))
+    })
+}
+
+/// Accept processes connecting to a TCP address, such as
+/// `"127.0.0.1:7400"`.
+///
+/// Listen on a loopback address: anything that can reach the address can
+/// push messages into the app. On Windows, this takes the place of
+/// [`serve_socket`].
+pub fn serve_tcp(address: impl ToSocketAddrs) -> IpcResult<IpcChannel> {
+    let listener = TcpListener::bind(address)?;
+    serve(move || {
+        let (stream, _) = listener.accept()?;
+        Ok((stream.try_clone()?, stream))
+    })
+}
+
+/// Accept connections on a background thread: `accept` waits for the next
+/// one and returns its reading and writing halves.
+fn serve<R, W>(mut accept: impl FnMut() -> std::io::Result<(R, W)> + Send + 'static) -> IpcResult<IpcChannel>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let channel = IpcChannel::new();
+    let accepting = channel.clone();
+    std::thread::Builder::new()
+        .name("rinch-ipc".into())
+        .spawn(move || loop {
+            let connected = accept()
+                .map_err(IpcError::from)
+                .and_then(|(reader, writer)| accepting.connect(reader, writer));
+            if let Err(e) = connected {
+                tracing::warn!("Failed to accept IPC connection: {}", e);
+            }
+        })?;
+    Ok(channel)
+}
+
+/// A bound signal or an `on_message` callback.
+type Handler = Rc<dyn Fn(&IpcMessage)>;
+
+/// What a channel does with its messages, on the UI thread.
+#[derive(Default)]
+struct Handlers {
+    bindings: HashMap<String, Vec<Handler>>,
+    callbacks: Vec<Handler>,
+    /// The latest message of each name, for signals bound later.
+    latest: HashMap<String, IpcMessage>,
+}
+
+thread_local! {
+    /// The handlers of each channel, by channel ID.
+    static HANDLERS: RefCell<HashMap<u64, Handlers>> = RefCell::new(HashMap::new());
+}
+
+fn with_handlers<R>(channel: u64, f: impl FnOnce(&mut Handlers) -> R) -> R {
+    HANDLERS.with(|handlers| f(handlers.borrow_mut().entry(channel).or_default()))
+}
+
+/// Read messages until the process disconnects, handing each to the UI
+/// thread.
+fn read_messages(channel: u64, reader: impl BufRead) {
+    let app = crate::tasks::app_handle();
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::debug!("IPC peer disconnected: {}", e);
+                return;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<IpcMessage>(&line) {
+            Ok(message) => app.run_on_ui_thread(move || deliver(channel, message)),
+            Err(e) => tracing::warn!("Ignoring malformed IPC message: {}", e),
+        }
+    }
+}
+
+/// Write the lines queued for a process until it can't be written to, or
+/// the process is disconnected and the queue closes.
+fn write_messages(mut writer: impl Write, lines: mpsc::Receiver<Arc<str>>) {
+    for line in lines {
+        if let Err(e) = writer.write_all(line.as_bytes()).and_then(|()| writer.flush()) {
+            tracing::debug!("Dropping IPC peer: {}", e);
+            return;
+        }
+    }
+}
+
+/// Set the bound signals and call the callbacks of a channel.
+fn deliver(channel: u64, message: IpcMessage) {
+    // Cloned out so the handlers can bind more signals
+    let (bindings, callbacks) = with_handlers(channel, |handlers| {
+        handlers.latest.insert(message.name.clone(), message.clone());
+        (
+            handlers.bindings.get(&message.name).cloned().unwrap_or_default(),
+            handlers.callbacks.clone(),
+        )
+    });
+    for binding in bindings {
+        binding(&message);
+    }
+    for callback in callbacks {
+        callback(&message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::run_ui_tasks;
+    use crate::tasks::tests::lock_ui_tasks;
+    use std::io::Cursor;
+    use std::time::{Duration, Instant};
+
+    /// Wait for `done`, running the closures scheduled for the UI thread.
+    fn wait_for(done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out");
+            run_ui_tasks();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn messages_read_set_bound_signals() {
+        let _lock = lock_ui_tasks();
+        let channel = IpcChannel::new();
+        let progress = Signal::new(0.0_f64);
+        channel.bind("progress", progress.clone());
+        let names = Rc::new(RefCell::new(Vec::new()));
+        let seen = names.clone();
+        channel.on_message(move |message| seen.borrow_mut().push(message.name.clone()));
+
+        let input = "{\"name\": \"progress\", \"value\": 0.5}\n\nnot json\n{\"name\": \"status\"}\n";
+        read_messages(channel.id, Cursor::new(input));
+        run_ui_tasks();
+
+        assert_eq!(progress.get(), 0.5);
+        assert_eq!(*names.borrow(), ["progress", "status"]);
+    }
+
+    #[test]
+    fn signals_bound_later_get_the_latest_value() {
+        let channel = IpcChannel::new();
+        deliver(channel.id, IpcMessage { name: "count".into(), value: 1.into() });
+        deliver(channel.id, IpcMessage { name: "count".into(), value: 2.into() });
+        // Values of the wrong type are skipped
+        deliver(channel.id, IpcMessage { name: "label".into(), value: 3.into() });
+
+        let count = Signal::new(0);
+        channel.bind("count", count.clone());
+        assert_eq!(count.get(), 2);
+        let label = Signal::new(String::new());
+        channel.bind("label", label.clone());
+        assert_eq!(label.get(), "");
+    }
+
+    #[test]
+    fn messages_are_exchanged_over_pipes() {
+        let _lock = lock_ui_tasks();
+        let channel = IpcChannel::new();
+        let (app_in, mut peer_out) = std::io::pipe().unwrap();
+        let (peer_in, app_out) = std::io::pipe().unwrap();
+        channel.connect(app_in, app_out).unwrap();
+        assert_eq!(channel.peer_count(), 1);
+
+        channel.send("hello", "peer").unwrap();
+        let mut line = String::new();
+        BufReader::new(peer_in).read_line(&mut line).unwrap();
+        let message: IpcMessage = serde_json::from_str(&line).unwrap();
+        assert_eq!(message, IpcMessage { name: "hello".into(), value: "peer".into() });
+
+        let greeting = Signal::new(String::new());
+        channel.bind("hello", greeting.clone());
+        peer_out.write_all(b"{\"name\": \"hello\", \"value\": \"app\"}\n").unwrap();
+        wait_for(|| greeting.get() == "app");
+
+        // Closing the peer's end disconnects it
+        drop(peer_out);
+        wait_for(|| channel.peer_count() == 0);
+    }
+
+    #[test]
+    fn sending_doesnt_wait_for_peers_that_dont_read() {
+        let channel = IpcChannel::new();
+        let (app_in, _peer_out) = std::io::pipe().unwrap();
+        let (_peer_in, app_out) = std::io::pipe().unwrap();
+        channel.connect(app_in, app_out).unwrap();
+
+        // Well past what a pipe buffers
+        let value = "x".repeat(1024);
+        for _ in 0..1024 {
+            channel.send("filler", &value).unwrap();
+        }
+        assert_eq!(channel.peer_count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sockets_are_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("rinch-ipc-test-{}.sock", std::process::id()));
+        serve_socket(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_file(path);
+    }
+}
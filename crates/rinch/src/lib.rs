@@ -38,6 +38,8 @@
 //! | [`use_mount`] | One-time effect on first render |
 //! | [`use_memo`] | Memoized expensive computations |
 //! | [`use_callback`] | Memoized callbacks |
+//! | [`use_coroutine`] | Long-lived async task that receives messages (`async` feature) |
+//! | [`use_stream`] | Signal holding the latest item of a stream (`async` feature) |
 //!
 //! ## Example with State
 //!
@@ -87,10 +89,36 @@
 //! [`use_mount`]: prelude::use_mount
 //! [`use_memo`]: prelude::use_memo
 //! [`use_callback`]: prelude::use_callback
+//! [`use_coroutine`]: prelude::use_coroutine
+//! [`use_stream`]: prelude::use_stream
 
 pub mod app;
+pub mod assets;
+pub mod autostart;
+pub mod deep_link;
+pub mod history;
+pub mod i18n;
+mod instance;
+pub mod logging;
+pub mod media;
 pub mod menu;
+pub mod messages;
+pub mod mouse;
+pub mod observe;
+pub mod plugin;
+pub mod power;
+pub mod recent_files;
+pub mod scroll;
+pub mod session;
+pub mod settings;
 pub mod shell;
+pub mod spelling;
+pub mod taskbar;
+pub mod tasks;
+pub mod testing;
+pub mod theme;
+pub mod ui_settings;
+pub mod undo;
 pub mod window;
 pub mod windows;
 
@@ -103,6 +131,12 @@ pub mod clipboard;
 #[cfg(feature = "system-tray")]
 pub mod tray;
 
+#[cfg(feature = "notifications")]
+pub mod notify;
+
+#[cfg(feature = "ipc")]
+pub mod ipc;
+
 pub mod prelude {
     //! Common imports for rinch applications.
     pub use crate::shell::run;
@@ -113,7 +147,13 @@ pub mod prelude {
         create_context, use_callback, use_context, use_derived, use_effect, use_effect_cleanup,
         use_memo, use_mount, use_ref, use_signal, use_state, RefHandle,
     };
+    pub use rinch_core::form::{use_form, validators, Form, FormFields};
+    pub use rinch_core::rich_text::{use_rich_text, RichText, RichTextCommand, RichTextState};
     pub use rinch_macros::rsx;
+    pub use crate::asset;
+    pub use crate::t;
+    #[cfg(feature = "async")]
+    pub use crate::tasks::{use_coroutine, use_stream, FromStream};
     // Window control functions
     pub use crate::windows::{
         close_current_window, minimize_current_window, toggle_maximize_current_window,
@@ -122,13 +162,22 @@ pub mod prelude {
 
 // Re-export core types at crate root
 pub use rinch_core::element::{
-    AppMenuProps, Children, Element, MenuItemProps, MenuProps, WindowProps,
+    AppMenuProps, Children, Element, MenuItemProps, MenuProps, MenuRole, RecentFilesMenuProps,
+    TextDirection, TrayProps, WindowColorScheme, WindowProps,
 };
 pub use rinch_core::{batch, derived, untracked, Effect, Memo, Scope, Signal};
 pub use rinch_macros::rsx;
-pub use shell::run;
+pub use shell::{on_exit, render_to_string, run, run_with_config, run_with_handle, RenderedWindow, RunConfig};
+pub use tasks::AppHandle;
+#[cfg(feature = "async")]
+pub use tasks::spawn;
+/// Alias of [`tasks`], so blocking work reads as `rinch::task::spawn_blocking`.
+pub use tasks as task;
+pub use app::App;
 #[cfg(feature = "hot-reload")]
-pub use shell::run_with_hot_reload;
+pub use shell::{run_with_hot_library, run_with_hot_reload};
 
+pub use blitz_dom;
+pub use winit;
 pub use rinch_core as core;
 pub use rinch_renderer as renderer;
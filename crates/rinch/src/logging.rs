@@ -0,0 +1,173 @@
+//! Log filtering and output.
+//!
+//! `run` sets up a `tracing` subscriber that writes log records to the
+//! terminal, to a file if [`RunConfig::log_file`](crate::RunConfig::log_file)
+//! is set, and to the DevTools console. Which records are kept is decided by
+//! a filter of comma-separated directives: a level on its own sets the
+//! default, and `target=level` sets it for a module and its children.
+//!
+//! ```text
+//! warn,my_app=debug,rinch::shell=info
+//! ```
+//!
+//! The filter comes from the `RUST_LOG` environment variable if it's set,
+//! then from [`RunConfig::log_filter`](crate::RunConfig::log_filter), and is
+//! `info` otherwise. It can be changed while the app runs with
+//! [`set_filter`], or from the DevTools console.
+//!
+//! Apps that install their own subscriber before calling `run` keep it; the
+//! functions here then return [`LogFilterError::NotInstalled`].
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Registry};
+
+use crate::shell::ConsoleLayer;
+
+/// Environment variable overriding the configured filter.
+pub const FILTER_ENV: &str = "RUST_LOG";
+
+/// The filter used when none is configured.
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Changes the filter of the subscriber `run` installed, and remembers its
+/// directives.
+struct FilterHandle {
+    handle: reload::Handle<Targets, Registry>,
+    directives: Mutex<String>,
+}
+
+static FILTER: OnceLock<FilterHandle> = OnceLock::new();
+
+/// Why the log filter couldn't be changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogFilterError {
+    /// The directives couldn't be parsed.
+    Invalid(String),
+    /// rinch didn't install the subscriber, because the app installed its own.
+    NotInstalled,
+}
+
+impl fmt::Display for LogFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFilterError::Invalid(msg) => write!(f, "invalid log filter: {}", msg),
+            LogFilterError::NotInstalled => write!(f, "the app installed its own tracing subscriber"),
+        }
+    }
+}
+
+impl std::error::Error for LogFilterError {}
+
+/// Result type for log filter operations.
+pub type LogFilterResult<T> = Result<T, LogFilterError>;
+
+/// Replace the log filter, for example `"warn,my_app=trace"`.
+///
+/// Records already filtered out aren't brought back.
+pub fn set_filter(directives: &str) -> LogFilterResult<()> {
+    let filter = FILTER.get().ok_or(LogFilterError::NotInstalled)?;
+    let targets = parse(directives)?;
+    filter
+        .handle
+        .reload(targets)
+        .map_err(|_| LogFilterError::NotInstalled)?;
+    *filter.directives.lock().unwrap() = directives.trim().to_string();
+    tracing::info!("Log filter set to `{}`", directives.trim());
+    Ok(())
+}
+
+/// The directives of the current log filter.
+pub fn filter() -> LogFilterResult<String> {
+    let filter = FILTER.get().ok_or(LogFilterError::NotInstalled)?;
+    Ok(filter.directives.lock().unwrap().clone())
+}
+
+fn parse(directives: &str) -> LogFilterResult<Targets> {
+    directives
+        .trim()
+        .parse::<Targets>()
+        .map_err(|e| LogFilterError::Invalid(e.to_string()))
+}
+
+/// Whether the terminal log goes to stderr, because stdout carries IPC
+/// messages.
+static TERMINAL_ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Send the terminal log to stderr from now on (called when an IPC channel
+/// takes stdout).
+#[cfg(feature = "ipc")]
+pub(crate) fn move_terminal_to_stderr() {
+    TERMINAL_ON_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Where a terminal log record is written.
+fn terminal() -> Box<dyn std::io::Write> {
+    if TERMINAL_ON_STDERR.load(Ordering::Relaxed) {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    }
+}
+
+/// Install the subscriber, unless the app already installed one.
+pub(crate) fn init(configured: Option<&str>, file: Option<&Path>) {
+    // Problems are logged once the subscriber is installed
+    let mut warnings = Vec::new();
+    let env = std::env::var(FILTER_ENV).ok().filter(|value| !value.trim().is_empty());
+    let mut chosen = None;
+    for (source, directives) in [(FILTER_ENV, env.as_deref()), ("RunConfig::log_filter", configured)] {
+        let Some(directives) = directives else {
+            continue;
+        };
+        match parse(directives) {
+            Ok(targets) => {
+                chosen = Some((directives.trim().to_string(), targets));
+                break;
+            }
+            Err(e) => warnings.push(format!("Ignoring {} `{}`: {}", source, directives, e)),
+        }
+    }
+    let (directives, targets) =
+        chosen.unwrap_or_else(|| (DEFAULT_FILTER.to_string(), parse(DEFAULT_FILTER).expect("valid default filter")));
+
+    let file_layer = file.and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(file)),
+            ),
+            Err(e) => {
+                warnings.push(format!("Can't write logs to {}: {}", path.display(), e));
+                None
+            }
+        }
+    });
+
+    let (filter_layer, handle) = reload::Layer::new(targets);
+    let installed = tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer().with_writer(terminal))
+        .with(file_layer)
+        .with(ConsoleLayer)
+        .try_init()
+        .is_ok();
+    if installed {
+        let _ = FILTER.set(FilterHandle {
+            handle,
+            directives: Mutex::new(directives),
+        });
+        crate::shell::console::mark_installed();
+    }
+    for warning in warnings {
+        tracing::warn!("{}", warning);
+    }
+}
@@ -0,0 +1,457 @@
+//! Media queries from the app.
+//!
+//! [`use_media_query`] gives a signal holding whether a CSS media query
+//! matches the window, for components that switch layouts by the room the
+//! window gives them rather than only restyling:
+//!
+//! ```ignore
+//! use rinch::media::use_media_query;
+//! use rinch::prelude::*;
+//!
+//! fn app() -> Element {
+//!     let narrow = use_media_query("(max-width: 600px)");
+//!
+//!     let class = if narrow.get() { "stacked" } else { "split" };
+//!     rsx! {
+//!         Window { title: "Mail",
+//!             div { class: {class},
+//!                 FolderList {}
+//!                 MessageList {}
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Queries are matched against the most recently focused window, or the
+//! first window opened before any was focused, as its stylesheets' `@media`
+//! rules are. The window works the signals out when it renders, so they
+//! follow it being resized, zoomed, moved to a screen with another scale
+//! or switched between light and dark. They are only set when they change,
+//! and the app only re-renders then if the render read them.
+//!
+//! The features understood are `width`, `height` and `aspect-ratio` with
+//! their `min-` and `max-` forms or in ranges such as `(width >= 600px)` and
+//! `(400px <= width < 700px)`, `orientation` and `prefers-color-scheme`.
+//! Lengths are in `px`, `em` or `rem` (16px). Queries are joined with `,`,
+//! conditions with `and`, and a query may start with `not`, `only` and a
+//! media type. As in CSS, a query of the list that can't be read, or uses a
+//! feature that isn't understood, never matches, while the others still
+//! can.
+
+use std::cell::RefCell;
+
+use rinch_core::{affects_render, untracked, Signal};
+
+use crate::windows::WindowHandle;
+
+/// What media queries are matched against: a window's viewport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Media {
+    /// The width of the window's content, in CSS pixels.
+    pub(crate) width: f32,
+    /// The height of the window's content, in CSS pixels.
+    pub(crate) height: f32,
+    /// Whether the window uses the dark color scheme.
+    pub(crate) dark: bool,
+}
+
+/// A signal following a media query.
+struct Watch {
+    query: String,
+    parsed: Vec<Option<Query>>,
+    signal: Signal<bool>,
+}
+
+thread_local! {
+    /// Signals following media queries.
+    static WATCHES: RefCell<Vec<Watch>> = const { RefCell::new(Vec::new()) };
+    /// The media of every window that rendered, in the order they opened.
+    static WINDOWS: RefCell<Vec<(WindowHandle, Media)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Get a signal holding whether the CSS media `query` matches the window,
+/// such as `"(max-width: 600px)"` or `"(orientation: portrait) and
+/// (prefers-color-scheme: dark)"`.
+///
+/// The signal is `false` until a window has rendered. Queries of a
+/// comma-separated list that can't be read never match. Every call with the
+/// same query returns the same signal.
+pub fn use_media_query(query: &str) -> Signal<bool> {
+    WATCHES.with(|watches| {
+        let mut watches = watches.borrow_mut();
+        if let Some(watch) = watches.iter().find(|w| w.query == query) {
+            return watch.signal.clone();
+        }
+        let parsed = parse_list(query);
+        if parsed.iter().any(Option::is_none) {
+            tracing::warn!("Media query {:?} can't be read, at least in part, which never matches", query);
+        }
+        let matches = current_media().is_some_and(|media| matches_list(&parsed, &media));
+        let signal = Signal::new(matches);
+        watches.push(Watch {
+            query: query.to_string(),
+            parsed,
+            signal: signal.clone(),
+        });
+        signal
+    })
+}
+
+/// Whether the app watches any media query.
+pub(crate) fn is_watched() -> bool {
+    WATCHES.with(|watches| !watches.borrow().is_empty())
+}
+
+/// Report the media of `window` (called by the window after it renders).
+pub(crate) fn update(window: WindowHandle, media: Media) {
+    let changed = WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        match windows.iter_mut().find(|(handle, _)| *handle == window) {
+            Some((_, current)) if *current == media => false,
+            Some((_, current)) => {
+                *current = media;
+                true
+            }
+            None => {
+                windows.push((window, media));
+                true
+            }
+        }
+    });
+    if changed {
+        refresh();
+    }
+}
+
+/// Forget the media of a closed window (called by the runtime).
+pub(crate) fn forget(window: WindowHandle) {
+    WINDOWS.with(|windows| windows.borrow_mut().retain(|(handle, _)| *handle != window));
+    refresh();
+}
+
+/// Match the queries again, against the window now followed (called when
+/// a window reports its media or another window is focused).
+pub(crate) fn refresh() {
+    let Some(media) = current_media() else {
+        return;
+    };
+    let changed: Vec<_> = WATCHES.with(|watches| {
+        watches
+            .borrow()
+            .iter()
+            .filter_map(|w| {
+                let matches = matches_list(&w.parsed, &media);
+                (untracked(|| w.signal.get()) != matches).then(|| (w.signal.clone(), matches))
+            })
+            .collect()
+    });
+    if changed.is_empty() {
+        return;
+    }
+    let ((), render) = affects_render(|| {
+        for (signal, matches) in changed {
+            signal.set(matches);
+        }
+    });
+    if render {
+        crate::shell::runtime::request_render();
+    }
+}
+
+/// The media of the most recently focused window, or of the first window
+/// if none that rendered was focused.
+fn current_media() -> Option<Media> {
+    let focused = crate::windows::focused_window();
+    WINDOWS.with(|windows| {
+        let windows = windows.borrow();
+        windows
+            .iter()
+            .find(|(handle, _)| Some(*handle) == focused)
+            .or_else(|| windows.first())
+            .map(|(_, media)| *media)
+    })
+}
+
+/// One query of a list: `[not|only] [type] [and (feature)]...`.
+#[derive(Debug)]
+struct Query {
+    negated: bool,
+    /// Whether the media type is one a window is (`all` or `screen`).
+    screen: bool,
+    features: Vec<Feature>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compare {
+    Less,
+    AtMost,
+    Equal,
+    AtLeast,
+    Greater,
+}
+
+impl Compare {
+    fn holds(self, value: f32, against: f32) -> bool {
+        match self {
+            Compare::Less => value < against,
+            Compare::AtMost => value <= against,
+            Compare::Equal => value == against,
+            Compare::AtLeast => value >= against,
+            Compare::Greater => value > against,
+        }
+    }
+
+    /// Whether the comparison is `<` or `<=` (`Some(true)`), or `>` or `>=`.
+    fn ascending(self) -> Option<bool> {
+        match self {
+            Compare::Less | Compare::AtMost => Some(true),
+            Compare::Equal => None,
+            Compare::AtLeast | Compare::Greater => Some(false),
+        }
+    }
+
+    /// The comparison with its sides swapped, for `600px <= width`.
+    fn flipped(self) -> Self {
+        match self {
+            Compare::Less => Compare::Greater,
+            Compare::AtMost => Compare::AtLeast,
+            Compare::Equal => Compare::Equal,
+            Compare::AtLeast => Compare::AtMost,
+            Compare::Greater => Compare::Less,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Feature {
+    Width(Compare, f32),
+    Height(Compare, f32),
+    AspectRatio(Compare, f32),
+    Portrait(bool),
+    Dark(bool),
+}
+
+impl Feature {
+    fn matches(&self, media: &Media) -> bool {
+        match *self {
+            Feature::Width(compare, px) => compare.holds(media.width, px),
+            Feature::Height(compare, px) => compare.holds(media.height, px),
+            Feature::AspectRatio(compare, ratio) => {
+                media.height > 0.0 && compare.holds(media.width / media.height, ratio)
+            }
+            Feature::Portrait(portrait) => (media.height >= media.width) == portrait,
+            Feature::Dark(dark) => media.dark == dark,
+        }
+    }
+}
+
+impl Query {
+    fn matches(&self, media: &Media) -> bool {
+        let matches = self.screen && self.features.iter().all(|feature| feature.matches(media));
+        matches != self.negated
+    }
+}
+
+/// Whether any query of a list matches; those that couldn't be read don't.
+fn matches_list(queries: &[Option<Query>], media: &Media) -> bool {
+    queries.iter().flatten().any(|query| query.matches(media))
+}
+
+/// Parse a comma-separated list of queries, with `None` for those that
+/// can't be read or use a feature that isn't understood.
+fn parse_list(list: &str) -> Vec<Option<Query>> {
+    list.split(',').map(parse_query).collect()
+}
+
+fn parse_query(query: &str) -> Option<Query> {
+    let lower = query.trim().to_ascii_lowercase();
+    let mut rest = lower.as_str();
+    let mut negated = false;
+    let mut screen = true;
+    let mut needs_and = false;
+
+    if let Some(after) = keyword(rest, "not") {
+        negated = true;
+        rest = after;
+    } else if let Some(after) = keyword(rest, "only") {
+        rest = after;
+    }
+    if !rest.starts_with('(') {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        screen = match &rest[..end] {
+            "all" | "screen" => true,
+            "print" | "speech" => false,
+            _ => return None,
+        };
+        rest = rest[end..].trim_start();
+        needs_and = true;
+    }
+
+    let mut features = Vec::new();
+    while !rest.is_empty() {
+        if needs_and {
+            rest = keyword(rest, "and")?;
+        }
+        let inner = rest.strip_prefix('(')?;
+        let close = inner.find(')')?;
+        features.extend(parse_feature(inner[..close].trim())?);
+        rest = inner[close + 1..].trim_start();
+        needs_and = true;
+    }
+    Some(Query { negated, screen, features })
+}
+
+/// The rest of `text` after the word `word` and the space following it.
+fn keyword<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let after = text.strip_prefix(word)?;
+    after
+        .starts_with(|c: char| c.is_whitespace() || c == '(')
+        .then(|| after.trim_start())
+}
+
+/// The features of a condition; a range between two values, such as
+/// `400px <= width < 700px`, is two.
+fn parse_feature(feature: &str) -> Option<Vec<Feature>> {
+    if let Some((name, value)) = feature.split_once(':') {
+        let (name, value) = (name.trim(), value.trim());
+        let (compare, name) = match (name.strip_prefix("min-"), name.strip_prefix("max-")) {
+            (Some(name), _) => (Compare::AtLeast, name),
+            (_, Some(name)) => (Compare::AtMost, name),
+            _ => (Compare::Equal, name),
+        };
+        let feature = match (name, value) {
+            ("orientation", "portrait") | ("orientation", "landscape") if matches!(compare, Compare::Equal) => {
+                Feature::Portrait(value == "portrait")
+            }
+            ("prefers-color-scheme", "dark") | ("prefers-color-scheme", "light")
+                if matches!(compare, Compare::Equal) =>
+            {
+                Feature::Dark(value == "dark")
+            }
+            _ => range_feature(name, compare, value)?,
+        };
+        return Some(vec![feature]);
+    }
+
+    // A range: `width >= 600px`, `600px <= width` or `400px <= width < 700px`
+    let (at, compare, len) = comparison(feature)?;
+    let (left, rest) = (feature[..at].trim(), &feature[at + len..]);
+    if let Some((second, second_compare, second_len)) = comparison(rest) {
+        // Both sides have to point the same way, as in `400px < width < 700px`
+        if compare.ascending()? != second_compare.ascending()? {
+            return None;
+        }
+        let (name, right) = (rest[..second].trim(), rest[second + second_len..].trim());
+        return Some(vec![
+            range_feature(name, compare.flipped(), left)?,
+            range_feature(name, second_compare, right)?,
+        ]);
+    }
+    let right = rest.trim();
+    let feature = range_feature(left, compare, right).or_else(|| range_feature(right, compare.flipped(), left))?;
+    Some(vec![feature])
+}
+
+/// The first comparison in `text`: where it is, what it is, and its length.
+fn comparison(text: &str) -> Option<(usize, Compare, usize)> {
+    let at = text.find(['<', '>', '='])?;
+    let or_equal = text[at + 1..].starts_with('=');
+    let compare = match (&text[at..at + 1], or_equal) {
+        ("<", true) => Compare::AtMost,
+        ("<", false) => Compare::Less,
+        (">", true) => Compare::AtLeast,
+        (">", false) => Compare::Greater,
+        _ => return Some((at, Compare::Equal, 1)),
+    };
+    Some((at, compare, if or_equal { 2 } else { 1 }))
+}
+
+/// A feature compared against a length or ratio, or `None` if it isn't
+/// one.
+fn range_feature(name: &str, compare: Compare, value: &str) -> Option<Feature> {
+    match name {
+        "width" => Some(Feature::Width(compare, length(value)?)),
+        "height" => Some(Feature::Height(compare, length(value)?)),
+        "aspect-ratio" => Some(Feature::AspectRatio(compare, ratio(value)?)),
+        _ => None,
+    }
+}
+
+/// A length in CSS pixels.
+fn length(value: &str) -> Option<f32> {
+    let (number, scale) = if let Some(number) = value.strip_suffix("rem") {
+        (number, 16.0)
+    } else if let Some(number) = value.strip_suffix("em") {
+        (number, 16.0)
+    } else if let Some(number) = value.strip_suffix("px") {
+        (number, 1.0)
+    } else if value == "0" {
+        (value, 1.0)
+    } else {
+        return None;
+    };
+    Some(number.trim().parse::<f32>().ok()? * scale)
+}
+
+/// A ratio such as `16/9`, or a single number.
+fn ratio(value: &str) -> Option<f32> {
+    match value.split_once('/') {
+        Some((width, height)) => {
+            let (width, height): (f32, f32) = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+            (height > 0.0).then(|| width / height)
+        }
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDE: Media = Media { width: 800.0, height: 600.0, dark: false };
+    const NARROW: Media = Media { width: 500.0, height: 700.0, dark: true };
+
+    fn matches(query: &str, media: &Media) -> bool {
+        matches_list(&parse_list(query), media)
+    }
+
+    #[test]
+    fn features_compare_with_the_window() {
+        assert!(matches("(max-width: 600px)", &NARROW));
+        assert!(!matches("(max-width: 600px)", &WIDE));
+        assert!(matches("(min-width: 50em)", &WIDE));
+        assert!(matches("(orientation: portrait) and (prefers-color-scheme: dark)", &NARROW));
+        assert!(matches("(min-aspect-ratio: 1/1)", &WIDE));
+        assert!(matches("not print", &WIDE));
+        assert!(!matches("only print and (min-width: 0)", &WIDE));
+        assert!(matches("screen and (width > 600px)", &WIDE));
+        assert!(matches("(600px >= width)", &NARROW));
+    }
+
+    #[test]
+    fn ranges_can_have_two_sides() {
+        assert!(matches("(400px <= width <= 700px)", &NARROW));
+        assert!(!matches("(400px <= width <= 700px)", &WIDE));
+        assert!(matches("(900px > width > 700px)", &WIDE));
+        assert!(matches("(0 < height < 650px)", &WIDE));
+        assert!(parse_query("(400px < width > 700px)").is_none());
+        assert!(parse_query("(400px = width < 700px)").is_none());
+    }
+
+    #[test]
+    fn unreadable_queries_of_a_list_never_match() {
+        assert!(parse_query("(max-width: 600pt)").is_none());
+        assert!(parse_query("(hover: hover)").is_none());
+        assert!(parse_query("tv").is_none());
+        assert!(parse_query("(width >= 600px) or (height > 0)").is_none());
+
+        let list = parse_list("(hover: hover), (max-width: 600px)");
+        assert!(list[0].is_none());
+        assert!(matches_list(&list, &NARROW));
+        assert!(!matches_list(&list, &WIDE));
+        assert!(!matches("(hover: hover)", &NARROW));
+        // A negated query that can't be read doesn't match either
+        assert!(!matches("not (hover: hover)", &NARROW));
+    }
+}
@@ -0,0 +1,211 @@
+//! Building menus from code instead of `rsx!`.
+//!
+//! The builders make the same elements as `AppMenu`, `Menu`, `MenuItem`,
+//! `MenuSeparator` and `RecentFilesMenu`, so menus made from data (items
+//! added by plugins, a toolbar the user configured) behave like declared
+//! ones, shortcuts and item handles included.
+//!
+//! ```ignore
+//! use rinch::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+//! use rinch::prelude::*;
+//!
+//! fn app() -> Element {
+//!     let mut tools = SubmenuBuilder::new("Tools");
+//!     for plugin in plugins() {
+//!         tools = tools.item(MenuItemBuilder::new(plugin.name()).onclick(move || plugin.run()));
+//!     }
+//!
+//!     let menu = MenuBuilder::new()
+//!         .menu(SubmenuBuilder::new("File").item(MenuItemBuilder::new("Quit").shortcut("Cmd+Q")))
+//!         .menu(tools);
+//!
+//!     Element::Fragment(vec![menu.build(), rsx! { Window { title: "Editor" } }])
+//! }
+//! ```
+
+use rinch_core::element::{
+    AppMenuProps, Children, Element, MenuActivation, MenuItemCallback, MenuItemProps, MenuProps,
+    MenuRole, RecentFilesMenuProps,
+};
+
+/// Builds an application menu, like `AppMenu` in `rsx!`.
+pub struct MenuBuilder {
+    props: AppMenuProps,
+    menus: Children,
+}
+
+impl MenuBuilder {
+    /// Create an empty native menu.
+    pub fn new() -> Self {
+        Self {
+            props: AppMenuProps::default(),
+            menus: Vec::new(),
+        }
+    }
+
+    /// Set whether the menu is a native OS menu rather than HTML.
+    pub fn native(mut self, native: bool) -> Self {
+        self.props.native = native;
+        self
+    }
+
+    /// Add a menu to the menu bar.
+    pub fn menu(mut self, menu: SubmenuBuilder) -> Self {
+        self.menus.push(menu.build());
+        self
+    }
+
+    /// Add several menus to the menu bar.
+    pub fn menus(mut self, menus: impl IntoIterator<Item = SubmenuBuilder>) -> Self {
+        self.menus.extend(menus.into_iter().map(SubmenuBuilder::build));
+        self
+    }
+
+    /// Make the `AppMenu` element.
+    pub fn build(self) -> Element {
+        Element::AppMenu(self.props, self.menus)
+    }
+}
+
+impl Default for MenuBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<MenuBuilder> for Element {
+    fn from(builder: MenuBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds a menu of items, like `Menu` in `rsx!`: a menu in the menu bar,
+/// or a submenu when added to another menu.
+pub struct SubmenuBuilder {
+    props: MenuProps,
+    items: Children,
+}
+
+impl SubmenuBuilder {
+    /// Create an empty menu with a label.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            props: MenuProps { label: label.into() },
+            items: Vec::new(),
+        }
+    }
+
+    /// Add an item.
+    pub fn item(mut self, item: MenuItemBuilder) -> Self {
+        self.items.push(item.build());
+        self
+    }
+
+    /// Add several items.
+    pub fn items(mut self, items: impl IntoIterator<Item = MenuItemBuilder>) -> Self {
+        self.items.extend(items.into_iter().map(MenuItemBuilder::build));
+        self
+    }
+
+    /// Add a separator line.
+    pub fn separator(mut self) -> Self {
+        self.items.push(Element::MenuSeparator);
+        self
+    }
+
+    /// Add a submenu.
+    pub fn submenu(mut self, submenu: SubmenuBuilder) -> Self {
+        self.items.push(submenu.build());
+        self
+    }
+
+    /// Add a submenu listing recently opened files, like `RecentFilesMenu`
+    /// in `rsx!`.
+    pub fn recent_files(mut self, props: RecentFilesMenuProps) -> Self {
+        self.items.push(Element::RecentFilesMenu(props));
+        self
+    }
+
+    /// Make the `Menu` element.
+    pub fn build(self) -> Element {
+        Element::Menu(self.props, self.items)
+    }
+}
+
+impl From<SubmenuBuilder> for Element {
+    fn from(builder: SubmenuBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds a menu item, like `MenuItem` in `rsx!`.
+pub struct MenuItemBuilder {
+    props: MenuItemProps,
+}
+
+impl MenuItemBuilder {
+    /// Create an enabled item with a label.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            props: MenuItemProps {
+                label: label.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Set the identifier for looking the item up with
+    /// [`MenuManager::item`](super::MenuManager::item).
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.props.id = Some(id.into());
+        self
+    }
+
+    /// Set the keyboard shortcut, such as `"Cmd+S"`.
+    pub fn shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.props.shortcut = Some(shortcut.into());
+        self
+    }
+
+    /// Set whether the item can be clicked.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.props.enabled = enabled;
+        self
+    }
+
+    /// Make the item checkable, with its checkmark set or not.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.props.checked = Some(checked);
+        self
+    }
+
+    /// Call `f` when the item is clicked or its shortcut pressed.
+    pub fn onclick(mut self, f: impl Fn() + 'static) -> Self {
+        self.props.onclick = Some(MenuItemCallback::new(f));
+        self
+    }
+
+    /// Call `f` with the activation details when the item is clicked or its
+    /// shortcut pressed.
+    pub fn on_activate(mut self, f: impl Fn(&MenuActivation) + 'static) -> Self {
+        self.props.onclick = Some(MenuItemCallback::with_activation(f));
+        self
+    }
+
+    /// Make the item perform an editing command on the focused text input.
+    pub fn role(mut self, role: MenuRole) -> Self {
+        self.props.role = Some(role);
+        self
+    }
+
+    /// Make the `MenuItem` element.
+    pub fn build(self) -> Element {
+        Element::MenuItem(self.props)
+    }
+}
+
+impl From<MenuItemBuilder> for Element {
+    fn from(builder: MenuItemBuilder) -> Self {
+        builder.build()
+    }
+}
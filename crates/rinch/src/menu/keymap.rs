@@ -0,0 +1,487 @@
+//! User-configurable keyboard shortcuts for menu items.
+//!
+//! A [`Keymap`] maps menu item `id`s to shortcut strings, overriding the
+//! `shortcut` prop declared in `rsx!`. Keymaps can be loaded from a simple
+//! settings file with one `id = Shortcut` binding per line.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::menu::keymap::{self, Keymap};
+//!
+//! // keymap.txt:
+//! //   # Remap save, unbind quit
+//! //   save = Ctrl+Alt+S
+//! //   quit = none
+//! let keymap = Keymap::load("keymap.txt").unwrap_or_default();
+//! keymap::set_keymap(keymap);
+//! ```
+//!
+//! Components can also bind shortcuts that have no menu item with
+//! [`use_shortcut`]. The keymap remaps those by their `id` in the same way.
+
+use super::{parse_shortcut_for_matching, KeyPress, ParsedShortcut};
+use crate::shell::RinchEvent;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Keymap error type.
+#[derive(Debug)]
+pub enum KeymapError {
+    /// Failed to read or write the keymap file.
+    Io(std::io::Error),
+    /// A line in the keymap could not be parsed.
+    Parse {
+        /// 1-based line number.
+        line: usize,
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+impl std::fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeymapError::Io(err) => write!(f, "keymap I/O error: {}", err),
+            KeymapError::Parse { line, message } => {
+                write!(f, "keymap parse error on line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+impl From<std::io::Error> for KeymapError {
+    fn from(err: std::io::Error) -> Self {
+        KeymapError::Io(err)
+    }
+}
+
+/// Result type for keymap operations.
+pub type KeymapResult<T> = Result<T, KeymapError>;
+
+/// A set of shortcut overrides keyed by menu item `id`.
+///
+/// A binding of `None` removes the item's shortcut entirely.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<String, Option<String>>,
+}
+
+impl Keymap {
+    /// Create an empty keymap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a shortcut to the menu item with the given `id`.
+    pub fn bind(mut self, item_id: impl Into<String>, shortcut: impl Into<String>) -> Self {
+        self.set(item_id, Some(shortcut.into()));
+        self
+    }
+
+    /// Remove the shortcut from the menu item with the given `id`.
+    pub fn unbind(mut self, item_id: impl Into<String>) -> Self {
+        self.set(item_id, None);
+        self
+    }
+
+    /// Set or clear the shortcut override for a menu item.
+    pub fn set(&mut self, item_id: impl Into<String>, shortcut: Option<String>) {
+        self.bindings.insert(item_id.into(), shortcut);
+    }
+
+    /// Remove the override for a menu item, restoring its declared shortcut.
+    pub fn reset(&mut self, item_id: &str) {
+        self.bindings.remove(item_id);
+    }
+
+    /// Look up the override for a menu item.
+    ///
+    /// Returns `None` if the keymap doesn't mention the item, and
+    /// `Some(None)` if the item is explicitly unbound.
+    pub fn get(&self, item_id: &str) -> Option<Option<&str>> {
+        self.bindings.get(item_id).map(|s| s.as_deref())
+    }
+
+    /// Whether the keymap has no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Parse a keymap from text.
+    ///
+    /// Each non-empty line has the form `id = Shortcut`. Lines starting with
+    /// `#` are comments. A shortcut of `none` unbinds the item.
+    pub fn parse(text: &str) -> KeymapResult<Self> {
+        let mut keymap = Self::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((item_id, shortcut)) = line.split_once('=') else {
+                return Err(KeymapError::Parse {
+                    line: idx + 1,
+                    message: format!("expected `id = Shortcut`, found `{}`", line),
+                });
+            };
+
+            let item_id = item_id.trim();
+            let shortcut = shortcut.trim();
+            if item_id.is_empty() {
+                return Err(KeymapError::Parse {
+                    line: idx + 1,
+                    message: "missing menu item id".into(),
+                });
+            }
+
+            if shortcut.eq_ignore_ascii_case("none") || shortcut.is_empty() {
+                keymap.set(item_id, None);
+            } else if parse_shortcut_for_matching(shortcut).is_some() {
+                keymap.set(item_id, Some(shortcut.to_string()));
+            } else {
+                return Err(KeymapError::Parse {
+                    line: idx + 1,
+                    message: format!("unrecognized shortcut `{}`", shortcut),
+                });
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Load a keymap from a settings file.
+    pub fn load(path: impl AsRef<Path>) -> KeymapResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Save the keymap to a settings file in the format read by [`Keymap::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> KeymapResult<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Keymap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ids: Vec<&String> = self.bindings.keys().collect();
+        ids.sort();
+        for id in ids {
+            match &self.bindings[id] {
+                Some(shortcut) => writeln!(f, "{} = {}", id, shortcut)?,
+                None => writeln!(f, "{} = none", id)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A shortcut bound by [`use_shortcut`].
+struct HookShortcut {
+    id: String,
+    declared: String,
+    callback: Rc<dyn Fn()>,
+}
+
+/// The shortcuts bound by [`use_shortcut`] during a render, taken by
+/// [`take_hook_shortcuts`].
+pub(crate) struct SavedHookShortcuts(Vec<HookShortcut>);
+
+thread_local! {
+    static KEYMAP: RefCell<Keymap> = RefCell::new(Keymap::new());
+    /// Shortcuts bound by the last render, in render order.
+    static HOOK_SHORTCUTS: RefCell<Vec<HookShortcut>> = const { RefCell::new(Vec::new()) };
+    /// The `id = shortcut` pairs last checked for conflicts, so the same
+    /// conflicts aren't reported on every render.
+    static CHECKED: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Install a keymap, updating the shortcuts of existing menu items.
+pub fn set_keymap(keymap: Keymap) {
+    KEYMAP.with(|k| *k.borrow_mut() = keymap);
+    crate::windows::send_event(RinchEvent::KeymapChanged);
+}
+
+/// Get a copy of the installed keymap.
+pub fn keymap() -> Keymap {
+    KEYMAP.with(|k| k.borrow().clone())
+}
+
+/// Resolve the effective shortcut for a menu item.
+pub(super) fn resolve_shortcut(item_id: Option<&str>, declared: Option<&str>) -> Option<String> {
+    if let Some(item_id) = item_id
+        && let Some(shortcut) = KEYMAP.with(|k| k.borrow().get(item_id).map(|s| s.map(String::from)))
+    {
+        return shortcut;
+    }
+    declared.map(String::from)
+}
+
+/// Bind a keyboard shortcut to `callback` for as long as the calling
+/// component renders.
+///
+/// The `id` names the binding in a [`Keymap`], which can remap or unbind it
+/// like a menu item's shortcut. The shortcut uses the syntax of the menu
+/// `shortcut` prop. Menu items win over shortcuts bound here, and of two
+/// bindings with the same shortcut the first one rendered wins; both cases
+/// are logged as conflicts.
+///
+/// ```ignore
+/// use rinch::menu::keymap::use_shortcut;
+/// use rinch::prelude::*;
+///
+/// fn palette() -> Element {
+///     let open = use_signal(|| false);
+///     let toggle = open.clone();
+///     use_shortcut("command-palette", "Cmd+Shift+P", move || toggle.update(|o| *o = !*o));
+///
+///     rsx! {
+///         if open.get() {
+///             div { class: "palette" }
+///         }
+///     }
+/// }
+/// ```
+pub fn use_shortcut(id: &str, shortcut: &str, callback: impl Fn() + 'static) {
+    HOOK_SHORTCUTS.with(|shortcuts| {
+        let mut shortcuts = shortcuts.borrow_mut();
+        if shortcuts.iter().any(|s| s.id == id) {
+            tracing::warn!("use_shortcut: '{}' is bound more than once; keeping the first", id);
+            return;
+        }
+        shortcuts.push(HookShortcut {
+            id: id.to_string(),
+            declared: shortcut.to_string(),
+            callback: Rc::new(callback),
+        });
+    });
+}
+
+/// Take the shortcuts bound by the last render, leaving none bound (called
+/// by the runtime before a render binds them again).
+///
+/// Put them back with [`restore_hook_shortcuts`] if the render fails.
+pub(crate) fn take_hook_shortcuts() -> SavedHookShortcuts {
+    SavedHookShortcuts(HOOK_SHORTCUTS.with(|shortcuts| shortcuts.take()))
+}
+
+/// Put back the shortcuts taken by [`take_hook_shortcuts`].
+pub(crate) fn restore_hook_shortcuts(saved: SavedHookShortcuts) {
+    let replaced = HOOK_SHORTCUTS.with(|shortcuts| shortcuts.replace(saved.0));
+    // Dropped outside the borrow, in case a callback's captures bind others
+    drop(replaced);
+}
+
+/// The effective shortcuts bound by [`use_shortcut`], with their ids.
+fn hook_shortcuts() -> Vec<(String, String, Option<ParsedShortcut>)> {
+    HOOK_SHORTCUTS.with(|shortcuts| {
+        shortcuts
+            .borrow()
+            .iter()
+            .filter_map(|s| {
+                let shortcut = resolve_shortcut(Some(&s.id), Some(&s.declared))?;
+                let parsed = parse_shortcut_for_matching(&shortcut);
+                Some((s.id.clone(), shortcut, parsed))
+            })
+            .collect()
+    })
+}
+
+/// Find the callback of the [`use_shortcut`] binding a key press triggers.
+pub(crate) fn match_hook_shortcut(press: &KeyPress) -> Option<Rc<dyn Fn()>> {
+    HOOK_SHORTCUTS.with(|shortcuts| {
+        shortcuts.borrow().iter().find_map(|s| {
+            let shortcut = resolve_shortcut(Some(&s.id), Some(&s.declared))?;
+            parse_shortcut_for_matching(&shortcut)
+                .filter(|parsed| parsed.matches(press))
+                .map(|_| s.callback.clone())
+        })
+    })
+}
+
+/// Find the callback of the [`use_shortcut`] binding with the given shortcut.
+pub(crate) fn hook_shortcut_callback(shortcut: &ParsedShortcut) -> Option<Rc<dyn Fn()>> {
+    HOOK_SHORTCUTS.with(|shortcuts| {
+        shortcuts.borrow().iter().find_map(|s| {
+            let resolved = resolve_shortcut(Some(&s.id), Some(&s.declared))?;
+            (parse_shortcut_for_matching(&resolved).as_ref() == Some(shortcut)).then(|| s.callback.clone())
+        })
+    })
+}
+
+/// Log the [`use_shortcut`] bindings that are unrecognized, or whose
+/// shortcut is taken by a menu item or an earlier binding.
+///
+/// `menu_item` gives the label of the menu item that has a shortcut.
+/// Nothing is logged while the bindings are the same as on the last check.
+pub(crate) fn check_hook_shortcuts(menu_item: impl Fn(&ParsedShortcut) -> Option<String>) {
+    let bindings = hook_shortcuts();
+    let checked: Vec<(String, String)> = bindings.iter().map(|(id, s, _)| (id.clone(), s.clone())).collect();
+    if CHECKED.with(|last| *last.borrow() == checked) {
+        return;
+    }
+
+    for (idx, (id, shortcut, parsed)) in bindings.iter().enumerate() {
+        let Some(parsed) = parsed else {
+            tracing::warn!("Unrecognized shortcut '{}' for use_shortcut '{}'", shortcut, id);
+            continue;
+        };
+        if let Some(label) = menu_item(parsed) {
+            tracing::warn!(
+                "Shortcut conflict: '{}' is assigned to both menu item '{}' and use_shortcut '{}'; keeping '{}'",
+                shortcut,
+                label,
+                id,
+                label
+            );
+        } else if let Some((other, _, _)) = bindings[..idx].iter().find(|(_, _, p)| p.as_ref() == Some(parsed)) {
+            tracing::warn!(
+                "Shortcut conflict: '{}' is assigned to both use_shortcut '{}' and '{}'; keeping '{}'",
+                shortcut,
+                other,
+                id,
+                other
+            );
+        }
+    }
+
+    CHECKED.with(|last| *last.borrow_mut() = checked);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::event::Modifiers;
+    use std::cell::Cell;
+    use winit::keyboard::KeyCode;
+
+    fn ctrl_press(key: KeyCode, ch: &str) -> KeyPress {
+        KeyPress {
+            modifiers: Modifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+            key,
+            named_key: None,
+            text: Some(ch.to_string()),
+            unmodified_text: Some(ch.to_string()),
+        }
+    }
+
+    #[test]
+    fn parse_bindings() {
+        let keymap = Keymap::parse(
+            "# Remap save\n\
+             save = Ctrl+Alt+S\n\
+             \n\
+             quit = none\n\
+             close =\n\
+             \x20 find   =   Cmd+F  \n",
+        )
+        .unwrap();
+
+        assert_eq!(keymap.get("save"), Some(Some("Ctrl+Alt+S")));
+        assert_eq!(keymap.get("quit"), Some(None));
+        assert_eq!(keymap.get("close"), Some(None));
+        assert_eq!(keymap.get("find"), Some(Some("Cmd+F")));
+        assert_eq!(keymap.get("open"), None);
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert!(Keymap::parse("").unwrap().is_empty());
+        assert!(Keymap::parse("# just a comment\n\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_later_binding_wins() {
+        let keymap = Keymap::parse("save = Ctrl+S\nsave = Ctrl+Alt+S").unwrap();
+        assert_eq!(keymap.get("save"), Some(Some("Ctrl+Alt+S")));
+    }
+
+    #[test]
+    fn parse_missing_equals() {
+        let err = Keymap::parse("save = Ctrl+S\nquit Ctrl+Q").unwrap_err();
+        match err {
+            KeymapError::Parse { line, message } => {
+                assert_eq!(line, 2);
+                assert!(message.contains("quit Ctrl+Q"), "{}", message);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_missing_id() {
+        let err = Keymap::parse("\n = Ctrl+S").unwrap_err();
+        assert!(matches!(err, KeymapError::Parse { line: 2, .. }), "{:?}", err);
+        assert_eq!(err.to_string(), "keymap parse error on line 2: missing menu item id");
+    }
+
+    #[test]
+    fn parse_unrecognized_shortcut() {
+        let err = Keymap::parse("save = Ctrl+NotAKey").unwrap_err();
+        match err {
+            KeymapError::Parse { line, message } => {
+                assert_eq!(line, 1);
+                assert_eq!(message, "unrecognized shortcut `Ctrl+NotAKey`");
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let keymap = Keymap::new().bind("save", "Ctrl+Alt+S").unbind("quit").bind("find", "Cmd+F");
+        let text = keymap.to_string();
+        assert_eq!(text, "find = Cmd+F\nquit = none\nsave = Ctrl+Alt+S\n");
+        assert_eq!(Keymap::parse(&text).unwrap(), keymap);
+    }
+
+    #[test]
+    fn hook_shortcut_fires_and_follows_keymap() {
+        let fired = Rc::new(Cell::new(0));
+        let counter = fired.clone();
+        use_shortcut("palette", "Ctrl+P", move || counter.set(counter.get() + 1));
+
+        if let Some(callback) = match_hook_shortcut(&ctrl_press(KeyCode::KeyP, "p")) {
+            callback();
+        }
+        assert_eq!(fired.get(), 1);
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::KeyK, "k")).is_none());
+
+        set_keymap(Keymap::new().bind("palette", "Ctrl+K"));
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::KeyP, "p")).is_none());
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::KeyK, "k")).is_some());
+
+        set_keymap(Keymap::new().unbind("palette"));
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::KeyK, "k")).is_none());
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::KeyP, "p")).is_none());
+        set_keymap(Keymap::new());
+    }
+
+    #[test]
+    fn hook_shortcuts_are_replaced_each_render() {
+        use_shortcut("first", "Ctrl+1", || {});
+        // A second binding with the same id is ignored
+        use_shortcut("first", "Ctrl+2", || {});
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::Digit2, "2")).is_none());
+
+        let saved = take_hook_shortcuts();
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::Digit1, "1")).is_none());
+
+        // A failed render puts the previous bindings back
+        use_shortcut("second", "Ctrl+2", || {});
+        restore_hook_shortcuts(saved);
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::Digit1, "1")).is_some());
+        assert!(match_hook_shortcut(&ctrl_press(KeyCode::Digit2, "2")).is_none());
+        drop(take_hook_shortcuts());
+    }
+}
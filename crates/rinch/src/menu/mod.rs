@@ -1,13 +1,24 @@
 //! Menu module - native menu support via muda.
 
+mod builder;
+pub mod keymap;
+
+pub use builder::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+
 use muda::{
-    accelerator::Accelerator, Menu, MenuEvent, MenuEventReceiver, MenuItem, PredefinedMenuItem,
-    Submenu,
+    accelerator::Accelerator, CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuEventReceiver,
+    MenuItem, PredefinedMenuItem, Submenu,
 };
-use rinch_core::element::{Element, MenuItemCallback, MenuItemProps};
+use rinch_core::element::{
+    Element, MenuActivation, MenuItemCallback, MenuItemProps, MenuRole, RecentFilesMenuProps,
+};
+use rinch_core::event::Modifiers;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
-use winit::keyboard::KeyCode;
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use winit::keyboard::{KeyCode, NamedKey};
 
 /// Manages native menus for the application.
 pub struct MenuManager {
@@ -19,24 +30,306 @@ pub struct MenuManager {
     callbacks: Vec<MenuCallback>,
     /// Keyboard shortcuts mapped to menu item IDs for manual matching.
     shortcuts: Vec<(ParsedShortcut, muda::MenuId)>,
+    /// Items that have (or may be given) a shortcut, in menu order.
+    bindings: Vec<ShortcutBinding>,
+    /// The recent files submenu, if the menu contains one.
+    recent_files: Option<RecentFilesMenu>,
+    /// Items performing editing commands, in menu order.
+    roles: Vec<RoleItem>,
 }
 
 /// A parsed keyboard shortcut for matching against keyboard events.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedShortcut {
     pub ctrl_or_cmd: bool,
     pub alt: bool,
     pub shift: bool,
+    pub key: ShortcutKey,
+}
+
+/// The non-modifier key of a shortcut.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutKey {
+    /// Matches a key by its physical position, regardless of layout.
+    Physical(KeyCode),
+    /// Matches a key by its meaning, like Enter on the main keyboard or the
+    /// numpad.
+    Named(NamedKey),
+    /// Matches a key by the (lowercase) character it produces in the current layout.
+    ///
+    /// `fallback` is the key's US layout position, used when the current
+    /// layout doesn't produce Latin characters (e.g. Cyrillic or Greek).
+    Character { ch: String, fallback: Option<KeyCode> },
+}
+
+/// A key press to match against menu shortcuts.
+#[derive(Debug, Clone)]
+pub struct KeyPress {
+    /// Modifier keys held during the press.
+    pub modifiers: Modifiers,
+    /// The physical key that was pressed.
     pub key: KeyCode,
+    /// The meaning of the key, if it doesn't produce a character.
+    pub named_key: Option<NamedKey>,
+    /// The character produced, with all modifiers except Ctrl applied.
+    pub text: Option<String>,
+    /// The character the key produces without any modifiers.
+    pub unmodified_text: Option<String>,
+}
+
+impl ParsedShortcut {
+    /// Check whether a key press triggers this shortcut.
+    pub fn matches(&self, press: &KeyPress) -> bool {
+        if self.ctrl_or_cmd != press.modifiers.ctrl_or_cmd() || self.alt != press.modifiers.alt {
+            return false;
+        }
+
+        let shift_matches = self.shift == press.modifiers.shift;
+
+        match &self.key {
+            ShortcutKey::Physical(code) => shift_matches && *code == press.key,
+            ShortcutKey::Named(named) => shift_matches && press.named_key == Some(*named),
+            ShortcutKey::Character { ch, fallback } => {
+                let unmodified = press.unmodified_text.as_ref().map(|t| t.to_lowercase());
+                if unmodified.as_deref() == Some(ch.as_str()) {
+                    return shift_matches;
+                }
+
+                // Characters like '?' that need Shift to type match the shifted text
+                if !self.shift
+                    && press.modifiers.shift
+                    && press.text.as_ref().map(|t| t.to_lowercase()).as_deref() == Some(ch.as_str())
+                {
+                    return true;
+                }
+
+                // Fall back to the physical key on layouts without Latin characters
+                let latin_layout = unmodified.as_ref().is_some_and(|t| t.is_ascii());
+                !latin_layout && shift_matches && *fallback == Some(press.key)
+            }
+        }
+    }
 }
 
 /// Stores menu item information and callback.
+#[derive(Clone)]
 pub struct MenuCallback {
     pub label: String,
+    /// The item's `id` prop, passed to the callback in [`MenuActivation`].
+    pub id: Option<String>,
     /// The callback to invoke when this menu item is activated.
     pub callback: Option<MenuItemCallback>,
 }
 
+/// A menu item whose accelerator is managed by the keymap.
+struct ShortcutBinding {
+    /// The item's `id` prop, used to look up keymap overrides.
+    item_id: Option<String>,
+    label: String,
+    /// The shortcut declared in the element tree.
+    declared: Option<String>,
+    handle: MenuItemHandle,
+}
+
+/// A menu item with a role, enabled while its command can be performed.
+struct RoleItem {
+    role: MenuRole,
+    /// The item's `enabled` prop.
+    declared_enabled: bool,
+    /// Whether the item is enabled now.
+    enabled: bool,
+    handle: MenuItemHandle,
+}
+
+thread_local! {
+    /// Handles to menu items declared with an `id`, for runtime updates.
+    static MENU_ITEMS: RefCell<HashMap<String, MenuItemHandle>> = RefCell::new(HashMap::new());
+}
+
+/// The native item backing a MenuItem element.
+#[derive(Clone)]
+enum NativeMenuItem {
+    Normal(MenuItem),
+    Check(CheckMenuItem),
+}
+
+/// A handle to a native menu item for updating it without rebuilding the menu.
+///
+/// Obtain one with [`MenuManager::item`] using the `id` given to the `MenuItem`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::menu::MenuManager;
+///
+/// // MenuItem { id: "save", label: "Save", shortcut: "Cmd+S" }
+/// if let Some(save) = MenuManager::item("save") {
+///     save.set_enabled(false);
+///     save.set_label("Saved");
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MenuItemHandle {
+    item: NativeMenuItem,
+}
+
+impl MenuItemHandle {
+    /// Enable or disable the item.
+    pub fn set_enabled(&self, enabled: bool) {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item.set_enabled(enabled),
+            NativeMenuItem::Check(item) => item.set_enabled(enabled),
+        }
+    }
+
+    /// Check whether the item is enabled.
+    pub fn is_enabled(&self) -> bool {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item.is_enabled(),
+            NativeMenuItem::Check(item) => item.is_enabled(),
+        }
+    }
+
+    /// Change the item's label.
+    pub fn set_label(&self, label: impl AsRef<str>) {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item.set_text(label),
+            NativeMenuItem::Check(item) => item.set_text(label),
+        }
+    }
+
+    /// Get the item's current label.
+    pub fn label(&self) -> String {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item.text(),
+            NativeMenuItem::Check(item) => item.text(),
+        }
+    }
+
+    /// Set the checkmark state.
+    ///
+    /// Only items declared with a `checked` prop can show a checkmark; for
+    /// other items this logs a warning and does nothing.
+    pub fn set_checked(&self, checked: bool) {
+        match &self.item {
+            NativeMenuItem::Check(item) => item.set_checked(checked),
+            NativeMenuItem::Normal(item) => {
+                tracing::warn!(
+                    "Menu item '{}' has no checked prop; set_checked ignored",
+                    item.text()
+                );
+            }
+        }
+    }
+
+    /// Get the checkmark state, or `None` if the item is not checkable.
+    pub fn is_checked(&self) -> Option<bool> {
+        match &self.item {
+            NativeMenuItem::Check(item) => Some(item.is_checked()),
+            NativeMenuItem::Normal(_) => None,
+        }
+    }
+
+    fn set_accelerator(&self, accelerator: Option<Accelerator>) {
+        let result = match &self.item {
+            NativeMenuItem::Normal(item) => item.set_accelerator(accelerator),
+            NativeMenuItem::Check(item) => item.set_accelerator(accelerator),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to set accelerator for '{}': {}", self.label(), e);
+        }
+    }
+
+    fn id(&self) -> &muda::MenuId {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item.id(),
+            NativeMenuItem::Check(item) => item.id(),
+        }
+    }
+
+    fn as_native(&self) -> &dyn IsMenuItem {
+        match &self.item {
+            NativeMenuItem::Normal(item) => item,
+            NativeMenuItem::Check(item) => item,
+        }
+    }
+}
+
+/// A recent files submenu whose entries are rebuilt when the list changes.
+struct RecentFilesMenu {
+    submenu: Submenu,
+    props: RecentFilesMenuProps,
+    /// Items currently in the submenu (file entries, separator, clear item).
+    items: Vec<Box<dyn IsMenuItem>>,
+    /// Map from file entry IDs to the path they open.
+    paths: HashMap<muda::MenuId, PathBuf>,
+    /// ID of the "Clear Menu" item.
+    clear_id: Option<muda::MenuId>,
+}
+
+impl RecentFilesMenu {
+    fn new(props: &RecentFilesMenuProps) -> Self {
+        let mut menu = Self {
+            submenu: Submenu::new(&props.label, true),
+            props: props.clone(),
+            items: Vec::new(),
+            paths: HashMap::new(),
+            clear_id: None,
+        };
+        menu.populate();
+        menu
+    }
+
+    /// Replace the submenu entries with the current recent files list.
+    fn populate(&mut self) {
+        for item in self.items.drain(..) {
+            let _ = self.submenu.remove(item.as_ref());
+        }
+        self.paths.clear();
+
+        let files = crate::recent_files::list();
+
+        for path in &files {
+            let item = MenuItem::new(path.to_string_lossy(), true, None);
+            self.paths.insert(item.id().clone(), path.clone());
+            let _ = self.submenu.append(&item);
+            self.items.push(Box::new(item));
+        }
+
+        if !files.is_empty() {
+            let separator = PredefinedMenuItem::separator();
+            let _ = self.submenu.append(&separator);
+            self.items.push(Box::new(separator));
+        }
+
+        let clear = MenuItem::new(&self.props.clear_label, !files.is_empty(), None);
+        self.clear_id = Some(clear.id().clone());
+        let _ = self.submenu.append(&clear);
+        self.items.push(Box::new(clear));
+    }
+
+    /// Handle a menu event targeting this submenu.
+    ///
+    /// Returns `true` if the event belonged to this submenu.
+    fn handle_event(&self, event: &MenuEvent) -> bool {
+        if let Some(path) = self.paths.get(event.id()) {
+            tracing::info!("Recent file selected: {}", path.display());
+            if let Some(cb) = &self.props.onselect {
+                cb.invoke(path.clone());
+            }
+            return true;
+        }
+
+        if self.clear_id.as_ref() == Some(event.id()) {
+            tracing::info!("Clearing recent files");
+            crate::recent_files::clear();
+            return true;
+        }
+
+        false
+    }
+}
+
 impl MenuManager {
     pub fn new() -> Self {
         Self {
@@ -44,6 +337,9 @@ impl MenuManager {
             item_callbacks: HashMap::new(),
             callbacks: Vec::new(),
             shortcuts: Vec::new(),
+            bindings: Vec::new(),
+            recent_files: None,
+            roles: Vec::new(),
         }
     }
 
@@ -59,6 +355,7 @@ impl MenuManager {
         }
 
         let menu = Menu::new();
+        MENU_ITEMS.with(|items| items.borrow_mut().clear());
 
         for child in children {
             if let Some(submenu) = self.build_submenu(child) {
@@ -66,6 +363,8 @@ impl MenuManager {
             }
         }
 
+        self.apply_shortcuts();
+
         self.menu = Some(menu);
         self.menu.as_ref()
     }
@@ -82,7 +381,7 @@ impl MenuManager {
             match child {
                 Element::MenuItem(item_props) => {
                     let menu_item = self.build_menu_item(item_props);
-                    let _ = submenu.append(&menu_item);
+                    let _ = submenu.append(menu_item.as_native());
                 }
                 Element::MenuSeparator => {
                     let _ = submenu.append(&PredefinedMenuItem::separator());
@@ -93,6 +392,11 @@ impl MenuManager {
                         let _ = submenu.append(&nested);
                     }
                 }
+                Element::RecentFilesMenu(recent_props) => {
+                    let recent = RecentFilesMenu::new(recent_props);
+                    let _ = submenu.append(&recent.submenu);
+                    self.recent_files = Some(recent);
+                }
                 _ => {}
             }
         }
@@ -100,34 +404,150 @@ impl MenuManager {
         Some(submenu)
     }
 
-    /// Build a MenuItem from MenuItemProps.
-    fn build_menu_item(&mut self, props: &MenuItemProps) -> MenuItem {
-        // Parse accelerator from shortcut string
-        let accelerator = props
-            .shortcut
-            .as_ref()
-            .and_then(|s| parse_shortcut(s));
-
-        let item = MenuItem::new(&props.label, props.enabled, accelerator);
+    /// Build a native menu item from MenuItemProps.
+    ///
+    /// Items with a `checked` prop become checkable items.
+    ///
+    /// Accelerators are assigned afterwards by [`MenuManager::apply_shortcuts`].
+    fn build_menu_item(&mut self, props: &MenuItemProps) -> MenuItemHandle {
+        let item = match props.checked {
+            Some(checked) => MenuItemHandle {
+                item: NativeMenuItem::Check(CheckMenuItem::new(
+                    &props.label,
+                    props.enabled,
+                    checked,
+                    None,
+                )),
+            },
+            None => MenuItemHandle {
+                item: NativeMenuItem::Normal(MenuItem::new(&props.label, props.enabled, None)),
+            },
+        };
 
         // Store callback mapping
         let callback_idx = self.callbacks.len();
         self.callbacks.push(MenuCallback {
             label: props.label.clone(),
+            id: props.id.clone(),
             callback: props.onclick.clone(),
         });
         self.item_callbacks.insert(item.id().clone(), callback_idx);
 
-        // Store keyboard shortcut for manual matching
-        if let Some(shortcut_str) = &props.shortcut {
-            if let Some(parsed) = parse_shortcut_for_matching(shortcut_str) {
-                self.shortcuts.push((parsed, item.id().clone()));
-            }
+        // Items with a role have its usual shortcut unless they declare one
+        let declared = props
+            .shortcut
+            .clone()
+            .or_else(|| props.role.map(|role| role.shortcut().to_string()));
+
+        // Items with an id can be given a shortcut by the keymap
+        if declared.is_some() || props.id.is_some() {
+            self.bindings.push(ShortcutBinding {
+                item_id: props.id.clone(),
+                label: props.label.clone(),
+                declared,
+                handle: item.clone(),
+            });
+        }
+
+        if let Some(role) = props.role {
+            self.roles.push(RoleItem {
+                role,
+                declared_enabled: props.enabled,
+                enabled: props.enabled,
+                handle: item.clone(),
+            });
+        }
+
+        if let Some(id) = &props.id {
+            MENU_ITEMS.with(|items| items.borrow_mut().insert(id.clone(), item.clone()));
         }
 
         item
     }
 
+    /// Get a handle to the menu item declared with the given `id`.
+    ///
+    /// Can be called from event handlers and menu callbacks. Returns `None`
+    /// if no native menu item has that `id`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rinch::menu::MenuManager;
+    ///
+    /// if let Some(word_wrap) = MenuManager::item("word-wrap") {
+    ///     word_wrap.set_checked(true);
+    /// }
+    /// ```
+    pub fn item(id: &str) -> Option<MenuItemHandle> {
+        MENU_ITEMS.with(|items| items.borrow().get(id).cloned())
+    }
+
+    /// Assign accelerators from the declared shortcuts and the installed keymap.
+    ///
+    /// Shortcuts assigned to more than one item are reported as conflicts;
+    /// the first item in menu order keeps the shortcut.
+    pub fn apply_shortcuts(&mut self) {
+        let mut shortcuts: Vec<(ParsedShortcut, muda::MenuId)> = Vec::new();
+        // Labels of the items in `shortcuts`, for conflict messages
+        let mut labels: Vec<&str> = Vec::new();
+
+        for binding in &self.bindings {
+            let shortcut =
+                keymap::resolve_shortcut(binding.item_id.as_deref(), binding.declared.as_deref());
+
+            let Some(shortcut) = shortcut else {
+                binding.handle.set_accelerator(None);
+                continue;
+            };
+
+            let Some(parsed) = parse_shortcut_for_matching(&shortcut) else {
+                tracing::warn!(
+                    "Unrecognized shortcut '{}' for menu item '{}'",
+                    shortcut,
+                    binding.label
+                );
+                binding.handle.set_accelerator(None);
+                continue;
+            };
+
+            if let Some(idx) = shortcuts.iter().position(|(p, _)| *p == parsed) {
+                let other = labels[idx];
+                tracing::warn!(
+                    "Shortcut conflict: '{}' is assigned to both '{}' and '{}'; keeping '{}'",
+                    shortcut,
+                    other,
+                    binding.label,
+                    other
+                );
+                binding.handle.set_accelerator(None);
+                continue;
+            }
+
+            binding.handle.set_accelerator(parse_shortcut(&shortcut));
+            shortcuts.push((parsed, binding.handle.id().clone()));
+            labels.push(&binding.label);
+        }
+
+        self.shortcuts = shortcuts;
+    }
+
+    /// Get the label of the menu item assigned `shortcut`, if any.
+    pub(crate) fn shortcut_label(&self, shortcut: &ParsedShortcut) -> Option<String> {
+        let (_, id) = self.shortcuts.iter().find(|(parsed, _)| parsed == shortcut)?;
+        self.bindings
+            .iter()
+            .find(|binding| binding.handle.id() == id)
+            .map(|binding| binding.label.clone())
+    }
+
+    /// Rebuild the recent files submenu from the current recent files list.
+    pub fn refresh_recent_files(&mut self) {
+        if let Some(recent) = &mut self.recent_files {
+            recent.populate();
+        }
+    }
+
     /// Get the menu for platform initialization.
     pub fn menu(&self) -> Option<&Menu> {
         self.menu.as_ref()
@@ -167,14 +587,29 @@ impl MenuManager {
 
     /// Handle a menu event, invoking the callback if one exists.
     ///
+    /// `via_shortcut` and `modifiers` describe how the item was activated and
+    /// are passed on to the callback as a [`MenuActivation`]. `via_shortcut` is
+    /// `true` only for shortcuts matched in a window (`RinchEvent::KeyboardShortcut`);
+    /// muda menu events, including accelerators the OS menu handles, are clicks.
+    ///
     /// Returns `true` if a callback was invoked (indicating state may have changed
     /// and a re-render may be needed), `false` otherwise.
-    pub fn handle_event(&self, event: &MenuEvent) -> bool {
+    pub fn handle_event(&self, event: &MenuEvent, via_shortcut: bool, modifiers: Modifiers) -> bool {
+        if let Some(recent) = &self.recent_files
+            && recent.handle_event(event)
+        {
+            return true;
+        }
+
         if let Some(&callback_idx) = self.item_callbacks.get(event.id()) {
             if let Some(stored) = self.callbacks.get(callback_idx) {
                 tracing::info!("Menu item activated: {}", stored.label);
                 if let Some(cb) = &stored.callback {
-                    cb.invoke();
+                    cb.invoke(&MenuActivation {
+                        via_shortcut,
+                        modifiers,
+                        item_id: stored.id.clone(),
+                    });
                     return true;
                 }
             }
@@ -182,6 +617,28 @@ impl MenuManager {
         false
     }
 
+    /// Get the editing command of a menu item by event, if it has a role.
+    pub fn role(&self, event: &MenuEvent) -> Option<MenuRole> {
+        self.roles
+            .iter()
+            .find(|item| item.handle.id() == event.id())
+            .map(|item| item.role)
+    }
+
+    /// Enable the items with a role whose command `can_perform`, and disable
+    /// the others.
+    ///
+    /// Items declared with `enabled: false` stay disabled.
+    pub fn update_roles(&mut self, can_perform: impl Fn(MenuRole) -> bool) {
+        for item in &mut self.roles {
+            let enabled = item.declared_enabled && can_perform(item.role);
+            if enabled != item.enabled {
+                item.enabled = enabled;
+                item.handle.set_enabled(enabled);
+            }
+        }
+    }
+
     /// Get the label of a menu item by event, for logging purposes.
     pub fn get_label(&self, event: &MenuEvent) -> Option<&str> {
         if let Some(&callback_idx) = self.item_callbacks.get(event.id()) {
@@ -197,30 +654,15 @@ impl MenuManager {
         MenuEvent::receiver()
     }
 
-    /// Check if a keyboard event matches any registered menu shortcut.
+    /// Check if a key press matches any registered menu shortcut.
     ///
     /// Returns the menu ID if a match is found, allowing the caller to
     /// trigger the appropriate menu event.
-    pub fn match_shortcut(
-        &self,
-        ctrl: bool,
-        meta: bool,
-        alt: bool,
-        shift: bool,
-        key: KeyCode,
-    ) -> Option<muda::MenuId> {
-        let ctrl_or_cmd = ctrl || meta;
-
-        for (shortcut, menu_id) in &self.shortcuts {
-            if shortcut.ctrl_or_cmd == ctrl_or_cmd
-                && shortcut.alt == alt
-                && shortcut.shift == shift
-                && shortcut.key == key
-            {
-                return Some(menu_id.clone());
-            }
-        }
-        None
+    pub fn match_shortcut(&self, press: &KeyPress) -> Option<muda::MenuId> {
+        self.shortcuts
+            .iter()
+            .find(|(shortcut, _)| shortcut.matches(press))
+            .map(|(_, menu_id)| menu_id.clone())
     }
 }
 
@@ -242,11 +684,20 @@ fn parse_shortcut(shortcut: &str) -> Option<Accelerator> {
     Accelerator::from_str(&normalized).ok()
 }
 
+/// Check whether a key press is the usual shortcut of an editing command,
+/// which text inputs perform themselves.
+pub(crate) fn is_role_shortcut(role: MenuRole, press: &KeyPress) -> bool {
+    parse_shortcut_for_matching(role.shortcut()).is_some_and(|shortcut| shortcut.matches(press))
+}
+
 /// Parse a shortcut string into a ParsedShortcut for keyboard event matching.
-fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
-    let parts: Vec<&str> = shortcut.split('+').collect();
-    if parts.is_empty() {
-        return None;
+pub(crate) fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
+    let mut parts: Vec<&str> = shortcut.split('+').collect();
+
+    // "Cmd++" splits into ["Cmd", "", ""] - the key is a literal '+'
+    if shortcut.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
     }
 
     let mut ctrl_or_cmd = false;
@@ -255,82 +706,16 @@ fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
     let mut key_str = "";
 
     for part in &parts {
-        let part_lower = part.to_lowercase();
+        let part_lower = part.trim().to_lowercase();
         match part_lower.as_str() {
             "cmd" | "ctrl" | "control" | "meta" | "cmdorctrl" => ctrl_or_cmd = true,
             "alt" | "option" => alt = true,
             "shift" => shift = true,
-            _ => key_str = part,
-        }
-    }
-
-    let key = match key_str.to_uppercase().as_str() {
-        "A" => KeyCode::KeyA,
-        "B" => KeyCode::KeyB,
-        "C" => KeyCode::KeyC,
-        "D" => KeyCode::KeyD,
-        "E" => KeyCode::KeyE,
-        "F" => KeyCode::KeyF,
-        "G" => KeyCode::KeyG,
-        "H" => KeyCode::KeyH,
-        "I" => KeyCode::KeyI,
-        "J" => KeyCode::KeyJ,
-        "K" => KeyCode::KeyK,
-        "L" => KeyCode::KeyL,
-        "M" => KeyCode::KeyM,
-        "N" => KeyCode::KeyN,
-        "O" => KeyCode::KeyO,
-        "P" => KeyCode::KeyP,
-        "Q" => KeyCode::KeyQ,
-        "R" => KeyCode::KeyR,
-        "S" => KeyCode::KeyS,
-        "T" => KeyCode::KeyT,
-        "U" => KeyCode::KeyU,
-        "V" => KeyCode::KeyV,
-        "W" => KeyCode::KeyW,
-        "X" => KeyCode::KeyX,
-        "Y" => KeyCode::KeyY,
-        "Z" => KeyCode::KeyZ,
-        "0" => KeyCode::Digit0,
-        "1" => KeyCode::Digit1,
-        "2" => KeyCode::Digit2,
-        "3" => KeyCode::Digit3,
-        "4" => KeyCode::Digit4,
-        "5" => KeyCode::Digit5,
-        "6" => KeyCode::Digit6,
-        "7" => KeyCode::Digit7,
-        "8" => KeyCode::Digit8,
-        "9" => KeyCode::Digit9,
-        "=" | "EQUAL" | "PLUS" => KeyCode::Equal,
-        "-" | "MINUS" => KeyCode::Minus,
-        "F1" => KeyCode::F1,
-        "F2" => KeyCode::F2,
-        "F3" => KeyCode::F3,
-        "F4" => KeyCode::F4,
-        "F5" => KeyCode::F5,
-        "F6" => KeyCode::F6,
-        "F7" => KeyCode::F7,
-        "F8" => KeyCode::F8,
-        "F9" => KeyCode::F9,
-        "F10" => KeyCode::F10,
-        "F11" => KeyCode::F11,
-        "F12" => KeyCode::F12,
-        "ENTER" | "RETURN" => KeyCode::Enter,
-        "ESCAPE" | "ESC" => KeyCode::Escape,
-        "BACKSPACE" => KeyCode::Backspace,
-        "TAB" => KeyCode::Tab,
-        "SPACE" => KeyCode::Space,
-        "DELETE" | "DEL" => KeyCode::Delete,
-        "HOME" => KeyCode::Home,
-        "END" => KeyCode::End,
-        "PAGEUP" => KeyCode::PageUp,
-        "PAGEDOWN" => KeyCode::PageDown,
-        "UP" | "ARROWUP" => KeyCode::ArrowUp,
-        "DOWN" | "ARROWDOWN" => KeyCode::ArrowDown,
-        "LEFT" | "ARROWLEFT" => KeyCode::ArrowLeft,
-        "RIGHT" | "ARROWRIGHT" => KeyCode::ArrowRight,
-        _ => return None,
-    };
+            _ => key_str = part.trim(),
+        }
+    }
+
+    let key = parse_shortcut_key(key_str)?;
 
     Some(ParsedShortcut {
         ctrl_or_cmd,
@@ -339,3 +724,264 @@ fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
         key,
     })
 }
+
+/// Short key names, with the winit names they stand for.
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("plus", "Equal"),
+    ("return", "Enter"),
+    ("esc", "Escape"),
+    ("del", "Delete"),
+    ("ins", "Insert"),
+    ("up", "ArrowUp"),
+    ("down", "ArrowDown"),
+    ("left", "ArrowLeft"),
+    ("right", "ArrowRight"),
+    ("numplus", "NumpadAdd"),
+    ("numpadplus", "NumpadAdd"),
+    ("numminus", "NumpadSubtract"),
+];
+
+/// The keys producing punctuation on a US layout.
+const US_PUNCTUATION: &[(char, KeyCode)] = &[
+    ('=', KeyCode::Equal),
+    ('-', KeyCode::Minus),
+    (',', KeyCode::Comma),
+    ('.', KeyCode::Period),
+    ('/', KeyCode::Slash),
+    ('\\', KeyCode::Backslash),
+    (';', KeyCode::Semicolon),
+    ('\'', KeyCode::Quote),
+    ('`', KeyCode::Backquote),
+    ('[', KeyCode::BracketLeft),
+    (']', KeyCode::BracketRight),
+];
+
+/// Parse the key part of a shortcut string.
+///
+/// Key names are winit's [`NamedKey`] names (`Enter`, `ArrowUp`, `F5`, ...),
+/// matched by the key's meaning, or its [`KeyCode`] names (`Comma`,
+/// `Numpad1`, `KeyA`, ...), matched by physical key. Case doesn't matter,
+/// and `Num` abbreviates `Numpad`. Single characters (`,`, `/`, `S`, ...)
+/// match by the character the key produces.
+fn parse_shortcut_key(key_str: &str) -> Option<ShortcutKey> {
+    let mut chars = key_str.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        if ch.is_ascii_digit() {
+            return physical_key_for_char(ch).map(ShortcutKey::Physical);
+        }
+        if ch.is_alphanumeric() || ch.is_ascii_punctuation() {
+            return Some(ShortcutKey::Character {
+                ch: ch.to_lowercase().collect(),
+                fallback: physical_key_for_char(ch.to_ascii_lowercase()),
+            });
+        }
+        return None;
+    }
+
+    let lower = key_str.to_ascii_lowercase();
+    let name = KEY_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == lower)
+        .map_or(key_str, |(_, name)| name);
+
+    if let Some(named) = key_by_name::<NamedKey>(name) {
+        return Some(ShortcutKey::Named(named));
+    }
+    if let Some(code) = key_by_name::<KeyCode>(name) {
+        return Some(ShortcutKey::Physical(code));
+    }
+    let numpad = lower.strip_prefix("num").filter(|rest| !rest.starts_with("pad"))?;
+    key_by_name::<KeyCode>(&format!("Numpad{}", numpad)).map(ShortcutKey::Physical)
+}
+
+/// The physical key producing a character on a US layout.
+fn physical_key_for_char(ch: char) -> Option<KeyCode> {
+    if ch.is_ascii_lowercase() {
+        return key_by_name(&format!("Key{}", ch));
+    }
+    if ch.is_ascii_digit() {
+        return key_by_name(&format!("Digit{}", ch));
+    }
+    US_PUNCTUATION.iter().find(|(c, _)| *c == ch).map(|(_, code)| *code)
+}
+
+/// Look up a variant of one of winit's key enums by name, ignoring case.
+fn key_by_name<K: DeserializeOwned>(name: &str) -> Option<K> {
+    K::deserialize(KeyName(name)).ok()
+}
+
+/// Deserializes a unit variant whose name matches, ignoring case.
+struct KeyName<'a>(&'a str);
+
+impl<'de> Deserializer<'de> for KeyName<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let variant = variants
+            .iter()
+            .find(|variant| variant.eq_ignore_ascii_case(self.0))
+            .ok_or_else(|| serde::de::Error::unknown_variant(self.0, variants))?;
+        visitor.visit_enum(variant.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(modifiers: Modifiers, key: KeyCode, text: Option<&str>, unmodified: Option<&str>) -> KeyPress {
+        KeyPress {
+            modifiers,
+            key,
+            named_key: None,
+            text: text.map(String::from),
+            unmodified_text: unmodified.map(String::from),
+        }
+    }
+
+    fn named(modifiers: Modifiers, key: KeyCode, named_key: NamedKey) -> KeyPress {
+        KeyPress {
+            named_key: Some(named_key),
+            ..press(modifiers, key, None, None)
+        }
+    }
+
+    fn ctrl() -> Modifiers {
+        Modifiers {
+            ctrl: true,
+            ..Default::default()
+        }
+    }
+
+    fn ctrl_shift() -> Modifiers {
+        Modifiers {
+            shift: true,
+            ..ctrl()
+        }
+    }
+
+    fn matches(shortcut: &str, press: &KeyPress) -> bool {
+        parse_shortcut_for_matching(shortcut)
+            .unwrap_or_else(|| panic!("'{}' didn't parse", shortcut))
+            .matches(press)
+    }
+
+    #[test]
+    fn modifiers_must_match_exactly() {
+        let ctrl_s = press(ctrl(), KeyCode::KeyS, Some("s"), Some("s"));
+        assert!(matches("Ctrl+S", &ctrl_s));
+        assert!(matches("Cmd+S", &ctrl_s));
+        assert!(!matches("Ctrl+Shift+S", &ctrl_s));
+        assert!(!matches("Alt+S", &ctrl_s));
+        assert!(!matches("S", &ctrl_s));
+
+        let cmd_s = press(
+            Modifiers {
+                meta: true,
+                ..Default::default()
+            },
+            KeyCode::KeyS,
+            Some("s"),
+            Some("s"),
+        );
+        assert!(matches("Cmd+S", &cmd_s));
+
+        let ctrl_shift_s = press(ctrl_shift(), KeyCode::KeyS, Some("S"), Some("s"));
+        assert!(matches("Cmd+Shift+S", &ctrl_shift_s));
+        assert!(!matches("Cmd+S", &ctrl_shift_s));
+    }
+
+    #[test]
+    fn characters_follow_the_layout() {
+        // '/' is Shift+7 on a German layout
+        let german_slash = press(ctrl_shift(), KeyCode::Digit7, Some("/"), Some("7"));
+        assert!(matches("Cmd+/", &german_slash));
+
+        // 'z' is where 'y' is on a US layout
+        let german_z = press(ctrl(), KeyCode::KeyY, Some("z"), Some("z"));
+        assert!(matches("Cmd+Z", &german_z));
+        assert!(!matches("Cmd+Y", &german_z));
+
+        // '?' needs Shift, which the shortcut doesn't have to mention
+        let question = press(ctrl_shift(), KeyCode::Slash, Some("?"), Some("/"));
+        assert!(matches("Cmd+?", &question));
+        assert!(!matches("Cmd+/", &question));
+        assert!(matches("Cmd+Shift+/", &question));
+    }
+
+    #[test]
+    fn characters_fall_back_to_us_positions() {
+        // Cyrillic layouts type 'с' on the S key
+        let cyrillic_s = press(ctrl(), KeyCode::KeyC, Some("с"), Some("с"));
+        assert!(matches("Cmd+C", &cyrillic_s));
+        assert!(!matches("Cmd+S", &cyrillic_s));
+
+        // Latin layouts don't fall back
+        let azerty_a = press(ctrl(), KeyCode::KeyQ, Some("a"), Some("a"));
+        assert!(!matches("Cmd+Q", &azerty_a));
+        assert!(matches("Cmd+A", &azerty_a));
+    }
+
+    #[test]
+    fn named_keys_match_by_meaning() {
+        let enter = named(Modifiers::default(), KeyCode::Enter, NamedKey::Enter);
+        let numpad_enter = named(Modifiers::default(), KeyCode::NumpadEnter, NamedKey::Enter);
+        assert!(matches("Enter", &enter));
+        assert!(matches("Return", &numpad_enter));
+        assert!(!matches("NumEnter", &enter));
+        assert!(matches("NumEnter", &numpad_enter));
+
+        let ctrl_up = named(ctrl(), KeyCode::ArrowUp, NamedKey::ArrowUp);
+        assert!(matches("Ctrl+Up", &ctrl_up));
+        assert!(matches("ctrl+arrowup", &ctrl_up));
+        assert!(!matches("Up", &ctrl_up));
+        assert!(!matches("Ctrl+Down", &ctrl_up));
+
+        let f5 = named(Modifiers::default(), KeyCode::F5, NamedKey::F5);
+        assert!(matches("F5", &f5));
+        assert!(!matches("Shift+F5", &f5));
+    }
+
+    #[test]
+    fn physical_key_names() {
+        let comma = press(ctrl(), KeyCode::Comma, Some(";"), Some(";"));
+        assert!(matches("Cmd+Comma", &comma));
+        assert!(!matches("Cmd+,", &comma));
+
+        let numpad_1 = press(ctrl(), KeyCode::Numpad1, Some("1"), Some("1"));
+        assert!(matches("Cmd+Num1", &numpad_1));
+        assert!(matches("Cmd+Numpad1", &numpad_1));
+        assert!(!matches("Cmd+1", &numpad_1));
+
+        let plus = press(ctrl(), KeyCode::Equal, Some("="), Some("="));
+        assert!(matches("Cmd+Plus", &plus));
+        assert!(matches("Cmd++", &press(ctrl_shift(), KeyCode::Equal, Some("+"), Some("="))));
+
+        let add = press(ctrl(), KeyCode::NumpadAdd, Some("+"), Some("+"));
+        assert!(matches("Cmd+NumAdd", &add));
+        assert!(matches("Cmd+NumPlus", &add));
+    }
+
+    #[test]
+    fn unknown_keys_dont_parse() {
+        assert!(parse_shortcut_for_matching("Ctrl+NotAKey").is_none());
+        assert!(parse_shortcut_for_matching("Ctrl+").is_none());
+        assert!(parse_shortcut_for_matching("Ctrl+Unidentified").is_none());
+        assert!(parse_shortcut_for_matching("Ctrl+Numbers").is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Messages between windows.
+//!
+//! [`WindowHandle::post_message`] sends a value of any type to a window,
+//! such as a settings window telling the main window that a setting
+//! changed. Messages go through the event loop, so they arrive after the
+//! handler that posted them returns, and are queued in the
+//! [`WindowMessages`] that [`use_window_messages`] returns for the
+//! receiving window.
+//!
+//! ```ignore
+//! use rinch::messages::use_window_messages;
+//!
+//! #[derive(Clone)]
+//! struct SettingsSaved {
+//!     font_size: u32,
+//! }
+//!
+//! // In the settings window's save button
+//! main_window.post_message(SettingsSaved { font_size: 16 });
+//!
+//! // In the main window
+//! fn app() -> Element {
+//!     let messages = use_window_messages();
+//!     let font_size = messages
+//!         .latest()
+//!         .and_then(|message| message.downcast::<SettingsSaved>())
+//!         .map_or(14, |settings| settings.font_size);
+//!     // ...
+//! }
+//! ```
+//!
+//! [`WindowMessages::latest`] suits messages that replace each other, like
+//! the whole of the saved settings. To act on every message, take them with
+//! [`WindowMessages::drain`], typically in an [`Effect`](rinch_core::Effect),
+//! which runs again whenever messages arrive.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use rinch_core::{affects_render, Signal};
+
+use crate::shell::runtime::RinchEvent;
+use crate::windows::{PerWindow, WindowHandle};
+
+/// A value posted to a window with [`WindowHandle::post_message`].
+#[derive(Clone)]
+pub struct WindowMessage {
+    from: Option<WindowHandle>,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl WindowMessage {
+    /// The window whose event handler posted the message, if it was posted
+    /// from one.
+    pub fn from(&self) -> Option<WindowHandle> {
+        self.from
+    }
+
+    /// Whether the message holds a `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    /// The message's value, if it is a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// A copy of the message's value, if it is a `T`.
+    pub fn downcast<T: Any + Clone>(&self) -> Option<T> {
+        self.downcast_ref().cloned()
+    }
+}
+
+impl std::fmt::Debug for WindowMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowMessage").field("from", &self.from).finish_non_exhaustive()
+    }
+}
+
+/// The most messages a window keeps waiting; older ones are dropped.
+const MAX_WAITING: usize = 1000;
+
+/// The messages posted to a window.
+///
+/// Reading them with [`latest`](Self::latest), [`drain`](Self::drain) or
+/// [`len`](Self::len) while rendering, or in an effect, subscribes like
+/// reading a signal: the render or effect runs again when a message arrives.
+/// Clones share the same messages.
+#[derive(Clone)]
+pub struct WindowMessages {
+    /// Counts the messages that arrived, to notify readers.
+    arrived: Signal<u64>,
+    waiting: Rc<RefCell<VecDeque<WindowMessage>>>,
+    latest: Rc<RefCell<Option<WindowMessage>>>,
+}
+
+impl WindowMessages {
+    fn new() -> Self {
+        Self {
+            arrived: Signal::new(0),
+            waiting: Rc::default(),
+            latest: Rc::default(),
+        }
+    }
+
+    /// The newest message that arrived, whether or not it was drained.
+    pub fn latest(&self) -> Option<WindowMessage> {
+        self.arrived.with(|_| self.latest.borrow().clone())
+    }
+
+    /// Take the messages waiting, oldest first.
+    ///
+    /// Taking them doesn't notify anyone, so it can be done while rendering.
+    pub fn drain(&self) -> Vec<WindowMessage> {
+        self.arrived.with(|_| self.waiting.borrow_mut().drain(..).collect())
+    }
+
+    /// The number of messages waiting.
+    pub fn len(&self) -> usize {
+        self.arrived.with(|_| self.waiting.borrow().len())
+    }
+
+    /// Whether no messages are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queue a message that arrived and notify readers.
+    fn push(&self, message: WindowMessage) {
+        {
+            let mut waiting = self.waiting.borrow_mut();
+            if waiting.len() == MAX_WAITING {
+                waiting.pop_front();
+            }
+            waiting.push_back(message.clone());
+        }
+        *self.latest.borrow_mut() = Some(message);
+        self.arrived.update(|count| *count += 1);
+    }
+}
+
+impl std::fmt::Debug for WindowMessages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowMessages").field("waiting", &self.waiting.borrow().len()).finish_non_exhaustive()
+    }
+}
+
+thread_local! {
+    /// The messages of open windows.
+    static MESSAGES: PerWindow<WindowMessages> = PerWindow::new(WindowMessages::new);
+}
+
+/// Get the messages posted to a window.
+///
+/// Every call for the same window returns the same messages.
+pub fn window_messages(window: WindowHandle) -> WindowMessages {
+    MESSAGES.with(|messages| messages.get(window))
+}
+
+/// Get the messages posted to the window handling the current event, or to
+/// the app's main window (the oldest one open) while rendering.
+pub fn use_window_messages() -> WindowMessages {
+    MESSAGES.with(PerWindow::current)
+}
+
+/// Send a message to a window through the event loop.
+pub(crate) fn post(to: WindowHandle, value: Arc<dyn Any + Send + Sync>) {
+    let from = crate::windows::get_current_window_id().and_then(crate::windows::window_handle);
+    crate::windows::send_event(RinchEvent::WindowMessage {
+        to,
+        message: WindowMessage { from, value },
+    });
+}
+
+/// Hand a posted message to its window (called by the runtime).
+///
+/// Messages to windows that closed in the meantime are dropped.
+pub(crate) fn deliver(to: WindowHandle, message: WindowMessage) {
+    if crate::windows::native_window_id(to).is_none() {
+        tracing::debug!("Dropping message to closed window {:?}", to);
+        return;
+    }
+    let messages = window_messages(to);
+    let ((), render) = affects_render(|| messages.push(message));
+    if render {
+        crate::shell::runtime::request_render();
+    }
+}
+
+/// Give a window that was just opened its messages (called by the runtime).
+pub(crate) fn attach(window: WindowHandle) {
+    MESSAGES.with(|messages| messages.attach(window));
+}
+
+/// Forget the messages of a closed window (called by the runtime).
+pub(crate) fn detach(window: WindowHandle) {
+    MESSAGES.with(|messages| messages.detach(window));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::Effect;
+    use std::cell::Cell;
+
+    fn message(value: u32) -> WindowMessage {
+        WindowMessage { from: None, value: Arc::new(value) }
+    }
+
+    #[test]
+    fn messages_queue_until_drained() {
+        let messages = WindowMessages::new();
+        assert!(messages.latest().is_none());
+        for value in 1..=3 {
+            messages.push(message(value));
+        }
+        assert_eq!(messages.len(), 3);
+        let values: Vec<u32> = messages.drain().iter().filter_map(WindowMessage::downcast).collect();
+        assert_eq!(values, [1, 2, 3]);
+        assert!(messages.is_empty());
+        // The latest is kept after draining
+        assert_eq!(messages.latest().and_then(|m| m.downcast::<u32>()), Some(3));
+    }
+
+    #[test]
+    fn arriving_messages_rerun_readers() {
+        let messages = WindowMessages::new();
+        let received = Rc::new(Cell::new(0));
+        let (reader, count) = (messages.clone(), received.clone());
+        let effect = Effect::new(move || count.set(count.get() + reader.drain().len()));
+        messages.push(message(1));
+        messages.push(message(2));
+        assert_eq!(received.get(), 2);
+        effect.dispose();
+    }
+
+    #[test]
+    fn oldest_waiting_messages_are_dropped() {
+        let messages = WindowMessages::new();
+        for value in 0..MAX_WAITING as u32 + 5 {
+            messages.push(message(value));
+        }
+        let waiting = messages.drain();
+        assert_eq!(waiting.len(), MAX_WAITING);
+        assert_eq!(waiting[0].downcast::<u32>(), Some(5));
+    }
+}
@@ -0,0 +1,171 @@
+//! Following the mouse from the app.
+//!
+//! [`use_mouse_position`] gives a signal holding where the mouse is, and
+//! [`use_hovered_element`] one holding the element under it, for custom
+//! tooltips, eyedroppers or a status bar describing what the mouse is
+//! over:
+//!
+//! ```ignore
+//! use rinch::mouse::use_hovered_element;
+//! use rinch::prelude::*;
+//!
+//! fn app() -> Element {
+//!     let hovered = use_hovered_element();
+//!
+//!     let help = match hovered.get().as_ref().and_then(|e| e.id()) {
+//!         Some("save") => "Save the document",
+//!         Some("export") => "Export as PDF",
+//!         _ => "",
+//!     };
+//!     rsx! {
+//!         Window { title: "Editor",
+//!             button { id: "save", "Save" }
+//!             button { id: "export", "Export" }
+//!             footer { {help} }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Both are `None` while the mouse is outside every window. The window
+//! only works them out once they have been asked for, and the app only
+//! re-renders when they change if the render read them.
+
+use std::cell::RefCell;
+
+use rinch_core::{affects_render, untracked, Signal};
+
+use crate::windows::WindowHandle;
+
+/// Where the mouse is in a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MousePosition {
+    /// The window the mouse is over.
+    pub window: WindowHandle,
+    /// Logical pixels from the left edge of the window's content.
+    pub x: f32,
+    /// Logical pixels from the top edge of the window's content.
+    pub y: f32,
+}
+
+/// The element under the mouse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoveredElement {
+    /// The window the element is in.
+    pub window: WindowHandle,
+    /// The element's tag name, such as `"button"`.
+    pub tag_name: String,
+    /// The element's attributes, in document order.
+    pub attributes: Vec<(String, String)>,
+    /// The left edge of the element's border box, in CSS pixels from the
+    /// left of the document.
+    pub x: f32,
+    /// The top edge of the element's border box, in CSS pixels from the
+    /// top of the document.
+    pub y: f32,
+    /// The width of the element's border box.
+    pub width: f32,
+    /// The height of the element's border box.
+    pub height: f32,
+}
+
+impl HoveredElement {
+    /// The value of an attribute of the element.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The element's `id` attribute.
+    pub fn id(&self) -> Option<&str> {
+        self.attribute("id")
+    }
+
+    /// Whether the element's `class` attribute has `class`.
+    pub fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    }
+}
+
+thread_local! {
+    /// The signal returned by `use_mouse_position`, once asked for.
+    static POSITION: RefCell<Option<Signal<Option<MousePosition>>>> = const { RefCell::new(None) };
+    /// The signal returned by `use_hovered_element`, once asked for.
+    static HOVERED: RefCell<Option<Signal<Option<HoveredElement>>>> = const { RefCell::new(None) };
+}
+
+/// Get a signal holding where the mouse is.
+///
+/// Every call returns the same signal. It is updated as the mouse moves,
+/// and is `None` while the mouse is outside every window.
+pub fn use_mouse_position() -> Signal<Option<MousePosition>> {
+    signal(&POSITION)
+}
+
+/// Get a signal holding the element under the mouse.
+///
+/// Every call returns the same signal. It is updated when the mouse moves
+/// onto another element, or the element under it changes, and is `None`
+/// while the mouse is outside every window or over no element.
+pub fn use_hovered_element() -> Signal<Option<HoveredElement>> {
+    signal(&HOVERED)
+}
+
+fn signal<T: 'static>(
+    key: &'static std::thread::LocalKey<RefCell<Option<Signal<Option<T>>>>>,
+) -> Signal<Option<T>> {
+    key.with(|s| s.borrow_mut().get_or_insert_with(|| Signal::new(None)).clone())
+}
+
+/// Whether the app asked for the mouse position.
+pub(crate) fn is_position_watched() -> bool {
+    POSITION.with(|s| s.borrow().is_some())
+}
+
+/// Whether the app asked for the hovered element.
+pub(crate) fn is_hovered_watched() -> bool {
+    HOVERED.with(|s| s.borrow().is_some())
+}
+
+/// Set a signal if its value changed, re-rendering if the render read it.
+fn update<T: Clone + PartialEq + 'static>(
+    key: &'static std::thread::LocalKey<RefCell<Option<Signal<Option<T>>>>>,
+    value: Option<T>,
+) {
+    let Some(signal) = key.with(|s| s.borrow().clone()) else {
+        return;
+    };
+    if untracked(|| signal.get()) == value {
+        return;
+    }
+    let ((), render) = affects_render(|| signal.set(value));
+    if render {
+        crate::shell::runtime::request_render();
+    }
+}
+
+/// Report where the mouse is (called by the window it moved in).
+pub(crate) fn moved(position: Option<MousePosition>) {
+    update(&POSITION, position);
+}
+
+/// Report the element under the mouse (called by the window it moved in).
+pub(crate) fn hovered(element: Option<HoveredElement>) {
+    update(&HOVERED, element);
+}
+
+/// Clear the signals if they point into a closed window (called by the
+/// runtime).
+pub(crate) fn forget(window: WindowHandle) {
+    let position = POSITION.with(|s| s.borrow().as_ref().and_then(|s| untracked(|| s.get())));
+    if position.is_some_and(|p| p.window == window) {
+        moved(None);
+    }
+    let element = HOVERED.with(|s| s.borrow().as_ref().and_then(|s| untracked(|| s.get())));
+    if element.is_some_and(|e| e.window == window) {
+        hovered(None);
+    }
+}
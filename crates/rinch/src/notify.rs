@@ -0,0 +1,216 @@
+//! Native desktop notifications.
+//!
+//! This module shows notifications in the OS notification center using the
+//! `notify-rust` crate. Action buttons are delivered back through the runtime,
+//! so their callbacks run on the UI thread like event handlers.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::notify::{send, Notification};
+//!
+//! let downloads = downloads_signal.clone();
+//! send(
+//!     Notification::new("Download finished")
+//!         .body("report.pdf was saved to Downloads")
+//!         .action("Open", move || open_download(&downloads)),
+//! );
+//! ```
+//!
+//! # Platform support
+//!
+//! Action buttons are shown on Linux and the BSDs (freedesktop
+//! notifications). On macOS and Windows the notification is shown without
+//! them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::shell::runtime::RinchEvent;
+
+thread_local! {
+    /// Action callbacks of notifications that are still shown, by notification ID.
+    static ACTIONS: RefCell<HashMap<NotificationId, Vec<NotificationAction>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A notification to show with [`send`].
+#[derive(Clone, Default)]
+pub struct Notification {
+    /// The title (summary) of the notification.
+    pub title: String,
+    /// The body text.
+    pub body: String,
+    /// An icon name from the icon theme, or a path to an image file.
+    pub icon: Option<String>,
+    /// Buttons shown on the notification.
+    pub actions: Vec<NotificationAction>,
+}
+
+impl Notification {
+    /// Create a notification with a title.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the body text.
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Set the icon, by icon theme name or image path.
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Add a button that runs `callback` on the UI thread when clicked.
+    pub fn action(mut self, label: impl Into<String>, callback: impl Fn() + 'static) -> Self {
+        self.actions.push(NotificationAction::new(label, callback));
+        self
+    }
+}
+
+impl fmt::Debug for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notification")
+            .field("title", &self.title)
+            .field("body", &self.body)
+            .field("icon", &self.icon)
+            .field("actions", &self.actions)
+            .finish()
+    }
+}
+
+/// A button on a [`Notification`].
+#[derive(Clone)]
+pub struct NotificationAction {
+    /// The button's label.
+    pub label: String,
+    callback: Rc<dyn Fn()>,
+}
+
+impl NotificationAction {
+    /// Create a button that runs `callback` on the UI thread when clicked.
+    pub fn new(label: impl Into<String>, callback: impl Fn() + 'static) -> Self {
+        Self {
+            label: label.into(),
+            callback: Rc::new(callback),
+        }
+    }
+}
+
+impl fmt::Debug for NotificationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotificationAction")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Identifies a notification shown with [`send`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotificationId(u64);
+
+/// How the user responded to a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationResponse {
+    /// The notification was closed without clicking a button, or the
+    /// platform doesn't report actions.
+    Closed,
+    /// The button at this index of [`Notification::actions`] was clicked.
+    Action(usize),
+}
+
+/// Show a notification.
+///
+/// Must be called on the UI thread, since action callbacks run there; from a
+/// background task, call it inside
+/// [`AppHandle::run_on_ui_thread`](crate::tasks::AppHandle::run_on_ui_thread).
+/// The notification is shown on a background thread, and failures are
+/// logged.
+pub fn send(notification: Notification) -> NotificationId {
+    static COUNTER: AtomicU64 = AtomicU64::new(1);
+    let id = NotificationId(COUNTER.fetch_add(1, Ordering::SeqCst));
+
+    let mut native = notify_rust::Notification::new();
+    native.summary(&notification.title).body(&notification.body);
+    if let Some(icon) = &notification.icon {
+        native.icon(icon);
+    }
+    for (index, action) in notification.actions.iter().enumerate() {
+        native.action(&index.to_string(), &action.label);
+    }
+    if !notification.actions.is_empty() {
+        ACTIONS.with(|actions| actions.borrow_mut().insert(id, notification.actions));
+    }
+
+    let spawned = std::thread::Builder::new()
+        .name("rinch-notification".into())
+        .spawn(move || {
+            let response = show(&native);
+            crate::tasks::app_handle().dispatch(RinchEvent::NotificationResponse { id, response });
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to show notification: {}", e);
+        ACTIONS.with(|actions| actions.borrow_mut().remove(&id));
+    }
+    id
+}
+
+/// Show a notification and wait for the response.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(native: &notify_rust::Notification) -> NotificationResponse {
+    let handle = match native.show() {
+        Ok(handle) => handle,
+        Err(e) => {
+            tracing::warn!("Failed to show notification: {}", e);
+            return NotificationResponse::Closed;
+        }
+    };
+    // Actions are identified by their index; anything else ("__closed", or
+    // "default" for a click on the notification itself) closes it
+    let mut response = NotificationResponse::Closed;
+    handle.wait_for_action(|action| {
+        if let Ok(index) = action.parse() {
+            response = NotificationResponse::Action(index);
+        }
+    });
+    response
+}
+
+/// Show a notification. Other platforms don't report actions.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show(native: &notify_rust::Notification) -> NotificationResponse {
+    if let Err(e) = native.show() {
+        tracing::warn!("Failed to show notification: {}", e);
+    }
+    NotificationResponse::Closed
+}
+
+/// Run the callback for a response, if any, and forget the notification's
+/// actions (called by runtime on `RinchEvent::NotificationResponse`).
+///
+/// Returns whether a callback ran.
+pub(crate) fn handle_response(id: NotificationId, response: NotificationResponse) -> bool {
+    let Some(actions) = ACTIONS.with(|actions| actions.borrow_mut().remove(&id)) else {
+        return false;
+    };
+    let NotificationResponse::Action(index) = response else {
+        return false;
+    };
+    match actions.get(index) {
+        Some(action) => {
+            (action.callback)();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,190 @@
+//! Watching elements from the app.
+//!
+//! [`use_visibility`] gives a signal holding whether an element shows in its
+//! window, for loading images once they scroll into view or counting what
+//! the user saw, and [`use_element_size`] one holding its size, for
+//! components that lay themselves out by the room they get rather than by
+//! the window's size. Elements are picked with a CSS selector, as in
+//! [`rinch::scroll`](crate::scroll):
+//!
+//! ```ignore
+//! use rinch::observe::use_visibility;
+//! use rinch::prelude::*;
+//!
+//! fn photo(id: usize) -> Element {
+//!     let visible = use_visibility(&format!("#photo-{}", id));
+//!
+//!     let src = if visible.get() {
+//!         format!("photos/{}.jpg", id)
+//!     } else {
+//!         String::from("placeholder.png")
+//!     };
+//!     rsx! { img { id: {format!("photo-{}", id)}, src: {src} } }
+//! }
+//! ```
+//!
+//! The window works the signals out after laying out its document, when it
+//! re-renders, scrolls or is resized. They are only set when they change,
+//! and the app only re-renders then if the render read them.
+
+use std::cell::RefCell;
+use std::thread::LocalKey;
+
+use rinch_core::{affects_render, untracked, Signal};
+
+use crate::windows::WindowHandle;
+
+/// The size of an element's border box, in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ElementSize {
+    /// The width, including padding and borders.
+    pub width: f32,
+    /// The height, including padding and borders.
+    pub height: f32,
+}
+
+/// A signal updated with something about the element matching a selector.
+struct Watch<T> {
+    selector: String,
+    /// The window the element was last found in.
+    window: Option<WindowHandle>,
+    signal: Signal<T>,
+}
+
+type Watches<T> = RefCell<Vec<Watch<T>>>;
+
+thread_local! {
+    /// Signals following whether elements show.
+    static VISIBILITY: Watches<bool> = const { RefCell::new(Vec::new()) };
+    /// Signals following the sizes of elements.
+    static SIZES: Watches<Option<ElementSize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Get a signal holding whether the element matching `selector` shows, in
+/// whichever window has one.
+///
+/// An element shows while part of its box is inside the window and inside
+/// every scroll container around it. The signal is `false` while no window
+/// has the element. Every call with the same selector returns the same
+/// signal.
+pub fn use_visibility(selector: &str) -> Signal<bool> {
+    watch(&VISIBILITY, selector, false)
+}
+
+/// Get a signal holding the size of the element matching `selector`, in
+/// whichever window has one.
+///
+/// The signal is `None` while no window has the element. It is set when the
+/// element's size changes, not when it moves. Every call with the same
+/// selector returns the same signal.
+pub fn use_element_size(selector: &str) -> Signal<Option<ElementSize>> {
+    watch(&SIZES, selector, None)
+}
+
+/// Get the signal following `selector`, adding it if it doesn't exist.
+fn watch<T: 'static>(key: &'static LocalKey<Watches<T>>, selector: &str, initial: T) -> Signal<T> {
+    key.with(|watches| {
+        let mut watches = watches.borrow_mut();
+        if let Some(watch) = watches.iter().find(|w| w.selector == selector) {
+            return watch.signal.clone();
+        }
+        let signal = Signal::new(initial);
+        watches.push(Watch {
+            selector: selector.to_string(),
+            window: None,
+            signal: signal.clone(),
+        });
+        signal
+    })
+}
+
+/// Whether the app watches whether any element shows.
+pub(crate) fn is_visibility_watched() -> bool {
+    VISIBILITY.with(|watches| !watches.borrow().is_empty())
+}
+
+/// Whether the app watches the size of any element.
+pub(crate) fn is_size_watched() -> bool {
+    SIZES.with(|watches| !watches.borrow().is_empty())
+}
+
+/// Update the signals with whether `visible_of` finds their elements
+/// showing in `window`, or `None` if it has no such element (called by the
+/// window after laying out its document).
+pub(crate) fn update_visibility(window: WindowHandle, visible_of: impl Fn(&str) -> Option<bool>) {
+    update(&VISIBILITY, window, visible_of, false);
+}
+
+/// Update the signals with the sizes `size_of` finds for their elements in
+/// `window`, or `None` if it has no such element (called by the window
+/// after laying out its document).
+pub(crate) fn update_sizes(window: WindowHandle, size_of: impl Fn(&str) -> Option<ElementSize>) {
+    update(&SIZES, window, |selector| size_of(selector).map(Some), None);
+}
+
+fn update<T: Clone + PartialEq + 'static>(
+    key: &'static LocalKey<Watches<T>>,
+    window: WindowHandle,
+    value_of: impl Fn(&str) -> Option<T>,
+    gone: T,
+) {
+    let changed: Vec<_> = key.with(|watches| {
+        watches
+            .borrow_mut()
+            .iter_mut()
+            .filter_map(|w| {
+                let value = match value_of(&w.selector) {
+                    Some(value) => {
+                        w.window = Some(window);
+                        value
+                    }
+                    // Gone from the window that had it
+                    None if w.window == Some(window) => {
+                        w.window = None;
+                        gone.clone()
+                    }
+                    None => return None,
+                };
+                (untracked(|| w.signal.get()) != value).then(|| (w.signal.clone(), value))
+            })
+            .collect()
+    });
+    set_all(changed);
+}
+
+/// Reset the signals following elements of a closed window (called by the
+/// runtime).
+pub(crate) fn forget(window: WindowHandle) {
+    forget_in(&VISIBILITY, window, false);
+    forget_in(&SIZES, window, None);
+}
+
+fn forget_in<T: Clone + PartialEq + 'static>(key: &'static LocalKey<Watches<T>>, window: WindowHandle, gone: T) {
+    let changed: Vec<_> = key.with(|watches| {
+        watches
+            .borrow_mut()
+            .iter_mut()
+            .filter(|w| w.window == Some(window))
+            .filter_map(|w| {
+                w.window = None;
+                (untracked(|| w.signal.get()) != gone).then(|| (w.signal.clone(), gone.clone()))
+            })
+            .collect()
+    });
+    set_all(changed);
+}
+
+/// Set signals, re-rendering if the render read one of them.
+fn set_all<T: 'static>(changed: Vec<(Signal<T>, T)>) {
+    if changed.is_empty() {
+        return;
+    }
+    let ((), render) = affects_render(|| {
+        for (signal, value) in changed {
+            signal.set(value);
+        }
+    });
+    if render {
+        crate::shell::runtime::request_render();
+    }
+}
@@ -0,0 +1,141 @@
+//! Packaging runtime features as reusable plugins.
+//!
+//! A [`RinchPlugin`] is added to the [`RunConfig`](crate::RunConfig) and is
+//! told when the app starts, before each render, when a window opens and
+//! about every window event. Analytics, crash reporting or a custom URL
+//! scheme can then live in their own crate and be turned on with one line:
+//!
+//! ```ignore
+//! use rinch::plugin::RinchPlugin;
+//! use rinch::AppHandle;
+//!
+//! struct CrashReporter;
+//!
+//! impl RinchPlugin for CrashReporter {
+//!     fn on_startup(&mut self, _app: &AppHandle) {
+//!         let default_hook = std::panic::take_hook();
+//!         std::panic::set_hook(Box::new(move |info| {
+//!             upload_report(info);
+//!             default_hook(info);
+//!         }));
+//!     }
+//! }
+//!
+//! fn main() {
+//!     rinch::run_with_config(app, rinch::RunConfig::new().with_plugin(CrashReporter));
+//! }
+//! ```
+//!
+//! Plugins run on the UI thread, in the order they were added. The DevTools
+//! window is left out.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rinch_core::element::WindowProps;
+use winit::event::WindowEvent;
+
+use crate::tasks::AppHandle;
+use crate::windows::WindowHandle;
+
+/// A bundle of behavior hooked into the runtime.
+///
+/// Every method does nothing by default, so a plugin only implements the
+/// ones it needs.
+pub trait RinchPlugin {
+    /// A name for the plugin in logs.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Called once when the event loop exists, before the first render.
+    ///
+    /// A good place to register protocols with
+    /// [`register_protocol`](crate::assets::register_protocol), install a
+    /// panic hook or hand `app` to a background thread.
+    fn on_startup(&mut self, app: &AppHandle) {
+        let _ = app;
+    }
+
+    /// Called with every event a window receives, before the window
+    /// handles it.
+    fn on_event(&mut self, window: WindowHandle, event: &WindowEvent) {
+        let _ = (window, event);
+    }
+
+    /// Called before each render of the app, including the first.
+    fn on_before_render(&mut self) {}
+
+    /// Called after a window opened, whether the app declared it or opened
+    /// it with [`open_window`](crate::windows::open_window).
+    fn on_window_created(&mut self, window: WindowHandle, props: &WindowProps) {
+        let _ = (window, props);
+    }
+}
+
+/// The plugins added to a [`RunConfig`](crate::RunConfig).
+#[derive(Clone, Default)]
+pub(crate) struct Plugins(Vec<Rc<RefCell<dyn RinchPlugin>>>);
+
+impl Plugins {
+    pub(crate) fn push(&mut self, plugin: impl RinchPlugin + 'static) {
+        self.0.push(Rc::new(RefCell::new(plugin)));
+    }
+}
+
+impl std::fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|plugin| match plugin.try_borrow() {
+                Ok(plugin) => plugin.name().to_string(),
+                Err(_) => String::from("(running)"),
+            }))
+            .finish()
+    }
+}
+
+thread_local! {
+    /// The plugins of the running app.
+    static PLUGINS: RefCell<Plugins> = RefCell::new(Plugins::default());
+}
+
+/// Install the plugins of the app about to run (called by the runtime).
+pub(crate) fn install(plugins: Plugins) {
+    PLUGINS.with(|p| *p.borrow_mut() = plugins);
+}
+
+/// Call `f` on every plugin.
+///
+/// The list isn't borrowed while they run, and a plugin whose hook caused
+/// another hook to run isn't called again from inside itself.
+fn each(mut f: impl FnMut(&mut dyn RinchPlugin)) {
+    let plugins = PLUGINS.with(|p| p.borrow().clone());
+    for plugin in &plugins.0 {
+        if let Ok(mut plugin) = plugin.try_borrow_mut() {
+            f(&mut *plugin);
+        }
+    }
+}
+
+/// Tell plugins the app started (called by the runtime).
+pub(crate) fn startup(app: &AppHandle) {
+    each(|plugin| {
+        tracing::debug!("Starting plugin {}", plugin.name());
+        plugin.on_startup(app);
+    });
+}
+
+/// Tell plugins about a window event (called by the runtime).
+pub(crate) fn event(window: WindowHandle, event: &WindowEvent) {
+    each(|plugin| plugin.on_event(window, event));
+}
+
+/// Tell plugins the app is about to render (called by the runtime).
+pub(crate) fn before_render() {
+    each(|plugin| plugin.on_before_render());
+}
+
+/// Tell plugins a window opened (called by the runtime).
+pub(crate) fn window_created(window: WindowHandle, props: &WindowProps) {
+    each(|plugin| plugin.on_window_created(window, props));
+}
@@ -0,0 +1,305 @@
+//! Battery and power source status.
+//!
+//! [`use_power_status`] gives a signal with the battery level and whether the
+//! computer runs on battery, so apps can throttle animations or warn before
+//! heavy work. [`on_power_source_change`] runs a callback when the computer
+//! is plugged in or unplugged.
+//!
+//! The status is polled every [`POLL_INTERVAL`] on a background thread,
+//! started when first accessed, and handed to the UI thread only when it
+//! changes. Reading it can take a while (on macOS it runs `pmset`), so the
+//! UI thread never waits for it, and nothing runs for apps that don't use it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::power::use_power_status;
+//!
+//! fn app() -> Element {
+//!     let power = use_power_status();
+//!
+//!     rsx! {
+//!         Window { title: "Export",
+//!             p { {if power.get().on_battery() { "On battery: exports will be slower" } else { "Plugged in" }} }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use rinch_core::Signal;
+
+/// How often the power status is read from the OS.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Where the computer draws its power from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PowerSource {
+    /// Plugged in (or a desktop without a battery).
+    Ac,
+    /// Running on battery.
+    Battery,
+    /// The OS doesn't report the power source.
+    #[default]
+    Unknown,
+}
+
+/// Battery level and power source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PowerStatus {
+    /// Where the computer draws its power from.
+    pub source: PowerSource,
+    /// Battery charge in percent, or `None` without a battery.
+    pub battery_percent: Option<u8>,
+    /// Whether the battery is charging.
+    pub charging: bool,
+}
+
+impl PowerStatus {
+    /// Whether the computer runs on battery.
+    pub fn on_battery(&self) -> bool {
+        self.source == PowerSource::Battery
+    }
+
+    /// Whether the computer has a battery.
+    pub fn has_battery(&self) -> bool {
+        self.battery_percent.is_some()
+    }
+}
+
+type SourceCallback = Rc<dyn Fn(PowerSource)>;
+
+thread_local! {
+    /// The power status, read on first access.
+    static POWER_STATUS: RefCell<Option<Signal<PowerStatus>>> = const { RefCell::new(None) };
+    /// Whether the first reading has arrived.
+    static RECEIVED: Cell<bool> = const { Cell::new(false) };
+    static SOURCE_CALLBACKS: RefCell<Vec<SourceCallback>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Get a signal holding the battery and power source status.
+///
+/// The signal is [`PowerStatus::default`] until the first reading arrives,
+/// shortly after the first call, and is then updated when the status changes.
+/// Must be called on the UI thread; every call returns the same signal. Where
+/// the OS doesn't report it, the status stays the default.
+pub fn use_power_status() -> Signal<PowerStatus> {
+    if let Some(signal) = POWER_STATUS.with(|p| p.borrow().clone()) {
+        return signal;
+    }
+
+    let signal = Signal::new(PowerStatus::default());
+    POWER_STATUS.with(|p| *p.borrow_mut() = Some(signal.clone()));
+    let spawned = std::thread::Builder::new()
+        .name("rinch-power".into())
+        .spawn(poll_power_status);
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to watch the power status: {}", e);
+    }
+    signal
+}
+
+/// Run a callback on the UI thread when the computer switches between AC
+/// and battery power.
+///
+/// The callback receives the new source. Must be called on the UI thread.
+pub fn on_power_source_change(callback: impl Fn(PowerSource) + 'static) {
+    use_power_status();
+    SOURCE_CALLBACKS.with(|callbacks| callbacks.borrow_mut().push(Rc::new(callback)));
+}
+
+/// Read the power status every [`POLL_INTERVAL`], sending it to the UI
+/// thread when it changes.
+fn poll_power_status() {
+    let mut sent = None;
+    loop {
+        let status = system_power_status().unwrap_or_default();
+        if sent != Some(status) {
+            sent = Some(status);
+            crate::tasks::app_handle().run_on_ui_thread(move || update_power_status(status));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Update the signal with a new reading, running the source callbacks if the
+/// source changed since the previous one.
+fn update_power_status(status: PowerStatus) {
+    let Some(signal) = POWER_STATUS.with(|p| p.borrow().clone()) else {
+        return;
+    };
+    let first = !RECEIVED.with(|received| received.replace(true));
+    let previous = signal.get();
+    if previous != status {
+        signal.set(status);
+    }
+
+    // The first reading replaces the placeholder, not a real source
+    if !first && previous.source != status.source {
+        tracing::debug!("Power source changed to {:?}", status.source);
+        let callbacks = SOURCE_CALLBACKS.with(|callbacks| callbacks.borrow().clone());
+        for callback in callbacks {
+            callback(status.source);
+        }
+    }
+}
+
+/// Read the status with `GetSystemPowerStatus`.
+#[cfg(target_os = "windows")]
+fn system_power_status() -> Option<PowerStatus> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: SYSTEM_POWER_STATUS is plain data, valid when zeroed.
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    // SAFETY: `status` is a valid SYSTEM_POWER_STATUS to write to.
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+
+    const NO_BATTERY: u8 = 128;
+    const CHARGING: u8 = 8;
+    const UNKNOWN: u8 = 255;
+    let has_battery = status.BatteryFlag != NO_BATTERY && status.BatteryFlag != UNKNOWN;
+    Some(PowerStatus {
+        source: match status.ACLineStatus {
+            0 => PowerSource::Battery,
+            1 => PowerSource::Ac,
+            _ => PowerSource::Unknown,
+        },
+        battery_percent: (has_battery && status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+        charging: has_battery && status.BatteryFlag & CHARGING != 0,
+    })
+}
+
+/// Read the status from the output of `pmset -g batt`, e.g.
+/// `Now drawing from 'Battery Power'` and `-InternalBattery-0 (id=...) 85%; discharging; ...`.
+#[cfg(target_os = "macos")]
+fn system_power_status() -> Option<PowerStatus> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+
+    let source = if output.contains("'AC Power'") {
+        PowerSource::Ac
+    } else if output.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else {
+        PowerSource::Unknown
+    };
+    let battery = output.lines().find(|line| line.contains("InternalBattery"));
+    let battery_percent = battery.and_then(|line| {
+        let (before, _) = line.split_once('%')?;
+        let digits = before.rsplit(|c: char| !c.is_ascii_digit()).next()?;
+        digits.parse::<u8>().ok().map(|percent| percent.min(100))
+    });
+    // "charging", as opposed to "discharging" or "charged"
+    let charging = battery.is_some_and(|line| line.split(';').any(|part| part.trim() == "charging"));
+    Some(PowerStatus {
+        source,
+        battery_percent,
+        charging,
+    })
+}
+
+/// Read the status from the power supplies in `/sys/class/power_supply`.
+#[cfg(target_os = "linux")]
+fn system_power_status() -> Option<PowerStatus> {
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name)).map(|value| value.trim().to_string()).ok()
+    };
+
+    let mut mains_online = None;
+    let mut batteries = Vec::new();
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_deref() {
+            Some("Mains") => {
+                let online = read(&path, "online").as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            // Peripherals such as mice report their batteries with a "Device" scope
+            Some("Battery") if read(&path, "scope").as_deref() != Some("Device") => {
+                let percent = read(&path, "capacity").and_then(|capacity| capacity.parse::<u8>().ok());
+                let status = read(&path, "status").unwrap_or_default();
+                batteries.push((percent, status));
+            }
+            _ => {}
+        }
+    }
+
+    let discharging = batteries.iter().any(|(_, status)| status == "Discharging");
+    let source = match mains_online {
+        Some(true) => PowerSource::Ac,
+        Some(false) => PowerSource::Battery,
+        None if discharging => PowerSource::Battery,
+        None if batteries.is_empty() => PowerSource::Unknown,
+        None => PowerSource::Ac,
+    };
+    let percents: Vec<u8> = batteries.iter().filter_map(|(percent, _)| *percent).collect();
+    let battery_percent = (!percents.is_empty())
+        .then(|| (percents.iter().map(|&p| p as u32).sum::<u32>() / percents.len() as u32).min(100) as u8);
+    Some(PowerStatus {
+        source,
+        battery_percent,
+        charging: batteries.iter().any(|(_, status)| status == "Charging"),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn system_power_status() -> Option<PowerStatus> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(source: PowerSource, battery_percent: Option<u8>) -> PowerStatus {
+        PowerStatus {
+            source,
+            battery_percent,
+            charging: false,
+        }
+    }
+
+    /// Set up the signal without starting the polling thread.
+    fn signal() -> Signal<PowerStatus> {
+        let signal = Signal::new(PowerStatus::default());
+        POWER_STATUS.with(|p| *p.borrow_mut() = Some(signal.clone()));
+        signal
+    }
+
+    #[test]
+    fn readings_update_the_signal() {
+        let signal = signal();
+        update_power_status(status(PowerSource::Battery, Some(80)));
+        assert_eq!(signal.get(), status(PowerSource::Battery, Some(80)));
+        assert!(signal.get().on_battery());
+        assert!(signal.get().has_battery());
+
+        update_power_status(status(PowerSource::Battery, Some(79)));
+        assert_eq!(signal.get().battery_percent, Some(79));
+    }
+
+    #[test]
+    fn source_callbacks_skip_the_first_reading() {
+        signal();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&changes);
+        SOURCE_CALLBACKS.with(|callbacks| {
+            callbacks.borrow_mut().push(Rc::new(move |source| seen.borrow_mut().push(source)));
+        });
+
+        update_power_status(status(PowerSource::Ac, Some(100)));
+        assert!(changes.borrow().is_empty());
+        // A battery level change isn't a source change
+        update_power_status(status(PowerSource::Ac, Some(99)));
+        assert!(changes.borrow().is_empty());
+        update_power_status(status(PowerSource::Battery, Some(99)));
+        update_power_status(status(PowerSource::Ac, Some(99)));
+        assert_eq!(*changes.borrow(), [PowerSource::Battery, PowerSource::Ac]);
+    }
+}
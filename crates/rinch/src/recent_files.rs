@@ -0,0 +1,337 @@
+//! Recently opened files, persisted across application runs.
+//!
+//! The list is stored as plain text (one path per line) in the platform
+//! configuration directory and is shown in the native menu by the
+//! `RecentFilesMenu` element.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::recent_files;
+//!
+//! fn app() -> Element {
+//!     rsx! {
+//!         Fragment {
+//!             AppMenu { native: true,
+//!                 Menu { label: "File",
+//!                     MenuItem { label: "Open...", shortcut: "Cmd+O",
+//!                         onclick: || {
+//!                             let path = std::path::PathBuf::from("notes.txt");
+//!                             recent_files::add(&path);
+//!                         }
+//!                     }
+//!                     RecentFilesMenu {
+//!                         onselect: |path| println!("Reopen {}", path.display())
+//!                     }
+//!                 }
+//!             }
+//!             Window { title: "Editor", div { "..." } }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::shell::RinchEvent;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// Default number of entries kept in the list.
+const DEFAULT_CAPACITY: usize = 10;
+
+/// File name used for the persisted list.
+const STORAGE_FILE_NAME: &str = "recent_files.txt";
+
+/// In-memory recent files list, lazily loaded from disk.
+struct RecentFiles {
+    paths: Vec<PathBuf>,
+    capacity: usize,
+    storage_path: Option<PathBuf>,
+    loaded: bool,
+}
+
+impl RecentFiles {
+    fn new() -> Self {
+        Self {
+            paths: Vec::new(),
+            capacity: DEFAULT_CAPACITY,
+            storage_path: default_storage_path(),
+            loaded: false,
+        }
+    }
+
+    /// Load the list from disk on first access.
+    fn ensure_loaded(&mut self) {
+        if self.loaded {
+            return;
+        }
+        self.loaded = true;
+
+        let Some(storage_path) = &self.storage_path else {
+            return;
+        };
+
+        match std::fs::read_to_string(storage_path) {
+            Ok(contents) => self.load_from(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                tracing::warn!("Failed to read recent files from {:?}: {}", storage_path, e);
+            }
+        }
+    }
+
+    /// Replace the list with the paths in `contents`, one per line.
+    fn load_from(&mut self, contents: &str) {
+        self.paths = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .take(self.capacity)
+            .collect();
+    }
+
+    /// Move `path` to the top of the list, dropping the oldest entry if full.
+    fn push(&mut self, path: PathBuf) {
+        self.paths.retain(|p| p != &path);
+        self.paths.insert(0, path);
+        self.paths.truncate(self.capacity);
+    }
+
+    /// Change the maximum number of entries, dropping the oldest ones.
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.paths.truncate(capacity);
+    }
+
+    /// Write the list to disk.
+    fn save(&self) {
+        let Some(storage_path) = &self.storage_path else {
+            return;
+        };
+
+        if let Some(parent) = storage_path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            tracing::warn!("Failed to create directory {:?}: {}", parent, e);
+            return;
+        }
+
+        let mut contents = String::new();
+        for path in &self.paths {
+            contents.push_str(&path.to_string_lossy());
+            contents.push('\n');
+        }
+
+        if let Err(e) = std::fs::write(storage_path, contents) {
+            tracing::warn!("Failed to write recent files to {:?}: {}", storage_path, e);
+        }
+    }
+}
+
+thread_local! {
+    static RECENT_FILES: RefCell<RecentFiles> = RefCell::new(RecentFiles::new());
+}
+
+/// Apply a change to the list, then persist it and refresh the menu.
+fn modify<F: FnOnce(&mut RecentFiles)>(f: F) {
+    RECENT_FILES.with(|r| {
+        let mut recent = r.borrow_mut();
+        recent.ensure_loaded();
+        f(&mut recent);
+        recent.save();
+    });
+
+    crate::windows::send_event(RinchEvent::RecentFilesChanged);
+}
+
+/// Add a file to the top of the recent files list.
+///
+/// The path is made absolute. If it is already in the list it is moved to
+/// the top, and the oldest entry is dropped once the list is full.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::recent_files;
+///
+/// recent_files::add("/home/user/notes.txt");
+/// ```
+pub fn add(path: impl AsRef<Path>) {
+    let path = absolute(path.as_ref());
+    modify(|recent| recent.push(path));
+}
+
+/// Remove a file from the recent files list.
+///
+/// The path is made absolute, like in [`add`], so a relative path removes
+/// the entry it added. Useful when reopening a recent file fails because it
+/// no longer exists.
+pub fn remove(path: impl AsRef<Path>) {
+    let path = absolute(path.as_ref());
+    modify(|recent| recent.paths.retain(|p| p != &path));
+}
+
+/// The path as stored in the list, or unchanged if it can't be made absolute.
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Clear the recent files list.
+pub fn clear() {
+    modify(|recent| recent.paths.clear());
+}
+
+/// Get the recent files, most recent first.
+pub fn list() -> Vec<PathBuf> {
+    RECENT_FILES.with(|r| {
+        let mut recent = r.borrow_mut();
+        recent.ensure_loaded();
+        recent.paths.clone()
+    })
+}
+
+/// Set the maximum number of entries kept in the list (default: 10).
+pub fn set_capacity(capacity: usize) {
+    modify(|recent| recent.set_capacity(capacity));
+}
+
+/// Override where the list is persisted.
+///
+/// By default the list is stored in `<config dir>/<executable name>/recent_files.txt`.
+/// Call this before the list is first used. Pass `None` to keep the list
+/// in memory only.
+pub fn set_storage_path(path: Option<PathBuf>) {
+    RECENT_FILES.with(|r| {
+        let mut recent = r.borrow_mut();
+        recent.storage_path = path;
+        recent.paths.clear();
+        recent.loaded = false;
+    });
+
+    crate::windows::send_event(RinchEvent::RecentFilesChanged);
+}
+
+/// Default location of the persisted list.
+fn default_storage_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let app_name = exe.file_stem()?.to_string_lossy().into_owned();
+    Some(config_dir()?.join(app_name).join(STORAGE_FILE_NAME))
+}
+
+/// Platform configuration directory.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_memory() -> RecentFiles {
+        RecentFiles {
+            paths: Vec::new(),
+            capacity: DEFAULT_CAPACITY,
+            storage_path: None,
+            loaded: true,
+        }
+    }
+
+    fn paths(list: &[&str]) -> Vec<PathBuf> {
+        list.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn push_puts_newest_first() {
+        let mut recent = in_memory();
+        recent.push("/a".into());
+        recent.push("/b".into());
+        recent.push("/c".into());
+        assert_eq!(recent.paths, paths(&["/c", "/b", "/a"]));
+    }
+
+    #[test]
+    fn push_moves_existing_path_to_top() {
+        let mut recent = in_memory();
+        recent.push("/a".into());
+        recent.push("/b".into());
+        recent.push("/c".into());
+        recent.push("/a".into());
+        assert_eq!(recent.paths, paths(&["/a", "/c", "/b"]));
+    }
+
+    #[test]
+    fn push_drops_oldest_when_full() {
+        let mut recent = in_memory();
+        recent.set_capacity(2);
+        recent.push("/a".into());
+        recent.push("/b".into());
+        recent.push("/c".into());
+        assert_eq!(recent.paths, paths(&["/c", "/b"]));
+    }
+
+    #[test]
+    fn lowering_capacity_keeps_newest() {
+        let mut recent = in_memory();
+        for path in ["/a", "/b", "/c", "/d"] {
+            recent.push(path.into());
+        }
+        recent.set_capacity(2);
+        assert_eq!(recent.paths, paths(&["/d", "/c"]));
+    }
+
+    #[test]
+    fn load_skips_blank_lines_and_respects_capacity() {
+        let mut recent = in_memory();
+        recent.set_capacity(2);
+        recent.load_from("/a\n\n  /b  \n/c\n");
+        assert_eq!(recent.paths, paths(&["/a", "/b"]));
+    }
+
+    #[test]
+    fn relative_paths_are_removed_like_they_were_added() {
+        let cwd = std::env::current_dir().unwrap();
+        set_storage_path(None);
+        add("notes.txt");
+        add("todo.txt");
+        assert_eq!(list(), [cwd.join("todo.txt"), cwd.join("notes.txt")]);
+
+        remove("notes.txt");
+        assert_eq!(list(), [cwd.join("todo.txt")]);
+    }
+
+    #[test]
+    fn saved_list_loads_back_in_order() {
+        let dir = std::env::temp_dir().join(format!("rinch-recent-{}", std::process::id()));
+        let file = dir.join(STORAGE_FILE_NAME);
+
+        let mut recent = in_memory();
+        recent.storage_path = Some(file.clone());
+        recent.push("/a".into());
+        recent.push("/b".into());
+        recent.save();
+
+        let mut loaded = in_memory();
+        loaded.storage_path = Some(file);
+        loaded.loaded = false;
+        loaded.ensure_loaded();
+        assert_eq!(loaded.paths, paths(&["/b", "/a"]));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,187 @@
+//! Scrolling elements from code and watching their scroll positions.
+//!
+//! Elements are picked with a CSS selector, usually the `#id` of the
+//! scroll container. [`scroll_to`] and [`scroll_into_view`] act on the
+//! window handling the current event; [`WindowHandle`] has the same
+//! methods for a specific window. Scrolling waits for the re-render the
+//! calling handler caused, so it sees the content the handler added.
+//!
+//! [`use_scroll_position`] gives a signal that follows where a container is
+//! scrolled, for keeping a chat log pinned to its newest message:
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::scroll::{scroll_to, use_scroll_position, ScrollPosition};
+//!
+//! fn app() -> Element {
+//!     let messages = use_signal(Vec::<String>::new);
+//!     let log = use_scroll_position("#log");
+//!
+//!     let add = messages.clone();
+//!     rsx! {
+//!         Window { title: "Chat",
+//!             div { id: "log", style: "overflow-y: auto; height: 300px",
+//!                 {messages.get().iter().map(|m| rsx! { p { {m.clone()} } })}
+//!             }
+//!             button {
+//!                 onclick: move || {
+//!                     // Follow new messages unless the user scrolled up to read
+//!                     let pinned = log.get().is_at_bottom();
+//!                     add.update(|m| m.push("Hello".into()));
+//!                     if pinned {
+//!                         scroll_to("#log", ScrollPosition::Bottom);
+//!                     }
+//!                 },
+//!                 "Send"
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Scroll positions are kept when a window re-renders, for elements that
+//! stay in the same place in the document.
+
+use std::cell::RefCell;
+
+use rinch_core::{untracked, Signal};
+
+use crate::windows::{current_window, WindowHandle};
+
+/// Where to scroll an element to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollPosition {
+    /// The start of the content.
+    Top,
+    /// The end of the content.
+    Bottom,
+    /// An offset from the start, in CSS pixels. Offsets past the end scroll
+    /// to the end.
+    Offset {
+        x: f64,
+        y: f64,
+    },
+}
+
+/// Where an element is scrolled to and how far it can scroll, in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollState {
+    /// Horizontal scroll offset.
+    pub x: f64,
+    /// Vertical scroll offset.
+    pub y: f64,
+    /// The largest horizontal offset; zero if the content fits.
+    pub max_x: f64,
+    /// The largest vertical offset; zero if the content fits.
+    pub max_y: f64,
+}
+
+impl ScrollState {
+    /// Whether the start of the content shows.
+    pub fn is_at_top(&self) -> bool {
+        self.y <= 1.0
+    }
+
+    /// Whether the end of the content shows. Also `true` when the content
+    /// fits without scrolling.
+    pub fn is_at_bottom(&self) -> bool {
+        self.y >= self.max_y - 1.0
+    }
+}
+
+/// A signal updated with the scroll state of the element matching a
+/// selector.
+struct Watch {
+    /// The window to look in, or `None` for every window.
+    window: Option<WindowHandle>,
+    selector: String,
+    state: Signal<ScrollState>,
+}
+
+thread_local! {
+    /// Signals following scroll positions.
+    static WATCHES: RefCell<Vec<Watch>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Scroll the element matching `selector` in the current window.
+///
+/// Shorthand for [`WindowHandle::scroll_to`] on [`current_window`].
+pub fn scroll_to(selector: impl Into<String>, position: ScrollPosition) {
+    if let Some(window) = current_window() {
+        window.scroll_to(selector, position);
+    }
+}
+
+/// Scroll the containers around the element matching `selector` in the
+/// current window, and the window itself, until the element shows.
+///
+/// Shorthand for [`WindowHandle::scroll_into_view`] on [`current_window`].
+pub fn scroll_into_view(selector: impl Into<String>) {
+    if let Some(window) = current_window() {
+        window.scroll_into_view(selector);
+    }
+}
+
+/// Get a signal that follows the scroll state of the element matching
+/// `selector`, in whichever window has one.
+///
+/// Every call with the same selector returns the same signal. It is
+/// updated when the element scrolls or its content changes size, and
+/// reading it while rendering re-renders the app then. Use
+/// [`WindowHandle::scroll_position`] when several windows have a matching
+/// element.
+pub fn use_scroll_position(selector: &str) -> Signal<ScrollState> {
+    watch(None, selector)
+}
+
+/// Get the signal for `selector` in `window` (or every window), adding it
+/// if it doesn't exist.
+pub(crate) fn watch(window: Option<WindowHandle>, selector: &str) -> Signal<ScrollState> {
+    WATCHES.with(|watches| {
+        let mut watches = watches.borrow_mut();
+        if let Some(watch) = watches.iter().find(|w| w.window == window && w.selector == selector) {
+            return watch.state.clone();
+        }
+        let state = Signal::new(ScrollState::default());
+        watches.push(Watch {
+            window,
+            selector: selector.to_string(),
+            state: state.clone(),
+        });
+        state
+    })
+}
+
+/// Whether any scroll position is watched in `window`.
+pub(crate) fn is_watched(window: WindowHandle) -> bool {
+    WATCHES.with(|watches| watches.borrow().iter().any(|w| w.window.is_none() || w.window == Some(window)))
+}
+
+/// Update the signals watching `window` with the states `state_of` finds
+/// for their selectors (called by the runtime after the window scrolls or
+/// re-renders).
+pub(crate) fn update(window: WindowHandle, state_of: impl Fn(&str) -> Option<ScrollState>) {
+    let changed: Vec<_> = WATCHES.with(|watches| {
+        watches
+            .borrow()
+            .iter()
+            .filter(|w| w.window.is_none() || w.window == Some(window))
+            .filter_map(|w| {
+                let state = state_of(&w.selector)?;
+                (untracked(|| w.state.get()) != state).then(|| (w.state.clone(), state))
+            })
+            .collect()
+    });
+    if changed.is_empty() {
+        return;
+    }
+    for (signal, state) in changed {
+        signal.set(state);
+    }
+    crate::shell::runtime::request_render();
+}
+
+/// Forget the signals watching a closed window (called by the runtime).
+pub(crate) fn forget(window: WindowHandle) {
+    WATCHES.with(|watches| watches.borrow_mut().retain(|w| w.window != Some(window)));
+}
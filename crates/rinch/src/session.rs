@@ -0,0 +1,276 @@
+//! Restoring windows and app state from the last run.
+//!
+//! Session restore is opt-in: call [`enable`] at the start of `main`. The
+//! position and size of every window with a `key` prop are then saved when
+//! the app exits, along with the values of [`use_session_state`] signals,
+//! and windows with the same keys open where they were on the next launch.
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::session::{self, use_session_state};
+//!
+//! fn app() -> Element {
+//!     let tab = use_session_state("tab", || String::from("inbox"));
+//!     rsx! {
+//!         Window { title: "Mail", key: "main",
+//!             p { {format!("Showing {}", tab.get())} }
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     session::enable();
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! Windows declared in `rsx!` open again by themselves. Windows opened with
+//! [`open_window`](crate::windows::open_window) are only placed where they
+//! were when the app opens them again with the same key;
+//! [`previous_windows`] lists the keys that were open, for reopening them.
+//!
+//! The session is stored in `<config dir>/<executable name>/session.json`
+//! (see [`set_path`]). It holds the windows that were open when the app
+//! quit, including the last one if closing it quit the app.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use rinch_core::element::WindowProps;
+use rinch_core::{untracked, Signal};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The file the session is stored in, in the app's config directory.
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// Where a window was and how big it was, in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionWindow {
+    /// The window's `key` prop.
+    pub key: String,
+    /// X position of the window's outer frame.
+    pub x: i32,
+    /// Y position of the window's outer frame.
+    pub y: i32,
+    /// Width of the content area.
+    pub width: u32,
+    /// Height of the content area.
+    pub height: u32,
+    pub maximized: bool,
+}
+
+/// The contents of the session file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionFile {
+    #[serde(default)]
+    windows: Vec<SessionWindow>,
+    #[serde(default)]
+    state: Map<String, Value>,
+}
+
+/// A [`use_session_state`] signal with a way to serialize its value.
+struct StateEntry {
+    signal: Rc<dyn Any>,
+    serialize: Box<dyn Fn() -> Option<Value>>,
+}
+
+thread_local! {
+    /// Whether [`enable`] was called.
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    /// Where the session is stored, once set with [`set_path`].
+    static PATH: RefCell<Option<Option<PathBuf>>> = const { RefCell::new(None) };
+    /// The session saved by the last run.
+    static PREVIOUS: RefCell<SessionFile> = RefCell::new(SessionFile::default());
+    /// Keyed windows open in this run, in the order they opened.
+    static OPEN_WINDOWS: RefCell<Vec<SessionWindow>> = const { RefCell::new(Vec::new()) };
+    /// Session state signals, by key.
+    static STATE: RefCell<HashMap<String, StateEntry>> = RefCell::new(HashMap::new());
+}
+
+/// Restore the last session and save this one when the app exits.
+///
+/// Call this at the start of `main`, before [`run`](crate::run), so the
+/// first windows open where they were.
+pub fn enable() {
+    if ENABLED.replace(true) {
+        return;
+    }
+    if let Some(path) = path() {
+        match read_file(&path) {
+            Ok(session) => PREVIOUS.with(|previous| *previous.borrow_mut() = session),
+            Err(e) => tracing::warn!("Could not restore the session from {}: {}", path.display(), e),
+        }
+    }
+    crate::shell::on_exit(|| {
+        if let Err(e) = save() {
+            tracing::error!("Failed to save the session: {}", e);
+        }
+    });
+}
+
+/// Whether session restore was enabled with [`enable`].
+pub fn is_enabled() -> bool {
+    ENABLED.get()
+}
+
+/// Store the session at `path` instead of the config directory, or not at
+/// all with `None`. Call this before [`enable`].
+pub fn set_path(path: Option<PathBuf>) {
+    PATH.with(|p| *p.borrow_mut() = Some(path));
+}
+
+/// The windows that were open when the app last quit, in the order they
+/// were opened.
+pub fn previous_windows() -> Vec<SessionWindow> {
+    PREVIOUS.with(|previous| previous.borrow().windows.clone())
+}
+
+/// Get a signal whose value is kept from one run of the app to the next.
+///
+/// The signal starts out with the value it had when the app last quit, or
+/// `initial()` if there is none (or session restore isn't enabled, or the
+/// saved value no longer deserializes as `T`). Every call with the same key
+/// returns the same signal; the key must always be used with the same type.
+pub fn use_session_state<T>(key: &str, initial: impl FnOnce() -> T) -> Signal<T>
+where
+    T: Serialize + DeserializeOwned + Clone + 'static,
+{
+    if let Some(signal) = STATE.with(|state| state.borrow().get(key).map(|entry| entry.signal.clone())) {
+        return signal
+            .downcast_ref::<Signal<T>>()
+            .unwrap_or_else(|| panic!("session state `{}` used with different types", key))
+            .clone();
+    }
+
+    let saved = PREVIOUS.with(|previous| previous.borrow().state.get(key).cloned());
+    let value = saved
+        .and_then(|value| match serde_json::from_value(value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Discarding saved session state `{}`: {}", key, e);
+                None
+            }
+        })
+        .unwrap_or_else(initial);
+    let signal = Signal::new(value);
+
+    let watched = signal.clone();
+    let entry = StateEntry {
+        signal: Rc::new(signal.clone()),
+        serialize: Box::new(move || serde_json::to_value(untracked(|| watched.get())).ok()),
+    };
+    STATE.with(|state| state.borrow_mut().insert(key.to_string(), entry));
+    signal
+}
+
+/// Save the session now, rather than when the app exits.
+///
+/// Does nothing unless session restore is enabled.
+pub fn save() -> std::io::Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    // State not used in this run is kept for the next
+    let mut state = PREVIOUS.with(|previous| previous.borrow().state.clone());
+    STATE.with(|entries| {
+        for (key, entry) in entries.borrow().iter() {
+            if let Some(value) = (entry.serialize)() {
+                state.insert(key.clone(), value);
+            }
+        }
+    });
+    let session = SessionFile {
+        windows: OPEN_WINDOWS.with(|windows| windows.borrow().clone()),
+        state,
+    };
+    write_file(&path, &session)
+}
+
+/// Put a keyed window where it was in the last session (called by the
+/// runtime before creating it).
+///
+/// `on_screen` tells whether a position is on a connected monitor; windows
+/// last seen elsewhere keep their own position. Returns whether the window
+/// was maximized.
+pub(crate) fn restore(props: &mut WindowProps, on_screen: impl Fn(i32, i32) -> bool) -> bool {
+    if !is_enabled() {
+        return false;
+    }
+    let Some(key) = &props.key else {
+        return false;
+    };
+    let Some(saved) = PREVIOUS.with(|previous| previous.borrow().windows.iter().find(|w| w.key == *key).cloned())
+    else {
+        return false;
+    };
+    props.width = saved.width;
+    props.height = saved.height;
+    if on_screen(saved.x, saved.y) {
+        props.x = Some(saved.x);
+        props.y = Some(saved.y);
+    }
+    saved.maximized
+}
+
+/// Record where a keyed window is (called by the runtime when it opens,
+/// moves or resizes).
+pub(crate) fn record(window: SessionWindow) {
+    if !is_enabled() {
+        return;
+    }
+    OPEN_WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        match windows.iter_mut().find(|w| w.key == window.key) {
+            // Keep the size to restore the window to when it is unmaximized
+            Some(existing) if window.maximized => existing.maximized = true,
+            Some(existing) => *existing = window,
+            None => windows.push(window),
+        }
+    });
+}
+
+/// Forget a keyed window that was closed while others stay open (called by
+/// the runtime).
+pub(crate) fn forget(key: &str) {
+    OPEN_WINDOWS.with(|windows| windows.borrow_mut().retain(|w| w.key != key));
+}
+
+/// Where the session is stored: the path given to [`set_path`], or else
+/// `<config dir>/<executable name>/session.json`.
+fn path() -> Option<PathBuf> {
+    if let Some(path) = PATH.with(|p| p.borrow().clone()) {
+        return path;
+    }
+    let exe = std::env::current_exe().ok()?;
+    let app_name = exe.file_stem()?.to_string_lossy().into_owned();
+    Some(crate::recent_files::config_dir()?.join(app_name).join(SESSION_FILE_NAME))
+}
+
+/// Read a session file. A missing file is an empty session.
+fn read_file(path: &Path) -> std::io::Result<SessionFile> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text).map_err(std::io::Error::other),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SessionFile::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write a session file, replacing the old one once the new one is written.
+fn write_file(path: &Path, session: &SessionFile) -> std::io::Result<()> {
+    let text = serde_json::to_string_pretty(session).map_err(std::io::Error::other)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, text)?;
+    std::fs::rename(&temp, path)
+}
@@ -0,0 +1,405 @@
+//! App settings saved between runs.
+//!
+//! Derive [`Settings`] on a struct to keep it in a TOML or JSON file in the
+//! platform's config directory. [`use_settings`] loads the file the first
+//! time it is called and returns a signal per field; setting one saves the
+//! file shortly after ([`SAVE_DELAY`]), so a burst of changes is written
+//! once. Pending changes are saved when the app exits.
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::settings::{use_settings, Settings};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Settings, Serialize, Deserialize, Clone, Default)]
+//! struct Preferences {
+//!     font_size: u32,
+//!     dark_mode: bool,
+//! }
+//!
+//! fn app() -> Element {
+//!     let prefs = use_settings::<Preferences>();
+//!     let dark_mode = prefs.dark_mode.clone();
+//!     rsx! {
+//!         Window { title: "Editor",
+//!             p { {format!("Font size: {}", prefs.font_size.get())} }
+//!             button { onclick: move || dark_mode.update(|on| *on = !*on), "Toggle dark mode" }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! # Files
+//!
+//! Settings are stored in `<config dir>/<executable name>/<name>.toml` (or
+//! `.json`), where the name is set with `#[settings(name = "...")]` and
+//! defaults to the struct's name in snake case. [`set_path`] stores them
+//! elsewhere, or only in memory.
+//!
+//! Fields missing from the file keep their `Default` values, so new fields
+//! can be added without a migration. A file that can't be read is logged
+//! and the defaults are used.
+//!
+//! # Migrations
+//!
+//! Files record the schema version given with `#[settings(version = N)]`.
+//! When a file was written by an older version, the `migrate` function is
+//! given the version it has and its contents as a [`Value`] to bring up to
+//! date before they are read:
+//!
+//! ```ignore
+//! #[derive(Settings, Serialize, Deserialize, Clone, Default)]
+//! #[settings(version = 2, migrate = migrate_preferences)]
+//! struct Preferences {
+//!     font_size: u32,
+//! }
+//!
+//! fn migrate_preferences(from_version: u32, value: &mut Value) {
+//!     // Version 1 called the field `text_size`
+//!     if from_version < 2 {
+//!         if let Some(size) = value.as_object_mut().and_then(|fields| fields.remove("text_size")) {
+//!             value["font_size"] = size;
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use rinch_core::{untracked, Effect};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::shell::TimerId;
+
+pub use rinch_macros::Settings;
+pub use serde_json::Value;
+
+/// How long after the last change settings are saved.
+pub const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// The key holding the schema version in settings files.
+const VERSION_KEY: &str = "settings_version";
+
+/// The file format of settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// The file extension, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// A struct stored as app settings.
+///
+/// Implement it with `#[derive(Settings)]`, which also generates the
+/// [`Fields`](Settings::Fields) struct.
+pub trait Settings: Serialize + DeserializeOwned + Default + Clone + 'static {
+    /// A signal per field.
+    type Fields: Clone + 'static;
+
+    /// The file name, without the extension.
+    const NAME: &'static str;
+    /// The file format.
+    const FORMAT: Format;
+    /// The schema version written to the file.
+    const VERSION: u32;
+
+    /// Bring the contents of a file written with an older schema version up
+    /// to date. Does nothing by default.
+    fn migrate(from_version: u32, value: &mut Value) {
+        let _ = (from_version, value);
+    }
+
+    /// Create the signals holding these settings.
+    fn into_fields(self) -> Self::Fields;
+
+    /// Read the settings from their signals.
+    fn from_fields(fields: &Self::Fields) -> Self;
+
+    /// Set the signals to these settings.
+    fn set_fields(self, fields: &Self::Fields);
+}
+
+/// An error reading or writing a settings file.
+#[derive(Debug)]
+pub enum SettingsError {
+    /// Reading or writing the file failed.
+    Io(std::io::Error),
+    /// The file isn't valid TOML or JSON, or doesn't match the settings.
+    Parse(String),
+    /// The settings couldn't be written in the file's format.
+    Serialize(String),
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Io(e) => write!(f, "settings file error: {}", e),
+            SettingsError::Parse(msg) => write!(f, "invalid settings file: {}", msg),
+            SettingsError::Serialize(msg) => write!(f, "failed to serialize settings: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        SettingsError::Io(e)
+    }
+}
+
+/// Result type for settings operations.
+pub type SettingsResult<T> = Result<T, SettingsError>;
+
+/// Loaded settings of one type.
+struct Store<T: Settings> {
+    fields: T::Fields,
+    /// Where the settings are saved; `None` keeps them in memory.
+    path: RefCell<Option<PathBuf>>,
+    /// The timer of the pending save.
+    pending: Cell<Option<TimerId>>,
+}
+
+thread_local! {
+    /// Loaded settings, by type.
+    static STORES: RefCell<HashMap<TypeId, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+    /// Paths given with `set_path` before the settings were loaded.
+    static PATHS: RefCell<HashMap<TypeId, Option<PathBuf>>> = RefCell::new(HashMap::new());
+    /// Saves to run when the app exits, by type.
+    static PENDING_SAVES: RefCell<HashMap<TypeId, Box<dyn Fn()>>> = RefCell::new(HashMap::new());
+    /// Whether saving pending changes on exit has been set up.
+    static SAVE_ON_EXIT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Get a signal per field of the settings, loading them on the first call.
+///
+/// Setting a signal saves the settings after [`SAVE_DELAY`]. Every call
+/// returns the same signals.
+pub fn use_settings<T: Settings>() -> T::Fields {
+    store::<T>().fields.clone()
+}
+
+/// Get a copy of the settings.
+///
+/// Reading it while rendering re-renders when any field changes.
+pub fn get<T: Settings>() -> T {
+    T::from_fields(&store::<T>().fields)
+}
+
+/// Replace all settings at once.
+pub fn set<T: Settings>(settings: T) {
+    settings.set_fields(&store::<T>().fields);
+}
+
+/// Set every field back to its default.
+pub fn reset<T: Settings>() {
+    set(T::default());
+}
+
+/// Save the settings now, rather than after [`SAVE_DELAY`].
+///
+/// Does nothing for settings kept in memory.
+pub fn save<T: Settings>() -> SettingsResult<()> {
+    let store = store::<T>();
+    if let Some(timer) = store.pending.take() {
+        timer.cancel();
+    }
+    PENDING_SAVES.with(|saves| saves.borrow_mut().remove(&TypeId::of::<T>()));
+    let Some(path) = store.path.borrow().clone() else {
+        return Ok(());
+    };
+    let settings = untracked(|| T::from_fields(&store.fields));
+    write_file(&path, &settings)
+}
+
+/// Store the settings at `path` instead of the config directory, or only in
+/// memory with `None`.
+///
+/// Call this before the settings are first used; once loaded, the settings
+/// are saved to the new path but not read from it.
+pub fn set_path<T: Settings>(path: Option<PathBuf>) {
+    let loaded = STORES.with(|stores| stores.borrow().get(&TypeId::of::<T>()).cloned());
+    match loaded {
+        Some(store) => {
+            let store = store.downcast::<Store<T>>().expect("settings store of the wrong type");
+            *store.path.borrow_mut() = path;
+        }
+        None => PATHS.with(|paths| {
+            paths.borrow_mut().insert(TypeId::of::<T>(), path);
+        }),
+    }
+}
+
+/// Where the settings are stored, or `None` if they are kept in memory.
+pub fn path<T: Settings>() -> Option<PathBuf> {
+    store::<T>().path.borrow().clone()
+}
+
+/// The loaded settings of a type, loading them first if needed.
+fn store<T: Settings>() -> Rc<Store<T>> {
+    let id = TypeId::of::<T>();
+    if let Some(store) = STORES.with(|stores| stores.borrow().get(&id).cloned()) {
+        return store.downcast::<Store<T>>().expect("settings store of the wrong type");
+    }
+
+    let path = PATHS
+        .with(|paths| paths.borrow_mut().remove(&id))
+        .unwrap_or_else(default_path::<T>);
+    let settings = match &path {
+        Some(path) => read_file::<T>(path).unwrap_or_else(|e| {
+            tracing::warn!("Using default settings, could not load {}: {}", path.display(), e);
+            T::default()
+        }),
+        None => T::default(),
+    };
+    let store = Rc::new(Store::<T> {
+        fields: settings.into_fields(),
+        path: RefCell::new(path),
+        pending: Cell::new(None),
+    });
+    STORES.with(|stores| stores.borrow_mut().insert(id, store.clone()));
+
+    // Save a while after any field changes
+    let watched = store.fields.clone();
+    let mut initial = true;
+    Effect::new(move || {
+        let _ = T::from_fields(&watched);
+        if initial {
+            initial = false;
+            return;
+        }
+        untracked(schedule_save::<T>);
+    });
+
+    store
+}
+
+/// Save the settings after [`SAVE_DELAY`], replacing an earlier pending save.
+fn schedule_save<T: Settings>() {
+    let store = store::<T>();
+    if store.path.borrow().is_none() {
+        return;
+    }
+    if let Some(timer) = store.pending.take() {
+        timer.cancel();
+    }
+    store.pending.set(Some(crate::shell::schedule(SAVE_DELAY, save_logged::<T>)));
+
+    PENDING_SAVES.with(|saves| saves.borrow_mut().insert(TypeId::of::<T>(), Box::new(save_logged::<T>)));
+    if !SAVE_ON_EXIT.replace(true) {
+        crate::shell::on_exit(|| {
+            let saves: Vec<_> = PENDING_SAVES.with(|saves| saves.borrow_mut().drain().collect());
+            for (_, save) in saves {
+                save();
+            }
+        });
+    }
+}
+
+/// Save the settings, logging failures.
+fn save_logged<T: Settings>() {
+    if let Err(e) = save::<T>() {
+        tracing::error!("Failed to save {} settings: {}", T::NAME, e);
+    }
+}
+
+/// `<config dir>/<executable name>/<name>.<extension>`.
+fn default_path<T: Settings>() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let app_name = exe.file_stem()?.to_string_lossy().into_owned();
+    let file_name = format!("{}.{}", T::NAME, T::FORMAT.extension());
+    Some(crate::recent_files::config_dir()?.join(app_name).join(file_name))
+}
+
+/// Read settings from a file, migrating them and filling in missing fields
+/// with defaults. A missing file gives the defaults.
+fn read_file<T: Settings>(path: &Path) -> SettingsResult<T> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut value = match T::FORMAT {
+        Format::Toml => {
+            let table: toml::Table = toml::from_str(&text).map_err(|e| SettingsError::Parse(e.to_string()))?;
+            serde_json::to_value(table).map_err(|e| SettingsError::Parse(e.to_string()))?
+        }
+        Format::Json => serde_json::from_str(&text).map_err(|e| SettingsError::Parse(e.to_string()))?,
+    };
+
+    let version = value
+        .as_object_mut()
+        .and_then(|fields| fields.remove(VERSION_KEY))
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0) as u32;
+    if version < T::VERSION {
+        tracing::info!("Migrating {} settings from version {} to {}", T::NAME, version, T::VERSION);
+        T::migrate(version, &mut value);
+    } else if version > T::VERSION {
+        tracing::warn!("{} was written by a newer version ({}) of the settings", path.display(), version);
+    }
+
+    let mut merged = serde_json::to_value(T::default()).map_err(|e| SettingsError::Serialize(e.to_string()))?;
+    merge(&mut merged, value);
+    serde_json::from_value(merged).map_err(|e| SettingsError::Parse(e.to_string()))
+}
+
+/// Overlay `value` on `base`, merging objects key by key.
+fn merge(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, value) in value {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// The contents of a settings file: the schema version, then the settings.
+#[derive(Serialize)]
+struct File<'a, T> {
+    settings_version: u32,
+    #[serde(flatten)]
+    settings: &'a T,
+}
+
+/// Write settings to a file, replacing it once the new contents are
+/// written so a failed save doesn't lose the old ones.
+fn write_file<T: Settings>(path: &Path, settings: &T) -> SettingsResult<()> {
+    let file = File {
+        settings_version: T::VERSION,
+        settings,
+    };
+    let text = match T::FORMAT {
+        Format::Toml => toml::to_string_pretty(&file).map_err(|e| SettingsError::Serialize(e.to_string()))?,
+        Format::Json => serde_json::to_string_pretty(&file).map_err(|e| SettingsError::Serialize(e.to_string()))?,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension(format!("{}.tmp", T::FORMAT.extension()));
+    std::fs::write(&temp, text)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
@@ -0,0 +1,483 @@
+//! Accessibility trees for screen readers (AccessKit).
+//!
+//! Each window has an `accesskit_winit` adapter. Once assistive technology
+//! asks for it, the window's tree is built from the document, and rebuilt
+//! after re-renders, focus changes and edits. AccessKit works out what
+//! changed and tells the platform.
+//!
+//! Elements map to roles by their `role` attribute, or else by tag (`button`,
+//! `a`, `input` by `type`, `h1`..`h6`, lists, landmarks, ...). Names come from
+//! `aria-labelledby`, the `aria-label`, `alt` or `title` attribute, or from
+//! the text inside buttons, links and headings. Other ARIA attributes set
+//! states such as expanded, selected or checked. Text outside named elements
+//! becomes `Label` nodes. Node IDs are blitz node IDs, with the window as an
+//! extra root above the document.
+
+use accesskit::{
+    Action, DefaultActionVerb, HasPopup, Live, NodeBuilder, NodeId, Orientation, Rect, Role, Toggled,
+    Tree, TreeUpdate,
+};
+use blitz_dom::{BaseDocument, ElementData, Node};
+
+use super::focus::{attr, has_attr};
+
+/// ID of the node representing the window, above the document's root.
+const WINDOW_NODE: NodeId = NodeId(u64::MAX);
+
+/// Elements that aren't shown and have nothing to expose.
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "template", "title", "meta", "link"];
+
+/// Attribute holding the ID of an element's `onclick` handler.
+const CLICK_HANDLER_ATTR: &str = "data-rid";
+
+/// Build the full accessibility tree of a document.
+///
+/// `scale` converts layout (CSS) pixels to the physical pixels AccessKit
+/// expects.
+pub(crate) fn build_tree(doc: &BaseDocument, title: &str, scale: f64) -> TreeUpdate {
+    let mut nodes = Vec::new();
+    let root = doc.root_node().id;
+    let children = build_node(doc, root, scale, &mut nodes);
+
+    let mut window = NodeBuilder::new(Role::Window);
+    window.set_name(title);
+    window.set_children(children);
+    nodes.push((WINDOW_NODE, window.build()));
+
+    let mut tree = Tree::new(WINDOW_NODE);
+    tree.toolkit_name = Some("rinch".to_string());
+    tree.toolkit_version = Some(env!("CARGO_PKG_VERSION").to_string());
+
+    let focus = doc
+        .get_focussed_node_id()
+        .filter(|&id| nodes.iter().any(|(node_id, _)| *node_id == node_id_of(id)))
+        .map_or(WINDOW_NODE, node_id_of);
+    TreeUpdate {
+        nodes,
+        tree: Some(tree),
+        focus,
+    }
+}
+
+/// The blitz node an accessibility node stands for, if any.
+pub(crate) fn dom_node(id: NodeId) -> Option<usize> {
+    (id != WINDOW_NODE).then_some(id.0 as usize)
+}
+
+fn node_id_of(dom_id: usize) -> NodeId {
+    NodeId(dom_id as u64)
+}
+
+/// Add the accessibility nodes of a DOM node and its descendants to `nodes`,
+/// returning the IDs of the top-level ones. Elements without a role of their
+/// own are flattened into their parent.
+fn build_node(doc: &BaseDocument, id: usize, scale: f64, nodes: &mut Vec<(NodeId, accesskit::Node)>) -> Vec<NodeId> {
+    let Some(node) = doc.get_node(id) else {
+        return Vec::new();
+    };
+
+    if node.is_text_node() {
+        let text = node.text_content();
+        let text = text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        let mut label = NodeBuilder::new(Role::Label);
+        label.set_name(text);
+        label.set_bounds(bounds(node, scale));
+        nodes.push((node_id_of(id), label.build()));
+        return vec![node_id_of(id)];
+    }
+
+    let Some(element) = node.element_data() else {
+        // The document itself
+        return node
+            .children
+            .iter()
+            .flat_map(|&child| build_node(doc, child, scale, nodes))
+            .collect();
+    };
+    let tag = element.name.local.as_ref();
+    if SKIPPED_ELEMENTS.contains(&tag) || has_attr(element, "hidden") || attr(element, "aria-hidden") == Some("true") {
+        return Vec::new();
+    }
+
+    let role = aria_role(element).unwrap_or_else(|| role_of(element));
+    let name = labelled_by(doc, element).or_else(|| name_of(node, element, role));
+
+    // Buttons, links and headings are named by their text, so it isn't
+    // repeated as children
+    let children: Vec<NodeId> = if name_from_content(role) {
+        Vec::new()
+    } else {
+        node.children
+            .iter()
+            .flat_map(|&child| build_node(doc, child, scale, nodes))
+            .collect()
+    };
+
+    let focusable = super::focus::can_focus(node, element);
+    let clickable = has_attr(element, CLICK_HANDLER_ATTR);
+    if role == Role::GenericContainer && name.is_none() && !focusable && !clickable {
+        return children;
+    }
+
+    let mut builder = NodeBuilder::new(role);
+    builder.set_bounds(bounds(node, scale));
+    builder.set_children(children);
+    // Described by `aria-describedby`, or by a title that isn't the name
+    // (it's shown as a tooltip)
+    let description = described_by(doc, element).or_else(|| attr(element, "aria-description").map(str::to_string));
+    if let Some(description) = description {
+        builder.set_description(description);
+    } else if let Some(title) = attr(element, "title")
+        && name.as_deref() != Some(title)
+    {
+        builder.set_description(title);
+    }
+    if let Some(name) = name {
+        builder.set_name(name);
+    }
+    if has_attr(element, "disabled") || aria_bool(element, "aria-disabled") == Some(true) {
+        builder.set_disabled();
+    }
+    if has_attr(element, "readonly") || aria_bool(element, "aria-readonly") == Some(true) {
+        builder.set_read_only();
+    }
+    if has_attr(element, "required") || aria_bool(element, "aria-required") == Some(true) {
+        builder.set_required();
+    }
+    if let Some(placeholder) = attr(element, "placeholder") {
+        builder.set_placeholder(placeholder);
+    }
+    if let Some(level) = attr(element, "aria-level")
+        .and_then(|level| level.trim().parse().ok())
+        .or_else(|| heading_level(tag))
+    {
+        builder.set_level(level);
+    }
+    if role == Role::Link
+        && let Some(href) = attr(element, "href")
+    {
+        builder.set_url(href);
+    }
+
+    let toggled = element
+        .checkbox_input_checked()
+        .map(|checked| if checked { Toggled::True } else { Toggled::False })
+        .or_else(|| aria_toggled(element, "aria-checked"))
+        .or_else(|| aria_toggled(element, "aria-pressed"));
+    if let Some(toggled) = toggled {
+        builder.set_toggled(toggled);
+        builder.set_default_action_verb(match toggled {
+            Toggled::True => DefaultActionVerb::Uncheck,
+            _ => DefaultActionVerb::Check,
+        });
+    } else if clickable || matches!(role, Role::Button | Role::Link) {
+        builder.set_default_action_verb(DefaultActionVerb::Click);
+    }
+    set_aria_states(&mut builder, element);
+    if let Some(input) = element.text_input_data() {
+        builder.set_value(input.editor.text().to_string());
+    }
+    if focusable {
+        builder.add_action(Action::Focus);
+    }
+    if clickable || focusable {
+        builder.add_action(Action::Default);
+    }
+
+    nodes.push((node_id_of(id), builder.build()));
+    vec![node_id_of(id)]
+}
+
+/// Set the states given by ARIA attributes that have no HTML equivalent.
+fn set_aria_states(builder: &mut NodeBuilder, element: &ElementData) {
+    if let Some(expanded) = aria_bool(element, "aria-expanded") {
+        builder.set_expanded(expanded);
+    }
+    if let Some(selected) = aria_bool(element, "aria-selected") {
+        builder.set_selected(selected);
+    }
+    if aria_bool(element, "aria-modal") == Some(true) {
+        builder.set_modal();
+    }
+    if aria_bool(element, "aria-multiselectable") == Some(true) {
+        builder.set_multiselectable();
+    }
+    let has_popup = match attr(element, "aria-haspopup") {
+        Some("true" | "menu") => Some(HasPopup::Menu),
+        Some("listbox") => Some(HasPopup::Listbox),
+        Some("tree") => Some(HasPopup::Tree),
+        Some("grid") => Some(HasPopup::Grid),
+        Some("dialog") => Some(HasPopup::Dialog),
+        _ => None,
+    };
+    if let Some(has_popup) = has_popup {
+        builder.set_has_popup(has_popup);
+    }
+    let live = match attr(element, "aria-live") {
+        Some("polite") => Some(Live::Polite),
+        Some("assertive") => Some(Live::Assertive),
+        Some("off") => Some(Live::Off),
+        _ => None,
+    };
+    if let Some(live) = live {
+        builder.set_live(live);
+    }
+    match attr(element, "aria-orientation") {
+        Some("horizontal") => builder.set_orientation(Orientation::Horizontal),
+        Some("vertical") => builder.set_orientation(Orientation::Vertical),
+        _ => {}
+    }
+    let number = |name| attr(element, name).and_then(|value| value.trim().parse::<f64>().ok());
+    if let Some(value) = number("aria-valuenow") {
+        builder.set_numeric_value(value);
+    }
+    if let Some(min) = number("aria-valuemin") {
+        builder.set_min_numeric_value(min);
+    }
+    if let Some(max) = number("aria-valuemax") {
+        builder.set_max_numeric_value(max);
+    }
+    if let Some(text) = attr(element, "aria-valuetext") {
+        builder.set_value(text);
+    }
+    if let Some(description) = attr(element, "aria-roledescription") {
+        builder.set_role_description(description);
+    }
+    if let Some(shortcut) = attr(element, "aria-keyshortcuts") {
+        builder.set_keyboard_shortcut(shortcut);
+    }
+}
+
+/// The role given by an element's `role` attribute: the first one AccessKit
+/// knows, of a list of fallbacks.
+fn aria_role(element: &ElementData) -> Option<Role> {
+    attr(element, "role")?.split_whitespace().find_map(|role| {
+        Some(match role {
+            "alert" => Role::Alert,
+            "alertdialog" => Role::AlertDialog,
+            "application" => Role::Application,
+            "article" => Role::Article,
+            "banner" => Role::Banner,
+            "blockquote" => Role::Blockquote,
+            "button" => Role::Button,
+            "caption" => Role::Caption,
+            "cell" | "gridcell" => Role::Cell,
+            "checkbox" => Role::CheckBox,
+            "code" => Role::Code,
+            "columnheader" => Role::ColumnHeader,
+            "combobox" => Role::ComboBox,
+            "complementary" => Role::Complementary,
+            "contentinfo" => Role::ContentInfo,
+            "definition" => Role::Definition,
+            "deletion" => Role::ContentDeletion,
+            "dialog" => Role::Dialog,
+            "document" => Role::Document,
+            "emphasis" => Role::Emphasis,
+            "feed" => Role::Feed,
+            "figure" => Role::Figure,
+            "form" => Role::Form,
+            "generic" | "none" | "presentation" => Role::GenericContainer,
+            "grid" => Role::Grid,
+            "group" => Role::Group,
+            "heading" => Role::Heading,
+            "img" => Role::Image,
+            "insertion" => Role::ContentInsertion,
+            "link" => Role::Link,
+            "list" => Role::List,
+            "listbox" => Role::ListBox,
+            "listitem" => Role::ListItem,
+            "log" => Role::Log,
+            "main" => Role::Main,
+            "marquee" => Role::Marquee,
+            "math" => Role::Math,
+            "menu" => Role::Menu,
+            "menubar" => Role::MenuBar,
+            "menuitem" => Role::MenuItem,
+            "menuitemcheckbox" => Role::MenuItemCheckBox,
+            "menuitemradio" => Role::MenuItemRadio,
+            "meter" => Role::Meter,
+            "navigation" => Role::Navigation,
+            "note" => Role::Note,
+            "option" => Role::ListBoxOption,
+            "paragraph" => Role::Paragraph,
+            "progressbar" => Role::ProgressIndicator,
+            "radio" => Role::RadioButton,
+            "radiogroup" => Role::RadioGroup,
+            "region" => Role::Region,
+            "row" => Role::Row,
+            "rowgroup" => Role::RowGroup,
+            "rowheader" => Role::RowHeader,
+            "scrollbar" => Role::ScrollBar,
+            "search" => Role::Search,
+            "searchbox" => Role::SearchInput,
+            "slider" => Role::Slider,
+            "spinbutton" => Role::SpinButton,
+            "status" => Role::Status,
+            "strong" => Role::Strong,
+            "switch" => Role::Switch,
+            "tab" => Role::Tab,
+            "table" => Role::Table,
+            "tablist" => Role::TabList,
+            "tabpanel" => Role::TabPanel,
+            "term" => Role::Term,
+            "textbox" if aria_bool(element, "aria-multiline") == Some(true) => Role::MultilineTextInput,
+            "textbox" => Role::TextInput,
+            "time" => Role::Time,
+            "timer" => Role::Timer,
+            "toolbar" => Role::Toolbar,
+            "tooltip" => Role::Tooltip,
+            "tree" => Role::Tree,
+            "treegrid" => Role::TreeGrid,
+            "treeitem" => Role::TreeItem,
+            _ => return None,
+        })
+    })
+}
+
+/// The role of an element from its tag (and `type`, for inputs).
+fn role_of(element: &ElementData) -> Role {
+    match element.name.local.as_ref() {
+        "button" => Role::Button,
+        "a" if has_attr(element, "href") => Role::Link,
+        "input" => match attr(element, "type").unwrap_or("text") {
+            "checkbox" => Role::CheckBox,
+            "radio" => Role::RadioButton,
+            "range" => Role::Slider,
+            "button" | "submit" | "reset" => Role::Button,
+            "password" => Role::PasswordInput,
+            "search" => Role::SearchInput,
+            "email" => Role::EmailInput,
+            "number" => Role::NumberInput,
+            "tel" => Role::PhoneNumberInput,
+            "url" => Role::UrlInput,
+            "date" => Role::DateInput,
+            "color" => Role::ColorWell,
+            _ => Role::TextInput,
+        },
+        "textarea" => Role::MultilineTextInput,
+        "select" => Role::ComboBox,
+        "option" => Role::ListBoxOption,
+        "img" => Role::Image,
+        "label" => Role::Label,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => Role::Heading,
+        "p" => Role::Paragraph,
+        "ul" | "ol" => Role::List,
+        "li" => Role::ListItem,
+        "table" => Role::Table,
+        "tr" => Role::Row,
+        "td" => Role::Cell,
+        "th" => Role::ColumnHeader,
+        "nav" => Role::Navigation,
+        "main" => Role::Main,
+        "header" => Role::Banner,
+        "footer" => Role::ContentInfo,
+        "aside" => Role::Complementary,
+        "article" => Role::Article,
+        "section" => Role::Section,
+        "form" => Role::Form,
+        "dialog" => Role::Dialog,
+        "progress" => Role::ProgressIndicator,
+        "meter" => Role::Meter,
+        "pre" => Role::Pre,
+        "code" => Role::Code,
+        "blockquote" => Role::Blockquote,
+        "figure" => Role::Figure,
+        "figcaption" => Role::FigureCaption,
+        "details" => Role::Details,
+        "summary" => Role::DisclosureTriangle,
+        _ => Role::GenericContainer,
+    }
+}
+
+/// Roles named by their text content.
+fn name_from_content(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button | Role::Link | Role::Heading | Role::ListBoxOption | Role::DisclosureTriangle
+    )
+}
+
+/// The accessible name of an element.
+fn name_of(node: &Node, element: &ElementData, role: Role) -> Option<String> {
+    let explicit = attr(element, "aria-label")
+        .or_else(|| if role == Role::Image { attr(element, "alt") } else { None })
+        .or_else(|| attr(element, "title"));
+    if let Some(name) = explicit {
+        return Some(name.to_string());
+    }
+    if role == Role::Button && element.name.local.as_ref() == "input" {
+        return attr(element, "value").map(str::to_string);
+    }
+    if name_from_content(role) {
+        let text = node.text_content();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        return (!text.is_empty()).then_some(text);
+    }
+    None
+}
+
+/// The text of the elements an ID reference attribute (such as
+/// `aria-labelledby`) points to.
+fn referenced_text(doc: &BaseDocument, element: &ElementData, name: &str) -> Option<String> {
+    let text: Vec<String> = attr(element, name)?
+        .split_whitespace()
+        .filter_map(|id| {
+            let selector = format!(r#"[id="{}"]"#, id.replace('\\', "\\\\").replace('"', "\\\""));
+            let node_id = doc.query_selector(&selector).ok()??;
+            let text = doc.get_node(node_id)?.text_content();
+            Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .filter(|text| !text.is_empty())
+        .collect();
+    (!text.is_empty()).then(|| text.join(" "))
+}
+
+/// The name given by `aria-labelledby`.
+fn labelled_by(doc: &BaseDocument, element: &ElementData) -> Option<String> {
+    referenced_text(doc, element, "aria-labelledby")
+}
+
+/// The description given by `aria-describedby`.
+fn described_by(doc: &BaseDocument, element: &ElementData) -> Option<String> {
+    referenced_text(doc, element, "aria-describedby")
+}
+
+/// A `true` / `false` ARIA attribute.
+fn aria_bool(element: &ElementData, name: &str) -> Option<bool> {
+    match attr(element, name)? {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// A `true` / `false` / `mixed` ARIA attribute.
+fn aria_toggled(element: &ElementData, name: &str) -> Option<Toggled> {
+    match attr(element, name)? {
+        "true" => Some(Toggled::True),
+        "false" => Some(Toggled::False),
+        "mixed" => Some(Toggled::Mixed),
+        _ => None,
+    }
+}
+
+fn heading_level(tag: &str) -> Option<usize> {
+    tag.strip_prefix('h')
+        .and_then(|level| level.parse::<usize>().ok())
+        .filter(|level| (1..=6).contains(level))
+}
+
+/// A node's border box in physical pixels.
+fn bounds(node: &Node, scale: f64) -> Rect {
+    let origin = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    Rect::new(
+        origin.x as f64 * scale,
+        origin.y as f64 * scale,
+        (origin.x + size.width) as f64 * scale,
+        (origin.y + size.height) as f64 * scale,
+    )
+}
@@ -0,0 +1,155 @@
+//! Attributes and text bound to signals.
+//!
+//! `rsx!` binds attributes written as `name: {signal.get()}` with
+//! [`bind_attribute`](rinch_core::events::bind_attribute), marking the
+//! element with `data-rbind-<name>="<id>"`, and text written as
+//! `{signal.get()}` with [`bind_text`](rinch_core::events::bind_text),
+//! writing it as `<!--rbind:<id>-->text<!--/rbind-->` and listing the ID in
+//! the parent element's `data-rbind-text` attribute. Comments rather than a
+//! wrapper element keep the text a plain child of its parent for CSS
+//! selectors and table parsing. When the signal changes, the attribute or
+//! text node is set in the document, and in the HTML the window rebuilds its
+//! document from, without rendering the app again.
+
+use blitz_dom::{ns, BaseDocument, LocalName, NodeData, QualName};
+use rinch_core::events::{html_escape_string, BindingPatch};
+
+use super::focus;
+
+/// Set a bound attribute or text in a document.
+///
+/// Returns `false` if bound text has no text node to update (its value was
+/// empty when rendered); the app should then render again instead.
+pub(crate) fn apply(doc: &mut BaseDocument, patch: &BindingPatch) -> bool {
+    let Some(name) = patch.attribute else {
+        return apply_text(doc, patch);
+    };
+    let marker = format!("[data-rbind-{}=\"{}\"]", name, patch.id);
+    let Ok(Some(node_id)) = doc.query_selector(&marker) else {
+        // The element is gone, or belongs to another window
+        return true;
+    };
+    let name = QualName::new(None, ns!(), LocalName::from(name));
+    doc.mutate().set_attribute(node_id, name, &patch.value);
+    true
+}
+
+/// Set bound text in a document.
+///
+/// The document doesn't keep the text of comments, so the text is found by
+/// its position in the parent's `data-rbind-text` list: the text of the
+/// `n`th binding follows the parent's `2n`th comment child.
+fn apply_text(doc: &mut BaseDocument, patch: &BindingPatch) -> bool {
+    let id = patch.id.to_string();
+    let Ok(Some(parent_id)) = doc.query_selector(&format!("[data-rbind-text~=\"{}\"]", id)) else {
+        return true;
+    };
+    let Some(parent) = doc.get_node(parent_id) else {
+        return true;
+    };
+    let Some(index) = parent
+        .element_data()
+        .and_then(|element| focus::attr(element, "data-rbind-text"))
+        .and_then(|ids| ids.split_whitespace().position(|other| other == id))
+    else {
+        return true;
+    };
+    let children = &parent.children;
+    let start = children
+        .iter()
+        .enumerate()
+        .filter(|&(_, &child)| doc.get_node(child).is_some_and(|child| matches!(child.data, NodeData::Comment)))
+        .nth(2 * index)
+        .map(|(position, _)| position);
+    let text_node = start
+        .and_then(|start| children.get(start + 1).copied())
+        .filter(|&child| doc.get_node(child).is_some_and(|child| child.is_text_node()));
+    let Some(text_node) = text_node else {
+        return false;
+    };
+    doc.mutate().set_node_text(text_node, &patch.value);
+    true
+}
+
+/// Set a bound attribute or text in the HTML a document was built from.
+///
+/// `rsx!` writes an attribute right before its marker, as
+/// `name="value" data-rbind-name="id"`, and text between
+/// `<!--rbind:id-->` and `<!--/rbind-->`.
+pub(crate) fn patch_content(content: &mut String, patch: &BindingPatch) {
+    let Some(name) = patch.attribute else {
+        let start = format!("<!--rbind:{}-->", patch.id);
+        let Some(text_start) = content.find(&start).map(|position| position + start.len()) else {
+            return;
+        };
+        if let Some(text_len) = content[text_start..].find("<!--/rbind-->") {
+            content.replace_range(text_start..text_start + text_len, &html_escape_string(&patch.value));
+        }
+        return;
+    };
+    let Some(marker_start) = content.find(&format!(" data-rbind-{}=\"{}\"", name, patch.id)) else {
+        return;
+    };
+    let open = format!(" {}=\"", name);
+    let Some(value_start) = content[..marker_start].rfind(&open).map(|start| start + open.len()) else {
+        return;
+    };
+    // The marker follows the closing quote of the value
+    let value_end = marker_start - 1;
+    if value_start <= value_end {
+        content.replace_range(value_start..value_end, &html_escape_string(&patch.value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blitz_dom::{Document, DocumentConfig};
+    use blitz_html::HtmlDocument;
+    use rinch_core::events::BindingId;
+
+    fn text(id: usize, value: &str) -> BindingPatch {
+        BindingPatch { id: BindingId(id), attribute: None, value: value.to_string() }
+    }
+
+    #[test]
+    fn patch_content_sets_bound_text() {
+        let mut content = String::from(
+            "<p data-rbind-text=\"1 12\"><!--rbind:1-->a<!--/rbind--> and <!--rbind:12-->b<!--/rbind--></p>",
+        );
+        patch_content(&mut content, &text(12, "<c>"));
+        patch_content(&mut content, &text(1, ""));
+        assert_eq!(
+            content,
+            "<p data-rbind-text=\"1 12\"><!--rbind:1--><!--/rbind--> and <!--rbind:12-->&lt;c&gt;<!--/rbind--></p>"
+        );
+        // Bindings of other windows are left alone
+        let before = content.clone();
+        patch_content(&mut content, &text(7, "d"));
+        assert_eq!(content, before);
+    }
+
+    #[test]
+    fn patch_content_sets_bound_attributes() {
+        let mut content = String::from("<div class=\"off\" data-rbind-class=\"3\" title=\"off\"></div>");
+        let patch = BindingPatch { id: BindingId(3), attribute: Some("class"), value: String::from("on \"x\"") };
+        patch_content(&mut content, &patch);
+        assert_eq!(content, "<div class=\"on &quot;x&quot;\" data-rbind-class=\"3\" title=\"off\"></div>");
+    }
+
+    #[test]
+    fn apply_sets_the_text_after_the_bindings_comment() {
+        let html = "<table><tr><td data-rbind-text=\"4 5\">Count: <!--rbind:4-->1<!--/rbind-->, \
+                    <!--rbind:5--><!--/rbind--></td></tr></table>";
+        let mut doc = HtmlDocument::from_html(html, DocumentConfig::default());
+        let mut doc = doc.inner_mut();
+
+        assert!(apply(&mut doc, &text(4, "2")));
+        let td = doc.query_selector("td").unwrap().unwrap();
+        assert_eq!(doc.get_node(td).unwrap().text_content(), "Count: 2, ");
+        // Text that was empty has no text node, so the app renders again
+        assert!(!apply(&mut doc, &text(5, "3")));
+        // Text of another window
+        assert!(apply(&mut doc, &text(6, "3")));
+    }
+}
@@ -0,0 +1,161 @@
+//! The caret of the focused text input.
+//!
+//! A window draws the insertion point of its focused `input` or `textarea`
+//! itself, over the content and beneath the [overlay](super::overlay), in
+//! the element's `caret-color` (or `color`). The caret blinks while the
+//! window has focus: it shows for [`BLINK_INTERVAL`], hides for as long, and
+//! so on, starting shown again whenever it moves, the text changes or
+//! another input is focused, so it never disappears while the user types or
+//! moves it with the arrow keys. After [`BLINK_TIMEOUT`] without any of
+//! these it stays shown, as native fields do, so an idle window isn't
+//! redrawn forever, and it doesn't blink at all while the user prefers
+//! reduced motion. There is no caret while text is selected or the window
+//! doesn't have focus.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::color::{parse_color, Srgb};
+use peniko::kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+use style::properties::LonghandId;
+
+use rinch_core::untracked;
+
+use super::direction;
+
+/// How long the caret shows, and then hides, while blinking.
+pub(crate) const BLINK_INTERVAL: Duration = Duration::from_millis(530);
+
+/// How long the caret blinks after it last moved.
+pub(crate) const BLINK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The width of the caret, in CSS pixels.
+const WIDTH: f32 = 1.5;
+
+/// What restarts the blink: the focused input, its selection and the
+/// length of its text.
+type State = (usize, Range<usize>, usize);
+
+/// The blinking of a window's caret.
+pub(crate) struct Caret {
+    state: Option<State>,
+    /// When the caret last started showing.
+    since: Instant,
+    window_focused: bool,
+    /// Whether the caret was shown when the window was last drawn.
+    drawn: bool,
+}
+
+/// A caret to draw, in CSS pixels from the top left of the window.
+pub(crate) struct Bar {
+    rect: Rect,
+    color: Color,
+}
+
+impl Caret {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: None,
+            since: Instant::now(),
+            window_focused: false,
+            drawn: false,
+        }
+    }
+
+    /// Follow whether the window has focus.
+    pub(crate) fn set_window_focused(&mut self, focused: bool) {
+        self.window_focused = focused;
+    }
+
+    /// Follow the focused input of `doc`, and get the caret to draw now, if
+    /// it shows.
+    pub(crate) fn update(&mut self, doc: &BaseDocument, now: Instant) -> Option<Bar> {
+        let state = self.window_focused.then(|| state(doc)).flatten();
+        if state != self.state {
+            self.state = state;
+            self.since = now;
+        }
+        let bar = self.is_shown(now).then(|| bar(doc)).flatten();
+        self.drawn = bar.is_some();
+        bar
+    }
+
+    /// When the window has to be drawn next for the caret to blink: `now`
+    /// or earlier if it's due, or `None` if the caret isn't blinking.
+    pub(crate) fn next_blink(&self, now: Instant) -> Option<Instant> {
+        self.state.as_ref()?;
+        if self.is_shown(now) != self.drawn {
+            return Some(now);
+        }
+        let elapsed = now.duration_since(self.since);
+        if elapsed >= BLINK_TIMEOUT || reduced_motion() {
+            return None;
+        }
+        let intervals = elapsed.as_millis() / BLINK_INTERVAL.as_millis() + 1;
+        Some(self.since + BLINK_INTERVAL * intervals as u32)
+    }
+
+    fn is_shown(&self, now: Instant) -> bool {
+        if self.state.is_none() {
+            return false;
+        }
+        let elapsed = now.duration_since(self.since);
+        elapsed >= BLINK_TIMEOUT
+            || reduced_motion()
+            || (elapsed.as_millis() / BLINK_INTERVAL.as_millis()).is_multiple_of(2)
+    }
+}
+
+impl Bar {
+    /// Draw the caret into a scene of the window at `scale`.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, scale: f64) {
+        scene.fill(Fill::NonZero, Affine::scale(scale), self.color, None, &self.rect);
+    }
+}
+
+fn reduced_motion() -> bool {
+    untracked(|| crate::theme::use_prefers_reduced_motion().get())
+}
+
+/// The focused text input, its selection and the length of its text.
+fn state(doc: &BaseDocument) -> Option<State> {
+    let node_id = doc.get_focussed_node_id()?;
+    let editor = &doc.get_node(node_id)?.element_data()?.text_input_data()?.editor;
+    Some((node_id, editor.raw_selection().text_range(), editor.text().to_string().len()))
+}
+
+/// The caret of the focused text input, if it has one with nothing selected.
+fn bar(doc: &BaseDocument) -> Option<Bar> {
+    let node_id = doc.get_focussed_node_id()?;
+    let node = doc.get_node(node_id)?;
+    let editor = &node.element_data()?.text_input_data()?.editor;
+    if !editor.raw_selection().text_range().is_empty() {
+        return None;
+    }
+    let caret = editor.cursor_geometry(WIDTH)?;
+    // The editor's text starts at the input's content box
+    let layout = node.final_layout;
+    let origin = node.absolute_position(
+        layout.border.left + layout.padding.left,
+        layout.border.top + layout.padding.top,
+    );
+    let scroll = doc.viewport_scroll();
+    let (x, y) = (f64::from(origin.x) - scroll.x, f64::from(origin.y) - scroll.y);
+    Some(Bar {
+        rect: Rect::new(x + caret.x0, y + caret.y0, x + caret.x1, y + caret.y1),
+        color: color(doc, node_id)?,
+    })
+}
+
+/// The element's `caret-color`, or its `color` for `auto`.
+fn color(doc: &BaseDocument, node_id: usize) -> Option<Color> {
+    let caret = direction::computed(doc, node_id, LonghandId::CaretColor)?;
+    let value = match caret.as_str() {
+        "auto" => direction::computed(doc, node_id, LonghandId::Color)?,
+        _ => caret,
+    };
+    Some(parse_color(&value).ok()?.to_alpha_color::<Srgb>())
+}
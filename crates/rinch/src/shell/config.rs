@@ -0,0 +1,172 @@
+//! Application-wide options for [`run_with_config`](super::run_with_config).
+
+use std::cell::Cell;
+use std::path::PathBuf;
+
+use crate::plugin::{Plugins, RinchPlugin};
+
+/// The default widget stylesheet, see [`RunConfig::default_stylesheet`].
+const DEFAULT_STYLESHEET: &str = include_str!("default.css");
+
+/// Options for running an application.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::RunConfig;
+///
+/// fn main() {
+///     let config = RunConfig::new().default_stylesheet(false);
+///     rinch::run_with_config(app, config);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    default_stylesheet: bool,
+    open_external_links: bool,
+    log_filter: Option<String>,
+    log_file: Option<PathBuf>,
+    assets_dir: Option<PathBuf>,
+    devtools: bool,
+    exit_on_last_window_closed: bool,
+    plugins: Plugins,
+}
+
+impl RunConfig {
+    /// Create a config with the default options.
+    pub fn new() -> Self {
+        Self {
+            default_stylesheet: true,
+            open_external_links: true,
+            log_filter: None,
+            log_file: None,
+            assets_dir: None,
+            devtools: true,
+            exit_on_last_window_closed: true,
+            plugins: Plugins::default(),
+        }
+    }
+
+    /// Include rinch's base stylesheet in every window (default `true`).
+    ///
+    /// The stylesheet gives buttons, inputs, scrollbars and focus rings a
+    /// consistent look, using the `rinch::theme` tokens when a theme is
+    /// active. Its rules have no specificity, so any application style
+    /// overrides them.
+    pub fn default_stylesheet(mut self, enabled: bool) -> Self {
+        self.default_stylesheet = enabled;
+        self
+    }
+
+    /// Open clicked links to web pages (`http`, `https` and `mailto` URLs
+    /// in an `a` element's `href`) in the default browser or mail client
+    /// (default `true`).
+    ///
+    /// Disable this to handle such links in `onclick` handlers instead.
+    pub fn open_external_links(mut self, enabled: bool) -> Self {
+        self.open_external_links = enabled;
+        self
+    }
+
+    /// Set which log records are kept, as comma-separated directives such
+    /// as `"warn,my_app=debug"` (default `info`).
+    ///
+    /// The `RUST_LOG` environment variable overrides this. See
+    /// [`logging`](crate::logging) for the syntax and for changing the filter
+    /// while the app runs.
+    pub fn log_filter(mut self, directives: impl Into<String>) -> Self {
+        self.log_filter = Some(directives.into());
+        self
+    }
+
+    /// Also append log records to a file, without terminal colors.
+    pub fn log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Resolve relative asset URLs against `dir` instead of the current
+    /// working directory, see [`set_assets_dir`](crate::assets::set_assets_dir).
+    pub fn assets_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = Some(dir.into());
+        self
+    }
+
+    /// Let F12 open the DevTools window (default `true`).
+    ///
+    /// Turn this off in release builds that shouldn't show the app's
+    /// internals.
+    pub fn devtools(mut self, enabled: bool) -> Self {
+        self.devtools = enabled;
+        self
+    }
+
+    /// Exit when the last window closes (default `true`).
+    ///
+    /// Turn this off for apps that live in the system tray; they exit with
+    /// [`AppHandle::exit`](crate::AppHandle::exit).
+    pub fn exit_on_last_window_closed(mut self, enabled: bool) -> Self {
+        self.exit_on_last_window_closed = enabled;
+        self
+    }
+
+    /// Add a [plugin](crate::plugin), called after the ones added before.
+    pub fn with_plugin(mut self, plugin: impl RinchPlugin + 'static) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    /// Install the log subscriber, unless the app installed its own.
+    pub(crate) fn init_logging(&self) {
+        crate::logging::init(self.log_filter.as_deref(), self.log_file.as_deref());
+    }
+
+    /// Apply the options to the current thread before the event loop starts.
+    pub(crate) fn apply(&self) {
+        STYLESHEET_ENABLED.with(|enabled| enabled.set(self.default_stylesheet));
+        OPEN_EXTERNAL_LINKS.with(|enabled| enabled.set(self.open_external_links));
+        DEVTOOLS_ENABLED.with(|enabled| enabled.set(self.devtools));
+        EXIT_ON_LAST_WINDOW_CLOSED.with(|enabled| enabled.set(self.exit_on_last_window_closed));
+        if let Some(dir) = &self.assets_dir {
+            crate::assets::set_assets_dir(dir);
+        }
+        crate::plugin::install(self.plugins.clone());
+    }
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STYLESHEET_ENABLED: Cell<bool> = const { Cell::new(true) };
+    static OPEN_EXTERNAL_LINKS: Cell<bool> = const { Cell::new(true) };
+    static DEVTOOLS_ENABLED: Cell<bool> = const { Cell::new(true) };
+    static EXIT_ON_LAST_WINDOW_CLOSED: Cell<bool> = const { Cell::new(true) };
+}
+
+/// The default widget stylesheet, unless disabled in the [`RunConfig`].
+pub(crate) fn default_stylesheet() -> Option<&'static str> {
+    STYLESHEET_ENABLED
+        .with(Cell::get)
+        .then_some(DEFAULT_STYLESHEET)
+}
+
+/// Whether clicked links to web pages are opened, see
+/// [`RunConfig::open_external_links`].
+pub(crate) fn open_external_links() -> bool {
+    OPEN_EXTERNAL_LINKS.with(Cell::get)
+}
+
+/// Whether F12 opens the DevTools window, see [`RunConfig::devtools`].
+pub(crate) fn devtools_enabled() -> bool {
+    DEVTOOLS_ENABLED.with(Cell::get)
+}
+
+/// Whether the app exits when its last window closes, see
+/// [`RunConfig::exit_on_last_window_closed`].
+pub(crate) fn exit_on_last_window_closed() -> bool {
+    EXIT_ON_LAST_WINDOW_CLOSED.with(Cell::get)
+}
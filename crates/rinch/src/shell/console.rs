@@ -0,0 +1,228 @@
+//! Capturing log output for the DevTools console.
+//!
+//! [`ConsoleLayer`] is a `tracing` layer that keeps the latest log records in
+//! memory, so they can be read in the DevTools window of apps started without
+//! a terminal (such as a double-clicked Windows build). `run` installs it with
+//! the terminal output (see [`logging`](crate::logging)), so it captures the
+//! records the log filter keeps; apps that set up their own subscriber can
+//! add it:
+//!
+//! ```ignore
+//! use rinch::shell::ConsoleLayer;
+//! use tracing_subscriber::prelude::*;
+//!
+//! tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .with(ConsoleLayer)
+//!     .init();
+//! ```
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use rinch_core::events::html_escape_string;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::devtools::{on_click, DevToolsAction};
+use super::runtime::RinchEvent;
+
+/// How many records are kept; older ones are dropped.
+const MAX_RECORDS: usize = 1000;
+
+/// Attribute of the input editing the log filter; Enter applies its text.
+pub(crate) const LOG_FILTER_ATTR: &str = "data-devtools-log-filter";
+
+/// Records shown in the console, oldest first.
+static RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+
+/// When the first layer was installed; records show the time since then.
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Whether a [`ConsoleLayer`] was installed (or has captured a record).
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the DevTools window is open and wants to hear about new records.
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the runtime was told about new records it hasn't shown yet.
+static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Set while telling the runtime about new records, so logging from the
+    /// event loop proxy can't re-enter it.
+    static NOTIFYING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A captured log record.
+#[derive(Debug, Clone)]
+struct LogRecord {
+    time: Instant,
+    level: Level,
+    target: String,
+    /// The message, followed by the other fields as `name=value`.
+    message: String,
+}
+
+/// A `tracing` layer capturing log records for the DevTools console.
+///
+/// It captures every record its subscriber enables; filter it (with
+/// `Layer::with_filter`) to capture fewer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleLayer;
+
+impl<S: Subscriber> Layer<S> for ConsoleLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        INSTALLED.store(true, Ordering::Relaxed);
+        START.get_or_init(Instant::now);
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            time: Instant::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        };
+        {
+            let mut records = RECORDS.lock().unwrap();
+            if records.len() == MAX_RECORDS {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+
+        // Debug records show up with the next refresh, so the debug logging
+        // of the refresh itself doesn't cause another one
+        if *metadata.level() <= Level::INFO && WATCHING.load(Ordering::Relaxed) {
+            notify();
+        }
+    }
+}
+
+/// Collects the `message` field and the other fields of an event.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Ask the runtime to refresh the DevTools window, once until it does.
+fn notify() {
+    if NOTIFYING.with(|notifying| notifying.replace(true)) {
+        return;
+    }
+    if !NOTIFIED.swap(true, Ordering::AcqRel)
+        && !crate::tasks::app_handle().dispatch(RinchEvent::DevToolsLogsChanged)
+    {
+        NOTIFIED.store(false, Ordering::Release);
+    }
+    NOTIFYING.with(|notifying| notifying.set(false));
+}
+
+/// Record that rinch installed a subscriber with a [`ConsoleLayer`].
+pub(crate) fn mark_installed() {
+    START.get_or_init(Instant::now);
+    INSTALLED.store(true, Ordering::Relaxed);
+}
+
+/// Start or stop telling the runtime about new records, as the DevTools
+/// window opens and closes.
+pub(crate) fn set_watching(watching: bool) {
+    WATCHING.store(watching, Ordering::Relaxed);
+}
+
+/// Mark the records as shown, so the next one notifies the runtime again.
+pub(crate) fn mark_shown() {
+    NOTIFIED.store(false, Ordering::Release);
+}
+
+/// Render the captured records at `min_level` or more severe as HTML, newest
+/// last, with buttons choosing the level and an input editing the log
+/// filter, followed by `filter_error` if setting it failed.
+pub(crate) fn console_html(min_level: Level, filter_error: Option<&str>) -> String {
+    let mut html = String::from(r#"<div style="display: flex; gap: 4px; margin-bottom: 8px;">"#);
+    for level in [Level::TRACE, Level::DEBUG, Level::INFO, Level::WARN, Level::ERROR] {
+        let active = if level == min_level {
+            "background: #007acc; color: #ffffff;"
+        } else {
+            "background: #2d2d2d; color: #d4d4d4;"
+        };
+        let _ = write!(
+            html,
+            r#"<button {} style="padding: 2px 8px; border: 1px solid #3c3c3c; border-radius: 3px; {}">{}</button>"#,
+            on_click(DevToolsAction::SetLogLevel(level)),
+            active,
+            level.as_str()
+        );
+    }
+    html.push_str("</div>");
+
+    if let Ok(filter) = crate::logging::filter() {
+        let _ = write!(
+            html,
+            r#"<input class="log-filter" {}="" value="{}" placeholder="Log filter, e.g. info,my_app=debug">"#,
+            LOG_FILTER_ATTR,
+            html_escape_string(&filter)
+        );
+        if let Some(error) = filter_error {
+            let _ = write!(html, r#"<div class="hook-error">{}</div>"#, html_escape_string(error));
+        }
+    }
+
+    if !INSTALLED.load(Ordering::Relaxed) {
+        html.push_str(
+            r#"<p style="color: #808080;">Logs aren't captured: the app installed its own tracing subscriber. Add <code>rinch::shell::ConsoleLayer</code> to it.</p>"#,
+        );
+        return html;
+    }
+
+    let records = RECORDS.lock().unwrap();
+    let shown: Vec<&LogRecord> = records.iter().filter(|record| record.level <= min_level).collect();
+    if shown.is_empty() {
+        html.push_str(r#"<p style="color: #808080;">No log records.</p>"#);
+        return html;
+    }
+    let start = START.get().copied();
+    for record in shown {
+        let color = match record.level {
+            Level::ERROR => "#f48771",
+            Level::WARN => "#cca700",
+            Level::INFO => "#4ec9b0",
+            _ => "#808080",
+        };
+        let _ = write!(
+            html,
+            r#"<div style="padding: 2px 0; border-bottom: 1px solid #2d2d2d; white-space: pre-wrap;"><span style="color: #808080;">{:.3}</span> <span style="color: {};">{:5}</span> <span style="color: #9cdcfe;">{}</span> {}</div>"#,
+            start.map_or(0.0, |start| record.time.saturating_duration_since(start).as_secs_f64()),
+            color,
+            record.level.as_str(),
+            html_escape_string(&record.target),
+            html_escape_string(&record.message)
+        );
+    }
+    html
+}
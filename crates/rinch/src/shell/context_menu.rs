@@ -0,0 +1,165 @@
+//! Menus shown at the mouse, such as the spelling corrections of a text
+//! input.
+//!
+//! A context menu is a layer of the window's [overlay](super::overlay),
+//! placed at the mouse and kept inside the window. The overlay doesn't get
+//! pointer input, so while a menu is open the window hands it the mouse:
+//! the item under the mouse is highlighted, clicking an item runs its
+//! [`Action`], and clicking anywhere else, pressing a key, scrolling or the
+//! window losing focus closes it.
+
+use std::ops::Range;
+
+use rinch_core::events::html_escape_string;
+
+use super::overlay::Overlay;
+
+/// The overlay layer menus are shown in, above the app's layers.
+const LAYER: &str = "rinch-context-menu";
+
+/// The order of the menu layer, beneath the DevTools inspector highlight.
+const ORDER: i32 = i32::MAX - 1;
+
+/// Attribute marking the menu.
+const MENU_ATTR: &str = "data-rinch-context-menu";
+
+/// Attribute holding the index of an enabled item.
+const ITEM_ATTR: &str = "data-rinch-menu-item";
+
+/// Rough size of the menu and its items, for keeping it inside the window.
+const MENU_WIDTH: f32 = 200.0;
+const ITEM_HEIGHT: f32 = 26.0;
+
+/// What choosing a menu item does.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Action {
+    /// Replace a range of the text of a text input.
+    ReplaceText {
+        node_id: usize,
+        range: Range<usize>,
+        text: String,
+    },
+}
+
+/// An item of a context menu.
+#[derive(Debug, Clone)]
+pub(crate) struct Item {
+    label: String,
+    /// `None` for an item that can't be chosen.
+    action: Option<Action>,
+}
+
+impl Item {
+    pub(crate) fn new(label: impl Into<String>, action: Action) -> Self {
+        Self {
+            label: label.into(),
+            action: Some(action),
+        }
+    }
+
+    pub(crate) fn disabled(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            action: None,
+        }
+    }
+}
+
+/// Where a click landed while a menu was open.
+pub(crate) enum Click {
+    /// On an item that can be chosen.
+    Item(Action),
+    /// On the menu, but not on such an item.
+    Menu,
+    /// Outside the menu.
+    Outside,
+}
+
+/// An open context menu.
+pub(crate) struct ContextMenu {
+    items: Vec<Item>,
+    /// Where the menu is, in CSS pixels from the top left of the window.
+    position: (f32, f32),
+    /// The item under the mouse.
+    hovered: Option<usize>,
+}
+
+impl ContextMenu {
+    /// Show a menu of `items` in `overlay` at `x`, `y` (CSS pixels from the
+    /// top left of a window `width` by `height` CSS pixels).
+    pub(crate) fn show(
+        overlay: &mut Overlay,
+        items: Vec<Item>,
+        (x, y): (f32, f32),
+        (width, height): (f32, f32),
+    ) -> Self {
+        // Open to the left and above when there's no room to the right and
+        // below
+        let menu_height = items.len() as f32 * ITEM_HEIGHT + 10.0;
+        let x = if x + MENU_WIDTH > width { (x - MENU_WIDTH).max(0.0) } else { x };
+        let y = if y + menu_height > height { (y - menu_height).max(0.0) } else { y };
+        let menu = Self {
+            items,
+            position: (x, y),
+            hovered: None,
+        };
+        overlay.set_layer(LAYER, ORDER, menu.html());
+        menu
+    }
+
+    /// Close the menu.
+    pub(crate) fn hide(overlay: &mut Overlay) {
+        overlay.remove_layer(LAYER);
+    }
+
+    /// Highlight the item under `x`, `y`. Returns `true` if that changed
+    /// the menu.
+    pub(crate) fn hover(&mut self, overlay: &mut Overlay, x: f32, y: f32) -> bool {
+        let hovered = overlay
+            .attribute_at(x, y, ITEM_ATTR)
+            .and_then(|index| index.parse().ok());
+        if hovered == self.hovered {
+            return false;
+        }
+        self.hovered = hovered;
+        overlay.set_layer(LAYER, ORDER, self.html());
+        true
+    }
+
+    /// Where a click at `x`, `y` lands.
+    pub(crate) fn click(&self, overlay: &Overlay, x: f32, y: f32) -> Click {
+        let item = overlay
+            .attribute_at(x, y, ITEM_ATTR)
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| self.items.get(index)?.action.clone());
+        match item {
+            Some(action) => Click::Item(action),
+            None if overlay.attribute_at(x, y, MENU_ATTR).is_some() => Click::Menu,
+            None => Click::Outside,
+        }
+    }
+
+    fn html(&self) -> String {
+        let (x, y) = self.position;
+        let mut html = format!(
+            r#"<div class="rinch-context-menu" role="menu" {}="" style="position: fixed; left: {}px; top: {}px">"#,
+            MENU_ATTR, x, y
+        );
+        for (index, item) in self.items.iter().enumerate() {
+            let label = html_escape_string(&item.label);
+            let hovered = if self.hovered == Some(index) { " rinch-hovered" } else { "" };
+            match item.action {
+                Some(_) => html.push_str(&format!(
+                    r#"<div class="rinch-context-menu-item{}" role="menuitem" {}="{}">{}</div>"#,
+                    hovered, ITEM_ATTR, index, label
+                )),
+                None => html.push_str(&format!(
+                    r#"<div class="rinch-context-menu-item" role="menuitem" aria-disabled="true">{}</div>"#,
+                    label
+                )),
+            }
+        }
+        html.push_str("</div>");
+        html
+    }
+}
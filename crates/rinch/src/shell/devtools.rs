@@ -1,7 +1,28 @@
 //! DevTools state for rinch applications.
 //!
 //! Provides a developer tools panel for inspecting the UI tree,
-//! viewing element styles, and debugging hook state.
+//! viewing element styles, debugging hook state and reading logs.
+
+use std::cell::RefCell;
+
+use tracing::Level;
+
+/// Attribute holding the index of the hook whose value an input edits.
+///
+/// Pressing Enter in the input sets the hook to the input's text.
+pub(crate) const HOOK_VALUE_ATTR: &str = "data-devtools-hook";
+
+/// Attribute holding the ID of the action clicking an element in the
+/// DevTools window performs (see [`on_click`]).
+pub(crate) const ACTION_ATTR: &str = "data-devtools-action";
+
+thread_local! {
+    /// Actions of the DevTools window's elements, indexed by their ID.
+    ///
+    /// Kept apart from the app's event handlers, which are cleared whenever
+    /// the app re-renders.
+    static ACTIONS: RefCell<Vec<DevToolsAction>> = const { RefCell::new(Vec::new()) };
+}
 
 /// The currently active panel in the devtools.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -13,10 +34,55 @@ pub enum DevToolsPanel {
     Styles,
     /// Hook state inspector.
     Hooks,
+    /// Captured log output.
+    Console,
+    /// What changed in recent renders, and why.
+    Renders,
+}
+
+/// What clicking a tab or button in the DevTools window does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DevToolsAction {
+    /// Show a panel.
+    ShowPanel(DevToolsPanel),
+    /// Toggle inspect mode in the inspected window.
+    ToggleInspect,
+    /// Toggle the layout debug overlay in the inspected window.
+    ToggleLayout,
+    /// Show log records at this level or more severe in the console.
+    SetLogLevel(Level),
+    /// Turn a style override on or off (by index).
+    ToggleStyle(usize),
+    /// Remove a style override (by index).
+    RemoveStyle(usize),
+}
+
+/// Register the action clicking an element performs, returning the
+/// attribute to add to the element.
+///
+/// Actions are registered while generating the DevTools window's content,
+/// after [`clear_actions`].
+pub(crate) fn on_click(action: DevToolsAction) -> String {
+    let id = ACTIONS.with(|actions| {
+        let mut actions = actions.borrow_mut();
+        actions.push(action);
+        actions.len() - 1
+    });
+    format!(r#"{}="{}""#, ACTION_ATTR, id)
+}
+
+/// The action with the given ID, if it's still registered.
+pub(crate) fn action(id: usize) -> Option<DevToolsAction> {
+    ACTIONS.with(|actions| actions.borrow().get(id).cloned())
+}
+
+/// Forget the actions, before generating new content.
+pub(crate) fn clear_actions() {
+    ACTIONS.with(|actions| actions.borrow_mut().clear());
 }
 
 /// State for the developer tools overlay.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct DevToolsState {
     /// Whether the devtools panel is visible.
     pub visible: bool,
@@ -28,6 +94,14 @@ pub struct DevToolsState {
     pub active_panel: DevToolsPanel,
     /// The width of the devtools panel in pixels.
     pub panel_width: u32,
+    /// The least severe log level shown in the console.
+    pub log_level: Level,
+}
+
+impl Default for DevToolsState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DevToolsState {
@@ -39,6 +113,7 @@ impl DevToolsState {
             inspect_mode: false,
             active_panel: DevToolsPanel::Elements,
             panel_width: 300,
+            log_level: Level::INFO,
         }
     }
 
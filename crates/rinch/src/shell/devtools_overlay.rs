@@ -2,8 +2,31 @@
 //!
 //! Generates HTML for the devtools panel overlay.
 
-use super::devtools::{DevToolsPanel, DevToolsState};
-use rinch_core::hooks::get_hooks_debug_info;
+use super::devtools::{DevToolsPanel, DevToolsState, HOOK_VALUE_ATTR};
+use super::overlay::Overlay;
+use blitz_dom::BaseDocument;
+use rinch_core::events::html_escape_string;
+use rinch_core::hooks::{get_hook_values, get_hooks_debug_info};
+
+/// The overlay layer highlighting the element selected in the inspector.
+const INSPECTOR_LAYER: &str = "rinch-inspector";
+
+/// Highlight the element selected in the inspector in a window's overlay,
+/// above all of the app's layers, or remove the highlight if none is.
+pub(crate) fn update_inspector_layer(overlay: &mut Overlay, doc: &BaseDocument, selected: Option<usize>) {
+    let Some(node) = selected.and_then(|id| doc.get_node(id)) else {
+        overlay.remove_layer(INSPECTOR_LAYER);
+        return;
+    };
+    let origin = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    let html = format!(
+        "<div style=\"position: fixed; left: {}px; top: {}px; width: {}px; height: {}px; \
+         background: rgba(111, 168, 220, 0.35); outline: 1px solid #6fa8dc; pointer-events: none;\"></div>",
+        origin.x, origin.y, size.width, size.height
+    );
+    overlay.set_layer(INSPECTOR_LAYER, i32::MAX, html);
+}
 
 /// Render the devtools overlay as HTML.
 ///
@@ -19,6 +42,8 @@ pub fn render_overlay(state: &DevToolsState) -> String {
         DevToolsPanel::Elements => render_elements_panel(state),
         DevToolsPanel::Styles => render_styles_panel(state),
         DevToolsPanel::Hooks => render_hooks_panel(),
+        DevToolsPanel::Console => super::console::console_html(state.log_level, None),
+        DevToolsPanel::Renders => super::render_diff::renders_html(),
     };
 
     let elements_active = if state.active_panel == DevToolsPanel::Elements {
@@ -36,6 +61,11 @@ pub fn render_overlay(state: &DevToolsState) -> String {
     } else {
         ""
     };
+    let console_active = if state.active_panel == DevToolsPanel::Console {
+        "background: #2a2a2a;"
+    } else {
+        ""
+    };
 
     let inspect_style = if state.inspect_mode {
         "background: #4a90d9; color: white;"
@@ -44,7 +74,7 @@ pub fn render_overlay(state: &DevToolsState) -> String {
     };
 
     format!(
-        r#"<div id="rinch-devtools" style="
+        r#"<div id="rinch-devtools" dir="ltr" style="
             position: fixed;
             right: 0;
             top: 0;
@@ -88,6 +118,14 @@ pub fn render_overlay(state: &DevToolsState) -> String {
                     cursor: pointer;
                     {hooks_active}
                 ">Hooks</button>
+                <button data-devtools-panel="console" style="
+                    flex: 1;
+                    padding: 8px;
+                    border: none;
+                    color: #d4d4d4;
+                    cursor: pointer;
+                    {console_active}
+                ">Console</button>
             </div>
             <div style="
                 padding: 4px 8px;
@@ -154,7 +192,7 @@ fn render_styles_panel(state: &DevToolsState) -> String {
                 <div style="font-weight: bold; margin-bottom: 8px; color: #dcdcaa;">Computed Styles</div>
                 <div style="color: #808080;">
                     Styles for Node #{node_id}<br>
-                    (Full style inspection coming soon)
+                    Open the DevTools window (F12) for its computed styles
                 </div>
             </div>"#
         )
@@ -179,10 +217,30 @@ fn render_hooks_panel() -> String {
             .to_string();
     }
 
+    let hook_values = get_hook_values();
     let hooks_html: String = hooks_info
         .iter()
+        .zip(&hook_values)
         .enumerate()
-        .map(|(i, info)| {
+        .map(|(i, (info, value))| {
+            // Primitive signals can be edited; Enter sets the value
+            let value_html = match value {
+                Some(value) => format!(
+                    r#"<input {}="{}" value="{}" style="
+                        width: 100%;
+                        margin-top: 4px;
+                        padding: 2px 4px;
+                        background: #1e1e1e;
+                        color: #b5cea8;
+                        border: 1px solid #3c3c3c;
+                        border-radius: 3px;
+                    ">"#,
+                    HOOK_VALUE_ATTR,
+                    i,
+                    html_escape_string(value)
+                ),
+                None => String::new(),
+            };
             format!(
                 r#"<div style="
                     padding: 6px 8px;
@@ -192,8 +250,12 @@ fn render_hooks_panel() -> String {
                 ">
                     <div style="color: #569cd6;">#{} {}</div>
                     <div style="color: #808080; font-size: 11px;">{}</div>
+                    {}
                 </div>"#,
-                i, info.hook_type, info.value_type
+                i,
+                info.hook_type,
+                html_escape_string(info.value_type),
+                value_html
             )
         })
         .collect();
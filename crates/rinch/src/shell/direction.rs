@@ -0,0 +1,74 @@
+//! Right-to-left layout.
+//!
+//! A window's direction (its `dir` prop, or the locale's for `auto`) is set
+//! as the `dir` attribute of the document's `<html>` element, and the
+//! default stylesheet maps `dir` attributes to the CSS `direction`. Elements
+//! can switch direction with their own `dir` attribute. The window mirrors
+//! what CSS doesn't decide:
+//!
+//! - The left and right arrow keys move the other way through horizontal
+//!   menus and radio groups (see [`focus::arrow_target`](super::focus)).
+//! - Dragging a slider measures its value from the right.
+//! - Content that scrolls sideways starts at its right end, like it starts
+//!   at its left end in a left-to-right window.
+
+use blitz_dom::BaseDocument;
+use style::properties::{LonghandId, PropertyDeclarationId};
+
+use super::focus::attr;
+
+/// Whether a node lays out right to left: the nearest `dir` attribute of
+/// it and its ancestors decides, or else its computed `direction`.
+pub(crate) fn is_rtl(doc: &BaseDocument, node_id: usize) -> bool {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node(id) else {
+            break;
+        };
+        match node.element_data().and_then(|element| attr(element, "dir")) {
+            Some(dir) if dir.eq_ignore_ascii_case("rtl") => return true,
+            Some(dir) if dir.eq_ignore_ascii_case("ltr") => return false,
+            _ => current = node.parent,
+        }
+    }
+    computed(doc, node_id, LonghandId::Direction).is_some_and(|direction| direction == "rtl")
+}
+
+/// Scroll every right-to-left element that scrolls sideways to its right
+/// end, where its content starts.
+///
+/// Call after the document was laid out.
+pub(crate) fn scroll_to_inline_start(doc: &mut BaseDocument) {
+    let mut scrolled = Vec::new();
+    let mut stack = vec![doc.root_node().id];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter().copied());
+
+        let layout = &node.final_layout;
+        let visible = layout.size.width - layout.border.left - layout.border.right;
+        let overflow = layout.content_size.width - visible;
+        if overflow <= 0.5 || node.scroll_offset.x != 0.0 {
+            continue;
+        }
+        let scrolls = computed(doc, node_id, LonghandId::OverflowX).is_some_and(|overflow| {
+            overflow == "auto" || overflow == "scroll"
+        });
+        if scrolls && is_rtl(doc, node_id) {
+            scrolled.push((node_id, f64::from(overflow)));
+        }
+    }
+    for (node_id, offset) in scrolled {
+        if let Some(node) = doc.get_node_mut(node_id) {
+            node.scroll_offset.x = offset;
+        }
+    }
+}
+
+/// The computed value of a property of an element, if it has been styled.
+pub(super) fn computed(doc: &BaseDocument, node_id: usize, longhand: LonghandId) -> Option<String> {
+    let styles = doc.get_node(node_id)?.primary_styles()?;
+    Some(styles.computed_value_to_string(PropertyDeclarationId::Longhand(longhand)))
+}
@@ -0,0 +1,52 @@
+//! Editing commands of menu items with a role.
+//!
+//! Menu items with a [`MenuRole`] cut, copy, paste and select the text of
+//! the focused `input` or `textarea` of the focused window. The document
+//! performs a command as it does when its shortcut is typed (Ctrl or Cmd
+//! with X, C, V or A), so the clipboard is used the same way and edits
+//! reach `oninput`. The items are enabled only while their command can be
+//! performed: cut and copy need selected text, which password inputs don't
+//! give out, cut and paste need an input that isn't read-only, and select
+//! all needs some text. Undo and redo are performed by the window's
+//! [`UndoManager`](crate::undo::UndoManager) instead.
+
+use blitz_dom::{BaseDocument, Node};
+use rinch_core::element::MenuRole;
+
+use super::focus::attr;
+
+/// Whether the focused element of `doc` can perform `role`.
+pub(crate) fn can_perform(doc: &BaseDocument, role: MenuRole) -> bool {
+    let Some(element) = doc
+        .get_focussed_node_id()
+        .and_then(|node_id| doc.get_node(node_id))
+        .and_then(Node::element_data)
+    else {
+        return false;
+    };
+    let Some(input) = element.text_input_data() else {
+        return false;
+    };
+    let editable = attr(element, "readonly").is_none() && attr(element, "disabled").is_none();
+    let concealed = attr(element, "type") == Some("password");
+    let selected = !input.editor.raw_selection().text_range().is_empty();
+    match role {
+        MenuRole::Cut => selected && editable && !concealed,
+        MenuRole::Copy => selected && !concealed,
+        MenuRole::Paste => editable,
+        MenuRole::SelectAll => !input.editor.text().to_string().is_empty(),
+        // Undo and redo are the window's, see `rinch::undo`
+        MenuRole::Undo | MenuRole::Redo => false,
+    }
+}
+
+/// The letter of the shortcut with which documents perform `role`.
+pub(crate) fn shortcut_letter(role: MenuRole) -> &'static str {
+    match role {
+        MenuRole::Undo | MenuRole::Redo => "z",
+        MenuRole::Cut => "x",
+        MenuRole::Copy => "c",
+        MenuRole::Paste => "v",
+        MenuRole::SelectAll => "a",
+    }
+}
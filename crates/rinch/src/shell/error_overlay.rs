@@ -0,0 +1,202 @@
+//! An overlay showing errors over the app's windows during development.
+//!
+//! While an error is set, every document ends with a layer drawn over the
+//! top of the content, showing the error's title and message. It is part of
+//! the content rather than the window's overlay so it can be clicked and
+//! scrolled. The app keeps running the code it last rendered with:
+//!
+//! - When a re-render panics, the runtime keeps the documents and event
+//!   handlers of the last render and shows the panic with its backtrace
+//!   (see [`catch_panic`]). The next render that succeeds removes it.
+//! - The `rinch dev` runner shows the errors of a failed build (see
+//!   [`hot_reload`](super::hot_reload)) until a build succeeds.
+//!
+//! The overlay's close button dismisses both.
+
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::html_escape_string;
+
+use super::focus::attr;
+
+/// Attribute marking the overlay's close button.
+const DISMISS_ATTR: &str = "data-rinch-error-dismiss";
+
+/// Where an error shown in the overlay comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorSource {
+    /// A re-render panicked.
+    Render,
+    /// The `rinch dev` runner failed to build the app.
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    Build,
+}
+
+/// An error shown in the overlay.
+struct ShownError {
+    source: ErrorSource,
+    title: String,
+    message: String,
+}
+
+thread_local! {
+    /// The errors shown, at most one per source.
+    static ERRORS: RefCell<Vec<ShownError>> = const { RefCell::new(Vec::new()) };
+    /// Whether a panic on this thread would be caught by [`catch_panic`].
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// Where the panic caught last happened, and its backtrace.
+    static PANIC_DETAILS: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Show an error over every window, replacing the one from the same source.
+pub(crate) fn show(source: ErrorSource, title: &str, message: &str) {
+    ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        errors.retain(|error| error.source != source);
+        errors.push(ShownError {
+            source,
+            title: title.to_string(),
+            message: message.to_string(),
+        });
+    });
+}
+
+/// Stop showing the error from `source`, returning whether one was shown.
+pub(crate) fn clear(source: ErrorSource) -> bool {
+    ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        let count = errors.len();
+        errors.retain(|error| error.source != source);
+        errors.len() != count
+    })
+}
+
+/// Stop showing every error, returning whether any was shown.
+pub(crate) fn dismiss() -> bool {
+    ERRORS.with(|errors| !std::mem::take(&mut *errors.borrow_mut()).is_empty())
+}
+
+/// Run `render`, returning `None` and showing the panic in the overlay if it
+/// panics.
+///
+/// The panic is still printed to the terminal by the panic hook.
+pub(crate) fn catch_panic<T>(render: impl FnOnce() -> T) -> Option<T> {
+    install_panic_hook();
+    CATCHING.with(|catching| catching.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(render));
+    CATCHING.with(|catching| catching.set(false));
+
+    let payload = match result {
+        Ok(value) => return Some(value),
+        Err(payload) => payload,
+    };
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    };
+    // Hook errors are formatted for the terminal
+    let message = strip_ansi(&message);
+    let details = PANIC_DETAILS.with(|details| details.borrow_mut().take()).unwrap_or_default();
+    tracing::error!("Render panicked, keeping the previous render: {}", message.trim());
+    show(
+        ErrorSource::Render,
+        "Render panicked",
+        &format!("{}\n\n{}", message.trim(), details),
+    );
+    None
+}
+
+/// Chain a panic hook recording where panics caught by [`catch_panic`]
+/// happened, with their backtrace.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // The hook may run on threads without the thread-local
+            let catching = CATCHING.try_with(Cell::get).unwrap_or(false);
+            if catching {
+                let location = info
+                    .location()
+                    .map(|location| format!("at {}:{}:{}\n\n", location.file(), location.line(), location.column()))
+                    .unwrap_or_default();
+                let details = format!("{}{}", location, Backtrace::force_capture());
+                PANIC_DETAILS.with(|slot| *slot.borrow_mut() = Some(details));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Whether a node is the overlay's close button or inside it.
+pub(crate) fn is_dismiss_button(doc: &BaseDocument, node_id: usize) -> bool {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node(id) else {
+            return false;
+        };
+        if node.element_data().is_some_and(|element| attr(element, DISMISS_ATTR).is_some()) {
+            return true;
+        }
+        current = node.parent;
+    }
+    false
+}
+
+/// The overlay, appended to every document; empty while there's no error.
+pub(crate) fn overlay_html() -> String {
+    ERRORS.with(|errors| {
+        let errors = errors.borrow();
+        if errors.is_empty() {
+            return String::new();
+        }
+        let mut html = String::from(
+            r#"<div class="rinch-error-overlay" role="alert" dir="ltr" style="position: fixed; top: 0; left: 0; right: 0; max-height: 60%; overflow: auto; background: #1e1e1e; border-bottom: 3px solid #f14c4c; color: #d4d4d4; font-family: monospace; font-size: 12px; z-index: 2147483647;">"#,
+        );
+        for (index, error) in errors.iter().enumerate() {
+            html.push_str(
+                r#"<div style="display: flex; align-items: center; padding: 8px 12px; background: #5a1d1d; color: #ffffff; font-weight: bold;">"#,
+            );
+            html.push_str(&format!(r#"<span style="flex: 1;">{}</span>"#, html_escape_string(&error.title)));
+            if index == 0 {
+                html.push_str(&format!(
+                    r#"<span {}="" title="Dismiss" style="padding: 0 4px; cursor: pointer;">×</span>"#,
+                    DISMISS_ATTR
+                ));
+            }
+            html.push_str("</div>");
+            html.push_str(&format!(
+                r#"<pre style="margin: 0; padding: 8px 12px; white-space: pre-wrap;">{}</pre>"#,
+                html_escape_string(&error.message)
+            ));
+        }
+        html.push_str("</div>");
+        html
+    })
+}
+
+/// Remove the color codes from a message formatted for the terminal.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the end of the escape sequence, a letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
@@ -0,0 +1,97 @@
+//! Opening files and URLs outside the app.
+//!
+//! [`open`] hands a path or URL to the OS, which opens it with the default
+//! handler (browser, mail client, file manager, ...). The runtime also calls
+//! it for clicks on links to web pages, unless disabled with
+//! [`RunConfig::open_external_links`](super::RunConfig::open_external_links).
+
+use std::ffi::OsStr;
+use std::io;
+
+/// URL schemes of links that clicking opens outside the app.
+const EXTERNAL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Open a file, folder or URL with the default system handler.
+///
+/// Returns once the handler has been started, so errors are only reported
+/// if it couldn't be started; a failure to open the target itself is
+/// logged.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::shell::open;
+///
+/// open("https://github.com/joeleaver/rinch")?;
+/// open(&exported_file)?;
+/// ```
+pub fn open(target: impl AsRef<OsStr>) -> io::Result<()> {
+    let target = target.as_ref();
+    tracing::debug!("Opening {:?}", target);
+    open_with_system(target)
+}
+
+/// Open a clicked link outside the app if it points to a web page (or mail
+/// address) and that isn't disabled.
+pub(crate) fn open_link(href: &str) {
+    if super::config::open_external_links()
+        && is_external_link(href)
+        && let Err(e) = open(href)
+    {
+        tracing::warn!("Failed to open {}: {}", href, e);
+    }
+}
+
+/// Whether clicking a link to `href` opens it outside the app.
+fn is_external_link(href: &str) -> bool {
+    href.split_once(':')
+        .is_some_and(|(scheme, _)| EXTERNAL_SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme)))
+}
+
+/// Open with `ShellExecuteW`, like double-clicking in Explorer.
+#[cfg(target_os = "windows")]
+fn open_with_system(target: &OsStr) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::ShellExecuteW;
+
+    // SW_SHOWNORMAL
+    const SHOW_NORMAL: i32 = 1;
+
+    let target: Vec<u16> = target.encode_wide().chain(std::iter::once(0)).collect();
+    let verb: Vec<u16> = "open\0".encode_utf16().collect();
+    // SAFETY: the strings are NUL-terminated and the other pointers may be null.
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            target.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SHOW_NORMAL,
+        )
+    };
+    // Values up to 32 are error codes
+    if result as isize <= 32 {
+        return Err(io::Error::other(format!("ShellExecuteW failed with {}", result as isize)));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn open_with_system(target: &OsStr) -> io::Result<()> {
+    let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    let mut child = std::process::Command::new(program).arg(target).spawn()?;
+
+    // Wait on a thread so the child doesn't linger as a zombie
+    let target = target.to_os_string();
+    std::thread::Builder::new()
+        .name("rinch-open".into())
+        .spawn(move || match child.wait() {
+            Ok(status) if !status.success() => {
+                tracing::warn!("Failed to open {:?}: {} exited with {}", target, program, status);
+            }
+            Err(e) => tracing::warn!("Failed to open {:?}: {}", target, e),
+            Ok(_) => {}
+        })?;
+    Ok(())
+}
@@ -0,0 +1,303 @@
+//! Keyboard focus: tab order, `autofocus` and focus rings.
+//!
+//! Tab order follows the HTML rules: elements with a positive `tabindex`
+//! come first, in increasing order, followed by the other focusable elements
+//! in document order. A negative `tabindex` makes an element focusable by
+//! mouse or [`focus_element`](crate::windows::focus_element) but skips it
+//! when tabbing. Elements with an `onclick` handler are focusable too, so
+//! everything clickable can be reached with the keyboard.
+//!
+//! Enter and Space click the focused element, unless it takes text. The
+//! arrow keys move between the radio buttons of a group (checking them, as
+//! in a browser) and between the items of a `role="menu"` or
+//! `role="menubar"` element.
+//!
+//! Focus moved with the keyboard is marked with the
+//! `data-rinch-focus-visible` attribute, which the default stylesheet uses to
+//! draw a focus ring, so clicking a button doesn't leave a ring behind.
+
+use blitz_dom::{ns, BaseDocument, ElementData, LocalName, Node, QualName};
+
+use super::direction;
+
+/// Attribute marking an element focused with the keyboard.
+const FOCUS_VISIBLE_ATTR: &str = "data-rinch-focus-visible";
+
+/// Attribute holding the ID of an element's `onclick` handler.
+const CLICK_HANDLER_ATTR: &str = "data-rid";
+
+/// Input types that are clicked rather than typed into.
+const BUTTON_INPUT_TYPES: &[&str] = &["checkbox", "radio", "button", "submit", "reset", "image"];
+
+/// Roles of elements whose items are navigated with the arrow keys.
+const MENU_ROLES: &[&str] = &["menu", "menubar"];
+
+/// Roles of the items of a menu.
+const MENU_ITEM_ROLES: &[&str] = &["menuitem", "menuitemcheckbox", "menuitemradio"];
+
+/// An arrow key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Where an arrow key moves focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ArrowTarget {
+    /// The element to focus.
+    pub node_id: usize,
+    /// Whether to click it too, which checks radio buttons.
+    pub check: bool,
+}
+
+/// The node to focus after pressing Tab (or Shift+Tab when `backwards`).
+///
+/// Wraps around at either end. Returns `None` if nothing can be focused.
+pub(crate) fn next_in_tab_order(doc: &BaseDocument, backwards: bool) -> Option<usize> {
+    let order = tab_order(doc);
+    if order.is_empty() {
+        return None;
+    }
+
+    let current = doc
+        .get_focussed_node_id()
+        .and_then(|focused| order.iter().position(|&id| id == focused));
+    let index = match (current, backwards) {
+        (None, false) => 0,
+        (None, true) => order.len() - 1,
+        (Some(i), false) => (i + 1) % order.len(),
+        (Some(i), true) => (i + order.len() - 1) % order.len(),
+    };
+    Some(order[index])
+}
+
+/// The focused element, if Enter and Space should click it rather than
+/// reach it as key presses (text inputs, text areas and selects take them).
+pub(crate) fn activation_target(doc: &BaseDocument) -> Option<usize> {
+    let focused = doc.get_focussed_node_id()?;
+    let element = doc.get_node(focused)?.element_data()?;
+    let takes_keys = match element.name.local.as_ref() {
+        "textarea" | "select" => true,
+        "input" => !attr(element, "type").is_some_and(|kind| BUTTON_INPUT_TYPES.contains(&kind)),
+        _ => attr(element, "contenteditable").is_some_and(|value| value != "false"),
+    };
+    (!takes_keys).then_some(focused)
+}
+
+/// Where an arrow key moves focus from the focused element: to the next or
+/// previous radio button of its group, or item of its menu, wrapping around.
+///
+/// Returns `None` if the focused element is in neither, so the key reaches
+/// the document. Left and right are swapped in right-to-left layouts.
+pub(crate) fn arrow_target(doc: &BaseDocument, arrow: Arrow) -> Option<ArrowTarget> {
+    let focused = doc.get_focussed_node_id()?;
+    let arrow = match arrow {
+        Arrow::Left if direction::is_rtl(doc, focused) => Arrow::Right,
+        Arrow::Right if direction::is_rtl(doc, focused) => Arrow::Left,
+        arrow => arrow,
+    };
+    let node = doc.get_node(focused)?;
+    let element = node.element_data()?;
+    // Text inputs move their caret, even in a menu
+    if element.text_input_data().is_some() {
+        return None;
+    }
+
+    let (items, forward, check) = if is_radio(element) {
+        // Radio groups go both ways
+        let name = attr(element, "name")?;
+        let items: Vec<usize> = elements(doc)
+            .into_iter()
+            .filter(|&(node, other)| {
+                is_radio(other)
+                    && attr(other, "name") == Some(name)
+                    && can_focus(node, other)
+                    && is_displayed(node)
+            })
+            .map(|(node, _)| node.id)
+            .collect();
+        (items, matches!(arrow, Arrow::Down | Arrow::Right), true)
+    } else {
+        let menu = menu_of(doc, node)?;
+        let horizontal = doc
+            .get_node(menu)
+            .and_then(Node::element_data)
+            .is_some_and(|menu| {
+                attr(menu, "role") == Some("menubar") || attr(menu, "aria-orientation") == Some("horizontal")
+            });
+        let forward = match (arrow, horizontal) {
+            (Arrow::Down, false) | (Arrow::Right, true) => true,
+            (Arrow::Up, false) | (Arrow::Left, true) => false,
+            _ => return None,
+        };
+        (menu_items(doc, menu), forward, false)
+    };
+
+    let index = items.iter().position(|&id| id == focused)?;
+    let next = if forward {
+        (index + 1) % items.len()
+    } else {
+        (index + items.len() - 1) % items.len()
+    };
+    Some(ArrowTarget {
+        node_id: items[next],
+        check,
+    })
+}
+
+/// The first element with an `autofocus` attribute that can be focused.
+pub(crate) fn autofocus_target(doc: &BaseDocument) -> Option<usize> {
+    elements(doc)
+        .into_iter()
+        .find(|&(node, element)| has_attr(element, "autofocus") && can_focus(node, element))
+        .map(|(node, _)| node.id)
+}
+
+/// The focusable element whose `id` attribute is `id`.
+pub(crate) fn element_by_id(doc: &BaseDocument, id: &str) -> Option<usize> {
+    elements(doc)
+        .into_iter()
+        .find(|&(_, element)| attr(element, "id") == Some(id))
+        .filter(|&(node, element)| can_focus(node, element))
+        .map(|(node, _)| node.id)
+}
+
+/// Add or remove the focus ring marker on an element.
+pub(crate) fn set_focus_visible(doc: &mut BaseDocument, node_id: usize, visible: bool) {
+    let name = QualName::new(None, ns!(), LocalName::from(FOCUS_VISIBLE_ATTR));
+    let mut mutator = doc.mutate();
+    if visible {
+        mutator.set_attribute(node_id, name, "");
+    } else {
+        mutator.clear_attribute(node_id, name);
+    }
+}
+
+/// Focusable elements in tab order.
+fn tab_order(doc: &BaseDocument) -> Vec<usize> {
+    let mut candidates: Vec<(i32, usize)> = elements(doc)
+        .into_iter()
+        .filter(|&(node, element)| can_focus(node, element) && is_displayed(node))
+        .filter_map(|(node, element)| {
+            let tabindex = tab_index(element).unwrap_or(0);
+            (tabindex >= 0).then_some((tabindex, node.id))
+        })
+        .collect();
+
+    // Positive indices first, then 0 (document order is kept by the stable sort)
+    candidates.sort_by_key(|&(tabindex, _)| if tabindex > 0 { tabindex } else { i32::MAX });
+    candidates.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Whether an element can receive focus.
+pub(crate) fn can_focus(node: &Node, element: &ElementData) -> bool {
+    (node.is_focussable() || tab_index(element).is_some() || has_attr(element, CLICK_HANDLER_ATTR))
+        && !has_attr(element, "disabled")
+}
+
+/// Whether a node is an element that can receive focus.
+pub(crate) fn can_focus_node(doc: &BaseDocument, node_id: usize) -> bool {
+    doc.get_node(node_id)
+        .and_then(|node| Some((node, node.element_data()?)))
+        .is_some_and(|(node, element)| can_focus(node, element))
+}
+
+fn is_radio(element: &ElementData) -> bool {
+    element.name.local.as_ref() == "input" && attr(element, "type") == Some("radio")
+}
+
+/// The menu an item belongs to: its nearest ancestor with a menu role.
+fn menu_of(doc: &BaseDocument, node: &Node) -> Option<usize> {
+    let is_item = node
+        .element_data()
+        .and_then(|element| attr(element, "role"))
+        .is_some_and(|role| MENU_ITEM_ROLES.contains(&role));
+    if !is_item {
+        return None;
+    }
+    let mut current = node.parent;
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if node
+            .element_data()
+            .and_then(|element| attr(element, "role"))
+            .is_some_and(|role| MENU_ROLES.contains(&role))
+        {
+            return Some(id);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// The focusable items of a menu in document order, leaving out those of
+/// nested menus.
+fn menu_items(doc: &BaseDocument, menu: usize) -> Vec<usize> {
+    let mut items = Vec::new();
+    let mut stack: Vec<usize> = doc
+        .get_node(menu)
+        .map(|menu| menu.children.iter().rev().copied().collect())
+        .unwrap_or_default();
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if let Some(element) = node.element_data() {
+            let role = attr(element, "role");
+            if role.is_some_and(|role| MENU_ROLES.contains(&role)) {
+                continue;
+            }
+            if role.is_some_and(|role| MENU_ITEM_ROLES.contains(&role))
+                && can_focus(node, element)
+                && is_displayed(node)
+            {
+                items.push(node_id);
+            }
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    items
+}
+
+/// Whether a laid out element takes up space, which hidden elements don't.
+fn is_displayed(node: &Node) -> bool {
+    let size = node.final_layout.size;
+    size.width > 0.0 || size.height > 0.0
+}
+
+/// Every element in document order.
+fn elements(doc: &BaseDocument) -> Vec<(&Node, &ElementData)> {
+    let mut elements = Vec::new();
+    let mut stack = vec![doc.root_node().id];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if let Some(element) = node.element_data() {
+            elements.push((node, element));
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    elements
+}
+
+fn tab_index(element: &ElementData) -> Option<i32> {
+    attr(element, "tabindex").and_then(|value| value.trim().parse().ok())
+}
+
+/// The value of an element's attribute.
+pub(crate) fn attr<'a>(element: &'a ElementData, name: &str) -> Option<&'a str> {
+    element
+        .attrs()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == name)
+        .map(|attr| attr.value.as_str())
+}
+
+/// Whether an element has an attribute, with any value.
+pub(crate) fn has_attr(element: &ElementData, name: &str) -> bool {
+    attr(element, name).is_some()
+}
@@ -0,0 +1,61 @@
+//! Finding the element under the mouse.
+//!
+//! `rsx!` renders the `pointer_events` prop as `data-rinch-pointer-events`.
+//! An element with `pointer_events: "none"`, and everything in it, is
+//! skipped when the window looks for the element under the mouse, so clicks,
+//! tooltips and hovering reach the element beneath it. A descendant with
+//! `pointer_events: "auto"` takes part again, as with CSS `pointer-events`.
+
+use blitz_dom::BaseDocument;
+
+use super::focus;
+
+/// The attribute holding an element's `pointer_events` prop.
+const POINTER_EVENTS_ATTR: &str = "data-rinch-pointer-events";
+
+/// The node under `x`, `y` (CSS pixels from the top left of the window),
+/// skipping elements with `pointer_events: "none"`.
+pub(crate) fn node_at(doc: &BaseDocument, x: f32, y: f32) -> Option<usize> {
+    let hit = doc.hit(x, y)?.node_id;
+    if !ignores_pointer(doc, hit) {
+        return Some(hit);
+    }
+    // The document only reports the topmost node, so look beneath it by
+    // the boxes of the elements, the last drawn first
+    let scroll = doc.viewport_scroll();
+    beneath(doc, doc.root_element().id, x + scroll.x as f32, y + scroll.y as f32)
+}
+
+/// Whether a node is skipped by hit testing: the nearest element with a
+/// `pointer_events` prop, itself or an ancestor, has `"none"`.
+fn ignores_pointer(doc: &BaseDocument, node_id: usize) -> bool {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node(id) else {
+            return false;
+        };
+        if let Some(value) = node.element_data().and_then(|element| focus::attr(element, POINTER_EVENTS_ATTR)) {
+            return value == "none";
+        }
+        current = node.parent;
+    }
+    false
+}
+
+/// The last drawn element in the subtree of `node_id` whose box holds `x`,
+/// `y` (CSS pixels from the top left of the document) and that doesn't
+/// ignore the pointer.
+fn beneath(doc: &BaseDocument, node_id: usize, x: f32, y: f32) -> Option<usize> {
+    let node = doc.get_node(node_id)?;
+    // Children may overflow their parent, so all of them are looked at
+    for &child in node.children.iter().rev() {
+        if let Some(hit) = beneath(doc, child, x, y) {
+            return Some(hit);
+        }
+    }
+    node.element_data()?;
+    let origin = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    let inside = x >= origin.x && x < origin.x + size.width && y >= origin.y && y < origin.y + size.height;
+    (inside && !ignores_pointer(doc, node_id)).then_some(node_id)
+}
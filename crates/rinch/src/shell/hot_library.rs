@@ -0,0 +1,274 @@
+//! Hot reloading of app code compiled into a dynamic library.
+//!
+//! [`HotReloader`](super::HotReloader) alone re-renders the app that was
+//! compiled into the binary, so only changes to CSS and other files show up.
+//! With a [`HotLibrary`], the app component lives in a library crate that is
+//! rebuilt with cargo when its source changes, loaded into the running app
+//! and called for the next render. Signals and other hooks keep their state
+//! as long as the new code calls them in the same order (see
+//! [`begin_hot_swap`](rinch_core::begin_hot_swap)); signals of primitive
+//! types that changed type, or come after a hook that changed, get their
+//! values back through a [`HookSnapshot`](rinch_core::HookSnapshot).
+//!
+//! The library and the binary must share one copy of rinch, or each would
+//! keep its own hooks and event handlers. Both depend on `rinch-dylib`,
+//! which is rinch built as a Rust `dylib`, instead of `rinch`:
+//!
+//! ```toml
+//! # my-app-ui/Cargo.toml
+//! [lib]
+//! crate-type = ["rlib", "dylib"]
+//!
+//! [dependencies]
+//! rinch = { package = "rinch-dylib", version = "0.1" }
+//! ```
+//!
+//! ```ignore
+//! // my-app-ui/src/lib.rs
+//! use rinch::prelude::*;
+//!
+//! pub fn app() -> Element {
+//!     rsx! { Window { title: "My App", p { "Edit me" } } }
+//! }
+//!
+//! rinch::hot_library!(app);
+//!
+//! // my-app/src/main.rs
+//! fn main() {
+//!     rinch::run_with_hot_library(rinch::shell::HotLibrary::new("my-app-ui"));
+//! }
+//! ```
+//!
+//! Libraries are never unloaded, since closures created by old code may
+//! still be referenced, so each reload uses a little more memory.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+use libloading::Library;
+use rinch_core::element::Element;
+
+/// Name of the function exported by [`hot_library!`](crate::hot_library).
+const APP_SYMBOL: &[u8] = b"rinch_hot_app";
+
+thread_local! {
+    /// The app function of the library loaded last.
+    static CURRENT_APP: Cell<Option<fn() -> Element>> = const { Cell::new(None) };
+    /// Every library loaded, kept so their code stays valid.
+    static LIBRARIES: RefCell<Vec<Library>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Export an app function from a library for [`HotLibrary`].
+///
+/// ```ignore
+/// rinch::hot_library!(app);
+/// ```
+#[macro_export]
+macro_rules! hot_library {
+    ($app:path) => {
+        #[doc(hidden)]
+        #[unsafe(no_mangle)]
+        pub fn rinch_hot_app() -> $crate::core::element::Element {
+            $app()
+        }
+    };
+}
+
+/// A library crate holding the app component, rebuilt and reloaded when
+/// its source changes.
+#[derive(Debug, Clone)]
+pub struct HotLibrary {
+    /// The cargo package to build.
+    pub package: String,
+    /// The library's crate name, by default the package name with `-`
+    /// replaced by `_`.
+    pub lib_name: String,
+    /// Whether to build with `--release`.
+    pub release: bool,
+}
+
+impl HotLibrary {
+    /// A library built from the given package.
+    pub fn new(package: impl Into<String>) -> Self {
+        let package = package.into();
+        Self {
+            lib_name: package.replace('-', "_"),
+            package,
+            release: false,
+        }
+    }
+
+    /// Set the library's crate name, if it isn't named after the package.
+    pub fn lib_name(mut self, lib_name: impl Into<String>) -> Self {
+        self.lib_name = lib_name.into();
+        self
+    }
+
+    /// Build with `--release`.
+    pub fn release(mut self, release: bool) -> Self {
+        self.release = release;
+        self
+    }
+
+    /// Build the library with cargo, returning the path of the built file.
+    ///
+    /// The path is taken from the artifacts cargo reports, so it's right
+    /// whatever the target directory, target triple or profile. Compiler
+    /// messages go to the terminal. Blocks until the build is done.
+    pub fn build(&self) -> HotLibraryResult<PathBuf> {
+        tracing::info!("Hot reload: building {}", self.package);
+        let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+        command.args(["build", "--message-format=json-render-diagnostics", "--lib", "-p", &self.package]);
+        if self.release {
+            command.arg("--release");
+        }
+        let mut child = command
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| HotLibraryError::Build(format!("failed to run cargo: {}", e)))?;
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut built = None;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(path) = self.artifact_path(&line) {
+                built = Some(path);
+            }
+        }
+
+        let status = child
+            .wait()
+            .map_err(|e| HotLibraryError::Build(format!("failed to wait for cargo: {}", e)))?;
+        if !status.success() {
+            return Err(HotLibraryError::Build(format!("cargo exited with {}", status)));
+        }
+        built.ok_or_else(|| {
+            HotLibraryError::Build(format!(
+                "cargo didn't build a dynamic library named {}; is its crate-type \"dylib\"?",
+                self.lib_name
+            ))
+        })
+    }
+
+    /// The dynamic library of this crate in a line of cargo's JSON output,
+    /// if the line reports building it.
+    fn artifact_path(&self, line: &str) -> Option<PathBuf> {
+        let message: serde_json::Value = serde_json::from_str(line).ok()?;
+        if message["reason"] != "compiler-artifact" || message["target"]["name"] != self.lib_name.as_str() {
+            return None;
+        }
+        let kinds = message["target"]["kind"].as_array()?;
+        if !kinds.iter().any(|kind| kind == "dylib" || kind == "cdylib") {
+            return None;
+        }
+        message["filenames"]
+            .as_array()?
+            .iter()
+            .filter_map(|file| file.as_str())
+            .find(|file| file.ends_with(std::env::consts::DLL_SUFFIX))
+            .map(PathBuf::from)
+    }
+}
+
+/// Hot library error type.
+#[derive(Debug)]
+pub enum HotLibraryError {
+    /// Building the library failed.
+    Build(String),
+    /// Loading the built library failed.
+    Load(String),
+}
+
+impl fmt::Display for HotLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotLibraryError::Build(msg) => write!(f, "failed to build the library: {}", msg),
+            HotLibraryError::Load(msg) => write!(f, "failed to load the library: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HotLibraryError {}
+
+/// Result type for hot library operations.
+pub type HotLibraryResult<T> = Result<T, HotLibraryError>;
+
+/// Load a built library and make its app function the current one.
+///
+/// The file is copied first, so the next build can replace it and the OS
+/// loads the new code instead of reusing the library it already loaded.
+pub(crate) fn load(built: &Path) -> HotLibraryResult<()> {
+    let load_error = |e: &dyn fmt::Display| HotLibraryError::Load(format!("{}: {}", built.display(), e));
+
+    let count = LIBRARIES.with(|libraries| libraries.borrow().len());
+    let dir = std::env::temp_dir().join(format!("rinch-hot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|e| load_error(&e))?;
+    let stem = built.file_stem().unwrap_or_default().to_string_lossy();
+    let copy = dir.join(format!("{}-{}{}", stem, count, std::env::consts::DLL_SUFFIX));
+    std::fs::copy(built, &copy).map_err(|e| load_error(&e))?;
+
+    // SAFETY: the library is built by cargo from the app's own sources with
+    // the same compiler, and exports `rinch_hot_app` with this signature
+    // through `hot_library!`. It's never unloaded.
+    let (library, app) = unsafe {
+        let library = Library::new(&copy).map_err(|e| load_error(&e))?;
+        let app = *library
+            .get::<fn() -> Element>(APP_SYMBOL)
+            .map_err(|e| load_error(&e))?;
+        (library, app)
+    };
+    LIBRARIES.with(|libraries| libraries.borrow_mut().push(library));
+    CURRENT_APP.with(|current| current.set(Some(app)));
+    tracing::info!("Hot reload: loaded {}", built.display());
+    Ok(())
+}
+
+/// Render the app with the library loaded last.
+pub(crate) fn app() -> Element {
+    let app = CURRENT_APP.with(Cell::get).expect("No hot library loaded");
+    app()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+
+    fn artifact(name: &str, kinds: &[&str], filenames: &[String]) -> String {
+        serde_json::json!({
+            "reason": "compiler-artifact",
+            "target": { "kind": kinds, "crate_types": kinds, "name": name },
+            "filenames": filenames,
+            "executable": null,
+            "fresh": false,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn finds_the_dynamic_library_of_the_crate() {
+        let library = HotLibrary::new("my-app-ui");
+        let dylib = format!("/work/target/x86_64-unknown-linux-gnu/dev-fast/{}my_app_ui{}", DLL_PREFIX, DLL_SUFFIX);
+        let line = artifact(
+            "my_app_ui",
+            &["rlib", "dylib"],
+            &["/work/target/dev-fast/libmy_app_ui.rlib".to_string(), dylib.clone()],
+        );
+        assert_eq!(library.artifact_path(&line), Some(PathBuf::from(dylib)));
+    }
+
+    #[test]
+    fn ignores_other_messages_and_crates() {
+        let library = HotLibrary::new("my-app-ui");
+        let dependency = format!("/work/target/debug/{}rinch_dylib{}", DLL_PREFIX, DLL_SUFFIX);
+        assert_eq!(library.artifact_path(&artifact("rinch_dylib", &["dylib"], &[dependency])), None);
+
+        let rlib_only = artifact("my_app_ui", &["rlib"], &["/work/target/debug/libmy_app_ui.rlib".to_string()]);
+        assert_eq!(library.artifact_path(&rlib_only), None);
+
+        assert_eq!(library.artifact_path(r#"{"reason":"build-finished","success":true}"#), None);
+        assert_eq!(library.artifact_path("Compiling my-app-ui"), None);
+    }
+}
@@ -1,18 +1,44 @@
 //! Hot reload support for rinch applications.
 //!
 //! When enabled with the `hot-reload` feature, this module provides file watching
-//! capabilities that trigger UI re-renders when source files change.
+//! capabilities that trigger UI re-renders when source files change. Changes to
+//! CSS files injected with `WindowHandle::inject_stylesheet_file` are applied
+//! in place instead, without losing UI state. HTML files shown with a
+//! window's `src` prop are watched wherever they are, and re-render the app
+//! even under `rinch dev`, since they don't need a build. With a
+//! [`HotLibrary`](super::HotLibrary), source changes rebuild and reload the
+//! app's code instead (see [`hot_library`](super::hot_library)).
+//!
+//! The values of the app's signals are saved to a file after each render
+//! and restored when the app starts, so restarting it with new code (with
+//! `cargo watch` or `rinch dev`, say) keeps its state where it can. See
+//! [`HookSnapshot`] for which values survive.
+//!
+//! Apps started by the `rinch dev` runner find the [`DEV_DIR_ENV`] variable
+//! set. The runner builds the app itself, so the reloader leaves Rust
+//! changes to it and watches the directory the variable names instead:
+//!
+//! - `errors` holds the compiler errors of the last build, empty if it
+//!   succeeded; they're shown over the app's windows.
+//! - `library` is written after the runner rebuilt the
+//!   [`HotLibrary`](super::HotLibrary), which is then reloaded.
 
 use notify::{
     event::ModifyKind, Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
 };
+use rinch_core::HookSnapshot;
 use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::{Duration, Instant};
 use winit::event_loop::EventLoopProxy;
 
+use super::hot_library::HotLibrary;
 use super::runtime::RinchEvent;
 
+/// Environment variable naming the directory the `rinch dev` runner reports
+/// builds in.
+pub const DEV_DIR_ENV: &str = "RINCH_DEV_DIR";
+
 /// Configuration for hot reload file watching.
 #[derive(Debug, Clone)]
 pub struct HotReloadConfig {
@@ -22,12 +48,17 @@ pub struct HotReloadConfig {
     pub extensions: Vec<String>,
     /// Debounce duration to prevent multiple rapid reloads.
     pub debounce: Duration,
+    /// Library holding the app's code, rebuilt and reloaded on changes.
+    pub library: Option<HotLibrary>,
+    /// File keeping the values of the app's signals, so they're restored
+    /// when the app is restarted. `None` starts from the initial values.
+    pub state_file: Option<PathBuf>,
 }
 
 impl Default for HotReloadConfig {
     fn default() -> Self {
         // Try to find source directories that exist
-        let candidates = ["src", "examples", "crates"];
+        let candidates = ["src", "examples", "crates", "assets"];
         let watch_paths: Vec<PathBuf> = candidates
             .iter()
             .map(PathBuf::from)
@@ -42,6 +73,8 @@ impl Default for HotReloadConfig {
             },
             extensions: vec!["rs".into(), "css".into(), "html".into()],
             debounce: Duration::from_millis(100),
+            library: None,
+            state_file: Some(default_state_file()),
         }
     }
 }
@@ -66,15 +99,47 @@ impl HotReloadConfig {
         self.debounce = debounce;
         self
     }
+
+    /// Rebuild and reload a library holding the app's code on changes,
+    /// instead of re-rendering the code compiled into the binary.
+    pub fn with_library(mut self, library: HotLibrary) -> Self {
+        self.library = Some(library);
+        self
+    }
+
+    /// Set the file keeping signal values across restarts, or turn keeping
+    /// them off with `None`.
+    pub fn with_state_file(mut self, state_file: Option<PathBuf>) -> Self {
+        self.state_file = state_file;
+        self
+    }
+}
+
+/// A file in the temp directory named after the app's executable.
+fn default_state_file() -> PathBuf {
+    let name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "app".to_string());
+    std::env::temp_dir().join(format!("rinch-hooks-{}", name))
 }
 
 /// Hot reloader that watches files and triggers UI re-renders.
 pub struct HotReloader {
-    _watcher: RecommendedWatcher,
+    watcher: RecommendedWatcher,
     receiver: Receiver<Result<Event, notify::Error>>,
     config: HotReloadConfig,
     last_reload: Instant,
     proxy: EventLoopProxy<RinchEvent>,
+    /// Asks the thread building the library for a build.
+    builder: Option<Sender<()>>,
+    /// The state last written to the state file.
+    saved_state: String,
+    /// The directory the `rinch dev` runner reports builds in, if it started
+    /// the app.
+    dev_dir: Option<PathBuf>,
+    /// The files windows were loaded from that are being watched.
+    page_files: Vec<PathBuf>,
 }
 
 impl HotReloader {
@@ -102,29 +167,121 @@ impl HotReloader {
             }
         }
 
+        let dev_dir = std::env::var_os(DEV_DIR_ENV).map(PathBuf::from);
+        if let Some(dir) = &dev_dir {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            tracing::info!("Hot reload: started by rinch dev");
+        }
+        let dev_dir = dev_dir.map(|dir| dir.canonicalize().unwrap_or(dir));
+
+        // The runner builds the library when it's there
+        let builder = match &dev_dir {
+            Some(_) => None,
+            None => config.library.clone().map(|library| start_builder(library, proxy.clone())),
+        };
+
         Ok(Self {
-            _watcher: watcher,
+            watcher,
             receiver: rx,
             config,
             last_reload: Instant::now(),
             proxy,
+            builder,
+            saved_state: String::new(),
+            dev_dir,
+            page_files: Vec::new(),
         })
     }
 
+    /// Restore the hook values saved in the state file by the previous run,
+    /// returning whether any were set and the app should re-render.
+    ///
+    /// Call this after the first render. Hooks that changed since are left
+    /// as they are.
+    pub fn restore_state(&mut self) -> bool {
+        let Some(path) = &self.config.state_file else {
+            return false;
+        };
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        let restored = HookSnapshot::from_text(&text).restore();
+        if restored > 0 {
+            tracing::info!("Hot reload: restored {} signal values from {:?}", restored, path);
+        }
+        self.saved_state = text;
+        restored > 0
+    }
+
+    /// Write the current hook values to the state file, if they changed.
+    ///
+    /// Called after each render, since a restart rarely lets the app exit
+    /// normally.
+    pub fn save_state(&mut self) {
+        let Some(path) = &self.config.state_file else {
+            return;
+        };
+        let text = HookSnapshot::capture().to_text();
+        if text == self.saved_state {
+            return;
+        }
+        if let Err(e) = std::fs::write(path, &text) {
+            tracing::warn!("Hot reload: failed to save state to {:?}: {}", path, e);
+        }
+        self.saved_state = text;
+    }
+
     /// Check for file changes and trigger re-render if needed.
     ///
     /// Call this periodically (e.g., in about_to_wait).
     pub fn poll(&mut self) {
+        self.watch_page_files();
         while let Ok(result) = self.receiver.try_recv() {
             match result {
                 Ok(event) => {
+                    if self.is_dev_report(&event) {
+                        self.read_dev_report(&event);
+                        continue;
+                    }
+                    if self.is_page_change(&event) {
+                        let now = Instant::now();
+                        if now.duration_since(self.last_reload) >= self.config.debounce {
+                            self.last_reload = now;
+                            tracing::info!("Hot reload: window page changed, triggering re-render");
+                            let _ = self.proxy.send_event(RinchEvent::ReRender);
+                        }
+                        continue;
+                    }
                     if self.should_reload(&event) {
+                        // CSS-only changes can be applied to injected stylesheets
+                        // in place, keeping UI state
+                        if is_css_only(&event) {
+                            let paths = event
+                                .paths
+                                .iter()
+                                .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+                                .collect();
+                            tracing::info!("Hot reload: stylesheet changed");
+                            let _ = self.proxy.send_event(RinchEvent::StylesheetsChanged(paths));
+                            continue;
+                        }
+
+                        // The runner rebuilds the app for other changes
+                        if self.dev_dir.is_some() {
+                            continue;
+                        }
+
                         // Check debounce
                         let now = Instant::now();
                         if now.duration_since(self.last_reload) >= self.config.debounce {
                             self.last_reload = now;
-                            tracing::info!("Hot reload: file changed, triggering re-render");
-                            let _ = self.proxy.send_event(RinchEvent::ReRender);
+                            if let Some(builder) = &self.builder {
+                                tracing::info!("Hot reload: file changed, rebuilding the library");
+                                let _ = builder.send(());
+                            } else {
+                                tracing::info!("Hot reload: file changed, triggering re-render");
+                                let _ = self.proxy.send_event(RinchEvent::ReRender);
+                            }
                         }
                     }
                 }
@@ -135,6 +292,69 @@ impl HotReloader {
         }
     }
 
+    /// Watch the files windows were loaded from since the last poll.
+    fn watch_page_files(&mut self) {
+        for path in crate::assets::page_files() {
+            if self.page_files.contains(&path) {
+                continue;
+            }
+            match self.watcher.watch(&path, RecursiveMode::NonRecursive) {
+                Ok(()) => tracing::info!("Hot reload: watching {:?}", path),
+                Err(e) => tracing::warn!("Hot reload: can't watch {:?}: {}", path, e),
+            }
+            self.page_files.push(path);
+        }
+    }
+
+    /// Check if an event changed a file a window was loaded from.
+    fn is_page_change(&self, event: &Event) -> bool {
+        matches!(event.kind, EventKind::Modify(ModifyKind::Data(_)) | EventKind::Create(_))
+            && event.paths.iter().any(|path| {
+                let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                self.page_files.contains(&path)
+            })
+    }
+
+    /// Check if an event is the `rinch dev` runner reporting a build.
+    fn is_dev_report(&self, event: &Event) -> bool {
+        let Some(dir) = &self.dev_dir else {
+            return false;
+        };
+        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|path| {
+                let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                path.parent() == Some(dir.as_path())
+            })
+    }
+
+    /// Act on the files the `rinch dev` runner wrote.
+    fn read_dev_report(&self, event: &Event) {
+        for path in &event.paths {
+            match path.file_name().and_then(|name| name.to_str()) {
+                Some("errors") => {
+                    // The runner replaces the file, so a failed read means
+                    // another event follows
+                    if let Ok(errors) = std::fs::read_to_string(path) {
+                        let _ = self.proxy.send_event(RinchEvent::BuildErrors(errors));
+                    }
+                }
+                Some("library") => match &self.config.library {
+                    // The runner writes the path of the library it built
+                    Some(_) => match std::fs::read_to_string(path) {
+                        Ok(built) if !built.trim().is_empty() => {
+                            tracing::info!("Hot reload: library rebuilt by rinch dev");
+                            let built = PathBuf::from(built.trim());
+                            let _ = self.proxy.send_event(RinchEvent::LibraryBuilt(built));
+                        }
+                        _ => {}
+                    },
+                    None => tracing::warn!("Hot reload: rinch dev rebuilt a library, but the app doesn't run one"),
+                },
+                _ => {}
+            }
+        }
+    }
+
     /// Check if an event should trigger a reload.
     fn should_reload(&self, event: &Event) -> bool {
         // Only reload on data modifications
@@ -158,3 +378,37 @@ impl HotReloader {
         false
     }
 }
+
+/// Start a thread building the library when asked, which sends
+/// [`RinchEvent::LibraryBuilt`] after each successful build.
+///
+/// Requests made during a build lead to a single build after it.
+fn start_builder(library: HotLibrary, proxy: EventLoopProxy<RinchEvent>) -> Sender<()> {
+    let (tx, rx) = channel::<()>();
+    let spawned = std::thread::Builder::new()
+        .name("rinch-hot-build".into())
+        .spawn(move || {
+            while rx.recv().is_ok() {
+                while rx.try_recv().is_ok() {}
+                match library.build() {
+                    Ok(path) => {
+                        let _ = proxy.send_event(RinchEvent::LibraryBuilt(path));
+                    }
+                    Err(e) => tracing::error!("Hot reload: {}", e),
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::error!("Hot reload: failed to start the build thread: {}", e);
+    }
+    tx
+}
+
+/// Check if all files changed by an event are CSS files.
+fn is_css_only(event: &Event) -> bool {
+    !event.paths.is_empty()
+        && event.paths.iter().all(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("css"))
+        })
+}
@@ -0,0 +1,117 @@
+//! Converting winit keyboard and IME events for blitz documents.
+//!
+//! Documents only need the keys used for editing text (characters,
+//! navigation, deletion and the shortcut modifiers); other named keys are
+//! passed on as `Unidentified`.
+
+use blitz_traits::events::{
+    BlitzImeEvent, BlitzKeyEvent, Code, Key, KeyState, Location, Modifiers, UiEvent,
+};
+use winit::event::{ElementState, Ime, KeyEvent};
+use winit::keyboard::{self, ModifiersState, NamedKey};
+
+/// Convert a winit key event into a blitz key event.
+pub(crate) fn to_blitz_key_event(event: &KeyEvent, modifiers: ModifiersState) -> BlitzKeyEvent {
+    let state = match event.state {
+        ElementState::Pressed => KeyState::Pressed,
+        ElementState::Released => KeyState::Released,
+    };
+
+    BlitzKeyEvent {
+        key: to_blitz_key(&event.logical_key),
+        code: Code::Unidentified,
+        modifiers: to_blitz_modifiers(modifiers),
+        location: to_blitz_location(event.location),
+        is_auto_repeating: event.repeat,
+        is_composing: false,
+        state,
+        text: event.text.as_ref().map(|text| text.as_str().into()),
+    }
+}
+
+/// The key down and up events of the Ctrl+`letter` shortcut (Cmd+`letter`
+/// on macOS), with which documents perform editing commands.
+pub(crate) fn shortcut_events(letter: &str) -> [UiEvent; 2] {
+    let modifiers = if cfg!(target_os = "macos") {
+        Modifiers::META
+    } else {
+        Modifiers::CONTROL
+    };
+    [KeyState::Pressed, KeyState::Released].map(|state| {
+        let event = BlitzKeyEvent {
+            key: Key::Character(letter.into()),
+            code: Code::Unidentified,
+            modifiers,
+            location: Location::Standard,
+            is_auto_repeating: false,
+            is_composing: false,
+            state,
+            text: None,
+        };
+        match state {
+            KeyState::Pressed => UiEvent::KeyDown(event),
+            KeyState::Released => UiEvent::KeyUp(event),
+        }
+    })
+}
+
+/// Convert a winit IME event into a blitz IME event.
+pub(crate) fn to_blitz_ime_event(event: Ime) -> BlitzImeEvent {
+    match event {
+        Ime::Enabled => BlitzImeEvent::Enabled,
+        Ime::Preedit(text, cursor) => BlitzImeEvent::Preedit(text, cursor),
+        Ime::Commit(text) => BlitzImeEvent::Commit(text),
+        Ime::Disabled => BlitzImeEvent::Disabled,
+    }
+}
+
+fn to_blitz_key(key: &keyboard::Key) -> Key {
+    match key {
+        keyboard::Key::Character(text) => Key::Character(text.as_str().into()),
+        keyboard::Key::Named(named) => match named {
+            NamedKey::Space => Key::Character(" ".into()),
+            NamedKey::Enter => Key::Enter,
+            NamedKey::Tab => Key::Tab,
+            NamedKey::Backspace => Key::Backspace,
+            NamedKey::Delete => Key::Delete,
+            NamedKey::Escape => Key::Escape,
+            NamedKey::ArrowLeft => Key::ArrowLeft,
+            NamedKey::ArrowRight => Key::ArrowRight,
+            NamedKey::ArrowUp => Key::ArrowUp,
+            NamedKey::ArrowDown => Key::ArrowDown,
+            NamedKey::Home => Key::Home,
+            NamedKey::End => Key::End,
+            NamedKey::PageUp => Key::PageUp,
+            NamedKey::PageDown => Key::PageDown,
+            NamedKey::Shift => Key::Shift,
+            NamedKey::Control => Key::Control,
+            NamedKey::Alt => Key::Alt,
+            NamedKey::Super | NamedKey::Meta => Key::Meta,
+            NamedKey::Copy => Key::Copy,
+            NamedKey::Cut => Key::Cut,
+            NamedKey::Paste => Key::Paste,
+            NamedKey::Undo => Key::Undo,
+            NamedKey::Redo => Key::Redo,
+            _ => Key::Unidentified,
+        },
+        _ => Key::Unidentified,
+    }
+}
+
+fn to_blitz_modifiers(state: ModifiersState) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, state.shift_key());
+    modifiers.set(Modifiers::CONTROL, state.control_key());
+    modifiers.set(Modifiers::ALT, state.alt_key());
+    modifiers.set(Modifiers::META, state.super_key());
+    modifiers
+}
+
+fn to_blitz_location(location: keyboard::KeyLocation) -> Location {
+    match location {
+        keyboard::KeyLocation::Standard => Location::Standard,
+        keyboard::KeyLocation::Left => Location::Left,
+        keyboard::KeyLocation::Right => Location::Right,
+        keyboard::KeyLocation::Numpad => Location::Numpad,
+    }
+}
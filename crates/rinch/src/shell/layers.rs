@@ -0,0 +1,116 @@
+//! Elements drawn in named layers above the content.
+//!
+//! `rsx!` renders the `layer:` prop as `data-rinch-layer` and `layer_order:`
+//! as `data-rinch-layer-order`. Before a window builds its document, such
+//! elements are taken out of the content and shown in the layer of that
+//! name in the window's [overlay](super::overlay), so they are painted above
+//! everything in the content whatever its `z-index`, stacking contexts and
+//! clipping. Layers are painted in order of their `layer_order` (0 if not
+//! given), and layers of the same order in the order they were first shown.
+
+use super::overlay::Layer;
+
+/// The attribute holding the layer of an element.
+const LAYER_ATTR: &str = "data-rinch-layer";
+
+/// The attribute holding the order of an element's layer.
+const ORDER_ATTR: &str = "data-rinch-layer-order";
+
+/// Elements that never have a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track",
+    "wbr",
+];
+
+/// Take the elements with a layer out of `content`.
+///
+/// Returns the content without them, and their HTML gathered by layer in
+/// the order the layers first appear. An element nested in another with a
+/// layer stays in that one's layer.
+pub(crate) fn extract(content: &str) -> (String, Vec<Layer>) {
+    let marker = format!(" {}=\"", LAYER_ATTR);
+    if !content.contains(&marker) {
+        return (content.to_string(), Vec::new());
+    }
+
+    let mut rest = String::with_capacity(content.len());
+    let mut layers: Vec<Layer> = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = content[pos..].find(&marker) {
+        let attr = pos + found;
+        let Some(start) = content[..attr].rfind('<') else {
+            break;
+        };
+        let Some(end) = element_end(content, start) else {
+            // Not well-formed; leave the rest alone
+            break;
+        };
+        let tag = &content[start..start + content[start..].find('>').unwrap_or(0)];
+        let name = attribute(tag, LAYER_ATTR).unwrap_or_default();
+        let order = attribute(tag, ORDER_ATTR).and_then(|order| order.parse().ok()).unwrap_or(0);
+        let html = &content[start..end];
+        match layers.iter_mut().find(|layer| layer.name == name) {
+            Some(layer) => layer.html.push_str(html),
+            None => layers.push(Layer { name, order, html: html.to_string() }),
+        }
+        rest.push_str(&content[pos..start]);
+        pos = end;
+    }
+    rest.push_str(&content[pos..]);
+    (rest, layers)
+}
+
+/// The value of an attribute in a start tag written by `rsx!`.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let open = format!(" {}=\"", name);
+    let start = tag.find(&open)? + open.len();
+    let len = tag[start..].find('"')?;
+    Some(html_unescape(&tag[start..start + len]))
+}
+
+/// Undo the escaping `rsx!` applies to attribute values.
+fn html_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// The end of the element whose start tag begins at `start`: the index just
+/// past its closing tag.
+fn element_end(content: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut pos = start;
+    loop {
+        let open = pos + content[pos..].find('<')?;
+        let close = open + content[open..].find('>')? + 1;
+        let tag = &content[open + 1..close - 1];
+        if tag.starts_with('/') {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(close);
+            }
+        } else if !tag.starts_with('!') && !tag.ends_with('/') && !is_void(tag) {
+            depth += 1;
+            // Raw text can hold anything but its closing tag
+            let name = tag_name(tag);
+            if matches!(name, "style" | "script" | "textarea" | "title") {
+                let end = format!("</{}>", name);
+                pos = close + content[close..].find(&end)?;
+                continue;
+            }
+        } else if depth == 0 {
+            return Some(close);
+        }
+        pos = close;
+    }
+}
+
+fn tag_name(tag: &str) -> &str {
+    tag.split(|c: char| c.is_whitespace()).next().unwrap_or(tag)
+}
+
+fn is_void(tag: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag_name(tag).to_ascii_lowercase().as_str())
+}
@@ -1,9 +1,38 @@
 //! Shell module - window management and event loop.
 
+pub mod config;
 pub mod devtools;
 pub mod devtools_overlay;
 #[cfg(feature = "hot-reload")]
+pub mod hot_library;
+#[cfg(feature = "hot-reload")]
 pub mod hot_reload;
+mod accessibility;
+mod bindings;
+mod caret;
+mod context_menu;
+mod direction;
+mod edit;
+mod error_overlay;
+mod external;
+mod hit;
+mod keys;
+mod layers;
+mod overlay;
+mod range;
+mod render_diff;
+mod scroll;
+mod segments;
+mod sortable;
+mod spelling;
+mod styles;
+mod timers;
+mod tooltip;
+pub(crate) mod console;
+pub(crate) mod focus;
+#[cfg(feature = "net")]
+pub(crate) mod remote;
+pub(crate) mod resources;
 pub mod runtime;
 pub mod transparent_renderer;
 pub mod window_manager;
@@ -11,8 +40,16 @@ pub mod window_manager;
 pub use devtools::{DevToolsPanel, DevToolsState};
 pub use devtools_overlay::render_overlay;
 #[cfg(feature = "hot-reload")]
+pub use hot_library::{HotLibrary, HotLibraryError, HotLibraryResult};
+#[cfg(feature = "hot-reload")]
 pub use hot_reload::{HotReloadConfig, HotReloader};
-pub use runtime::{run, RinchEvent, Runtime};
+pub use config::RunConfig;
+pub use console::ConsoleLayer;
+pub use external::open;
+pub use runtime::{
+    on_exit, render_to_string, run, run_with_config, run_with_handle, schedule, schedule_repeating,
+    RenderedWindow, RinchEvent, Runtime, TimerId,
+};
 #[cfg(feature = "hot-reload")]
-pub use runtime::run_with_hot_reload;
+pub use runtime::{run_with_hot_library, run_with_hot_reload};
 pub use window_manager::{ManagedWindow, WindowManager};
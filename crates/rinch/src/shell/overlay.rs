@@ -0,0 +1,272 @@
+//! A document drawn above a window's content.
+//!
+//! Transient UI, such as tooltips, toasts, context menus and drag previews,
+//! lives in a small document of its own rather than in the window's
+//! content. Showing, moving or hiding it restyles and lays out only that
+//! document, never the content, and rebuilding the content leaves it
+//! alone. It is painted in a pass of its own after the content, and not at
+//! all while nothing is shown in it.
+//!
+//! The overlay holds named layers of HTML, painted in order of their
+//! [`Layer::order`]: the elements the content puts in a layer with `layer:`
+//! (see [`layers`](super::layers)), the layers added with
+//! [`Overlay::set_layer`], such as the app's toasts and the DevTools
+//! inspector highlight, and the tooltip above them all. It uses the window's
+//! viewport, stylesheets and text direction, and its background is
+//! transparent. Pointer input always goes to the content.
+
+use std::sync::Arc;
+
+use anyrender::PaintScene;
+use blitz_dom::{BaseDocument, Document, DocumentConfig};
+use blitz_html::HtmlDocument;
+use blitz_paint::paint_scene;
+use blitz_traits::shell::Viewport;
+use peniko::kurbo::Affine;
+
+use rinch_core::events::BindingPatch;
+
+use super::bindings;
+use super::caret::Bar;
+use super::focus;
+use super::resources::AssetProvider;
+use super::spelling::Squiggle;
+use super::tooltip::{self, Anchor};
+
+/// Stands for the layers in the page the overlay is built from.
+pub(crate) const LAYERS_MARKER: &str = "<!--rinch-overlay-layers-->";
+
+/// Keeps the content visible through the overlay, whatever the app's
+/// stylesheets give the page.
+const TRANSPARENT_CSS: &str = "<style>html, body { background: transparent !important; }</style>";
+
+/// A named layer of an overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Layer {
+    pub(crate) name: String,
+    /// Layers are painted from the lowest order up, and layers of the same
+    /// order in the order they were added.
+    pub(crate) order: i32,
+    pub(crate) html: String,
+}
+
+/// The overlay of a window.
+pub(crate) struct Overlay {
+    doc: Box<dyn Document>,
+    /// Loads images and fonts the layers use.
+    net_provider: Arc<AssetProvider>,
+    /// The window's stylesheets and direction, wrapped around the layers,
+    /// which go where [`LAYERS_MARKER`] is.
+    page: String,
+    /// The layers of elements taken out of the content.
+    element_layers: Vec<Layer>,
+    /// The layers added with `set_layer`, in the order they were added.
+    layers: Vec<Layer>,
+    /// The text of the tooltip shown and the box it is placed next to.
+    tooltip: Option<(String, Anchor)>,
+    /// The time the overlay was last laid out at, for restyling it after a
+    /// rebuild.
+    animation_time: f64,
+}
+
+impl Overlay {
+    /// Create an empty overlay for a window with `viewport`.
+    pub(crate) fn new(viewport: Viewport, net_provider: &Arc<AssetProvider>) -> Self {
+        let mut overlay = Self {
+            doc: Box::new(HtmlDocument::from_html("", DocumentConfig::default())),
+            net_provider: net_provider.clone(),
+            page: String::from(LAYERS_MARKER),
+            element_layers: Vec::new(),
+            layers: Vec::new(),
+            tooltip: None,
+            animation_time: 0.0,
+        };
+        overlay.rebuild(viewport);
+        overlay
+    }
+
+    /// Take on the window's stylesheets and direction, from a page built
+    /// like the window's document with [`LAYERS_MARKER`] as its content.
+    pub(crate) fn set_page(&mut self, page: String) {
+        if page != self.page {
+            self.page = page;
+            let viewport = self.doc.inner().viewport().clone();
+            self.rebuild(viewport);
+        }
+    }
+
+    /// Show the layers of the elements taken out of the content, in place
+    /// of the ones from before.
+    pub(crate) fn set_element_layers(&mut self, layers: Vec<Layer>) {
+        if layers != self.element_layers {
+            self.element_layers = layers;
+            let viewport = self.doc.inner().viewport().clone();
+            self.rebuild(viewport);
+        }
+    }
+
+    /// Show `html` in the layer called `name`, painted at `order`.
+    pub(crate) fn set_layer(&mut self, name: &str, order: i32, html: String) {
+        match self.layers.iter_mut().find(|layer| layer.name == name) {
+            Some(layer) if layer.order == order && layer.html == html => return,
+            Some(layer) => {
+                layer.order = order;
+                layer.html = html;
+            }
+            None => self.layers.push(Layer { name: name.to_string(), order, html }),
+        }
+        let viewport = self.doc.inner().viewport().clone();
+        self.rebuild(viewport);
+    }
+
+    /// Remove the layer called `name`. Returns `false` if there is none.
+    pub(crate) fn remove_layer(&mut self, name: &str) -> bool {
+        let count = self.layers.len();
+        self.layers.retain(|layer| layer.name != name);
+        if self.layers.len() == count {
+            return false;
+        }
+        let viewport = self.doc.inner().viewport().clone();
+        self.rebuild(viewport);
+        true
+    }
+
+    /// Show a tooltip with `text` next to `anchor`, in place of any other.
+    ///
+    /// Returns `false`, showing nothing, if the tooltip can't be shown.
+    pub(crate) fn show_tooltip(&mut self, text: String, anchor: Anchor, animation_time: f64) -> bool {
+        self.animation_time = animation_time;
+        let shown = tooltip::show(&mut self.doc.inner_mut(), &text, anchor, animation_time);
+        self.tooltip = shown.then_some((text, anchor));
+        shown
+    }
+
+    /// Hide the tooltip.
+    pub(crate) fn hide_tooltip(&mut self) {
+        if self.tooltip.take().is_some() {
+            tooltip::hide(&mut self.doc.inner_mut());
+        }
+    }
+
+    /// Set bound attributes and text in the layers of elements.
+    ///
+    /// Returns `false` if bound text has no text node to update.
+    pub(crate) fn patch_bindings(&mut self, patches: &[BindingPatch]) -> bool {
+        if self.element_layers.is_empty() {
+            return true;
+        }
+        let mut applied = true;
+        let mut inner = self.doc.inner_mut();
+        for patch in patches {
+            applied &= bindings::apply(&mut inner, patch);
+            for layer in &mut self.element_layers {
+                bindings::patch_content(&mut layer.html, patch);
+            }
+        }
+        applied
+    }
+
+    /// The value of the attribute `name` of the element under `x`, `y` (CSS
+    /// pixels from the top left of the window), or of its nearest ancestor
+    /// with one, as the overlay was last laid out.
+    pub(crate) fn attribute_at(&self, x: f32, y: f32, name: &str) -> Option<String> {
+        if !self.is_shown() {
+            return None;
+        }
+        let inner = self.doc.inner();
+        let mut current = Some(inner.hit(x, y)?.node_id);
+        while let Some(node_id) = current {
+            let node = inner.get_node(node_id)?;
+            if let Some(value) = node.element_data().and_then(|element| focus::attr(element, name)) {
+                return Some(value.to_string());
+            }
+            current = node.parent;
+        }
+        None
+    }
+
+    /// Whether anything is shown, so the overlay has to be painted.
+    fn is_shown(&self) -> bool {
+        self.tooltip.is_some() || !self.layers.is_empty() || !self.element_layers.is_empty()
+    }
+
+    /// Lay the overlay out in the content's `viewport` and call `f` with it,
+    /// or with `None` if nothing is shown.
+    pub(crate) fn prepare<R>(
+        &mut self,
+        viewport: &Viewport,
+        animation_time: f64,
+        f: impl FnOnce(Option<&BaseDocument>) -> R,
+    ) -> R {
+        if !self.is_shown() {
+            return f(None);
+        }
+        self.animation_time = animation_time;
+        let mut inner = self.doc.inner_mut();
+        let current = inner.viewport();
+        let changed = current.window_size != viewport.window_size
+            || current.scale() != viewport.scale()
+            || current.color_scheme != viewport.color_scheme;
+        if changed {
+            *inner.viewport_mut() = viewport.clone();
+        }
+        inner.resolve(animation_time);
+        f(Some(&*inner))
+    }
+
+    fn rebuild(&mut self, viewport: Viewport) {
+        let mut ordered: Vec<&Layer> = self.element_layers.iter().chain(&self.layers).collect();
+        ordered.sort_by_key(|layer| layer.order);
+        let mut layers = String::from(TRANSPARENT_CSS);
+        for layer in ordered {
+            layers.push_str(&layer.html);
+        }
+        layers.push_str(&tooltip::layer_html());
+        let html = self.page.replacen(LAYERS_MARKER, &layers, 1);
+        let config = DocumentConfig {
+            viewport: Some(viewport),
+            base_url: crate::assets::base_url(),
+            net_provider: Some(self.net_provider.clone()),
+            ..Default::default()
+        };
+        self.doc = Box::new(HtmlDocument::from_html(&html, config));
+        if let Some((text, anchor)) = self.tooltip.take() {
+            self.show_tooltip(text, anchor, self.animation_time);
+        }
+    }
+}
+
+/// What a window draws over its content, beneath the overlay.
+pub(crate) struct Marks {
+    /// The caret of the focused text input, if it shows.
+    pub(crate) caret: Option<Bar>,
+    /// The lines under misspelled words.
+    pub(crate) misspellings: Vec<Squiggle>,
+}
+
+/// Paint a window: its content, the marks over it, then the overlay if
+/// anything is shown.
+pub(crate) fn paint(
+    scene: &mut impl PaintScene,
+    content: &BaseDocument,
+    marks: &Marks,
+    overlay: Option<&BaseDocument>,
+    scale: f64,
+    width: u32,
+    height: u32,
+) {
+    paint_scene(scene, content, scale, width, height);
+    for squiggle in &marks.misspellings {
+        squiggle.paint(scene, scale);
+    }
+    if let Some(caret) = &marks.caret {
+        caret.paint(scene, scale);
+    }
+    if let Some(overlay) = overlay {
+        // Painting a document starts a new scene, so the overlay is
+        // recorded separately and added on top
+        let mut layer = anyrender::Scene::new();
+        paint_scene(&mut layer, overlay, scale, width, height);
+        scene.append_scene(layer, Affine::IDENTITY);
+    }
+}
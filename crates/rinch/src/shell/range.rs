@@ -0,0 +1,249 @@
+//! Range inputs (`input type="range"`).
+//!
+//! Blitz has no native slider, so rinch draws one with CSS and handles the
+//! dragging itself. The window stores the value's position along the track
+//! (0 to 1) in the `--rinch-range` custom property on the element's inline
+//! style, which the default stylesheet uses to place the thumb and fill.
+
+use blitz_dom::{local_name, ns, BaseDocument, ElementData, QualName};
+
+use super::focus::attr;
+
+/// Custom property holding the position of the value along the track.
+const POSITION_PROPERTY: &str = "--rinch-range";
+
+/// The value and limits of a range input, from its attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RangeInput {
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+    value: f64,
+}
+
+impl RangeInput {
+    /// Read a range input's attributes, or `None` if the element isn't one.
+    ///
+    /// Like HTML, `min` defaults to 0, `max` to 100, `step` to 1 (`step="any"`
+    /// allows any value) and `value` to halfway between `min` and `max`.
+    pub(crate) fn from_element(element: &ElementData) -> Option<Self> {
+        if element.name.local.as_ref() != "input"
+            || !attr(element, "type").is_some_and(|t| t.eq_ignore_ascii_case("range"))
+        {
+            return None;
+        }
+
+        let number = |name| attr(element, name).and_then(|v| v.trim().parse::<f64>().ok());
+        let min = number("min").unwrap_or(0.0);
+        let max = number("max").unwrap_or(100.0).max(min);
+        let step = parse_step(attr(element, "step"));
+        let value = number("value").unwrap_or(min + (max - min) / 2.0);
+
+        let mut input = Self { min, max, step, value };
+        input.value = input.snap(value);
+        Some(input)
+    }
+
+    /// The value's position along the track, from 0 to 1.
+    pub(crate) fn position(&self) -> f64 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+
+    /// Set the value from a position along the track, returning whether it changed.
+    pub(crate) fn set_position(&mut self, position: f64) -> bool {
+        let value = self.snap(self.min + position.clamp(0.0, 1.0) * (self.max - self.min));
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+
+    /// The value as it appears in the `value` attribute and `oninput`.
+    pub(crate) fn value_string(&self) -> String {
+        let decimals = self.step.map_or(6, |step| decimals(step).max(decimals(self.min)));
+        let text = format!("{:.*}", decimals, self.value);
+        if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Clamp a value to the limits and round it to the nearest step.
+    fn snap(&self, value: f64) -> f64 {
+        let value = value.clamp(self.min, self.max);
+        let Some(step) = self.step else {
+            return value;
+        };
+        let snapped = self.min + ((value - self.min) / step).round() * step;
+        // Rounding up can overshoot a max that isn't on a step
+        if snapped > self.max {
+            snapped - step
+        } else {
+            snapped
+        }
+    }
+}
+
+/// Parse a `step` attribute: `None` for `any`, and 1 if it's missing, not a
+/// number or not positive.
+fn parse_step(step: Option<&str>) -> Option<f64> {
+    match step.map(str::trim) {
+        Some(step) if step.eq_ignore_ascii_case("any") => None,
+        step => Some(step.and_then(|s| s.parse::<f64>().ok()).filter(|step| *step > 0.0).unwrap_or(1.0)),
+    }
+}
+
+/// Write the value and its position to a range input in the document.
+pub(crate) fn apply(doc: &mut BaseDocument, node_id: usize, input: &RangeInput) {
+    let Some(style) = doc
+        .get_node(node_id)
+        .and_then(|node| node.element_data())
+        .map(|element| position_style(attr(element, "style").unwrap_or_default(), input))
+    else {
+        return;
+    };
+
+    let value = QualName::new(None, ns!(), local_name!("value"));
+    let style_attr = QualName::new(None, ns!(), local_name!("style"));
+    let mut mutator = doc.mutate();
+    mutator.set_attribute(node_id, value, &input.value_string());
+    mutator.set_attribute(node_id, style_attr, &style);
+}
+
+/// Give every range input in a new document the position of its value.
+pub(crate) fn init_all(doc: &mut BaseDocument) {
+    let node_ids = doc.query_selector_all("input[type=range]").unwrap_or_default();
+    for node_id in node_ids {
+        let input = doc
+            .get_node(node_id)
+            .and_then(|node| node.element_data())
+            .and_then(RangeInput::from_element);
+        if let Some(input) = input {
+            apply(doc, node_id, &input);
+        }
+    }
+}
+
+/// Replace the position property in an inline style.
+fn position_style(style: &str, input: &RangeInput) -> String {
+    let mut declarations: Vec<&str> = style
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && !d.starts_with(POSITION_PROPERTY))
+        .collect();
+    let position = format!("{}: {:.4}", POSITION_PROPERTY, input.position());
+    declarations.push(&position);
+    declarations.join("; ")
+}
+
+/// The number of digits after the decimal point, as written.
+fn decimals(number: f64) -> usize {
+    let text = number.to_string();
+    text.split_once('.').map_or(0, |(_, fraction)| fraction.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: f64, max: f64, step: Option<f64>, value: f64) -> RangeInput {
+        let mut input = RangeInput { min, max, step, value };
+        input.value = input.snap(value);
+        input
+    }
+
+    #[test]
+    fn values_snap_to_steps_from_min() {
+        let input = range(0.5, 10.0, Some(1.0), 0.7);
+        assert_eq!(input.value, 0.5);
+        assert_eq!(range(0.5, 10.0, Some(1.0), 2.0).value, 2.5);
+        assert_eq!(range(0.0, 10.0, Some(2.5), 6.0).value, 5.0);
+    }
+
+    #[test]
+    fn values_clamp_to_limits() {
+        assert_eq!(range(0.0, 10.0, Some(1.0), -3.0).value, 0.0);
+        assert_eq!(range(0.0, 10.0, Some(1.0), 42.0).value, 10.0);
+        assert_eq!(range(0.0, 10.0, None, 10.5).value, 10.0);
+    }
+
+    #[test]
+    fn max_off_step_snaps_to_last_step() {
+        let mut input = range(0.0, 10.0, Some(3.0), 0.0);
+        assert!(input.set_position(1.0));
+        assert_eq!(input.value, 9.0);
+        // 9.6 rounds to 12, past max
+        assert!(!input.set_position(0.96));
+        assert_eq!(input.value, 9.0);
+        assert!(input.set_position(0.0));
+        assert_eq!(input.value, 0.0);
+    }
+
+    #[test]
+    fn set_position_clamps_and_reports_changes() {
+        let mut input = range(0.0, 100.0, Some(1.0), 50.0);
+        assert!(!input.set_position(0.501));
+        assert!(input.set_position(-1.0));
+        assert_eq!(input.value, 0.0);
+        assert!(input.set_position(2.0));
+        assert_eq!(input.value, 100.0);
+    }
+
+    #[test]
+    fn negative_ranges() {
+        let mut input = range(-10.0, 10.0, Some(5.0), 0.0);
+        assert_eq!(input.position(), 0.5);
+        assert!(input.set_position(0.3));
+        assert_eq!(input.value, -5.0);
+        assert_eq!(input.position(), 0.25);
+        assert_eq!(input.value_string(), "-5");
+
+        let below_zero = range(-20.0, -10.0, Some(0.5), -12.3);
+        assert_eq!(below_zero.value, -12.5);
+        assert_eq!(below_zero.value_string(), "-12.5");
+        assert_eq!(below_zero.position(), 0.75);
+    }
+
+    #[test]
+    fn empty_range_is_at_start() {
+        let mut input = range(5.0, 5.0, Some(1.0), 7.0);
+        assert_eq!(input.value, 5.0);
+        assert_eq!(input.position(), 0.0);
+        assert!(!input.set_position(1.0));
+    }
+
+    #[test]
+    fn step_attribute() {
+        assert_eq!(parse_step(None), Some(1.0));
+        assert_eq!(parse_step(Some("0.25")), Some(0.25));
+        assert_eq!(parse_step(Some(" ANY ")), None);
+        // Zero, negative and invalid steps fall back to 1, as in HTML
+        assert_eq!(parse_step(Some("0")), Some(1.0));
+        assert_eq!(parse_step(Some("-2")), Some(1.0));
+        assert_eq!(parse_step(Some("big")), Some(1.0));
+    }
+
+    #[test]
+    fn value_strings_use_the_step_precision() {
+        let mut input = range(0.0, 1.0, Some(0.1), 0.0);
+        input.set_position(0.3);
+        assert_eq!(input.value_string(), "0.3");
+        assert_eq!(range(0.0, 100.0, Some(1.0), 42.0).value_string(), "42");
+        assert_eq!(range(0.0, 1.0, None, 1.0 / 3.0).value_string(), "0.333333");
+        assert_eq!(range(0.05, 1.0, Some(0.5), 0.6).value_string(), "0.55");
+    }
+
+    #[test]
+    fn position_replaces_the_custom_property() {
+        let input = range(0.0, 4.0, Some(1.0), 1.0);
+        assert_eq!(position_style("", &input), "--rinch-range: 0.2500");
+        assert_eq!(
+            position_style("width: 80px; --rinch-range: 0.9;color: red", &input),
+            "width: 80px; color: red; --rinch-range: 0.2500"
+        );
+    }
+}
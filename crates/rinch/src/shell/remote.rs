@@ -0,0 +1,319 @@
+//! Fetching remote (`http`/`https`) resources for documents.
+//!
+//! Requests run on a small background tokio runtime. Responses are kept in a
+//! size-limited cache, so documents rebuilt on re-render get their images and
+//! stylesheets back immediately instead of downloading them again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex, OnceLock};
+
+use blitz_dom::net::Resource;
+use blitz_traits::net::{BoxedHandler, Bytes, SharedCallback, Url};
+
+/// Default total size of cached responses.
+const DEFAULT_CACHE_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Number of worker threads used for downloads.
+const WORKER_THREADS: usize = 2;
+
+/// Downloaded responses, evicting the least recently used when over the limit.
+struct ResponseCache {
+    /// Each URL's response and the tick it was last used at.
+    entries: HashMap<String, (Bytes, u64)>,
+    /// URLs by the tick they were last used at, least recent first.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    size: usize,
+    limit: usize,
+}
+
+impl ResponseCache {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            size: 0,
+            limit: DEFAULT_CACHE_LIMIT,
+        }
+    }
+
+    fn get(&mut self, url: &str) -> Option<Bytes> {
+        self.tick += 1;
+        let (bytes, used) = self.entries.get_mut(url)?;
+        let url = self.order.remove(&*used).expect("cached URL has a use tick");
+        *used = self.tick;
+        self.order.insert(self.tick, url);
+        Some(bytes.clone())
+    }
+
+    fn insert(&mut self, url: String, bytes: Bytes) {
+        // Responses larger than the whole cache aren't worth keeping
+        if bytes.len() > self.limit {
+            return;
+        }
+        self.remove(&url);
+        self.tick += 1;
+        self.size += bytes.len();
+        self.order.insert(self.tick, url.clone());
+        self.entries.insert(url, (bytes, self.tick));
+        self.evict();
+    }
+
+    fn remove(&mut self, url: &str) {
+        if let Some((bytes, used)) = self.entries.remove(url) {
+            self.size -= bytes.len();
+            self.order.remove(&used);
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.limit
+            && let Some((_, oldest)) = self.order.pop_first()
+        {
+            if let Some((bytes, _)) = self.entries.remove(&oldest) {
+                self.size -= bytes.len();
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+}
+
+/// Downloads in progress, with everyone waiting for each one.
+struct InFlight<W> {
+    waiting: HashMap<String, Vec<W>>,
+}
+
+impl<W> InFlight<W> {
+    fn new() -> Self {
+        Self { waiting: HashMap::new() }
+    }
+
+    /// Wait for `url`, returning `true` if nothing was downloading it yet
+    /// and the caller should start the download.
+    fn join(&mut self, url: &str, waiter: W) -> bool {
+        match self.waiting.get_mut(url) {
+            Some(waiters) => {
+                waiters.push(waiter);
+                false
+            }
+            None => {
+                self.waiting.insert(url.to_string(), vec![waiter]);
+                true
+            }
+        }
+    }
+
+    /// End the download of `url`, returning who was waiting for it.
+    fn finish(&mut self, url: &str) -> Vec<W> {
+        self.waiting.remove(url).unwrap_or_default()
+    }
+}
+
+/// A document waiting for a download.
+struct Waiter {
+    doc_id: usize,
+    handler: BoxedHandler<Resource>,
+    callback: SharedCallback<Resource>,
+}
+
+// Lock IN_FLIGHT before CACHE, so a download can't complete between a cache
+// miss and joining it
+static IN_FLIGHT: LazyLock<Mutex<InFlight<Waiter>>> = LazyLock::new(|| Mutex::new(InFlight::new()));
+
+static CACHE: LazyLock<Mutex<ResponseCache>> = LazyLock::new(|| Mutex::new(ResponseCache::new()));
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+static RUNTIME: OnceLock<Option<tokio::runtime::Runtime>> = OnceLock::new();
+
+/// The runtime downloads run on, created on first use.
+fn runtime() -> Option<&'static tokio::runtime::Runtime> {
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(WORKER_THREADS)
+                .thread_name("rinch-net")
+                .enable_all()
+                .build()
+                .inspect_err(|e| tracing::error!("Failed to start network runtime: {}", e))
+                .ok()
+        })
+        .as_ref()
+}
+
+/// Set the maximum total size of cached responses, evicting entries if needed.
+pub(crate) fn set_cache_limit(bytes: usize) {
+    let mut cache = CACHE.lock().unwrap();
+    cache.limit = bytes;
+    cache.evict();
+}
+
+/// Remove all cached responses.
+pub(crate) fn clear_cache() {
+    CACHE.lock().unwrap().clear();
+}
+
+/// Fetch a remote resource, from the cache if possible.
+///
+/// Cached responses are handled immediately; others are downloaded in the
+/// background and delivered to the callback when they complete. Requests for
+/// a URL that is already downloading wait for that download.
+pub(crate) fn fetch(
+    doc_id: usize,
+    url: Url,
+    handler: BoxedHandler<Resource>,
+    callback: SharedCallback<Resource>,
+) {
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    let cached = CACHE.lock().unwrap().get(url.as_str());
+    if let Some(bytes) = cached {
+        drop(in_flight);
+        handler.bytes(doc_id, bytes, callback);
+        return;
+    }
+
+    let Some(runtime) = runtime() else {
+        drop(in_flight);
+        callback.call(doc_id, Err(Some(format!("{}: network runtime unavailable", url))));
+        return;
+    };
+
+    let waiter = Waiter {
+        doc_id,
+        handler,
+        callback,
+    };
+    if !in_flight.join(url.as_str(), waiter) {
+        return;
+    }
+    drop(in_flight);
+
+    runtime.spawn(async move {
+        let result = download(&url).await;
+
+        let waiters = {
+            let mut in_flight = IN_FLIGHT.lock().unwrap();
+            if let Ok(bytes) = &result {
+                CACHE.lock().unwrap().insert(url.to_string(), bytes.clone());
+            }
+            in_flight.finish(url.as_str())
+        };
+
+        for waiter in waiters {
+            match &result {
+                Ok(bytes) => waiter.handler.bytes(waiter.doc_id, bytes.clone(), waiter.callback),
+                Err(e) => waiter.callback.call(waiter.doc_id, Err(Some(format!("{}: {}", url, e)))),
+            }
+        }
+    });
+}
+
+async fn download(url: &Url) -> reqwest::Result<Bytes> {
+    let response = CLIENT.get(url.as_str()).send().await?.error_for_status()?;
+    let bytes = response.bytes().await?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(limit: usize) -> ResponseCache {
+        ResponseCache {
+            limit,
+            ..ResponseCache::new()
+        }
+    }
+
+    fn bytes(len: usize) -> Bytes {
+        Bytes::from(vec![0; len])
+    }
+
+    #[test]
+    fn evicts_least_recently_inserted_first() {
+        let mut cache = cache(3);
+        cache.insert("a".into(), bytes(1));
+        cache.insert("b".into(), bytes(1));
+        cache.insert("c".into(), bytes(1));
+        cache.insert("d".into(), bytes(1));
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+        assert_eq!(cache.size, 3);
+    }
+
+    #[test]
+    fn get_marks_entries_as_recently_used() {
+        let mut cache = cache(3);
+        cache.insert("a".into(), bytes(1));
+        cache.insert("b".into(), bytes(1));
+        cache.insert("c".into(), bytes(1));
+        cache.get("a");
+        cache.insert("d".into(), bytes(1));
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn evicts_until_under_the_limit() {
+        let mut cache = cache(4);
+        cache.insert("a".into(), bytes(2));
+        cache.insert("b".into(), bytes(2));
+        cache.insert("c".into(), bytes(3));
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.size, 3);
+        assert_eq!(cache.order.len(), 1);
+    }
+
+    #[test]
+    fn replacing_an_entry_updates_the_size() {
+        let mut cache = cache(10);
+        cache.insert("a".into(), bytes(4));
+        cache.insert("a".into(), bytes(2));
+        assert_eq!(cache.size, 2);
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.order.len(), 1);
+    }
+
+    #[test]
+    fn skips_responses_larger_than_the_cache() {
+        let mut cache = cache(2);
+        cache.insert("a".into(), bytes(1));
+        cache.insert("big".into(), bytes(3));
+        assert!(cache.get("big").is_none());
+        assert!(cache.get("a").is_some());
+    }
+
+    #[test]
+    fn lowering_the_limit_evicts() {
+        let mut cache = cache(3);
+        cache.insert("a".into(), bytes(1));
+        cache.insert("b".into(), bytes(1));
+        cache.insert("c".into(), bytes(1));
+        cache.limit = 1;
+        cache.evict();
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.size, 1);
+    }
+
+    #[test]
+    fn concurrent_requests_share_one_download() {
+        let mut in_flight = InFlight::new();
+        assert!(in_flight.join("a", 1));
+        assert!(!in_flight.join("a", 2));
+        assert!(in_flight.join("b", 3));
+        assert_eq!(in_flight.finish("a"), [1, 2]);
+        assert!(in_flight.finish("a").is_empty());
+        // Once finished, the next request downloads again
+        assert!(in_flight.join("a", 4));
+        assert_eq!(in_flight.finish("b"), [3]);
+    }
+}
@@ -0,0 +1,442 @@
+//! What changed each render, for the DevTools Renders panel.
+//!
+//! While the DevTools window is open, the runtime compares the HTML each
+//! window had before a render with the HTML the render generated, and
+//! records the changed lines with the signals that made the render
+//! necessary. Lines are compared after putting every tag and text on a line
+//! of its own, so a change shows as the elements it touched rather than as
+//! one long changed line.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+
+use rinch_core::events::html_escape_string;
+use rinch_core::hooks::hook_of_signal;
+use rinch_core::SignalId;
+
+/// How many renders are kept; older ones are dropped.
+const MAX_RENDERS: usize = 20;
+
+/// How many unchanged lines are shown around each change.
+const CONTEXT_LINES: usize = 2;
+
+/// How many lines are shown for each window; the rest are counted.
+const MAX_LINES: usize = 200;
+
+/// Above this many lines (old times new) between the first and last change,
+/// the lines are shown as all removed and all added instead of being
+/// matched up.
+const MAX_MATCHED: usize = 250_000;
+
+/// Elements that have no closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// A line of the compared HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DiffLine {
+    /// In both the old and the new HTML.
+    Same(String),
+    /// Only in the old HTML.
+    Removed(String),
+    /// Only in the new HTML.
+    Added(String),
+    /// Unchanged lines left out, and how many.
+    Skipped(usize),
+}
+
+/// The changes one render made to a window's HTML.
+struct WindowChanges {
+    title: String,
+    lines: Vec<DiffLine>,
+}
+
+/// A recorded render.
+struct Render {
+    /// Counts the renders recorded since the app started.
+    number: u64,
+    /// Why the app rendered, one entry per signal.
+    triggers: Vec<String>,
+    /// The windows whose HTML changed.
+    windows: Vec<WindowChanges>,
+}
+
+thread_local! {
+    /// Recorded renders, oldest first.
+    static RENDERS: RefCell<VecDeque<Render>> = const { RefCell::new(VecDeque::new()) };
+    /// The number of the last recorded render.
+    static COUNT: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Compares the HTML of the windows of one render, created by [`begin`].
+pub(crate) struct RenderRecorder {
+    triggers: Vec<String>,
+    windows: Vec<WindowChanges>,
+}
+
+/// Start recording a render made necessary by changes to `triggers`.
+pub(crate) fn begin(triggers: &[SignalId]) -> RenderRecorder {
+    let mut labels = Vec::new();
+    let mut unhooked = 0;
+    for &signal in triggers {
+        match hook_of_signal(signal) {
+            Some(index) => labels.push(format!("use_signal #{}", index)),
+            None => unhooked += 1,
+        }
+    }
+    match unhooked {
+        0 => {}
+        1 => labels.push("a signal not held by a use_signal hook".to_string()),
+        n => labels.push(format!("{} signals not held by use_signal hooks", n)),
+    }
+    RenderRecorder {
+        triggers: labels,
+        windows: Vec::new(),
+    }
+}
+
+impl RenderRecorder {
+    /// Compare a window's HTML before and after the render.
+    pub(crate) fn window(&mut self, title: &str, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        let lines = collapse(diff(&format_html(old), &format_html(new)));
+        self.windows.push(WindowChanges {
+            title: title.to_string(),
+            lines,
+        });
+    }
+
+    /// Keep the render for the Renders panel.
+    pub(crate) fn finish(self) {
+        let number = COUNT.get() + 1;
+        COUNT.set(number);
+        RENDERS.with(|renders| {
+            let mut renders = renders.borrow_mut();
+            if renders.len() == MAX_RENDERS {
+                renders.pop_front();
+            }
+            renders.push_back(Render {
+                number,
+                triggers: self.triggers,
+                windows: self.windows,
+            });
+        });
+    }
+}
+
+/// Put every tag and every text of `html` on a line of its own, indented by
+/// its depth.
+fn format_html(html: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut rest = html;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = rest[..end].trim();
+            if !text.is_empty() {
+                lines.push(format!("{}{}", "  ".repeat(depth), text));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        // The end of the tag, skipping `>` in quoted attribute values
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| match quote {
+                Some(q) => {
+                    if c == q {
+                        quote = None;
+                    }
+                    false
+                }
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    false
+                }
+                None => c == '>',
+            })
+            .map_or(rest.len(), |(i, _)| i + 1);
+        let tag = &rest[..end];
+        rest = &rest[end..];
+
+        if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            lines.push(format!("{}{}", "  ".repeat(depth), tag));
+            continue;
+        }
+        lines.push(format!("{}{}", "  ".repeat(depth), tag));
+        let name = tag[1..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let opens = !name.is_empty() && !tag.ends_with("/>") && !VOID_ELEMENTS.contains(&name.as_str());
+        if opens {
+            depth += 1;
+        }
+    }
+    lines
+}
+
+/// Compare two lists of lines.
+fn diff(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_middle, new_middle) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().cloned().map(DiffLine::Same).collect();
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_MATCHED {
+        lines.extend(matched(old_middle, new_middle));
+    } else {
+        lines.extend(old_middle.iter().cloned().map(DiffLine::Removed));
+        lines.extend(new_middle.iter().cloned().map(DiffLine::Added));
+    }
+    lines.extend(old[old.len() - suffix..].iter().cloned().map(DiffLine::Same));
+    lines
+}
+
+/// Compare two lists of lines by their longest common subsequence.
+fn matched(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    // common[i][j]: the length of the longest common subsequence of
+    // old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut common = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i * width + j] = if old[i] == new[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if common[(i + 1) * width + j] >= common[i * width + j + 1] {
+            lines.push(DiffLine::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().cloned().map(DiffLine::Removed));
+    lines.extend(new[j..].iter().cloned().map(DiffLine::Added));
+    lines
+}
+
+/// Leave out the unchanged lines further than [`CONTEXT_LINES`] from a
+/// change.
+fn collapse(lines: Vec<DiffLine>) -> Vec<DiffLine> {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| {
+        let first = changed.partition_point(|&c| c + CONTEXT_LINES < i);
+        changed.get(first).is_some_and(|&c| c <= i + CONTEXT_LINES)
+    };
+
+    let mut collapsed = Vec::new();
+    for (i, line) in lines.into_iter().enumerate() {
+        if near_change(i) {
+            collapsed.push(line);
+        } else if let Some(DiffLine::Skipped(count)) = collapsed.last_mut() {
+            *count += 1;
+        } else {
+            collapsed.push(DiffLine::Skipped(1));
+        }
+    }
+    collapsed
+}
+
+/// Generate the Renders panel: the recorded renders, latest first.
+pub(crate) fn renders_html() -> String {
+    RENDERS.with(|renders| {
+        let renders = renders.borrow();
+        if renders.is_empty() {
+            return r#"<p style="color: #808080;">No renders recorded yet. Renders are recorded while DevTools is open.</p>"#
+                .to_string();
+        }
+
+        let mut html = String::new();
+        for render in renders.iter().rev() {
+            let _ = write!(
+                html,
+                r#"<div style="margin-bottom: 12px; padding: 8px; background: #252526; border-radius: 4px;"><div style="color: #dcdcaa; font-weight: bold;">Render #{}</div>"#,
+                render.number
+            );
+            if render.triggers.is_empty() {
+                html.push_str(
+                    r#"<div style="color: #808080;">No signal read by the render changed: an event handler, plugin or the runtime asked for it.</div>"#,
+                );
+            } else {
+                let _ = write!(
+                    html,
+                    r#"<div style="color: #808080;">Changed: <span style="color: #9cdcfe;">{}</span></div>"#,
+                    html_escape_string(&render.triggers.join(", "))
+                );
+            }
+            if render.windows.is_empty() {
+                html.push_str(r#"<div style="color: #808080;">The HTML didn't change.</div>"#);
+            }
+            for window in &render.windows {
+                let _ = write!(
+                    html,
+                    r#"<div style="margin-top: 6px; color: #4ec9b0;">{}</div><div style="font-size: 11px; white-space: pre; overflow-x: auto;">"#,
+                    html_escape_string(&window.title)
+                );
+                for line in window.lines.iter().take(MAX_LINES) {
+                    let (style, sign, text) = match line {
+                        DiffLine::Same(text) => ("color: #808080;", ' ', text.as_str()),
+                        DiffLine::Removed(text) => ("color: #f48771; background: #4b1818;", '-', text.as_str()),
+                        DiffLine::Added(text) => ("color: #b5cea8; background: #1e3a1e;", '+', text.as_str()),
+                        DiffLine::Skipped(count) => {
+                            let _ = write!(
+                                html,
+                                r#"<div style="color: #569cd6;">  … {} unchanged line{}</div>"#,
+                                count,
+                                if *count == 1 { "" } else { "s" }
+                            );
+                            continue;
+                        }
+                    };
+                    let _ = write!(
+                        html,
+                        r#"<div style="{}">{} {}</div>"#,
+                        style,
+                        sign,
+                        html_escape_string(text)
+                    );
+                }
+                if window.lines.len() > MAX_LINES {
+                    let _ = write!(
+                        html,
+                        r#"<div style="color: #808080;">… {} more lines</div>"#,
+                        window.lines.len() - MAX_LINES
+                    );
+                }
+                html.push_str("</div>");
+            }
+            html.push_str("</div>");
+        }
+        html
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn same(line: &str) -> DiffLine {
+        DiffLine::Same(line.to_string())
+    }
+
+    fn removed(line: &str) -> DiffLine {
+        DiffLine::Removed(line.to_string())
+    }
+
+    fn added(line: &str) -> DiffLine {
+        DiffLine::Added(line.to_string())
+    }
+
+    #[test]
+    fn html_is_formatted_a_tag_or_text_per_line() {
+        let html = r#"<div title="a > b"><p>Hi <b>you</b></p><br><img src="x" /> <!--rbind:1-->3<!--/rbind--></div>"#;
+        assert_eq!(
+            format_html(html),
+            lines(&[
+                r#"<div title="a > b">"#,
+                "  <p>",
+                "    Hi",
+                "    <b>",
+                "      you",
+                "    </b>",
+                "  </p>",
+                "  <br>",
+                r#"  <img src="x" />"#,
+                "  <!--rbind:1-->",
+                "  3",
+                "  <!--/rbind-->",
+                "</div>",
+            ])
+        );
+    }
+
+    #[test]
+    fn insertions_and_deletions_keep_the_lines_around_them() {
+        let old = lines(&["a", "b", "c"]);
+        let new = lines(&["a", "x", "b", "c"]);
+        assert_eq!(diff(&old, &new), [same("a"), added("x"), same("b"), same("c")]);
+        assert_eq!(diff(&new, &old), [same("a"), removed("x"), same("b"), same("c")]);
+        assert_eq!(diff(&old, &[]), [removed("a"), removed("b"), removed("c")]);
+    }
+
+    #[test]
+    fn changed_lines_are_removed_then_added() {
+        let old = lines(&["<ul>", "a", "b", "c", "</ul>"]);
+        let new = lines(&["<ul>", "a", "B", "c", "d", "</ul>"]);
+        assert_eq!(
+            diff(&old, &new),
+            [same("<ul>"), same("a"), removed("b"), added("B"), same("c"), added("d"), same("</ul>")]
+        );
+    }
+
+    #[test]
+    fn large_changes_are_not_matched_up() {
+        let old: Vec<String> = (0..600).map(|i| i.to_string()).collect();
+        let new: Vec<String> = (0..600).map(|i| (i * 2).to_string()).collect();
+        let lines = diff(&old, &new);
+        // Both start with "0"
+        assert_eq!(lines[0], same("0"));
+        assert!(lines[1..600].iter().all(|line| matches!(line, DiffLine::Removed(_))));
+        assert!(lines[600..].iter().all(|line| matches!(line, DiffLine::Added(_))));
+    }
+
+    #[test]
+    fn unchanged_lines_far_from_changes_collapse() {
+        let mut lines: Vec<DiffLine> = (0..12).map(|i| same(&i.to_string())).collect();
+        lines[5] = added("x");
+        lines.push(removed("y"));
+        assert_eq!(
+            collapse(lines),
+            [
+                DiffLine::Skipped(3),
+                same("3"),
+                same("4"),
+                added("x"),
+                same("6"),
+                same("7"),
+                DiffLine::Skipped(2),
+                same("10"),
+                same("11"),
+                removed("y"),
+            ]
+        );
+        assert_eq!(collapse(vec![same("a"), same("b")]), [DiffLine::Skipped(2)]);
+    }
+}
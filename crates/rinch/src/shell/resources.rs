@@ -0,0 +1,117 @@
+//! Resource loading for blitz documents.
+//!
+//! Documents request images, stylesheets and fonts through a net provider,
+//! which serves them from custom protocol handlers, embedded assets and the
+//! assets directory. Loaded resources are queued per window and applied to
+//! the document the next time the window is polled. Remote resources need
+//! the `net` feature.
+
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+use blitz_dom::net::Resource;
+use blitz_traits::net::{BoxedHandler, Bytes, NetCallback, NetProvider, Request, SharedCallback};
+use winit::event_loop::EventLoopProxy;
+use winit::window::WindowId;
+
+use super::runtime::RinchEvent;
+use crate::assets::AssetSource;
+
+/// Resources loaded for a window, tagged with the ID of the requesting document.
+pub(crate) type ResourceQueue = Arc<Mutex<Vec<(usize, Resource)>>>;
+
+/// Queues loaded resources and wakes the window to apply them.
+struct WindowCallback {
+    queue: ResourceQueue,
+    proxy: EventLoopProxy<RinchEvent>,
+    window_id: WindowId,
+}
+
+impl NetCallback<Resource> for WindowCallback {
+    fn call(&self, doc_id: usize, result: Result<Resource, Option<String>>) {
+        match result {
+            Ok(resource) => {
+                self.queue.lock().unwrap().push((doc_id, resource));
+                let _ = self.proxy.send_event(RinchEvent::Poll {
+                    window_id: self.window_id,
+                });
+            }
+            Err(Some(msg)) => tracing::warn!("Failed to load resource: {}", msg),
+            Err(None) => tracing::warn!("Failed to load resource"),
+        }
+    }
+}
+
+/// Net provider serving local and embedded assets to a window's documents.
+pub(crate) struct AssetProvider {
+    callback: SharedCallback<Resource>,
+}
+
+impl AssetProvider {
+    /// Create a provider for a window, returning it with the queue it fills.
+    pub(crate) fn new(
+        proxy: EventLoopProxy<RinchEvent>,
+        window_id: WindowId,
+    ) -> (Arc<Self>, ResourceQueue) {
+        let queue = ResourceQueue::default();
+        let callback = Arc::new(WindowCallback {
+            queue: queue.clone(),
+            proxy,
+            window_id,
+        });
+        (Arc::new(Self { callback }), queue)
+    }
+}
+
+impl NetProvider<Resource> for AssetProvider {
+    fn fetch(&self, doc_id: usize, request: Request, handler: BoxedHandler<Resource>) {
+        if let Some(response) = crate::assets::handle_protocol(&request.url) {
+            if !response.is_success() {
+                let msg = format!("{}: status {}", request.url, response.status());
+                self.callback.call(doc_id, Err(Some(msg)));
+                return;
+            }
+            handler.bytes(doc_id, to_bytes(response.into_body()), self.callback.clone());
+            return;
+        }
+
+        if matches!(request.url.scheme(), "http" | "https") {
+            #[cfg(feature = "net")]
+            super::remote::fetch(doc_id, request.url, handler, self.callback.clone());
+            #[cfg(not(feature = "net"))]
+            tracing::warn!(
+                "Can't load {}: enable the `net` feature to load remote resources",
+                request.url
+            );
+            return;
+        }
+
+        let Some(source) = crate::assets::resolve_url(&request.url) else {
+            tracing::warn!("Can't load {}: unsupported URL scheme", request.url);
+            return;
+        };
+
+        // Local assets are loaded synchronously. Documents are rebuilt on every
+        // re-render, so this keeps images from flickering while they reload.
+        let bytes = match source {
+            AssetSource::Embedded(bytes) => to_bytes(bytes),
+            AssetSource::File(path) => match std::fs::read(&path) {
+                Ok(bytes) => Bytes::from(bytes),
+                Err(e) => {
+                    self.callback
+                        .call(doc_id, Err(Some(format!("{}: {}", path.display(), e))));
+                    return;
+                }
+            },
+        };
+        handler.bytes(doc_id, bytes, self.callback.clone());
+    }
+}
+
+/// Convert asset bytes without copying borrowed (static) data.
+fn to_bytes(bytes: Cow<'static, [u8]>) -> Bytes {
+    match bytes {
+        Cow::Borrowed(bytes) => Bytes::from(bytes),
+        Cow::Owned(bytes) => Bytes::from(bytes),
+    }
+}
@@ -1,17 +1,32 @@
 //! Runtime - application event loop and lifecycle.
 
-use crate::menu::MenuManager;
+use crate::menu::{self, MenuManager};
 use muda::MenuEvent;
-use rinch_core::element::{Element, WindowProps};
-use rinch_core::events::{clear_handlers, dispatch_event, EventHandlerId};
-use rinch_core::hooks::{begin_render, clear_hooks, end_render};
+use rinch_core::element::{Element, TextDirection, WindowColorScheme, WindowProps};
+use rinch_core::event::Modifiers;
+use rinch_core::events::{
+    clear_handlers, dispatch_change, dispatch_event, dispatch_input, dispatch_reorder,
+    dispatch_select, html_escape_string, restore_handlers, set_patch_notifier, take_binding_patches,
+    take_handlers, EventHandlerId,
+};
+use rinch_core::hooks::{abort_render, begin_render, clear_hooks, end_render, set_hook_value};
+use rinch_core::{affects_render, take_render_triggers, track_render};
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Instant;
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::window::WindowId;
 
+use super::config::RunConfig;
+use super::console;
+use super::devtools::{self, on_click, DevToolsAction, DevToolsPanel, DevToolsState, HOOK_VALUE_ATTR};
+use super::error_overlay::{self, ErrorSource};
+use super::render_diff;
+use super::segments::HtmlSegments;
+pub use super::timers::{schedule, schedule_repeating, TimerId};
+use crate::tasks::AppHandle;
 use super::window_manager::WindowManager;
 
 /// Events used internally by rinch.
@@ -25,26 +40,119 @@ pub enum RinchEvent {
     ReRender,
     /// An element was clicked (with handler ID and source window).
     ElementClicked { handler_id: EventHandlerId, window_id: WindowId },
+    /// The text of an input with an `oninput` handler was edited.
+    ElementInput { handler_id: EventHandlerId, value: String, window_id: WindowId },
+    /// A checkbox or radio button with an `onchange` handler was toggled.
+    ElementChange { handler_id: EventHandlerId, checked: bool, window_id: WindowId },
+    /// The selection of a text input with an `onselect` handler moved.
+    ElementSelect { handler_id: EventHandlerId, start: usize, end: usize, window_id: WindowId },
+    /// An item of a `Sortable` was dragged to a new position.
+    ElementReorder { handler_id: EventHandlerId, from: usize, to: usize, window_id: WindowId },
+    /// The mouse rested on an element with a tooltip (with the window's timer count).
+    ShowTooltip { window_id: WindowId, timer: u64 },
     /// Toggle the DevTools window.
     ToggleDevTools { source_window: WindowId },
     /// Update DevTools with hovered element info.
     UpdateDevToolsHover { element_info: Option<HoveredElementInfo> },
+    /// A hook's value was edited in DevTools (with the hook's index).
+    DevToolsSetHook { index: usize, value: String },
+    /// A tab or button was clicked in the DevTools window.
+    DevToolsAction(DevToolsAction),
+    /// Something shown in the DevTools window changed.
+    RefreshDevTools,
+    /// Log records were captured for the DevTools console.
+    DevToolsLogsChanged,
+    /// An element was selected in inspect mode.
+    DevToolsSelectionChanged,
+    /// A `property: value` declaration was added in the DevTools Styles tab.
+    DevToolsAddStyle { declaration: String },
+    /// A log filter was entered in the DevTools console.
+    DevToolsSetLogFilter { directives: String },
     /// A keyboard shortcut was pressed - check against menu shortcuts.
-    KeyboardShortcut {
-        ctrl: bool,
-        meta: bool,
-        alt: bool,
-        shift: bool,
-        key: winit::keyboard::KeyCode,
-    },
+    KeyboardShortcut(crate::menu::KeyPress),
     /// Process pending window requests (open/close).
     ProcessWindowRequests,
+    /// A message was posted to a window with `WindowHandle::post_message`.
+    WindowMessage {
+        to: crate::windows::WindowHandle,
+        message: crate::messages::WindowMessage,
+    },
+    /// Run closures scheduled with `AppHandle::run_on_ui_thread`, then re-render.
+    RunUiTasks,
+    /// Close all windows and exit the event loop.
+    Exit,
     /// Minimize a window.
     MinimizeWindow { window_id: WindowId },
     /// Toggle maximize state of a window.
     ToggleMaximizeWindow { window_id: WindowId },
     /// Close a window (from window controls).
     CloseWindowControl { window_id: WindowId },
+    /// The recent files list changed - refresh the recent files menu.
+    RecentFilesChanged,
+    /// The menu keymap changed - reassign menu accelerators.
+    KeymapChanged,
+    /// A tray icon was clicked or hovered.
+    #[cfg(feature = "system-tray")]
+    TrayIcon(tray_icon::TrayIconEvent),
+    /// Show, restore and focus all application windows.
+    ShowWindows,
+    /// Hide all application windows.
+    HideWindows,
+    /// Watched CSS files changed (hot reload).
+    #[cfg(feature = "hot-reload")]
+    StylesheetsChanged(Vec<std::path::PathBuf>),
+    /// The hot library was rebuilt (with the path of the built file).
+    #[cfg(feature = "hot-reload")]
+    LibraryBuilt(std::path::PathBuf),
+    /// The `rinch dev` runner finished a build, with the compiler errors
+    /// (empty if it succeeded).
+    #[cfg(feature = "hot-reload")]
+    BuildErrors(String),
+    /// The error overlay's close button was clicked.
+    DismissErrors,
+    /// The active theme changed - restyle all windows.
+    ThemeChanged,
+    /// The spell checker was set or cleared - check all windows again.
+    SpellCheckerChanged,
+    /// The zoom or layout debugging changed - update all windows.
+    UiSettingsChanged,
+    /// Signals bound to attributes or text changed - patch the documents.
+    BindingsChanged,
+    /// Assistive technology started using a window's accessibility tree.
+    AccessibilityTreeRequested { window_id: WindowId },
+    /// Assistive technology asked for an action, such as focusing or clicking an element.
+    AccessibilityAction {
+        window_id: WindowId,
+        request: accesskit::ActionRequest,
+    },
+    /// Assistive technology stopped using a window's accessibility tree.
+    AccessibilityDeactivated { window_id: WindowId },
+    /// The system clipboard content changed.
+    #[cfg(feature = "clipboard")]
+    ClipboardChanged,
+    /// A notification was closed or one of its buttons clicked.
+    #[cfg(feature = "notifications")]
+    NotificationResponse {
+        id: crate::notify::NotificationId,
+        response: crate::notify::NotificationResponse,
+    },
+}
+
+impl From<accesskit_winit::Event> for RinchEvent {
+    fn from(event: accesskit_winit::Event) -> Self {
+        let window_id = event.window_id;
+        match event.window_event {
+            accesskit_winit::WindowEvent::InitialTreeRequested => {
+                RinchEvent::AccessibilityTreeRequested { window_id }
+            }
+            accesskit_winit::WindowEvent::ActionRequested(request) => {
+                RinchEvent::AccessibilityAction { window_id, request }
+            }
+            accesskit_winit::WindowEvent::AccessibilityDeactivated => {
+                RinchEvent::AccessibilityDeactivated { window_id }
+            }
+        }
+    }
 }
 
 /// Information about a hovered element for DevTools display.
@@ -114,6 +222,11 @@ impl RenderContext {
         }
     }
 
+    /// Whether a re-render was requested and hasn't happened yet.
+    fn is_render_pending(&self) -> bool {
+        self.inner.borrow().needs_render
+    }
+
     fn clear_render_flag(&self) {
         self.inner.borrow_mut().needs_render = false;
     }
@@ -122,6 +235,8 @@ impl RenderContext {
 // Thread-local render context for triggering re-renders
 thread_local! {
     static RENDER_CONTEXT: RefCell<Option<RenderContext>> = const { RefCell::new(None) };
+    /// Callbacks registered with `on_exit`, in registration order.
+    static EXIT_CALLBACKS: RefCell<Vec<Box<dyn FnOnce()>>> = const { RefCell::new(Vec::new()) };
 }
 
 /// Request a re-render of the UI.
@@ -135,14 +250,51 @@ pub fn request_render() {
     });
 }
 
+/// Run a callback when the application exits.
+///
+/// Callbacks run on the UI thread, in the order they were registered, when
+/// the app is about to stop: when the last window is closed (just before it
+/// is destroyed), on [`AppHandle::exit`], or when the OS quits the app.
+/// Windows and the tray icon still exist at that point, so this is the place
+/// to save unsaved state, stop workers and remove tray icons created with
+/// `TrayIconBuilder`.
+///
+/// Each call adds a callback, so register them once (in `main` or with
+/// `use_mount`) rather than on every render.
+///
+/// # Example
+///
+/// ```ignore
+/// fn main() {
+///     rinch::on_exit(|| save_settings());
+///     rinch::run(app);
+/// }
+/// ```
+pub fn on_exit(callback: impl FnOnce() + 'static) {
+    EXIT_CALLBACKS.with(|callbacks| callbacks.borrow_mut().push(Box::new(callback)));
+}
+
+/// Run the `on_exit` callbacks, once.
+fn run_exit_callbacks() {
+    // Take the callbacks so the list isn't borrowed while they run
+    let callbacks = EXIT_CALLBACKS.with(|callbacks| std::mem::take(&mut *callbacks.borrow_mut()));
+    for callback in callbacks {
+        callback();
+    }
+}
+
 /// The rinch application runtime.
 pub struct Runtime {
     window_manager: WindowManager,
     menu_manager: MenuManager,
     pending_windows: Vec<PendingWindow>,
     pending_menu: Option<Element>,
+    pending_tray: Option<Element>,
     proxy: Option<EventLoopProxy<RinchEvent>>,
     menus_initialized: bool,
+    /// The tray icon declared in the element tree, if any.
+    #[cfg(feature = "system-tray")]
+    tray: Option<crate::tray::ElementTray>,
     app_fn: Option<Box<dyn Fn() -> Element>>,
     render_context: RenderContext,
     #[cfg(feature = "hot-reload")]
@@ -151,12 +303,22 @@ pub struct Runtime {
     devtools_window: Option<WindowId>,
     /// The window being inspected by DevTools.
     devtools_target: Option<WindowId>,
+    /// The hook whose last edit in DevTools failed, and why.
+    devtools_hook_error: Option<(usize, String)>,
+    /// Why the last declaration added in the DevTools Styles tab was rejected.
+    devtools_style_error: Option<String>,
+    /// Why the log filter last entered in the DevTools console was rejected.
+    devtools_log_filter_error: Option<String>,
+    /// The DevTools window's panel and console level.
+    devtools_state: DevToolsState,
     /// Current hovered element info for DevTools display.
     hovered_element: Option<HoveredElementInfo>,
     /// Mapping from WindowHandle to winit WindowId for programmatic window management.
     window_handles: std::collections::HashMap<crate::windows::WindowHandle, WindowId>,
     /// Reverse mapping from winit WindowId to WindowHandle.
     window_ids_to_handles: std::collections::HashMap<WindowId, crate::windows::WindowHandle>,
+    /// Keyboard modifiers currently held, for menu activation details.
+    keyboard_modifiers: Modifiers,
 }
 
 impl Runtime {
@@ -173,17 +335,25 @@ impl Runtime {
             menu_manager: MenuManager::new(),
             pending_windows: Vec::new(),
             pending_menu: None,
+            pending_tray: None,
             proxy: None,
             menus_initialized: false,
+            #[cfg(feature = "system-tray")]
+            tray: None,
             app_fn: None,
             render_context,
             #[cfg(feature = "hot-reload")]
             hot_reloader: None,
             devtools_window: None,
             devtools_target: None,
+            devtools_hook_error: None,
+            devtools_style_error: None,
+            devtools_log_filter_error: None,
+            devtools_state: DevToolsState::new(),
             hovered_element: None,
             window_handles: std::collections::HashMap::new(),
             window_ids_to_handles: std::collections::HashMap::new(),
+            keyboard_modifiers: Modifiers::default(),
         }
     }
 
@@ -194,8 +364,11 @@ impl Runtime {
     pub fn enable_hot_reload(&mut self, config: super::hot_reload::HotReloadConfig) {
         if let Some(proxy) = &self.proxy {
             match super::hot_reload::HotReloader::new(proxy.clone(), config) {
-                Ok(reloader) => {
+                Ok(mut reloader) => {
                     tracing::info!("Hot reload enabled");
+                    if reloader.restore_state() {
+                        self.render_context.request_render();
+                    }
                     self.hot_reloader = Some(reloader);
                 }
                 Err(e) => {
@@ -221,13 +394,17 @@ impl Runtime {
     fn process_element(&mut self, element: Element) {
         match element {
             Element::Window(props, children) => {
-                let html = children_to_html(&children);
+                let html = window_html(&props, &children);
                 self.queue_window(props, html);
             }
             Element::AppMenu(_, _) => {
                 // Store the menu element for later building
                 self.pending_menu = Some(element);
             }
+            Element::Tray(_, _) => {
+                // Store the tray element for later building
+                self.pending_tray = Some(element);
+            }
             Element::Fragment(children) => {
                 for child in children {
                     self.process_element(child);
@@ -243,7 +420,9 @@ impl Runtime {
             return;
         };
 
-        for pending in self.pending_windows.drain(..) {
+        let pending_windows: Vec<PendingWindow> = self.pending_windows.drain(..).collect();
+        for mut pending in pending_windows {
+            let maximized = restore_session(event_loop, &mut pending.props);
             match self.window_manager.create_window(
                 event_loop,
                 proxy.clone(),
@@ -252,6 +431,15 @@ impl Runtime {
             ) {
                 Ok(id) => {
                     tracing::info!("Created window {:?}: {}", id, pending.props.title);
+                    if let Some(window) = self.window_manager.get(id) {
+                        let handle = crate::windows::WindowHandle::new();
+                        crate::windows::register_native_window(handle, id, &window.window);
+                        if maximized {
+                            window.window.set_maximized(true);
+                        }
+                        crate::plugin::window_created(handle, &pending.props);
+                    }
+                    self.record_session_window(id);
                 }
                 Err(e) => {
                     tracing::error!("Failed to create window: {}", e);
@@ -269,6 +457,8 @@ impl Runtime {
         if let Some(menu_element) = self.pending_menu.take() {
             self.menu_manager.build_from_element(&menu_element);
         }
+        // Bindings of the first render can only be checked once the menu exists
+        self.check_hook_shortcuts();
 
         // Initialize menu for macOS (app-level menu bar)
         #[cfg(target_os = "macos")]
@@ -296,19 +486,90 @@ impl Runtime {
         self.menus_initialized = true;
     }
 
+    fn initialize_tray(&mut self) {
+        let Some(tray_element) = self.pending_tray.take() else {
+            return;
+        };
+
+        #[cfg(feature = "system-tray")]
+        if let Element::Tray(props, children) = &tray_element {
+            match crate::tray::ElementTray::build(props, children) {
+                Ok(tray) => {
+                    tracing::info!("Initialized tray icon");
+                    self.tray = Some(tray);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to create tray icon: {}", e);
+                }
+            }
+        }
+
+        #[cfg(not(feature = "system-tray"))]
+        {
+            let _ = tray_element;
+            tracing::warn!("Tray element requires the `system-tray` feature");
+        }
+    }
+
+    /// Update the tray icon from a re-rendered element tree.
+    ///
+    /// Creates the tray if a `Tray` element appeared and removes it if the
+    /// element is gone.
+    #[cfg(feature = "system-tray")]
+    fn update_tray(&mut self, tray_element: Option<Element>) {
+        match (&mut self.tray, tray_element) {
+            (Some(tray), Some(Element::Tray(props, children))) => {
+                if let Err(e) = tray.update(&props, &children) {
+                    tracing::error!("Failed to update tray icon: {}", e);
+                }
+            }
+            (None, Some(element)) => {
+                self.pending_tray = Some(element);
+                self.initialize_tray();
+            }
+            (Some(_), None) => {
+                tracing::info!("Removing tray icon");
+                self.tray = None;
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(not(feature = "system-tray"))]
+    fn update_tray(&mut self, _tray_element: Option<Element>) {}
+
     fn poll_menu_events(&mut self) {
         // Poll for menu events
         while let Ok(event) = MenuEvent::receiver().try_recv() {
-            if self.menu_manager.handle_event(&event) {
+            self.perform_menu_role(&event);
+            if self.menu_manager.handle_event(&event, false, self.keyboard_modifiers) {
                 // Callback was invoked - request re-render in case state changed
                 self.render_context.request_render();
             }
         }
     }
 
+    /// Perform the editing command of a menu item with a role on the focused
+    /// text input of the focused window.
+    fn perform_menu_role(&mut self, event: &MenuEvent) {
+        if let Some(role) = self.menu_manager.role(event)
+            && let Some(window) = crate::windows::focused_window_id().and_then(|id| self.window_manager.get_mut(id))
+        {
+            window.edit(role);
+        }
+    }
+
+    /// Enable the menu items with a role whose command the focused text
+    /// input of the focused window can perform.
+    fn update_menu_roles(&mut self) {
+        let window = crate::windows::focused_window_id().and_then(|id| self.window_manager.get(id));
+        self.menu_manager
+            .update_roles(|role| window.is_some_and(|window| window.can_edit(role)));
+    }
+
     /// Process any pending window requests (open/close).
     fn process_window_requests(&mut self, event_loop: &ActiveEventLoop) {
-        use crate::windows::{take_window_requests, WindowRequest};
+        use crate::windows::{take_window_requests, StylesheetChange, WindowRequest};
 
         let requests = take_window_requests();
         if requests.is_empty() {
@@ -322,7 +583,8 @@ impl Runtime {
 
         for request in requests {
             match request {
-                WindowRequest::Open(open_req) => {
+                WindowRequest::Open(mut open_req) => {
+                    let maximized = restore_session(event_loop, &mut open_req.props);
                     match self.window_manager.create_window(
                         event_loop,
                         proxy.clone(),
@@ -341,16 +603,100 @@ impl Runtime {
                             self.window_ids_to_handles.insert(window_id, open_req.handle);
                             // Resume the window to start rendering
                             if let Some(window) = self.window_manager.get_mut(window_id) {
+                                crate::windows::register_native_window(
+                                    open_req.handle,
+                                    window_id,
+                                    &window.window,
+                                );
                                 window.resume();
+                                if maximized {
+                                    window.window.set_maximized(true);
+                                }
                                 // Set initial window state
                                 Self::update_window_state_for_handle(open_req.handle, window);
                             }
+                            crate::plugin::window_created(open_req.handle, &open_req.props);
+                            self.record_session_window(window_id);
                         }
                         Err(e) => {
                             tracing::error!("Failed to open window: {:?}", e);
                         }
                     }
                 }
+                WindowRequest::Stylesheet(sheet_req) => {
+                    let window = crate::windows::native_window_id(sheet_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    let Some(window) = window else {
+                        tracing::warn!(
+                            "Attempted to change a stylesheet of unknown window handle {:?}",
+                            sheet_req.handle
+                        );
+                        continue;
+                    };
+                    match sheet_req.change {
+                        StylesheetChange::Set { css, path } => {
+                            window.set_stylesheet(sheet_req.id, css, path);
+                        }
+                        StylesheetChange::Remove => window.remove_stylesheet(sheet_req.id),
+                    }
+                }
+                WindowRequest::Focus(focus_req) => {
+                    let window = crate::windows::native_window_id(focus_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    match window {
+                        Some(window) => window.focus_element(&focus_req.element_id),
+                        None => tracing::warn!(
+                            "Attempted to focus an element in unknown window handle {:?}",
+                            focus_req.handle
+                        ),
+                    }
+                }
+                WindowRequest::Document(document_req) => {
+                    let after_render = self.render_context.is_render_pending();
+                    let window = crate::windows::native_window_id(document_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    match window {
+                        Some(window) => window.with_document(document_req.callback, after_render),
+                        None => tracing::warn!(
+                            "Attempted to access the document of unknown window handle {:?}",
+                            document_req.handle
+                        ),
+                    }
+                }
+                WindowRequest::Scale(scale_req) => {
+                    let window = crate::windows::native_window_id(scale_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    match window {
+                        Some(window) => window.override_scale(scale_req.scale),
+                        None => tracing::warn!(
+                            "Attempted to scale unknown window handle {:?}",
+                            scale_req.handle
+                        ),
+                    }
+                }
+                WindowRequest::Overlay(overlay_req) => {
+                    let window = crate::windows::native_window_id(overlay_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    match window {
+                        Some(window) => window.set_overlay(&overlay_req.name, overlay_req.html),
+                        None => tracing::warn!(
+                            "Attempted to change the overlay of unknown window handle {:?}",
+                            overlay_req.handle
+                        ),
+                    }
+                }
+                WindowRequest::Scroll(scroll_req) => {
+                    let after_render = self.render_context.is_render_pending();
+                    let window = crate::windows::native_window_id(scroll_req.handle)
+                        .and_then(|window_id| self.window_manager.get_mut(window_id));
+                    match window {
+                        Some(window) => window.scroll(&scroll_req.selector, scroll_req.target, after_render),
+                        None => tracing::warn!(
+                            "Attempted to scroll an element in unknown window handle {:?}",
+                            scroll_req.handle
+                        ),
+                    }
+                }
                 WindowRequest::Close(close_req) => {
                     if let Some(window_id) = self.window_handles.remove(&close_req.handle) {
                         tracing::info!(
@@ -360,6 +706,8 @@ impl Runtime {
                         );
                         self.window_ids_to_handles.remove(&window_id);
                         crate::windows::remove_window_state(close_req.handle);
+                        crate::windows::unregister_native_window(window_id);
+                        self.forget_session_window(window_id);
                         self.window_manager.close_window(window_id);
                     } else {
                         tracing::warn!(
@@ -372,6 +720,100 @@ impl Runtime {
         }
     }
 
+    /// Whether an event asks to go back (`false`) or forward (`true`) in a
+    /// window's history.
+    fn history_input(&self, window_id: WindowId, event: &WindowEvent) -> Option<bool> {
+        use winit::event::{ElementState, MouseButton};
+        use winit::keyboard::{Key, NamedKey};
+
+        match event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Back, .. } => Some(false),
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Forward, .. } => Some(true),
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                let Modifiers { ctrl, alt, shift, meta } = self.keyboard_modifiers;
+                if !alt || ctrl || shift || meta {
+                    return None;
+                }
+                // Alt+arrows move by word in text fields on macOS
+                if self.window_manager.get(window_id).is_some_and(|window| window.is_editing_text()) {
+                    return None;
+                }
+                match event.logical_key {
+                    Key::Named(NamedKey::ArrowLeft) => Some(false),
+                    Key::Named(NamedKey::ArrowRight) => Some(true),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Record where a window with a `key` is, for session restore.
+    fn record_session_window(&self, window_id: WindowId) {
+        let Some(managed) = self.window_manager.get(window_id) else {
+            return;
+        };
+        let Some(key) = managed.props.key.clone() else {
+            return;
+        };
+        let window = &managed.window;
+        // A minimized window's position is off screen
+        if window.is_minimized().unwrap_or(false) {
+            return;
+        }
+        let scale = window.scale_factor();
+        let size = window.inner_size().to_logical::<u32>(scale);
+        let position = window.outer_position().unwrap_or_default().to_logical::<i32>(scale);
+        crate::session::record(crate::session::SessionWindow {
+            key,
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized: window.is_maximized(),
+        });
+    }
+
+    /// Close a window the user closed, exiting if it was the last one.
+    ///
+    /// When it was, the `on_exit` callbacks run before the window is
+    /// destroyed, so they can still read its state.
+    fn close_user_window(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId) {
+        let closes_last = super::config::exit_on_last_window_closed()
+            && self.window_manager.window_ids().iter().all(|&id| id == window_id);
+        if closes_last {
+            run_exit_callbacks();
+        }
+
+        // Clean up window state tracking if this is a programmatically opened window
+        if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
+            self.window_handles.remove(&handle);
+            crate::windows::remove_window_state(handle);
+        }
+
+        crate::windows::unregister_native_window(window_id);
+        self.forget_session_window(window_id);
+        self.window_manager.close_window(window_id);
+
+        if closes_last {
+            event_loop.exit();
+        }
+    }
+
+    /// Drop a closing window from the session, unless closing it quits the
+    /// app, so the session keeps the windows open when the app quit.
+    fn forget_session_window(&self, window_id: WindowId) {
+        let others_open = self
+            .window_manager
+            .window_ids()
+            .into_iter()
+            .any(|id| id != window_id && Some(id) != self.devtools_window);
+        let key = self.window_manager.get(window_id).and_then(|window| window.props.key.clone());
+        if others_open && let Some(key) = key {
+            crate::session::forget(&key);
+        }
+    }
+
     /// Update the window state for a given handle.
     fn update_window_state_for_handle(
         handle: crate::windows::WindowHandle,
@@ -395,6 +837,22 @@ impl Runtime {
         crate::windows::update_window_state(handle, state);
     }
 
+    /// Rebuild the documents of the app's windows to show the error
+    /// overlay's current errors.
+    fn refresh_error_overlay(&mut self) {
+        for (id, window) in self.window_manager.windows_iter_mut() {
+            if Some(*id) != self.devtools_window {
+                window.refresh_content();
+            }
+        }
+    }
+
+    /// Log conflicts between `use_shortcut` bindings and menu shortcuts.
+    fn check_hook_shortcuts(&self) {
+        let menu_manager = &self.menu_manager;
+        menu::keymap::check_hook_shortcuts(|parsed| menu_manager.shortcut_label(parsed));
+    }
+
     /// Re-render all windows by re-running the app function.
     fn re_render(&mut self) {
         let Some(app_fn) = &self.app_fn else {
@@ -402,47 +860,128 @@ impl Runtime {
             return;
         };
 
-        // Clear old event handlers
-        clear_handlers();
+        // Set old event handlers aside; the documents on screen still use
+        // them if the render fails
+        let previous_handlers = take_handlers();
+        let previous_shortcuts = menu::keymap::take_hook_shortcuts();
 
         // Re-run the app function to get new element tree
-        begin_render();
-        let root = app_fn();
-        end_render();
+        crate::plugin::before_render();
+        // The signals that made this render necessary, for DevTools
+        let triggers = take_render_triggers();
+        let rendered = error_overlay::catch_panic(|| {
+            begin_render();
+            let root = track_render(|| app_fn().expand_components());
+            end_render();
+            root
+        });
+        let Some(root) = rendered else {
+            abort_render();
+            restore_handlers(previous_handlers);
+            menu::keymap::restore_hook_shortcuts(previous_shortcuts);
+            self.refresh_error_overlay();
+            self.render_context.clear_render_flag();
+            return;
+        };
+        drop(previous_handlers);
+        drop(previous_shortcuts);
+        error_overlay::clear(ErrorSource::Render);
+        self.check_hook_shortcuts();
 
         // Extract HTML for each window
-        let mut window_contents: Vec<(WindowProps, String)> = Vec::new();
+        let mut window_contents: Vec<(WindowProps, HtmlSegments)> = Vec::new();
+        let mut tray_element: Option<Element> = None;
 
-        fn extract_windows(element: Element, contents: &mut Vec<(WindowProps, String)>) {
+        fn extract_windows(
+            element: Element,
+            contents: &mut Vec<(WindowProps, HtmlSegments)>,
+            tray: &mut Option<Element>,
+        ) {
             match element {
                 Element::Window(props, children) => {
-                    let html = children_to_html(&children);
-                    contents.push((props, html));
+                    let segments = window_segments(&props, children);
+                    contents.push((props, segments));
+                }
+                Element::Tray(_, _) => {
+                    *tray = Some(element);
                 }
                 Element::Fragment(children) => {
                     for child in children {
-                        extract_windows(child, contents);
+                        extract_windows(child, contents, tray);
                     }
                 }
                 _ => {}
             }
         }
 
-        extract_windows(root, &mut window_contents);
+        extract_windows(root, &mut window_contents, &mut tray_element);
+        self.update_tray(tray_element);
 
         // Update each window's content
         // For now, we assume windows are in the same order
         let window_ids: Vec<WindowId> = self.window_manager.window_ids();
 
-        for (id, (_props, html)) in window_ids.iter().zip(window_contents.iter()) {
+        // Record what changed while DevTools is open
+        let mut recorder = self.devtools_window.map(|_| render_diff::begin(&triggers));
+        for (id, (props, segments)) in window_ids.iter().zip(window_contents) {
             if let Some(window) = self.window_manager.get_mut(*id) {
-                window.update_content(html.clone());
+                window.set_color_scheme(props.color_scheme);
+                window.set_text_direction(props.dir);
+                window.set_scale(props.scale);
+                window.set_shadow(props.shadow);
+                // Static segments compare by address, so this only reads the dynamic HTML
+                if window.shows_segments(&segments) {
+                    continue;
+                }
+                let html = segments.to_html();
+                if let Some(recorder) = &mut recorder {
+                    recorder.window(&props.title, window.content(), &html);
+                }
+                window.update_content(html);
+                window.keep_segments(segments);
             }
         }
+        for (_, window) in self.window_manager.windows_iter_mut() {
+            window.run_after_render();
+        }
+        if let Some(recorder) = recorder {
+            recorder.finish();
+        }
+
+        // Show the new hook values, unless one is being edited
+        let devtools = self.devtools_window.and_then(|id| self.window_manager.get(id));
+        if devtools.is_some_and(|window| !window.is_editing_text()) {
+            self.refresh_devtools();
+        }
+
+        #[cfg(feature = "hot-reload")]
+        if let Some(reloader) = &mut self.hot_reloader {
+            reloader.save_state();
+        }
 
         self.render_context.clear_render_flag();
     }
 
+    /// Set the attributes and text bound to signals that changed in every
+    /// window.
+    fn apply_binding_patches(&mut self) {
+        let patches = take_binding_patches();
+        if patches.is_empty() {
+            return;
+        }
+        let devtools = self.devtools_window;
+        for (id, window) in self.window_manager.windows_iter_mut() {
+            if Some(*id) != devtools && !window.patch_bindings(&patches) {
+                self.render_context.request_render();
+            }
+        }
+        // Show the new hook values
+        let devtools = self.devtools_window.and_then(|id| self.window_manager.get(id));
+        if devtools.is_some_and(|window| !window.is_editing_text()) {
+            self.refresh_devtools();
+        }
+    }
+
     /// Handle a click event by dispatching to the registered handler.
     fn handle_element_click(&mut self, handler_id: EventHandlerId, window_id: WindowId) {
         tracing::debug!("Dispatching click event to handler {:?} from window {:?}", handler_id, window_id);
@@ -450,8 +989,10 @@ impl Runtime {
         // Track the current window so event handlers can call window control functions
         crate::windows::set_current_window_id(Some(window_id));
 
-        if dispatch_event(handler_id) {
-            // Handler was called - request re-render in case state changed
+        // Re-render in case state changed, unless the handler only changed
+        // signals bound to attributes, which are patched instead
+        let (handled, render) = affects_render(|| dispatch_event(handler_id));
+        if handled && render {
             self.render_context.request_render();
         }
 
@@ -459,6 +1000,62 @@ impl Runtime {
         crate::windows::set_current_window_id(None);
     }
 
+    /// Handle an edit to a text input by passing its new value to the handler.
+    fn handle_element_input(&mut self, handler_id: EventHandlerId, value: String, window_id: WindowId) {
+        tracing::debug!("Dispatching input event to handler {:?} from window {:?}", handler_id, window_id);
+
+        crate::windows::set_current_window_id(Some(window_id));
+
+        let (handled, render) = affects_render(|| dispatch_input(handler_id, value));
+        if handled && render {
+            self.render_context.request_render();
+        }
+
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Handle a toggled checkbox or radio button by passing its state to the handler.
+    fn handle_element_change(&mut self, handler_id: EventHandlerId, checked: bool, window_id: WindowId) {
+        tracing::debug!("Dispatching change event to handler {:?} from window {:?}", handler_id, window_id);
+
+        crate::windows::set_current_window_id(Some(window_id));
+
+        let (handled, render) = affects_render(|| dispatch_change(handler_id, checked));
+        if handled && render {
+            self.render_context.request_render();
+        }
+
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Handle a moved text selection by passing it to the handler.
+    fn handle_element_select(&mut self, handler_id: EventHandlerId, start: usize, end: usize, window_id: WindowId) {
+        tracing::debug!("Dispatching select event to handler {:?} from window {:?}", handler_id, window_id);
+
+        crate::windows::set_current_window_id(Some(window_id));
+
+        let (handled, render) = affects_render(|| dispatch_select(handler_id, start, end));
+        if handled && render {
+            self.render_context.request_render();
+        }
+
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Handle a dragged sortable item by passing its old and new index to the handler.
+    fn handle_element_reorder(&mut self, handler_id: EventHandlerId, from: usize, to: usize, window_id: WindowId) {
+        tracing::debug!("Dispatching reorder event to handler {:?} from window {:?}", handler_id, window_id);
+
+        crate::windows::set_current_window_id(Some(window_id));
+
+        let (handled, render) = affects_render(|| dispatch_reorder(handler_id, from, to));
+        if handled && render {
+            self.render_context.request_render();
+        }
+
+        crate::windows::set_current_window_id(None);
+    }
+
     /// Toggle the DevTools window.
     fn toggle_devtools(&mut self, event_loop: &ActiveEventLoop, source_window: WindowId) {
         // If DevTools is already open, close it
@@ -468,6 +1065,7 @@ impl Runtime {
                 window.suspend();
             }
             self.devtools_target = None;
+            console::set_watching(false);
             return;
         }
 
@@ -485,8 +1083,14 @@ impl Runtime {
             borderless: false,
             resizable: true,
             transparent: false,
+            shadow: true,
             always_on_top: true,
             visible: true,
+            color_scheme: WindowColorScheme::System,
+            dir: TextDirection::Ltr,
+            key: None,
+            src: None,
+            scale: None,
         };
 
         let proxy = self.proxy.clone().expect("Proxy should be set");
@@ -494,8 +1098,10 @@ impl Runtime {
             Ok(window_id) => {
                 self.devtools_window = Some(window_id);
                 if let Some(window) = self.window_manager.get_mut(window_id) {
+                    window.ignore_ui_settings();
                     window.resume();
                 }
+                console::set_watching(true);
             }
             Err(e) => {
                 tracing::error!("Failed to create DevTools window: {:?}", e);
@@ -503,6 +1109,48 @@ impl Runtime {
         }
     }
 
+    /// Regenerate the DevTools window's content, if it's open.
+    fn refresh_devtools(&mut self) {
+        if let Some(devtools_id) = self.devtools_window {
+            let html = self.generate_devtools_html();
+            if let Some(window) = self.window_manager.get_mut(devtools_id) {
+                window.update_content(html);
+            }
+        }
+    }
+
+    /// Perform the action of a tab or button clicked in the DevTools window.
+    fn handle_devtools_action(&mut self, action: DevToolsAction) {
+        let target = self.devtools_target.and_then(|id| self.window_manager.get_mut(id));
+        match action {
+            DevToolsAction::ShowPanel(panel) => self.devtools_state.set_panel(panel),
+            DevToolsAction::SetLogLevel(level) => self.devtools_state.log_level = level,
+            // The window refreshes DevTools itself
+            DevToolsAction::ToggleInspect => {
+                if let Some(window) = target {
+                    window.toggle_inspect_mode();
+                }
+                return;
+            }
+            // Refreshed once the windows are updated
+            DevToolsAction::ToggleLayout => {
+                crate::ui_settings::toggle_layout_debug();
+                return;
+            }
+            DevToolsAction::ToggleStyle(index) => {
+                if let Some(window) = target {
+                    window.toggle_style_override(index);
+                }
+            }
+            DevToolsAction::RemoveStyle(index) => {
+                if let Some(window) = target {
+                    window.remove_style_override(index);
+                }
+            }
+        }
+        self.refresh_devtools();
+    }
+
     /// Generate an HTML representation of the DOM tree for the target window.
     fn generate_dom_tree_html(&self) -> String {
         let Some(target_id) = self.devtools_target else {
@@ -608,23 +1256,50 @@ impl Runtime {
 
     /// Generate HTML content for the DevTools window.
     fn generate_devtools_html(&self) -> String {
-        use rinch_core::get_hooks_debug_info;
+        use rinch_core::{get_hook_values, get_hooks_debug_info};
+
+        // The actions of the previous content's tabs and buttons are replaced
+        devtools::clear_actions();
 
         let hooks_info = get_hooks_debug_info();
+        let hook_values = get_hook_values();
         let hooks_html: String = if hooks_info.is_empty() {
             r#"<p style="color: #808080;">No hooks registered.</p>"#.to_string()
         } else {
             hooks_info
                 .iter()
+                .zip(&hook_values)
                 .enumerate()
-                .map(|(i, info)| {
+                .map(|(i, (info, value))| {
+                    // Primitive signals can be edited; Enter sets the value
+                    let value_html = match value {
+                        Some(value) => format!(
+                            r#"<input class="hook-value" {}="{}" value="{}">"#,
+                            HOOK_VALUE_ATTR,
+                            i,
+                            html_escape_string(value)
+                        ),
+                        None => String::new(),
+                    };
+                    let error_html = match &self.devtools_hook_error {
+                        Some((index, error)) if *index == i => {
+                            format!(r#"<div class="hook-error">{}</div>"#, html_escape_string(error))
+                        }
+                        _ => String::new(),
+                    };
                     format!(
                         r#"<div class="hook-item">
                             <span class="hook-index">#{}</span>
                             <span class="hook-type">{}</span>
                             <span class="hook-value-type">{}</span>
+                            {}
+                            {}
                         </div>"#,
-                        i, info.hook_type, info.value_type
+                        i,
+                        info.hook_type,
+                        html_escape_string(info.value_type),
+                        value_html,
+                        error_html
                     )
                 })
                 .collect()
@@ -688,6 +1363,76 @@ impl Runtime {
             None => r#"<p style="color: #808080;">Enable inspect mode (Alt+I) and hover over elements.</p>"#.to_string(),
         };
 
+        let tabs_html: String = [
+            (DevToolsPanel::Elements, "Elements"),
+            (DevToolsPanel::Styles, "Styles"),
+            (DevToolsPanel::Hooks, "Hooks"),
+            (DevToolsPanel::Console, "Console"),
+            (DevToolsPanel::Renders, "Renders"),
+        ]
+        .into_iter()
+        .map(|(panel, label)| {
+            let class = if panel == self.devtools_state.active_panel { "tab active" } else { "tab" };
+            format!(r#"<div class="{}" {}>{}</div>"#, class, on_click(DevToolsAction::ShowPanel(panel)), label)
+        })
+        .collect();
+        let target = self.devtools_target.and_then(|id| self.window_manager.get(id));
+        let toolbar_html: String = [
+            (DevToolsAction::ToggleInspect, "Inspect", target.is_some_and(|window| window.devtools.inspect_mode)),
+            (DevToolsAction::ToggleLayout, "Layout", target.is_some_and(|window| window.shows_layout_debug())),
+        ]
+        .into_iter()
+        .map(|(action, label, active)| {
+            let class = if active { "tool active" } else { "tool" };
+            format!(r#"<button class="{}" {}>{}</button>"#, class, on_click(action), label)
+        })
+        .collect();
+        let panel_html = match self.devtools_state.active_panel {
+            DevToolsPanel::Hooks => format!(
+                r#"<div class="section">
+            <div class="section-title">Registered Hooks ({} total)</div>
+            {}
+        </div>"#,
+                hooks_info.len(),
+                hooks_html
+            ),
+            DevToolsPanel::Console => format!(
+                r#"<div class="section">
+            <div class="section-title">Console</div>
+            {}
+        </div>"#,
+                console::console_html(self.devtools_state.log_level, self.devtools_log_filter_error.as_deref())
+            ),
+            DevToolsPanel::Renders => format!(
+                r#"<div class="section">
+            <div class="section-title">Renders</div>
+            {}
+        </div>"#,
+                render_diff::renders_html()
+            ),
+            DevToolsPanel::Styles => format!(
+                r#"<div class="section">
+            <div class="section-title">Styles</div>
+            {}
+        </div>"#,
+                target
+                    .map(|window| window.styles_html(self.devtools_style_error.as_deref()))
+                    .unwrap_or_default()
+            ),
+            DevToolsPanel::Elements => format!(
+                r#"<div class="section">
+            <div class="section-title">DOM Tree</div>
+            {}
+        </div>
+        <div class="section">
+            <div class="section-title">Hovered Element</div>
+            {}
+        </div>"#,
+                self.generate_dom_tree_html(),
+                element_html
+            ),
+        };
+
         format!(
             r#"<!DOCTYPE html>
 <html>
@@ -724,6 +1469,25 @@ impl Runtime {
             border-bottom-color: #007acc;
             color: #ffffff;
         }}
+        .toolbar {{
+            display: flex;
+            gap: 8px;
+            padding: 4px 12px;
+            background: #252526;
+            border-bottom: 1px solid #3c3c3c;
+        }}
+        .tool {{
+            padding: 2px 8px;
+            border: 1px solid #3c3c3c;
+            border-radius: 3px;
+            background: #2d2d2d;
+            color: #d4d4d4;
+            cursor: pointer;
+        }}
+        .tool.active {{
+            background: #007acc;
+            color: #ffffff;
+        }}
         .panel {{
             padding: 12px;
         }}
@@ -743,6 +1507,7 @@ impl Runtime {
             margin-bottom: 4px;
             border-radius: 4px;
             display: flex;
+            flex-wrap: wrap;
             gap: 8px;
             align-items: center;
         }}
@@ -758,6 +1523,22 @@ impl Runtime {
             color: #ce9178;
             font-size: 11px;
         }}
+        .hook-value {{
+            flex: 1;
+            min-width: 60px;
+            background: #1e1e1e;
+            color: #b5cea8;
+            border: 1px solid #3c3c3c;
+            border-radius: 3px;
+            padding: 2px 4px;
+            font-family: inherit;
+            font-size: 12px;
+        }}
+        .hook-error {{
+            flex-basis: 100%;
+            color: #f48771;
+            font-size: 11px;
+        }}
         .info {{
             color: #808080;
             font-size: 11px;
@@ -832,6 +1613,20 @@ impl Runtime {
         .style-value {{
             color: #ce9178;
         }}
+        .style-toggle, .style-remove {{
+            cursor: pointer;
+            color: #808080;
+        }}
+        .style-add, .log-filter {{
+            width: 100%;
+            margin: 4px 0 8px;
+            background: #3c3c3c;
+            color: #d4d4d4;
+            border: 1px solid #555555;
+            padding: 2px 4px;
+            font-family: inherit;
+            font-size: inherit;
+        }}
         .dom-tree {{
             background: #252526;
             padding: 8px;
@@ -862,23 +1657,10 @@ impl Runtime {
 </head>
 <body>
     <div class="header">Rinch DevTools</div>
-    <div class="tabs">
-        <div class="tab active">Elements</div>
-        <div class="tab">Hooks</div>
-    </div>
+    <div class="tabs">{}</div>
+    <div class="toolbar">{}</div>
     <div class="panel">
-        <div class="section">
-            <div class="section-title">DOM Tree</div>
-            {}
-        </div>
-        <div class="section">
-            <div class="section-title">Hovered Element</div>
-            {}
-        </div>
-        <div class="section">
-            <div class="section-title">Registered Hooks ({} total)</div>
-            {}
-        </div>
+        {}
         <div class="section">
             <div class="section-title">Keyboard Shortcuts</div>
             <div class="shortcuts">
@@ -908,14 +1690,27 @@ impl Runtime {
     </div>
 </body>
 </html>"#,
-            self.generate_dom_tree_html(),
-            element_html,
-            hooks_info.len(),
-            hooks_html
+            tabs_html,
+            toolbar_html,
+            panel_html
         )
     }
 }
 
+/// Size and place a window with a `key` like in the last session, if session
+/// restore is enabled. Returns whether it should be maximized.
+fn restore_session(event_loop: &ActiveEventLoop, props: &mut WindowProps) -> bool {
+    crate::session::restore(props, |x, y| {
+        event_loop.available_monitors().any(|monitor| {
+            let scale = monitor.scale_factor();
+            let position = monitor.position().to_logical::<f64>(scale);
+            let size = monitor.size().to_logical::<f64>(scale);
+            let (x, y) = (f64::from(x), f64::from(y));
+            x >= position.x && y >= position.y && x < position.x + size.width && y < position.y + size.height
+        })
+    })
+}
+
 impl ApplicationHandler<RinchEvent> for Runtime {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         // Create any pending windows
@@ -923,6 +1718,7 @@ impl ApplicationHandler<RinchEvent> for Runtime {
 
         // Initialize menus after windows are created
         self.initialize_menus();
+        self.initialize_tray();
 
         // Resume existing windows (activates rendering)
         self.window_manager.resume_all();
@@ -933,6 +1729,12 @@ impl ApplicationHandler<RinchEvent> for Runtime {
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        if self.devtools_window != Some(window_id)
+            && let Some(handle) = crate::windows::window_handle(window_id)
+        {
+            crate::plugin::event(handle, &event);
+        }
+
         // Handle close request at runtime level
         if matches!(event, WindowEvent::CloseRequested) {
             tracing::info!("Window {:?} close requested", window_id);
@@ -941,20 +1743,42 @@ impl ApplicationHandler<RinchEvent> for Runtime {
             if self.devtools_window == Some(window_id) {
                 self.devtools_window = None;
                 self.devtools_target = None;
+                console::set_watching(false);
             }
 
-            // Clean up window state tracking if this is a programmatically opened window
-            if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
-                self.window_handles.remove(&handle);
-                crate::windows::remove_window_state(handle);
-            }
+            self.close_user_window(event_loop, window_id);
+            return;
+        }
 
-            self.window_manager.close_window(window_id);
+        // Track the focused window so dialogs can be parented to it
+        if let WindowEvent::Focused(true) = &event
+            && self.devtools_window != Some(window_id)
+        {
+            crate::windows::set_focused_window_id(window_id);
+            crate::media::refresh();
+        }
 
-            if !self.window_manager.has_windows() {
-                event_loop.exit();
-            }
-            return;
+        // The accent color and accessibility settings are changed in the OS
+        // settings, so check them again when the user comes back to the app
+        // or the OS theme changes
+        if matches!(event, WindowEvent::Focused(true) | WindowEvent::ThemeChanged(_)) {
+            crate::theme::refresh_system_preferences();
+        }
+
+        // Track modifiers so menu clicks can report them
+        if let WindowEvent::ModifiersChanged(modifiers) = &event {
+            let state = modifiers.state();
+            self.keyboard_modifiers = Modifiers {
+                ctrl: state.control_key(),
+                alt: state.alt_key(),
+                shift: state.shift_key(),
+                meta: state.super_key(),
+            };
+        }
+
+        // Remember where keyed windows are for session restore
+        if matches!(event, WindowEvent::Resized(_) | WindowEvent::Moved(_)) {
+            self.record_session_window(window_id);
         }
 
         // Track window state changes for programmatically opened windows
@@ -969,8 +1793,22 @@ impl ApplicationHandler<RinchEvent> for Runtime {
             }
         }
 
+        // The mouse's back and forward buttons and Alt+Left / Alt+Right move
+        // through the window's history, if it is bound to them
+        if let Some(forward) = self.history_input(window_id, &event)
+            && let Some(handle) = crate::windows::window_handle(window_id)
+            && crate::history::navigate_from_input(handle, forward)
+        {
+            return;
+        }
+
         // Forward other events to the window
         if let Some(window) = self.window_manager.get_mut(window_id) {
+            // An open context menu takes clicks and keys first
+            if window.context_menu_input(&event) {
+                return;
+            }
+
             // Check for mouse down events that might trigger window dragging
             if let WindowEvent::MouseInput {
                 state: winit::event::ElementState::Pressed,
@@ -992,12 +1830,44 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 ..
             } = &event
             {
+                // In inspect mode, clicks select the element for DevTools
+                // instead of clicking it
+                if window.select_inspected() {
+                    if let Some(proxy) = &self.proxy {
+                        let _ = proxy.send_event(RinchEvent::DevToolsSelectionChanged);
+                    }
+                    window.handle_event(event);
+                    return;
+                }
+
+                // The error overlay covers the app
+                if window.is_error_dismiss_clicked() {
+                    if let Some(proxy) = &self.proxy {
+                        let _ = proxy.send_event(RinchEvent::DismissErrors);
+                    }
+                    window.handle_event(event);
+                    return;
+                }
+
                 // Check if we clicked on an element with a handler
                 if let Some(handler_id) = window.get_clicked_handler() {
                     if let Some(proxy) = &self.proxy {
                         let _ = proxy.send_event(RinchEvent::ElementClicked { handler_id, window_id });
                     }
                 }
+
+                // DevTools tabs and buttons
+                if self.devtools_window == Some(window_id)
+                    && let Some(action) = window.get_clicked_devtools_action()
+                    && let Some(proxy) = &self.proxy
+                {
+                    let _ = proxy.send_event(RinchEvent::DevToolsAction(action));
+                }
+
+                // Links to web pages open in the browser, like in one
+                if let Some(href) = window.get_clicked_link() {
+                    super::external::open_link(&href);
+                }
             }
 
             window.handle_event(event);
@@ -1014,7 +1884,8 @@ impl ApplicationHandler<RinchEvent> for Runtime {
             RinchEvent::MenuEvent(id) => {
                 // Find the menu item and trigger its callback
                 let event = muda::MenuEvent { id };
-                if self.menu_manager.handle_event(&event) {
+                self.perform_menu_role(&event);
+                if self.menu_manager.handle_event(&event, false, self.keyboard_modifiers) {
                     // Callback was invoked - request re-render
                     self.render_context.request_render();
                 }
@@ -1023,42 +1894,166 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 tracing::debug!("Re-rendering...");
                 self.re_render();
             }
+            RinchEvent::AccessibilityTreeRequested { window_id } => {
+                if let Some(window) = self.window_manager.get_mut(window_id) {
+                    window.update_accessibility();
+                }
+            }
+            RinchEvent::AccessibilityAction { window_id, request } => {
+                if let Some(window) = self.window_manager.get_mut(window_id) {
+                    window.handle_accessibility_action(request);
+                }
+            }
+            // Nothing to clean up: trees are only sent while it's in use
+            RinchEvent::AccessibilityDeactivated { .. } => {}
+            #[cfg(feature = "hot-reload")]
+            RinchEvent::StylesheetsChanged(paths) => {
+                // Update injected stylesheets in place; other CSS may be
+                // embedded in the app, so fall back to a full re-render
+                let mut reloaded = false;
+                for (_, window) in self.window_manager.windows_iter_mut() {
+                    for path in &paths {
+                        reloaded |= window.reload_stylesheet_file(path);
+                    }
+                }
+                if !reloaded {
+                    self.re_render();
+                }
+            }
+            #[cfg(feature = "hot-reload")]
+            RinchEvent::LibraryBuilt(path) => match super::hot_library::load(&path) {
+                Ok(()) => {
+                    // Hooks the new code doesn't match start over; put back
+                    // the values that still fit them
+                    let snapshot = rinch_core::HookSnapshot::capture();
+                    rinch_core::begin_hot_swap();
+                    self.re_render();
+                    if snapshot.restore() > 0 {
+                        self.re_render();
+                    }
+                }
+                Err(e) => tracing::error!("Hot reload: {}", e),
+            },
+            #[cfg(feature = "hot-reload")]
+            RinchEvent::BuildErrors(errors) => {
+                let changed = if errors.trim().is_empty() {
+                    error_overlay::clear(ErrorSource::Build)
+                } else {
+                    error_overlay::show(ErrorSource::Build, "Build failed", errors.trim_end());
+                    true
+                };
+                if changed {
+                    self.refresh_error_overlay();
+                }
+            }
+            RinchEvent::DismissErrors => {
+                if error_overlay::dismiss() {
+                    self.refresh_error_overlay();
+                }
+            }
             RinchEvent::ElementClicked { handler_id, window_id } => {
                 self.handle_element_click(handler_id, window_id);
             }
+            RinchEvent::ElementInput { handler_id, value, window_id } => {
+                self.handle_element_input(handler_id, value, window_id);
+            }
+            RinchEvent::ElementChange { handler_id, checked, window_id } => {
+                self.handle_element_change(handler_id, checked, window_id);
+            }
+            RinchEvent::ElementSelect { handler_id, start, end, window_id } => {
+                self.handle_element_select(handler_id, start, end, window_id);
+            }
+            RinchEvent::ElementReorder { handler_id, from, to, window_id } => {
+                self.handle_element_reorder(handler_id, from, to, window_id);
+            }
+            RinchEvent::ShowTooltip { window_id, timer } => {
+                if let Some(window) = self.window_manager.get_mut(window_id) {
+                    window.show_tooltip(timer);
+                }
+            }
             RinchEvent::ToggleDevTools { source_window } => {
-                self.toggle_devtools(event_loop, source_window);
+                if super::config::devtools_enabled() {
+                    self.toggle_devtools(event_loop, source_window);
+                }
             }
             RinchEvent::UpdateDevToolsHover { element_info } => {
                 self.hovered_element = element_info;
-                // Update DevTools window content
-                if let Some(devtools_id) = self.devtools_window {
-                    let html = self.generate_devtools_html();
-                    if let Some(window) = self.window_manager.get_mut(devtools_id) {
-                        window.update_content(html);
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsSetHook { index, value } => {
+                self.devtools_hook_error = match set_hook_value(index, &value) {
+                    Ok(()) => {
+                        self.render_context.request_render();
+                        None
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to set hook #{}: {}", index, e);
+                        Some((index, e.to_string()))
                     }
+                };
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsAction(action) => {
+                self.handle_devtools_action(action);
+            }
+            RinchEvent::RefreshDevTools => {
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsSelectionChanged => {
+                self.devtools_state.set_panel(DevToolsPanel::Styles);
+                self.devtools_style_error = None;
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsAddStyle { declaration } => {
+                if let Some(window) = self.devtools_target.and_then(|id| self.window_manager.get_mut(id)) {
+                    self.devtools_style_error = window.add_style_override(&declaration).err();
+                }
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsSetLogFilter { directives } => {
+                self.devtools_log_filter_error = crate::logging::set_filter(&directives).err().map(|e| e.to_string());
+                self.refresh_devtools();
+            }
+            RinchEvent::DevToolsLogsChanged => {
+                console::mark_shown();
+                if self.devtools_state.active_panel == DevToolsPanel::Console {
+                    self.refresh_devtools();
                 }
             }
-            RinchEvent::KeyboardShortcut {
-                ctrl,
-                meta,
-                alt,
-                shift,
-                key,
-            } => {
+            RinchEvent::KeyboardShortcut(press) => {
                 // Check if keyboard shortcut matches a menu item
-                if let Some(menu_id) = self.menu_manager.match_shortcut(ctrl, meta, alt, shift, key)
-                {
+                if let Some(menu_id) = self.menu_manager.match_shortcut(&press) {
                     let event = muda::MenuEvent { id: menu_id };
-                    if self.menu_manager.handle_event(&event) {
+                    // Text inputs already got the key of a command's usual shortcut
+                    let typed = self
+                        .menu_manager
+                        .role(&event)
+                        .is_some_and(|role| menu::is_role_shortcut(role, &press));
+                    if !typed {
+                        self.perform_menu_role(&event);
+                    }
+                    if self.menu_manager.handle_event(&event, true, press.modifiers) {
                         // Callback was invoked - request re-render
                         self.render_context.request_render();
                     }
+                } else if let Some(callback) = menu::keymap::match_hook_shortcut(&press) {
+                    callback();
+                    self.render_context.request_render();
                 }
             }
+            RinchEvent::RunUiTasks => {
+                crate::tasks::run_ui_tasks();
+                self.render_context.request_render();
+            }
+            RinchEvent::Exit => {
+                event_loop.exit();
+            }
             RinchEvent::ProcessWindowRequests => {
                 self.process_window_requests(event_loop);
             }
+            RinchEvent::WindowMessage { to, message } => {
+                crate::messages::deliver(to, message);
+            }
             RinchEvent::MinimizeWindow { window_id } => {
                 if let Some(window) = self.window_manager.get(window_id) {
                     window.window.set_minimized(true);
@@ -1071,56 +2066,296 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 }
             }
             RinchEvent::CloseWindowControl { window_id } => {
-                // Clean up window state tracking if this is a programmatically opened window
-                if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
-                    self.window_handles.remove(&handle);
-                    crate::windows::remove_window_state(handle);
+                self.close_user_window(event_loop, window_id);
+            }
+            RinchEvent::RecentFilesChanged => {
+                self.menu_manager.refresh_recent_files();
+            }
+            RinchEvent::KeymapChanged => {
+                self.menu_manager.apply_shortcuts();
+                self.check_hook_shortcuts();
+            }
+            #[cfg(feature = "system-tray")]
+            RinchEvent::TrayIcon(event) => {
+                if crate::tray::handle_icon_event(&event) {
+                    self.render_context.request_render();
                 }
-
-                self.window_manager.close_window(window_id);
-
-                if !self.window_manager.has_windows() {
-                    event_loop.exit();
+            }
+            #[cfg(feature = "clipboard")]
+            RinchEvent::ClipboardChanged => {
+                crate::clipboard::notify_changed();
+                self.render_context.request_render();
+            }
+            #[cfg(feature = "notifications")]
+            RinchEvent::NotificationResponse { id, response } => {
+                if crate::notify::handle_response(id, response) {
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::ThemeChanged => {
+                for (id, window) in self.window_manager.windows_iter_mut() {
+                    if Some(*id) == self.devtools_window {
+                        continue;
+                    }
+                    window.apply_theme();
+                }
+            }
+            RinchEvent::SpellCheckerChanged => {
+                for (_, window) in self.window_manager.windows_iter() {
+                    window.request_redraw();
+                }
+            }
+            RinchEvent::BindingsChanged => {
+                self.apply_binding_patches();
+            }
+            RinchEvent::UiSettingsChanged => {
+                for (_, window) in self.window_manager.windows_iter_mut() {
+                    window.apply_ui_settings();
+                }
+                self.refresh_devtools();
+            }
+            RinchEvent::ShowWindows => {
+                for (id, window) in self.window_manager.windows_iter() {
+                    if Some(*id) == self.devtools_window {
+                        continue;
+                    }
+                    window.window.set_visible(true);
+                    window.window.set_minimized(false);
+                    window.window.focus_window();
+                }
+            }
+            RinchEvent::HideWindows => {
+                for (id, window) in self.window_manager.windows_iter() {
+                    if Some(*id) == self.devtools_window {
+                        continue;
+                    }
+                    window.window.set_visible(false);
                 }
             }
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        run_exit_callbacks();
+
+        // Remove the tray icon now rather than when the runtime is dropped
+        #[cfg(feature = "system-tray")]
+        {
+            self.tray = None;
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         // Poll menu events
         self.poll_menu_events();
+        self.update_menu_roles();
+
+        // Poll tray menu events for all tray icons
+        #[cfg(feature = "system-tray")]
+        if crate::tray::poll_menu_events(self.keyboard_modifiers) {
+            // Callback was invoked - request re-render in case state changed
+            self.render_context.request_render();
+        }
 
         // Poll hot reloader for file changes
         #[cfg(feature = "hot-reload")]
         if let Some(reloader) = &mut self.hot_reloader {
             reloader.poll();
         }
+
+        // Run due timers and sleep until the next one, or the next blink of
+        // a caret
+        if super::timers::run_due(Instant::now()) {
+            self.render_context.request_render();
+        }
+        let mut deadline = super::timers::next_deadline();
+
+        // Blink the carets of focused text inputs
+        let now = Instant::now();
+        for (_, window) in self.window_manager.windows_iter() {
+            match window.caret_blink(now) {
+                Some(blink) if blink <= now => window.request_redraw(),
+                Some(blink) => deadline = Some(deadline.map_or(blink, |deadline| deadline.min(blink))),
+                None => {}
+            }
+        }
+
+        match deadline {
+            Some(deadline) => event_loop.set_control_flow(ControlFlow::WaitUntil(deadline)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
     }
 }
 
+/// The HTML a window shows: the page its `src` names, or else its
+/// children, which are also shown if the page can't be loaded.
+pub(crate) fn window_html(props: &WindowProps, children: &[Element]) -> String {
+    page_html(props).unwrap_or_else(|| children_to_html(children))
+}
+
+/// [`window_html`] in segments, moving the children's HTML out of the tree.
+fn window_segments(props: &WindowProps, children: Vec<Element>) -> HtmlSegments {
+    match page_html(props) {
+        Some(html) => HtmlSegments::page(html),
+        None => HtmlSegments::from_children(children),
+    }
+}
+
+/// The page a window's `src` names, if it has one that loads.
+fn page_html(props: &WindowProps) -> Option<String> {
+    let src = props.src.as_ref()?;
+    crate::assets::load_page(src)
+        .inspect_err(|e| tracing::error!("Can't show {} in window '{}': {}", src, props.title, e))
+        .ok()
+}
+
 /// Convert element children to an HTML string for blitz.
 fn children_to_html(children: &[Element]) -> String {
-    let mut html = String::new();
+    let mut html = String::with_capacity(html_len(children));
+    push_children_html(children, &mut html);
+    html
+}
+
+/// The length of the HTML of element children.
+fn html_len(children: &[Element]) -> usize {
+    children
+        .iter()
+        .map(|child| match child {
+            Element::Html(content) => content.len(),
+            Element::Fragment(kids) => html_len(kids),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Append the HTML of element children to `html`.
+fn push_children_html(children: &[Element], html: &mut String) {
     for child in children {
         match child {
             Element::Html(content) => {
                 html.push_str(content);
             }
             Element::Fragment(kids) => {
-                html.push_str(&children_to_html(kids));
+                push_children_html(kids, html);
             }
             _ => {}
         }
     }
-    html
+}
+
+/// The HTML of one window, returned by [`render_to_string`].
+#[derive(Debug, Clone)]
+pub struct RenderedWindow {
+    /// The `Window` element's props.
+    pub props: WindowProps,
+    /// The complete HTML the window's document is built from, including the
+    /// default stylesheet and the theme's tokens.
+    pub html: String,
+}
+
+/// Render the app once and return the HTML of each of its windows, without
+/// opening them.
+///
+/// Components are expanded and the `Window` elements are found as [`run`]
+/// finds them, so the HTML is what the windows would display. Useful for
+/// static previews, generating documentation and debugging. It starts from
+/// fresh hook state, so call it instead of running the app, not while it runs.
+///
+/// # Example
+///
+/// ```ignore
+/// for window in rinch::render_to_string(app) {
+///     std::fs::write(format!("{}.html", window.props.title), window.html)?;
+/// }
+/// ```
+pub fn render_to_string<F>(app: F) -> Vec<RenderedWindow>
+where
+    F: Fn() -> Element,
+{
+    clear_handlers();
+    clear_hooks();
+
+    begin_render();
+    let root = app().expand_components();
+    end_render();
+
+    fn collect(element: Element, windows: &mut Vec<RenderedWindow>) {
+        match element {
+            Element::Window(props, children) => {
+                let html = super::window_manager::document_html(&window_html(&props, &children), &[], props.dir);
+                windows.push(RenderedWindow { props, html });
+            }
+            Element::Fragment(children) => {
+                for child in children {
+                    collect(child, windows);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut windows = Vec::new();
+    collect(root, &mut windows);
+    windows
 }
 
 /// Run the application with the given root element.
+///
+/// Shorthand for [`App::new(app).run()`](crate::App); use [`App`](crate::App)
+/// to set options.
 pub fn run<F>(app: F)
 where
     F: Fn() -> Element + 'static,
 {
-    run_internal(app, false);
+    run_internal(app, RunConfig::default(), None, |_| {});
+}
+
+/// Run the application with custom options.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::RunConfig;
+///
+/// fn main() {
+///     rinch::run_with_config(app, RunConfig::new().default_stylesheet(false));
+/// }
+/// ```
+pub fn run_with_config<F>(app: F, config: RunConfig)
+where
+    F: Fn() -> Element + 'static,
+{
+    run_internal(app, config, None, |_| {});
+}
+
+/// Run the application, passing an [`AppHandle`] to `setup` once the event
+/// loop exists.
+///
+/// `setup` runs on the main thread just before the event loop starts. Use it
+/// to hand the handle to background services or an embedding application,
+/// which can then re-render the UI, open windows, run closures on the UI
+/// thread or exit the app.
+///
+/// # Example
+///
+/// ```ignore
+/// fn main() {
+///     rinch::run_with_handle(app, |handle| {
+///         std::thread::spawn(move || {
+///             for update in updates() {
+///                 handle.run_on_ui_thread(move || apply(update));
+///             }
+///             handle.exit();
+///         });
+///     });
+/// }
+/// ```
+pub fn run_with_handle<F, S>(app: F, setup: S)
+where
+    F: Fn() -> Element + 'static,
+    S: FnOnce(AppHandle),
+{
+    run_internal(app, RunConfig::default(), None, setup);
 }
 
 /// Run the application with hot reloading enabled.
@@ -1128,6 +2363,10 @@ where
 /// When files in the `src` directory change, the UI will automatically re-render.
 /// This is useful during development to see changes without restarting.
 ///
+/// The re-render runs the code compiled into the binary, so it shows
+/// changes to stylesheets and other files the app loads, not to its Rust
+/// code; use [`run_with_hot_library`] for that.
+///
 /// # Example
 ///
 /// ```ignore
@@ -1140,48 +2379,114 @@ pub fn run_with_hot_reload<F>(app: F)
 where
     F: Fn() -> Element + 'static,
 {
-    run_internal(app, true);
+    run_internal(app, RunConfig::default(), Some(super::hot_reload::HotReloadConfig::default()), |_| {});
 }
 
-fn run_internal<F>(app: F, #[allow(unused)] enable_hot_reload: bool)
-where
+/// Run the application from a library that is rebuilt and reloaded when
+/// its source changes, keeping the state of its hooks.
+///
+/// The library is built before the app starts; see
+/// [`hot_library`](super::hot_library) for how to set it up.
+///
+/// # Panics
+///
+/// Panics if the library can't be built or loaded.
+///
+/// # Example
+///
+/// ```ignore
+/// fn main() {
+///     rinch::run_with_hot_library(rinch::shell::HotLibrary::new("my-app-ui"));
+/// }
+/// ```
+#[cfg(feature = "hot-reload")]
+pub fn run_with_hot_library(library: super::hot_library::HotLibrary) {
+    let loaded = library.build().and_then(|path| super::hot_library::load(&path));
+    if let Err(e) = loaded {
+        panic!("Hot reload: {}", e);
+    }
+    let config = super::hot_reload::HotReloadConfig::default().with_library(library);
+    run_internal(super::hot_library::app, RunConfig::default(), Some(config), |_| {});
+}
+
+/// Hot reload settings for [`run_internal`]; there are none without the
+/// `hot-reload` feature.
+#[cfg(feature = "hot-reload")]
+pub(crate) type HotReloadSettings = super::hot_reload::HotReloadConfig;
+#[cfg(not(feature = "hot-reload"))]
+pub(crate) type HotReloadSettings = ();
+
+pub(crate) fn run_internal<F, S>(
+    app: F,
+    config: RunConfig,
+    #[allow(unused)] hot_reload: Option<HotReloadSettings>,
+    setup: S,
+) where
     F: Fn() -> Element + 'static,
+    S: FnOnce(AppHandle),
 {
     // Initialize tracing
-    let _ = tracing_subscriber::fmt::try_init();
+    config.init_logging();
+    config.apply();
 
     // Clear any stale state from previous runs
     clear_handlers();
     clear_hooks();
 
+    // Create event loop
+    let event_loop = EventLoop::<RinchEvent>::with_user_event()
+        .build()
+        .expect("Failed to create event loop");
+
+    let proxy = event_loop.create_proxy();
+
+    // Forward tray icon clicks to the event loop
+    #[cfg(feature = "system-tray")]
+    crate::tray::install_event_handler(proxy.clone());
+
+    // Let async tasks reach the UI thread
+    crate::tasks::set_event_proxy(proxy.clone());
+
+    // Set proxy for window management API
+    crate::windows::set_event_proxy(proxy.clone());
+
+    // Windows follow the OS accessibility settings once they are read
+    crate::theme::refresh_system_preferences();
+
+    // Apply changes to attributes and text bound to signals
+    let notify = proxy.clone();
+    set_patch_notifier(move || {
+        let _ = notify.send_event(RinchEvent::BindingsChanged);
+    });
+
+    // Plugins start before the first render, so it can use what they set up
+    crate::plugin::startup(&crate::tasks::app_handle());
+
     // Build the initial element tree
+    crate::plugin::before_render();
     begin_render();
-    let root = app();
+    let root = track_render(|| app().expand_components());
     end_render();
 
     // Create runtime and process elements
     let mut runtime = Runtime::new();
+    runtime.proxy = Some(proxy.clone());
+    runtime.render_context.set_proxy(proxy);
     runtime.set_app_fn(app);
     runtime.process_element(root);
 
-    // Create event loop
-    let event_loop = EventLoop::<RinchEvent>::with_user_event()
-        .build()
-        .expect("Failed to create event loop");
-
-    let proxy = event_loop.create_proxy();
-    runtime.proxy = Some(proxy.clone());
-    runtime.render_context.set_proxy(proxy.clone());
-
-    // Set proxy for window management API
-    crate::windows::set_event_proxy(proxy);
+    // Start watching the clipboard if watch() was called during the first render
+    #[cfg(feature = "clipboard")]
+    crate::clipboard::start_watcher();
 
     // Enable hot reload if requested
     #[cfg(feature = "hot-reload")]
-    if enable_hot_reload {
-        runtime.enable_hot_reload(super::hot_reload::HotReloadConfig::default());
+    if let Some(config) = hot_reload {
+        runtime.enable_hot_reload(config);
     }
 
+    setup(crate::tasks::app_handle());
+
     event_loop.set_control_flow(ControlFlow::Wait);
     event_loop.run_app(&mut runtime).expect("Event loop error");
 }
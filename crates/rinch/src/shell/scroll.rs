@@ -0,0 +1,254 @@
+//! Scrolling elements for [`rinch::scroll`](crate::scroll), and finding
+//! out whether they show for [`rinch::observe`](crate::observe).
+//!
+//! A window's document is replaced whenever it re-renders, so the scroll
+//! offsets of the old document are carried over to the elements at the
+//! same places in the new one, or in the same keyed loop item, as the focus
+//! is.
+
+use blitz_dom::BaseDocument;
+use style::properties::LonghandId;
+
+use super::direction::computed;
+use super::window_manager::{keyed_node_path, node_at_keyed_path, PathStep};
+use crate::scroll::{ScrollPosition, ScrollState};
+use crate::windows::ScrollTarget;
+
+/// The scroll offsets of a document.
+pub(crate) struct SavedScroll {
+    viewport: (f64, f64),
+    /// Scrolled elements by their [`keyed_node_path`], with their offsets.
+    nodes: Vec<(Vec<PathStep>, f64, f64)>,
+}
+
+/// Remember the scroll offsets of the viewport and every scrolled element.
+pub(crate) fn save(doc: &BaseDocument) -> SavedScroll {
+    let mut nodes = Vec::new();
+    let mut stack = vec![doc.root_node().id];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter().copied());
+        if node.scroll_offset.x != 0.0 || node.scroll_offset.y != 0.0 {
+            nodes.push((keyed_node_path(doc, node_id), node.scroll_offset.x, node.scroll_offset.y));
+        }
+    }
+    let viewport = doc.viewport_scroll();
+    SavedScroll { viewport: (viewport.x, viewport.y), nodes }
+}
+
+/// Scroll a new document like the one `saved` was taken from.
+///
+/// Call after the document was laid out, so offsets can be limited to how
+/// far the new content scrolls.
+pub(crate) fn restore(doc: &mut BaseDocument, saved: &SavedScroll) {
+    for (path, x, y) in &saved.nodes {
+        if let Some(node_id) = node_at_keyed_path(doc, path) {
+            set_offset(doc, node_id, *x, *y);
+        }
+    }
+    let (x, y) = saved.viewport;
+    if x != 0.0 || y != 0.0 {
+        doc.scroll_viewport_by(-x, -y);
+    }
+}
+
+/// Scroll the element matching `selector`.
+pub(crate) fn apply(doc: &mut BaseDocument, selector: &str, target: ScrollTarget) {
+    let node_id = match doc.query_selector(selector) {
+        Ok(Some(node_id)) => node_id,
+        Ok(None) => {
+            tracing::warn!("No element matches `{}` to scroll", selector);
+            return;
+        }
+        Err(_) => {
+            tracing::warn!("Invalid selector `{}` to scroll", selector);
+            return;
+        }
+    };
+    match target {
+        ScrollTarget::Position(position) => scroll_to(doc, node_id, position),
+        ScrollTarget::IntoView => scroll_into_view(doc, node_id),
+    }
+}
+
+/// The scroll state of the element matching `selector`.
+pub(crate) fn state(doc: &BaseDocument, selector: &str) -> Option<ScrollState> {
+    let node_id = doc.query_selector(selector).ok()??;
+    let node = doc.get_node(node_id)?;
+    let (max_x, max_y) = max_offset(doc, node_id);
+    Some(ScrollState {
+        x: node.scroll_offset.x,
+        y: node.scroll_offset.y,
+        max_x,
+        max_y,
+    })
+}
+
+/// Whether part of the element matching `selector` shows: inside the
+/// window and the visible part of every container scrolling it.
+pub(crate) fn is_visible(doc: &BaseDocument, selector: &str) -> Option<bool> {
+    let node_id = doc.query_selector(selector).ok()??;
+    let node = doc.get_node(node_id)?;
+    // The element's box, relative to the border box of the ancestor looked at,
+    // cut down to what the containers looked at so far show
+    let layout = node.final_layout;
+    let (mut x, mut y) = (f64::from(layout.location.x), f64::from(layout.location.y));
+    let (mut width, mut height) = (f64::from(layout.size.width), f64::from(layout.size.height));
+
+    let mut current = node.parent;
+    while let Some(ancestor_id) = current {
+        let ancestor = doc.get_node(ancestor_id)?;
+        let layout = ancestor.final_layout;
+        let offset = ancestor.scroll_offset;
+        current = ancestor.parent;
+
+        if scrolls(doc, ancestor_id) {
+            let (visible_width, visible_height) = visible_size(doc, ancestor_id);
+            let (left, top) = (f64::from(layout.border.left), f64::from(layout.border.top));
+            let Some((start_x, shown_width)) = clip(x - left, width, offset.x, visible_width) else {
+                return Some(false);
+            };
+            let Some((start_y, shown_height)) = clip(y - top, height, offset.y, visible_height) else {
+                return Some(false);
+            };
+            (x, y, width, height) = (left + start_x, top + start_y, shown_width, shown_height);
+        }
+        x += f64::from(layout.location.x) - offset.x;
+        y += f64::from(layout.location.y) - offset.y;
+    }
+
+    // `x` and `y` are now relative to the document
+    let scroll = doc.viewport_scroll();
+    let (window_width, window_height) = doc.viewport().window_size;
+    let scale = doc.viewport().scale_f64();
+    let shown = clip(x, width, scroll.x, f64::from(window_width) / scale).is_some()
+        && clip(y, height, scroll.y, f64::from(window_height) / scale).is_some();
+    Some(shown)
+}
+
+/// The part of `length` pixels from `start` that shows in a container
+/// `visible` pixels long scrolled to `offset`, as its start and length, or
+/// `None` if none of it does.
+fn clip(start: f64, length: f64, offset: f64, visible: f64) -> Option<(f64, f64)> {
+    let from = start.max(offset);
+    let to = (start + length).min(offset + visible);
+    // An empty element shows if it is inside
+    (to > from || (length == 0.0 && to == from)).then_some((from, to - from))
+}
+
+/// Scroll an element to `position`.
+fn scroll_to(doc: &mut BaseDocument, node_id: usize, position: ScrollPosition) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    let (x, y) = match position {
+        ScrollPosition::Top => (node.scroll_offset.x, 0.0),
+        ScrollPosition::Bottom => (node.scroll_offset.x, f64::INFINITY),
+        ScrollPosition::Offset { x, y } => (x, y),
+    };
+    set_offset(doc, node_id, x, y);
+}
+
+/// Scroll the containers around an element, innermost first, and then the
+/// viewport, until the element shows.
+fn scroll_into_view(doc: &mut BaseDocument, node_id: usize) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    // The element's box, relative to the border box of the ancestor looked at
+    let layout = node.final_layout;
+    let (mut x, mut y) = (f64::from(layout.location.x), f64::from(layout.location.y));
+    let (width, height) = (f64::from(layout.size.width), f64::from(layout.size.height));
+
+    let mut current = node.parent;
+    while let Some(ancestor_id) = current {
+        let Some(ancestor) = doc.get_node(ancestor_id) else {
+            break;
+        };
+        let layout = ancestor.final_layout;
+        current = ancestor.parent;
+
+        if scrolls(doc, ancestor_id) {
+            let (visible_width, visible_height) = visible_size(doc, ancestor_id);
+            let offset = ancestor.scroll_offset;
+            let left = x - f64::from(layout.border.left);
+            let top = y - f64::from(layout.border.top);
+            set_offset(
+                doc,
+                ancestor_id,
+                reveal(offset.x, left, width, visible_width),
+                reveal(offset.y, top, height, visible_height),
+            );
+        }
+        let Some(offset) = doc.get_node(ancestor_id).map(|ancestor| ancestor.scroll_offset) else {
+            break;
+        };
+        x += f64::from(layout.location.x) - offset.x;
+        y += f64::from(layout.location.y) - offset.y;
+    }
+
+    // `x` and `y` are now relative to the document
+    let scroll = doc.viewport_scroll();
+    let (window_width, window_height) = doc.viewport().window_size;
+    let scale = doc.viewport().scale_f64();
+    let target_x = reveal(scroll.x, x, width, f64::from(window_width) / scale);
+    let target_y = reveal(scroll.y, y, height, f64::from(window_height) / scale);
+    doc.scroll_viewport_by(scroll.x - target_x, scroll.y - target_y);
+}
+
+/// The offset that shows `length` pixels from `start` in a container
+/// `visible` pixels long that is scrolled to `offset`, moving as little as
+/// possible. Content that doesn't fit shows from its start.
+fn reveal(offset: f64, start: f64, length: f64, visible: f64) -> f64 {
+    if start < offset || length > visible {
+        start
+    } else if start + length > offset + visible {
+        start + length - visible
+    } else {
+        offset
+    }
+}
+
+/// Scroll an element to an offset, limited to how far it scrolls.
+fn set_offset(doc: &mut BaseDocument, node_id: usize, x: f64, y: f64) {
+    let (max_x, max_y) = max_offset(doc, node_id);
+    if let Some(node) = doc.get_node_mut(node_id) {
+        node.scroll_offset.x = x.clamp(0.0, max_x);
+        node.scroll_offset.y = y.clamp(0.0, max_y);
+    }
+}
+
+/// The size of the part of an element its content scrolls in.
+fn visible_size(doc: &BaseDocument, node_id: usize) -> (f64, f64) {
+    let Some(node) = doc.get_node(node_id) else {
+        return (0.0, 0.0);
+    };
+    let layout = &node.final_layout;
+    (
+        f64::from(layout.size.width - layout.border.left - layout.border.right),
+        f64::from(layout.size.height - layout.border.top - layout.border.bottom),
+    )
+}
+
+/// How far an element's content scrolls, horizontally and vertically.
+fn max_offset(doc: &BaseDocument, node_id: usize) -> (f64, f64) {
+    let Some(node) = doc.get_node(node_id) else {
+        return (0.0, 0.0);
+    };
+    let (width, height) = visible_size(doc, node_id);
+    let content = node.final_layout.content_size;
+    (
+        (f64::from(content.width) - width).max(0.0),
+        (f64::from(content.height) - height).max(0.0),
+    )
+}
+
+/// Whether an element scrolls its overflowing content, rather than showing
+/// or clipping it.
+fn scrolls(doc: &BaseDocument, node_id: usize) -> bool {
+    [LonghandId::OverflowX, LonghandId::OverflowY].into_iter().any(|longhand| {
+        computed(doc, node_id, longhand).is_some_and(|overflow| overflow == "auto" || overflow == "scroll")
+    })
+}
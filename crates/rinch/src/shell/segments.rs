@@ -0,0 +1,94 @@
+//! The HTML of a window's children in the pieces `rsx!` generated.
+//!
+//! Static `rsx!` subtrees are `Cow::Borrowed` string literals, so two renders
+//! of the same tree hold the very same `&'static str` for them. Comparing
+//! segments by address first means a re-render only reads the dynamic HTML
+//! to find out whether a window changed; an unchanged window is neither joined
+//! into one string nor rebuilt.
+
+use std::borrow::Cow;
+
+use rinch_core::element::Element;
+
+/// The HTML pieces of one window, in document order.
+#[derive(Debug, Default)]
+pub(crate) struct HtmlSegments(Vec<Cow<'static, str>>);
+
+impl HtmlSegments {
+    /// Move the HTML out of element children.
+    pub(crate) fn from_children(children: Vec<Element>) -> Self {
+        let mut segments = Vec::new();
+        take_html(children, &mut segments);
+        Self(segments)
+    }
+
+    /// A page loaded as one piece.
+    pub(crate) fn page(html: String) -> Self {
+        Self(vec![Cow::Owned(html)])
+    }
+
+    /// Whether `other` makes the same HTML piece by piece. Static pieces at
+    /// the same place compare by address without reading them.
+    pub(crate) fn same_html(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| same_segment(a, b))
+    }
+
+    /// The window's HTML.
+    pub(crate) fn to_html(&self) -> String {
+        let mut html = String::with_capacity(self.0.iter().map(|segment| segment.len()).sum());
+        for segment in &self.0 {
+            html.push_str(segment);
+        }
+        html
+    }
+}
+
+fn take_html(children: Vec<Element>, segments: &mut Vec<Cow<'static, str>>) {
+    for child in children {
+        match child {
+            Element::Html(content) => segments.push(content),
+            Element::Fragment(kids) => take_html(kids, segments),
+            _ => {}
+        }
+    }
+}
+
+fn same_segment(a: &str, b: &str) -> bool {
+    std::ptr::eq(a, b) || a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "<h1>Notes</h1>";
+
+    fn render(count: u32) -> HtmlSegments {
+        HtmlSegments::from_children(vec![
+            Element::Html(Cow::Borrowed(HEADER)),
+            Element::Fragment(vec![Element::Html(Cow::Owned(format!("<p>{}</p>", count)))]),
+            Element::Html(Cow::Borrowed("<footer></footer>")),
+        ])
+    }
+
+    #[test]
+    fn segments_join_in_document_order() {
+        assert_eq!(render(1).to_html(), "<h1>Notes</h1><p>1</p><footer></footer>");
+        assert_eq!(HtmlSegments::page("<p>page</p>".into()).to_html(), "<p>page</p>");
+    }
+
+    #[test]
+    fn only_changed_dynamic_html_differs() {
+        assert!(render(1).same_html(&render(1)));
+        assert!(!render(1).same_html(&render(2)));
+        assert!(!render(1).same_html(&HtmlSegments::default()));
+    }
+
+    #[test]
+    fn equal_static_html_from_another_literal_is_the_same() {
+        let copy = String::from(HEADER);
+        let other = HtmlSegments(vec![Cow::Borrowed(copy.leak())]);
+        assert!(HtmlSegments(vec![Cow::Borrowed(HEADER)]).same_html(&other));
+        assert!(!HtmlSegments(vec![Cow::Borrowed("<h1>Other</h1>")]).same_html(&other));
+    }
+}
@@ -0,0 +1,138 @@
+//! Drag-to-reorder lists (the `Sortable` element).
+//!
+//! `rsx!` renders `Sortable` as a `div` holding the ID of its `onreorder`
+//! handler in `data-onreorder`, and the window lets its children be dragged.
+//! While dragging, the child is marked with `data-rinch-dragging` and the
+//! child it would be dropped next to with `data-rinch-drop` (`above`, `below`,
+//! `left` or `right`), which the default stylesheet draws as a line.
+//!
+//! Children are treated as a row if the second one is beside the first, and
+//! as a column otherwise.
+
+use blitz_dom::{ns, BaseDocument, LocalName, Node, QualName};
+
+/// Attribute holding the ID of a sortable element's `onreorder` handler.
+pub(crate) const REORDER_HANDLER_ATTR: &str = "data-onreorder";
+
+/// Attribute marking the item being dragged.
+const DRAGGING_ATTR: &str = "data-rinch-dragging";
+
+/// Attribute marking where the dragged item would be dropped.
+const DROP_ATTR: &str = "data-rinch-drop";
+
+/// How far the mouse has to move before a press becomes a drag, so that
+/// clicks on items still work.
+pub(crate) const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Elements that keep mouse presses for themselves instead of starting a drag.
+const INTERACTIVE_ELEMENTS: &[&str] = &["input", "textarea", "select"];
+
+/// The sortable element and the index of its child under a point, if any.
+pub(crate) fn item_at(doc: &BaseDocument, x: f32, y: f32) -> Option<(usize, usize)> {
+    let mut current = super::hit::node_at(doc, x, y)?;
+    loop {
+        let node = doc.get_node(current)?;
+        let element = node.element_data();
+        if element.is_some_and(|element| INTERACTIVE_ELEMENTS.contains(&element.name.local.as_ref())) {
+            return None;
+        }
+
+        let parent_id = node.parent?;
+        let parent = doc.get_node(parent_id)?;
+        let is_sortable = parent.element_data().is_some_and(|element| {
+            element
+                .attrs()
+                .iter()
+                .any(|attr| attr.name.local.as_ref() == REORDER_HANDLER_ATTR)
+        });
+        if is_sortable && element.is_some() {
+            let index = items(doc, parent_id).iter().position(|&item| item == current)?;
+            return Some((parent_id, index));
+        }
+        current = parent_id;
+    }
+}
+
+/// Where an item dragged to a point would be dropped: the index of the child
+/// it would go before, or the number of children to go after the last one.
+pub(crate) fn drop_slot(doc: &BaseDocument, container: usize, x: f32, y: f32) -> usize {
+    let items = items(doc, container);
+    let horizontal = is_horizontal(doc, &items);
+    items
+        .iter()
+        .filter_map(|&item| doc.get_node(item))
+        .position(|node| {
+            let origin = node.absolute_position(0.0, 0.0);
+            let size = node.final_layout.size;
+            if horizontal {
+                x < origin.x + size.width / 2.0
+            } else {
+                y < origin.y + size.height / 2.0
+            }
+        })
+        .unwrap_or(items.len())
+}
+
+/// The index an item moves to when dropped in `slot`, or `None` if it
+/// stays where it is.
+pub(crate) fn target_index(from: usize, slot: usize) -> Option<usize> {
+    let to = if slot > from { slot - 1 } else { slot };
+    (to != from).then_some(to)
+}
+
+/// Mark the dragged item and the drop position, or clear the marks when
+/// `slot` is `None`.
+pub(crate) fn mark(doc: &mut BaseDocument, container: usize, from: usize, slot: Option<usize>) {
+    let items = items(doc, container);
+    let horizontal = is_horizontal(doc, &items);
+    let dragging = QualName::new(None, ns!(), LocalName::from(DRAGGING_ATTR));
+    let drop = QualName::new(None, ns!(), LocalName::from(DROP_ATTR));
+
+    let mut mutator = doc.mutate();
+    for &item in &items {
+        mutator.clear_attribute(item, dragging.clone());
+        mutator.clear_attribute(item, drop.clone());
+    }
+    let Some(slot) = slot else {
+        return;
+    };
+    if let Some(&item) = items.get(from) {
+        mutator.set_attribute(item, dragging, "");
+    }
+    if target_index(from, slot).is_none() {
+        return;
+    }
+    let (item, side) = match items.get(slot) {
+        Some(&item) => (item, if horizontal { "left" } else { "above" }),
+        None => match items.last() {
+            Some(&item) => (item, if horizontal { "right" } else { "below" }),
+            None => return,
+        },
+    };
+    mutator.set_attribute(item, drop, side);
+}
+
+/// The element children of a sortable element.
+fn items(doc: &BaseDocument, container: usize) -> Vec<usize> {
+    doc.get_node(container)
+        .map(|node| {
+            node.children
+                .iter()
+                .copied()
+                .filter(|&child| doc.get_node(child).is_some_and(|node| node.element_data().is_some()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the items are laid out in a row.
+fn is_horizontal(doc: &BaseDocument, items: &[usize]) -> bool {
+    let node = |index: usize| items.get(index).and_then(|&id| doc.get_node(id));
+    let (Some(first), Some(second)) = (node(0), node(1)) else {
+        return false;
+    };
+    let position = |node: &Node| node.absolute_position(0.0, 0.0);
+    let (a, b) = (position(first), position(second));
+    let size = first.final_layout.size;
+    b.x >= a.x + size.width / 2.0 && (b.y - a.y).abs() < size.height / 2.0
+}
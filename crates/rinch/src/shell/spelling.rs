@@ -0,0 +1,227 @@
+//! Marking the misspelled words of text inputs.
+//!
+//! With a [spell checker](crate::spelling) set, a window checks the text of
+//! its text inputs when it is drawn, and draws a red wavy line under each
+//! misspelled word, over the content like the [caret](super::caret). The
+//! words of each input are kept until its text changes, so only edited
+//! inputs are checked again. Right-clicking a marked word shows its
+//! corrections in a [context menu](super::context_menu).
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyrender::PaintScene;
+use blitz_dom::{local_name, ns, BaseDocument, ElementData, Node, QualName};
+use parley::{Affinity, Cursor, Selection};
+use peniko::kurbo::{Affine, BezPath, Stroke};
+use peniko::Color;
+
+use super::focus::attr;
+
+/// The color of the lines under misspelled words.
+const COLOR: Color = Color::from_rgb8(0xe0, 0x1b, 0x24);
+
+/// Half the height of the wave, and a quarter of its length, in CSS pixels.
+const WAVE: f64 = 1.0;
+
+/// Input types whose text isn't words, checked only with
+/// `spellcheck: "true"`.
+const UNCHECKED_INPUT_TYPES: &[&str] = &["password", "email", "url", "number", "tel"];
+
+/// The misspelled words of a window's text inputs.
+pub(crate) struct Spelling {
+    /// The spell checker generation the words were found with.
+    generation: u64,
+    /// The text of each input checked and the byte ranges of its
+    /// misspelled words.
+    checked: HashMap<usize, (String, Vec<Range<usize>>)>,
+}
+
+/// A wavy line under a misspelled word, in CSS pixels from the top left of
+/// the window.
+pub(crate) struct Squiggle {
+    x0: f64,
+    x1: f64,
+    y: f64,
+}
+
+impl Spelling {
+    pub(crate) fn new() -> Self {
+        Self {
+            generation: crate::spelling::generation(),
+            checked: HashMap::new(),
+        }
+    }
+
+    /// Check the text inputs of `doc` whose text changed, and get the lines
+    /// to draw under their misspelled words.
+    pub(crate) fn update(&mut self, doc: &BaseDocument) -> Vec<Squiggle> {
+        let Some(checker) = crate::spelling::checker() else {
+            self.checked.clear();
+            return Vec::new();
+        };
+        let generation = crate::spelling::generation();
+        if generation != self.generation {
+            self.generation = generation;
+            self.checked.clear();
+        }
+
+        let mut inputs = Vec::new();
+        checked_inputs(doc, doc.root_element().id, None, &mut inputs);
+        let focused = doc.get_focussed_node_id();
+        let mut checked = HashMap::with_capacity(inputs.len());
+        let mut squiggles = Vec::new();
+        for node_id in inputs {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            let Some(input) = node.element_data().and_then(ElementData::text_input_data) else {
+                continue;
+            };
+            let text = input.editor.text().to_string();
+            let words = match self.checked.remove(&node_id) {
+                Some((checked_text, words)) if checked_text == text => words,
+                _ => crate::spelling::misspelled(&*checker, &text),
+            };
+            // The word being typed isn't marked until the caret leaves it
+            let selection = input.editor.raw_selection().text_range();
+            let caret = (focused == Some(node_id) && selection.is_empty()).then_some(selection.start);
+            let typing = |word: &Range<usize>| caret.is_some_and(|caret| (word.start..=word.end).contains(&caret));
+            for word in words.iter().filter(|word| !typing(word)) {
+                squiggles.extend(underline(doc, node, word.clone()));
+            }
+            checked.insert(node_id, (text, words));
+        }
+        self.checked = checked;
+        squiggles
+    }
+
+    /// The text input and the misspelled word in it at `x`, `y` (CSS
+    /// pixels from the top left of the window), with the word's byte range.
+    pub(crate) fn word_at(&self, doc: &BaseDocument, x: f32, y: f32) -> Option<(usize, Range<usize>, String)> {
+        let mut node_id = super::hit::node_at(doc, x, y)?;
+        // The input, not the text inside it
+        while !self.checked.contains_key(&node_id) {
+            node_id = doc.get_node(node_id)?.parent?;
+        }
+        let (text, words) = self.checked.get(&node_id)?;
+        let node = doc.get_node(node_id)?;
+        let layout = node.element_data()?.text_input_data()?.editor.try_layout()?;
+        let (origin_x, origin_y) = content_origin(doc, node);
+        let index = Cursor::from_point(layout, x - origin_x as f32, y - origin_y as f32).index();
+        let word = words.iter().find(|word| (word.start..=word.end).contains(&index))?;
+        Some((node_id, word.clone(), text[word.clone()].to_string()))
+    }
+}
+
+impl Squiggle {
+    /// Draw the line into a scene of the window at `scale`.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, scale: f64) {
+        let mut path = BezPath::new();
+        path.move_to((self.x0, self.y));
+        let mut x = self.x0;
+        let mut up = true;
+        while x < self.x1 {
+            x = (x + 2.0 * WAVE).min(self.x1);
+            path.line_to((x, if up { self.y - WAVE } else { self.y + WAVE }));
+            up = !up;
+        }
+        scene.stroke(&Stroke::new(1.0), Affine::scale(scale), COLOR, None, &path);
+    }
+}
+
+/// Gather the text inputs in the subtree of `node_id` whose spelling is
+/// checked. `spellcheck` is the nearest `spellcheck` attribute above it.
+fn checked_inputs(doc: &BaseDocument, node_id: usize, spellcheck: Option<bool>, inputs: &mut Vec<usize>) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    let mut spellcheck = spellcheck;
+    if let Some(element) = node.element_data() {
+        if let Some(value) = attr(element, "spellcheck") {
+            spellcheck = Some(value != "false");
+        }
+        if element.text_input_data().is_some() {
+            let checked_by_default = element.name.local.as_ref() == "textarea"
+                || !attr(element, "type").is_some_and(|kind| UNCHECKED_INPUT_TYPES.contains(&kind));
+            if spellcheck.unwrap_or(checked_by_default) {
+                inputs.push(node_id);
+            }
+            return;
+        }
+    }
+    for &child in &node.children {
+        checked_inputs(doc, child, spellcheck, inputs);
+    }
+}
+
+/// Where the text of a text input starts: its content box, in CSS pixels
+/// from the top left of the window.
+fn content_origin(doc: &BaseDocument, node: &Node) -> (f64, f64) {
+    let layout = node.final_layout;
+    let origin = node.absolute_position(
+        layout.border.left + layout.padding.left,
+        layout.border.top + layout.padding.top,
+    );
+    let scroll = doc.viewport_scroll();
+    (f64::from(origin.x) - scroll.x, f64::from(origin.y) - scroll.y)
+}
+
+/// The lines under a word of a text input, one for each line of text it is
+/// on, cut to the input's content box.
+fn underline(doc: &BaseDocument, node: &Node, word: Range<usize>) -> Vec<Squiggle> {
+    let Some(layout) = node
+        .element_data()
+        .and_then(ElementData::text_input_data)
+        .and_then(|input| input.editor.try_layout())
+    else {
+        return Vec::new();
+    };
+    let (x, y) = content_origin(doc, node);
+    let box_layout = node.final_layout;
+    let width = f64::from(
+        box_layout.size.width
+            - box_layout.border.left
+            - box_layout.border.right
+            - box_layout.padding.left
+            - box_layout.padding.right,
+    );
+    let height = f64::from(
+        box_layout.size.height
+            - box_layout.border.top
+            - box_layout.border.bottom
+            - box_layout.padding.top
+            - box_layout.padding.bottom,
+    );
+    let selection = Selection::new(
+        Cursor::from_byte_index(layout, word.start, Affinity::Downstream),
+        Cursor::from_byte_index(layout, word.end, Affinity::Upstream),
+    );
+    selection
+        .geometry(layout)
+        .into_iter()
+        .filter_map(|(rect, _)| {
+            let (x0, x1) = (rect.x0.max(0.0), rect.x1.min(width));
+            let line_y = rect.y1 - WAVE;
+            (x0 < x1 && line_y >= 0.0 && line_y <= height + WAVE).then_some(Squiggle {
+                x0: x + x0,
+                x1: x + x1,
+                y: y + line_y,
+            })
+        })
+        .collect()
+}
+
+/// Replace the bytes `range` of the text of a text input with `text`.
+///
+/// Returns the input's new value, or `None` if the range isn't in its text
+/// any more.
+pub(crate) fn replace(doc: &mut BaseDocument, node_id: usize, range: Range<usize>, text: &str) -> Option<String> {
+    let input = doc.get_node(node_id)?.element_data()?.text_input_data()?;
+    let mut value = input.editor.text().to_string();
+    value.get(range.clone())?;
+    value.replace_range(range, text);
+    let name = QualName::new(None, ns!(), local_name!("value"));
+    doc.mutate().set_attribute(node_id, name, &value);
+    Some(value)
+}
@@ -0,0 +1,178 @@
+//! Computed styles and style overrides for the DevTools Styles tab.
+//!
+//! The Styles tab lists every longhand property stylo computed for the
+//! element selected in inspect mode. Declarations added there are written to
+//! the element's inline style with `!important`, so they win over the app's
+//! stylesheets; the inline style the app set is kept in
+//! `data-devtools-original-style` so overrides can be turned off again.
+//! Overrides find their element by its position in the tree, so they
+//! survive re-renders as long as the structure around it doesn't change.
+
+use std::fmt::Write as _;
+
+use blitz_dom::{ns, BaseDocument, LocalName, QualName};
+use rinch_core::events::html_escape_string;
+use style::properties::{LonghandId, PropertyDeclarationId, PropertyId, ShorthandId};
+
+use super::devtools::{on_click, DevToolsAction};
+use super::focus::attr;
+use super::window_manager::{node_at_path, node_path};
+
+/// Attribute keeping the inline style an element had before overrides.
+const ORIGINAL_STYLE_ATTR: &str = "data-devtools-original-style";
+
+/// Attribute of the input adding an override; Enter adds its text.
+pub(crate) const STYLE_ADD_ATTR: &str = "data-devtools-style-add";
+
+/// A declaration added in the Styles tab.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StyleOverride {
+    /// Position of the element in the tree (see [`node_path`]).
+    pub path: Vec<usize>,
+    pub property: String,
+    pub value: String,
+    pub enabled: bool,
+}
+
+impl StyleOverride {
+    /// Parse a `property: value` declaration for the element at `path`.
+    pub(crate) fn parse(path: Vec<usize>, declaration: &str) -> Result<Self, String> {
+        let declaration = declaration.trim().trim_end_matches(';');
+        let Some((property, value)) = declaration.split_once(':') else {
+            return Err("expected `property: value`".to_string());
+        };
+        let property = property.trim().to_ascii_lowercase();
+        let value = value.trim();
+        let value = value.strip_suffix("!important").unwrap_or(value).trim();
+        if PropertyId::parse_enabled_for_all_content(&property).is_err() {
+            return Err(format!("unknown property `{}`", property));
+        }
+        if value.is_empty() {
+            return Err(format!("missing value for `{}`", property));
+        }
+        Ok(Self {
+            path,
+            property,
+            value: value.to_string(),
+            enabled: true,
+        })
+    }
+}
+
+/// Every longhand property's computed value for an element, by name.
+///
+/// Empty if the node isn't an element or hasn't been styled yet.
+pub(crate) fn computed_styles(doc: &BaseDocument, node_id: usize) -> Vec<(&'static str, String)> {
+    let Some(styles) = doc.get_node(node_id).and_then(|node| node.primary_styles()) else {
+        return Vec::new();
+    };
+    // `all` covers every longhand except these two
+    let mut longhands: Vec<LonghandId> = ShorthandId::All.longhands().collect();
+    longhands.extend([LonghandId::Direction, LonghandId::UnicodeBidi]);
+    longhands.sort_unstable_by_key(|longhand| longhand.name());
+    longhands
+        .into_iter()
+        .map(|longhand| {
+            let value = styles.computed_value_to_string(PropertyDeclarationId::Longhand(longhand));
+            (longhand.name(), value)
+        })
+        .collect()
+}
+
+/// Write the enabled overrides to the inline styles of their elements, and
+/// restore the original inline style of elements whose overrides are gone.
+pub(crate) fn apply_overrides(doc: &mut BaseDocument, overrides: &[StyleOverride]) {
+    let mut node_ids = doc
+        .query_selector_all(&format!("[{}]", ORIGINAL_STYLE_ATTR))
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    node_ids.extend(overrides.iter().filter_map(|o| node_at_path(doc, &o.path)));
+    node_ids.sort_unstable();
+    node_ids.dedup();
+
+    for node_id in node_ids {
+        let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+            continue;
+        };
+        let original = attr(element, ORIGINAL_STYLE_ATTR)
+            .or_else(|| attr(element, "style"))
+            .unwrap_or_default()
+            .to_string();
+        let path = node_path(doc, node_id);
+        let mut style = original.trim_end().to_string();
+        for o in overrides.iter().filter(|o| o.enabled && o.path == path) {
+            if !style.is_empty() && !style.ends_with(';') {
+                style.push(';');
+            }
+            let _ = write!(style, " {}: {} !important;", o.property, o.value);
+        }
+
+        let mut mutator = doc.mutate();
+        mutator.set_attribute(node_id, qual_name(ORIGINAL_STYLE_ATTR), &original);
+        mutator.set_attribute(node_id, qual_name("style"), style.trim_start());
+    }
+}
+
+/// Render the selected element's overrides and computed styles as HTML.
+pub(crate) fn styles_html(
+    doc: &BaseDocument,
+    selected: Option<usize>,
+    overrides: &[StyleOverride],
+    error: Option<&str>,
+) -> String {
+    let Some(node_id) = selected else {
+        return r#"<p style="color: #808080;">Enable inspect mode (Alt+I) and click an element.</p>"#.to_string();
+    };
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return r#"<p style="color: #808080;">The selected element is gone.</p>"#.to_string();
+    };
+
+    let mut html = format!(r#"<div class="element-tag">&lt;{}"#, element.name.local);
+    if let Some(id) = attr(element, "id") {
+        let _ = write!(html, "#{}", html_escape_string(id));
+    }
+    if let Some(class) = attr(element, "class") {
+        for class in class.split_whitespace() {
+            let _ = write!(html, ".{}", html_escape_string(class));
+        }
+    }
+    html.push_str("&gt;</div>");
+
+    html.push_str(r#"<div class="layout-title">Overrides</div>"#);
+    let path = node_path(doc, node_id);
+    for (index, o) in overrides.iter().enumerate().filter(|(_, o)| o.path == path) {
+        let _ = write!(
+            html,
+            r#"<div class="style-item"><span class="style-toggle" {}>{}</span> <span class="style-name">{}</span>: <span class="style-value">{}</span> <span class="style-remove" {}>×</span></div>"#,
+            on_click(DevToolsAction::ToggleStyle(index)),
+            if o.enabled { "☑" } else { "☐" },
+            html_escape_string(&o.property),
+            html_escape_string(&o.value),
+            on_click(DevToolsAction::RemoveStyle(index))
+        );
+    }
+    let _ = write!(
+        html,
+        r#"<input class="style-add" {}="" placeholder="property: value">"#,
+        STYLE_ADD_ATTR
+    );
+    if let Some(error) = error {
+        let _ = write!(html, r#"<div class="hook-error">{}</div>"#, html_escape_string(error));
+    }
+
+    html.push_str(r#"<div class="layout-title">Computed Styles</div>"#);
+    for (name, value) in computed_styles(doc, node_id) {
+        let _ = write!(
+            html,
+            r#"<div class="style-item"><span class="style-name">{}</span>: <span class="style-value">{}</span></div>"#,
+            name,
+            html_escape_string(&value)
+        );
+    }
+    html
+}
+
+fn qual_name(name: &str) -> QualName {
+    QualName::new(None, ns!(), LocalName::from(name))
+}
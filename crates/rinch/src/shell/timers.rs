@@ -0,0 +1,304 @@
+//! Timers that run callbacks on the UI thread.
+//!
+//! Timers are kept in a binary heap ordered by deadline. The runtime runs the
+//! due ones in `about_to_wait` and has the event loop wake up at the next
+//! deadline with `ControlFlow::WaitUntil`, so no thread is needed per timer
+//! and the loop sleeps while nothing is scheduled.
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Shortest interval of a repeating timer, so a zero interval doesn't keep
+/// the event loop busy.
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+thread_local! {
+    static TIMERS: RefCell<TimerQueue> = RefCell::new(TimerQueue::default());
+}
+
+/// Identifies a timer created with [`schedule`] or [`schedule_repeating`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+impl TimerId {
+    /// Stop the timer. Cancelling a timer that already fired has no effect.
+    pub fn cancel(self) {
+        // Dropped after the queue is released, since it may cancel other timers
+        let callback = TIMERS.with(|timers| timers.borrow_mut().callbacks.remove(&self.0));
+        drop(callback);
+    }
+
+    /// Whether the timer will still fire.
+    pub fn is_active(self) -> bool {
+        TIMERS.with(|timers| timers.borrow().callbacks.contains_key(&self.0))
+    }
+}
+
+enum Callback {
+    Once(Box<dyn FnOnce()>),
+    Repeating(Rc<dyn Fn()>, Duration),
+}
+
+#[derive(Default)]
+struct TimerQueue {
+    next_id: u64,
+    /// Deadlines with their timer IDs, earliest first. Entries of cancelled
+    /// timers are skipped when they come up.
+    deadlines: BinaryHeap<Reverse<(Instant, u64)>>,
+    callbacks: HashMap<u64, Callback>,
+}
+
+impl TimerQueue {
+    fn insert(&mut self, deadline: Instant, callback: Callback) -> TimerId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.deadlines.push(Reverse((deadline, id)));
+        self.callbacks.insert(id, callback);
+        TimerId(id)
+    }
+
+    /// Take the next timer due at `now`, with its deadline. Repeating timers
+    /// stay registered.
+    fn pop_due(&mut self, now: Instant) -> Option<(u64, Instant, Callback)> {
+        while let Some(&Reverse((deadline, id))) = self.deadlines.peek() {
+            if deadline > now {
+                return None;
+            }
+            self.deadlines.pop();
+            match self.callbacks.get(&id) {
+                Some(Callback::Repeating(f, interval)) => {
+                    return Some((id, deadline, Callback::Repeating(Rc::clone(f), *interval)));
+                }
+                Some(Callback::Once(_)) => {
+                    return self.callbacks.remove(&id).map(|f| (id, deadline, f));
+                }
+                // Cancelled
+                None => {}
+            }
+        }
+        None
+    }
+
+    fn next_deadline(&mut self) -> Option<Instant> {
+        while let Some(&Reverse((deadline, id))) = self.deadlines.peek() {
+            if self.callbacks.contains_key(&id) {
+                return Some(deadline);
+            }
+            self.deadlines.pop();
+        }
+        None
+    }
+}
+
+/// Run a callback on the UI thread once `delay` has passed.
+///
+/// The UI re-renders after timers fire, so the callback can set signals.
+/// Must be called on the UI thread, e.g. from a component or event handler.
+///
+/// # Example
+///
+/// ```ignore
+/// use std::time::Duration;
+/// use rinch::shell::runtime::schedule;
+///
+/// let toast = toast_signal.clone();
+/// schedule(Duration::from_secs(3), move || toast.set(None));
+/// ```
+pub fn schedule(delay: Duration, callback: impl FnOnce() + 'static) -> TimerId {
+    let deadline = Instant::now() + delay;
+    TIMERS.with(|timers| {
+        timers
+            .borrow_mut()
+            .insert(deadline, Callback::Once(Box::new(callback)))
+    })
+}
+
+/// Run a callback on the UI thread every `interval` until the timer is
+/// cancelled.
+///
+/// The first call happens after one interval. If the app falls behind, missed
+/// calls are skipped rather than run back to back. Must be called on the UI
+/// thread.
+pub fn schedule_repeating(interval: Duration, callback: impl Fn() + 'static) -> TimerId {
+    let interval = interval.max(MIN_INTERVAL);
+    let deadline = Instant::now() + interval;
+    TIMERS.with(|timers| {
+        timers
+            .borrow_mut()
+            .insert(deadline, Callback::Repeating(Rc::new(callback), interval))
+    })
+}
+
+/// Run the timers due at `now` (called by runtime in `about_to_wait`).
+///
+/// Returns whether any timer fired.
+pub(crate) fn run_due(now: Instant) -> bool {
+    let mut fired = false;
+    // The queue isn't borrowed while callbacks run, so they can schedule or
+    // cancel timers
+    while let Some((id, deadline, due)) = TIMERS.with(|timers| timers.borrow_mut().pop_due(now)) {
+        fired = true;
+        match due {
+            Callback::Once(callback) => callback(),
+            Callback::Repeating(callback, interval) => {
+                callback();
+                let mut next = deadline + interval;
+                if next <= now {
+                    next = now + interval;
+                }
+                TIMERS.with(|timers| {
+                    let mut timers = timers.borrow_mut();
+                    // Unless the callback cancelled its own timer
+                    if timers.callbacks.contains_key(&id) {
+                        timers.deadlines.push(Reverse((next, id)));
+                    }
+                });
+            }
+        }
+    }
+    fired
+}
+
+/// When the next timer is due, if any (called by runtime to set the control flow).
+pub(crate) fn next_deadline() -> Option<Instant> {
+    TIMERS.with(|timers| timers.borrow_mut().next_deadline())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log() -> Rc<RefCell<Vec<&'static str>>> {
+        Rc::new(RefCell::new(Vec::new()))
+    }
+
+    fn push(log: &Rc<RefCell<Vec<&'static str>>>, name: &'static str) -> impl Fn() + 'static {
+        let log = Rc::clone(log);
+        move || log.borrow_mut().push(name)
+    }
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        let fired = log();
+        let start = Instant::now();
+        schedule(Duration::from_millis(30), push(&fired, "third"));
+        schedule(Duration::from_millis(10), push(&fired, "first"));
+        schedule(Duration::from_millis(20), push(&fired, "second"));
+
+        assert!(!run_due(start));
+        assert!(next_deadline().is_some_and(|deadline| deadline >= start + Duration::from_millis(10)));
+        assert!(run_due(start + Duration::from_secs(1)));
+        assert_eq!(*fired.borrow(), ["first", "second", "third"]);
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn only_due_timers_fire() {
+        let fired = log();
+        let soon = schedule(Duration::ZERO, push(&fired, "soon"));
+        let later = schedule(Duration::from_secs(60), push(&fired, "later"));
+
+        assert!(run_due(Instant::now()));
+        assert_eq!(*fired.borrow(), ["soon"]);
+        assert!(!soon.is_active());
+        assert!(later.is_active());
+        later.cancel();
+    }
+
+    #[test]
+    fn cancelled_timers_do_not_fire() {
+        let fired = log();
+        let cancelled = schedule(Duration::from_millis(5), push(&fired, "cancelled"));
+        let kept = schedule(Duration::from_millis(10), push(&fired, "kept"));
+        cancelled.cancel();
+        assert!(!cancelled.is_active());
+        // The cancelled entry no longer counts as the next deadline
+        assert!(next_deadline().is_some_and(|deadline| deadline > Instant::now() + Duration::from_millis(5)));
+
+        run_due(Instant::now() + Duration::from_secs(1));
+        assert_eq!(*fired.borrow(), ["kept"]);
+        assert!(!kept.is_active());
+        // Cancelling after firing is harmless
+        kept.cancel();
+    }
+
+    #[test]
+    fn callbacks_can_cancel_other_timers() {
+        let fired = log();
+        let victim = schedule(Duration::from_millis(20), push(&fired, "victim"));
+        let first = push(&fired, "first");
+        schedule(Duration::from_millis(10), move || {
+            first();
+            victim.cancel();
+        });
+
+        run_due(Instant::now() + Duration::from_secs(1));
+        assert_eq!(*fired.borrow(), ["first"]);
+    }
+
+    #[test]
+    fn repeating_timers_fire_each_interval() {
+        let fired = log();
+        let start = Instant::now();
+        let timer = schedule_repeating(Duration::from_millis(10), push(&fired, "tick"));
+
+        assert!(!run_due(start));
+        run_due(start + Duration::from_millis(15));
+        assert_eq!(fired.borrow().len(), 1);
+        assert!(timer.is_active());
+        run_due(start + Duration::from_millis(25));
+        assert_eq!(fired.borrow().len(), 2);
+
+        timer.cancel();
+        run_due(start + Duration::from_secs(1));
+        assert_eq!(fired.borrow().len(), 2);
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn repeating_timers_skip_missed_calls() {
+        let fired = log();
+        let start = Instant::now();
+        let timer = schedule_repeating(Duration::from_millis(10), push(&fired, "tick"));
+
+        // Far behind: one call, then the next is an interval from now
+        let late = start + Duration::from_secs(1);
+        run_due(late);
+        assert_eq!(fired.borrow().len(), 1);
+        assert_eq!(next_deadline(), Some(late + Duration::from_millis(10)));
+        timer.cancel();
+    }
+
+    #[test]
+    fn repeating_timers_can_cancel_themselves() {
+        let count = Rc::new(RefCell::new(0));
+        let id = Rc::new(RefCell::new(None::<TimerId>));
+        let (counter, own_id) = (Rc::clone(&count), Rc::clone(&id));
+        let timer = schedule_repeating(Duration::ZERO, move || {
+            *counter.borrow_mut() += 1;
+            if *counter.borrow() == 2 {
+                own_id.borrow().unwrap().cancel();
+            }
+        });
+        *id.borrow_mut() = Some(timer);
+
+        let start = Instant::now();
+        for ms in 1..10 {
+            run_due(start + Duration::from_millis(ms));
+        }
+        assert_eq!(*count.borrow(), 2);
+        assert!(!timer.is_active());
+        assert_eq!(next_deadline(), None);
+    }
+
+    #[test]
+    fn zero_interval_is_clamped() {
+        let start = Instant::now();
+        let timer = schedule_repeating(Duration::ZERO, || {});
+        assert!(next_deadline().is_some_and(|deadline| deadline >= start + MIN_INTERVAL));
+        timer.cancel();
+    }
+}
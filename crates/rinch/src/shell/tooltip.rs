@@ -0,0 +1,148 @@
+//! Tooltips for elements with a `tooltip:` prop.
+//!
+//! `rsx!` stores the text in the `data-rinch-tooltip` attribute. The
+//! window's [overlay](super::overlay) starts with a hidden tooltip layer, a
+//! fixed-position element. When the mouse rests on an element with a
+//! tooltip, the window fills the layer with the text and places it below the
+//! element, or above it if there's no room, kept inside the window. Only the
+//! overlay is laid out again; the content isn't touched.
+
+use std::time::Duration;
+
+use blitz_dom::{local_name, ns, BaseDocument, ElementData, QualName};
+
+/// Attribute holding an element's tooltip text.
+const TOOLTIP_ATTR: &str = "data-rinch-tooltip";
+
+/// Attribute marking the element tooltips are shown in.
+const LAYER_ATTR: &str = "data-rinch-tooltip-layer";
+
+/// How long the mouse has to rest on an element before its tooltip appears.
+pub(crate) const DELAY: Duration = Duration::from_millis(500);
+
+/// Space between the tooltip and its element.
+const GAP: f32 = 6.0;
+
+/// Minimum space between the tooltip and the edges of the window.
+const MARGIN: f32 = 4.0;
+
+/// Style of the layer while no tooltip is shown.
+const HIDDEN_STYLE: &str = "display: none";
+
+/// The box of an element a tooltip is placed next to: x, y, width and
+/// height in CSS pixels.
+pub(crate) type Anchor = (f32, f32, f32, f32);
+
+/// The tooltip layer, above the other layers of every overlay.
+///
+/// The layer starts with a non-breaking space so it has a text node to
+/// replace.
+pub(crate) fn layer_html() -> String {
+    format!(
+        r#"<div class="rinch-tooltip" role="tooltip" {} style="{}">&#160;</div>"#,
+        LAYER_ATTR, HIDDEN_STYLE
+    )
+}
+
+/// The element with a tooltip under a point, if any.
+pub(crate) fn target_at(doc: &BaseDocument, x: f32, y: f32) -> Option<usize> {
+    let mut current = Some(super::hit::node_at(doc, x, y)?);
+    while let Some(node_id) = current {
+        let node = doc.get_node(node_id)?;
+        if node.element_data().and_then(text).is_some() {
+            return Some(node_id);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// The tooltip text of `target` in the content, and the box to place it
+/// next to. `None` if the element has no tooltip.
+pub(crate) fn of(doc: &BaseDocument, target: usize) -> Option<(String, Anchor)> {
+    let node = doc.get_node(target)?;
+    let content = text(node.element_data()?)?.to_string();
+    let origin = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    Some((content, (origin.x, origin.y, size.width, size.height)))
+}
+
+/// Show a tooltip with `content` in an overlay document, placed next to
+/// `anchor`.
+///
+/// Resolves the overlay to measure the tooltip, using `animation_time`.
+/// Returns `false` if the overlay has no tooltip layer.
+pub(crate) fn show(doc: &mut BaseDocument, content: &str, anchor: Anchor, animation_time: f64) -> bool {
+    let Some(layer) = layer(doc) else {
+        return false;
+    };
+    let Some(text_id) = doc.get_node(layer).and_then(|node| node.children.first().copied())
+    else {
+        return false;
+    };
+
+    // Lay the tooltip out invisibly to find its size
+    doc.mutate().set_node_text(text_id, content);
+    set_style(doc, layer, "position: fixed; left: 0; top: 0; visibility: hidden");
+    doc.resolve(animation_time);
+
+    let Some(tooltip) = doc.get_node(layer) else {
+        return false;
+    };
+    let size = (tooltip.final_layout.size.width, tooltip.final_layout.size.height);
+    let viewport = doc.viewport();
+    let scale = viewport.scale();
+    let window = (
+        viewport.window_size.0 as f32 / scale,
+        viewport.window_size.1 as f32 / scale,
+    );
+
+    let (x, y) = place(anchor, size, window);
+    set_style(doc, layer, &format!("position: fixed; left: {x:.0}px; top: {y:.0}px"));
+    true
+}
+
+/// Hide the tooltip.
+pub(crate) fn hide(doc: &mut BaseDocument) {
+    if let Some(layer) = layer(doc) {
+        set_style(doc, layer, HIDDEN_STYLE);
+    }
+}
+
+/// Where to put a tooltip of `size` for an element at `anchor` (x, y, width,
+/// height): centered below the element, or above it if it doesn't fit, and
+/// moved inside the window.
+fn place(anchor: Anchor, size: (f32, f32), window: (f32, f32)) -> (f32, f32) {
+    let (x, y, width, height) = anchor;
+    let clamp = |value: f32, length: f32, limit: f32| {
+        value.min(limit - length - MARGIN).max(MARGIN)
+    };
+
+    let left = x + (width - size.0) / 2.0;
+    let below = y + height + GAP;
+    let top = if below + size.1 + MARGIN > window.1 && y - GAP - size.1 >= MARGIN {
+        y - GAP - size.1
+    } else {
+        below
+    };
+    (clamp(left, size.0, window.0), clamp(top, size.1, window.1))
+}
+
+fn layer(doc: &BaseDocument) -> Option<usize> {
+    doc.query_selector(&format!("[{}]", LAYER_ATTR)).ok().flatten()
+}
+
+fn set_style(doc: &mut BaseDocument, node_id: usize, style: &str) {
+    let name = QualName::new(None, ns!(), local_name!("style"));
+    doc.mutate().set_attribute(node_id, name, style);
+}
+
+/// An element's tooltip text, unless it's missing or blank.
+fn text(element: &ElementData) -> Option<&str> {
+    element
+        .attrs()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == TOOLTIP_ATTR)
+        .map(|attr| attr.value.as_str())
+        .filter(|text| !text.trim().is_empty())
+}
@@ -1,5 +1,10 @@
 //! Custom Vello window renderer with proper transparency support.
 //!
+//! Transparent windows use this renderer on every platform: each frame is
+//! cleared to the transparent base color, and the surface is configured
+//! with an alpha mode the compositor blends, rather than one it treats as
+//! opaque (which shows as black on some drivers).
+//!
 //! On Windows, true window transparency requires:
 //! 1. DirectComposition swapchain (via WGPU_DX12_PRESENTATION_SYSTEM=DxgiFromVisual)
 //! 2. DX12 backend
@@ -10,13 +15,16 @@
 //! - Uses a patched wgpu-fork that enables Rgba8Unorm storage texture support on DX12
 //!   (see ../../../wgpu-fork for the patches)
 //! - Since swapchain textures don't support STORAGE_BINDING, we render to an
-//!   intermediate texture first, then copy to the surface
+//!   intermediate Rgba8Unorm texture first, then copy it to the surface, or
+//!   blit it when the surface has another format (Bgra8Unorm on macOS and
+//!   most Linux drivers)
 
 use anyrender_vello::VelloScenePainter;
 use peniko::Color;
 use std::num::NonZero;
 use std::sync::Arc;
 use vello::{AaConfig, AaSupport, RenderParams, Renderer as VelloRenderer, RendererOptions, Scene};
+use wgpu::util::TextureBlitter;
 use wgpu::{
     Backends, CommandEncoderDescriptor, CompositeAlphaMode, Device, Extent3d, Features, Instance,
     InstanceDescriptor, Limits, MemoryHints, PresentMode, Queue, Surface, SurfaceConfiguration,
@@ -26,6 +34,9 @@ use winit::window::Window;
 
 const DEFAULT_THREADS: Option<NonZero<usize>> = None;
 
+/// The format Vello renders to.
+const RENDER_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
 struct ActiveRenderState {
     renderer: VelloRenderer,
     surface: Surface<'static>,
@@ -34,6 +45,8 @@ struct ActiveRenderState {
     queue: Queue,
     // Intermediate texture for Vello's compute shaders (needs STORAGE_BINDING)
     render_texture: Texture,
+    // Copies the render texture to a surface of another format
+    blitter: Option<TextureBlitter>,
 }
 
 enum RenderState {
@@ -157,27 +170,36 @@ impl TransparentWindowRenderer {
         tracing::info!("Adapter: {:?}", adapter.get_info().name);
         tracing::info!("Available alpha modes: {:?}", caps.alpha_modes);
 
-        // For transparency, we need PreMultiplied alpha mode (supported on DX12 with DirectComposition)
-        // Fall back to Auto (usually Opaque) if PreMultiplied isn't available
-        let alpha_mode = if self.config.transparent
-            && caps.alpha_modes.contains(&CompositeAlphaMode::PreMultiplied)
-        {
-            tracing::info!("Using PreMultiplied alpha mode for transparency");
-            CompositeAlphaMode::PreMultiplied
-        } else {
-            if self.config.transparent {
-                tracing::warn!(
-                    "Transparency requested but PreMultiplied alpha mode not available. \
-                     Available modes: {:?}",
-                    caps.alpha_modes
-                );
+        // For transparency, the compositor has to blend the surface. Vello writes
+        // unpremultiplied colors, so PostMultiplied (Metal, some Vulkan drivers)
+        // is exact; PreMultiplied (DX12 with DirectComposition, Wayland) only
+        // differs at partly transparent edges; Inherit leaves it to the window
+        // system (X11). Fall back to Auto (usually Opaque) if none is available
+        let blended = [
+            CompositeAlphaMode::PostMultiplied,
+            CompositeAlphaMode::PreMultiplied,
+            CompositeAlphaMode::Inherit,
+        ];
+        let alpha_mode = match blended.into_iter().find(|mode| caps.alpha_modes.contains(mode)) {
+            Some(mode) if self.config.transparent => {
+                tracing::info!("Using {:?} alpha mode for transparency", mode);
+                mode
+            }
+            _ => {
+                if self.config.transparent {
+                    tracing::warn!(
+                        "Transparency requested but no blended alpha mode available. \
+                         Available modes: {:?}",
+                        caps.alpha_modes
+                    );
+                }
+                CompositeAlphaMode::Auto
             }
-            CompositeAlphaMode::Auto
         };
 
         // Vello prefers Rgba8Unorm
-        let format = if caps.formats.contains(&TextureFormat::Rgba8Unorm) {
-            TextureFormat::Rgba8Unorm
+        let format = if caps.formats.contains(&RENDER_FORMAT) {
+            RENDER_FORMAT
         } else if caps.formats.contains(&TextureFormat::Bgra8Unorm) {
             TextureFormat::Bgra8Unorm
         } else {
@@ -213,7 +235,8 @@ impl TransparentWindowRenderer {
         surface.configure(&device, &surface_config);
 
         // Create intermediate render texture for Vello
-        let render_texture = Self::create_render_texture(&device, format, width, height);
+        let render_texture = Self::create_render_texture(&device, RENDER_FORMAT, width, height);
+        let blitter = (format != RENDER_FORMAT).then(|| TextureBlitter::new(&device, format));
 
         let renderer = VelloRenderer::new(
             &device,
@@ -240,6 +263,7 @@ impl TransparentWindowRenderer {
             device,
             queue,
             render_texture,
+            blitter,
         }
     }
 
@@ -253,12 +277,7 @@ impl TransparentWindowRenderer {
             state.surface_config.height = height;
             state.surface.configure(&state.device, &state.surface_config);
             // Recreate the render texture with new size
-            state.render_texture = Self::create_render_texture(
-                &state.device,
-                state.surface_config.format,
-                width,
-                height,
-            );
+            state.render_texture = Self::create_render_texture(&state.device, RENDER_FORMAT, width, height);
         }
     }
 
@@ -312,25 +331,32 @@ impl TransparentWindowRenderer {
                 label: Some("copy encoder"),
             });
 
-        encoder.copy_texture_to_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &state.render_texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            wgpu::TexelCopyTextureInfo {
-                texture: &surface_texture.texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            Extent3d {
-                width: state.surface_config.width,
-                height: state.surface_config.height,
-                depth_or_array_layers: 1,
-            },
-        );
+        if let Some(blitter) = &state.blitter {
+            let surface_view = surface_texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            blitter.copy(&state.device, &mut encoder, &render_texture_view, &surface_view);
+        } else {
+            encoder.copy_texture_to_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &state.render_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &surface_texture.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                Extent3d {
+                    width: state.surface_config.width,
+                    height: state.surface_config.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
 
         state.queue.submit(Some(encoder.finish()));
 
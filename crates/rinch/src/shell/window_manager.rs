@@ -1,6 +1,10 @@
 //! Window manager - tracks and manages multiple windows.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::task::Waker;
 use std::time::Instant;
@@ -10,34 +14,116 @@ use anyrender::WindowRenderer;
 use peniko::Color;
 
 use super::transparent_renderer::{TransparentRendererOptions, TransparentWindowRenderer};
-use blitz_dom::{Document, DocumentConfig};
+use blitz_dom::{
+    local_name, ns, Attribute, BaseDocument, Document, DocumentConfig, ElementData, LocalName, QualName,
+};
 use blitz_html::HtmlDocument;
-use blitz_paint::paint_scene;
 use blitz_traits::shell::{ColorScheme, Viewport};
 use blitz_traits::events::{
     BlitzMouseButtonEvent, BlitzWheelDelta, BlitzWheelEvent, MouseEventButton, MouseEventButtons,
     UiEvent,
 };
 use futures_util::task::ArcWake;
-use rinch_core::element::WindowProps;
-use rinch_core::events::EventHandlerId;
-use winit::dpi::{LogicalPosition, LogicalSize};
-use winit::event::{ElementState, Modifiers, MouseButton, WindowEvent};
+use rinch_core::element::{MenuRole, TextDirection, WindowColorScheme, WindowProps};
+use rinch_core::events::{BindingPatch, EventHandlerId};
+use rinch_core::untracked;
+use winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Ime, KeyEvent, Modifiers, MouseButton, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
 use winit::window::{Theme, Window, WindowAttributes, WindowId};
 
+#[cfg(target_os = "macos")]
+use winit::platform::macos::{WindowAttributesExtMacOS, WindowExtMacOS};
 #[cfg(target_os = "windows")]
-use winit::platform::windows::WindowAttributesExtWindows;
-
-use super::devtools::DevToolsState;
+use winit::platform::windows::{WindowAttributesExtWindows, WindowExtWindows};
+
+use super::accessibility;
+use super::bindings;
+use super::caret::Caret;
+use super::context_menu::{Action, Click, ContextMenu, Item};
+use super::console::LOG_FILTER_ATTR;
+use super::devtools::{self, DevToolsAction, DevToolsState, ACTION_ATTR, HOOK_VALUE_ATTR};
+use super::focus;
+use super::hit;
+use super::keys::{self, to_blitz_ime_event, to_blitz_key_event};
+use super::direction;
+use super::edit;
+use super::range::{self, RangeInput};
+use super::scroll;
+use super::segments::HtmlSegments;
+use super::resources::{AssetProvider, ResourceQueue};
 use super::runtime::{ElementLayout, HoveredElementInfo, RinchEvent};
+use super::sortable;
+use super::spelling::{self, Spelling};
+use super::styles::{self, StyleOverride, STYLE_ADD_ATTR};
+use super::devtools_overlay;
+use super::layers;
+use super::overlay::{self, Marks, Overlay};
+use super::tooltip;
+use crate::menu::KeyPress;
+use crate::ui_settings::UiSettings;
+use crate::undo::UndoManager;
+use crate::mouse::HoveredElement;
+use crate::observe::ElementSize;
+use crate::windows::{DocumentCallback, ScrollTarget, StylesheetId, WindowHandle};
+
+/// Attribute marking `<style>` elements that hold injected stylesheets.
+const STYLESHEET_ATTR: &str = "data-rinch-stylesheet";
+
+/// Attribute marking the `<style>` element that holds the theme's tokens.
+const THEME_ATTR: &str = "data-rinch-theme";
+
+/// Attribute marking the `<style>` element that holds the default stylesheet.
+const DEFAULT_STYLESHEET_ATTR: &str = "data-rinch-default";
+
+/// Attribute holding the ID of an element's `oninput` handler.
+pub(crate) const INPUT_HANDLER_ATTR: &str = "data-oninput";
+
+/// Attribute holding the ID of a checkbox or radio button's `onchange` handler.
+pub(crate) const CHANGE_HANDLER_ATTR: &str = "data-onchange";
+
+/// Attribute holding the ID of a text input's `onselect` handler.
+const SELECT_HANDLER_ATTR: &str = "data-onselect";
+
+/// Attribute holding the key of a keyed `rsx!` loop item on its elements.
+const KEY_ATTR: &str = "data-rkey";
+
+/// The focused text input, its current value and selection.
+struct FocusedInput {
+    node_id: usize,
+    input_handler: Option<EventHandlerId>,
+    select_handler: Option<EventHandlerId>,
+    value: String,
+    selection: Range<usize>,
+}
+
+/// A mouse press on an item of a sortable element, which becomes a drag once
+/// the mouse moves far enough.
+struct SortDrag {
+    /// The path of the sortable element, see [`node_path`].
+    container: Vec<usize>,
+    /// The index of the item being dragged.
+    from: usize,
+    /// Where the mouse was pressed.
+    start: (f32, f32),
+    /// Where the item would be dropped, once the drag has started.
+    slot: Option<usize>,
+}
+
+/// A stylesheet added with `WindowHandle::inject_stylesheet`.
+pub(crate) struct InjectedStylesheet {
+    id: StylesheetId,
+    css: String,
+    /// The file the CSS was loaded from, for hot reload.
+    path: Option<PathBuf>,
+}
 
 /// Renderer wrapper that supports both standard and transparent rendering.
 pub enum RinchWindowRenderer {
     /// Standard Vello renderer (Vulkan backend, opaque).
     Standard(VelloWindowRenderer),
-    /// Transparent renderer (DX12 + DirectComposition).
+    /// Transparent renderer (DX12 + DirectComposition on Windows).
     Transparent(TransparentWindowRenderer),
 }
 
@@ -101,12 +187,82 @@ pub struct ManagedWindow {
     pub buttons: MouseEventButtons,
     /// Current mouse position.
     pub mouse_pos: (f32, f32),
+    /// Whether the mouse is over the window.
+    mouse_inside: bool,
     /// Animation start time.
     pub animation_timer: Option<Instant>,
     /// Window visibility state.
     pub is_visible: bool,
+    /// Whether the window is covered by others, as last reported by the OS.
+    occluded: bool,
+    /// Whether `content` changed while the window was hidden, and the
+    /// document still has to be rebuilt from it.
+    content_stale: bool,
+    /// The segments of the render `content` was built from, to skip
+    /// rebuilding the document when a render generates the same HTML.
+    segments: HtmlSegments,
     /// DevTools state for this window.
     pub devtools: DevToolsState,
+    /// Exposes the document to screen readers.
+    accessibility: accesskit_winit::Adapter,
+    /// Loads images, stylesheets and fonts requested by the document.
+    net_provider: Arc<AssetProvider>,
+    /// Resources loaded by `net_provider`, waiting to be applied to the document.
+    resources: ResourceQueue,
+    /// The HTML content the document was last built from.
+    content: String,
+    /// Stylesheets injected at runtime, applied after the content's own styles.
+    stylesheets: Vec<InjectedStylesheet>,
+    /// Whether IME input is enabled, which it is while a text input is focused.
+    ime_allowed: bool,
+    /// Whether an IME composition is in progress.
+    ime_composing: bool,
+    /// The focused input and its text when the IME composition in progress
+    /// started, so the committed text is recorded as one edit.
+    ime_text_before: Option<(usize, String)>,
+    /// Where the input method's candidate window was last placed: x, y,
+    /// width and height of the caret in physical pixels.
+    ime_cursor_area: Option<[f64; 4]>,
+    /// The path of the range input being dragged, see [`node_path`].
+    range_drag: Option<Vec<usize>>,
+    /// The item of a sortable element being pressed or dragged.
+    sort_drag: Option<SortDrag>,
+    /// Whether focus was last moved with the keyboard, so it shows a focus ring.
+    focus_visible: bool,
+    /// Whether the document changed since the accessibility tree was last
+    /// built, so the next redraw rebuilds it.
+    accessibility_stale: bool,
+    /// The `id` of an element to focus once it exists, see [`focus_element`](Self::focus_element).
+    pending_focus: Option<String>,
+    /// Whether the window follows the app's [`UiSettings`].
+    uses_ui_settings: bool,
+    /// Callbacks waiting for the next re-render, see [`with_document`](Self::with_document).
+    after_render: Vec<DocumentCallback>,
+    /// The element with a tooltip under the mouse.
+    tooltip_target: Option<usize>,
+    /// Whether the tooltip of `tooltip_target` is shown.
+    tooltip_visible: bool,
+    /// Counts tooltip timers, so only the latest one shows a tooltip.
+    tooltip_timer: u64,
+    /// Transient UI drawn above the content, such as the tooltip.
+    overlay: Overlay,
+    /// The blinking of the focused text input's caret.
+    caret: Caret,
+    /// The misspelled words of the text inputs.
+    spelling: Spelling,
+    /// The context menu shown, such as the corrections of a misspelled word.
+    context_menu: Option<ContextMenu>,
+    /// Whether the next mouse button release belongs to a click on the
+    /// context menu, so it goes no further either.
+    context_menu_release: bool,
+    /// Declarations added in the DevTools Styles tab.
+    style_overrides: Vec<StyleOverride>,
+    /// Whether right-to-left content that scrolls sideways still has to be
+    /// scrolled to its start, once the new window is laid out.
+    scroll_start_pending: bool,
+    /// The HiDPI scale set with [`WindowHandle::set_scale`](crate::windows::WindowHandle::set_scale),
+    /// which wins over the `scale` prop.
+    scale_override: Option<f32>,
 }
 
 impl ManagedWindow {
@@ -132,7 +288,9 @@ impl ManagedWindow {
             .with_resizable(props.resizable)
             .with_decorations(!props.borderless)
             .with_transparent(props.transparent)
-            .with_visible(props.visible);
+            // Shown once the accessibility adapter exists, which must be first
+            .with_visible(false)
+            .with_theme(winit_theme(props.color_scheme));
 
         if let (Some(x), Some(y)) = (props.x, props.y) {
             attrs = attrs.with_position(LogicalPosition::new(x, y));
@@ -146,8 +304,23 @@ impl ManagedWindow {
             tracing::info!("Enabled no_redirection_bitmap for transparent window");
         }
 
+        // Decorated windows always have a shadow on Windows; Linux leaves it
+        // to the compositor
+        #[cfg(target_os = "windows")]
+        {
+            attrs = attrs.with_undecorated_shadow(props.shadow);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            attrs = attrs.with_has_shadow(props.shadow);
+        }
+
         // Create winit window
         let window = Arc::new(event_loop.create_window(attrs)?);
+        let accessibility = accesskit_winit::Adapter::with_event_loop_proxy(&window, proxy.clone());
+        if props.visible {
+            window.set_visible(true);
+        }
 
         // Log actual window state after creation
         tracing::info!(
@@ -158,22 +331,24 @@ impl ManagedWindow {
 
         // Set up viewport
         let size = window.inner_size();
-        let scale = window.scale_factor() as f32;
-        let theme = window.theme().unwrap_or(Theme::Light);
-        let color_scheme = match theme {
-            Theme::Light => ColorScheme::Light,
-            Theme::Dark => ColorScheme::Dark,
-        };
+        let scale = props.scale.unwrap_or(window.scale_factor() as f32);
+        let color_scheme = resolve_color_scheme(props.color_scheme, window.theme());
         let viewport = Viewport::new(size.width, size.height, scale, color_scheme);
 
         // Create document config
-        let config = DocumentConfig {
-            viewport: Some(viewport),
-            ..Default::default()
-        };
+        let (net_provider, resources) = AssetProvider::new(proxy.clone(), window.id());
+        let config = document_config(viewport.clone(), &net_provider);
+        let mut overlay = Overlay::new(viewport, &net_provider);
+        overlay.set_page(overlay_page(&[], props.dir));
+        let (content, element_layers) = layers::extract(&html_content);
+        overlay.set_element_layers(element_layers);
 
         // Parse HTML into document
-        let doc: Box<dyn Document> = Box::new(HtmlDocument::from_html(&html_content, config));
+        let html = document_html(&content, &[], props.dir);
+        let mut doc: Box<dyn Document> = Box::new(HtmlDocument::from_html(&html, config));
+        load_resources(&mut doc, &resources);
+        range::init_all(&mut doc.inner_mut());
+        set_ui_settings(&mut doc.inner_mut(), &crate::ui_settings::current());
 
         // Set the document title from HTML if present
         {
@@ -184,8 +359,9 @@ impl ManagedWindow {
             }
         }
 
-        // Create renderer - use transparent renderer for transparent windows on Windows
-        let renderer = if props.transparent && cfg!(target_os = "windows") {
+        // Create renderer - the standard one clears to white, so transparent
+        // windows use the transparent renderer on every platform
+        let renderer = if props.transparent {
             RinchWindowRenderer::Transparent(TransparentWindowRenderer::with_options(
                 TransparentRendererOptions {
                     // Fully transparent base for true window transparency
@@ -200,7 +376,7 @@ impl ManagedWindow {
 
         let is_visible = window.is_visible().unwrap_or(true);
 
-        Ok(Self {
+        let mut managed = Self {
             doc,
             renderer,
             waker: None,
@@ -210,10 +386,48 @@ impl ManagedWindow {
             keyboard_modifiers: Default::default(),
             buttons: MouseEventButtons::None,
             mouse_pos: (0.0, 0.0),
+            mouse_inside: false,
             animation_timer: None,
             is_visible,
+            occluded: false,
+            content_stale: false,
+            segments: HtmlSegments::default(),
             devtools: DevToolsState::new(),
-        })
+            accessibility,
+            net_provider,
+            resources,
+            content: html_content,
+            stylesheets: Vec::new(),
+            ime_allowed: false,
+            ime_composing: false,
+            ime_text_before: None,
+            ime_cursor_area: None,
+            range_drag: None,
+            sort_drag: None,
+            focus_visible: false,
+            accessibility_stale: true,
+            pending_focus: None,
+            uses_ui_settings: true,
+            after_render: Vec::new(),
+            tooltip_target: None,
+            tooltip_visible: false,
+            tooltip_timer: 0,
+            overlay,
+            caret: Caret::new(),
+            spelling: Spelling::new(),
+            context_menu: None,
+            context_menu_release: false,
+            style_overrides: Vec::new(),
+            scroll_start_pending: true,
+            scale_override: None,
+        };
+
+        let autofocus = focus::autofocus_target(&managed.doc.inner());
+        if let Some(node_id) = autofocus {
+            managed.set_focus(node_id, true);
+        }
+
+        Ok(managed)
     }
 
     /// Get the window ID.
@@ -228,6 +442,12 @@ impl ManagedWindow {
         }
     }
 
+    /// When the window has to be redrawn next for the caret of its focused
+    /// input to blink, if it is blinking.
+    pub fn caret_blink(&self, now: Instant) -> Option<Instant> {
+        self.caret.next_blink(now)
+    }
+
     /// Get current animation time.
     fn current_animation_time(&mut self) -> f64 {
         match &self.animation_timer {
@@ -246,6 +466,9 @@ impl ManagedWindow {
 
         let mut inner = self.doc.inner_mut();
         inner.resolve(animation_time);
+        if std::mem::take(&mut self.scroll_start_pending) {
+            direction::scroll_to_inline_start(&mut inner);
+        }
 
         let (width, height) = inner.viewport().window_size;
         let scale = inner.viewport().scale_f64();
@@ -256,7 +479,14 @@ impl ManagedWindow {
             return;
         }
 
-        self.renderer.render(|scene| paint_scene(scene, &inner, scale, width, height));
+        let marks = Marks {
+            caret: self.caret.update(&inner, Instant::now()),
+            misspellings: self.spelling.update(&inner),
+        };
+        let renderer = &mut self.renderer;
+        self.overlay.prepare(inner.viewport(), animation_time, |overlay| {
+            renderer.render(|scene| overlay::paint(scene, &inner, &marks, overlay, scale, width, height))
+        });
 
         drop(inner);
 
@@ -270,8 +500,9 @@ impl ManagedWindow {
         self.renderer.suspend();
     }
 
-    /// Poll for document updates.
+    /// Poll for document updates and loaded resources.
     pub fn poll(&mut self) -> bool {
+        let loaded = load_resources(&mut self.doc, &self.resources);
         if let Some(waker) = &self.waker {
             let cx = std::task::Context::from_waker(waker);
             if self.doc.poll(Some(cx)) {
@@ -279,11 +510,18 @@ impl ManagedWindow {
                 return true;
             }
         }
-        false
+        if loaded {
+            self.request_redraw();
+        }
+        loaded
     }
 
     /// Redraw the window.
     pub fn redraw(&mut self) {
+        // Nothing would show; the window is redrawn when it is uncovered
+        if self.occluded {
+            return;
+        }
         let animation_time = self.current_animation_time();
         let is_visible = self.is_visible;
 
@@ -293,10 +531,24 @@ impl ManagedWindow {
         let (width, height) = inner.viewport().window_size;
         let scale = inner.viewport().scale_f64();
         let is_animating = inner.is_animating();
+        devtools_overlay::update_inspector_layer(&mut self.overlay, &inner, self.devtools.selected_node);
 
-        self.renderer.render(|scene| paint_scene(scene, &inner, scale, width, height));
+        let marks = Marks {
+            caret: self.caret.update(&inner, Instant::now()),
+            misspellings: self.spelling.update(&inner),
+        };
+        let renderer = &mut self.renderer;
+        self.overlay.prepare(inner.viewport(), animation_time, |overlay| {
+            renderer.render(|scene| overlay::paint(scene, &inner, &marks, overlay, scale, width, height))
+        });
 
         drop(inner);
+        if std::mem::take(&mut self.accessibility_stale) {
+            self.update_accessibility();
+        }
+        self.update_ime_cursor_area();
+        self.report_scroll();
+        self.report_observed();
 
         if is_visible && is_animating {
             self.request_redraw();
@@ -305,13 +557,33 @@ impl ManagedWindow {
 
     /// Handle a winit window event.
     pub fn handle_event(&mut self, event: WindowEvent) {
+        self.accessibility.process_event(&self.window, &event);
+        // A focused window is on screen, and input has to reach the elements
+        // of the latest render, whose handler IDs are the registered ones
+        if matches!(
+            event,
+            WindowEvent::Focused(true)
+                | WindowEvent::KeyboardInput { .. }
+                | WindowEvent::Ime(_)
+                | WindowEvent::MouseInput { .. }
+        ) {
+            self.show_latest_content();
+        }
         match event {
             WindowEvent::RedrawRequested => {
                 self.redraw();
             }
+            WindowEvent::Focused(focused) => {
+                self.caret.set_window_focused(focused);
+                self.request_redraw();
+            }
             WindowEvent::Occluded(is_occluded) => {
                 self.is_visible = !is_occluded;
+                self.occluded = is_occluded;
                 if self.is_visible {
+                    if !self.is_hidden() {
+                        self.show_latest_content();
+                    }
                     self.request_redraw();
                 }
             }
@@ -322,27 +594,29 @@ impl ManagedWindow {
                 drop(inner);
                 if width > 0 && height > 0 {
                     self.renderer.set_size(width, height);
+                    // Restored after being minimized
+                    if !self.is_hidden() {
+                        self.show_latest_content();
+                    }
                     self.request_redraw();
                 }
+                self.accessibility_stale = true;
             }
-            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                let mut inner = self.doc.inner_mut();
-                inner.viewport_mut().set_hidpi_scale(scale_factor as f32);
-                drop(inner);
+            WindowEvent::ScaleFactorChanged { .. } => self.apply_scale(),
+            // An explicit color scheme ignores the OS setting
+            WindowEvent::ThemeChanged(theme)
+                if self.props.color_scheme == WindowColorScheme::System =>
+            {
+                let color_scheme = resolve_color_scheme(self.props.color_scheme, Some(theme));
+                self.doc.inner_mut().viewport_mut().color_scheme = color_scheme;
                 self.request_redraw();
             }
-            WindowEvent::ThemeChanged(theme) => {
-                let color_scheme = match theme {
-                    Theme::Light => ColorScheme::Light,
-                    Theme::Dark => ColorScheme::Dark,
-                };
-                let mut inner = self.doc.inner_mut();
-                inner.viewport_mut().color_scheme = color_scheme;
-            }
             WindowEvent::ModifiersChanged(new_state) => {
                 self.keyboard_modifiers = new_state;
             }
             WindowEvent::KeyboardInput { event, .. } => {
+                self.forward_key_event(&event);
+
                 let PhysicalKey::Code(key_code) = event.physical_key else {
                     return;
                 };
@@ -353,21 +627,12 @@ impl ManagedWindow {
                     let alt = self.keyboard_modifiers.state().alt_key();
                     let shift = self.keyboard_modifiers.state().shift_key();
 
-                    // Ctrl/Cmd keyboard shortcuts for zoom
+                    // Ctrl/Cmd keyboard shortcuts for zoom, in every window
                     if ctrl || meta {
                         match key_code {
-                            KeyCode::Equal => {
-                                self.doc.inner_mut().viewport_mut().zoom_by(0.1);
-                                self.request_redraw();
-                            }
-                            KeyCode::Minus => {
-                                self.doc.inner_mut().viewport_mut().zoom_by(-0.1);
-                                self.request_redraw();
-                            }
-                            KeyCode::Digit0 => {
-                                self.doc.inner_mut().viewport_mut().set_zoom(1.0);
-                                self.request_redraw();
-                            }
+                            KeyCode::Equal => crate::ui_settings::zoom_by(0.1),
+                            KeyCode::Minus => crate::ui_settings::zoom_by(-0.1),
+                            KeyCode::Digit0 => crate::ui_settings::reset_zoom(),
                             _ => {}
                         }
                     }
@@ -375,17 +640,8 @@ impl ManagedWindow {
                     // Alt keyboard shortcuts for dev tools
                     if alt {
                         match key_code {
-                            KeyCode::KeyD => {
-                                self.doc.inner_mut().devtools_mut().toggle_show_layout();
-                                self.request_redraw();
-                            }
-                            KeyCode::KeyI => {
-                                // Toggle hover highlight (inspect mode)
-                                self.doc.inner_mut().devtools_mut().toggle_highlight_hover();
-                                self.devtools.toggle_inspect_mode();
-                                tracing::info!("Inspect mode: {}", self.devtools.inspect_mode);
-                                self.request_redraw();
-                            }
+                            KeyCode::KeyD => crate::ui_settings::toggle_layout_debug(),
+                            KeyCode::KeyI => self.toggle_inspect_mode(),
                             KeyCode::KeyT => {
                                 self.doc.inner().print_taffy_tree();
                             }
@@ -401,18 +657,42 @@ impl ManagedWindow {
                     }
 
                     // Send keyboard shortcut to runtime for menu accelerator matching
-                    let _ = self.proxy.send_event(RinchEvent::KeyboardShortcut {
-                        ctrl,
-                        meta,
-                        alt,
-                        shift,
+                    let key_text = |key: &Key| match key {
+                        Key::Character(text) => Some(text.to_string()),
+                        _ => None,
+                    };
+
+                    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+                    let unmodified_text = {
+                        use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+                        key_text(&event.key_without_modifiers())
+                    };
+                    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+                    let unmodified_text = key_text(&event.logical_key);
+
+                    let _ = self.proxy.send_event(RinchEvent::KeyboardShortcut(KeyPress {
+                        modifiers: rinch_core::event::Modifiers { ctrl, alt, shift, meta },
                         key: key_code,
-                    });
+                        named_key: match &event.logical_key {
+                            Key::Named(named) => Some(*named),
+                            _ => None,
+                        },
+                        text: key_text(&event.logical_key),
+                        unmodified_text,
+                    }));
                 }
             }
             WindowEvent::CursorMoved { position, .. } => {
-                let pos: winit::dpi::LogicalPosition<f32> = position.to_logical(self.window.scale_factor());
+                let pos: winit::dpi::LogicalPosition<f32> = position.to_logical(self.hidpi_scale() as f64);
                 self.mouse_pos = (pos.x, pos.y);
+                self.mouse_inside = true;
+                if let Some(menu) = &mut self.context_menu
+                    && menu.hover(&mut self.overlay, pos.x, pos.y)
+                {
+                    self.request_redraw();
+                }
+                self.drag_range();
+                self.drag_sortable();
 
                 let event = UiEvent::MouseMove(BlitzMouseButtonEvent {
                     x: pos.x,
@@ -421,7 +701,14 @@ impl ManagedWindow {
                     buttons: self.buttons,
                     mods: Default::default(),
                 });
-                self.doc.handle_ui_event(event);
+                // Dragging with a button held can select text
+                if self.buttons == MouseEventButtons::None {
+                    self.doc.handle_ui_event(event);
+                } else {
+                    self.dispatch_to_document(event);
+                }
+                self.update_tooltip_target();
+                self.report_mouse(true);
 
                 // If in inspect mode, send hovered element info to DevTools
                 if self.devtools.inspect_mode {
@@ -456,8 +743,61 @@ impl ManagedWindow {
                     ElementState::Pressed => UiEvent::MouseDown(event_data),
                     ElementState::Released => UiEvent::MouseUp(event_data),
                 };
-                self.doc.handle_ui_event(event);
-                self.request_redraw();
+                if state == ElementState::Pressed {
+                    self.hide_tooltip();
+                }
+                if state == ElementState::Pressed && self.focus_visible {
+                    let mut inner = self.doc.inner_mut();
+                    if let Some(focused) = inner.get_focussed_node_id() {
+                        focus::set_focus_visible(&mut inner, focused, false);
+                    }
+                    self.focus_visible = false;
+                }
+                self.dispatch_to_document(event);
+
+                if button == MouseEventButton::Secondary && state == ElementState::Pressed {
+                    self.show_spelling_menu();
+                }
+                if button == MouseEventButton::Main {
+                    self.range_drag = match state {
+                        ElementState::Pressed => self.range_at_mouse(),
+                        ElementState::Released => None,
+                    };
+                    self.drag_range();
+                    match state {
+                        ElementState::Pressed => self.sort_drag = self.sortable_at_mouse(),
+                        ElementState::Released => self.drop_sortable(),
+                    }
+                }
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.hide_tooltip();
+                self.tooltip_target = None;
+                self.mouse_inside = false;
+                if crate::windows::window_handle(self.window_id()).is_some() {
+                    crate::mouse::moved(None);
+                    crate::mouse::hovered(None);
+                }
+            }
+            WindowEvent::Ime(ime) => {
+                if matches!(&ime, Ime::Preedit(text, _) if !text.is_empty()) && !self.ime_composing {
+                    self.ime_text_before = self.focused_text();
+                }
+                let commit = matches!(ime, Ime::Commit(_));
+                if commit && self.ime_text_before.is_none() {
+                    self.ime_text_before = self.focused_text();
+                }
+                // The document shows the preedit text underlined at the caret
+                self.ime_composing = matches!(&ime, Ime::Preedit(text, _) if !text.is_empty());
+                self.dispatch_to_document(UiEvent::Ime(to_blitz_ime_event(ime)));
+                if commit {
+                    let before = self.ime_text_before.take();
+                    self.record_edit(before);
+                }
+                // Content that arrived during the composition
+                if !self.ime_composing && !self.is_hidden() {
+                    self.show_latest_content();
+                }
             }
             WindowEvent::MouseWheel { delta, .. } => {
                 let blitz_delta = match delta {
@@ -485,174 +825,1482 @@ impl ManagedWindow {
         }
     }
 
-    /// Update the window's HTML content and re-render.
-    pub fn update_content(&mut self, html_content: String) {
-        // Get current viewport settings
-        let (viewport, scale) = {
-            let inner = self.doc.inner();
-            (inner.viewport().clone(), inner.viewport().scale_f64())
-        };
-
-        // Create new document config with current viewport
-        let config = DocumentConfig {
-            viewport: Some(viewport),
-            ..Default::default()
-        };
-
-        // Create new document with updated HTML
-        self.doc = Box::new(HtmlDocument::from_html(&html_content, config));
-
-        // Re-resolve and redraw
-        let animation_time = self.current_animation_time();
-        {
-            let mut inner = self.doc.inner_mut();
-            inner.resolve(animation_time);
-        }
-
-        // Render the updated content
-        let inner = self.doc.inner();
-        let (width, height) = inner.viewport().window_size;
-        self.renderer.render(|scene| paint_scene(scene, &inner, scale, width, height));
-    }
-
-    /// Get information about the element under the current mouse position.
+    /// Pass a key event to the document.
     ///
-    /// Returns element info for DevTools display.
-    pub fn get_hovered_element_info(&self) -> Option<HoveredElementInfo> {
-        let inner = self.doc.inner();
-        let hit_result = inner.hit(self.mouse_pos.0, self.mouse_pos.1)?;
-        let node_id = hit_result.node_id;
-
-        let node = inner.get_node(node_id)?;
-        let element = node.element_data()?;
+    /// Keys are left to the input method while it is composing text.
+    fn forward_key_event(&mut self, event: &KeyEvent) {
+        if self.ime_composing {
+            return;
+        }
 
-        // Get tag name
-        let tag_name = element.name.local.to_string();
+        // Tab moves focus instead of reaching the document
+        let modifiers = self.keyboard_modifiers.state();
+        let plain = !(modifiers.control_key() || modifiers.alt_key() || modifiers.super_key());
+        if event.logical_key == Key::Named(NamedKey::Tab) && plain {
+            if event.state.is_pressed() {
+                let next = focus::next_in_tab_order(&self.doc.inner(), modifiers.shift_key());
+                if let Some(node_id) = next {
+                    self.set_focus(node_id, true);
+                }
+            }
+            return;
+        }
 
-        // Get id and classes
-        let mut id = None;
-        let mut classes = None;
-        for attr in element.attrs() {
-            let name = attr.name.local.as_ref();
-            if name == "id" {
-                id = Some(attr.value.to_string());
-            } else if name == "class" {
-                classes = Some(attr.value.to_string());
+        // Enter in a DevTools hook editor sets the hook, in the style input
+        // adds the declaration, and in the log filter input applies it
+        if event.logical_key == Key::Named(NamedKey::Enter) && plain && !modifiers.shift_key() {
+            if let Some((index, value)) = self.focused_hook_edit() {
+                if event.state.is_pressed() && !event.repeat {
+                    let _ = self.proxy.send_event(RinchEvent::DevToolsSetHook { index, value });
+                }
+                return;
+            }
+            if let Some(declaration) = self.focused_style_edit() {
+                if event.state.is_pressed() && !event.repeat {
+                    let _ = self.proxy.send_event(RinchEvent::DevToolsAddStyle { declaration });
+                }
+                return;
+            }
+            if let Some(directives) = self.focused_log_filter_edit() {
+                if event.state.is_pressed() && !event.repeat {
+                    let _ = self.proxy.send_event(RinchEvent::DevToolsSetLogFilter { directives });
+                }
+                return;
             }
         }
 
-        // Get layout info - hit_result has x, y; get size from the node's layout
-        let width = node.final_layout.size.width;
-        let height = node.final_layout.size.height;
+        // Enter and Space click the focused element, unless it takes text
+        if matches!(event.logical_key, Key::Named(NamedKey::Enter | NamedKey::Space)) && plain {
+            let target = focus::activation_target(&self.doc.inner());
+            if let Some(node_id) = target {
+                if event.state.is_pressed() && !event.repeat {
+                    self.activate(node_id);
+                }
+                return;
+            }
+        }
 
-        let layout = ElementLayout {
-            x: hit_result.x,
-            y: hit_result.y,
-            width,
-            height,
+        // Arrow keys move through radio groups (checking the radio button)
+        // and menus
+        let arrow = match event.logical_key {
+            Key::Named(NamedKey::ArrowUp) => Some(focus::Arrow::Up),
+            Key::Named(NamedKey::ArrowDown) => Some(focus::Arrow::Down),
+            Key::Named(NamedKey::ArrowLeft) => Some(focus::Arrow::Left),
+            Key::Named(NamedKey::ArrowRight) => Some(focus::Arrow::Right),
+            _ => None,
         };
+        if let Some(arrow) = arrow.filter(|_| plain && !modifiers.shift_key()) {
+            let target = focus::arrow_target(&self.doc.inner(), arrow);
+            if let Some(target) = target {
+                if event.state.is_pressed() {
+                    self.set_focus(target.node_id, true);
+                    if target.check {
+                        self.activate(target.node_id);
+                    }
+                }
+                return;
+            }
+        }
 
-        // Extract computed styles from the node
-        let mut styles = Vec::new();
+        // Cmd+Z (Ctrl+Z) undoes and Shift+Cmd+Z (Ctrl+Shift+Z) redoes, if
+        // there is something to undo or redo
+        let action = if cfg!(target_os = "macos") {
+            modifiers.super_key() && !modifiers.control_key()
+        } else {
+            modifiers.control_key() && !modifiers.super_key()
+        };
+        if action
+            && !modifiers.alt_key()
+            && matches!(&event.logical_key, Key::Character(text) if text.eq_ignore_ascii_case("z"))
+        {
+            let role = if modifiers.shift_key() { MenuRole::Redo } else { MenuRole::Undo };
+            if self.can_edit(role) {
+                if event.state.is_pressed() {
+                    self.edit(role);
+                }
+                return;
+            }
+        }
 
-        // Get size
-        styles.push((
-            "size".to_string(),
-            format!("{:.0} × {:.0}", width, height),
-        ));
+        let key_event = to_blitz_key_event(event, self.keyboard_modifiers.state());
+        self.dispatch_to_document(match event.state {
+            ElementState::Pressed => UiEvent::KeyDown(key_event),
+            ElementState::Released => UiEvent::KeyUp(key_event),
+        });
+    }
 
-        // Get padding from layout
-        let padding = &node.final_layout.padding;
-        if padding.top > 0.0 || padding.right > 0.0 || padding.bottom > 0.0 || padding.left > 0.0 {
-            styles.push((
-                "padding".to_string(),
-                format!(
-                    "{:.0} {:.0} {:.0} {:.0}",
-                    padding.top, padding.right, padding.bottom, padding.left
-                ),
-            ));
+    /// Pass an event to the document, reporting edits and selection changes
+    /// in text inputs and toggled checkboxes and radio buttons.
+    fn dispatch_to_document(&mut self, event: UiEvent) {
+        // The window records IME compositions itself, once committed
+        let ime = matches!(event, UiEvent::Ime(_));
+        if !ime {
+            self.ime_text_before = None;
+        }
+        let text_before = if ime { None } else { self.focused_text() };
+        let before = self.focused_input();
+        let toggles_before = self.toggle_states();
+        self.doc.handle_ui_event(event);
+        self.record_edit(text_before);
+
+        let toggles_after = self.toggle_states();
+        if toggles_before.len() == toggles_after.len() {
+            for (before, after) in toggles_before.iter().zip(toggles_after) {
+                if before.0 == after.0 && before.1 != after.1 {
+                    let _ = self.proxy.send_event(RinchEvent::ElementChange {
+                        handler_id: after.0,
+                        checked: after.1,
+                        window_id: self.window_id(),
+                    });
+                }
+            }
         }
 
-        // Get margin from layout
-        let margin = &node.final_layout.margin;
-        if margin.top > 0.0 || margin.right > 0.0 || margin.bottom > 0.0 || margin.left > 0.0 {
-            styles.push((
-                "margin".to_string(),
-                format!(
-                    "{:.0} {:.0} {:.0} {:.0}",
-                    margin.top, margin.right, margin.bottom, margin.left
-                ),
-            ));
+        // The text being composed isn't part of the value until it is committed
+        if let Some(after) = self.focused_input().filter(|_| !self.ime_composing) {
+            let before = before.filter(|before| before.node_id == after.node_id);
+            if let (Some(before), Some(handler_id)) = (&before, after.input_handler)
+                && before.value != after.value
+            {
+                let _ = self.proxy.send_event(RinchEvent::ElementInput {
+                    handler_id,
+                    value: after.value,
+                    window_id: self.window_id(),
+                });
+            }
+            // A newly focused input reports its selection too
+            if let Some(handler_id) = after.select_handler
+                && before.is_none_or(|before| before.selection != after.selection)
+            {
+                let _ = self.proxy.send_event(RinchEvent::ElementSelect {
+                    handler_id,
+                    start: after.selection.start,
+                    end: after.selection.end,
+                    window_id: self.window_id(),
+                });
+            }
         }
+        self.update_ime_allowed();
+        self.accessibility_stale = true;
+        self.request_redraw();
+    }
 
-        // Get border from layout
-        let border = &node.final_layout.border;
-        if border.top > 0.0 || border.right > 0.0 || border.bottom > 0.0 || border.left > 0.0 {
-            styles.push((
-                "border-width".to_string(),
-                format!(
-                    "{:.0} {:.0} {:.0} {:.0}",
-                    border.top, border.right, border.bottom, border.left
-                ),
-            ));
+    /// Whether the focused text input, or for undo and redo the window's
+    /// undo manager, can perform an editing command.
+    pub fn can_edit(&self, role: MenuRole) -> bool {
+        if self.ime_composing {
+            return false;
         }
+        match role {
+            MenuRole::Undo => self.undo_manager().is_some_and(|undo| untracked(|| undo.can_undo().get())),
+            MenuRole::Redo => self.undo_manager().is_some_and(|undo| untracked(|| undo.can_redo().get())),
+            _ => edit::can_perform(&self.doc.inner(), role),
+        }
+    }
 
-        Some(HoveredElementInfo {
-            tag_name,
-            id,
-            classes,
-            styles,
-            layout,
-        })
+    /// Perform an editing command on the focused text input, as typing its
+    /// shortcut does, or undo or redo a change of the window.
+    pub fn edit(&mut self, role: MenuRole) {
+        if !self.can_edit(role) {
+            return;
+        }
+        match role {
+            MenuRole::Undo | MenuRole::Redo => {
+                if let Some(undo) = self.undo_manager() {
+                    let _ = if role == MenuRole::Undo { undo.undo() } else { undo.redo() };
+                }
+            }
+            _ => {
+                for event in keys::shortcut_events(edit::shortcut_letter(role)) {
+                    self.dispatch_to_document(event);
+                }
+            }
+        }
     }
 
-    /// Get the event handler ID of the element under the current mouse position.
-    ///
-    /// Returns `Some(id)` if there's an element with a `data-rid` attribute at the
-    /// current mouse position, `None` otherwise.
-    pub fn get_clicked_handler(&self) -> Option<EventHandlerId> {
-        let inner = self.doc.inner();
+    /// The undo manager of this window, if it is open to the app.
+    fn undo_manager(&self) -> Option<UndoManager> {
+        crate::windows::window_handle(self.window_id()).map(crate::undo::undo_manager)
+    }
 
-        // Hit test at current mouse position
-        let hit_result = inner.hit(self.mouse_pos.0, self.mouse_pos.1)?;
-        let node_id = hit_result.node_id;
+    /// Record the edit of the focused text input since its text was
+    /// `before`, if it was edited.
+    fn record_edit(&self, before: Option<(usize, String)>) {
+        let (Some((node_id, before)), Some((after_id, after))) = (before, self.focused_text()) else {
+            return;
+        };
+        if node_id != after_id || before == after || self.ime_composing {
+            return;
+        }
+        let Some(window) = crate::windows::window_handle(self.window_id()) else {
+            return;
+        };
+        let path = node_path(&self.doc.inner(), node_id);
+        crate::undo::record_text_edit(window, path, before, after);
+    }
 
-        // Walk up the tree looking for a data-rid attribute
-        let mut current = Some(node_id);
-        while let Some(id) = current {
-            if let Some(node) = inner.get_node(id) {
-                if let Some(element) = node.element_data() {
-                    // Check all attributes for data-rid
-                    for attr in element.attrs() {
-                        if attr.name.local.as_ref() == "data-rid" {
-                            if let Ok(rid) = attr.value.parse::<usize>() {
-                                return Some(EventHandlerId(rid));
-                            }
-                        }
-                    }
-                }
-                current = node.parent;
-            } else {
-                break;
+    /// Focus the element with the given `id` attribute.
+    ///
+    /// If there's no such element yet, it is focused after the re-render that
+    /// creates it, so an event handler can show an element and focus it.
+    pub fn focus_element(&mut self, id: &str) {
+        let node_id = focus::element_by_id(&self.doc.inner(), id);
+        match node_id {
+            Some(node_id) => {
+                self.pending_focus = None;
+                self.set_focus(node_id, self.focus_visible);
             }
+            None => self.pending_focus = Some(id.to_string()),
         }
+    }
 
-        None
+    /// Scroll the element matching a CSS selector.
+    ///
+    /// `after_render` works as for [`with_document`](Self::with_document).
+    pub fn scroll(&mut self, selector: &str, target: ScrollTarget, after_render: bool) {
+        let selector = selector.to_string();
+        self.with_document(Box::new(move |doc| scroll::apply(doc, &selector, target)), after_render);
     }
 
-    /// Check if the element under the current mouse position should trigger window dragging.
+    /// Call `f` with the document and redraw the window.
     ///
-    /// Returns `true` if there's an element with `data-drag-window` attribute at the
-    /// current mouse position.
-    pub fn should_drag_window(&self) -> bool {
-        let inner = self.doc.inner();
+    /// With `after_render`, `f` waits for the re-render that is about to
+    /// happen (see [`run_after_render`](Self::run_after_render)), so it sees
+    /// the content the app just added.
+    pub fn with_document(&mut self, f: DocumentCallback, after_render: bool) {
+        if after_render {
+            self.after_render.push(f);
+            return;
+        }
+        f(&mut self.doc.inner_mut());
+        self.accessibility_stale = true;
+        self.request_redraw();
+    }
 
-        // Hit test at current mouse position
-        let Some(hit_result) = inner.hit(self.mouse_pos.0, self.mouse_pos.1) else {
+    /// Set attributes and text bound to signals that changed.
+    ///
+    /// Returns `false` if the document is waiting for the window to show, or
+    /// bound text couldn't be updated in place; the app should then render
+    /// again instead.
+    pub fn patch_bindings(&mut self, patches: &[BindingPatch]) -> bool {
+        if self.content_stale {
             return false;
-        };
+        }
+        let mut applied = true;
+        {
+            let mut inner = self.doc.inner_mut();
+            for patch in patches {
+                applied &= bindings::apply(&mut inner, patch);
+            }
+        }
+        applied &= self.overlay.patch_bindings(patches);
+        // Keep rebuilds of the document from bringing old values back
+        for patch in patches {
+            bindings::patch_content(&mut self.content, patch);
+        }
+        self.accessibility_stale = true;
+        self.request_redraw();
+        applied
+    }
+
+    /// Call the [`with_document`](Self::with_document) callbacks that
+    /// waited for a re-render, unless the document is still waiting for the
+    /// window to show (called by the runtime after re-rendering).
+    pub fn run_after_render(&mut self) {
+        if self.after_render.is_empty() || self.content_stale {
+            return;
+        }
+        {
+            let mut inner = self.doc.inner_mut();
+            for f in std::mem::take(&mut self.after_render) {
+                f(&mut inner);
+            }
+        }
+        self.accessibility_stale = true;
+        self.request_redraw();
+    }
+
+    /// Update the signals following scroll positions in this window.
+    fn report_scroll(&self) {
+        let Some(handle) = crate::windows::window_handle(self.window_id()) else {
+            return;
+        };
+        if !crate::scroll::is_watched(handle) {
+            return;
+        }
+        let inner = self.doc.inner();
+        crate::scroll::update(handle, |selector| scroll::state(&inner, selector));
+    }
+
+    /// Update the signals following whether elements show in this window,
+    /// their sizes, and the media queries matched against it.
+    fn report_observed(&self) {
+        let (visibility, size) = (crate::observe::is_visibility_watched(), crate::observe::is_size_watched());
+        let media = crate::media::is_watched();
+        if !visibility && !size && !media {
+            return;
+        }
+        let Some(handle) = crate::windows::window_handle(self.window_id()) else {
+            return;
+        };
+        let inner = self.doc.inner();
+        if media {
+            let viewport = inner.viewport();
+            let scale = viewport.scale();
+            crate::media::update(handle, crate::media::Media {
+                width: viewport.window_size.0 as f32 / scale,
+                height: viewport.window_size.1 as f32 / scale,
+                dark: viewport.color_scheme == ColorScheme::Dark,
+            });
+        }
+        if visibility {
+            crate::observe::update_visibility(handle, |selector| scroll::is_visible(&inner, selector));
+        }
+        if size {
+            crate::observe::update_sizes(handle, |selector| element_size(&inner, selector));
+        }
+    }
+
+    /// Update the signals following the mouse, if the app asked for them
+    /// and the mouse is over the window: the position if the mouse `moved`,
+    /// and the element under it.
+    fn report_mouse(&self, moved: bool) {
+        if !self.mouse_inside {
+            return;
+        }
+        let Some(handle) = crate::windows::window_handle(self.window_id()) else {
+            return;
+        };
+        let (x, y) = self.mouse_pos;
+        if moved && crate::mouse::is_position_watched() {
+            crate::mouse::moved(Some(crate::mouse::MousePosition { window: handle, x, y }));
+        }
+        if crate::mouse::is_hovered_watched() {
+            crate::mouse::hovered(hovered_element(&self.doc.inner(), handle, x, y));
+        }
+    }
+
+    /// Move focus to a node, with a focus ring if `visible`.
+    fn set_focus(&mut self, node_id: usize, visible: bool) {
+        {
+            let mut inner = self.doc.inner_mut();
+            if let Some(previous) = inner.get_focussed_node_id() {
+                focus::set_focus_visible(&mut inner, previous, false);
+            }
+            inner.set_focus_to(node_id);
+            if visible {
+                focus::set_focus_visible(&mut inner, node_id, true);
+            }
+        }
+        self.focus_visible = visible;
+        self.update_ime_allowed();
+        self.accessibility_stale = true;
+        self.request_redraw();
+    }
+
+    /// Send the accessibility tree to the platform, if assistive technology
+    /// is using it.
+    ///
+    /// Redraws call this once the document changed; it's only called
+    /// directly when the platform asks for the tree.
+    pub fn update_accessibility(&mut self) {
+        let title = self.window.title();
+        let inner = self.doc.inner();
+        let scale = inner.viewport().scale_f64();
+        self.accessibility
+            .update_if_active(|| accessibility::build_tree(&inner, &title, scale));
+    }
+
+    /// Perform an action requested by assistive technology.
+    pub fn handle_accessibility_action(&mut self, request: accesskit::ActionRequest) {
+        let Some(node_id) = accessibility::dom_node(request.target) else {
+            return;
+        };
+        match request.action {
+            accesskit::Action::Focus => self.set_focus(node_id, true),
+            accesskit::Action::Default => self.activate(node_id),
+            _ => {}
+        }
+    }
+
+    /// Click an element without the mouse, for the keyboard and assistive
+    /// technology: runs its `onclick` handler and opens its link.
+    fn activate(&mut self, node_id: usize) {
+        self.click_node(node_id);
+        // The click may have landed on a child that takes focus
+        let focused = self.doc.inner().get_focussed_node_id();
+        if focused != Some(node_id) && focus::can_focus_node(&self.doc.inner(), node_id) {
+            self.set_focus(node_id, self.focus_visible);
+        }
+
+        let (handler_id, href) = {
+            let inner = self.doc.inner();
+            (click_handler(&inner, node_id), link_href(&inner, node_id))
+        };
+        if let Some(handler_id) = handler_id {
+            let _ = self.proxy.send_event(RinchEvent::ElementClicked {
+                handler_id,
+                window_id: self.window_id(),
+            });
+        }
+        if let Some(href) = href {
+            super::external::open_link(&href);
+        }
+    }
+
+    /// Click the center of a node, so the document toggles checkboxes and
+    /// focuses inputs as it does for the mouse.
+    fn click_node(&mut self, node_id: usize) {
+        let (x, y) = {
+            let inner = self.doc.inner();
+            let Some(node) = inner.get_node(node_id) else {
+                return;
+            };
+            let origin = node.absolute_position(0.0, 0.0);
+            let size = node.final_layout.size;
+            (origin.x + size.width / 2.0, origin.y + size.height / 2.0)
+        };
+        let event = |buttons| BlitzMouseButtonEvent {
+            x,
+            y,
+            button: MouseEventButton::Main,
+            buttons,
+            mods: Default::default(),
+        };
+        self.dispatch_to_document(UiEvent::MouseDown(event(MouseEventButton::Main.into())));
+        self.dispatch_to_document(UiEvent::MouseUp(event(MouseEventButtons::None)));
+    }
+
+    /// Track the element with a tooltip under the mouse, starting the delay
+    /// before its tooltip is shown when it changes.
+    fn update_tooltip_target(&mut self) {
+        let target = tooltip::target_at(&self.doc.inner(), self.mouse_pos.0, self.mouse_pos.1);
+        if target == self.tooltip_target {
+            return;
+        }
+        self.hide_tooltip();
+        self.tooltip_target = target;
+        if target.is_none() {
+            return;
+        }
+
+        let proxy = self.proxy.clone();
+        let window_id = self.window_id();
+        let timer = self.tooltip_timer;
+        std::thread::spawn(move || {
+            std::thread::sleep(tooltip::DELAY);
+            let _ = proxy.send_event(RinchEvent::ShowTooltip { window_id, timer });
+        });
+    }
+
+    /// Show the tooltip of the element under the mouse, if `timer` is the
+    /// latest tooltip timer and the mouse hasn't left the element since.
+    pub fn show_tooltip(&mut self, timer: u64) {
+        if timer != self.tooltip_timer || self.tooltip_visible {
+            return;
+        }
+        let Some((text, anchor)) = self.tooltip_target.and_then(|target| tooltip::of(&self.doc.inner(), target)) else {
+            return;
+        };
+        let animation_time = self.current_animation_time();
+        self.tooltip_visible = self.overlay.show_tooltip(text, anchor, animation_time);
+        self.request_redraw();
+    }
+
+    /// Hide the tooltip and cancel any pending one.
+    ///
+    /// The tooltip doesn't come back until the mouse moves to another element.
+    fn hide_tooltip(&mut self) {
+        self.tooltip_timer += 1;
+        if self.tooltip_visible {
+            self.tooltip_visible = false;
+            self.overlay.hide_tooltip();
+            self.request_redraw();
+        }
+    }
+
+    /// Give input to the context menu while one is open: clicking an item
+    /// runs its action, and clicking elsewhere, pressing a key, scrolling
+    /// or the window losing focus closes it.
+    ///
+    /// Returns `true` if the menu took the event, so it goes no further.
+    pub fn context_menu_input(&mut self, event: &WindowEvent) -> bool {
+        if self.context_menu_release
+            && let WindowEvent::MouseInput { state: ElementState::Released, .. } = event
+        {
+            self.context_menu_release = false;
+            return true;
+        }
+        let Some(menu) = &self.context_menu else {
+            return false;
+        };
+        match event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, .. } => {
+                let click = menu.click(&self.overlay, self.mouse_pos.0, self.mouse_pos.1);
+                self.close_context_menu();
+                let taken = !matches!(click, Click::Outside);
+                if let Click::Item(action) = click {
+                    self.run_menu_action(action);
+                }
+                self.context_menu_release = taken;
+                taken
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                self.close_context_menu();
+                event.logical_key == Key::Named(NamedKey::Escape)
+            }
+            WindowEvent::MouseWheel { .. } | WindowEvent::Focused(false) | WindowEvent::Resized(_) => {
+                self.close_context_menu();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn close_context_menu(&mut self) {
+        if self.context_menu.take().is_some() {
+            ContextMenu::hide(&mut self.overlay);
+            self.request_redraw();
+        }
+    }
+
+    fn run_menu_action(&mut self, action: Action) {
+        match action {
+            Action::ReplaceText { node_id, range, text } => self.replace_text(node_id, range, &text),
+        }
+    }
+
+    /// Show the corrections of the misspelled word under the mouse, if any.
+    fn show_spelling_menu(&mut self) {
+        let (x, y) = self.mouse_pos;
+        let (word, size) = {
+            let inner = self.doc.inner();
+            let scale = inner.viewport().scale();
+            let (width, height) = inner.viewport().window_size;
+            (self.spelling.word_at(&inner, x, y), (width as f32 / scale, height as f32 / scale))
+        };
+        let (Some((node_id, range, word)), Some(checker)) = (word, crate::spelling::checker()) else {
+            return;
+        };
+        let mut items: Vec<Item> = checker
+            .suggest(&word)
+            .into_iter()
+            .take(crate::spelling::MAX_SUGGESTIONS)
+            .map(|text| {
+                let label = text.clone();
+                Item::new(label, Action::ReplaceText { node_id, range: range.clone(), text })
+            })
+            .collect();
+        if items.is_empty() {
+            items.push(Item::disabled("No suggestions"));
+        }
+        self.context_menu = Some(ContextMenu::show(&mut self.overlay, items, (x, y), size));
+        self.request_redraw();
+    }
+
+    /// Replace part of the text of a text input, reporting it to the
+    /// input's `oninput` handler like an edit.
+    fn replace_text(&mut self, node_id: usize, range: Range<usize>, text: &str) {
+        let before = self.focused_text().filter(|(focused, _)| *focused == node_id);
+        let Some(value) = spelling::replace(&mut self.doc.inner_mut(), node_id, range, text) else {
+            return;
+        };
+        self.record_edit(before);
+        let handler_id = {
+            let inner = self.doc.inner();
+            inner
+                .get_node(node_id)
+                .and_then(|node| node.element_data())
+                .and_then(|element| handler_attr(element, INPUT_HANDLER_ATTR))
+        };
+        if let Some(handler_id) = handler_id {
+            let _ = self.proxy.send_event(RinchEvent::ElementInput {
+                handler_id,
+                value,
+                window_id: self.window_id(),
+            });
+        }
+        self.request_redraw();
+    }
+
+    /// The path of the enabled range input under the mouse, if any.
+    fn range_at_mouse(&self) -> Option<Vec<usize>> {
+        let inner = self.doc.inner();
+        let node_id = hit::node_at(&inner, self.mouse_pos.0, self.mouse_pos.1)?;
+        let element = inner.get_node(node_id)?.element_data()?;
+        if element.attrs().iter().any(|attr| attr.name.local.as_ref() == "disabled") {
+            return None;
+        }
+        RangeInput::from_element(element)?;
+        Some(node_path(&inner, node_id))
+    }
+
+    /// Move the value of the range input being dragged to the mouse position,
+    /// reporting changes to its `oninput` handler.
+    fn drag_range(&mut self) {
+        let Some(path) = &self.range_drag else {
+            return;
+        };
+        let dragged = {
+            let inner = self.doc.inner();
+            node_at_path(&inner, path).and_then(|node_id| {
+                let node = inner.get_node(node_id)?;
+                let element = node.element_data()?;
+                let input = RangeInput::from_element(element)?;
+
+                // The thumb's center stays half the input's height from the ends
+                let origin = node.absolute_position(0.0, 0.0);
+                let size = node.final_layout.size;
+                let inset = size.height.min(size.width) / 2.0;
+                let track = size.width - 2.0 * inset;
+                let mut position = if track > 0.0 {
+                    (self.mouse_pos.0 - origin.x - inset) / track
+                } else {
+                    0.0
+                };
+                // Right-to-left sliders start at the right end
+                if direction::is_rtl(&inner, node_id) {
+                    position = 1.0 - position;
+                }
+                Some((node_id, input, position, handler_attr(element, INPUT_HANDLER_ATTR)))
+            })
+        };
+        let Some((node_id, mut input, position, handler_id)) = dragged else {
+            return;
+        };
+        if !input.set_position(f64::from(position)) {
+            return;
+        }
+
+        range::apply(&mut self.doc.inner_mut(), node_id, &input);
+        if let Some(handler_id) = handler_id {
+            let _ = self.proxy.send_event(RinchEvent::ElementInput {
+                handler_id,
+                value: input.value_string(),
+                window_id: self.window_id(),
+            });
+        }
+        self.request_redraw();
+    }
+
+    /// The item of a sortable element under the mouse, if any.
+    fn sortable_at_mouse(&self) -> Option<SortDrag> {
+        let inner = self.doc.inner();
+        let (container, from) = sortable::item_at(&inner, self.mouse_pos.0, self.mouse_pos.1)?;
+        Some(SortDrag {
+            container: node_path(&inner, container),
+            from,
+            start: self.mouse_pos,
+            slot: None,
+        })
+    }
+
+    /// Move the drop position of the sortable item being dragged to the mouse.
+    fn drag_sortable(&mut self) {
+        let Some(drag) = &mut self.sort_drag else {
+            return;
+        };
+        if drag.slot.is_none() {
+            let distance = (self.mouse_pos.0 - drag.start.0).hypot(self.mouse_pos.1 - drag.start.1);
+            if distance < sortable::DRAG_THRESHOLD {
+                return;
+            }
+        }
+
+        let mut inner = self.doc.inner_mut();
+        let Some(container) = node_at_path(&inner, &drag.container) else {
+            return;
+        };
+        let slot = sortable::drop_slot(&inner, container, self.mouse_pos.0, self.mouse_pos.1);
+        if drag.slot != Some(slot) {
+            drag.slot = Some(slot);
+            sortable::mark(&mut inner, container, drag.from, Some(slot));
+            drop(inner);
+            self.request_redraw();
+        }
+    }
+
+    /// Finish dragging a sortable item, reporting the move to the sortable
+    /// element's `onreorder` handler.
+    fn drop_sortable(&mut self) {
+        let Some(SortDrag { container, from, slot: Some(slot), .. }) = self.sort_drag.take() else {
+            return;
+        };
+        let handler_id = {
+            let mut inner = self.doc.inner_mut();
+            let Some(container) = node_at_path(&inner, &container) else {
+                return;
+            };
+            sortable::mark(&mut inner, container, from, None);
+            inner
+                .get_node(container)
+                .and_then(|node| node.element_data())
+                .and_then(|element| handler_attr(element, sortable::REORDER_HANDLER_ATTR))
+        };
+        if let (Some(handler_id), Some(to)) = (handler_id, sortable::target_index(from, slot)) {
+            let _ = self.proxy.send_event(RinchEvent::ElementReorder {
+                handler_id,
+                from,
+                to,
+                window_id: self.window_id(),
+            });
+        }
+        self.request_redraw();
+    }
+
+    /// Enable IME input while a text input (or textarea) has focus.
+    fn update_ime_allowed(&mut self) {
+        let allowed = {
+            let inner = self.doc.inner();
+            inner
+                .get_focussed_node_id()
+                .and_then(|node_id| inner.get_node(node_id))
+                .and_then(|node| node.element_data())
+                .is_some_and(|element| element.text_input_data().is_some())
+        };
+        if allowed != self.ime_allowed {
+            self.ime_allowed = allowed;
+            self.ime_composing = false;
+            self.ime_cursor_area = None;
+            self.window.set_ime_allowed(allowed);
+        }
+    }
+
+    /// Put the input method's candidate window next to the caret of the
+    /// focused text input.
+    fn update_ime_cursor_area(&mut self) {
+        if !self.ime_allowed {
+            return;
+        }
+        let area = {
+            let inner = self.doc.inner();
+            let scale = inner.viewport().scale_f64();
+            inner
+                .get_focussed_node_id()
+                .and_then(|node_id| inner.get_node(node_id))
+                .and_then(|node| {
+                    let caret = node.element_data()?.text_input_data()?.editor.ime_cursor_area();
+                    // The editor's text starts at the input's content box
+                    let layout = node.final_layout;
+                    let origin = node.absolute_position(
+                        layout.border.left + layout.padding.left,
+                        layout.border.top + layout.padding.top,
+                    );
+                    Some([
+                        (origin.x as f64 + caret.x0) * scale,
+                        (origin.y as f64 + caret.y0) * scale,
+                        (caret.x1 - caret.x0) * scale,
+                        (caret.y1 - caret.y0) * scale,
+                    ])
+                })
+        };
+        if let Some(area) = area
+            && self.ime_cursor_area != Some(area)
+        {
+            self.ime_cursor_area = Some(area);
+            let [x, y, width, height] = area;
+            self.window.set_ime_cursor_area(PhysicalPosition::new(x, y), PhysicalSize::new(width, height));
+        }
+    }
+
+    /// The focused text input, if it has an `oninput` or `onselect` handler.
+    fn focused_input(&self) -> Option<FocusedInput> {
+        let inner = self.doc.inner();
+        let node_id = inner.get_focussed_node_id()?;
+        let element = inner.get_node(node_id)?.element_data()?;
+        let input_handler = handler_attr(element, INPUT_HANDLER_ATTR);
+        let select_handler = handler_attr(element, SELECT_HANDLER_ATTR);
+        if input_handler.is_none() && select_handler.is_none() {
+            return None;
+        }
+        let editor = &element.text_input_data()?.editor;
+
+        Some(FocusedInput {
+            node_id,
+            input_handler,
+            select_handler,
+            value: editor.text().to_string(),
+            selection: editor.raw_selection().text_range(),
+        })
+    }
+
+    /// The hook index and text of the focused DevTools hook editor, if any.
+    fn focused_hook_edit(&self) -> Option<(usize, String)> {
+        let inner = self.doc.inner();
+        let element = inner.get_node(inner.get_focussed_node_id()?)?.element_data()?;
+        let index = focus::attr(element, HOOK_VALUE_ATTR)?.parse().ok()?;
+        Some((index, element.text_input_data()?.editor.text().to_string()))
+    }
+
+    /// The focused text input and its text, if a text input has focus.
+    fn focused_text(&self) -> Option<(usize, String)> {
+        let inner = self.doc.inner();
+        let node_id = inner.get_focussed_node_id()?;
+        let element = inner.get_node(node_id)?.element_data()?;
+        Some((node_id, element.text_input_data()?.editor.text().to_string()))
+    }
+
+    /// The text of the focused DevTools style input, if it has focus.
+    fn focused_style_edit(&self) -> Option<String> {
+        let inner = self.doc.inner();
+        let element = inner.get_node(inner.get_focussed_node_id()?)?.element_data()?;
+        focus::attr(element, STYLE_ADD_ATTR)?;
+        Some(element.text_input_data()?.editor.text().to_string())
+    }
+
+    /// The text of the focused DevTools log filter input, if it has focus.
+    fn focused_log_filter_edit(&self) -> Option<String> {
+        let inner = self.doc.inner();
+        let element = inner.get_node(inner.get_focussed_node_id()?)?.element_data()?;
+        focus::attr(element, LOG_FILTER_ATTR)?;
+        Some(element.text_input_data()?.editor.text().to_string())
+    }
+
+    /// Whether a text input has focus.
+    pub(crate) fn is_editing_text(&self) -> bool {
+        self.ime_allowed
+    }
+
+    /// The checked state of every checkbox and radio button with an
+    /// `onchange` handler, in document order.
+    fn toggle_states(&self) -> Vec<(EventHandlerId, bool)> {
+        let inner = self.doc.inner();
+        let mut states = Vec::new();
+        let mut stack = vec![inner.root_node().id];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = inner.get_node(node_id) else {
+                continue;
+            };
+            if let Some(element) = node.element_data()
+                && let Some(checked) = element.checkbox_input_checked()
+                && let Some(handler_id) = handler_attr(element, CHANGE_HANDLER_ATTR)
+            {
+                states.push((handler_id, checked));
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        states
+    }
+
+    /// Whether the window is minimized or covered by others, so updates to
+    /// its content can wait until it is shown.
+    fn is_hidden(&self) -> bool {
+        self.occluded || self.window.is_minimized().unwrap_or(false)
+    }
+
+    /// Rebuild the document from content that arrived while the window was
+    /// hidden.
+    fn show_latest_content(&mut self) {
+        // Rebuilding the document would drop the text being composed
+        if self.ime_composing {
+            return;
+        }
+        if std::mem::take(&mut self.content_stale) {
+            let content = std::mem::take(&mut self.content);
+            self.rebuild_document(content);
+        }
+    }
+
+    /// The HTML content the document was last built from.
+    pub(crate) fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Update the window's HTML content and re-render.
+    ///
+    /// While the window is minimized or occluded, or an input method is
+    /// composing text, only the content is stored, and the document is
+    /// rebuilt from the latest content once the window shows again or the
+    /// composition ends.
+    pub fn update_content(&mut self, html_content: String) {
+        self.segments = HtmlSegments::default();
+        if self.is_hidden() || self.ime_composing {
+            self.content = html_content;
+            self.content_stale = true;
+            return;
+        }
+        self.content_stale = false;
+        self.rebuild_document(html_content);
+    }
+
+    /// Whether the content was last set from a render that generated the
+    /// same HTML as `segments`.
+    pub(crate) fn shows_segments(&self, segments: &HtmlSegments) -> bool {
+        self.segments.same_html(segments)
+    }
+
+    /// Remember the segments of a render just passed to [`Self::update_content`].
+    pub(crate) fn keep_segments(&mut self, segments: HtmlSegments) {
+        self.segments = segments;
+    }
+
+    /// Build a new document from `html_content` and render it.
+    fn rebuild_document(&mut self, html_content: String) {
+        // Get current viewport settings
+        let (viewport, scale) = {
+            let inner = self.doc.inner();
+            (inner.viewport().clone(), inner.viewport().scale_f64())
+        };
+
+        // Create new document config with current viewport
+        let config = document_config(viewport, &self.net_provider);
+
+        // Create new document with updated HTML
+        let (content, element_layers) = layers::extract(&html_content);
+        let html = document_html(&content, &self.stylesheets, self.props.dir);
+        self.overlay.set_page(overlay_page(&self.stylesheets, self.props.dir));
+        self.overlay.set_element_layers(element_layers);
+        self.content = html_content;
+        let mut old_doc = std::mem::replace(
+            &mut self.doc,
+            Box::new(HtmlDocument::from_html(&html, config)),
+        );
+        load_resources(&mut self.doc, &self.resources);
+        range::init_all(&mut self.doc.inner_mut());
+        if let Some(SortDrag { container, from, slot: Some(slot), .. }) = &self.sort_drag {
+            let mut inner = self.doc.inner_mut();
+            if let Some(container) = node_at_path(&inner, container) {
+                sortable::mark(&mut inner, container, *from, Some(*slot));
+            }
+        }
+        transfer_focus(&mut old_doc, &mut self.doc);
+        let saved_scroll = scroll::save(&old_doc.inner());
+        {
+            // The viewport, and with it the zoom, was passed on in `config`
+            let old = old_doc.inner();
+            let mut new = self.doc.inner_mut();
+            new.devtools_mut().show_layout = old.devtools().show_layout;
+            new.devtools_mut().highlight_hover = old.devtools().highlight_hover;
+        }
+        if let Some(selected) = self.devtools.selected_node {
+            let path = keyed_node_path(&old_doc.inner(), selected);
+            self.devtools.selected_node = node_at_keyed_path(&self.doc.inner(), &path);
+        }
+        if !self.style_overrides.is_empty() {
+            styles::apply_overrides(&mut self.doc.inner_mut(), &self.style_overrides);
+        }
+        if self.focus_visible {
+            let mut inner = self.doc.inner_mut();
+            if let Some(focused) = inner.get_focussed_node_id() {
+                focus::set_focus_visible(&mut inner, focused, true);
+            }
+        }
+        if let Some(id) = self.pending_focus.take() {
+            let node_id = focus::element_by_id(&self.doc.inner(), &id);
+            if let Some(node_id) = node_id {
+                self.set_focus(node_id, self.focus_visible);
+            }
+        }
+        self.update_ime_allowed();
+
+        // Re-resolve and redraw
+        let animation_time = self.current_animation_time();
+        {
+            let mut inner = self.doc.inner_mut();
+            inner.resolve(animation_time);
+            scroll::restore(&mut inner, &saved_scroll);
+            direction::scroll_to_inline_start(&mut inner);
+
+            // Keep the tooltip (or its timer) if the mouse is still on an element with one,
+            // following the element if it moved
+            self.tooltip_target = tooltip::target_at(&inner, self.mouse_pos.0, self.mouse_pos.1);
+            match self.tooltip_target.map(|target| tooltip::of(&inner, target)) {
+                Some(Some((text, anchor))) if self.tooltip_visible => {
+                    self.tooltip_visible = self.overlay.show_tooltip(text, anchor, animation_time);
+                }
+                Some(_) => {}
+                None => {
+                    self.tooltip_timer += 1;
+                    if std::mem::take(&mut self.tooltip_visible) {
+                        self.overlay.hide_tooltip();
+                    }
+                }
+            }
+        }
+
+        // Render the updated content
+        {
+            let inner = self.doc.inner();
+            let (width, height) = inner.viewport().window_size;
+            devtools_overlay::update_inspector_layer(&mut self.overlay, &inner, self.devtools.selected_node);
+            let marks = Marks {
+                caret: self.caret.update(&inner, Instant::now()),
+                misspellings: self.spelling.update(&inner),
+            };
+            let renderer = &mut self.renderer;
+            self.overlay.prepare(inner.viewport(), animation_time, |overlay| {
+                renderer.render(|scene| overlay::paint(scene, &inner, &marks, overlay, scale, width, height))
+            });
+        }
+        self.accessibility_stale = false;
+        self.update_accessibility();
+        self.report_scroll();
+        self.report_observed();
+        self.report_mouse(false);
+    }
+
+    /// Add a stylesheet, or replace the contents of an existing one.
+    ///
+    /// The document's `<style>` elements are updated in place, without
+    /// rebuilding the document.
+    pub fn set_stylesheet(&mut self, id: StylesheetId, css: String, path: Option<PathBuf>) {
+        let selector = format!(r#"style[{}="{}"]"#, STYLESHEET_ATTR, id.id());
+        // Styles decide what is hidden and where nodes are
+        self.accessibility_stale = true;
+        let updated = if let Some(sheet) = self.stylesheets.iter_mut().find(|sheet| sheet.id == id) {
+            sheet.css = css;
+            if path.is_some() {
+                sheet.path = path;
+            }
+            let css = sheet.css.clone();
+            self.set_style_text(&selector, &css)
+        } else {
+            let updated = self.insert_style(id, &css);
+            self.stylesheets.push(InjectedStylesheet { id, css, path });
+            updated
+        };
+        if updated {
+            self.overlay.set_page(overlay_page(&self.stylesheets, self.props.dir));
+        } else {
+            self.refresh_content();
+        }
+    }
+
+    /// Remove an injected stylesheet.
+    pub fn remove_stylesheet(&mut self, id: StylesheetId) {
+        let count = self.stylesheets.len();
+        self.stylesheets.retain(|sheet| sheet.id != id);
+        if self.stylesheets.len() == count {
+            return;
+        }
+        let selector = format!(r#"style[{}="{}"]"#, STYLESHEET_ATTR, id.id());
+        let node_id = self.doc.inner().query_selector(&selector).ok().flatten();
+        match node_id {
+            Some(node_id) => {
+                self.doc.inner_mut().mutate().remove_node(node_id);
+                self.accessibility_stale = true;
+                self.overlay.set_page(overlay_page(&self.stylesheets, self.props.dir));
+                self.request_redraw();
+            }
+            None => self.refresh_content(),
+        }
+    }
+
+    /// Re-read stylesheets loaded from `path` and update them in place.
+    ///
+    /// Returns whether this window had a stylesheet from that file.
+    pub fn reload_stylesheet_file(&mut self, path: &Path) -> bool {
+        let ids: Vec<StylesheetId> = self
+            .stylesheets
+            .iter()
+            .filter(|sheet| sheet.path.as_deref() == Some(path))
+            .map(|sheet| sheet.id)
+            .collect();
+        if ids.is_empty() {
+            return false;
+        }
+
+        match std::fs::read_to_string(path) {
+            Ok(css) => {
+                tracing::info!("Hot reload: updating stylesheet {:?}", path);
+                for id in ids {
+                    self.set_stylesheet(id, css.clone(), None);
+                }
+            }
+            Err(e) => tracing::warn!("Hot reload: failed to read {:?}: {}", path, e),
+        }
+        true
+    }
+
+    /// Change the window's color scheme, following the OS again for `System`.
+    pub fn set_color_scheme(&mut self, scheme: WindowColorScheme) {
+        if self.props.color_scheme == scheme {
+            return;
+        }
+        self.props.color_scheme = scheme;
+        self.window.set_theme(winit_theme(scheme));
+
+        let color_scheme = resolve_color_scheme(scheme, self.window.theme());
+        self.doc.inner_mut().viewport_mut().color_scheme = color_scheme;
+        self.request_redraw();
+    }
+
+    /// Change the HiDPI scale from the window's `scale` prop.
+    pub fn set_scale(&mut self, scale: Option<f32>) {
+        self.props.scale = scale;
+        self.apply_scale();
+    }
+
+    /// Turn the window's drop shadow on or off from its `shadow` prop.
+    pub fn set_shadow(&mut self, shadow: bool) {
+        if self.props.shadow == shadow {
+            return;
+        }
+        self.props.shadow = shadow;
+        #[cfg(target_os = "windows")]
+        self.window.set_undecorated_shadow(shadow);
+        #[cfg(target_os = "macos")]
+        self.window.set_has_shadow(shadow);
+    }
+
+    /// Show `html` in the overlay layer called `name`, or remove the layer
+    /// with `None`.
+    pub fn set_overlay(&mut self, name: &str, html: Option<String>) {
+        match html {
+            Some(html) => self.overlay.set_layer(name, 0, html),
+            None => {
+                if !self.overlay.remove_layer(name) {
+                    return;
+                }
+            }
+        }
+        self.request_redraw();
+    }
+
+    /// Change the HiDPI scale regardless of the `scale` prop, or go back to
+    /// the prop with `None`.
+    pub fn override_scale(&mut self, scale: Option<f32>) {
+        self.scale_override = scale;
+        self.apply_scale();
+    }
+
+    /// The HiDPI scale the window draws at: the override, the `scale` prop
+    /// or the monitor's.
+    fn hidpi_scale(&self) -> f32 {
+        self.scale_override
+            .or(self.props.scale)
+            .unwrap_or(self.window.scale_factor() as f32)
+    }
+
+    /// Set the document's HiDPI scale after one of its sources changed.
+    fn apply_scale(&mut self) {
+        let scale = self.hidpi_scale();
+        let mut inner = self.doc.inner_mut();
+        if inner.viewport().hidpi_scale == scale {
+            return;
+        }
+        inner.viewport_mut().set_hidpi_scale(scale);
+        drop(inner);
+        self.accessibility_stale = true;
+        self.request_redraw();
+    }
+
+    /// Change the direction the window lays out in, taking effect with the
+    /// next content update.
+    pub fn set_text_direction(&mut self, dir: TextDirection) {
+        if self.props.dir != dir {
+            self.props.dir = dir;
+            // The next render has to rebuild the document, even with the same HTML
+            self.segments = HtmlSegments::default();
+        }
+    }
+
+    /// Rebuild the document from the content it was last built from, to
+    /// pick up changes to what every document shows, such as the error
+    /// overlay.
+    pub fn refresh_content(&mut self) {
+        let content = std::mem::take(&mut self.content);
+        self.update_content(content);
+    }
+
+    /// Restyle the window with the active theme, in place if possible.
+    pub fn apply_theme(&mut self) {
+        let css = crate::theme::theme_css().unwrap_or_default();
+        let selector = format!("style[{}]", THEME_ATTR);
+        // Nothing to remove if there was no theme CSS before either
+        if !self.set_style_text(&selector, &css) && !css.is_empty() {
+            self.refresh_content();
+        }
+    }
+
+    /// Add a `<style>` element holding an injected stylesheet at the end of
+    /// the document's body, where the document's HTML has them.
+    ///
+    /// Returns `false` if the document has no body.
+    fn insert_style(&mut self, id: StylesheetId, css: &str) -> bool {
+        {
+            let mut inner = self.doc.inner_mut();
+            let Ok(Some(body)) = inner.query_selector("body") else {
+                return false;
+            };
+            let mut mutator = inner.mutate();
+            let attr = Attribute {
+                name: QualName::new(None, ns!(), LocalName::from(STYLESHEET_ATTR)),
+                value: id.id().to_string(),
+            };
+            let style = mutator.create_element(QualName::new(None, ns!(html), local_name!("style")), vec![attr]);
+            let text = mutator.create_text_node(css);
+            mutator.append_children(style, &[text]);
+            mutator.append_children(body, &[style]);
+        }
+        self.request_redraw();
+        true
+    }
+
+
+    /// Replace the text of the `<style>` element matching `selector`.
+    ///
+    /// Returns `false` if the element couldn't be found or has no text node.
+    fn set_style_text(&mut self, selector: &str, css: &str) -> bool {
+        {
+            let mut inner = self.doc.inner_mut();
+            let Ok(Some(node_id)) = inner.query_selector(selector) else {
+                return false;
+            };
+            let Some(text_id) = inner
+                .get_node(node_id)
+                .and_then(|node| node.children.first().copied())
+            else {
+                return false;
+            };
+            inner.mutate().set_node_text(text_id, css);
+        }
+        self.request_redraw();
+        true
+    }
+
+    /// Get information about the element under the current mouse position.
+    ///
+    /// Returns element info for DevTools display.
+    pub fn get_hovered_element_info(&self) -> Option<HoveredElementInfo> {
+        let inner = self.doc.inner();
+        let hit_result = inner.hit(self.mouse_pos.0, self.mouse_pos.1)?;
         let node_id = hit_result.node_id;
 
+        let node = inner.get_node(node_id)?;
+        let element = node.element_data()?;
+
+        // Get tag name
+        let tag_name = element.name.local.to_string();
+
+        // Get id and classes
+        let mut id = None;
+        let mut classes = None;
+        for attr in element.attrs() {
+            let name = attr.name.local.as_ref();
+            if name == "id" {
+                id = Some(attr.value.to_string());
+            } else if name == "class" {
+                classes = Some(attr.value.to_string());
+            }
+        }
+
+        // Get layout info - hit_result has x, y; get size from the node's layout
+        let width = node.final_layout.size.width;
+        let height = node.final_layout.size.height;
+
+        let layout = ElementLayout {
+            x: hit_result.x,
+            y: hit_result.y,
+            width,
+            height,
+        };
+
+        // Extract computed styles from the node
+        let mut styles = Vec::new();
+
+        // Get size
+        styles.push((
+            "size".to_string(),
+            format!("{:.0} × {:.0}", width, height),
+        ));
+
+        if direction::is_rtl(&inner, hit_result.node_id) {
+            styles.push(("direction".to_string(), "rtl".to_string()));
+        }
+
+        // Get padding from layout
+        let padding = &node.final_layout.padding;
+        if padding.top > 0.0 || padding.right > 0.0 || padding.bottom > 0.0 || padding.left > 0.0 {
+            styles.push((
+                "padding".to_string(),
+                format!(
+                    "{:.0} {:.0} {:.0} {:.0}",
+                    padding.top, padding.right, padding.bottom, padding.left
+                ),
+            ));
+        }
+
+        // Get margin from layout
+        let margin = &node.final_layout.margin;
+        if margin.top > 0.0 || margin.right > 0.0 || margin.bottom > 0.0 || margin.left > 0.0 {
+            styles.push((
+                "margin".to_string(),
+                format!(
+                    "{:.0} {:.0} {:.0} {:.0}",
+                    margin.top, margin.right, margin.bottom, margin.left
+                ),
+            ));
+        }
+
+        // Get border from layout
+        let border = &node.final_layout.border;
+        if border.top > 0.0 || border.right > 0.0 || border.bottom > 0.0 || border.left > 0.0 {
+            styles.push((
+                "border-width".to_string(),
+                format!(
+                    "{:.0} {:.0} {:.0} {:.0}",
+                    border.top, border.right, border.bottom, border.left
+                ),
+            ));
+        }
+
+        Some(HoveredElementInfo {
+            tag_name,
+            id,
+            classes,
+            styles,
+            layout,
+        })
+    }
+
+    /// Zoom and show the layout debug overlay as the app's
+    /// [`UiSettings`](crate::ui_settings::UiSettings) say.
+    pub(crate) fn apply_ui_settings(&mut self) {
+        if !self.uses_ui_settings {
+            return;
+        }
+        let settings = crate::ui_settings::current();
+        set_ui_settings(&mut self.doc.inner_mut(), &settings);
+        self.request_redraw();
+    }
+
+    /// Keep the default zoom and no layout overlay, whatever the app's
+    /// settings (for the DevTools window).
+    pub(crate) fn ignore_ui_settings(&mut self) {
+        self.uses_ui_settings = false;
+        set_ui_settings(&mut self.doc.inner_mut(), &UiSettings::default());
+    }
+
+    /// Whether the layout debug overlay is shown.
+    pub(crate) fn shows_layout_debug(&self) -> bool {
+        self.doc.inner().devtools().show_layout
+    }
+
+    /// Toggle inspect mode, which highlights the element under the mouse
+    /// and selects it on click.
+    pub(crate) fn toggle_inspect_mode(&mut self) {
+        self.doc.inner_mut().devtools_mut().toggle_highlight_hover();
+        self.devtools.toggle_inspect_mode();
+        tracing::info!("Inspect mode: {}", self.devtools.inspect_mode);
+        self.request_redraw();
+        let _ = self.proxy.send_event(RinchEvent::RefreshDevTools);
+    }
+
+    /// In inspect mode, select the element under the mouse for the DevTools
+    /// Styles tab. Returns whether it was selected.
+    pub(crate) fn select_inspected(&mut self) -> bool {
+        if !self.devtools.inspect_mode {
+            return false;
+        }
+        let hit = self.doc.inner().hit(self.mouse_pos.0, self.mouse_pos.1);
+        match hit {
+            Some(hit) => self.devtools.select_node(hit.node_id),
+            None => self.devtools.clear_selection(),
+        }
+        self.request_redraw();
+        true
+    }
+
+    /// The selected element's style overrides and computed styles as HTML.
+    pub(crate) fn styles_html(&self, error: Option<&str>) -> String {
+        styles::styles_html(&self.doc.inner(), self.devtools.selected_node, &self.style_overrides, error)
+    }
+
+    /// Override a style of the selected element with a `property: value`
+    /// declaration.
+    pub(crate) fn add_style_override(&mut self, declaration: &str) -> Result<(), String> {
+        let Some(selected) = self.devtools.selected_node else {
+            return Err("no element is selected".to_string());
+        };
+        let path = node_path(&self.doc.inner(), selected);
+        let style_override = StyleOverride::parse(path, declaration)?;
+        // A new value for a property replaces the old one
+        self.style_overrides
+            .retain(|o| o.path != style_override.path || o.property != style_override.property);
+        self.style_overrides.push(style_override);
+        self.apply_style_overrides();
+        Ok(())
+    }
+
+    /// Turn a style override on or off.
+    pub(crate) fn toggle_style_override(&mut self, index: usize) {
+        if let Some(style_override) = self.style_overrides.get_mut(index) {
+            style_override.enabled = !style_override.enabled;
+            self.apply_style_overrides();
+        }
+    }
+
+    /// Remove a style override.
+    pub(crate) fn remove_style_override(&mut self, index: usize) {
+        if index < self.style_overrides.len() {
+            self.style_overrides.remove(index);
+            self.apply_style_overrides();
+        }
+    }
+
+    fn apply_style_overrides(&mut self) {
+        styles::apply_overrides(&mut self.doc.inner_mut(), &self.style_overrides);
+        self.request_redraw();
+    }
+
+    /// Get the event handler ID of the element under the current mouse position.
+    ///
+    /// Returns `Some(id)` if there's an element with a `data-rid` attribute at the
+    /// current mouse position, `None` otherwise.
+    pub fn get_clicked_handler(&self) -> Option<EventHandlerId> {
+        // Dropping a dragged item isn't a click
+        if self.sort_drag.as_ref().is_some_and(|drag| drag.slot.is_some()) {
+            return None;
+        }
+
+        let inner = self.doc.inner();
+        let node_id = hit::node_at(&inner, self.mouse_pos.0, self.mouse_pos.1)?;
+        click_handler(&inner, node_id)
+    }
+
+    /// The DevTools action of the element under the mouse or its nearest
+    /// ancestor with one (see [`devtools::on_click`]).
+    pub(crate) fn get_clicked_devtools_action(&self) -> Option<DevToolsAction> {
+        let inner = self.doc.inner();
+        let mut current = Some(hit::node_at(&inner, self.mouse_pos.0, self.mouse_pos.1)?);
+        while let Some(id) = current {
+            let node = inner.get_node(id)?;
+            if let Some(value) = node.element_data().and_then(|element| focus::attr(element, ACTION_ATTR)) {
+                return devtools::action(value.parse().ok()?);
+            }
+            current = node.parent;
+        }
+        None
+    }
+
+    /// Whether the mouse is on the error overlay's close button.
+    pub(crate) fn is_error_dismiss_clicked(&self) -> bool {
+        let inner = self.doc.inner();
+        inner
+            .hit(self.mouse_pos.0, self.mouse_pos.1)
+            .is_some_and(|hit| super::error_overlay::is_dismiss_button(&inner, hit.node_id))
+    }
+
+    /// Get the `href` of the link under the current mouse position, if any.
+    pub fn get_clicked_link(&self) -> Option<String> {
+        let inner = self.doc.inner();
+        let node_id = hit::node_at(&inner, self.mouse_pos.0, self.mouse_pos.1)?;
+        link_href(&inner, node_id)
+    }
+
+    /// Check if the element under the current mouse position should trigger window dragging.
+    ///
+    /// Returns `true` if there's an element with `data-drag-window` attribute at the
+    /// current mouse position.
+    pub fn should_drag_window(&self) -> bool {
+        let inner = self.doc.inner();
+
+        // Hit test at current mouse position
+        let Some(node_id) = hit::node_at(&inner, self.mouse_pos.0, self.mouse_pos.1) else {
+            return false;
+        };
+
         // Walk up the tree looking for a data-drag-window attribute
         let mut current = Some(node_id);
         while let Some(id) = current {
@@ -743,6 +2391,11 @@ impl WindowManager {
         }
     }
 
+    /// Iterate mutably over all windows.
+    pub fn windows_iter_mut(&mut self) -> impl Iterator<Item = (&WindowId, &mut ManagedWindow)> {
+        self.windows.iter_mut()
+    }
+
     /// Iterate over all windows.
     pub fn windows_iter(&self) -> impl Iterator<Item = (&WindowId, &ManagedWindow)> {
         self.windows.iter()
@@ -760,6 +2413,373 @@ impl Default for WindowManager {
     }
 }
 
+/// The winit theme to request for a color scheme (`None` follows the OS).
+fn winit_theme(scheme: WindowColorScheme) -> Option<Theme> {
+    match scheme {
+        WindowColorScheme::System => None,
+        WindowColorScheme::Light => Some(Theme::Light),
+        WindowColorScheme::Dark => Some(Theme::Dark),
+    }
+}
+
+/// The document color scheme for a window, given the OS theme if known.
+fn resolve_color_scheme(scheme: WindowColorScheme, os_theme: Option<Theme>) -> ColorScheme {
+    match scheme {
+        WindowColorScheme::Light => ColorScheme::Light,
+        WindowColorScheme::Dark => ColorScheme::Dark,
+        WindowColorScheme::System => match os_theme.unwrap_or(Theme::Light) {
+            Theme::Light => ColorScheme::Light,
+            Theme::Dark => ColorScheme::Dark,
+        },
+    }
+}
+
+/// Build the HTML for a window's document.
+///
+/// The default stylesheet and the theme's tokens come first so the content's
+/// styles can use and override them, and injected stylesheets come last so
+/// they take precedence. A right-to-left window's root gets `dir="rtl"`.
+pub(crate) fn document_html(content: &str, stylesheets: &[InjectedStylesheet], dir: TextDirection) -> String {
+    page_html(&[content, &super::error_overlay::overlay_html()], stylesheets, dir)
+}
+
+/// Build the page a window's overlay is built from: like its document,
+/// with the overlay's layers in place of the content.
+fn overlay_page(stylesheets: &[InjectedStylesheet], dir: TextDirection) -> String {
+    page_html(&[overlay::LAYERS_MARKER], stylesheets, dir)
+}
+
+/// Wrap `parts` of a page in the window's stylesheets and direction.
+fn page_html(parts: &[&str], stylesheets: &[InjectedStylesheet], dir: TextDirection) -> String {
+    let default_css = super::config::default_stylesheet();
+    let theme_css = crate::theme::theme_css();
+
+    // Sized for everything but the tags around the stylesheets
+    let capacity = parts.iter().map(|part| part.len()).sum::<usize>()
+        + default_css.map_or(0, str::len)
+        + theme_css.as_deref().map_or(0, str::len)
+        + stylesheets.iter().map(|sheet| sheet.css.len()).sum::<usize>();
+    let mut html = String::with_capacity(capacity + 64 * (stylesheets.len() + 3));
+
+    // Attributes of a leading `<html>` tag go on the root element
+    if crate::i18n::resolve_direction(dir).is_rtl() {
+        html.push_str(r#"<html dir="rtl">"#);
+    }
+    if let Some(css) = default_css {
+        let _ = write!(html, "<style {}>{}</style>", DEFAULT_STYLESHEET_ATTR, escape_style_text(css));
+    }
+    if let Some(css) = theme_css {
+        let _ = write!(html, "<style {}>{}</style>", THEME_ATTR, escape_style_text(&css));
+    }
+    for part in parts {
+        html.push_str(part);
+    }
+    for sheet in stylesheets {
+        let _ = write!(
+            html,
+            r#"<style {}="{}">{}</style>"#,
+            STYLESHEET_ATTR,
+            sheet.id.id(),
+            escape_style_text(&sheet.css)
+        );
+    }
+    html
+}
+
+/// Escape `</style` in CSS going into a `<style>` element, which would
+/// otherwise end the element and let the rest be parsed as HTML.
+///
+/// `<\/style` means the same in CSS strings and comments, the only places
+/// it can appear in valid CSS.
+fn escape_style_text(css: &str) -> Cow<'_, str> {
+    // Lowercasing ASCII keeps byte offsets
+    let lower = css.to_ascii_lowercase();
+    if !lower.contains("</style") {
+        return Cow::Borrowed(css);
+    }
+    let mut escaped = String::with_capacity(css.len() + 8);
+    let mut start = 0;
+    for (i, _) in lower.match_indices("</style") {
+        escaped.push_str(&css[start..=i]);
+        escaped.push('\\');
+        start = i + 1;
+    }
+    escaped.push_str(&css[start..]);
+    Cow::Owned(escaped)
+}
+
+/// Create the config for a window's document.
+///
+/// Relative URLs resolve into the assets directory and are loaded by the
+/// window's asset provider.
+fn document_config(viewport: Viewport, net_provider: &Arc<AssetProvider>) -> DocumentConfig {
+    DocumentConfig {
+        viewport: Some(viewport),
+        base_url: crate::assets::base_url(),
+        net_provider: Some(net_provider.clone()),
+        ..Default::default()
+    }
+}
+
+/// The size of the element matching `selector`, for
+/// [`use_element_size`](crate::observe::use_element_size).
+fn element_size(doc: &BaseDocument, selector: &str) -> Option<ElementSize> {
+    let node_id = doc.query_selector(selector).ok()??;
+    let size = doc.get_node(node_id)?.final_layout.size;
+    Some(ElementSize {
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// The element at `x`, `y` in a document, for
+/// [`use_hovered_element`](crate::mouse::use_hovered_element).
+///
+/// Text is reported as the element containing it.
+fn hovered_element(doc: &BaseDocument, window: WindowHandle, x: f32, y: f32) -> Option<HoveredElement> {
+    let mut node = doc.get_node(hit::node_at(doc, x, y)?)?;
+    while node.element_data().is_none() {
+        node = doc.get_node(node.parent?)?;
+    }
+    let element = node.element_data()?;
+    let position = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    Some(HoveredElement {
+        window,
+        tag_name: element.name.local.to_string(),
+        attributes: element
+            .attrs()
+            .iter()
+            .map(|attr| (attr.name.local.to_string(), attr.value.to_string()))
+            .collect(),
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// Zoom a document and show or hide its layout debug overlay.
+fn set_ui_settings(doc: &mut BaseDocument, settings: &UiSettings) {
+    doc.viewport_mut().set_zoom(settings.zoom);
+    doc.devtools_mut().show_layout = settings.layout_debug;
+}
+
+/// Move keyboard focus from a document to its rebuilt replacement.
+///
+/// The focused element is found again by its position in the tree, keyed
+/// loop items by key (see [`keyed_node_path`]). If it's a text input whose
+/// value didn't change, its editor is moved across as well, so the caret,
+/// selection and any IME composition survive the rebuild.
+fn transfer_focus(old: &mut Box<dyn Document>, new: &mut Box<dyn Document>) {
+    let mut old = old.inner_mut();
+    let Some(old_id) = old.get_focussed_node_id() else {
+        return;
+    };
+    let mut new = new.inner_mut();
+    let Some(new_id) = node_at_keyed_path(&new, &keyed_node_path(&old, old_id)) else {
+        return;
+    };
+
+    let tag = |doc: &BaseDocument, id: usize| {
+        doc.get_node(id)
+            .and_then(|node| node.element_data())
+            .map(|element| element.name.local.to_string())
+    };
+    let old_tag = tag(&old, old_id);
+    if old_tag.is_none() || old_tag != tag(&new, new_id) {
+        return;
+    }
+
+    let old_input = old
+        .get_node_mut(old_id)
+        .and_then(|node| node.element_data_mut())
+        .and_then(|element| element.text_input_data_mut());
+    let new_input = new
+        .get_node_mut(new_id)
+        .and_then(|node| node.element_data_mut())
+        .and_then(|element| element.text_input_data_mut());
+    if let (Some(old_input), Some(new_input)) = (old_input, new_input)
+        && old_input.editor.text().to_string() == new_input.editor.text().to_string()
+    {
+        std::mem::swap(old_input, new_input);
+    }
+
+    new.set_focus_to(new_id);
+}
+
+/// The handler ID stored in one of an element's `data-on*` attributes.
+pub(crate) fn handler_attr(element: &ElementData, name: &str) -> Option<EventHandlerId> {
+    element
+        .attrs()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == name)?
+        .value
+        .parse::<usize>()
+        .ok()
+        .map(EventHandlerId)
+}
+
+/// The click handler of a node or its nearest ancestor with one.
+pub(crate) fn click_handler(doc: &BaseDocument, node_id: usize) -> Option<EventHandlerId> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if let Some(handler_id) = node.element_data().and_then(|element| handler_attr(element, "data-rid")) {
+            return Some(handler_id);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// The `href` of the link containing a node.
+fn link_href(doc: &BaseDocument, node_id: usize) -> Option<String> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if let Some(element) = node.element_data()
+            && element.name.local.as_ref() == "a"
+        {
+            return element
+                .attrs()
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == "href")
+                .map(|attr| attr.value.to_string());
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// The position of a node in the tree, as child indices from the root.
+pub(crate) fn node_path(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current = node_id;
+    while let Some(parent_id) = doc.get_node(current).and_then(|node| node.parent) {
+        let Some(index) = doc
+            .get_node(parent_id)
+            .and_then(|parent| parent.children.iter().position(|&child| child == current))
+        else {
+            break;
+        };
+        path.push(index);
+        current = parent_id;
+    }
+    path.reverse();
+    path
+}
+
+/// Find the node at a position returned by [`node_path`].
+pub(crate) fn node_at_path(doc: &BaseDocument, path: &[usize]) -> Option<usize> {
+    let mut current = doc.root_node().id;
+    for &index in path {
+        current = *doc.get_node(current)?.children.get(index)?;
+    }
+    Some(current)
+}
+
+/// A step of a [`keyed_node_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathStep {
+    /// The child at this index.
+    Index(usize),
+    /// The `n`th child with this key (the elements of one loop item share it).
+    Key(String, usize),
+}
+
+/// Like [`node_path`], but the elements of keyed `rsx!` loop items are
+/// found by key, so the node is found again in a new document where the
+/// loop moved its item.
+pub(crate) fn keyed_node_path(doc: &BaseDocument, node_id: usize) -> Vec<PathStep> {
+    let mut path = Vec::new();
+    let mut current = node_id;
+    while let Some(parent) = doc.get_node(current).and_then(|node| node.parent).and_then(|id| doc.get_node(id)) {
+        let Some(index) = parent.children.iter().position(|&child| child == current) else {
+            break;
+        };
+        path.push(match item_key(doc, current) {
+            Some(key) => {
+                let before = parent.children[..index].iter().filter(|&&child| item_key(doc, child) == Some(key));
+                PathStep::Key(key.to_string(), before.count())
+            }
+            None => PathStep::Index(index),
+        });
+        current = parent.id;
+    }
+    path.reverse();
+    path
+}
+
+/// Find the node at a position returned by [`keyed_node_path`].
+pub(crate) fn node_at_keyed_path(doc: &BaseDocument, path: &[PathStep]) -> Option<usize> {
+    let mut current = doc.root_node().id;
+    for step in path {
+        let children = &doc.get_node(current)?.children;
+        current = match step {
+            PathStep::Index(index) => *children.get(*index)?,
+            PathStep::Key(key, n) => {
+                children.iter().copied().filter(|&child| item_key(doc, child) == Some(key.as_str())).nth(*n)?
+            }
+        };
+    }
+    Some(current)
+}
+
+/// The key of a keyed `rsx!` loop item an element belongs to.
+fn item_key(doc: &BaseDocument, node_id: usize) -> Option<&str> {
+    doc.get_node(node_id)?
+        .element_data()?
+        .attrs()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == KEY_ATTR)
+        .map(|attr| attr.value.as_str())
+}
+
+/// Set the text of the text input at `path`, such as when undoing an edit
+/// of it, and tell its `oninput`.
+pub(crate) fn restore_input_text(doc: &mut BaseDocument, window: WindowHandle, path: &[usize], text: &str) {
+    let Some(node_id) = node_at_path(doc, path) else {
+        return;
+    };
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return;
+    };
+    if element.text_input_data().is_none() {
+        return;
+    }
+    let handler_id = handler_attr(element, INPUT_HANDLER_ATTR);
+    let name = QualName::new(None, ns!(), local_name!("value"));
+    doc.mutate().set_attribute(node_id, name, text);
+    if let (Some(handler_id), Some(window_id)) = (handler_id, crate::windows::native_window_id(window)) {
+        crate::windows::send_event(RinchEvent::ElementInput {
+            handler_id,
+            value: text.to_string(),
+            window_id,
+        });
+    }
+}
+
+/// Apply queued resources to the document, dropping those requested by a
+/// previous document. Returns whether any were applied.
+fn load_resources(doc: &mut Box<dyn Document>, resources: &ResourceQueue) -> bool {
+    let pending = std::mem::take(&mut *resources.lock().unwrap());
+    if pending.is_empty() {
+        return false;
+    }
+
+    let mut inner = doc.inner_mut();
+    let doc_id = inner.id();
+    let mut loaded = false;
+    for (id, resource) in pending {
+        if id == doc_id {
+            inner.load_resource(resource);
+            loaded = true;
+        }
+    }
+    loaded
+}
+
 /// Create a waker that sends poll events to the event loop.
 fn create_waker(proxy: &EventLoopProxy<RinchEvent>, id: WindowId) -> Waker {
     struct WakerHandle {
@@ -780,3 +2800,51 @@ fn create_waker(proxy: &EventLoopProxy<RinchEvent>, id: WindowId) -> Waker {
         id,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_text_without_end_tags_is_unchanged() {
+        let css = "a::after { content: '<b>'; } /* </div> */";
+        assert!(matches!(escape_style_text(css), Cow::Borrowed(text) if text == css));
+    }
+
+    #[test]
+    fn style_end_tags_are_escaped() {
+        assert_eq!(
+            escape_style_text("a::after { content: '</style><script>'; }"),
+            r"a::after { content: '<\/style><script>'; }"
+        );
+        assert_eq!(
+            escape_style_text("/* </STYLE> </Style */ b {}"),
+            r"/* <\/STYLE> <\/Style */ b {}"
+        );
+    }
+
+    #[test]
+    fn keyed_paths_follow_moved_items() {
+        use blitz_html::HtmlDocument;
+
+        let item = |key: &str| format!("<li data-rkey=\"{0}\"><input id=\"{0}\"></li><hr data-rkey=\"{0}\">", key);
+        let list = |first: &str, second: &str| {
+            HtmlDocument::from_html(&format!("<ul>{}{}</ul>", item(first), item(second)), DocumentConfig::default())
+        };
+        let (before, after) = (list("a", "b"), list("b", "a"));
+        let (before, after) = (before.inner(), after.inner());
+
+        let input = before.query_selector("#b").unwrap().unwrap();
+        let path = keyed_node_path(&before, input);
+        assert_eq!(node_at_keyed_path(&before, &path), Some(input));
+        assert_eq!(node_at_keyed_path(&after, &path), after.query_selector("#b").unwrap());
+        // The second element of an item
+        let hr = before.query_selector("hr[data-rkey=\"b\"]").unwrap().unwrap();
+        let path = keyed_node_path(&before, hr);
+        assert_eq!(node_at_keyed_path(&after, &path), after.query_selector("hr[data-rkey=\"b\"]").unwrap());
+        // Unkeyed elements keep their position
+        let ul = before.query_selector("ul").unwrap().unwrap();
+        let positions: Vec<PathStep> = node_path(&before, ul).into_iter().map(PathStep::Index).collect();
+        assert_eq!(keyed_node_path(&before, ul), positions);
+    }
+}
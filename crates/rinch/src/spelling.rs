@@ -0,0 +1,243 @@
+//! Spell checking of text inputs.
+//!
+//! Once the app sets a [`SpellChecker`], windows underline the misspelled
+//! words of their text inputs and textareas with a red wavy line, and
+//! right-clicking one shows a menu of corrections to replace it with. Inputs
+//! are checked unless they or an ancestor have `spellcheck: "false"`, except
+//! for passwords, email addresses, URLs and numbers, which are only checked
+//! with `spellcheck: "true"`. The word being typed isn't marked until the
+//! caret leaves it.
+//!
+//! Rinch doesn't come with dictionaries. [`WordList`] checks against a list
+//! of words, such as a Hunspell `.dic` file, and apps can wrap the OS spell
+//! checker or a full Hunspell binding by implementing [`SpellChecker`]:
+//!
+//! ```ignore
+//! use rinch::spelling::{set_spell_checker, WordList};
+//!
+//! let words = std::fs::read_to_string("dictionaries/en_US.dic")?;
+//! set_spell_checker(WordList::from_hunspell_dic(&words));
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::shell::runtime::RinchEvent;
+
+/// The most corrections offered for a word.
+pub(crate) const MAX_SUGGESTIONS: usize = 5;
+
+/// Checks the spelling of words.
+pub trait SpellChecker {
+    /// Whether `word` is spelled correctly.
+    fn check(&self, word: &str) -> bool;
+
+    /// Corrections for a misspelled `word`, the most likely first.
+    fn suggest(&self, word: &str) -> Vec<String>;
+}
+
+thread_local! {
+    static CHECKER: RefCell<Option<Rc<dyn SpellChecker>>> = const { RefCell::new(None) };
+    /// Counts changes of the spell checker, so windows know to check again.
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Check the spelling of text inputs in every window with `checker`, in
+/// place of any other.
+pub fn set_spell_checker(checker: impl SpellChecker + 'static) {
+    CHECKER.with(|c| *c.borrow_mut() = Some(Rc::new(checker)));
+    changed();
+}
+
+/// Stop checking the spelling of text inputs.
+pub fn clear_spell_checker() {
+    if CHECKER.with(|c| c.borrow_mut().take()).is_some() {
+        changed();
+    }
+}
+
+fn changed() {
+    GENERATION.with(|g| g.set(g.get() + 1));
+    crate::windows::send_event(RinchEvent::SpellCheckerChanged);
+}
+
+/// The spell checker set, if any.
+pub(crate) fn checker() -> Option<Rc<dyn SpellChecker>> {
+    CHECKER.with(|c| c.borrow().clone())
+}
+
+/// Counts changes of the spell checker.
+pub(crate) fn generation() -> u64 {
+    GENERATION.with(|g| g.get())
+}
+
+/// The byte ranges of the words of `text` that `checker` finds misspelled.
+pub(crate) fn misspelled(checker: &dyn SpellChecker, text: &str) -> Vec<Range<usize>> {
+    words(text).filter(|range| !checker.check(&text[range.clone()])).collect()
+}
+
+/// The byte ranges of the words of `text` worth checking.
+///
+/// Words are runs of letters with apostrophes between them. Runs touching
+/// digits or underscores, in URLs, paths or email addresses, and with
+/// capitals after the first letter (acronyms, identifiers) are left out.
+fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut pos = 0;
+    text.split_whitespace().flat_map(move |chunk| {
+        let start = pos + text[pos..].find(chunk).unwrap_or(0);
+        pos = start + chunk.len();
+        let skip = chunk.contains("://") || chunk.contains(['@', '/', '\\']);
+        tokens(chunk)
+            .filter(move |_| !skip)
+            .filter_map(move |range| {
+                let word = &chunk[range.clone()];
+                let checked = word.chars().all(|c| c.is_alphabetic() || is_apostrophe(c))
+                    && !word.chars().skip(1).any(char::is_uppercase);
+                checked.then(|| start + range.start..start + range.end)
+            })
+    })
+}
+
+/// The runs of letters, digits, underscores and apostrophes of `chunk`,
+/// without apostrophes at their ends.
+fn tokens(chunk: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let part_of_token = |c: char| c.is_alphanumeric() || c == '_' || is_apostrophe(c);
+    let mut rest = 0;
+    std::iter::from_fn(move || {
+        let start = rest + chunk[rest..].find(part_of_token)?;
+        let end = chunk[start..].find(|c| !part_of_token(c)).map_or(chunk.len(), |len| start + len);
+        rest = end;
+        let token = &chunk[start..end];
+        let trimmed = token.trim_matches(is_apostrophe);
+        let offset = token.len() - token.trim_start_matches(is_apostrophe).len();
+        Some(start + offset..start + offset + trimmed.len())
+    })
+    .filter(|range| !range.is_empty())
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+/// A spell checker that knows the words of a list.
+///
+/// Words are matched ignoring case, so a capitalized word at the start of
+/// a sentence is found too. Corrections are the words of the list that
+/// one or two edits (inserting, deleting, replacing or swapping letters)
+/// turn the misspelled word into.
+#[derive(Debug, Clone, Default)]
+pub struct WordList {
+    /// The words in list order, which breaks ties between corrections.
+    words: Vec<String>,
+    lowercase: HashSet<String>,
+}
+
+impl WordList {
+    /// Create a word list.
+    pub fn new(words: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut list = Self::default();
+        for word in words {
+            list.add(word);
+        }
+        list
+    }
+
+    /// Create a word list from the words of a Hunspell dictionary (`.dic`
+    /// file).
+    ///
+    /// Only the words as listed are known: the affix rules of the `.aff`
+    /// file, which add their other forms, aren't applied.
+    pub fn from_hunspell_dic(dic: &str) -> Self {
+        let mut lines = dic.lines().peekable();
+        // The first line holds the number of words
+        if lines.peek().is_some_and(|line| line.trim().chars().all(|c| c.is_ascii_digit())) {
+            lines.next();
+        }
+        Self::new(lines.filter_map(|line| {
+            let word = line.split(['/', '\t', ' ']).next()?.trim();
+            (!word.is_empty()).then_some(word)
+        }))
+    }
+
+    /// Add a word to the list.
+    pub fn add(&mut self, word: impl Into<String>) {
+        let word = word.into();
+        if self.lowercase.insert(word.to_lowercase()) {
+            self.words.push(word);
+        }
+    }
+
+    /// The number of words in the list.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether the list has no words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+impl SpellChecker for WordList {
+    fn check(&self, word: &str) -> bool {
+        let word = word.replace('\u{2019}', "'");
+        self.lowercase.contains(&word.to_lowercase())
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let lower: Vec<char> = word.to_lowercase().chars().collect();
+        let mut found: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            .filter_map(|candidate| {
+                let candidate_lower: Vec<char> = candidate.to_lowercase().chars().collect();
+                let distance = edit_distance(&lower, &candidate_lower, 2)?;
+                Some((distance, candidate))
+            })
+            .collect();
+        found.sort_by_key(|&(distance, _)| distance);
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        found
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| if capitalized { capitalize(candidate) } else { candidate.clone() })
+            .collect()
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The number of insertions, deletions, replacements and swaps of
+/// neighbouring letters that turn `a` into `b`, if at most `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    // Rows of the optimal string alignment distance
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1).min(row[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        if row.iter().all(|&distance| distance > max) {
+            return None;
+        }
+        before = std::mem::replace(&mut previous, row);
+    }
+    let distance = previous[b.len()];
+    (distance <= max).then_some(distance)
+}
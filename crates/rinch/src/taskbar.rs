@@ -0,0 +1,372 @@
+//! Jump lists: tasks in the menu of the app's taskbar or dock icon.
+//!
+//! [`set_jump_list`] adds tasks such as "New Document" to the menu shown when
+//! right-clicking the app's taskbar button on Windows, or its dock icon on
+//! macOS. On Windows, clicking a task starts the app with an argument naming
+//! the task; that instance hands it to the one already running and exits,
+//! and the task's callback runs on the UI thread of the running instance. If
+//! the app wasn't running, the callback runs in the new instance instead. The
+//! dock menu belongs to the running app, so on macOS the callback runs
+//! directly.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::taskbar::{set_jump_list, JumpListItem};
+//!
+//! fn main() {
+//!     // Forwards the task and exits if the app is already running
+//!     let result = set_jump_list(vec![
+//!         JumpListItem::new("New Document", || new_document()),
+//!         JumpListItem::new("Open Recent", || show_recent_files())
+//!             .description("Pick one of the files you worked on last"),
+//!     ]);
+//!     if let Err(e) = result {
+//!         eprintln!("Jump list unavailable: {e}");
+//!     }
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! # Platform support
+//!
+//! - **Windows**: a jump list of user tasks.
+//! - **macOS**: a dock menu, returned from `applicationDockMenu:`, which is
+//!   added to the app delegate once the event loop has started. Descriptions
+//!   are shown as tooltips.
+//! - **Linux**: desktop actions are declared in the app's `.desktop` file
+//!   rather than at runtime, so [`set_jump_list`] returns
+//!   [`TaskbarError::Unsupported`].
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+/// Command line argument naming the task to run, followed by its index.
+const TASK_ARG: &str = "--rinch-jump-list-task=";
+
+thread_local! {
+    /// Callbacks of the current jump list's tasks, by index.
+    static TASKS: RefCell<Vec<Rc<dyn Fn()>>> = const { RefCell::new(Vec::new()) };
+    /// Whether the launch argument was handled and the listener started.
+    static STARTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A task in the jump list.
+#[derive(Clone)]
+pub struct JumpListItem {
+    /// The task's label.
+    pub label: String,
+    /// Tooltip shown when hovering the task.
+    pub description: String,
+    callback: Rc<dyn Fn()>,
+}
+
+impl JumpListItem {
+    /// Create a task that runs `callback` on the UI thread when clicked.
+    pub fn new(label: impl Into<String>, callback: impl Fn() + 'static) -> Self {
+        Self {
+            label: label.into(),
+            description: String::new(),
+            callback: Rc::new(callback),
+        }
+    }
+
+    /// Set the tooltip.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+impl fmt::Debug for JumpListItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JumpListItem")
+            .field("label", &self.label)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Taskbar error type.
+#[derive(Debug)]
+pub enum TaskbarError {
+    /// Updating the jump list failed.
+    Failed(String),
+    /// Jump lists aren't supported on this platform.
+    Unsupported,
+}
+
+impl fmt::Display for TaskbarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskbarError::Failed(msg) => write!(f, "failed to update the jump list: {}", msg),
+            TaskbarError::Unsupported => write!(f, "jump lists are not supported on this platform"),
+        }
+    }
+}
+
+impl std::error::Error for TaskbarError {}
+
+/// Result type for taskbar operations.
+pub type TaskbarResult<T> = Result<T, TaskbarError>;
+
+/// Replace the tasks in the jump list.
+///
+/// Must be called on the UI thread. Call it at the start of `main`: if the
+/// app was started from a task and another instance is already running, the
+/// task is handed to that instance and this process exits. Later calls
+/// replace the tasks, e.g. to list recent files.
+pub fn set_jump_list(items: Vec<JumpListItem>) -> TaskbarResult<()> {
+    if !cfg!(any(target_os = "windows", target_os = "macos")) {
+        return Err(TaskbarError::Unsupported);
+    }
+    // The dock menu runs tasks in the running app, with nothing to forward
+    if cfg!(target_os = "windows") {
+        start();
+    }
+    write_jump_list(&items)?;
+    TASKS.with(|tasks| *tasks.borrow_mut() = items.into_iter().map(|item| item.callback).collect());
+    Ok(())
+}
+
+/// Remove all tasks from the jump list.
+pub fn clear_jump_list() -> TaskbarResult<()> {
+    set_jump_list(Vec::new())
+}
+
+/// On the first call, forward a task from the command line to the running
+/// instance, or run it here once the tasks are set, and start accepting tasks
+/// from later instances.
+fn start() {
+    if STARTED.with(|started| started.replace(true)) {
+        return;
+    }
+
+    let channel = match std::env::current_exe() {
+        Ok(exe) => format!(
+            "jump-list-{}",
+            exe.file_stem().unwrap_or_default().to_string_lossy()
+        ),
+        Err(_) => "jump-list".to_string(),
+    };
+    let launch_task: Vec<String> = std::env::args().filter(|arg| arg.starts_with(TASK_ARG)).collect();
+    if !launch_task.is_empty() && crate::instance::forward(&channel, &launch_task) {
+        tracing::info!("Forwarded jump list task to the running instance");
+        std::process::exit(0);
+    }
+
+    crate::instance::listen(&channel, |messages| {
+        crate::tasks::app_handle().run_on_ui_thread(move || {
+            crate::windows::show_windows();
+            for message in messages {
+                run_task(&message);
+            }
+        });
+    });
+    for arg in launch_task {
+        crate::tasks::app_handle().run_on_ui_thread(move || run_task(&arg));
+    }
+}
+
+/// Run the task named by a command line argument.
+fn run_task(arg: &str) {
+    if let Some(index) = arg.strip_prefix(TASK_ARG).and_then(|index| index.parse::<usize>().ok()) {
+        run_task_at(index);
+    }
+}
+
+/// Run the task at `index` of the current jump list.
+fn run_task_at(index: usize) {
+    match TASKS.with(|tasks| tasks.borrow().get(index).cloned()) {
+        Some(callback) => callback(),
+        None => tracing::warn!("Jump list task {} no longer exists", index),
+    }
+}
+
+/// Write the tasks with `ICustomDestinationList`, as shortcuts to this
+/// executable with the task's argument.
+#[cfg(target_os = "windows")]
+fn write_jump_list(items: &[JumpListItem]) -> TaskbarResult<()> {
+    use ::windows::core::{Interface, HSTRING, PCWSTR, PROPVARIANT};
+    use ::windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use ::windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use ::windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use ::windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use ::windows::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink};
+
+    let failed = |e: ::windows::core::Error| TaskbarError::Failed(e.to_string());
+    let exe = std::env::current_exe().map_err(|e| TaskbarError::Failed(e.to_string()))?;
+    let exe = HSTRING::from(exe.as_os_str());
+
+    // SAFETY: COM is used on a single thread, and every pointer passed is a
+    // live local.
+    unsafe {
+        // Usually already initialized on the UI thread; that's not an error
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+        if items.is_empty() {
+            return list.DeleteList(PCWSTR::null()).map_err(failed);
+        }
+
+        let mut max_slots = 0;
+        let _removed: IObjectArray = list.BeginList(&mut max_slots).map_err(failed)?;
+        let tasks: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+        for (index, item) in items.iter().enumerate() {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+            link.SetPath(&exe).map_err(failed)?;
+            link.SetArguments(&HSTRING::from(format!("{}{}", TASK_ARG, index)))
+                .map_err(failed)?;
+            link.SetIconLocation(&exe, 0).map_err(failed)?;
+            link.SetDescription(&HSTRING::from(item.description.as_str()))
+                .map_err(failed)?;
+            // Tasks are labelled with the shortcut's title
+            let properties: IPropertyStore = link.cast().map_err(failed)?;
+            properties
+                .SetValue(&PKEY_Title, &PROPVARIANT::from(item.label.as_str()))
+                .map_err(failed)?;
+            properties.Commit().map_err(failed)?;
+            tasks.AddObject(&link).map_err(failed)?;
+        }
+        list.AddUserTasks(&tasks).map_err(failed)?;
+        list.CommitList().map_err(failed)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn write_jump_list(items: &[JumpListItem]) -> TaskbarResult<()> {
+    dock_menu::set_items(items)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn write_jump_list(_items: &[JumpListItem]) -> TaskbarResult<()> {
+    Err(TaskbarError::Unsupported)
+}
+
+/// The dock menu, served by adding `applicationDockMenu:` to the class of
+/// winit's app delegate.
+#[cfg(target_os = "macos")]
+mod dock_menu {
+    use std::cell::{Cell, RefCell};
+
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Imp, NSObject, Sel};
+    use objc2::{define_class, msg_send, sel, AllocAnyThread, MainThreadMarker};
+    use objc2_app_kit::{NSApplication, NSMenu, NSMenuItem};
+    use objc2_foundation::{ns_string, NSString};
+
+    use super::{JumpListItem, TaskbarError, TaskbarResult};
+
+    thread_local! {
+        /// The menu returned to the dock, `None` without tasks.
+        static MENU: RefCell<Option<Retained<NSMenu>>> = const { RefCell::new(None) };
+        /// Target of the menu items, which menu items don't retain.
+        static TARGET: RefCell<Option<Retained<TaskTarget>>> = const { RefCell::new(None) };
+        /// Whether `applicationDockMenu:` was added to the delegate.
+        static INSTALLED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    define_class!(
+        /// Runs the task at the tag of the clicked menu item.
+        #[unsafe(super(NSObject))]
+        struct TaskTarget;
+
+        impl TaskTarget {
+            #[unsafe(method(runTask:))]
+            fn run_task(&self, sender: &NSMenuItem) {
+                // Through the task queue, so the UI re-renders afterwards
+                let index = sender.tag() as usize;
+                crate::tasks::app_handle().run_on_ui_thread(move || super::run_task_at(index));
+            }
+        }
+    );
+
+    /// Replace the items of the dock menu.
+    pub(super) fn set_items(items: &[JumpListItem]) -> TaskbarResult<()> {
+        let mtm = MainThreadMarker::new()
+            .ok_or_else(|| TaskbarError::Failed("the dock menu must be set on the main thread".into()))?;
+        let target = TARGET.with(|target| {
+            target
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let target = TaskTarget::alloc().set_ivars(());
+                    // SAFETY: NSObject's init takes no arguments.
+                    unsafe { msg_send![super(target), init] }
+                })
+                .clone()
+        });
+
+        let menu = (!items.is_empty()).then(|| {
+            let menu = NSMenu::new(mtm);
+            for (index, item) in items.iter().enumerate() {
+                // SAFETY: the target implements runTask:, taking the item.
+                let menu_item = unsafe {
+                    NSMenuItem::initWithTitle_action_keyEquivalent(
+                        NSMenuItem::alloc(mtm),
+                        &NSString::from_str(&item.label),
+                        Some(sel!(runTask:)),
+                        ns_string!(""),
+                    )
+                };
+                // SAFETY: the target lives in TARGET for the rest of the app.
+                unsafe { menu_item.setTarget(Some(&target)) };
+                menu_item.setTag(index as isize);
+                if !item.description.is_empty() {
+                    menu_item.setToolTip(Some(&NSString::from_str(&item.description)));
+                }
+                menu.addItem(&menu_item);
+            }
+            menu
+        });
+        MENU.with(|current| *current.borrow_mut() = menu);
+
+        // winit sets the app delegate when the event loop starts
+        crate::tasks::app_handle().run_on_ui_thread(install);
+        Ok(())
+    }
+
+    /// Add `applicationDockMenu:` to the class of the app delegate.
+    fn install() {
+        if INSTALLED.with(Cell::get) {
+            return;
+        }
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let Some(delegate) = NSApplication::sharedApplication(mtm).delegate() else {
+            tracing::warn!("No app delegate to add the dock menu to");
+            return;
+        };
+        let delegate: &AnyObject = (*delegate).as_ref();
+        let class = delegate.class();
+        let method = dock_menu as extern "C-unwind" fn(&AnyObject, Sel, *mut AnyObject) -> *mut NSMenu;
+        // SAFETY: the implementation has the `@@:@` signature of
+        // applicationDockMenu:, which winit's delegate doesn't implement.
+        let added = unsafe {
+            objc2::ffi::class_addMethod(
+                std::ptr::from_ref(class).cast_mut(),
+                sel!(applicationDockMenu:),
+                std::mem::transmute::<_, Imp>(method),
+                c"@@:@".as_ptr(),
+            )
+        };
+        if added.as_bool() {
+            INSTALLED.with(|installed| installed.set(true));
+        } else {
+            tracing::warn!("The app delegate already provides a dock menu");
+        }
+    }
+
+    /// `-[delegate applicationDockMenu:]`, returning the current menu, which
+    /// stays retained by MENU.
+    extern "C-unwind" fn dock_menu(_this: &AnyObject, _cmd: Sel, _sender: *mut AnyObject) -> *mut NSMenu {
+        MENU.with(|menu| {
+            menu.borrow()
+                .as_ref()
+                .map_or(std::ptr::null_mut(), |menu| Retained::as_ptr(menu).cast_mut())
+        })
+    }
+}
@@ -0,0 +1,763 @@
+//! Async tasks and updating the UI from other threads.
+//!
+//! [`spawn`] runs a future on a background tokio runtime. Signals and other
+//! UI state can't be sent to other threads, so tasks talk to the UI through
+//! an [`AppHandle`], which schedules closures to run on the UI thread, and
+//! [`UiRef`], a wrapper that can be sent anywhere but only opened on the UI
+//! thread.
+//!
+//! [`use_coroutine`] starts a long-lived task that components send messages
+//! to, [`spawn_blocking`] runs blocking work on a thread pool, reporting
+//! progress and the result on the UI thread, and [`use_stream`] /
+//! [`FromStream`] feed the items of a stream into a signal.
+//!
+//! Everything that runs on tokio needs the `async` feature; [`AppHandle`]
+//! and [`UiRef`] are always available.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::tasks::UiRef;
+//!
+//! fn app() -> Element {
+//!     let status = use_signal(|| String::from("Idle"));
+//!     let remote_status = UiRef::new(status.clone());
+//!
+//!     rsx! {
+//!         Window { title: "Fetch", width: 400, height: 200,
+//!             p { {status.get()} }
+//!             button {
+//!                 onclick: move || {
+//!                     let status = remote_status.clone();
+//!                     status.set("Loading...".into());
+//!                     rinch::spawn(async move {
+//!                         let text = fetch_status().await;
+//!                         // Runs on the UI thread and re-renders
+//!                         status.set(text);
+//!                     });
+//!                 },
+//!                 "Refresh"
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "async")]
+use std::sync::OnceLock;
+use std::thread::{self, ThreadId};
+
+#[cfg(feature = "async")]
+use futures_util::{Stream, StreamExt};
+use rinch_core::element::WindowProps;
+use rinch_core::Signal;
+#[cfg(feature = "async")]
+use rinch_core::use_ref;
+#[cfg(feature = "async")]
+use tokio::sync::mpsc;
+#[cfg(feature = "async")]
+use tokio::task::JoinHandle;
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::runtime::RinchEvent;
+use crate::windows::{self, WindowHandle};
+
+/// A closure scheduled to run on the UI thread.
+type UiTask = Box<dyn FnOnce() + Send>;
+
+/// Closures waiting for the runtime to run them.
+static UI_TASKS: Mutex<Vec<UiTask>> = Mutex::new(Vec::new());
+
+/// Event loop proxy for waking the runtime, set once the event loop exists.
+static EVENT_PROXY: Mutex<Option<EventLoopProxy<RinchEvent>>> = Mutex::new(None);
+
+#[cfg(feature = "async")]
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+thread_local! {
+    /// Values wrapped in a [`UiRef`] on this thread.
+    static UI_VALUES: RefCell<HashMap<u64, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Run a future on the background tokio runtime.
+///
+/// If called from inside a tokio runtime (e.g. an app using
+/// `#[tokio::main]`), the task runs on that runtime instead. Use
+/// [`AppHandle::run_on_ui_thread`] or a [`UiRef`] to update the UI with the
+/// result.
+///
+/// # Panics
+///
+/// Panics if the background runtime can't be started.
+#[cfg(feature = "async")]
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.spawn(future),
+        Err(_) => runtime().spawn(future),
+    }
+}
+
+/// The runtime tasks run on, created on first use.
+#[cfg(feature = "async")]
+fn runtime() -> &'static tokio::runtime::Runtime {
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .thread_name("rinch-async")
+            .enable_all()
+            .build()
+            .expect("Failed to start async runtime")
+    })
+}
+
+/// Run blocking work on a thread pool, with progress and the result handled
+/// on the UI thread.
+///
+/// `work` runs on tokio's blocking thread pool and can report progress
+/// through the [`Progress`] it's given. `on_progress` and `on_done` run on the
+/// UI thread, so they can capture signals, and the UI re-renders after each
+/// call. If `work` panics, `on_done` isn't called.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::task::spawn_blocking;
+///
+/// let scanned = use_signal(|| 0usize);
+/// let files = use_signal(Vec::new);
+/// let (progress_count, done_files) = (scanned.clone(), files.clone());
+///
+/// spawn_blocking(
+///     move |progress| {
+///         let mut found = Vec::new();
+///         for entry in walk(&root) {
+///             found.push(entry);
+///             progress.report(found.len());
+///         }
+///         found
+///     },
+///     move |count| progress_count.set(count),
+///     move |found| done_files.set(found),
+/// );
+/// ```
+#[cfg(feature = "async")]
+pub fn spawn_blocking<P, T>(
+    work: impl FnOnce(Progress<P>) -> T + Send + 'static,
+    on_progress: impl Fn(P) + 'static,
+    on_done: impl FnOnce(T) + 'static,
+) where
+    P: Send + 'static,
+    T: Send + 'static,
+{
+    let progress = Progress {
+        latest: Arc::new(Mutex::new(None)),
+        callback: UiRef::new(Rc::new(on_progress) as Rc<dyn Fn(P)>),
+    };
+    let on_done = UiRef::new(RefCell::new(Some(Box::new(on_done) as Box<dyn FnOnce(T)>)));
+    let task = move || {
+        let result = work(progress);
+        app_handle().run_on_ui_thread(move || {
+            if let Some(on_done) = on_done.with(|on_done| on_done.borrow_mut().take()) {
+                on_done(result);
+            }
+        });
+    };
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.spawn_blocking(task),
+        Err(_) => runtime().spawn_blocking(task),
+    };
+}
+
+/// Reports the progress of work started with [`spawn_blocking`].
+///
+/// Reports are coalesced: if the UI thread hasn't handled the previous
+/// report yet, it only receives the latest one. The final report is always
+/// handled before the result.
+#[cfg(feature = "async")]
+pub struct Progress<P: 'static> {
+    latest: Arc<Mutex<Option<P>>>,
+    callback: UiRef<Rc<dyn Fn(P)>>,
+}
+
+#[cfg(feature = "async")]
+impl<P: Send + 'static> Progress<P> {
+    /// Report progress to the UI thread.
+    pub fn report(&self, value: P) {
+        let pending = self.latest.lock().unwrap().replace(value).is_some();
+        if pending {
+            // The scheduled closure will pick up the new value
+            return;
+        }
+        let latest = Arc::clone(&self.latest);
+        let callback = self.callback.clone();
+        app_handle().run_on_ui_thread(move || {
+            let value = latest.lock().unwrap().take();
+            if let Some(value) = value {
+                callback.get()(value);
+            }
+        });
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P: 'static> Clone for Progress<P> {
+    fn clone(&self) -> Self {
+        Self {
+            latest: Arc::clone(&self.latest),
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P: 'static> fmt::Debug for Progress<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress").finish_non_exhaustive()
+    }
+}
+
+/// Receives the messages sent to a coroutine started with [`use_coroutine`].
+#[cfg(feature = "async")]
+pub type Receiver<M> = mpsc::UnboundedReceiver<M>;
+
+/// Sends messages to a coroutine started with [`use_coroutine`].
+///
+/// Senders are `Send` and cheap to clone; every clone sends to the same
+/// coroutine.
+#[cfg(feature = "async")]
+pub struct Sender<M> {
+    inner: mpsc::UnboundedSender<M>,
+}
+
+#[cfg(feature = "async")]
+impl<M> Sender<M> {
+    /// Send a message to the coroutine without waiting for it to be handled.
+    ///
+    /// Returns `false` if the coroutine has finished.
+    pub fn send(&self, message: M) -> bool {
+        self.inner.send(message).is_ok()
+    }
+
+    /// Whether the coroutine has finished, so messages are no longer received.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M> Clone for Sender<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M> fmt::Debug for Sender<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Start a long-lived async task on the first render and get a sender for
+/// messages to it.
+///
+/// `init` is called once with the receiving end of the channel, and the
+/// future it returns is [`spawn`]ed. The coroutine typically loops over
+/// `rx.recv()`, which returns `None` once every [`Sender`] has been
+/// dropped, and reports back to the UI through a [`UiRef`].
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+/// use rinch::tasks::UiRef;
+///
+/// enum SearchMessage {
+///     Query(String),
+/// }
+///
+/// fn app() -> Element {
+///     let results = use_signal(Vec::<String>::new);
+///     let remote_results = UiRef::new(results.clone());
+///
+///     let search = use_coroutine(move |mut rx| async move {
+///         while let Some(SearchMessage::Query(query)) = rx.recv().await {
+///             let found = search_index(&query).await;
+///             remote_results.set(found);
+///         }
+///     });
+///
+///     rsx! {
+///         Window { title: "Search",
+///             input { oninput: move |query: String| { search.send(SearchMessage::Query(query)); } }
+///             p { {results.get().join(", ")} }
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub fn use_coroutine<M, F, Fut>(init: F) -> Sender<M>
+where
+    M: Send + 'static,
+    F: FnOnce(Receiver<M>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    use_ref(|| {
+        let (tx, rx) = mpsc::unbounded_channel();
+        spawn(init(rx));
+        Sender { inner: tx }
+    })
+    .get()
+}
+
+/// Creating a signal that follows a stream.
+///
+/// Implemented for `Signal<Option<T>>`, which holds `None` until the stream
+/// yields its first item and then always holds the latest one.
+#[cfg(feature = "async")]
+pub trait FromStream<S> {
+    /// Create a signal that is set to each item of `stream`.
+    ///
+    /// The stream is driven by a [`spawn`]ed task, and each item is set on
+    /// the UI thread, re-rendering the UI. The task ends when the stream does.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rinch::tasks::FromStream;
+    ///
+    /// let price: Signal<Option<f64>> = Signal::from_stream(price_feed());
+    /// ```
+    fn from_stream(stream: S) -> Self;
+}
+
+#[cfg(feature = "async")]
+impl<T, S> FromStream<S> for Signal<Option<T>>
+where
+    T: Send + 'static,
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from_stream(stream: S) -> Self {
+        let signal = Signal::new(None);
+        let remote = UiRef::new(signal.clone());
+        spawn(async move {
+            let mut stream = std::pin::pin!(stream);
+            while let Some(item) = stream.next().await {
+                remote.set(Some(item));
+            }
+        });
+        signal
+    }
+}
+
+/// Create a signal following a stream, on the first render.
+///
+/// `init` is only called on the first render; see [`FromStream`] for how the
+/// signal follows the stream. To keep every item, e.g. for a log tail,
+/// collect them in the stream with `scan`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+///
+/// fn app() -> Element {
+///     let line = use_stream(|| tail_log("app.log"));
+///
+///     rsx! {
+///         Window { title: "Log",
+///             pre { {line.get().unwrap_or_default()} }
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub fn use_stream<T, S>(init: impl FnOnce() -> S) -> Signal<Option<T>>
+where
+    T: Send + 'static,
+    S: Stream<Item = T> + Send + 'static,
+{
+    use_ref(|| Signal::from_stream(init())).get()
+}
+
+/// Get a handle for reaching the UI from other threads.
+///
+/// Handles can be created before the event loop starts; closures scheduled
+/// before then run once it does. See also
+/// [`run_with_handle`](crate::run_with_handle).
+pub fn app_handle() -> AppHandle {
+    AppHandle { _private: () }
+}
+
+/// A handle to the running application that can be sent to other threads.
+///
+/// Get one with [`app_handle`] or [`run_with_handle`](crate::run_with_handle).
+#[derive(Debug, Clone)]
+pub struct AppHandle {
+    _private: (),
+}
+
+impl AppHandle {
+    /// Run a closure on the UI thread, then re-render.
+    ///
+    /// Closures run in the order they were scheduled. The closure must be
+    /// `Send`, so to reach signals it should capture them in a [`UiRef`].
+    pub fn run_on_ui_thread(&self, f: impl FnOnce() + Send + 'static) {
+        UI_TASKS.lock().unwrap().push(Box::new(f));
+        wake();
+    }
+
+    /// Request a re-render of the UI.
+    pub fn request_render(&self) {
+        wake();
+    }
+
+    /// Open a new window, like [`windows::open_window`](crate::windows::open_window).
+    ///
+    /// The window opens once the UI thread handles the request, but the
+    /// returned handle can be used straight away.
+    pub fn open_window(&self, props: WindowProps, html_content: String) -> WindowHandle {
+        let handle = WindowHandle::new();
+        self.run_on_ui_thread(move || windows::open_window_with_handle(handle, props, html_content));
+        handle
+    }
+
+    /// Exit the application, returning from `run`.
+    ///
+    /// Callbacks registered with [`on_exit`](crate::on_exit) run before the
+    /// windows close.
+    pub fn exit(&self) {
+        self.dispatch(RinchEvent::Exit);
+    }
+
+    /// Send an event to the runtime.
+    ///
+    /// Returns `false` if the event loop isn't running.
+    pub fn dispatch(&self, event: RinchEvent) -> bool {
+        EVENT_PROXY
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|proxy| proxy.send_event(event).is_ok())
+    }
+}
+
+/// Wake the runtime to run scheduled closures and re-render.
+fn wake() {
+    app_handle().dispatch(RinchEvent::RunUiTasks);
+}
+
+/// Set the event loop proxy (called by runtime during initialization).
+///
+/// Wakes the runtime if closures were scheduled before it started.
+pub(crate) fn set_event_proxy(proxy: EventLoopProxy<RinchEvent>) {
+    *EVENT_PROXY.lock().unwrap() = Some(proxy);
+    if !UI_TASKS.lock().unwrap().is_empty() {
+        wake();
+    }
+}
+
+/// Run the scheduled closures (called by runtime on `RinchEvent::RunUiTasks`).
+pub(crate) fn run_ui_tasks() {
+    // Take the closures first so they can schedule more
+    let tasks = std::mem::take(&mut *UI_TASKS.lock().unwrap());
+    for task in tasks {
+        task();
+    }
+}
+
+/// A value that lives on the UI thread but can be referred to from anywhere.
+///
+/// `UiRef` is `Send` and `Sync` whatever it holds, so it can be moved into
+/// tasks and [`AppHandle::run_on_ui_thread`] closures, but the value can only
+/// be used on the thread that created it. Cloning is cheap and every clone
+/// refers to the same value, which is dropped with the last clone.
+///
+/// A `UiRef` holding a [`Signal`] can also be set from any thread.
+pub struct UiRef<T: 'static> {
+    key: Arc<UiKey>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Identifies a value in its thread's `UI_VALUES`, removing it when dropped.
+struct UiKey {
+    id: u64,
+    thread: ThreadId,
+}
+
+impl Drop for UiKey {
+    fn drop(&mut self) {
+        let id = self.id;
+        if thread::current().id() == self.thread {
+            remove_value(id);
+        } else {
+            app_handle().run_on_ui_thread(move || remove_value(id));
+        }
+    }
+}
+
+fn remove_value(id: u64) {
+    // The thread-local is gone if the UI thread is shutting down. The value is
+    // dropped after the map is released, since it may hold other UiRefs.
+    let removed = UI_VALUES.try_with(|values| values.borrow_mut().remove(&id));
+    drop(removed);
+}
+
+impl<T: 'static> UiRef<T> {
+    /// Wrap a value owned by the current thread, usually the UI thread.
+    pub fn new(value: T) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(1);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        UI_VALUES.with(|values| values.borrow_mut().insert(id, Rc::new(value)));
+        Self {
+            key: Arc::new(UiKey {
+                id,
+                thread: thread::current().id(),
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Whether the value can be used on the current thread.
+    pub fn is_owner_thread(&self) -> bool {
+        thread::current().id() == self.key.thread
+    }
+
+    /// Call a function with a reference to the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on a thread other than the one that created the
+    /// `UiRef`.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        assert!(
+            self.is_owner_thread(),
+            "UiRef used outside the thread that created it; use AppHandle::run_on_ui_thread"
+        );
+        // Release the map before calling `f`, which may create or drop UiRefs
+        let value = UI_VALUES
+            .with(|values| values.borrow().get(&self.key.id).cloned())
+            .expect("UiRef value missing");
+        f(value.downcast_ref::<T>().expect("UiRef value has the wrong type"))
+    }
+
+    /// Clone the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on a thread other than the one that created the
+    /// `UiRef`.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+}
+
+impl<T: Send + 'static> UiRef<Signal<T>> {
+    /// Set the signal, on the UI thread.
+    ///
+    /// Called on the UI thread, this is the same as [`Signal::set`];
+    /// elsewhere it schedules the update with
+    /// [`AppHandle::run_on_ui_thread`], which re-renders afterwards.
+    pub fn set(&self, value: T) {
+        self.update(move |current| *current = value);
+    }
+
+    /// Update the signal's value using a function, on the UI thread.
+    ///
+    /// See [`set`](Self::set).
+    pub fn update(&self, f: impl FnOnce(&mut T) + Send + 'static) {
+        if self.is_owner_thread() {
+            self.with(|signal| signal.update(f));
+        } else {
+            let this = self.clone();
+            app_handle().run_on_ui_thread(move || this.with(|signal| signal.update(f)));
+        }
+    }
+}
+
+impl<T: 'static> Clone for UiRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: Arc::clone(&self.key),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static> fmt::Debug for UiRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UiRef").field("id", &self.key.id).finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::MutexGuard;
+    #[cfg(feature = "async")]
+    use std::time::{Duration, Instant};
+
+    /// Hold while a test runs the shared UI task queue, so it doesn't run
+    /// closures scheduled by tests on other threads.
+    pub(crate) fn lock_ui_tasks() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        run_ui_tasks();
+        guard
+    }
+
+    /// Run scheduled closures until `done` returns true.
+    #[cfg(feature = "async")]
+    fn run_until(done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out waiting for UI tasks");
+            run_ui_tasks();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn ui_tasks_run_in_order() {
+        let _lock = lock_ui_tasks();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3 {
+            let log = Arc::clone(&log);
+            app_handle().run_on_ui_thread(move || log.lock().unwrap().push(i));
+        }
+        let nested = Arc::clone(&log);
+        app_handle().run_on_ui_thread(move || {
+            let log = Arc::clone(&nested);
+            app_handle().run_on_ui_thread(move || log.lock().unwrap().push(4));
+        });
+        run_ui_tasks();
+        assert_eq!(*log.lock().unwrap(), [0, 1, 2]);
+        // Closures scheduled while running wait for the next pass
+        run_ui_tasks();
+        assert_eq!(*log.lock().unwrap(), [0, 1, 2, 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn progress_reports_coalesce() {
+        let _lock = lock_ui_tasks();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let callback = Rc::clone(&seen);
+        let progress = Progress {
+            latest: Arc::new(Mutex::new(None)),
+            callback: UiRef::new(Rc::new(move |value| callback.borrow_mut().push(value)) as Rc<dyn Fn(u32)>),
+        };
+
+        let remote = progress.clone();
+        thread::spawn(move || (1..=3).for_each(|i| remote.report(i))).join().unwrap();
+        run_ui_tasks();
+        assert_eq!(*seen.borrow(), [3u32]);
+
+        progress.report(4);
+        run_ui_tasks();
+        run_ui_tasks();
+        assert_eq!(*seen.borrow(), [3u32, 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn spawn_blocking_reports_final_progress_before_result() {
+        #[derive(Debug, PartialEq)]
+        enum Event {
+            Progress(usize),
+            Done(&'static str),
+        }
+
+        let _lock = lock_ui_tasks();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (on_progress, on_done) = (Rc::clone(&events), Rc::clone(&events));
+        spawn_blocking(
+            |progress| {
+                for i in 1..=100 {
+                    progress.report(i);
+                }
+                "done"
+            },
+            move |count| on_progress.borrow_mut().push(Event::Progress(count)),
+            move |result| on_done.borrow_mut().push(Event::Done(result)),
+        );
+        run_until(|| events.borrow().last().is_some_and(|event| matches!(event, Event::Done(_))));
+
+        let events = events.borrow();
+        assert!(events.len() <= 101);
+        assert_eq!(events[events.len() - 2..], [Event::Progress(100), Event::Done("done")]);
+        let counts: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Progress(count) => Some(*count),
+                Event::Done(_) => None,
+            })
+            .collect();
+        assert!(counts.is_sorted());
+    }
+
+    #[test]
+    fn ui_ref_sets_signals_from_other_threads() {
+        let _lock = lock_ui_tasks();
+        let signal = Signal::new(0);
+        let remote = UiRef::new(signal.clone());
+        assert!(remote.is_owner_thread());
+
+        let sender = remote.clone();
+        thread::spawn(move || {
+            assert!(!sender.is_owner_thread());
+            sender.set(5);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(signal.get(), 0);
+        run_ui_tasks();
+        assert_eq!(signal.get(), 5);
+
+        remote.update(|value| *value += 1);
+        assert_eq!(signal.get(), 6);
+    }
+
+    #[test]
+    fn ui_ref_dropped_elsewhere_is_removed_on_owner_thread() {
+        let _lock = lock_ui_tasks();
+        let value = UiRef::new(String::from("kept"));
+        let id = value.key.id;
+        let contains = || UI_VALUES.with(|values| values.borrow().contains_key(&id));
+        assert_eq!(value.get(), "kept");
+
+        thread::spawn(move || drop(value)).join().unwrap();
+        assert!(contains());
+        run_ui_tasks();
+        assert!(!contains());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the thread")]
+    fn ui_ref_panics_on_other_threads() {
+        let value = UiRef::new(1);
+        let result = thread::spawn(move || value.get()).join();
+        std::panic::resume_unwind(result.unwrap_err());
+    }
+}
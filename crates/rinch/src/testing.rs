@@ -0,0 +1,622 @@
+//! Driving an app from tests, without windows or a GPU.
+//!
+//! [`TestApp`] renders the app function as the runtime does and lays out each
+//! window's HTML in a document that is never drawn. Its methods find elements
+//! with CSS selectors and act on them as the user would: they call the event
+//! handlers registered during the render, run closures scheduled with
+//! [`run_on_ui_thread`](crate::tasks::AppHandle::run_on_ui_thread), and render
+//! again, so the assertions that follow see the updated UI.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::testing::TestApp;
+//!
+//! #[test]
+//! fn saving_clears_the_modified_marker() {
+//!     let mut app = TestApp::new(app);
+//!
+//!     app.type_text("#name", "abc");
+//!     assert_eq!(app.text("h1").as_deref(), Some("abc*"));
+//!
+//!     app.click("button.save");
+//!     assert_eq!(app.text("h1").as_deref(), Some("abc"));
+//!
+//!     app.press_key("Ctrl+N");
+//!     assert_eq!(app.attr("#name", "value").as_deref(), Some(""));
+//! }
+//! ```
+//!
+//! Hooks, signals and event handlers are per thread, and each test runs on
+//! its own thread, so tests don't see each other's state. Links aren't
+//! opened and the default stylesheet isn't applied.
+
+use blitz_dom::{Document, DocumentConfig, ElementData};
+use blitz_html::HtmlDocument;
+use blitz_traits::shell::{ColorScheme, Viewport};
+use rinch_core::element::{Element, MenuActivation, MenuItemProps, WindowColorScheme, WindowProps};
+use rinch_core::event::Modifiers;
+use rinch_core::events::{clear_handlers, dispatch_change, dispatch_event, dispatch_input};
+use rinch_core::hooks::{begin_render, clear_hooks, end_render};
+use winit::keyboard::NamedKey;
+
+use crate::menu::{keymap, parse_shortcut_for_matching, ShortcutKey};
+use crate::shell::focus::{self, Arrow};
+use crate::shell::runtime::window_html;
+use crate::shell::window_manager::{
+    click_handler, handler_attr, keyed_node_path, node_at_keyed_path, PathStep, CHANGE_HANDLER_ATTR,
+    INPUT_HANDLER_ATTR,
+};
+
+/// An app rendered for a test.
+pub struct TestApp {
+    app: Box<dyn Fn() -> Element>,
+    windows: Vec<TestWindow>,
+    /// Menu and tray items, which are matched against [`press_key`](Self::press_key).
+    menu_items: Vec<MenuItemProps>,
+    /// The window with keyboard focus and the focused node's keyed path in its
+    /// tree, kept across renders.
+    focus: Option<(usize, Vec<PathStep>)>,
+}
+
+/// The content of one `Window` element.
+struct TestWindow {
+    props: WindowProps,
+    doc: HtmlDocument,
+}
+
+impl TestApp {
+    /// Render an app, as [`run`](crate::run) would before opening its windows.
+    pub fn new(app: impl Fn() -> Element + 'static) -> Self {
+        clear_handlers();
+        clear_hooks();
+
+        let mut test_app = Self {
+            app: Box::new(app),
+            windows: Vec::new(),
+            menu_items: Vec::new(),
+            focus: None,
+        };
+        test_app.render();
+        test_app
+    }
+
+    /// Run the closures scheduled for the UI thread and render the app again.
+    ///
+    /// The methods that act on the app call this; call it after changing
+    /// signals from the test itself.
+    pub fn render(&mut self) {
+        crate::tasks::run_ui_tasks();
+
+        clear_handlers();
+        // Only the components rendered this time keep their shortcuts
+        drop(keymap::take_hook_shortcuts());
+        begin_render();
+        let root = (self.app)().expand_components();
+        end_render();
+
+        self.windows.clear();
+        self.menu_items.clear();
+        self.collect(root);
+
+        // Focus the same element in the new documents
+        if let Some((index, path)) = &self.focus {
+            let node_id = self
+                .windows
+                .get(*index)
+                .and_then(|window| node_at_keyed_path(&window.doc.inner(), path));
+            match node_id {
+                Some(node_id) => {
+                    self.windows[*index].doc.inner_mut().set_focus_to(node_id);
+                }
+                None => self.focus = None,
+            }
+        }
+    }
+
+    /// Click the first element matching `selector`.
+    ///
+    /// Focuses it if it can take focus, toggles it if it's a checkbox or
+    /// radio button, and calls the `onclick` handler of the element or its
+    /// nearest ancestor with one.
+    ///
+    /// # Panics
+    ///
+    /// If no element matches.
+    pub fn click(&mut self, selector: &str) {
+        let (index, node_id) = self.expect(selector);
+        if focus::can_focus_node(&self.windows[index].doc.inner(), node_id) {
+            self.set_focus(index, node_id);
+        }
+        self.activate(index, node_id);
+        self.render();
+    }
+
+    /// Type `text` at the end of the text input or text area matching
+    /// `selector`, passing the new value to its `oninput` handler.
+    ///
+    /// The input is focused first. Bind its `value` to a signal for typing
+    /// to add up over several calls, as the document is rebuilt from the
+    /// app's HTML after each one.
+    ///
+    /// # Panics
+    ///
+    /// If no element matches or it doesn't take text.
+    pub fn type_text(&mut self, selector: &str, text: &str) {
+        let (index, node_id) = self.expect(selector);
+        let (handler_id, value) = {
+            let doc = self.windows[index].doc.inner();
+            let element = doc
+                .get_node(node_id)
+                .and_then(|node| node.element_data())
+                .filter(|element| element.text_input_data().is_some())
+                .unwrap_or_else(|| panic!("`{}` doesn't take text", selector));
+            let value = element.text_input_data().map(|input| input.editor.text().to_string());
+            (handler_attr(element, INPUT_HANDLER_ATTR), value.unwrap_or_default() + text)
+        };
+
+        self.set_focus(index, node_id);
+        if let Some(handler_id) = handler_id {
+            dispatch_input(handler_id, value);
+        }
+        self.render();
+    }
+
+    /// Press a key, written like a menu shortcut: `"Enter"`, `"Shift+Tab"`,
+    /// `"ArrowDown"`, `"Ctrl+S"`.
+    ///
+    /// A key that is the shortcut of an enabled menu or tray item activates
+    /// the item, and one bound by [`use_shortcut`](keymap::use_shortcut)
+    /// calls its callback. Otherwise Tab and Shift+Tab move focus, Enter and Space
+    /// click the focused element unless it takes text, and the arrow keys
+    /// move through radio groups and menus, as in a window. Other keys do
+    /// nothing.
+    ///
+    /// # Panics
+    ///
+    /// If `key` isn't a valid shortcut.
+    pub fn press_key(&mut self, key: &str) {
+        let pressed = parse_shortcut_for_matching(key).unwrap_or_else(|| panic!("invalid key `{}`", key));
+
+        let item = self.menu_items.iter().find(|item| {
+            item.enabled && item.shortcut.as_deref().and_then(parse_shortcut_for_matching).as_ref() == Some(&pressed)
+        });
+        if let Some(item) = item {
+            if let Some(callback) = &item.onclick {
+                let ctrl_or_cmd = pressed.ctrl_or_cmd;
+                callback.invoke(&MenuActivation {
+                    via_shortcut: true,
+                    modifiers: Modifiers {
+                        ctrl: ctrl_or_cmd && !cfg!(target_os = "macos"),
+                        alt: pressed.alt,
+                        shift: pressed.shift,
+                        meta: ctrl_or_cmd && cfg!(target_os = "macos"),
+                    },
+                    item_id: item.id.clone(),
+                });
+            }
+            self.render();
+            return;
+        }
+        if let Some(callback) = keymap::hook_shortcut_callback(&pressed) {
+            callback();
+            self.render();
+            return;
+        }
+
+        let ShortcutKey::Named(key) = pressed.key else {
+            return;
+        };
+        if pressed.ctrl_or_cmd || pressed.alt {
+            return;
+        }
+        let index = self.focus.as_ref().map_or(0, |(index, _)| *index);
+        if index >= self.windows.len() {
+            return;
+        }
+
+        let arrow = match key {
+            NamedKey::Tab => {
+                let next = focus::next_in_tab_order(&self.windows[index].doc.inner(), pressed.shift);
+                if let Some(node_id) = next {
+                    self.set_focus(index, node_id);
+                }
+                None
+            }
+            NamedKey::Enter | NamedKey::Space if !pressed.shift => {
+                let target = focus::activation_target(&self.windows[index].doc.inner());
+                if let Some(node_id) = target {
+                    self.activate(index, node_id);
+                }
+                None
+            }
+            NamedKey::ArrowUp => Some(Arrow::Up),
+            NamedKey::ArrowDown => Some(Arrow::Down),
+            NamedKey::ArrowLeft => Some(Arrow::Left),
+            NamedKey::ArrowRight => Some(Arrow::Right),
+            _ => None,
+        };
+        if let Some(arrow) = arrow.filter(|_| !pressed.shift) {
+            let target = focus::arrow_target(&self.windows[index].doc.inner(), arrow);
+            if let Some(target) = target {
+                self.set_focus(index, target.node_id);
+                if target.check {
+                    self.activate(index, target.node_id);
+                }
+            }
+        }
+        self.render();
+    }
+
+    /// Whether an element matches `selector`.
+    pub fn exists(&self, selector: &str) -> bool {
+        self.find(selector).is_some()
+    }
+
+    /// The text content of the first element matching `selector`.
+    pub fn text(&self, selector: &str) -> Option<String> {
+        let (index, node_id) = self.find(selector)?;
+        self.windows[index]
+            .doc
+            .inner()
+            .get_node(node_id)
+            .map(|node| node.text_content())
+    }
+
+    /// An attribute of the first element matching `selector`.
+    pub fn attr(&self, selector: &str, name: &str) -> Option<String> {
+        let (index, node_id) = self.find(selector)?;
+        let doc = self.windows[index].doc.inner();
+        let element = doc.get_node(node_id)?.element_data()?;
+        focus::attr(element, name).map(str::to_string)
+    }
+
+    /// Whether the first element matching `selector` has keyboard focus.
+    pub fn is_focused(&self, selector: &str) -> bool {
+        let Some((index, node_id)) = self.find(selector) else {
+            return false;
+        };
+        self.focus.as_ref().is_some_and(|(focused, _)| *focused == index)
+            && self.windows[index].doc.inner().get_focussed_node_id() == Some(node_id)
+    }
+
+    /// The titles of the app's windows, in order.
+    pub fn window_titles(&self) -> Vec<String> {
+        self.windows.iter().map(|window| window.props.title.clone()).collect()
+    }
+
+    /// The first element matching `selector`, as its window's index and its
+    /// node ID.
+    ///
+    /// # Panics
+    ///
+    /// If `selector` isn't a valid CSS selector.
+    fn find(&self, selector: &str) -> Option<(usize, usize)> {
+        self.windows.iter().enumerate().find_map(|(index, window)| {
+            match window.doc.inner().query_selector(selector) {
+                Ok(node_id) => node_id.map(|node_id| (index, node_id)),
+                Err(_) => panic!("invalid selector `{}`", selector),
+            }
+        })
+    }
+
+    /// Like [`find`](Self::find), but panics if nothing matches.
+    fn expect(&self, selector: &str) -> (usize, usize) {
+        self.find(selector)
+            .unwrap_or_else(|| panic!("no element matches `{}`", selector))
+    }
+
+    fn set_focus(&mut self, index: usize, node_id: usize) {
+        let mut doc = self.windows[index].doc.inner_mut();
+        doc.set_focus_to(node_id);
+        self.focus = Some((index, keyed_node_path(&doc, node_id)));
+    }
+
+    /// Click an element: toggle it if it's a checkbox or radio button and
+    /// call its `onclick` handler, as the window does for Enter and Space.
+    fn activate(&mut self, index: usize, node_id: usize) {
+        let (toggle, handler_id) = {
+            let doc = self.windows[index].doc.inner();
+            let toggle = doc
+                .get_node(node_id)
+                .and_then(|node| node.element_data())
+                .and_then(|element| Some((toggled(element)?, handler_attr(element, CHANGE_HANDLER_ATTR)?)));
+            (toggle, click_handler(&doc, node_id))
+        };
+
+        if let Some((checked, handler_id)) = toggle {
+            dispatch_change(handler_id, checked);
+        }
+        if let Some(handler_id) = handler_id {
+            dispatch_event(handler_id);
+        }
+    }
+
+    /// Build a document for each window in the element tree and collect the
+    /// menu items.
+    fn collect(&mut self, element: Element) {
+        match element {
+            Element::Window(props, children) => {
+                collect_menu_items(&children, &mut self.menu_items);
+                let doc = document(&props, &window_html(&props, &children));
+                self.windows.push(TestWindow { props, doc });
+            }
+            Element::Fragment(children) => {
+                for child in children {
+                    self.collect(child);
+                }
+            }
+            element => collect_menu_items(std::slice::from_ref(&element), &mut self.menu_items),
+        }
+    }
+}
+
+/// The checked state of a checkbox or radio button after clicking it.
+fn toggled(element: &ElementData) -> Option<bool> {
+    let checked = element.checkbox_input_checked()?;
+    let after = focus::attr(element, "type") == Some("radio") || !checked;
+    (after != checked).then_some(after)
+}
+
+/// Add the items of the menus and tray menus in `elements` to `items`.
+fn collect_menu_items(elements: &[Element], items: &mut Vec<MenuItemProps>) {
+    for element in elements {
+        match element {
+            Element::MenuItem(props) => items.push(props.clone()),
+            Element::AppMenu(_, children)
+            | Element::Menu(_, children)
+            | Element::Tray(_, children)
+            | Element::Fragment(children) => collect_menu_items(children, items),
+            _ => {}
+        }
+    }
+}
+
+/// Parse and lay out a window's HTML at the window's size.
+fn document(props: &WindowProps, html: &str) -> HtmlDocument {
+    let color_scheme = match props.color_scheme {
+        WindowColorScheme::Dark => ColorScheme::Dark,
+        WindowColorScheme::Light | WindowColorScheme::System => ColorScheme::Light,
+    };
+    let config = DocumentConfig {
+        viewport: Some(Viewport::new(props.width, props.height, 1.0, color_scheme)),
+        base_url: crate::assets::base_url(),
+        ..Default::default()
+    };
+    let mut doc = HtmlDocument::from_html(html, config);
+    // Tab order skips elements without a layout box
+    doc.inner_mut().resolve(0.0);
+    doc
+}
+
+impl std::fmt::Debug for TestApp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestApp")
+            .field("windows", &self.window_titles())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rinch_core::element::{AppMenuProps, MenuItemCallback, MenuProps};
+    use rinch_core::events::{html_escape_string, register_change_handler, register_handler, register_input_handler};
+    use rinch_core::hooks::use_signal;
+
+    use super::*;
+    use crate::tasks::tests::lock_ui_tasks;
+
+    /// A counter, a name field and a checkbox, with a menu item resetting
+    /// the counter.
+    fn app() -> Element {
+        let count = use_signal(|| 0);
+        let name = use_signal(String::new);
+        let agreed = use_signal(|| false);
+
+        let add = register_handler(Box::new({
+            let count = count.clone();
+            move || count.update(|count| *count += 1)
+        }));
+        let input = register_input_handler({
+            let name = name.clone();
+            move |value| name.set(value)
+        });
+        let change = register_change_handler({
+            let agreed = agreed.clone();
+            move |checked| agreed.set(checked)
+        });
+        let reset = MenuItemProps {
+            label: "Reset".into(),
+            shortcut: Some("Ctrl+R".into()),
+            onclick: Some(MenuItemCallback::new({
+                let count = count.clone();
+                move || count.set(0)
+            })),
+            ..Default::default()
+        };
+
+        let html = format!(
+            r#"<h1>{name}</h1>
+            <p id="count">{count}</p>
+            <button id="add" data-rid="{add}">Add</button>
+            <input id="name" value="{name}" data-oninput="{input}">
+            <input id="agree" type="checkbox" {checked} data-onchange="{change}">
+            <p id="agreed">{agreed}</p>"#,
+            name = html_escape_string(&name.get()),
+            count = count.get(),
+            checked = if agreed.get() { "checked" } else { "" },
+            agreed = agreed.get(),
+        );
+        Element::Fragment(vec![
+            Element::AppMenu(AppMenuProps { native: false }, vec![Element::Menu(
+                MenuProps { label: "Edit".into() },
+                vec![Element::MenuItem(reset)],
+            )]),
+            Element::Window(
+                WindowProps {
+                    title: "Test".into(),
+                    ..Default::default()
+                },
+                vec![Element::Html(html.into())],
+            ),
+        ])
+    }
+
+    #[test]
+    fn renders_windows() {
+        let _lock = lock_ui_tasks();
+        let app = TestApp::new(app);
+        assert_eq!(app.window_titles(), ["Test"]);
+        assert_eq!(app.text("#count").as_deref(), Some("0"));
+        assert!(app.exists("#add"));
+        assert!(!app.exists("#missing"));
+    }
+
+    #[test]
+    fn click_calls_the_handler_and_renders() {
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.click("#add");
+        app.click("#add");
+        assert_eq!(app.text("#count").as_deref(), Some("2"));
+        assert!(app.is_focused("#add"));
+    }
+
+    #[test]
+    fn click_toggles_checkboxes() {
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.click("#agree");
+        assert_eq!(app.text("#agreed").as_deref(), Some("true"));
+        assert_eq!(app.attr("#agree", "checked").as_deref(), Some(""));
+        app.click("#agree");
+        assert_eq!(app.text("#agreed").as_deref(), Some("false"));
+        assert_eq!(app.attr("#agree", "checked"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "no element matches `#missing`")]
+    fn click_panics_without_a_match() {
+        let _lock = lock_ui_tasks();
+        TestApp::new(app).click("#missing");
+    }
+
+    #[test]
+    fn type_text_appends_to_the_value() {
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.type_text("#name", "Ad");
+        app.type_text("#name", "a <3");
+        assert_eq!(app.text("h1").as_deref(), Some("Ada <3"));
+        assert_eq!(app.attr("#name", "value").as_deref(), Some("Ada <3"));
+        assert!(app.is_focused("#name"));
+    }
+
+    #[test]
+    #[should_panic(expected = "`#add` doesn't take text")]
+    fn type_text_panics_on_buttons() {
+        let _lock = lock_ui_tasks();
+        TestApp::new(app).type_text("#add", "a");
+    }
+
+    #[test]
+    fn press_key_moves_focus_and_clicks() {
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.press_key("Tab");
+        assert!(app.is_focused("#add"));
+        app.press_key("Enter");
+        app.press_key("Space");
+        assert_eq!(app.text("#count").as_deref(), Some("2"));
+
+        app.press_key("Tab");
+        assert!(app.is_focused("#name"));
+        // Enter doesn't click text inputs
+        app.press_key("Enter");
+        assert_eq!(app.text("#count").as_deref(), Some("2"));
+
+        app.press_key("Shift+Tab");
+        assert!(app.is_focused("#add"));
+    }
+
+    #[test]
+    fn press_key_activates_menu_shortcuts() {
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.click("#add");
+        assert_eq!(app.text("#count").as_deref(), Some("1"));
+        app.press_key("Ctrl+R");
+        assert_eq!(app.text("#count").as_deref(), Some("0"));
+        // Other keys do nothing
+        app.press_key("F5");
+        assert_eq!(app.text("#count").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn press_key_uses_the_shortcuts_of_the_last_render() {
+        use keymap::use_shortcut;
+
+        /// Binds Ctrl+K only once the counter is shown, to a callback that
+        /// adds the step chosen in the last render.
+        fn app() -> Element {
+            let shown = use_signal(|| false);
+            let step = use_signal(|| 1);
+            let count = use_signal(|| 0);
+
+            if shown.get() {
+                let count = count.clone();
+                let step = step.get();
+                use_shortcut("add", "Ctrl+K", move || count.update(|count| *count += step));
+            }
+            let show = register_handler(Box::new({
+                let shown = shown.clone();
+                move || shown.set(true)
+            }));
+            let double = register_handler(Box::new({
+                let step = step.clone();
+                move || step.set(2)
+            }));
+
+            let html = format!(
+                r#"<button id="show" data-rid="{show}">Show</button>
+                <button id="double" data-rid="{double}">Double</button>
+                <p id="count">{count}</p>"#,
+                count = count.get(),
+            );
+            Element::Window(
+                WindowProps {
+                    title: "Test".into(),
+                    ..Default::default()
+                },
+                vec![Element::Html(html.into())],
+            )
+        }
+
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        app.press_key("Ctrl+K");
+        assert_eq!(app.text("#count").as_deref(), Some("0"));
+
+        app.click("#show");
+        app.press_key("Ctrl+K");
+        assert_eq!(app.text("#count").as_deref(), Some("1"));
+
+        app.click("#double");
+        app.press_key("Ctrl+K");
+        assert_eq!(app.text("#count").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn render_runs_ui_tasks() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static RAN: AtomicUsize = AtomicUsize::new(0);
+
+        let _lock = lock_ui_tasks();
+        let mut app = TestApp::new(app);
+        crate::tasks::app_handle().run_on_ui_thread(|| {
+            RAN.fetch_add(1, Ordering::SeqCst);
+        });
+        app.render();
+        assert_eq!(RAN.load(Ordering::SeqCst), 1);
+    }
+}
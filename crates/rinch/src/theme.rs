@@ -0,0 +1,654 @@
+//! Themes as sets of CSS custom properties.
+//!
+//! A [`Theme`] is a named set of design tokens (colors, spacing, radii) that
+//! is exposed to every window as CSS custom properties on the document root.
+//! Styles refer to the tokens with `var(...)`, and switching the theme
+//! restyles all windows without re-rendering.
+//!
+//! Nothing is injected until the theme is first accessed with [`theme`] or
+//! [`set_theme`], so apps that don't use themes are unaffected.
+//!
+//! The OS accent color is available as a signal from [`use_accent_color`],
+//! for apps that want to match the native look.
+//!
+//! The OS accessibility settings are followed automatically:
+//! [`use_prefers_reduced_motion`] is set when the user turned off animations,
+//! which disables CSS transitions and animations in every window, and
+//! [`use_high_contrast`] when they turned on high contrast, which replaces the
+//! theme's colors with those of [`Theme::high_contrast`].
+//! The settings are read on a background thread when the app starts and
+//! whenever a window regains focus.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::theme::{self, Theme};
+//!
+//! fn app() -> Element {
+//!     let current = theme::theme();
+//!
+//!     rsx! {
+//!         Window { title: "Themed",
+//!             style { "
+//!                 body { background: var(--color-background); color: var(--color-text); }
+//!                 .card { padding: var(--spacing-md); border-radius: var(--radius-md); }
+//!             " }
+//!             div { class: "card",
+//!                 button {
+//!                     onclick: move || {
+//!                         let next = if current.get().name() == "dark" {
+//!                             Theme::light()
+//!                         } else {
+//!                             Theme::dark()
+//!                         };
+//!                         current.set(next);
+//!                     },
+//!                     "Toggle theme"
+//!                 }
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::thread::LocalKey;
+
+use rinch_core::{Effect, Signal};
+
+use crate::shell::RinchEvent;
+
+/// A named set of design tokens, exposed as CSS custom properties.
+///
+/// Tokens are stored without the leading `--`. The [`color`](Self::color),
+/// [`spacing`](Self::spacing) and [`radius`](Self::radius) helpers prefix the
+/// token name, so `.color("primary", "#0066cc")` defines `--color-primary`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    name: String,
+    tokens: BTreeMap<String, String>,
+}
+
+impl Theme {
+    /// Create an empty theme.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tokens: BTreeMap::new(),
+        }
+    }
+
+    /// The built-in light theme.
+    pub fn light() -> Self {
+        Self::new("light")
+            .color("background", "#ffffff")
+            .color("surface", "#f5f5f5")
+            .color("text", "#1f1f1f")
+            .color("text-muted", "#6b6b6b")
+            .color("primary", "#0066cc")
+            .color("primary-text", "#ffffff")
+            .color("border", "#d0d0d0")
+            .color("danger", "#c62828")
+            .with_layout_tokens()
+    }
+
+    /// The built-in dark theme.
+    pub fn dark() -> Self {
+        Self::new("dark")
+            .color("background", "#1e1e1e")
+            .color("surface", "#252526")
+            .color("text", "#d4d4d4")
+            .color("text-muted", "#8c8c8c")
+            .color("primary", "#3794ff")
+            .color("primary-text", "#ffffff")
+            .color("border", "#3c3c3c")
+            .color("danger", "#f14c4c")
+            .with_layout_tokens()
+    }
+
+    /// The built-in high contrast theme, used for the colors of the active
+    /// theme while the OS is in high contrast mode.
+    pub fn high_contrast() -> Self {
+        Self::new("high-contrast")
+            .color("background", "#000000")
+            .color("surface", "#000000")
+            .color("text", "#ffffff")
+            .color("text-muted", "#ffffff")
+            .color("primary", "#ffff00")
+            .color("primary-text", "#000000")
+            .color("border", "#ffffff")
+            .color("danger", "#ff6b6b")
+            .with_layout_tokens()
+    }
+
+    /// Spacing and radius tokens shared by the built-in themes.
+    fn with_layout_tokens(self) -> Self {
+        self.spacing("xs", "4px")
+            .spacing("sm", "8px")
+            .spacing("md", "16px")
+            .spacing("lg", "24px")
+            .spacing("xl", "32px")
+            .radius("sm", "4px")
+            .radius("md", "8px")
+            .radius("lg", "12px")
+    }
+
+    /// Set a color token (`--color-<name>`).
+    pub fn color(self, name: &str, value: impl Into<String>) -> Self {
+        self.token(format!("color-{}", name), value)
+    }
+
+    /// Set a spacing token (`--spacing-<name>`).
+    pub fn spacing(self, name: &str, value: impl Into<String>) -> Self {
+        self.token(format!("spacing-{}", name), value)
+    }
+
+    /// Set a border radius token (`--radius-<name>`).
+    pub fn radius(self, name: &str, value: impl Into<String>) -> Self {
+        self.token(format!("radius-{}", name), value)
+    }
+
+    /// Set any token (`--<name>`), such as `font-body` or `shadow-lg`.
+    pub fn token(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tokens.insert(name.into(), value.into());
+        self
+    }
+
+    /// The theme's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get a token's value by its full name, e.g. `color-primary`.
+    pub fn get(&self, token: &str) -> Option<&str> {
+        self.tokens.get(token).map(String::as_str)
+    }
+
+    /// Iterate over the tokens as `(name, value)` pairs.
+    pub fn tokens(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tokens.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Render the tokens as custom properties on `:root`.
+    pub fn to_css(&self) -> String {
+        let mut css = String::from(":root {\n");
+        for (name, value) in &self.tokens {
+            css.push_str(&format!("  --{}: {};\n", name, value));
+        }
+        css.push_str("}\n");
+        css
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+thread_local! {
+    /// The active theme, created on first access.
+    static THEME: RefCell<Option<Signal<Theme>>> = const { RefCell::new(None) };
+}
+
+/// Get the signal holding the active theme.
+///
+/// Setting the signal restyles every window. The theme starts out as
+/// [`Theme::light`]. Every call returns the same signal.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::theme::{theme, Theme};
+///
+/// theme().set(Theme::dark());
+/// ```
+pub fn theme() -> Signal<Theme> {
+    if let Some(signal) = THEME.with(|t| t.borrow().clone()) {
+        return signal;
+    }
+
+    let signal = Signal::new(Theme::default());
+    THEME.with(|t| *t.borrow_mut() = Some(signal.clone()));
+
+    // Restyle windows whenever the theme changes
+    let watched = signal.clone();
+    let mut initial = true;
+    Effect::new(move || {
+        watched.with(|_| ());
+        if initial {
+            initial = false;
+            return;
+        }
+        crate::windows::send_event(RinchEvent::ThemeChanged);
+    });
+
+    signal
+}
+
+/// Switch all windows to a theme.
+///
+/// Shorthand for `theme().set(theme)`.
+pub fn set_theme(theme: Theme) {
+    self::theme().set(theme);
+}
+
+/// CSS for the active theme, if themes are in use, and for the OS
+/// accessibility settings that are turned on.
+pub(crate) fn theme_css() -> Option<String> {
+    let preferences = PREFERENCES.with(Cell::get);
+    let high_contrast = preferences.high_contrast;
+    let mut css = THEME
+        .with(|t| t.borrow().clone())
+        .map(|signal| {
+            signal.with(|theme| {
+                if !high_contrast {
+                    return theme.to_css();
+                }
+                // Keep the theme's other tokens, which the app may rely on
+                let mut theme = theme.clone();
+                for (name, value) in Theme::high_contrast().tokens() {
+                    if name.starts_with("color-") {
+                        theme = theme.token(name, value);
+                    }
+                }
+                theme.to_css()
+            })
+        })
+        .unwrap_or_default();
+
+    if preferences.reduced_motion {
+        css.push_str(REDUCED_MOTION_CSS);
+    }
+    if high_contrast {
+        css.push_str(HIGH_CONTRAST_CSS);
+    }
+    (!css.is_empty()).then_some(css)
+}
+
+/// Turns off transitions and animations.
+const REDUCED_MOTION_CSS: &str = "\n*, *::before, *::after { transition: none !important; animation: none !important; }\n";
+
+/// Makes focus rings easier to see.
+const HIGH_CONTRAST_CSS: &str = "\n[data-rinch-focus-visible] { outline-width: 3px !important; }\n";
+
+/// The OS accessibility settings the windows follow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SystemPreferences {
+    reduced_motion: bool,
+    high_contrast: bool,
+}
+
+impl SystemPreferences {
+    /// Read the settings, which can be slow (it runs `gsettings` on Linux).
+    fn read() -> Self {
+        Self {
+            reduced_motion: system_reduced_motion(),
+            high_contrast: system_high_contrast(),
+        }
+    }
+}
+
+thread_local! {
+    /// The OS accessibility settings as last read, all off until the first
+    /// reading arrives.
+    static PREFERENCES: Cell<SystemPreferences> = const {
+        Cell::new(SystemPreferences { reduced_motion: false, high_contrast: false })
+    };
+    /// Whether the settings are being read on a background thread.
+    static READING: Cell<bool> = const { Cell::new(false) };
+    /// Whether the OS asks for reduced motion, created on first access.
+    static REDUCED_MOTION: RefCell<Option<Signal<bool>>> = const { RefCell::new(None) };
+    /// Whether the OS is in high contrast mode, created on first access.
+    static HIGH_CONTRAST: RefCell<Option<Signal<bool>>> = const { RefCell::new(None) };
+}
+
+/// Get a signal that is `true` while the user has asked the OS to reduce
+/// motion (turned off animations).
+///
+/// Windows honor it by turning off CSS transitions and animations; use it to
+/// skip animations driven from code, such as scrolling into view. The setting
+/// is read in the background, so the signal is `false` until shortly after
+/// the app starts, and then updated like [`use_accent_color`]. Every call
+/// returns the same signal.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::theme::use_prefers_reduced_motion;
+///
+/// let reduced_motion = use_prefers_reduced_motion();
+/// let duration = if reduced_motion.get() { 0 } else { 250 };
+/// ```
+pub fn use_prefers_reduced_motion() -> Signal<bool> {
+    preference_signal(&REDUCED_MOTION, |preferences| preferences.reduced_motion)
+}
+
+/// Get a signal that is `true` while the OS is in high contrast mode.
+///
+/// Windows honor it by using the colors of [`Theme::high_contrast`] for the
+/// active theme and drawing thicker focus rings; use it to adjust colors set
+/// outside the theme. Read and updated like [`use_prefers_reduced_motion`].
+/// Every call returns the same signal.
+pub fn use_high_contrast() -> Signal<bool> {
+    preference_signal(&HIGH_CONTRAST, |preferences| preferences.high_contrast)
+}
+
+/// The signal of an OS setting, holding the value `get` picks from the last
+/// reading.
+fn preference_signal(
+    key: &'static LocalKey<RefCell<Option<Signal<bool>>>>,
+    get: fn(SystemPreferences) -> bool,
+) -> Signal<bool> {
+    if let Some(signal) = key.with(|s| s.borrow().clone()) {
+        return signal;
+    }
+
+    let signal = Signal::new(get(PREFERENCES.with(Cell::get)));
+    key.with(|s| *s.borrow_mut() = Some(signal.clone()));
+    signal
+}
+
+/// Re-read the OS settings (accent color, reduced motion and high contrast),
+/// updating their signals and restyling windows if they changed.
+///
+/// The accessibility settings are read on a background thread, as reading
+/// them can take a while; nothing happens if a reading is already underway.
+pub(crate) fn refresh_system_preferences() {
+    refresh_accent_color();
+    if READING.with(|reading| reading.replace(true)) {
+        return;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("rinch-preferences".into())
+        .spawn(|| {
+            let preferences = SystemPreferences::read();
+            crate::tasks::app_handle().run_on_ui_thread(move || update_preferences(preferences));
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to read the accessibility settings: {}", e);
+        READING.with(|reading| reading.set(false));
+    }
+}
+
+/// Store a reading of the OS settings, restyling windows if it changed.
+fn update_preferences(preferences: SystemPreferences) {
+    READING.with(|reading| reading.set(false));
+    if PREFERENCES.with(|p| p.replace(preferences)) == preferences {
+        return;
+    }
+    for (key, value) in [
+        (&REDUCED_MOTION, preferences.reduced_motion),
+        (&HIGH_CONTRAST, preferences.high_contrast),
+    ] {
+        if let Some(signal) = key.with(|s| s.borrow().clone())
+            && signal.get() != value
+        {
+            signal.set(value);
+        }
+    }
+    crate::windows::send_event(RinchEvent::ThemeChanged);
+}
+
+/// An sRGB color chosen by the user in the OS settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AccentColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl AccentColor {
+    /// Used where the OS doesn't expose an accent color.
+    pub const FALLBACK: AccentColor = AccentColor::rgb(0x00, 0x66, 0xcc);
+
+    /// Create a color from its components.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// The color as a CSS hex color, e.g. `#0066cc`.
+    pub fn to_css(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl std::fmt::Display for AccentColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_css())
+    }
+}
+
+thread_local! {
+    /// The OS accent color, read on first access.
+    static ACCENT_COLOR: RefCell<Option<Signal<AccentColor>>> = const { RefCell::new(None) };
+}
+
+/// Get a signal holding the OS accent (highlight) color.
+///
+/// The signal is updated when the user changes the color, which is checked
+/// whenever a window regains focus or the OS theme changes. On platforms
+/// without an accent color setting (currently Linux), it holds
+/// [`AccentColor::FALLBACK`]. Every call returns the same signal.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+/// use rinch::theme::use_accent_color;
+///
+/// fn app() -> Element {
+///     let accent = use_accent_color();
+///
+///     rsx! {
+///         Window { title: "Native look",
+///             button { style: format!("background: {}", accent.get()), "OK" }
+///         }
+///     }
+/// }
+/// ```
+pub fn use_accent_color() -> Signal<AccentColor> {
+    if let Some(signal) = ACCENT_COLOR.with(|a| a.borrow().clone()) {
+        return signal;
+    }
+
+    let color = system_accent_color().unwrap_or(AccentColor::FALLBACK);
+    let signal = Signal::new(color);
+    ACCENT_COLOR.with(|a| *a.borrow_mut() = Some(signal.clone()));
+    signal
+}
+
+/// Re-read the OS accent color, updating the signal if it changed.
+fn refresh_accent_color() {
+    let Some(signal) = ACCENT_COLOR.with(|a| a.borrow().clone()) else {
+        return;
+    };
+    let color = system_accent_color().unwrap_or(AccentColor::FALLBACK);
+    if signal.get() != color {
+        tracing::debug!("Accent color changed to {}", color);
+        signal.set(color);
+    }
+}
+
+/// Read the accent color from `HKCU\Software\Microsoft\Windows\DWM`.
+#[cfg(target_os = "windows")]
+fn system_accent_color() -> Option<AccentColor> {
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let key: Vec<u16> = "Software\\Microsoft\\Windows\\DWM\0".encode_utf16().collect();
+    let value: Vec<u16> = "AccentColor\0".encode_utf16().collect();
+    let mut data: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+
+    // SAFETY: the strings are NUL-terminated and `data` is a DWORD of `size` bytes.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            (&mut data as *mut u32).cast(),
+            &mut size,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+
+    // Stored as 0xAABBGGRR
+    let [r, g, b, _] = data.to_le_bytes();
+    Some(AccentColor::rgb(r, g, b))
+}
+
+/// Read `NSColor.controlAccentColor`, converted to sRGB.
+#[cfg(target_os = "macos")]
+fn system_accent_color() -> Option<AccentColor> {
+    use objc2_app_kit::{NSColor, NSColorSpace};
+
+    let color = NSColor::controlAccentColor().colorUsingColorSpace(&NSColorSpace::sRGBColorSpace())?;
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Some(AccentColor::rgb(
+        channel(color.redComponent()),
+        channel(color.greenComponent()),
+        channel(color.blueComponent()),
+    ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_accent_color() -> Option<AccentColor> {
+    None
+}
+
+/// Whether "Animation effects" are turned off in the Windows settings.
+#[cfg(target_os = "windows")]
+fn system_reduced_motion() -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION};
+
+    let mut animations = 1;
+    // SAFETY: SPI_GETCLIENTAREAANIMATION writes a BOOL to the pointer.
+    let ok = unsafe {
+        SystemParametersInfoW(SPI_GETCLIENTAREAANIMATION, 0, (&mut animations as *mut i32).cast(), 0)
+    };
+    ok != 0 && animations == 0
+}
+
+#[cfg(target_os = "windows")]
+fn system_high_contrast() -> bool {
+    use windows_sys::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETHIGHCONTRAST};
+
+    let mut high_contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        dwFlags: 0,
+        lpszDefaultScheme: std::ptr::null_mut(),
+    };
+    // SAFETY: SPI_GETHIGHCONTRAST fills a HIGHCONTRASTW of `cbSize` bytes.
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            high_contrast.cbSize,
+            (&mut high_contrast as *mut HIGHCONTRASTW).cast(),
+            0,
+        )
+    };
+    ok != 0 && high_contrast.dwFlags & HCF_HIGHCONTRASTON != 0
+}
+
+#[cfg(target_os = "macos")]
+fn system_reduced_motion() -> bool {
+    objc2_app_kit::NSWorkspace::sharedWorkspace().accessibilityDisplayShouldReduceMotion()
+}
+
+#[cfg(target_os = "macos")]
+fn system_high_contrast() -> bool {
+    objc2_app_kit::NSWorkspace::sharedWorkspace().accessibilityDisplayShouldIncreaseContrast()
+}
+
+/// Whether animations are turned off in the GNOME settings.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_reduced_motion() -> bool {
+    gsetting("org.gnome.desktop.interface", "enable-animations").as_deref() == Some("false")
+}
+
+/// Whether high contrast is turned on in the GNOME settings.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_high_contrast() -> bool {
+    gsetting("org.gnome.desktop.a11y.interface", "high-contrast").as_deref() == Some("true")
+}
+
+/// Read a value with `gsettings`, which fails where GNOME isn't installed.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn gsetting(schema: &str, key: &str) -> Option<String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", schema, key])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_preferences(reduced_motion: bool, high_contrast: bool) {
+        PREFERENCES.with(|p| p.set(SystemPreferences { reduced_motion, high_contrast }));
+    }
+
+    #[test]
+    fn to_css_lists_tokens_on_root() {
+        let theme = Theme::new("custom").color("primary", "red").spacing("md", "16px").token("font-body", "serif");
+        assert_eq!(
+            theme.to_css(),
+            ":root {\n  --color-primary: red;\n  --font-body: serif;\n  --spacing-md: 16px;\n}\n"
+        );
+        assert_eq!(Theme::new("empty").to_css(), ":root {\n}\n");
+    }
+
+    #[test]
+    fn theme_css_is_empty_until_themes_are_used() {
+        assert_eq!(theme_css(), None);
+        set_theme(Theme::dark());
+        assert_eq!(theme_css(), Some(Theme::dark().to_css()));
+    }
+
+    #[test]
+    fn high_contrast_replaces_only_colors() {
+        set_theme(Theme::dark().color("accent", "#ff00ff").token("font-body", "serif"));
+        set_preferences(false, true);
+        let css = theme_css().unwrap();
+
+        assert!(css.contains("--color-background: #000000;"));
+        assert!(css.contains("--color-primary: #ffff00;"));
+        // Colors the high contrast theme doesn't set, and other tokens, stay
+        assert!(css.contains("--color-accent: #ff00ff;"));
+        assert!(css.contains("--font-body: serif;"));
+        assert!(css.contains("--spacing-md: 16px;"));
+        assert!(!css.contains("#1e1e1e"));
+        assert!(css.ends_with(HIGH_CONTRAST_CSS));
+        assert!(!css.contains(REDUCED_MOTION_CSS));
+    }
+
+    #[test]
+    fn preferences_apply_without_a_theme() {
+        set_preferences(true, false);
+        assert_eq!(theme_css().as_deref(), Some(REDUCED_MOTION_CSS));
+
+        set_preferences(true, true);
+        let css = theme_css().unwrap();
+        assert_eq!(css, format!("{}{}", REDUCED_MOTION_CSS, HIGH_CONTRAST_CSS));
+        assert!(css.contains("transition: none !important"));
+    }
+
+    #[test]
+    fn reduced_motion_follows_the_theme() {
+        set_theme(Theme::light());
+        set_preferences(true, false);
+        assert_eq!(theme_css(), Some(format!("{}{}", Theme::light().to_css(), REDUCED_MOTION_CSS)));
+    }
+}
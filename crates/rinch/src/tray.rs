@@ -22,9 +22,56 @@
 //!     .build()
 //!     .unwrap();
 //! ```
+//!
+//! The icon, tooltip and menu can be changed while the app runs:
+//!
+//! ```ignore
+//! tray.set_tooltip(Some("My App - 3 unread"))?;
+//! tray.set_icon_path("assets/tray-unread.png", None)?;
+//! tray.set_menu(Some(TrayMenu::new().add_item(TrayMenuItem::new("Mark All Read"))))?;
+//! ```
+//!
+//! A tray icon can also be declared in the element tree with the `Tray`
+//! component. The runtime builds it at startup, updates it whenever the app
+//! re-renders, and invokes `onclick` callbacks like those of application
+//! menu items:
+//!
+//! ```ignore
+//! rsx! {
+//!     Fragment {
+//!         Tray { tooltip: "My App", icon: "assets/tray.png",
+//!             onclick: |click: &TrayClick| {
+//!                 if click.button == TrayButton::Left {
+//!                     rinch::windows::show_windows();
+//!                 }
+//!             },
+//!             MenuItem { label: "Show Window", onclick: || println!("Show") }
+//!             MenuSeparator {}
+//!             Menu { label: "Status",
+//!                 MenuItem { label: "Online", checked: true }
+//!             }
+//!             MenuSeparator {}
+//!             MenuItem { label: "Quit", onclick: || std::process::exit(0) }
+//!         }
+//!         Window { title: "My App", div { "..." } }
+//!     }
+//! }
+//! ```
 
-use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
-use tray_icon::{Icon, TrayIcon as TrayIconInner, TrayIconBuilder as TrayIconBuilderInner};
+use crate::menu::MenuCallback;
+use crate::shell::RinchEvent;
+use rinch_core::element::{
+    Element, MenuActivation, MenuItemCallback, TrayButton, TrayClick, TrayClickCallback,
+    TrayProps,
+};
+use rinch_core::event::Modifiers;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{
+    Icon, MouseButton, MouseButtonState, TrayIcon as TrayIconInner,
+    TrayIconBuilder as TrayIconBuilderInner, TrayIconEvent, TrayIconId,
+};
 
 /// Error type for tray operations.
 #[derive(Debug)]
@@ -70,10 +117,158 @@ impl From<tray_icon::BadIcon> for TrayError {
 /// Result type for tray operations.
 pub type TrayResult<T> = Result<T, TrayError>;
 
+/// Click callbacks registered for a tray icon.
+#[derive(Clone, Default)]
+struct ClickHandlers {
+    onclick: Option<TrayClickCallback>,
+    ondoubleclick: Option<TrayClickCallback>,
+}
+
+thread_local! {
+    /// Click callbacks for live tray icons, keyed by icon id.
+    static CLICK_HANDLERS: RefCell<HashMap<TrayIconId, ClickHandlers>> = RefCell::new(HashMap::new());
+}
+
+impl ClickHandlers {
+    fn from_props(props: &TrayProps) -> Self {
+        Self {
+            onclick: props.onclick.clone(),
+            ondoubleclick: props.ondoubleclick.clone(),
+        }
+    }
+}
+
+fn register_click_handlers(id: &TrayIconId, handlers: ClickHandlers) {
+    if handlers.onclick.is_some() || handlers.ondoubleclick.is_some() {
+        CLICK_HANDLERS.with(|h| h.borrow_mut().insert(id.clone(), handlers));
+    }
+}
+
+fn unregister_click_handlers(id: &TrayIconId) {
+    CLICK_HANDLERS.with(|h| h.borrow_mut().remove(id));
+}
+
+thread_local! {
+    /// Menu item callbacks for live tray icons, keyed by menu item id.
+    static MENU_CALLBACKS: RefCell<HashMap<tray_icon::menu::MenuId, MenuCallback>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Register menu item callbacks, returning their ids for later removal.
+fn register_menu_callbacks(
+    callbacks: HashMap<tray_icon::menu::MenuId, MenuCallback>,
+) -> Vec<tray_icon::menu::MenuId> {
+    let ids = callbacks.keys().cloned().collect();
+    MENU_CALLBACKS.with(|c| c.borrow_mut().extend(callbacks));
+    ids
+}
+
+/// Register the items of a built [`TrayMenu`].
+fn register_builder_items(
+    items: Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>,
+) -> Vec<tray_icon::menu::MenuId> {
+    let callbacks = items
+        .into_iter()
+        .map(|(id, label, callback)| {
+            let callback = callback.map(MenuItemCallback::new);
+            (id, MenuCallback { label, id: None, callback })
+        })
+        .collect();
+    register_menu_callbacks(callbacks)
+}
+
+fn unregister_menu_callbacks(ids: &[tray_icon::menu::MenuId]) {
+    MENU_CALLBACKS.with(|c| {
+        let mut callbacks = c.borrow_mut();
+        for id in ids {
+            callbacks.remove(id);
+        }
+    });
+}
+
+/// Poll for tray menu events and invoke the callbacks of all live tray icons.
+///
+/// Called by the runtime in `about_to_wait`. Returns `true` if a callback
+/// was invoked.
+pub(crate) fn poll_menu_events(modifiers: Modifiers) -> bool {
+    let mut invoked = false;
+    while let Ok(event) = MenuEvent::receiver().try_recv() {
+        // Clone the callback so it can rebuild tray menus
+        let Some(stored) = MENU_CALLBACKS.with(|c| c.borrow().get(event.id()).cloned()) else {
+            continue;
+        };
+
+        tracing::info!("Tray menu item activated: {}", stored.label);
+        if let Some(cb) = &stored.callback {
+            cb.invoke(&MenuActivation {
+                via_shortcut: false,
+                modifiers,
+                item_id: stored.id.clone(),
+            });
+            invoked = true;
+        }
+    }
+    invoked
+}
+
+/// Forward tray icon events to the event loop as [`RinchEvent::TrayIcon`].
+pub(crate) fn install_event_handler(proxy: winit::event_loop::EventLoopProxy<RinchEvent>) {
+    TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+        let _ = proxy.send_event(RinchEvent::TrayIcon(event));
+    }));
+}
+
+/// Invoke the click callbacks registered for the icon an event came from.
+///
+/// Returns `true` if a callback was invoked.
+pub(crate) fn handle_icon_event(event: &TrayIconEvent) -> bool {
+    let (id, click) = match event {
+        TrayIconEvent::Click {
+            id,
+            button,
+            button_state: MouseButtonState::Up,
+            ..
+        } => (id, TrayClick { button: convert_button(*button), double_click: false }),
+        TrayIconEvent::DoubleClick { id, button, .. } => {
+            (id, TrayClick { button: convert_button(*button), double_click: true })
+        }
+        _ => return false,
+    };
+
+    // Clone the callback so it can create or drop tray icons
+    let Some(handlers) = CLICK_HANDLERS.with(|h| h.borrow().get(id).cloned()) else {
+        return false;
+    };
+
+    let callback = if click.double_click {
+        handlers.ondoubleclick
+    } else {
+        handlers.onclick
+    };
+
+    match callback {
+        Some(cb) => {
+            cb.invoke(&click);
+            true
+        }
+        None => false,
+    }
+}
+
+fn convert_button(button: MouseButton) -> TrayButton {
+    match button {
+        MouseButton::Left => TrayButton::Left,
+        MouseButton::Right => TrayButton::Right,
+        MouseButton::Middle => TrayButton::Middle,
+    }
+}
+
 /// A system tray icon with optional menu.
+///
+/// The icon is removed from the tray when dropped.
 pub struct TrayIcon {
-    _inner: TrayIconInner,
-    menu_items: Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>,
+    inner: TrayIconInner,
+    menu_ids: Vec<tray_icon::menu::MenuId>,
 }
 
 /// Callback type for tray menu items.
@@ -82,18 +277,59 @@ pub type TrayCallback = Box<dyn Fn() + Send + Sync>;
 impl TrayIcon {
     /// Poll for tray menu events and invoke callbacks.
     ///
-    /// Call this periodically (e.g., in your event loop's about_to_wait).
+    /// The rinch runtime polls all tray icons automatically, so this is only
+    /// needed when driving an event loop yourself.
     pub fn poll_events(&self) {
-        while let Ok(event) = MenuEvent::receiver().try_recv() {
-            for (id, label, callback) in &self.menu_items {
-                if event.id() == id {
-                    tracing::info!("Tray menu item activated: {}", label);
-                    if let Some(cb) = callback {
-                        cb();
-                    }
-                }
+        poll_menu_events(Modifiers::default());
+    }
+
+    /// Set the tooltip text shown on hover, or remove it with `None`.
+    pub fn set_tooltip(&self, tooltip: Option<&str>) -> TrayResult<()> {
+        self.inner.set_tooltip(tooltip)?;
+        Ok(())
+    }
+
+    /// Replace the icon with RGBA pixel data (4 bytes per pixel).
+    ///
+    /// Useful for drawing badges such as an unread count.
+    pub fn set_icon_rgba(&self, rgba: Vec<u8>, width: u32, height: u32) -> TrayResult<()> {
+        self.inner.set_icon(Some(Icon::from_rgba(rgba, width, height)?))?;
+        Ok(())
+    }
+
+    /// Replace the icon with an image file (PNG, ICO, etc.).
+    pub fn set_icon_path(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        size: Option<(u32, u32)>,
+    ) -> TrayResult<()> {
+        self.inner.set_icon(Some(Icon::from_path(path, size)?))?;
+        Ok(())
+    }
+
+    /// Replace the tray menu, or remove it with `None`.
+    pub fn set_menu(&mut self, menu: Option<TrayMenu>) -> TrayResult<()> {
+        match menu {
+            Some(tray_menu) => {
+                let (menu, items) = tray_menu.build()?;
+                self.inner.set_menu(Some(Box::new(menu)));
+                unregister_menu_callbacks(&self.menu_ids);
+                self.menu_ids = register_builder_items(items);
+            }
+            None => {
+                self.inner.set_menu(None);
+                unregister_menu_callbacks(&self.menu_ids);
+                self.menu_ids.clear();
             }
         }
+        Ok(())
+    }
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        unregister_click_handlers(self.inner.id());
+        unregister_menu_callbacks(&self.menu_ids);
     }
 }
 
@@ -102,6 +338,7 @@ pub struct TrayIconBuilder {
     tooltip: Option<String>,
     icon: Option<Icon>,
     menu: Option<TrayMenu>,
+    click_handlers: ClickHandlers,
 }
 
 impl TrayIconBuilder {
@@ -111,6 +348,7 @@ impl TrayIconBuilder {
             tooltip: None,
             icon: None,
             menu: None,
+            click_handlers: ClickHandlers::default(),
         }
     }
 
@@ -150,6 +388,34 @@ impl TrayIconBuilder {
         self
     }
 
+    /// Set the callback to invoke when the icon is clicked.
+    ///
+    /// Click events are delivered while the rinch runtime is running. They
+    /// are not reported on Linux.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let tray = TrayIconBuilder::new()
+    ///     .with_tooltip("My App")
+    ///     .on_click(|click| {
+    ///         if click.button == TrayButton::Left {
+    ///             rinch::windows::show_windows();
+    ///         }
+    ///     })
+    ///     .build()?;
+    /// ```
+    pub fn on_click<F: Fn(&TrayClick) + 'static>(mut self, callback: F) -> Self {
+        self.click_handlers.onclick = Some(TrayClickCallback::with_click(callback));
+        self
+    }
+
+    /// Set the callback to invoke when the icon is double-clicked.
+    pub fn on_double_click<F: Fn(&TrayClick) + 'static>(mut self, callback: F) -> Self {
+        self.click_handlers.ondoubleclick = Some(TrayClickCallback::with_click(callback));
+        self
+    }
+
     /// Build the tray icon.
     pub fn build(self) -> TrayResult<TrayIcon> {
         let mut builder = TrayIconBuilderInner::new();
@@ -171,10 +437,11 @@ impl TrayIconBuilder {
         };
 
         let inner = builder.build()?;
+        register_click_handlers(inner.id(), self.click_handlers);
 
         Ok(TrayIcon {
-            _inner: inner,
-            menu_items,
+            inner,
+            menu_ids: register_builder_items(menu_items),
         })
     }
 }
@@ -333,3 +600,238 @@ impl TrayMenuItem {
         self
     }
 }
+
+/// A tray icon declared with the `Tray` element.
+pub(crate) struct ElementTray {
+    inner: TrayIconInner,
+    /// The native items of the menu, in element order.
+    entries: Vec<TrayEntry>,
+    menu_ids: Vec<tray_icon::menu::MenuId>,
+    tooltip: Option<String>,
+    icon: Option<std::path::PathBuf>,
+}
+
+/// A native item of a `Tray` element's menu, kept to update it in place.
+enum TrayEntry {
+    Item(MenuItem),
+    Check(CheckMenuItem),
+    Separator,
+    Submenu(Submenu, Vec<TrayEntry>),
+}
+
+impl ElementTray {
+    /// Build a tray icon from a `Tray` element.
+    pub(crate) fn build(props: &TrayProps, children: &[Element]) -> TrayResult<Self> {
+        let mut builder = TrayIconBuilderInner::new();
+
+        if let Some(tooltip) = &props.tooltip {
+            builder = builder.with_tooltip(tooltip);
+        }
+
+        if let Some(icon) = &props.icon {
+            builder = builder.with_icon(Icon::from_path(icon, None)?);
+        }
+
+        let mut callbacks = HashMap::new();
+        let (menu, entries) = build_element_menu(children, &mut callbacks)?;
+        if let Some(menu) = menu {
+            builder = builder.with_menu(Box::new(menu));
+        }
+
+        let inner = builder.build()?;
+        register_click_handlers(inner.id(), ClickHandlers::from_props(props));
+
+        Ok(Self {
+            inner,
+            entries,
+            menu_ids: register_menu_callbacks(callbacks),
+            tooltip: props.tooltip.clone(),
+            icon: props.icon.clone(),
+        })
+    }
+
+    /// Update the tray icon from a re-rendered `Tray` element.
+    ///
+    /// The tooltip and icon are only touched when they change. While the
+    /// menu keeps its items, submenus and separators in the same order, the
+    /// labels, enabled and checked states of its items are updated in place;
+    /// otherwise it is rebuilt.
+    pub(crate) fn update(&mut self, props: &TrayProps, children: &[Element]) -> TrayResult<()> {
+        if self.tooltip != props.tooltip {
+            self.inner.set_tooltip(props.tooltip.as_deref())?;
+            self.tooltip = props.tooltip.clone();
+        }
+
+        if self.icon != props.icon {
+            let icon = match &props.icon {
+                Some(path) => Some(Icon::from_path(path, None)?),
+                None => None,
+            };
+            self.inner.set_icon(icon)?;
+            self.icon = props.icon.clone();
+        }
+
+        let mut callbacks = HashMap::new();
+        if same_shape(&self.entries, children) {
+            update_entries(&self.entries, children, &mut callbacks);
+        } else {
+            let (menu, entries) = build_element_menu(children, &mut callbacks)?;
+            self.inner
+                .set_menu(menu.map(|m| Box::new(m) as Box<dyn tray_icon::menu::ContextMenu>));
+            self.entries = entries;
+        }
+        unregister_menu_callbacks(&self.menu_ids);
+        self.menu_ids = register_menu_callbacks(callbacks);
+
+        unregister_click_handlers(self.inner.id());
+        register_click_handlers(self.inner.id(), ClickHandlers::from_props(props));
+
+        Ok(())
+    }
+}
+
+impl Drop for ElementTray {
+    fn drop(&mut self) {
+        unregister_click_handlers(self.inner.id());
+        unregister_menu_callbacks(&self.menu_ids);
+    }
+}
+
+/// The children of a `Tray` or `Menu` element that are menu entries.
+fn menu_entries(children: &[Element]) -> impl Iterator<Item = &Element> {
+    children
+        .iter()
+        .filter(|child| matches!(child, Element::MenuItem(_) | Element::MenuSeparator | Element::Menu(_, _)))
+}
+
+/// Whether `children` have the same items, separators and submenus as the
+/// built `entries`, in the same order.
+fn same_shape(entries: &[TrayEntry], children: &[Element]) -> bool {
+    let children: Vec<&Element> = menu_entries(children).collect();
+    entries.len() == children.len()
+        && entries.iter().zip(children).all(|(entry, child)| match (entry, child) {
+            (TrayEntry::Item(_), Element::MenuItem(props)) => props.checked.is_none(),
+            (TrayEntry::Check(_), Element::MenuItem(props)) => props.checked.is_some(),
+            (TrayEntry::Separator, Element::MenuSeparator) => true,
+            (TrayEntry::Submenu(_, entries), Element::Menu(_, children)) => same_shape(entries, children),
+            _ => false,
+        })
+}
+
+/// Update the built `entries` in place from `children` of the same shape.
+fn update_entries(
+    entries: &[TrayEntry],
+    children: &[Element],
+    callbacks: &mut HashMap<tray_icon::menu::MenuId, MenuCallback>,
+) {
+    for (entry, child) in entries.iter().zip(menu_entries(children)) {
+        match (entry, child) {
+            (TrayEntry::Item(item), Element::MenuItem(props)) => {
+                if item.text() != props.label {
+                    item.set_text(&props.label);
+                }
+                if item.is_enabled() != props.enabled {
+                    item.set_enabled(props.enabled);
+                }
+                insert_callback(item.id(), props, callbacks);
+            }
+            (TrayEntry::Check(item), Element::MenuItem(props)) => {
+                if item.text() != props.label {
+                    item.set_text(&props.label);
+                }
+                if item.is_enabled() != props.enabled {
+                    item.set_enabled(props.enabled);
+                }
+                // Clicking toggles the native item, so compare with it
+                // rather than with the last render
+                if let Some(checked) = props.checked
+                    && item.is_checked() != checked
+                {
+                    item.set_checked(checked);
+                }
+                insert_callback(item.id(), props, callbacks);
+            }
+            (TrayEntry::Submenu(submenu, entries), Element::Menu(props, children)) => {
+                if submenu.text() != props.label {
+                    submenu.set_text(&props.label);
+                }
+                update_entries(entries, children, callbacks);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Build the tray context menu from a `Tray` element's children, with its
+/// native items.
+///
+/// Returns no menu if the tray has no menu entries.
+fn build_element_menu(
+    children: &[Element],
+    callbacks: &mut HashMap<tray_icon::menu::MenuId, MenuCallback>,
+) -> TrayResult<(Option<Menu>, Vec<TrayEntry>)> {
+    if menu_entries(children).next().is_none() {
+        return Ok((None, Vec::new()));
+    }
+
+    let menu = Menu::new();
+    let entries = build_entries(children, &mut |item| menu.append(item), callbacks)?;
+    Ok((Some(menu), entries))
+}
+
+/// Build the native items of a `Tray` or `Menu` element's children, adding
+/// each with `append`.
+fn build_entries(
+    children: &[Element],
+    append: &mut dyn FnMut(&dyn tray_icon::menu::IsMenuItem) -> tray_icon::menu::Result<()>,
+    callbacks: &mut HashMap<tray_icon::menu::MenuId, MenuCallback>,
+) -> TrayResult<Vec<TrayEntry>> {
+    let mut entries = Vec::new();
+    for child in menu_entries(children) {
+        let entry = match child {
+            Element::MenuItem(props) => match props.checked {
+                Some(checked) => {
+                    let item = CheckMenuItem::new(&props.label, props.enabled, checked, None);
+                    append(&item)?;
+                    insert_callback(item.id(), props, callbacks);
+                    TrayEntry::Check(item)
+                }
+                None => {
+                    let item = MenuItem::new(&props.label, props.enabled, None);
+                    append(&item)?;
+                    insert_callback(item.id(), props, callbacks);
+                    TrayEntry::Item(item)
+                }
+            },
+            Element::MenuSeparator => {
+                append(&PredefinedMenuItem::separator())?;
+                TrayEntry::Separator
+            }
+            Element::Menu(props, children) => {
+                let submenu = Submenu::new(&props.label, true);
+                let entries = build_entries(children, &mut |item| submenu.append(item), callbacks)?;
+                append(&submenu)?;
+                TrayEntry::Submenu(submenu, entries)
+            }
+            _ => continue,
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Route clicks on the native item `id` to a `MenuItem` element's callback.
+fn insert_callback(
+    id: &tray_icon::menu::MenuId,
+    props: &rinch_core::element::MenuItemProps,
+    callbacks: &mut HashMap<tray_icon::menu::MenuId, MenuCallback>,
+) {
+    callbacks.insert(
+        id.clone(),
+        MenuCallback {
+            label: props.label.clone(),
+            id: props.id.clone(),
+            callback: props.onclick.clone(),
+        },
+    );
+}
@@ -0,0 +1,136 @@
+//! Zoom and layout debugging, shared by all windows.
+//!
+//! The [`UiSettings`] are kept by the app rather than by each window:
+//! zooming with Ctrl/Cmd `+`, `-` and `0`, or turning on the layout debug
+//! overlay with Alt+D or from DevTools, changes every window, and windows
+//! opened later start out the same. The DevTools window keeps its own
+//! defaults.
+//!
+//! The settings are a signal, so the app can change them too, for example
+//! from a View menu, and re-renders when they change:
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::ui_settings::{self, use_ui_settings};
+//!
+//! fn app() -> Element {
+//!     let settings = use_ui_settings();
+//!
+//!     rsx! {
+//!         Window { title: "Editor",
+//!             p { {format!("Zoom: {:.0}%", settings.get().zoom * 100.0)} }
+//!             button { onclick: || ui_settings::zoom_by(0.1), "Zoom in" }
+//!             button { onclick: || ui_settings::reset_zoom(), "Actual size" }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+
+use rinch_core::{untracked, Effect, Signal};
+
+use crate::shell::RinchEvent;
+
+/// The smallest zoom factor.
+pub const MIN_ZOOM: f32 = 0.3;
+
+/// The largest zoom factor.
+pub const MAX_ZOOM: f32 = 5.0;
+
+/// Settings that apply to every window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiSettings {
+    /// How much content is enlarged, on top of the display's scale factor;
+    /// 1.0 is actual size. Kept between [`MIN_ZOOM`] and [`MAX_ZOOM`].
+    pub zoom: f32,
+    /// Whether the layout debug overlay outlines every box.
+    pub layout_debug: bool,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            layout_debug: false,
+        }
+    }
+}
+
+thread_local! {
+    /// The settings, created on first access.
+    static SETTINGS: RefCell<Option<Signal<UiSettings>>> = const { RefCell::new(None) };
+}
+
+/// Get the signal holding the settings.
+///
+/// Setting the signal updates every window. Every call returns the same
+/// signal.
+pub fn use_ui_settings() -> Signal<UiSettings> {
+    if let Some(signal) = SETTINGS.with(|s| s.borrow().clone()) {
+        return signal;
+    }
+
+    let signal = Signal::new(UiSettings::default());
+    SETTINGS.with(|s| *s.borrow_mut() = Some(signal.clone()));
+
+    // Update windows whenever the settings change
+    let watched = signal.clone();
+    let mut initial = true;
+    Effect::new(move || {
+        watched.with(|_| ());
+        if initial {
+            initial = false;
+            return;
+        }
+        crate::windows::send_event(RinchEvent::UiSettingsChanged);
+    });
+
+    signal
+}
+
+/// Change the settings, if `change` changes them.
+pub fn update_ui_settings(change: impl FnOnce(&mut UiSettings)) {
+    let signal = use_ui_settings();
+    let mut settings = untracked(|| signal.get());
+    change(&mut settings);
+    settings.zoom = settings.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    if untracked(|| signal.get()) != settings {
+        signal.set(settings);
+    }
+}
+
+/// Zoom every window to `zoom` (1.0 is actual size).
+pub fn set_zoom(zoom: f32) {
+    update_ui_settings(|settings| settings.zoom = zoom);
+}
+
+/// Zoom every window in (positive `delta`) or out (negative).
+pub fn zoom_by(delta: f32) {
+    // Round so repeated steps land on whole percentages
+    update_ui_settings(|settings| settings.zoom = ((settings.zoom + delta) * 100.0).round() / 100.0);
+}
+
+/// Zoom every window back to actual size.
+pub fn reset_zoom() {
+    set_zoom(1.0);
+}
+
+/// Turn the layout debug overlay on or off in every window.
+pub fn set_layout_debug(on: bool) {
+    update_ui_settings(|settings| settings.layout_debug = on);
+}
+
+/// Turn the layout debug overlay on if it's off, and off if it's on.
+pub fn toggle_layout_debug() {
+    update_ui_settings(|settings| settings.layout_debug = !settings.layout_debug);
+}
+
+/// The current settings, without creating the signal (called by windows
+/// as they open and rebuild their documents).
+pub(crate) fn current() -> UiSettings {
+    SETTINGS
+        .with(|s| s.borrow().clone())
+        .map(|signal| untracked(|| signal.get()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,510 @@
+//! Undo and redo.
+//!
+//! Every window has an [`UndoManager`]: the changes made in it, which
+//! [`undo`](UndoManager::undo) takes back one at a time, newest first, and
+//! [`redo`](UndoManager::redo) makes again. Recording a change drops the
+//! undone ones, which can't be redone any more.
+//!
+//! The window records what is typed into its text inputs and textareas,
+//! with typing in one input without a pause kept as one change; undoing it
+//! puts the input's text back and calls its `oninput`. Apps record their own
+//! changes, after making them, with [`push`](UndoManager::push) or
+//! [`push_fn`](UndoManager::push_fn). Cmd+Z (Ctrl+Z) undoes and Shift+Cmd+Z
+//! (Ctrl+Shift+Z) redoes in the window, as do menu items with
+//! `role: "undo"` and `role: "redo"`, which are enabled while there is
+//! something to undo or redo.
+//!
+//! ```ignore
+//! use rinch::undo::use_undo_manager;
+//!
+//! fn app() -> Element {
+//!     let shapes = use_signal(Vec::<Shape>::new);
+//!
+//!     let add_shapes = shapes.clone();
+//!     let add = move |shape: Shape| {
+//!         add_shapes.update(|shapes| shapes.push(shape.clone()));
+//!         let (undo_shapes, redo_shapes) = (add_shapes.clone(), add_shapes.clone());
+//!         use_undo_manager().push_fn(
+//!             "Add Shape",
+//!             move || undo_shapes.update(|shapes| { shapes.pop(); }),
+//!             move || redo_shapes.update(|shapes| shapes.push(shape.clone())),
+//!         );
+//!     };
+//!     // ...
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rinch_core::{untracked, Signal};
+
+use crate::windows::{PerWindow, WindowHandle};
+
+/// How many changes a manager keeps, unless set with
+/// [`set_limit`](UndoManager::set_limit).
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// The pause in typing that starts a new change.
+const TYPING_PAUSE: Duration = Duration::from_secs(1);
+
+/// The label of typing into a text input.
+const TYPING_LABEL: &str = "Typing";
+
+/// A change that can be undone and redone.
+///
+/// The change is made before it is recorded with
+/// [`UndoManager::push`], so the first call is to `undo`.
+pub trait UndoCommand {
+    /// Take the change back.
+    fn undo(&mut self);
+
+    /// Make the change again, after it was undone.
+    fn redo(&mut self);
+
+    /// A short description, such as `"Delete Shape"`, for menu items like
+    /// "Undo Delete Shape".
+    fn label(&self) -> String {
+        String::new()
+    }
+}
+
+/// An [`UndoCommand`] made of two functions.
+struct FnCommand<U, R> {
+    label: String,
+    undo: U,
+    redo: R,
+}
+
+impl<U: FnMut(), R: FnMut()> UndoCommand for FnCommand<U, R> {
+    fn undo(&mut self) {
+        (self.undo)()
+    }
+
+    fn redo(&mut self) {
+        (self.redo)()
+    }
+
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+}
+
+/// An edit of a text input, found by its path in the window's document.
+struct TextEdit {
+    window: WindowHandle,
+    path: Vec<usize>,
+    before: String,
+    after: String,
+    /// When the last edit folded into this one was made.
+    at: Instant,
+}
+
+impl TextEdit {
+    /// Show `text` in the input and tell its `oninput`.
+    fn restore(&self, text: &str) {
+        let (path, text, window) = (self.path.clone(), text.to_string(), self.window);
+        self.window.with_document(move |doc| {
+            crate::shell::window_manager::restore_input_text(doc, window, &path, &text);
+        });
+    }
+}
+
+enum Entry {
+    Text(TextEdit),
+    Command(Box<dyn UndoCommand>),
+}
+
+impl Entry {
+    fn undo(&mut self) {
+        match self {
+            Entry::Text(edit) => edit.restore(&edit.before),
+            Entry::Command(command) => command.undo(),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self {
+            Entry::Text(edit) => edit.restore(&edit.after),
+            Entry::Command(command) => command.redo(),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Entry::Text(_) => TYPING_LABEL.to_string(),
+            Entry::Command(command) => command.label(),
+        }
+    }
+}
+
+struct Stacks {
+    /// Changes that can be undone, oldest first.
+    done: Vec<Entry>,
+    /// Changes that can be redone, most recently undone last.
+    undone: Vec<Entry>,
+    limit: usize,
+    /// Whether the newest change is typing that more typing extends.
+    typing: bool,
+}
+
+/// The changes of a window that can be undone and redone.
+///
+/// Clones refer to the same changes.
+#[derive(Clone)]
+pub struct UndoManager {
+    stacks: Rc<RefCell<Stacks>>,
+    can_undo: Signal<bool>,
+    can_redo: Signal<bool>,
+    /// The labels of the changes to undo and redo next.
+    labels: Signal<(Option<String>, Option<String>)>,
+}
+
+impl UndoManager {
+    fn new() -> Self {
+        Self {
+            stacks: Rc::new(RefCell::new(Stacks {
+                done: Vec::new(),
+                undone: Vec::new(),
+                limit: DEFAULT_LIMIT,
+                typing: false,
+            })),
+            can_undo: Signal::new(false),
+            can_redo: Signal::new(false),
+            labels: Signal::new((None, None)),
+        }
+    }
+
+    /// Record a change the app just made.
+    pub fn push(&self, command: impl UndoCommand + 'static) {
+        self.record(Entry::Command(Box::new(command)));
+    }
+
+    /// Record a change the app just made, which `undo` takes back and
+    /// `redo` makes again.
+    pub fn push_fn(&self, label: impl Into<String>, undo: impl FnMut() + 'static, redo: impl FnMut() + 'static) {
+        self.push(FnCommand {
+            label: label.into(),
+            undo,
+            redo,
+        });
+    }
+
+    /// Undo the newest change. Returns `false` if there is none.
+    pub fn undo(&self) -> bool {
+        // Taken out while it runs, so the command can use the manager
+        let Some(mut entry) = self.stacks.borrow_mut().done.pop() else {
+            return false;
+        };
+        entry.undo();
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.undone.push(entry);
+        stacks.typing = false;
+        drop(stacks);
+        self.changed();
+        true
+    }
+
+    /// Redo the most recently undone change. Returns `false` if there is
+    /// none.
+    pub fn redo(&self) -> bool {
+        let Some(mut entry) = self.stacks.borrow_mut().undone.pop() else {
+            return false;
+        };
+        entry.redo();
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.done.push(entry);
+        stacks.typing = false;
+        drop(stacks);
+        self.changed();
+        true
+    }
+
+    /// A signal that is `true` while there is a change to undo.
+    pub fn can_undo(&self) -> Signal<bool> {
+        self.can_undo.clone()
+    }
+
+    /// A signal that is `true` while there is a change to redo.
+    pub fn can_redo(&self) -> Signal<bool> {
+        self.can_redo.clone()
+    }
+
+    /// The label of the change [`undo`](Self::undo) takes back, if any.
+    ///
+    /// Reading it while rendering re-renders when it changes.
+    pub fn undo_label(&self) -> Option<String> {
+        self.labels.with(|(undo, _)| undo.clone())
+    }
+
+    /// The label of the change [`redo`](Self::redo) makes again, if any.
+    ///
+    /// Reading it while rendering re-renders when it changes.
+    pub fn redo_label(&self) -> Option<String> {
+        self.labels.with(|(_, redo)| redo.clone())
+    }
+
+    /// Forget every change, such as after opening another document.
+    pub fn clear(&self) {
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.done.clear();
+        stacks.undone.clear();
+        stacks.typing = false;
+        drop(stacks);
+        self.changed();
+    }
+
+    /// Keep at most `limit` changes to undo, forgetting the oldest.
+    pub fn set_limit(&self, limit: usize) {
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.limit = limit;
+        let excess = stacks.done.len().saturating_sub(limit);
+        stacks.done.drain(..excess);
+        drop(stacks);
+        self.changed();
+    }
+
+    /// Record an edit of the text input at `path` in `window`'s document,
+    /// made at `now`, extending the newest change if it is typing in the
+    /// same input.
+    fn record_text_edit(&self, window: WindowHandle, path: Vec<usize>, before: String, after: String, now: Instant) {
+        {
+            let mut stacks = self.stacks.borrow_mut();
+            let typing = stacks.typing;
+            if let Some(Entry::Text(last)) = stacks.done.last_mut()
+                && typing
+                && last.path == path
+                && last.after == before
+                && now.duration_since(last.at) < TYPING_PAUSE
+            {
+                last.after = after;
+                last.at = now;
+                return;
+            }
+        }
+        self.record(Entry::Text(TextEdit {
+            window,
+            path,
+            before,
+            after,
+            at: now,
+        }));
+        self.stacks.borrow_mut().typing = true;
+    }
+
+    fn record(&self, entry: Entry) {
+        let mut stacks = self.stacks.borrow_mut();
+        stacks.undone.clear();
+        stacks.done.push(entry);
+        stacks.typing = false;
+        let excess = stacks.done.len().saturating_sub(stacks.limit);
+        stacks.done.drain(..excess);
+        drop(stacks);
+        self.changed();
+    }
+
+    /// Update the signals to the stacks, re-rendering if they changed.
+    fn changed(&self) {
+        let stacks = self.stacks.borrow();
+        let labels = (
+            stacks.done.last().map(Entry::label),
+            stacks.undone.last().map(Entry::label),
+        );
+        let can_undo = !stacks.done.is_empty();
+        let can_redo = !stacks.undone.is_empty();
+        drop(stacks);
+
+        let mut changed = false;
+        if untracked(|| self.can_undo.get()) != can_undo {
+            self.can_undo.set(can_undo);
+            changed = true;
+        }
+        if untracked(|| self.can_redo.get()) != can_redo {
+            self.can_redo.set(can_redo);
+            changed = true;
+        }
+        if untracked(|| self.labels.get()) != labels {
+            self.labels.set(labels);
+            changed = true;
+        }
+        if changed {
+            crate::shell::runtime::request_render();
+        }
+    }
+}
+
+impl std::fmt::Debug for UndoManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stacks = self.stacks.borrow();
+        f.debug_struct("UndoManager")
+            .field("undo", &stacks.done.iter().map(Entry::label).collect::<Vec<_>>())
+            .field("redo", &stacks.undone.iter().map(Entry::label).collect::<Vec<_>>())
+            .field("limit", &stacks.limit)
+            .finish()
+    }
+}
+
+thread_local! {
+    /// The undo managers of open windows.
+    static MANAGERS: PerWindow<UndoManager> = PerWindow::new(UndoManager::new);
+}
+
+/// Get the undo manager of a window.
+///
+/// Every call for the same window returns the same manager.
+pub fn undo_manager(window: WindowHandle) -> UndoManager {
+    MANAGERS.with(|managers| managers.get(window))
+}
+
+/// Get the undo manager of the window handling the current event, or of
+/// the app's main window (the oldest one open) while rendering.
+pub fn use_undo_manager() -> UndoManager {
+    MANAGERS.with(PerWindow::current)
+}
+
+/// Give a window that was just opened its undo manager (called by the
+/// runtime).
+pub(crate) fn attach(window: WindowHandle) {
+    MANAGERS.with(|managers| managers.attach(window));
+}
+
+/// Forget the undo manager of a closed window (called by the runtime).
+pub(crate) fn detach(window: WindowHandle) {
+    MANAGERS.with(|managers| managers.detach(window));
+}
+
+/// Record an edit typed into a text input of a window (called by the
+/// window).
+pub(crate) fn record_text_edit(window: WindowHandle, path: Vec<usize>, before: String, after: String) {
+    undo_manager(window).record_text_edit(window, path, before, after, Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A manager and a counter its commands add to and take from.
+    fn counter() -> (UndoManager, Rc<Cell<i32>>) {
+        (UndoManager::new(), Rc::new(Cell::new(0)))
+    }
+
+    /// Add `n` to the counter and record it.
+    fn add(manager: &UndoManager, count: &Rc<Cell<i32>>, n: i32) {
+        count.set(count.get() + n);
+        let (undo, redo) = (count.clone(), count.clone());
+        manager.push_fn(format!("Add {}", n), move || undo.set(undo.get() - n), move || redo.set(redo.get() + n));
+    }
+
+    /// The text edits to undo, as `(before, after)`.
+    fn text_edits(manager: &UndoManager) -> Vec<(String, String)> {
+        manager
+            .stacks
+            .borrow()
+            .done
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Text(edit) => Some((edit.before.clone(), edit.after.clone())),
+                Entry::Command(_) => None,
+            })
+            .collect()
+    }
+
+    fn edit(before: &str, after: &str) -> (String, String) {
+        (before.to_string(), after.to_string())
+    }
+
+    #[test]
+    fn undo_and_redo_newest_first() {
+        let (manager, count) = counter();
+        add(&manager, &count, 1);
+        add(&manager, &count, 10);
+        assert_eq!(untracked(|| manager.undo_label()).as_deref(), Some("Add 10"));
+
+        assert!(manager.undo());
+        assert_eq!(count.get(), 1);
+        assert!(manager.undo());
+        assert_eq!(count.get(), 0);
+        assert!(!manager.undo());
+        assert_eq!(untracked(|| manager.redo_label()).as_deref(), Some("Add 1"));
+
+        assert!(manager.redo());
+        assert_eq!(count.get(), 1);
+        assert!(untracked(|| manager.can_undo().get()));
+        assert!(untracked(|| manager.can_redo().get()));
+    }
+
+    #[test]
+    fn recording_a_change_clears_redo() {
+        let (manager, count) = counter();
+        add(&manager, &count, 1);
+        add(&manager, &count, 2);
+        manager.undo();
+        add(&manager, &count, 5);
+
+        assert!(!manager.redo());
+        assert!(!untracked(|| manager.can_redo().get()));
+        assert_eq!(count.get(), 6);
+    }
+
+    #[test]
+    fn limit_forgets_the_oldest_changes() {
+        let (manager, count) = counter();
+        manager.set_limit(2);
+        for n in [1, 10, 100] {
+            add(&manager, &count, n);
+        }
+        while manager.undo() {}
+        assert_eq!(count.get(), 1);
+
+        let (manager, count) = counter();
+        for n in [1, 10, 100] {
+            add(&manager, &count, n);
+        }
+        manager.set_limit(1);
+        while manager.undo() {}
+        assert_eq!(count.get(), 11);
+    }
+
+    #[test]
+    fn typing_without_a_pause_is_one_change() {
+        let manager = UndoManager::new();
+        let window = WindowHandle::new();
+        let start = Instant::now();
+        manager.record_text_edit(window, vec![0, 1], "".into(), "a".into(), start);
+        manager.record_text_edit(window, vec![0, 1], "a".into(), "ab".into(), start + TYPING_PAUSE / 2);
+        manager.record_text_edit(window, vec![0, 1], "ab".into(), "abc".into(), start + TYPING_PAUSE);
+        assert_eq!(text_edits(&manager), [edit("", "abc")]);
+        assert_eq!(untracked(|| manager.undo_label()).as_deref(), Some(TYPING_LABEL));
+    }
+
+    #[test]
+    fn typing_starts_a_new_change_after_a_pause_or_elsewhere() {
+        let manager = UndoManager::new();
+        let window = WindowHandle::new();
+        let start = Instant::now();
+        manager.record_text_edit(window, vec![0], "".into(), "a".into(), start);
+        // After a pause
+        manager.record_text_edit(window, vec![0], "a".into(), "ab".into(), start + TYPING_PAUSE * 2);
+        // In another input
+        manager.record_text_edit(window, vec![1], "".into(), "x".into(), start + TYPING_PAUSE * 2);
+        // Back in the first one, which the last change didn't touch
+        manager.record_text_edit(window, vec![0], "ab".into(), "abc".into(), start + TYPING_PAUSE * 2);
+        assert_eq!(
+            text_edits(&manager),
+            [edit("", "a"), edit("a", "ab"), edit("", "x"), edit("ab", "abc")]
+        );
+    }
+
+    #[test]
+    fn app_changes_end_typing() {
+        let (manager, count) = counter();
+        let window = WindowHandle::new();
+        let start = Instant::now();
+        manager.record_text_edit(window, vec![0], "".into(), "a".into(), start);
+        add(&manager, &count, 1);
+        manager.record_text_edit(window, vec![0], "a".into(), "ab".into(), start);
+        assert_eq!(text_edits(&manager), [edit("", "a"), edit("a", "ab")]);
+    }
+}
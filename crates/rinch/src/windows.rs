@@ -37,13 +37,19 @@
 //! }
 //! ```
 
-use rinch_core::element::WindowProps;
+use blitz_dom::BaseDocument;
+use rinch_core::element::{TextDirection, WindowColorScheme, WindowProps};
+use rinch_core::Signal;
+use std::any::Any;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 use winit::event_loop::EventLoopProxy;
-use winit::window::WindowId;
+use winit::window::{Window, WindowId};
 
+use crate::scroll::{ScrollPosition, ScrollState};
 use crate::shell::runtime::RinchEvent;
 
 /// A handle to an open window.
@@ -53,7 +59,7 @@ use crate::shell::runtime::RinchEvent;
 pub struct WindowHandle(u64);
 
 impl WindowHandle {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         static COUNTER: AtomicU64 = AtomicU64::new(1);
         Self(COUNTER.fetch_add(1, Ordering::SeqCst))
     }
@@ -62,6 +68,263 @@ impl WindowHandle {
     pub fn id(&self) -> u64 {
         self.0
     }
+
+    /// Add a stylesheet to this window.
+    ///
+    /// The stylesheet applies on top of the window's content and is kept
+    /// across re-renders. Returns an ID for replacing or removing it later.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rinch::windows::current_window;
+    ///
+    /// if let Some(window) = current_window() {
+    ///     let id = window.inject_stylesheet("body { font-size: 18px; }");
+    ///     // Later...
+    ///     window.replace_stylesheet(id, "body { font-size: 14px; }");
+    /// }
+    /// ```
+    pub fn inject_stylesheet(&self, css: impl Into<String>) -> StylesheetId {
+        let id = StylesheetId::new();
+        self.request_stylesheet(id, StylesheetChange::Set { css: css.into(), path: None });
+        id
+    }
+
+    /// Add a stylesheet loaded from a CSS file to this window.
+    ///
+    /// With hot reload enabled, saving the file updates the window's styles
+    /// in place, without re-rendering or losing UI state.
+    pub fn inject_stylesheet_file(&self, path: impl AsRef<Path>) -> std::io::Result<StylesheetId> {
+        let path = path.as_ref().canonicalize()?;
+        let css = std::fs::read_to_string(&path)?;
+        let id = StylesheetId::new();
+        self.request_stylesheet(id, StylesheetChange::Set { css, path: Some(path) });
+        Ok(id)
+    }
+
+    /// Replace the contents of a stylesheet added with [`inject_stylesheet`](Self::inject_stylesheet).
+    ///
+    /// The document's styles are updated in place, without rebuilding it.
+    pub fn replace_stylesheet(&self, id: StylesheetId, css: impl Into<String>) {
+        self.request_stylesheet(id, StylesheetChange::Set { css: css.into(), path: None });
+    }
+
+    /// Remove a stylesheet added with [`inject_stylesheet`](Self::inject_stylesheet).
+    pub fn remove_stylesheet(&self, id: StylesheetId) {
+        self.request_stylesheet(id, StylesheetChange::Remove);
+    }
+
+    /// Move keyboard focus to the element with the given `id` attribute.
+    ///
+    /// If the element doesn't exist yet, for example because the handler
+    /// that calls this also shows it, it is focused after the next render.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // rsx! { input { id: "search" } }
+    /// if let Some(window) = current_window() {
+    ///     window.focus_element("search");
+    /// }
+    /// ```
+    pub fn focus_element(&self, id: impl Into<String>) {
+        queue_request(WindowRequest::Focus(FocusRequest {
+            handle: *self,
+            element_id: id.into(),
+        }));
+    }
+
+    /// Scroll the element matching the CSS `selector` to `position`.
+    ///
+    /// Called from an event handler, this waits for the re-render the
+    /// handler causes, so the element's new content is taken into account.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rinch::scroll::ScrollPosition;
+    ///
+    /// // rsx! { div { id: "log", style: "overflow-y: auto", ... } }
+    /// if let Some(window) = current_window() {
+    ///     window.scroll_to("#log", ScrollPosition::Bottom);
+    /// }
+    /// ```
+    pub fn scroll_to(&self, selector: impl Into<String>, position: ScrollPosition) {
+        queue_request(WindowRequest::Scroll(ScrollRequest {
+            handle: *self,
+            selector: selector.into(),
+            target: ScrollTarget::Position(position),
+        }));
+    }
+
+    /// Scroll the containers around the element matching the CSS
+    /// `selector`, and the window itself, until the element shows.
+    pub fn scroll_into_view(&self, selector: impl Into<String>) {
+        queue_request(WindowRequest::Scroll(ScrollRequest {
+            handle: *self,
+            selector: selector.into(),
+            target: ScrollTarget::IntoView,
+        }));
+    }
+
+    /// Call `f` with this window's blitz document, to query nodes, read
+    /// computed styles or change attributes where rinch has no API for it.
+    ///
+    /// `f` runs on the UI thread once pending window requests are handled.
+    /// Called from an event handler, it waits for the re-render the handler
+    /// causes, so the document shows the handler's changes. The window is
+    /// redrawn afterwards.
+    ///
+    /// Re-rendering builds a new document from the app's elements, so
+    /// changes made here last until the window's content changes; keep
+    /// state in signals and use this for reading the layout or for effects
+    /// that are redone on every render.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if let Some(window) = current_window() {
+    ///     window.with_document(|doc| {
+    ///         if let Ok(Some(node_id)) = doc.query_selector("#chart") {
+    ///             let size = doc.get_node(node_id).unwrap().final_layout.size;
+    ///             println!("The chart is {}x{}", size.width, size.height);
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn with_document(&self, f: impl FnOnce(&mut BaseDocument) + 'static) {
+        queue_request(WindowRequest::Document(DocumentRequest {
+            handle: *self,
+            callback: Box::new(f),
+        }));
+    }
+
+    /// Draw this window at a HiDPI `scale`, such as `1.5`, instead of the
+    /// monitor's, or follow the monitor again with `None`.
+    ///
+    /// Unlike the `scale` prop of `Window`, this lasts across re-renders,
+    /// so a presentation mode or accessibility zoom can be turned on from
+    /// an event handler without threading it through the app's state.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if let Some(window) = current_window() {
+    ///     window.set_scale(Some(2.0));
+    /// }
+    /// ```
+    pub fn set_scale(&self, scale: Option<f32>) {
+        queue_request(WindowRequest::Scale(ScaleRequest { handle: *self, scale }));
+    }
+
+    /// Show `html` in the layer called `name` of this window's overlay,
+    /// replacing what it showed before.
+    ///
+    /// The overlay is drawn above the window's content with the same
+    /// stylesheets, for transient UI such as toasts. Showing, changing or
+    /// removing a layer lays out only the overlay, never the content, and
+    /// layers stay shown across re-renders until removed. The layer is
+    /// ordered like the layer of an element with `layer_order: 0`, and
+    /// pointer input goes through it to the content.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if let Some(window) = current_window() {
+    ///     window.set_overlay(
+    ///         "toast",
+    ///         r#"<div style="position: fixed; bottom: 16px; right: 16px">Saved</div>"#,
+    ///     );
+    /// }
+    /// ```
+    pub fn set_overlay(&self, name: &str, html: impl Into<String>) {
+        queue_request(WindowRequest::Overlay(OverlayRequest {
+            handle: *self,
+            name: name.to_string(),
+            html: Some(html.into()),
+        }));
+    }
+
+    /// Remove the layer called `name` from this window's overlay.
+    pub fn remove_overlay(&self, name: &str) {
+        queue_request(WindowRequest::Overlay(OverlayRequest {
+            handle: *self,
+            name: name.to_string(),
+            html: None,
+        }));
+    }
+
+    /// Get a signal that follows the scroll state of the element matching
+    /// the CSS `selector` in this window.
+    ///
+    /// See [`use_scroll_position`](crate::scroll::use_scroll_position).
+    pub fn scroll_position(&self, selector: &str) -> Signal<ScrollState> {
+        crate::scroll::watch(Some(*self), selector)
+    }
+
+    /// Get this window's back and forward history.
+    ///
+    /// See [`rinch::history`](crate::history).
+    pub fn history(&self) -> crate::history::History {
+        crate::history::history(*self)
+    }
+
+    /// Send `value` to this window, queued in the
+    /// [`WindowMessages`](crate::messages::WindowMessages) that
+    /// [`use_window_messages`](crate::messages::use_window_messages)
+    /// returns for it.
+    ///
+    /// The message arrives through the event loop, after the current event
+    /// handler returns. Messages to a closed window are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // In the settings window, with the main window's handle
+    /// main_window.post_message(SettingsSaved { font_size: 16 });
+    /// ```
+    pub fn post_message<T: Any + Send + Sync>(&self, value: T) {
+        crate::messages::post(*self, Arc::new(value));
+    }
+
+    /// Get the messages posted to this window.
+    ///
+    /// See [`rinch::messages`](crate::messages).
+    pub fn messages(&self) -> crate::messages::WindowMessages {
+        crate::messages::window_messages(*self)
+    }
+
+    /// Get this window's undo manager.
+    ///
+    /// See [`rinch::undo`](crate::undo).
+    pub fn undo_manager(&self) -> crate::undo::UndoManager {
+        crate::undo::undo_manager(*self)
+    }
+
+    fn request_stylesheet(&self, id: StylesheetId, change: StylesheetChange) {
+        queue_request(WindowRequest::Stylesheet(StylesheetRequest {
+            handle: *self,
+            id,
+            change,
+        }));
+    }
+}
+
+/// Identifies a stylesheet injected into a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StylesheetId(u64);
+
+impl StylesheetId {
+    fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(1);
+        Self(COUNTER.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Get the internal ID of this stylesheet.
+    pub fn id(&self) -> u64 {
+        self.0
+    }
 }
 
 /// A request to open a new window.
@@ -82,6 +345,97 @@ pub struct CloseWindowRequest {
     pub handle: WindowHandle,
 }
 
+/// A request to add, replace or remove a window's stylesheet.
+#[derive(Debug, Clone)]
+pub struct StylesheetRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// The stylesheet to change.
+    pub id: StylesheetId,
+    /// The change to make.
+    pub change: StylesheetChange,
+}
+
+/// A request to focus an element in a window.
+#[derive(Debug, Clone)]
+pub struct FocusRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// The `id` attribute of the element to focus.
+    pub element_id: String,
+}
+
+/// A request to scroll an element in a window.
+#[derive(Debug, Clone)]
+pub struct ScrollRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// A CSS selector for the element to scroll.
+    pub selector: String,
+    /// Where to scroll.
+    pub target: ScrollTarget,
+}
+
+/// A request to change the HiDPI scale of a window.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaleRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// The scale to draw at, or `None` to follow the monitor.
+    pub scale: Option<f32>,
+}
+
+/// A request to show or remove a layer of a window's overlay.
+#[derive(Debug, Clone)]
+pub struct OverlayRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// The name of the layer.
+    pub name: String,
+    /// The HTML to show, or `None` to remove the layer.
+    pub html: Option<String>,
+}
+
+/// Where a [`ScrollRequest`] scrolls to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollTarget {
+    /// Scroll the element itself.
+    Position(ScrollPosition),
+    /// Scroll the element's containers until it shows.
+    IntoView,
+}
+
+/// A callback run with a window's document, see [`WindowHandle::with_document`].
+pub type DocumentCallback = Box<dyn FnOnce(&mut BaseDocument)>;
+
+/// A request to call a function with a window's document.
+pub struct DocumentRequest {
+    /// The handle of the window.
+    pub handle: WindowHandle,
+    /// The function to call.
+    pub callback: DocumentCallback,
+}
+
+impl std::fmt::Debug for DocumentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DocumentRequest").field("handle", &self.handle).finish_non_exhaustive()
+    }
+}
+
+/// A change to a window's stylesheet.
+#[derive(Debug, Clone)]
+pub enum StylesheetChange {
+    /// Add the stylesheet, or replace its contents if it exists.
+    Set {
+        /// The CSS source.
+        css: String,
+        /// The file the CSS was loaded from, for hot reload.
+        path: Option<PathBuf>,
+    },
+    /// Remove the stylesheet.
+    Remove,
+}
+
 /// Current state of a window (position, size).
 ///
 /// This can be used by applications to save and restore window state.
@@ -123,13 +477,24 @@ thread_local! {
     static WINDOW_STATES: RefCell<HashMap<WindowHandle, WindowState>> = RefCell::new(HashMap::new());
     /// The window ID that is currently handling an event (set by runtime during event dispatch).
     static CURRENT_WINDOW_ID: RefCell<Option<WindowId>> = RefCell::new(None);
+    /// The most recently focused window.
+    static FOCUSED_WINDOW_ID: RefCell<Option<WindowId>> = const { RefCell::new(None) };
+    /// Native windows by handle, for parenting dialogs.
+    static NATIVE_WINDOWS: RefCell<HashMap<WindowHandle, (WindowId, Weak<Window>)>> =
+        RefCell::new(HashMap::new());
 }
 
 /// Window request types.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum WindowRequest {
     Open(OpenWindowRequest),
     Close(CloseWindowRequest),
+    Stylesheet(StylesheetRequest),
+    Focus(FocusRequest),
+    Scroll(ScrollRequest),
+    Document(DocumentRequest),
+    Scale(ScaleRequest),
+    Overlay(OverlayRequest),
 }
 
 /// Set the event loop proxy (called by runtime during initialization).
@@ -139,6 +504,27 @@ pub(crate) fn set_event_proxy(proxy: EventLoopProxy<RinchEvent>) {
     });
 }
 
+/// Get a clone of the event loop proxy, for sending events from other threads.
+#[cfg(feature = "clipboard")]
+pub(crate) fn event_proxy() -> Option<EventLoopProxy<RinchEvent>> {
+    EVENT_PROXY.with(|p| p.borrow().clone())
+}
+
+/// Send an event to the runtime, if the event loop is running.
+pub(crate) fn send_event(event: RinchEvent) {
+    EVENT_PROXY.with(|p| {
+        if let Some(proxy) = p.borrow().as_ref() {
+            let _ = proxy.send_event(event);
+        }
+    });
+}
+
+/// Queue a window request and wake the runtime to process it.
+fn queue_request(request: WindowRequest) {
+    WINDOW_REQUESTS.with(|r| r.borrow_mut().push(request));
+    send_event(RinchEvent::ProcessWindowRequests);
+}
+
 /// Take all pending window requests (called by runtime).
 pub(crate) fn take_window_requests() -> Vec<WindowRequest> {
     WINDOW_REQUESTS.with(|r| r.borrow_mut().drain(..).collect())
@@ -170,6 +556,98 @@ pub(crate) fn get_current_window_id() -> Option<WindowId> {
     CURRENT_WINDOW_ID.with(|id| *id.borrow())
 }
 
+/// Set the most recently focused window (called by runtime on focus changes).
+pub(crate) fn set_focused_window_id(window_id: WindowId) {
+    FOCUSED_WINDOW_ID.with(|id| {
+        *id.borrow_mut() = Some(window_id);
+    });
+}
+
+/// Get the ID of the most recently focused window.
+pub(crate) fn focused_window_id() -> Option<WindowId> {
+    FOCUSED_WINDOW_ID.with(|id| *id.borrow())
+}
+
+/// Get the most recently focused window, if it is still open.
+pub(crate) fn focused_window() -> Option<WindowHandle> {
+    FOCUSED_WINDOW_ID.with(|id| *id.borrow()).and_then(window_handle)
+}
+
+/// Register a native window so it can be found by handle (called by runtime).
+pub(crate) fn register_native_window(handle: WindowHandle, window_id: WindowId, window: &Arc<Window>) {
+    NATIVE_WINDOWS.with(|w| {
+        w.borrow_mut().insert(handle, (window_id, Arc::downgrade(window)));
+    });
+    crate::history::attach(handle);
+    crate::undo::attach(handle);
+    crate::messages::attach(handle);
+}
+
+/// Forget a native window (called by runtime when a window is closed).
+pub(crate) fn unregister_native_window(window_id: WindowId) {
+    let mut closed = Vec::new();
+    NATIVE_WINDOWS.with(|w| {
+        w.borrow_mut().retain(|handle, (id, _)| {
+            if *id == window_id {
+                closed.push(*handle);
+            }
+            *id != window_id
+        });
+    });
+    for handle in closed {
+        crate::history::detach(handle);
+        crate::undo::detach(handle);
+        crate::messages::detach(handle);
+        crate::scroll::forget(handle);
+        crate::mouse::forget(handle);
+        crate::observe::forget(handle);
+        crate::media::forget(handle);
+    }
+}
+
+/// Get the handle of an open window.
+pub(crate) fn window_handle(window_id: WindowId) -> Option<WindowHandle> {
+    NATIVE_WINDOWS.with(|w| {
+        w.borrow()
+            .iter()
+            .find(|(_, (id, _))| *id == window_id)
+            .map(|(handle, _)| *handle)
+    })
+}
+
+/// Get the native window for a handle, if it is still open.
+pub(crate) fn native_window(handle: WindowHandle) -> Option<Arc<Window>> {
+    NATIVE_WINDOWS.with(|w| w.borrow().get(&handle).and_then(|(_, window)| window.upgrade()))
+}
+
+/// Get the winit window ID for a handle, if the window is still open.
+pub(crate) fn native_window_id(handle: WindowHandle) -> Option<WindowId> {
+    NATIVE_WINDOWS.with(|w| w.borrow().get(&handle).map(|(id, _)| *id))
+}
+
+/// Get a handle to the window the user is interacting with.
+///
+/// Inside an event handler this is the window containing the element;
+/// elsewhere (e.g. in menu callbacks) it is the most recently focused window.
+/// Works for windows declared in `rsx!` as well as programmatically opened ones.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::dialogs::open_file;
+/// use rinch::windows::current_window;
+///
+/// let mut dialog = open_file();
+/// if let Some(window) = current_window() {
+///     dialog = dialog.set_parent(&window);
+/// }
+/// let path = dialog.pick_file();
+/// ```
+pub fn current_window() -> Option<WindowHandle> {
+    let window_id = get_current_window_id().or_else(|| FOCUSED_WINDOW_ID.with(|id| *id.borrow()))?;
+    window_handle(window_id)
+}
+
 /// Get the current state of a window.
 ///
 /// Returns `None` if the window handle is invalid or the window has been closed.
@@ -225,7 +703,12 @@ pub fn get_all_window_states() -> Vec<(WindowHandle, WindowState)> {
 /// ```
 pub fn open_window(props: WindowProps, html_content: String) -> WindowHandle {
     let handle = WindowHandle::new();
+    open_window_with_handle(handle, props, html_content);
+    handle
+}
 
+/// Queue a request to open a window with an already allocated handle.
+pub(crate) fn open_window_with_handle(handle: WindowHandle, props: WindowProps, html_content: String) {
     WINDOW_REQUESTS.with(|r| {
         r.borrow_mut().push(WindowRequest::Open(OpenWindowRequest {
             handle,
@@ -240,8 +723,6 @@ pub fn open_window(props: WindowProps, html_content: String) -> WindowHandle {
             let _ = proxy.send_event(RinchEvent::ProcessWindowRequests);
         }
     });
-
-    handle
 }
 
 /// Close a window by its handle.
@@ -335,12 +816,45 @@ impl WindowBuilder {
         self
     }
 
+    /// Set whether the window casts a drop shadow (macOS, and borderless
+    /// windows on Windows).
+    pub fn shadow(mut self, shadow: bool) -> Self {
+        self.props.shadow = shadow;
+        self
+    }
+
     /// Set whether the window is always on top.
     pub fn always_on_top(mut self, always_on_top: bool) -> Self {
         self.props.always_on_top = always_on_top;
         self
     }
 
+    /// Force a light or dark appearance, regardless of the OS theme.
+    pub fn color_scheme(mut self, color_scheme: WindowColorScheme) -> Self {
+        self.props.color_scheme = color_scheme;
+        self
+    }
+
+    /// Lay the window out left to right or right to left, instead of
+    /// following the locale.
+    pub fn dir(mut self, dir: TextDirection) -> Self {
+        self.props.dir = dir;
+        self
+    }
+
+    /// Draw the window at a HiDPI scale, such as `1.25`, instead of the
+    /// monitor's.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.props.scale = Some(scale);
+        self
+    }
+
+    /// Identify the window across runs of the app, for session restore.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.props.key = Some(key.into());
+        self
+    }
+
     /// Set the HTML content of the window.
     pub fn content(mut self, html: impl Into<String>) -> Self {
         self.html_content = html.into();
@@ -422,3 +936,207 @@ pub fn close_current_window() {
         });
     }
 }
+
+/// Move keyboard focus to the element with the given `id` attribute in the
+/// current window.
+///
+/// Shorthand for [`WindowHandle::focus_element`] on [`current_window`].
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::windows::focus_element;
+///
+/// rsx! {
+///     input { id: "name" }
+///     button { onclick: || focus_element("name"), "Edit name" }
+/// }
+/// ```
+pub fn focus_element(id: impl Into<String>) {
+    if let Some(window) = current_window() {
+        window.focus_element(id);
+    }
+}
+
+// =============================================================================
+// Per-Window State
+// =============================================================================
+
+/// A value kept for every open window, such as its history.
+///
+/// Outside of event handlers, such as while rendering, the value of the
+/// main window is used: the open window that opened first. Before any
+/// window is open, that value is created on its own and handed to the next
+/// window to open.
+pub(crate) struct PerWindow<T> {
+    inner: RefCell<PerWindowInner<T>>,
+    create: fn() -> T,
+}
+
+struct PerWindowInner<T> {
+    values: HashMap<WindowHandle, T>,
+    /// Open windows, in the order they opened.
+    windows: Vec<WindowHandle>,
+    /// The main window's value, asked for while no window is open.
+    pending: Option<T>,
+}
+
+impl<T: Clone> PerWindow<T> {
+    /// Create an empty store making new values with `create`.
+    pub(crate) fn new(create: fn() -> T) -> Self {
+        Self {
+            inner: RefCell::new(PerWindowInner {
+                values: HashMap::new(),
+                windows: Vec::new(),
+                pending: None,
+            }),
+            create,
+        }
+    }
+
+    /// Get the value of a window, creating it if needed.
+    pub(crate) fn get(&self, window: WindowHandle) -> T {
+        self.inner
+            .borrow_mut()
+            .values
+            .entry(window)
+            .or_insert_with(self.create)
+            .clone()
+    }
+
+    /// Get the value of a window, if it has one.
+    pub(crate) fn existing(&self, window: WindowHandle) -> Option<T> {
+        self.inner.borrow().values.get(&window).cloned()
+    }
+
+    /// Get the value of the window handling the current event, or of the
+    /// main window.
+    pub(crate) fn current(&self) -> T {
+        if let Some(window) = get_current_window_id().and_then(window_handle) {
+            return self.get(window);
+        }
+        let main = self.inner.borrow().windows.first().copied();
+        match main {
+            Some(window) => self.get(window),
+            None => {
+                let mut inner = self.inner.borrow_mut();
+                inner.pending.get_or_insert_with(self.create).clone()
+            }
+        }
+    }
+
+    /// Track a window that was just opened, giving it the value asked for
+    /// while no window was open.
+    pub(crate) fn attach(&self, window: WindowHandle) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.windows.contains(&window) {
+            return;
+        }
+        inner.windows.push(window);
+        if inner.windows.len() == 1
+            && let Some(pending) = inner.pending.take()
+        {
+            inner.values.insert(window, pending);
+        }
+    }
+
+    /// Forget a closed window and its value. The next oldest window becomes
+    /// the main window.
+    pub(crate) fn detach(&self, window: WindowHandle) {
+        let removed = {
+            let mut inner = self.inner.borrow_mut();
+            inner.windows.retain(|w| *w != window);
+            inner.values.remove(&window)
+        };
+        // Dropped outside the borrow, in case the value's drop uses the store
+        drop(removed);
+    }
+}
+
+// =============================================================================
+// Application Window Visibility
+// =============================================================================
+
+/// Show all application windows, restoring minimized ones and focusing them.
+///
+/// Commonly used from a tray icon click to bring a hidden app back.
+///
+/// # Example
+///
+/// ```ignore
+/// Tray { tooltip: "My App", onclick: || rinch::windows::show_windows() }
+/// ```
+pub fn show_windows() {
+    send_event(RinchEvent::ShowWindows);
+}
+
+/// Hide all application windows without closing them.
+///
+/// Use [`show_windows`] to bring them back.
+pub fn hide_windows() {
+    send_event(RinchEvent::HideWindows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A store of counters, so tests can tell values apart.
+    fn store() -> PerWindow<Rc<Cell<u32>>> {
+        PerWindow::new(|| Rc::new(Cell::new(0)))
+    }
+
+    #[test]
+    fn each_window_keeps_its_value() {
+        let store = store();
+        let (a, b) = (WindowHandle::new(), WindowHandle::new());
+        store.get(a).set(1);
+        store.get(b).set(2);
+        assert_eq!(store.get(a).get(), 1);
+        assert_eq!(store.get(b).get(), 2);
+        assert!(store.existing(WindowHandle::new()).is_none());
+    }
+
+    #[test]
+    fn value_asked_for_before_any_window_goes_to_the_first() {
+        let store = store();
+        store.current().set(7);
+        let (a, b) = (WindowHandle::new(), WindowHandle::new());
+        store.attach(a);
+        store.attach(b);
+        assert_eq!(store.get(a).get(), 7);
+        assert_eq!(store.get(b).get(), 0);
+        assert_eq!(store.current().get(), 7);
+    }
+
+    #[test]
+    fn next_window_becomes_main_when_the_main_closes() {
+        let store = store();
+        let (a, b) = (WindowHandle::new(), WindowHandle::new());
+        store.attach(a);
+        store.attach(b);
+        store.get(b).set(2);
+
+        store.detach(a);
+        assert!(store.existing(a).is_none());
+        assert_eq!(store.current().get(), 2);
+    }
+
+    #[test]
+    fn window_opened_after_all_closed_becomes_main() {
+        let store = store();
+        let a = WindowHandle::new();
+        store.attach(a);
+        store.get(a).set(1);
+        store.detach(a);
+
+        // Asked for while no window is open, then handed to the next one
+        store.current().set(3);
+        let b = WindowHandle::new();
+        store.attach(b);
+        assert_eq!(store.get(b).get(), 3);
+        assert_eq!(store.current().get(), 3);
+    }
+}
@@ -6,6 +6,7 @@
 //! - use_context for shared state
 //! - use_derived for computed state
 //! - Frameless window with custom chrome
+//! - Rich text editing with use_rich_text
 
 use rinch::prelude::*;
 
@@ -27,6 +28,10 @@ fn app() -> Element {
     let count = use_signal(|| 0);
     let text = use_signal(|| String::from("Hello, Rinch!"));
     let show_about = use_signal(|| false);
+    let editor = use_rich_text(|| {
+        RichText::from_plain("Select some text and make it bold or italic.\nA list item\nAnother item")
+    });
+    let word_count = use_signal(|| 0);
 
     // Use derived to compute values automatically
     let doubled = use_derived({
@@ -44,6 +49,7 @@ fn app() -> Element {
     let count_dec = count.clone();
     let count_reset = count.clone();
     let text_change = text.clone();
+    let word_count_change = word_count.clone();
 
     // Clones for menu callbacks
     let menu_count_reset = count.clone();
@@ -331,6 +337,14 @@ fn app() -> Element {
                                 flex-shrink: 0;
                                 border-radius: 0 0 8px 8px;
                             }
+                            .rinch-rich-text-input {
+                                background: #1e1e1e;
+                                color: #cccccc;
+                                border-color: #3c3c3c;
+                            }
+                            .rinch-rich-text-view {
+                                border-color: #3c3c3c;
+                            }
                             .keyboard-hint {
                                 color: #808080;
                                 font-size: 12px;
@@ -380,17 +394,19 @@ fn app() -> Element {
                             p { "A demonstration of rinch's reactive system with custom window chrome" }
 
                             // About dialog using menu callback
-                            div { class: "about-dialog", style: if show_about.get() { "display: block" } else { "display: none" },
-                                h2 { "About smyeditor" }
-                                p { "Built with " strong { "rinch" } " - a reactive GUI framework for Rust" }
-                                p { "Features demonstrated:" }
-                                ul { style: "text-align: left; display: inline-block;",
-                                    li { "Menu item callbacks (onclick)" }
-                                    li { "use_context for shared state" }
-                                    li { "use_derived for computed values" }
-                                    li { "Frameless window with custom chrome" }
+                            if show_about.get() {
+                                div { class: "about-dialog",
+                                    h2 { "About smyeditor" }
+                                    p { "Built with " strong { "rinch" } " - a reactive GUI framework for Rust" }
+                                    p { "Features demonstrated:" }
+                                    ul { style: "text-align: left; display: inline-block;",
+                                        li { "Menu item callbacks (onclick)" }
+                                        li { "use_context for shared state" }
+                                        li { "use_derived for computed values" }
+                                        li { "Frameless window with custom chrome" }
+                                    }
+                                    p { style: "color: #808080;", "Click Help > About again to close" }
                                 }
-                                p { style: "color: #808080;", "Click Help > About again to close" }
                             }
 
                             div { class: "section",
@@ -467,6 +483,21 @@ fn app() -> Element {
                             }
                         }
 
+                        div { class: "section",
+                            h2 {
+                                "Rich Text Demo"
+                                span { class: "feature-badge", "use_rich_text" }
+                            }
+                            RichTextEditor {
+                                editor: editor.clone(),
+                                rows: "6",
+                                onchange: move |document: RichText| {
+                                    word_count_change.set(document.plain_text().split_whitespace().count());
+                                },
+                            }
+                            p { class: "info", "Words: " {word_count.get()} }
+                        }
+
                             div { class: "keyboard-hint",
                                 "Developer Tools: "
                                 kbd { "F12" } " Toggle DevTools | "